@@ -1,4 +1,6 @@
 mod parser;
+mod queue;
+mod request;
 mod seek_sequence;
 mod standalone_executable;
 
@@ -7,13 +9,20 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::str::Utf8Error;
 
-use anyhow::Context;
 use anyhow::Result;
 pub use parser::parse_patch;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
-use parser::UpdateFileChunk;
+pub use parser::UpdateFileChunk;
+pub use queue::PatchQueue;
+pub use request::DiffStat;
+pub use request::FileDiffStat;
+pub use request::FileOutcome;
+pub use request::FilePatchResult;
+pub use request::PatchReport;
+pub use request::PatchRequest;
+use serde::{Deserialize, Serialize};
 use similar::TextDiff;
 use thiserror::Error;
 use tree_sitter::LanguageError;
@@ -111,7 +120,8 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ApplyPatchFileChange {
     Add {
         content: String,
@@ -316,6 +326,10 @@ pub fn apply_hunks(
         Err(err) => {
             let msg = err.to_string();
             writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
+            let err = match err.downcast::<ApplyPatchError>() {
+                Ok(err) => return Err(err),
+                Err(err) => err,
+            };
             if let Some(io) = err.downcast_ref::<std::io::Error>() {
                 Err(ApplyPatchError::from(io))
             } else {
@@ -343,53 +357,15 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
     for hunk in hunks {
-        match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent() {
-                    if !parent.as_os_str().is_empty() {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", path.display())
-                        })?;
-                    }
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
-                added.push(path.clone());
-            }
-            Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
-                deleted.push(path.clone());
-            }
-            Hunk::UpdateFile {
-                path,
-                move_path,
-                chunks,
-            } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
-                if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent() {
-                        if !parent.as_os_str().is_empty() {
-                            std::fs::create_dir_all(parent).with_context(|| {
-                                format!(
-                                    "Failed to create parent directories for {}",
-                                    dest.display()
-                                )
-                            })?;
-                        }
-                    }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
-                    modified.push(dest.clone());
-                } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
-                    modified.push(path.clone());
-                }
-            }
+        let path = hunk.resolve_path(Path::new(""));
+        match apply_hunk(hunk, Path::new(""))?.0 {
+            FileOutcome::Added => added.push(path),
+            FileOutcome::Modified { moved_to: None } => modified.push(path),
+            FileOutcome::Modified {
+                moved_to: Some(dest),
+            } => modified.push(dest),
+            FileOutcome::Deleted => deleted.push(path),
+            FileOutcome::Skipped | FileOutcome::Failed(_) => {}
         }
     }
     Ok(AffectedPaths {
@@ -399,6 +375,94 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     })
 }
 
+/// Applies a single hunk, resolving its paths against `cwd`. Returns
+/// `Added`, `Modified` or `Deleted` with the lines the file gained and lost.
+pub(crate) fn apply_hunk(
+    hunk: &Hunk,
+    cwd: &Path,
+) -> Result<(FileOutcome, DiffStat), ApplyPatchError> {
+    let path = hunk.resolve_path(cwd);
+    match hunk {
+        Hunk::AddFile { contents, .. } => {
+            create_parent_dirs(&path)?;
+            std::fs::write(&path, contents).map_err(io_context(format!(
+                "Failed to write file {}",
+                path.display()
+            )))?;
+            Ok((FileOutcome::Added, DiffStat::between("", contents)))
+        }
+        Hunk::DeleteFile { .. } => {
+            // Unreadable (e.g. binary) files count as no lines
+            let stat = std::fs::read_to_string(&path)
+                .map(|contents| DiffStat::between(&contents, ""))
+                .unwrap_or_default();
+            std::fs::remove_file(&path).map_err(io_context(format!(
+                "Failed to delete file {}",
+                path.display()
+            )))?;
+            Ok((FileOutcome::Deleted, stat))
+        }
+        Hunk::UpdateFile {
+            move_path, chunks, ..
+        } => {
+            let AppliedPatch {
+                original_contents,
+                new_contents,
+            } = derive_new_contents_from_chunks(&path, chunks)?;
+            let dest = move_path.as_ref().map(|p| cwd.join(p));
+            write_update(&path, dest, &original_contents, &new_contents)
+        }
+    }
+}
+
+/// Write the updated contents of `path`, to `dest` when the file moves.
+pub(crate) fn write_update(
+    path: &Path,
+    dest: Option<PathBuf>,
+    original_contents: &str,
+    new_contents: &str,
+) -> Result<(FileOutcome, DiffStat), ApplyPatchError> {
+    let stat = DiffStat::between(original_contents, new_contents);
+    let Some(dest) = dest else {
+        std::fs::write(path, new_contents).map_err(io_context(format!(
+            "Failed to write file {}",
+            path.display()
+        )))?;
+        return Ok((FileOutcome::Modified { moved_to: None }, stat));
+    };
+    create_parent_dirs(&dest)?;
+    std::fs::write(&dest, new_contents).map_err(io_context(format!(
+        "Failed to write file {}",
+        dest.display()
+    )))?;
+    std::fs::remove_file(path).map_err(io_context(format!(
+        "Failed to remove original {}",
+        path.display()
+    )))?;
+    Ok((
+        FileOutcome::Modified {
+            moved_to: Some(dest),
+        },
+        stat,
+    ))
+}
+
+fn create_parent_dirs(path: &Path) -> Result<(), ApplyPatchError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            std::fs::create_dir_all(parent).map_err(io_context(format!(
+                "Failed to create parent directories for {}",
+                path.display()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn io_context(context: String) -> impl FnOnce(std::io::Error) -> ApplyPatchError {
+    move |source| ApplyPatchError::IoError(IoError { context, source })
+}
+
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
@@ -418,6 +482,20 @@ fn derive_new_contents_from_chunks(
         }
     };
 
+    let new_contents = apply_chunks_to_text(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
+
+/// Contents of the file at `path` after applying `chunks` to
+/// `original_contents`; `path` only names the file in errors.
+pub(crate) fn apply_chunks_to_text(
+    original_contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents
         .split('\n')
         .map(|s| s.to_string())
@@ -432,11 +510,7 @@ fn derive_new_contents_from_chunks(
     if !new_lines.last().is_some_and(|s| s.is_empty()) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
 }
 
 fn compute_replacements(
@@ -573,5 +647,3 @@ pub fn print_summary(
     }
     Ok(())
 }
-
-// removed legacy minimal applier; full implementation above
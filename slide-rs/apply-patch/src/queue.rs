@@ -0,0 +1,357 @@
+//! Applying several patches to the same files in sequence.
+//!
+//! A model often sends a few `apply_patch` calls in one turn, each written
+//! against the files as they were when the turn started. Once the first
+//! patch changed a file, the context lines of the next one may no longer be
+//! there. [`PatchQueue`] remembers what each file looked like before the
+//! first patch touched it; when a later patch does not fit the current
+//! contents, it is applied to that original instead and the two results
+//! are merged line by line, like a rebase. Patches changing the same lines
+//! differently still fail, with a conflict error.
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use similar::DiffTag;
+use similar::TextDiff;
+
+use crate::apply_chunks_to_text;
+use crate::apply_hunk;
+use crate::io_context;
+use crate::write_update;
+use crate::ApplyPatchError;
+use crate::ApplyPatchFileChange;
+use crate::DiffStat;
+use crate::FileOutcome;
+use crate::FilePatchResult;
+use crate::Hunk;
+use crate::PatchReport;
+use crate::PatchRequest;
+use crate::UpdateFileChunk;
+
+/// Applies patches in order, rebasing each one onto the changes of the
+/// patches before it. Use one queue per turn.
+#[derive(Debug, Default)]
+pub struct PatchQueue {
+    /// Contents before the first patch of the queue, by path
+    bases: HashMap<PathBuf, String>,
+}
+
+impl PatchQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply the hunks of `request` whose path passes `keep`, like
+    /// [`PatchRequest::apply_filtered`], rebasing updates that no longer fit.
+    pub fn apply(&mut self, request: &PatchRequest, keep: impl Fn(&Path) -> bool) -> PatchReport {
+        let cwd = request.cwd();
+        let files = request
+            .hunks()
+            .iter()
+            .map(|hunk| {
+                let path = hunk.resolve_path(cwd);
+                if !keep(&path) {
+                    return FilePatchResult {
+                        path,
+                        outcome: FileOutcome::Skipped,
+                        stat: DiffStat::default(),
+                        rebased: false,
+                    };
+                }
+                self.remember(&path);
+                let (result, rebased) = match hunk {
+                    Hunk::UpdateFile {
+                        move_path, chunks, ..
+                    } => match self.updated_contents(&path, chunks) {
+                        Ok(update) => {
+                            let dest = move_path.as_ref().map(|p| cwd.join(p));
+                            let result = write_update(&path, dest, &update.current, &update.new);
+                            (result, update.rebased)
+                        }
+                        Err(e) => (Err(e), false),
+                    },
+                    _ => (apply_hunk(hunk, cwd), false),
+                };
+                let (outcome, stat) =
+                    result.unwrap_or_else(|e| (FileOutcome::Failed(e), DiffStat::default()));
+                if let FileOutcome::Modified {
+                    moved_to: Some(dest),
+                } = &outcome
+                {
+                    // Later patches address the file by its new name
+                    if let Some(base) = self.bases.get(&path).cloned() {
+                        self.bases.entry(dest.clone()).or_insert(base);
+                    }
+                }
+                FilePatchResult {
+                    path,
+                    outcome,
+                    stat,
+                    rebased,
+                }
+            })
+            .collect();
+        PatchReport { files }
+    }
+
+    /// Keep the contents of `path` unless a patch of the queue touched it
+    /// already.
+    fn remember(&mut self, path: &Path) {
+        if self.bases.contains_key(path) {
+            return;
+        }
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            self.bases.insert(path.to_path_buf(), contents);
+        }
+    }
+
+    /// What applying `request` would change, for approval: updates that no
+    /// longer fit are shown rebased, the way [`PatchQueue::apply`] will
+    /// write them.
+    pub fn changes(
+        &self,
+        request: &PatchRequest,
+    ) -> Result<HashMap<PathBuf, ApplyPatchFileChange>, ApplyPatchError> {
+        let cwd = request.cwd();
+        let mut changes = HashMap::new();
+        for hunk in request.hunks() {
+            let path = hunk.resolve_path(cwd);
+            let change = match hunk {
+                Hunk::AddFile { contents, .. } => ApplyPatchFileChange::Add {
+                    content: contents.clone(),
+                },
+                Hunk::DeleteFile { .. } => ApplyPatchFileChange::Delete,
+                Hunk::UpdateFile {
+                    move_path, chunks, ..
+                } => {
+                    let update = self.updated_contents(&path, chunks)?;
+                    ApplyPatchFileChange::Update {
+                        unified_diff: TextDiff::from_lines(&update.current, &update.new)
+                            .unified_diff()
+                            .context_radius(1)
+                            .to_string(),
+                        move_path: move_path.as_ref().map(|p| cwd.join(p)),
+                        new_content: update.new,
+                    }
+                }
+            };
+            changes.insert(path, change);
+        }
+        Ok(changes)
+    }
+
+    /// Contents of `path` after applying `chunks`. When they do not fit the
+    /// current contents, they are applied to the contents the file had
+    /// before the queue changed it and merged with the current ones.
+    fn updated_contents(
+        &self,
+        path: &Path,
+        chunks: &[UpdateFileChunk],
+    ) -> Result<Update, ApplyPatchError> {
+        let current = std::fs::read_to_string(path).map_err(io_context(format!(
+            "Failed to read file to update {}",
+            path.display()
+        )))?;
+        let error = match apply_chunks_to_text(&current, path, chunks) {
+            Ok(new) => {
+                return Ok(Update {
+                    current,
+                    new,
+                    rebased: false,
+                })
+            }
+            Err(e @ ApplyPatchError::ComputeReplacements(_)) => e,
+            Err(e) => return Err(e),
+        };
+        let Some(base) = self.bases.get(path).filter(|base| **base != current) else {
+            return Err(error);
+        };
+        // Not written against the original either: report the first error
+        let Ok(theirs) = apply_chunks_to_text(base, path, chunks) else {
+            return Err(error);
+        };
+        match merge(base, &current, &theirs) {
+            Ok(new) => Ok(Update {
+                current,
+                new,
+                rebased: true,
+            }),
+            Err(line) => Err(ApplyPatchError::ComputeReplacements(format!(
+                "Conflict in {} around line {line}: an earlier patch changed the same lines",
+                path.display()
+            ))),
+        }
+    }
+}
+
+struct Update {
+    current: String,
+    new: String,
+    /// Merged with the changes of earlier patches
+    rebased: bool,
+}
+
+/// Lines `start..end` of the original replaced by `lines`.
+#[derive(Debug, PartialEq)]
+struct Change<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+impl Change<'_> {
+    fn is_insertion(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Whether both touch the same original lines, or insert at the same
+    /// place, so their order cannot be decided.
+    fn overlaps(&self, other: &Self) -> bool {
+        (self.start < other.end && other.start < self.end)
+            || (self.is_insertion() && other.is_insertion() && self.start == other.start)
+    }
+}
+
+/// Changes from `base` to `other`, neighbouring ones joined.
+fn changes<'a>(base: &str, other: &'a str) -> Vec<Change<'a>> {
+    let diff = TextDiff::from_lines(base, other);
+    let new_lines = diff.new_slices();
+    let mut changes: Vec<Change<'a>> = Vec::new();
+    for op in diff.ops() {
+        let (tag, old, new) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        let lines = new_lines.get(new).unwrap_or_default();
+        match changes.last_mut() {
+            Some(last) if last.end == old.start => {
+                last.end = old.end;
+                last.lines.extend_from_slice(lines);
+            }
+            _ => changes.push(Change {
+                start: old.start,
+                end: old.end,
+                lines: lines.to_vec(),
+            }),
+        }
+    }
+    changes
+}
+
+/// Three-way merge of the changes from `base` to `ours` and to `theirs`;
+/// the 1-based line of the first conflict on failure.
+fn merge(base: &str, ours: &str, theirs: &str) -> Result<String, usize> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let mut ours = changes(base, ours).into_iter().peekable();
+    let mut theirs = changes(base, theirs).into_iter().peekable();
+    let mut merged = String::new();
+    let mut pos = 0;
+    loop {
+        let next = match (ours.peek(), theirs.peek()) {
+            (None, None) => break,
+            (Some(_), None) => ours.next(),
+            (None, Some(_)) => theirs.next(),
+            (Some(a), Some(b)) if a.overlaps(b) => {
+                if a != b {
+                    return Err(a.start.min(b.start) + 1);
+                }
+                // The same change on both sides is kept once
+                theirs.next();
+                ours.next()
+            }
+            (Some(a), Some(b)) => {
+                if a.start < b.start || (a.start == b.start && a.is_insertion()) {
+                    ours.next()
+                } else {
+                    theirs.next()
+                }
+            }
+        };
+        let Some(change) = next else {
+            break;
+        };
+        merged.extend(base_lines.get(pos..change.start).unwrap_or_default().iter().copied());
+        merged.extend(change.lines);
+        pos = change.end;
+    }
+    merged.extend(base_lines.get(pos..).unwrap_or_default().iter().copied());
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK: &str = "# Title\n## Intro\n- one\n- two\n## End\n";
+
+    fn patch(body: &str) -> String {
+        format!("*** Begin Patch\n*** Update File: deck.md\n{body}*** End Patch")
+    }
+
+    #[test]
+    fn later_patches_are_rebased_onto_earlier_ones() -> Result<(), ApplyPatchError> {
+        let dir = std::env::temp_dir().join(format!("patch-queue-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("deck.md"), DECK)?;
+        let first = PatchRequest::parse(&patch("@@ ## Intro\n-- one\n+- uno\n"), &dir)?;
+        // Written against the deck before the first patch
+        let second = PatchRequest::parse(&patch("@@\n - one\n-- two\n+- dos\n"), &dir)?;
+        let conflicting = PatchRequest::parse(&patch("@@\n-- one\n+- eins\n"), &dir)?;
+
+        let mut queue = PatchQueue::new();
+        let report = queue.apply(&first, |_| true);
+        assert!(report.is_success());
+        assert!(!report.files[0].rebased);
+
+        // Applied on its own the second patch no longer fits
+        assert!(!second.apply().is_success());
+
+        let report = queue.apply(&second, |_| true);
+        assert!(report.is_success(), "{report:?}");
+        assert!(report.files[0].rebased);
+        assert_eq!(
+            report.total_stat(),
+            DiffStat {
+                added: 1,
+                removed: 1
+            }
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("deck.md"))?,
+            "# Title\n## Intro\n- uno\n- dos\n## End\n"
+        );
+
+        let report = queue.apply(&conflicting, |_| true);
+        assert!(matches!(
+            &report.files[0].outcome,
+            FileOutcome::Failed(ApplyPatchError::ComputeReplacements(m)) if m.contains("line 3")
+        ));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("deck.md"))?,
+            "# Title\n## Intro\n- uno\n- dos\n## End\n"
+        );
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn merges_keep_both_sides_outside_conflicts() {
+        let base = "a\nb\nc\nd\n";
+        assert_eq!(
+            merge(base, "a\nB\nc\nd\n", "a\nb\nc\nD\n"),
+            Ok("a\nB\nc\nD\n".into())
+        );
+        assert_eq!(
+            merge(base, "a\nB\nc\nd\n", "a\nB\nc\nd\n"),
+            Ok("a\nB\nc\nd\n".into())
+        );
+        assert_eq!(
+            merge(base, "x\na\nb\nc\nd\n", "a\nb\nc\nd\ny\n"),
+            Ok("x\na\nb\nc\nd\ny\n".into())
+        );
+        assert_eq!(merge(base, "a\nB\nc\nd\n", "a\nb2\nc\nd\n"), Err(2));
+    }
+}
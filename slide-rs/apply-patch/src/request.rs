@@ -0,0 +1,391 @@
+//! High-level API over the hunk engine for callers that apply a patch on
+//! someone's behalf: paths resolve against a working directory, files can be
+//! held back, and every file gets its own outcome instead of the first error
+//! aborting the rest.
+use std::ops::AddAssign;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use similar::ChangeTag;
+use similar::TextDiff;
+
+use crate::apply_hunk;
+use crate::parse_patch;
+use crate::ApplyPatchError;
+use crate::Hunk;
+
+/// A parsed patch bound to the directory its paths are relative to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchRequest {
+    patch: String,
+    hunks: Vec<Hunk>,
+    cwd: PathBuf,
+}
+
+/// What applying (or not applying) the hunk of one file did.
+#[derive(Debug, PartialEq)]
+pub enum FileOutcome {
+    Added,
+    Modified {
+        moved_to: Option<PathBuf>,
+    },
+    Deleted,
+    /// Held back by the filter passed to [`PatchRequest::apply_filtered`]
+    Skipped,
+    Failed(ApplyPatchError),
+}
+
+/// Lines added and removed, as in `git diff --stat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStat {
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl DiffStat {
+    /// Line counts of the change from `old` to `new`.
+    pub fn between(old: &str, new: &str) -> Self {
+        let mut stat = Self::default();
+        for change in TextDiff::from_lines(old, new).iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => stat.added += 1,
+                ChangeTag::Delete => stat.removed += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        stat
+    }
+}
+
+impl AddAssign for DiffStat {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.removed += other.removed;
+    }
+}
+
+/// [`DiffStat`] of one applied file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDiffStat {
+    /// Where the file is now: the move destination for moved files
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub stat: DiffStat,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FilePatchResult {
+    /// The file the hunk targets, resolved against the request's cwd
+    pub path: PathBuf,
+    pub outcome: FileOutcome,
+    /// Zero for skipped and failed files
+    pub stat: DiffStat,
+    /// Applied by [`PatchQueue`](crate::PatchQueue) on top of the changes an
+    /// earlier patch made, the file no longer matching the patch's context
+    pub rebased: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PatchReport {
+    /// One entry per hunk, in patch order
+    pub files: Vec<FilePatchResult>,
+}
+
+impl PatchReport {
+    /// Whether no file failed; skipped files do not count as failures.
+    pub fn is_success(&self) -> bool {
+        !self
+            .files
+            .iter()
+            .any(|file| matches!(file.outcome, FileOutcome::Failed(_)))
+    }
+
+    /// Line counts of every file that was applied, in patch order.
+    pub fn file_stats(&self) -> Vec<FileDiffStat> {
+        self.files
+            .iter()
+            .filter_map(|file| {
+                let path = match &file.outcome {
+                    FileOutcome::Modified {
+                        moved_to: Some(dest),
+                    } => dest,
+                    FileOutcome::Added | FileOutcome::Modified { .. } | FileOutcome::Deleted => {
+                        &file.path
+                    }
+                    FileOutcome::Skipped | FileOutcome::Failed(_) => return None,
+                };
+                Some(FileDiffStat {
+                    path: path.clone(),
+                    stat: file.stat,
+                })
+            })
+            .collect()
+    }
+
+    /// Lines added and removed across all files.
+    pub fn total_stat(&self) -> DiffStat {
+        let mut total = DiffStat::default();
+        for file in &self.files {
+            total += file.stat;
+        }
+        total
+    }
+}
+
+impl PatchRequest {
+    /// Parse `patch`; relative paths in it resolve against `cwd`.
+    pub fn parse(patch: &str, cwd: &Path) -> Result<Self, ApplyPatchError> {
+        let args = parse_patch(patch)?;
+        Ok(Self {
+            patch: args.patch,
+            hunks: args.hunks,
+            cwd: cwd.to_path_buf(),
+        })
+    }
+
+    /// Patch rewriting `path` from `old` to `new`: one `*** Update File`
+    /// hunk with a chunk per changed region and three lines of context.
+    pub fn update_file(
+        path: &Path,
+        old: &str,
+        new: &str,
+        cwd: &Path,
+    ) -> Result<Self, ApplyPatchError> {
+        let mut patch = format!("*** Begin Patch\n*** Update File: {}\n", path.display());
+        let diff = TextDiff::from_lines(old, new);
+        for group in diff.grouped_ops(3) {
+            patch.push_str("@@\n");
+            for op in group {
+                for change in diff.iter_changes(&op) {
+                    let sign = match change.tag() {
+                        ChangeTag::Equal => ' ',
+                        ChangeTag::Delete => '-',
+                        ChangeTag::Insert => '+',
+                    };
+                    patch.push(sign);
+                    patch.push_str(change.value().trim_end_matches(['\n', '\r']));
+                    patch.push('\n');
+                }
+            }
+        }
+        patch.push_str("*** End Patch");
+        Self::parse(&patch, cwd)
+    }
+
+    pub fn patch(&self) -> &str {
+        &self.patch
+    }
+
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    /// Every path the patch writes or removes, move destinations included.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for hunk in &self.hunks {
+            paths.push(hunk.resolve_path(&self.cwd));
+            if let Hunk::UpdateFile {
+                move_path: Some(dest),
+                ..
+            } = hunk
+            {
+                paths.push(self.cwd.join(dest));
+            }
+        }
+        paths
+    }
+
+    pub fn apply(&self) -> PatchReport {
+        self.apply_filtered(|_| true)
+    }
+
+    /// Apply the hunks whose resolved path passes `keep`; the others are
+    /// reported as skipped. A failing file does not stop the files after it.
+    pub fn apply_filtered(&self, keep: impl Fn(&Path) -> bool) -> PatchReport {
+        let files = self
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let path = hunk.resolve_path(&self.cwd);
+                let (outcome, stat) = if keep(&path) {
+                    apply_hunk(hunk, &self.cwd)
+                        .unwrap_or_else(|e| (FileOutcome::Failed(e), DiffStat::default()))
+                } else {
+                    (FileOutcome::Skipped, DiffStat::default())
+                };
+                FilePatchResult {
+                    path,
+                    outcome,
+                    stat,
+                    rebased: false,
+                }
+            })
+            .collect();
+        PatchReport { files }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).ok();
+        dir
+    }
+
+    #[test]
+    fn update_file_patches_rewrite_the_file() -> Result<(), ApplyPatchError> {
+        let dir = temp_dir("patch-update");
+        let old = "# Deck\n\n## One\n- a\n\n## Two\n- b\n\n## Three\n- c\n- d\n- e\n- f\n\n## Four\n- g\n";
+        let new = "# Deck\n\n## Two\n- b\n\n## One\n- a\n\n## Three\n- c\n- d\n- e\n- f\n\n## Four\n- g\n- h\n";
+        std::fs::write(dir.join("deck.md"), old)?;
+        let request = PatchRequest::update_file(Path::new("deck.md"), old, new, &dir)?;
+        assert_eq!(request.patch().matches("@@").count(), 2);
+        assert!(request.apply().is_success());
+        assert_eq!(std::fs::read_to_string(dir.join("deck.md"))?, new);
+        Ok(())
+    }
+
+    #[test]
+    fn applies_each_file_against_the_cwd() -> Result<(), ApplyPatchError> {
+        let dir = temp_dir("patch-request");
+        std::fs::write(dir.join("deck.md"), "# Title\nold line\n")?;
+        std::fs::write(dir.join("obsolete.md"), "gone\n")?;
+        std::fs::write(dir.join("notes.md"), "notes\n")?;
+        let request = PatchRequest::parse(
+            "*** Begin Patch\n\
+             *** Add File: slides/new.md\n\
+             +# New\n\
+             *** Update File: deck.md\n\
+             @@ # Title\n\
+             -old line\n\
+             +new line\n\
+             *** Delete File: obsolete.md\n\
+             *** Update File: notes.md\n\
+             *** Move to: archive/notes.md\n\
+             -notes\n\
+             +archived\n\
+             *** End Patch",
+            &dir,
+        )?;
+        assert_eq!(
+            request.paths(),
+            [
+                dir.join("slides/new.md"),
+                dir.join("deck.md"),
+                dir.join("obsolete.md"),
+                dir.join("notes.md"),
+                dir.join("archive/notes.md"),
+            ]
+        );
+
+        let report = request.apply();
+        assert!(report.is_success());
+        let stat = |added, removed| DiffStat { added, removed };
+        assert_eq!(
+            report.file_stats(),
+            [
+                FileDiffStat {
+                    path: dir.join("slides/new.md"),
+                    stat: stat(1, 0),
+                },
+                FileDiffStat {
+                    path: dir.join("deck.md"),
+                    stat: stat(1, 1),
+                },
+                FileDiffStat {
+                    path: dir.join("obsolete.md"),
+                    stat: stat(0, 1),
+                },
+                FileDiffStat {
+                    path: dir.join("archive/notes.md"),
+                    stat: stat(1, 1),
+                },
+            ]
+        );
+        assert_eq!(report.total_stat(), stat(3, 3));
+        let outcomes: Vec<_> = report.files.into_iter().map(|f| f.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                FileOutcome::Added,
+                FileOutcome::Modified { moved_to: None },
+                FileOutcome::Deleted,
+                FileOutcome::Modified {
+                    moved_to: Some(dir.join("archive/notes.md"))
+                },
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("slides/new.md"))?,
+            "# New\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("deck.md"))?,
+            "# Title\nnew line\n"
+        );
+        assert!(!dir.join("obsolete.md").exists());
+        assert!(!dir.join("notes.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("archive/notes.md"))?,
+            "archived\n"
+        );
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn skipped_and_failed_files_do_not_stop_the_rest() -> Result<(), ApplyPatchError> {
+        let dir = temp_dir("patch-request-filtered");
+        std::fs::write(dir.join("deck.md"), "keep\n")?;
+        let request = PatchRequest::parse(
+            "*** Begin Patch\n\
+             *** Update File: missing.md\n\
+             -a\n\
+             +b\n\
+             *** Delete File: deck.md\n\
+             *** Add File: added.md\n\
+             +hello\n\
+             *** End Patch",
+            &dir,
+        )?;
+
+        let report = request.apply_filtered(|path| path != dir.join("deck.md"));
+        assert!(!report.is_success());
+        assert!(matches!(
+            report.files[0].outcome,
+            FileOutcome::Failed(ApplyPatchError::IoError(_))
+        ));
+        assert_eq!(report.files[1].outcome, FileOutcome::Skipped);
+        assert_eq!(report.files[2].outcome, FileOutcome::Added);
+        assert_eq!(report.file_stats().len(), 1);
+        assert_eq!(
+            report.total_stat(),
+            DiffStat {
+                added: 1,
+                removed: 0
+            }
+        );
+        assert!(dir.join("deck.md").exists());
+        assert!(dir.join("added.md").exists());
+
+        assert!(matches!(
+            PatchRequest::parse("*** Update File: deck.md", &dir),
+            Err(ApplyPatchError::ParseError(_))
+        ));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
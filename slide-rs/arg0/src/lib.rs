@@ -1,12 +1,29 @@
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// arg0 dispatch function - simplified version for MVP
+/// Run the Linux sandbox helper when invoked as `slide-linux-sandbox`,
+/// otherwise `main_fn` on a Tokio runtime. `main_fn` gets the executable to
+/// re-run as the helper (Linux only).
 pub fn arg0_dispatch_or_else<F, Fut>(main_fn: F) -> !
 where
     F: FnOnce(Option<PathBuf>) -> Fut,
     Fut: Future<Output = anyhow::Result<()>>,
 {
+    let argv0 = std::env::args_os().next().unwrap_or_default();
+    if Path::new(&argv0).file_name() == Some(slide_linux_sandbox::ARG0.as_ref()) {
+        // Before any runtime threads exist: the sandbox applies per thread
+        slide_linux_sandbox::run_main();
+    }
+
+    let slide_linux_sandbox_exe = if cfg!(target_os = "linux") {
+        std::env::current_exe().ok()
+    } else {
+        None
+    };
+    // Called before the runtime exists, so its synchronous part runs while the
+    // process is still single-threaded
+    let main_future = main_fn(slide_linux_sandbox_exe);
+
     // Create Tokio runtime
     let runtime = match tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -20,7 +37,7 @@ where
     };
 
     // Run the main function
-    let result = runtime.block_on(main_fn(None));
+    let result = runtime.block_on(main_future);
 
     match result {
         Ok(()) => std::process::exit(0),
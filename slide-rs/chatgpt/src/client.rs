@@ -1,19 +1,9 @@
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
+use slide_common::privacy::{DataFlow, DataFlowRegistry, DataSink};
+use slide_common::SlideConfig;
+use std::path::PathBuf;
 use tokio::{io::AsyncBufReadExt, sync::mpsc};
 
-#[derive(Debug, Serialize)]
-pub struct SlideRequest {
-    pub prompt: String,
-    pub num_slides: usize,
-    pub language: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct SlideResponse {
-    pub markdown: String,
-}
-
 fn append_log(line: &str) {
     use std::io::Write;
     if let Ok(mut f) = std::fs::OpenOptions::new()
@@ -21,42 +11,74 @@ fn append_log(line: &str) {
         .append(true)
         .open("/tmp/slide.log")
     {
-        let _ = writeln!(f, "[chatgpt-client] {}", line);
+        let _ = writeln!(
+            f,
+            "[chatgpt-client] {}",
+            slide_common::secrets::redact(line)
+        );
     }
 }
 
-pub struct ChatGptClient {
-    #[allow(dead_code)]
-    api_key: String,
+pub const CHAT_COMPLETIONS_URL: &str = "https://api.openai.com/v1/chat/completions";
+const MODELS_URL: &str = "https://api.openai.com/v1/models";
+
+/// Register the endpoints and files this client writes to.
+pub fn register_data_flows(registry: &mut DataFlowRegistry, config: &SlideConfig) {
+    registry.register(DataFlow {
+        subsystem: "chatgpt.api",
+        description: "Prompts, conversation history and tool output sent to the model",
+        sink: DataSink::Network(CHAT_COMPLETIONS_URL.to_string()),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "chatgpt.images",
+        description: "Image prompts sent by the `image` tool when generating slide art",
+        sink: DataSink::Network(crate::images::IMAGE_GENERATIONS_URL.to_string()),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "chatgpt.login",
+        description: "API key check made by `slide login`",
+        sink: DataSink::Network(MODELS_URL.to_string()),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "chatgpt.web-search",
+        description: "Queries sent by the `web_search` tool (after approval)",
+        sink: DataSink::Network(crate::web::BRAVE_SEARCH_URL.to_string()),
+        active: crate::web::search_api_key().is_some(),
+    });
+    registry.register(DataFlow {
+        subsystem: "chatgpt.moderation",
+        description: "Deck content the agent writes, checked by the content safety filter",
+        sink: DataSink::Network(crate::moderation::MODERATIONS_URL.to_string()),
+        active: config.moderation.sends_content(),
+    });
+    registry.register(DataFlow {
+        subsystem: "chatgpt.log",
+        description: "Request bodies and streaming diagnostics",
+        sink: DataSink::File(PathBuf::from("/tmp/slide.log")),
+        active: true,
+    });
 }
 
-impl ChatGptClient {
-    pub fn new(api_key: String) -> Self {
-        Self { api_key }
+/// Check an API key with a cheap authenticated request (listing models).
+pub async fn validate_api_key(api_key: &str) -> Result<()> {
+    let resp = reqwest::Client::new()
+        .get(MODELS_URL)
+        .bearer_auth(api_key)
+        .send()
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
     }
-
-    pub async fn generate_slides(&self, request: SlideRequest) -> Result<SlideResponse> {
-        // TODO: Implement actual OpenAI API call
-        // For now, return a mock response
-        let mock_markdown = format!(
-            r#"# {}
-
-## Slide 1: Introduction
-- Point A
-- Point B
-
-## Slide 2: Content
-- Content point 1
-- Content point 2
-
-"#,
-            request.prompt
-        );
-
-        Ok(SlideResponse {
-            markdown: mock_markdown,
-        })
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(anyhow!("the API key was rejected (http {status})"));
     }
+    let text = resp.text().await.unwrap_or_default();
+    Err(anyhow!("openai http {status}: {text}"))
 }
 
 /// Minimal OpenAI Chat Completions streaming client compatible with `ModelClient` trait
@@ -90,7 +112,7 @@ impl OpenAiModelClient {
         ));
 
         let mut req = client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(CHAT_COMPLETIONS_URL)
             .bearer_auth(&self.api_key)
             .header("content-type", "application/json");
         if let Ok(project) = std::env::var("OPENAI_PROJECT") {
@@ -0,0 +1,55 @@
+use anyhow::{anyhow, Result};
+use base64::Engine as _;
+
+pub const IMAGE_GENERATIONS_URL: &str = "https://api.openai.com/v1/images/generations";
+
+/// Refuse to download anything larger than this.
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Download an image from a user-provided URL.
+pub async fn download_image(url: &str) -> Result<Vec<u8>> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(anyhow!("only http(s) URLs can be downloaded: {url}"));
+    }
+    let resp = reqwest::get(url).await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("download failed with http {status}"));
+    }
+    if let Some(len) = resp.content_length() {
+        if len as usize > MAX_IMAGE_BYTES {
+            return Err(anyhow!("image is too large ({len} bytes)"));
+        }
+    }
+    let bytes = resp.bytes().await?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(anyhow!("image is too large ({} bytes)", bytes.len()));
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Generate an image with the OpenAI Images API and return the PNG bytes.
+pub async fn generate_image(api_key: &str, prompt: &str, size: &str) -> Result<Vec<u8>> {
+    let body = serde_json::json!({
+        "model": "gpt-image-1",
+        "prompt": prompt,
+        "size": size,
+        "n": 1,
+    });
+    let resp = reqwest::Client::new()
+        .post(IMAGE_GENERATIONS_URL)
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("openai images http {status}: {text}"));
+    }
+    let value: serde_json::Value = resp.json().await?;
+    let b64 = value["data"][0]["b64_json"]
+        .as_str()
+        .ok_or_else(|| anyhow!("image response did not contain b64_json"))?;
+    Ok(base64::engine::general_purpose::STANDARD.decode(b64)?)
+}
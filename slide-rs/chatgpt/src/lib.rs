@@ -1,4 +1,7 @@
 /// OpenAI ChatGPT integration for slide generation
 pub mod client;
+pub mod images;
+pub mod moderation;
+pub mod web;
 
 pub use client::*;
@@ -0,0 +1,48 @@
+use anyhow::{anyhow, Result};
+
+use crate::web::FETCH_TIMEOUT;
+
+pub const MODERATIONS_URL: &str = "https://api.openai.com/v1/moderations";
+pub const MODERATION_MODEL: &str = "omni-moderation-latest";
+
+/// Categories the OpenAI moderation model flags in `text`, such as `hate`
+/// or `violence/graphic`; empty when it flags nothing.
+pub async fn moderate(api_key: &str, text: &str) -> Result<Vec<String>> {
+    let body = serde_json::json!({
+        "model": MODERATION_MODEL,
+        "input": text,
+    });
+    let resp = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()?
+        .post(MODERATIONS_URL)
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("openai moderation http {status}: {text}"));
+    }
+    let value: serde_json::Value = resp.json().await?;
+    Ok(flagged_categories(&value))
+}
+
+fn flagged_categories(value: &serde_json::Value) -> Vec<String> {
+    let mut flagged: Vec<String> = value["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| result["categories"].as_object())
+        .flat_map(|categories| {
+            categories
+                .iter()
+                .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+                .map(|(name, _)| name.clone())
+        })
+        .collect();
+    flagged.sort();
+    flagged.dedup();
+    flagged
+}
@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use std::time::Duration;
+
+/// Responses larger than this are cut off.
+pub const MAX_FETCH_BYTES: usize = 2 * 1024 * 1024;
+/// Time allowed for a whole request, body included.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Key for the Brave Search API, which backs `web_search`.
+pub const SEARCH_API_KEY_ENV: &str = "BRAVE_SEARCH_API_KEY";
+pub const BRAVE_SEARCH_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedPage {
+    /// URL after redirects
+    pub url: String,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+    /// Whether the body was cut at [`MAX_FETCH_BYTES`]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// Download `url`, reading at most [`MAX_FETCH_BYTES`] of the body.
+pub async fn fetch_url(url: &str) -> Result<FetchedPage> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(anyhow!("only http(s) URLs can be fetched: {url}"));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("slide/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let resp = client.get(url).send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("fetch failed with http {status}"));
+    }
+    let final_url = resp.url().to_string();
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+
+    let mut body = Vec::new();
+    let mut truncated = false;
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        let room = MAX_FETCH_BYTES - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            truncated = true;
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(FetchedPage {
+        url: final_url,
+        content_type,
+        body,
+        truncated,
+    })
+}
+
+/// Brave Search API key from the environment, if configured.
+pub fn search_api_key() -> Option<String> {
+    std::env::var(SEARCH_API_KEY_ENV)
+        .ok()
+        .filter(|k| !k.is_empty())
+}
+
+/// Top `count` web results for `query` from the Brave Search API.
+pub async fn search(api_key: &str, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+    let count = count.to_string();
+    let resp = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()?
+        .get(BRAVE_SEARCH_URL)
+        .header("X-Subscription-Token", api_key)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .query(&[("q", query), ("count", count.as_str())])
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("web search http {status}: {text}"));
+    }
+    let value: serde_json::Value = resp.json().await?;
+    Ok(parse_search_results(&value))
+}
+
+fn parse_search_results(value: &serde_json::Value) -> Vec<SearchResult> {
+    value["web"]["results"]
+        .as_array()
+        .map(|results| {
+            results
+                .iter()
+                .filter_map(|r| {
+                    Some(SearchResult {
+                        title: r["title"].as_str()?.to_string(),
+                        url: r["url"].as_str()?.to_string(),
+                        snippet: r["description"].as_str().unwrap_or_default().to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,91 @@
+//! The `slide` command line: the TUI flags plus every subcommand. This is the
+//! one definition `main` parses and `slide completions`/`slide man` render.
+use clap::Parser;
+use slide_tui::Cli as TuiCli;
+
+use crate::commit::CommitCommand;
+use crate::completions::{CompletionsCommand, ManCommand};
+use crate::doctor::DoctorCommand;
+use crate::generate::{GenerateCommand, RegenerateCommand};
+use crate::history::HistoryCommand;
+use crate::lint::LintCommand;
+use crate::login::LoginCommand;
+use crate::merge::MergeCommand;
+use crate::plan::PlanCommand;
+use crate::privacy::PrivacyCommand;
+use crate::project::PreviewCommand;
+use crate::replay::ReplayCommand;
+use crate::sessions::SessionsCommand;
+use crate::stats::StatsCommand;
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "slide",
+    version,
+    about = "Chat with an agent that writes Markdown slide decks"
+)]
+pub struct MultitoolCli {
+    #[clap(flatten)]
+    pub interactive: TuiCli,
+
+    #[clap(subcommand)]
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Subcommand {
+    /// Draft a Conventional Commits message for the current diff and commit
+    Commit(CommitCommand),
+
+    /// Print a shell completion script
+    Completions(CompletionsCommand),
+
+    /// Check the API key, network, sandbox, git, slides directory and terminal
+    Doctor(DoctorCommand),
+
+    /// Generate a slide deck from a prompt without opening the TUI
+    Generate(GenerateCommand),
+
+    /// List saved versions of a deck
+    History(HistoryCommand),
+
+    /// Check the frontmatter settings of decks and flag claims without a source
+    Lint(LintCommand),
+
+    /// List the decks of the project (slides/slide.toml)
+    List,
+
+    /// Check an API key and save it to the OS keyring (~/.slide/auth.json
+    /// when no keyring is available)
+    Login(LoginCommand),
+
+    /// Remove the credentials saved by `slide login`
+    Logout,
+
+    /// Print the man page, or write one per subcommand to a directory
+    Man(ManCommand),
+
+    /// Combine decks into one, optionally in the order of an outline
+    Merge(MergeCommand),
+
+    /// List saved agent plans or continue an interrupted one
+    Plan(PlanCommand),
+
+    /// Preview a deck by name or path
+    Preview(PreviewCommand),
+
+    /// Inspect privacy settings and data flows
+    Privacy(PrivacyCommand),
+
+    /// Rewrite only the slides whose entries in <deck>.outline.md changed
+    Regenerate(RegenerateCommand),
+
+    /// Replay a recorded session in the TUI or export it as markdown/HTML
+    Replay(ReplayCommand),
+
+    /// List recorded sessions or export one as a markdown/HTML report
+    Sessions(SessionsCommand),
+
+    /// Show slide count, words per slide and speaking-time estimates
+    Stats(StatsCommand),
+}
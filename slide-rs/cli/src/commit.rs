@@ -0,0 +1,99 @@
+use std::io::{BufRead as _, Write as _};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use clap::Args;
+use slide_core::git_commit::{create_commit, generate_commit_message, CommitDiff};
+
+#[derive(Debug, Args)]
+pub struct CommitCommand {
+    /// Commit with the drafted message without asking
+    #[clap(long, short = 'y')]
+    yes: bool,
+
+    /// Print the drafted message and exit without committing
+    #[clap(long, conflicts_with = "yes")]
+    dry_run: bool,
+}
+
+pub async fn run(cmd: CommitCommand, model: Option<String>) -> anyhow::Result<()> {
+    let cwd = std::env::current_dir()?;
+    let diff = tokio::task::spawn_blocking({
+        let cwd = cwd.clone();
+        move || CommitDiff::collect(&cwd)
+    })
+    .await??;
+    eprintln!(
+        "Drafting a message for the {} changes…",
+        if diff.staged { "staged" } else { "tracked" }
+    );
+    eprint!("{}", diff.stat);
+
+    let client = slide_core::client::client_from_env(model);
+    let mut message = generate_commit_message(client.as_ref(), &diff).await?;
+    if cmd.dry_run {
+        print!("{message}");
+        return Ok(());
+    }
+
+    if !cmd.yes {
+        loop {
+            eprintln!("\n{}", message.trim_end());
+            match ask("\nCommit with this message? [y]es / [e]dit / [n]o: ")?.as_str() {
+                "y" | "yes" => break,
+                "e" | "edit" => message = edit(&message)?,
+                "" | "n" | "no" => {
+                    eprintln!("Commit cancelled");
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let summary = create_commit(&cwd, &message, diff.staged)?;
+    println!("{summary}");
+    Ok(())
+}
+
+/// Answer to `question`, trimmed and lowercased; empty at end of input.
+fn ask(question: &str) -> anyhow::Result<String> {
+    eprint!("{question}");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_ascii_lowercase())
+}
+
+/// Open `message` in `$VISUAL`/`$EDITOR` (default `vi`) and return what
+/// was saved.
+fn edit(message: &str) -> anyhow::Result<String> {
+    let path = std::env::temp_dir().join(format!("slide-commit-{}.txt", std::process::id()));
+    std::fs::write(&path, message)?;
+    let result = run_editor(&path)
+        .and_then(|()| std::fs::read_to_string(&path).context("failed to read the edited message"));
+    let _ = std::fs::remove_file(&path);
+    let edited = result?;
+    if edited.trim().is_empty() {
+        bail!("empty commit message");
+    }
+    Ok(edited)
+}
+
+fn run_editor(path: &Path) -> anyhow::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The variable may carry arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().map(PathBuf::from).context("empty $EDITOR")?;
+    let status = std::process::Command::new(&program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to start {}", program.display()))?;
+    if !status.success() {
+        bail!("{} exited with {status}", program.display());
+    }
+    Ok(())
+}
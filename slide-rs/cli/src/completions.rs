@@ -0,0 +1,94 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::{Args, CommandFactory, ValueEnum};
+use clap_complete::Shell;
+
+use crate::cli::MultitoolCli;
+
+#[derive(Debug, Args)]
+pub struct CompletionsCommand {
+    /// Shell to generate the script for
+    #[clap(value_enum)]
+    shell: CompletionShell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "pwsh", alias = "powershell")]
+    PowerShell,
+}
+
+impl From<CompletionShell> for Shell {
+    fn from(shell: CompletionShell) -> Self {
+        match shell {
+            CompletionShell::Bash => Shell::Bash,
+            CompletionShell::Zsh => Shell::Zsh,
+            CompletionShell::Fish => Shell::Fish,
+            CompletionShell::PowerShell => Shell::PowerShell,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ManCommand {
+    /// Write slide.1 and slide-<subcommand>.1 pages here instead of
+    /// printing slide.1
+    #[clap(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+}
+
+/// e.g. `slide completions zsh > ~/.zfunc/_slide`
+pub fn run_completions(cmd: CompletionsCommand) -> anyhow::Result<()> {
+    let mut command = MultitoolCli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(
+        Shell::from(cmd.shell),
+        &mut command,
+        name,
+        &mut std::io::stdout(),
+    );
+    Ok(())
+}
+
+pub fn run_man(cmd: ManCommand) -> anyhow::Result<()> {
+    let command = MultitoolCli::command();
+    let Some(dir) = cmd.output_dir else {
+        let mut stdout = std::io::stdout();
+        clap_mangen::Man::new(command).render(&mut stdout)?;
+        return Ok(stdout.flush()?);
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut written = Vec::new();
+    write_man_pages(command, &dir, &mut written)?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// The page of `command`, then one per subcommand named after its path
+/// (`slide-privacy-report.1`), as `git` and `cargo` do.
+fn write_man_pages(
+    command: clap::Command,
+    dir: &Path,
+    written: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let command = command.disable_help_subcommand(true);
+    let name = command.get_name().to_string();
+    let path = dir.join(format!("{name}.1"));
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command.clone()).render(&mut page)?;
+    std::fs::write(&path, page).with_context(|| format!("failed to write {}", path.display()))?;
+    written.push(path);
+
+    for sub in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let sub_name = format!("{name}-{}", sub.get_name());
+        write_man_pages(sub.clone().name(sub_name), dir, written)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,358 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::Args;
+use slide_common::auth::{AuthStore, OPENAI_API_KEY_ENV};
+use slide_common::i18n::{t, tf, Msg};
+use slide_common::SlideConfig;
+
+/// Time allowed for each network check
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Args)]
+pub struct DoctorCommand {
+    /// Skip the checks that contact the provider
+    #[clap(long)]
+    offline: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    /// What to do about a warning or failure
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check and print the results. Fails when any check failed;
+/// warnings alone keep the exit status at 0.
+pub async fn run(cmd: DoctorCommand, config: &SlideConfig) -> anyhow::Result<()> {
+    let provider = AuthStore::default()
+        .load()
+        .map_or_else(|| "openai".to_string(), |auth| auth.provider);
+    let network = if cmd.offline {
+        Check::warn("network", "skipped (--offline)", "run without --offline")
+    } else {
+        check_network(&provider).await
+    };
+    let reachable = network.status == Status::Ok;
+    let checks = vec![
+        check_api_key(&provider, reachable).await,
+        network,
+        check_sandbox(),
+        check_git(),
+        check_instructions(),
+        check_output_dir(&config.output_dir),
+        check_terminal(),
+    ];
+
+    for check in &checks {
+        let label = match check.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        println!("[{label:>4}] {:<10} {}", check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("{:18}→ {fix}", "");
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    if failed > 0 {
+        return Err(anyhow!(
+            "{}",
+            tf(
+                Msg::DoctorFailed,
+                &[("failed", &failed), ("warned", &warned)]
+            )
+        ));
+    }
+    if warned > 0 {
+        println!(
+            "{}",
+            tf(Msg::DoctorReadyWithWarnings, &[("warned", &warned)])
+        );
+    } else {
+        println!("{}", t(Msg::DoctorAllGood));
+    }
+    Ok(())
+}
+
+/// Host the agent's model requests go to
+fn provider_host(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openai" => Some("api.openai.com"),
+        _ => None,
+    }
+}
+
+async fn check_api_key(provider: &str, reachable: bool) -> Check {
+    let from_env =
+        provider == "openai" && std::env::var(OPENAI_API_KEY_ENV).is_ok_and(|key| !key.is_empty());
+    let Some(key) = AuthStore::default().api_key(provider) else {
+        return Check::fail(
+            "api key",
+            format!("no {provider} key; sessions fall back to the offline stub"),
+            format!("run `slide login` or set {OPENAI_API_KEY_ENV}"),
+        );
+    };
+    let source = if from_env {
+        OPENAI_API_KEY_ENV.to_string()
+    } else {
+        "`slide login`".to_string()
+    };
+    if !reachable {
+        return Check::warn(
+            "api key",
+            format!("{provider} key from {source} not verified"),
+            "fix the network check, then run `slide doctor` again",
+        );
+    }
+    let validated = tokio::time::timeout(
+        NETWORK_TIMEOUT,
+        slide_core::client::validate_api_key(provider, &key),
+    )
+    .await;
+    match validated {
+        Ok(Ok(())) => Check::ok("api key", format!("{provider} key from {source} accepted")),
+        Ok(Err(e)) => Check::fail(
+            "api key",
+            format!("{provider} key from {source}: {e}"),
+            "create a new key and run `slide login --api-key <key>`",
+        ),
+        Err(_) => Check::warn(
+            "api key",
+            format!(
+                "{provider} did not answer within {}s",
+                NETWORK_TIMEOUT.as_secs()
+            ),
+            "try again later",
+        ),
+    }
+}
+
+async fn check_network(provider: &str) -> Check {
+    let Some(host) = provider_host(provider) else {
+        return Check::warn(
+            "network",
+            format!("unknown provider {provider}"),
+            "run `slide login` with a supported provider",
+        );
+    };
+    let addr = format!("{host}:443");
+    match tokio::time::timeout(NETWORK_TIMEOUT, tokio::net::TcpStream::connect(&addr)).await {
+        Ok(Ok(_)) => Check::ok("network", format!("{addr} reachable")),
+        Ok(Err(e)) => Check::fail(
+            "network",
+            format!("cannot reach {addr}: {e}"),
+            "check the connection, DNS and firewall or proxy settings",
+        ),
+        Err(_) => Check::fail(
+            "network",
+            format!("{addr} timed out after {}s", NETWORK_TIMEOUT.as_secs()),
+            "check the connection, DNS and firewall or proxy settings",
+        ),
+    }
+}
+
+/// Whether the tool the exec layer sandboxes commands with is available.
+fn check_sandbox() -> Check {
+    if cfg!(target_os = "macos") {
+        if Path::new("/usr/bin/sandbox-exec").exists() {
+            Check::ok("sandbox", "seatbelt (sandbox-exec) available")
+        } else {
+            Check::fail(
+                "sandbox",
+                "/usr/bin/sandbox-exec not found",
+                "commands cannot be sandboxed; keep approvals on `untrusted`",
+            )
+        }
+    } else if cfg!(target_os = "linux") {
+        let landlock = std::fs::read_to_string("/sys/kernel/security/lsm")
+            .is_ok_and(|lsm| lsm.split(',').any(|m| m.trim() == "landlock"));
+        // Commands are confined by `slide` itself, re-run as slide-linux-sandbox
+        if landlock {
+            Check::ok("sandbox", "landlock enabled")
+        } else {
+            Check::fail(
+                "sandbox",
+                "landlock not enabled in the kernel",
+                "enable landlock (lsm=...,landlock); sandboxed commands will refuse to run",
+            )
+        }
+    } else {
+        Check::warn(
+            "sandbox",
+            format!("no sandbox on {}", std::env::consts::OS),
+            "commands run unsandboxed; keep approvals on `untrusted`",
+        )
+    }
+}
+
+fn check_git() -> Check {
+    let output = std::process::Command::new("git").arg("--version").output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let in_repo = std::process::Command::new("git")
+                .args(["rev-parse", "--is-inside-work-tree"])
+                .output()
+                .is_ok_and(|o| o.status.success());
+            if in_repo {
+                Check::ok("git", version)
+            } else {
+                Check::warn(
+                    "git",
+                    format!("{version}; the current directory is not a repository"),
+                    "run `git init` to keep a history of the agent's changes",
+                )
+            }
+        }
+        _ => Check::warn(
+            "git",
+            "git not found",
+            "install git; the agent uses it to inspect and commit changes",
+        ),
+    }
+}
+
+/// The `SLIDE.md`/`AGENTS.md` files merged into the agent's instructions.
+fn check_instructions() -> Check {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let docs = slide_core::project_doc::discover_project_docs(&cwd);
+    if docs.is_empty() {
+        return Check::ok("agents-md", "no SLIDE.md or AGENTS.md found");
+    }
+    let files = docs
+        .iter()
+        .map(|doc| {
+            doc.path
+                .strip_prefix(&cwd)
+                .unwrap_or(&doc.path)
+                .display()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if docs.iter().any(|doc| doc.truncated) {
+        Check::warn(
+            "agents-md",
+            format!("{files} (cut to fit)"),
+            format!(
+                "keep instruction files under {} KiB in total",
+                slide_core::project_doc::PROJECT_DOC_MAX_BYTES / 1024
+            ),
+        )
+    } else {
+        Check::ok("agents-md", files)
+    }
+}
+
+/// The directory decks are written to, or the parent it would be created in.
+fn check_output_dir(dir: &Path) -> Check {
+    let (target, existing) = if dir.is_dir() {
+        (dir, true)
+    } else {
+        let parent = dir
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        (parent, false)
+    };
+    let probe = target.join(format!(".slide-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            if existing {
+                Check::ok("slides", format!("{} is writable", dir.display()))
+            } else {
+                Check::ok(
+                    "slides",
+                    format!("{} will be created on first use", dir.display()),
+                )
+            }
+        }
+        Err(e) => Check::fail(
+            "slides",
+            format!("cannot write to {}: {e}", target.display()),
+            "fix the permissions or set `output_dir` in the config",
+        ),
+    }
+}
+
+fn check_terminal() -> Check {
+    if !std::io::stdout().is_terminal() {
+        return Check::warn(
+            "terminal",
+            "stdout is not a terminal",
+            "run `slide` itself in an interactive terminal",
+        );
+    }
+    let term = std::env::var("TERM").unwrap_or_default();
+    if term.is_empty() || term == "dumb" {
+        return Check::warn(
+            "terminal",
+            format!("TERM={term:?} has no cursor control"),
+            "set TERM, e.g. xterm-256color",
+        );
+    }
+    let colors = match std::env::var("COLORTERM").as_deref() {
+        Ok("truecolor" | "24bit") => "truecolor",
+        _ if term.contains("256color") => "256 colors",
+        _ => "basic colors",
+    };
+    match crossterm::terminal::size() {
+        Ok((cols, rows)) if cols < 80 || rows < 24 => Check::warn(
+            "terminal",
+            format!("{term}, {colors}, {cols}x{rows}"),
+            "enlarge the window to at least 80x24",
+        ),
+        Ok((cols, rows)) => Check::ok("terminal", format!("{term}, {colors}, {cols}x{rows}")),
+        Err(e) => Check::warn(
+            "terminal",
+            format!("{term}, {colors}, size unknown: {e}"),
+            "run `slide` itself in an interactive terminal",
+        ),
+    }
+}
@@ -0,0 +1,206 @@
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use slide_common::moderation::ModerationAction;
+use slide_common::versions::unified_diff;
+use slide_common::{Deck, ModerationConfig, Outline, SlideConfig};
+use slide_core::moderation::added_lines;
+use slide_core::slide_generate::{generate_slides, GenerateProgress, SlideGenerateOptions};
+use slide_core::slide_regenerate::{plan_regeneration, regenerate_deck};
+
+#[derive(Debug, Args)]
+pub struct GenerateCommand {
+    /// What the deck should be about
+    #[clap(required = true)]
+    prompt: Vec<String>,
+
+    /// Number of slides to generate
+    #[clap(long, short = 'n', default_value_t = 6)]
+    slides: usize,
+
+    /// Language the slides are written in
+    #[clap(long, default_value = "ja")]
+    lang: String,
+
+    /// Only generate slide titles with a one-line summary each
+    #[clap(long)]
+    outline_only: bool,
+
+    /// Markdown deck whose structure the output should follow
+    #[clap(long, value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Where to write the deck (default: <output_dir>/<timestamp>_<slug>.md)
+    #[clap(long, short = 'o', value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct RegenerateCommand {
+    /// Deck whose outline (<deck>.outline.md) was edited
+    deck: PathBuf,
+
+    /// Language of the new slides (default: the deck's `lang`, else ja)
+    #[clap(long)]
+    lang: Option<String>,
+
+    /// Show which slides would be written without calling the model
+    #[clap(long)]
+    dry_run: bool,
+}
+
+pub async fn run(
+    cmd: GenerateCommand,
+    model: Option<String>,
+    config: &SlideConfig,
+) -> anyhow::Result<()> {
+    let template = match &cmd.template {
+        Some(path) => Some(
+            tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("failed to read template {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let opts = SlideGenerateOptions {
+        prompt: cmd.prompt.join(" "),
+        num_slides: cmd.slides,
+        language: cmd.lang,
+        outline_only: cmd.outline_only,
+        template,
+    };
+
+    let client = slide_core::client::configured_client_from_env(model)?;
+    eprintln!(
+        "Generating {} {} ({})…",
+        opts.num_slides,
+        if opts.outline_only {
+            "outline entries"
+        } else {
+            "slides"
+        },
+        opts.language
+    );
+    let markdown = generate_slides(client.as_ref(), &opts, report_progress).await?;
+    eprintln!();
+    check_content(&config.moderation, &markdown).await?;
+
+    let path = match cmd.output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, &markdown).await?;
+            path
+        }
+        None => {
+            let filename = slide_common::generate_slide_filename(&opts.prompt);
+            slide_common::save_slide(&config.output_dir, &filename, &markdown).await?
+        }
+    };
+    eprintln!("Wrote {}", path.display());
+    // The outline to edit, and the one the deck was written from, for
+    // `slide regenerate`
+    if !opts.outline_only {
+        let outline = Outline::from_deck(&Deck::parse(&markdown));
+        tokio::fs::write(Outline::path_for(&path), outline.to_markdown()).await?;
+        outline.save_snapshot(&path)?;
+    }
+    Ok(())
+}
+
+/// `slide regenerate`: write again the slides whose outline entries were
+/// added or edited since the deck was written, and keep the others.
+pub async fn run_regenerate(
+    cmd: RegenerateCommand,
+    model: Option<String>,
+    config: &SlideConfig,
+) -> anyhow::Result<()> {
+    let outline_path = Outline::path_for(&cmd.deck);
+    let edited = Outline::parse(
+        &tokio::fs::read_to_string(&outline_path)
+            .await
+            .with_context(|| format!("failed to read the outline {}", outline_path.display()))?,
+    );
+    // A deck not written yet is generated from the outline as a whole
+    let markdown = match tokio::fs::read_to_string(&cmd.deck).await {
+        Ok(markdown) => markdown,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", cmd.deck.display())),
+    };
+    let deck = Deck::parse(&markdown);
+    let written_from = Outline::load_snapshot(&cmd.deck).unwrap_or_default();
+    let plan = plan_regeneration(&deck, &written_from, &edited);
+
+    let to_generate = plan.to_generate();
+    for index in &to_generate {
+        eprintln!("  write: {}", edited.entries[*index].title);
+    }
+    for index in &plan.dropped {
+        eprintln!("  drop:  {}", deck.slide_title(*index));
+    }
+    if to_generate.is_empty() && plan.dropped.is_empty() {
+        eprintln!("No outline entries changed");
+    }
+    if cmd.dry_run {
+        return Ok(());
+    }
+
+    let language = cmd
+        .lang
+        .or(deck.settings.language.clone())
+        .unwrap_or_else(|| "ja".to_string());
+    let client = slide_core::client::configured_client_from_env(model)?;
+    let mut written = 0;
+    let total = to_generate.len();
+    let new = regenerate_deck(
+        client.as_ref(),
+        &markdown,
+        &plan,
+        &edited,
+        &language,
+        |title| {
+            written += 1;
+            eprintln!("Writing {written}/{total}: {title}…");
+        },
+    )
+    .await?;
+    if new != markdown {
+        let added = unified_diff(&markdown, &new);
+        check_content(&config.moderation, &added_lines(&added)).await?;
+        if let Some(parent) = cmd.deck.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&cmd.deck, &new).await?;
+        eprintln!("Wrote {}", cmd.deck.display());
+    }
+    edited.save_snapshot(&cmd.deck)?;
+    Ok(())
+}
+
+/// Run the profile's content safety filter over generated text; fails
+/// when it blocks the text, and warns when it only flags it.
+async fn check_content(config: &ModerationConfig, text: &str) -> anyhow::Result<()> {
+    let reasons = slide_core::moderation::check_content(config, text).await;
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    let reasons = reasons.join(", ");
+    if config.action == ModerationAction::Block {
+        anyhow::bail!("the content safety filter blocked the generated slides ({reasons})");
+    }
+    eprintln!("warning: the content safety filter flagged the generated slides ({reasons})");
+    Ok(())
+}
+
+fn report_progress(progress: GenerateProgress) {
+    let mut stderr = std::io::stderr();
+    let _ = write!(
+        stderr,
+        "\r  {} slides, {} bytes received",
+        progress.slides, progress.bytes
+    );
+    let _ = stderr.flush();
+}
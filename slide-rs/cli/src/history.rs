@@ -0,0 +1,49 @@
+use anyhow::anyhow;
+use clap::Args;
+use slide_common::versions::diff_stat;
+use slide_common::{Project, SlideConfig, VersionStore};
+
+#[derive(Debug, Args)]
+pub struct HistoryCommand {
+    /// Deck name from slide.toml, or a path to a markdown file
+    deck: String,
+
+    /// Open the diff view to compare and restore versions
+    #[clap(long)]
+    tui: bool,
+}
+
+pub async fn run(cmd: HistoryCommand, config: &SlideConfig) -> anyhow::Result<()> {
+    let project = Project::open(&config.output_dir)?;
+    let deck = project
+        .resolve(&cmd.deck)
+        .ok_or_else(|| anyhow!("unknown deck: {} (see `slide list`)", cmd.deck))?;
+    if cmd.tui {
+        return slide_tui::run_history(deck).await;
+    }
+
+    let store = VersionStore::new(".");
+    let versions = store.list(&deck)?;
+    if versions.is_empty() {
+        println!(
+            "No versions of {} in {}",
+            deck.display(),
+            store.deck_dir(&deck).display()
+        );
+        return Ok(());
+    }
+
+    let current = std::fs::read_to_string(&deck).unwrap_or_default();
+    println!("{} ({} versions)", deck.display(), versions.len());
+    for (i, version) in versions.iter().enumerate().rev() {
+        let content = version.read()?;
+        let change = if content == current {
+            "current".to_string()
+        } else {
+            let (added, removed) = diff_stat(&content, &current);
+            format!("+{added} -{removed} to current")
+        };
+        println!("{:>3}  {}  {}", i + 1, version.label(), change);
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use slide_common::sources::unsourced_claims;
+use slide_common::DeckSettings;
+
+#[derive(Debug, Args)]
+pub struct LintCommand {
+    /// Markdown decks to check
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+/// Check the frontmatter of each deck against the settings schema and warn
+/// about factual-looking bullets that cite no source; fails when any deck
+/// has frontmatter errors.
+pub async fn run(cmd: LintCommand) -> anyhow::Result<()> {
+    let mut problems = 0;
+    let mut warnings = 0;
+    for file in &cmd.files {
+        let content = tokio::fs::read_to_string(file)
+            .await
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let (_, errors) = DeckSettings::parse(&content);
+        for error in &errors {
+            println!("{}:{}: {}", file.display(), error.line, error.message);
+        }
+        problems += errors.len();
+        let claims = unsourced_claims(&content);
+        for claim in &claims {
+            println!(
+                "{}:{}: warning: claim without a source: {}",
+                file.display(),
+                claim.line,
+                claim.text
+            );
+        }
+        warnings += claims.len();
+    }
+    if problems > 0 {
+        anyhow::bail!("frontmatter errors: {problems}");
+    }
+    if warnings > 0 {
+        println!(
+            "{warnings} warning(s): link each claim to its source, or ignore if it needs none"
+        );
+    } else {
+        println!("No problems found");
+    }
+    Ok(())
+}
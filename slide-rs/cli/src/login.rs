@@ -0,0 +1,52 @@
+use anyhow::anyhow;
+use clap::Args;
+use slide_common::auth::{AuthDotJson, AuthStore, LOGIN_PROVIDERS};
+
+#[derive(Debug, Args)]
+pub struct LoginCommand {
+    /// Store this key without opening the login screen
+    #[clap(long)]
+    api_key: Option<String>,
+
+    /// Provider the key belongs to
+    #[clap(long, default_value = "openai")]
+    provider: String,
+}
+
+pub async fn run_login(cmd: LoginCommand) -> anyhow::Result<()> {
+    let store = AuthStore::default();
+    let Some(api_key) = cmd.api_key else {
+        match slide_tui::run_login().await? {
+            Some(location) => println!("Logged in; credentials saved to {location}"),
+            None => println!("Login cancelled"),
+        }
+        return Ok(());
+    };
+
+    if !LOGIN_PROVIDERS.iter().any(|(id, _)| *id == cmd.provider) {
+        let known: Vec<&str> = LOGIN_PROVIDERS.iter().map(|(id, _)| *id).collect();
+        return Err(anyhow!(
+            "unknown provider: {} (expected one of: {})",
+            cmd.provider,
+            known.join(", ")
+        ));
+    }
+    let api_key = api_key.trim().to_string();
+    slide_core::client::validate_api_key(&cmd.provider, &api_key).await?;
+    let location = store.save(&AuthDotJson {
+        provider: cmd.provider,
+        api_key,
+    })?;
+    println!("Logged in; credentials saved to {location}");
+    Ok(())
+}
+
+pub fn run_logout() -> anyhow::Result<()> {
+    let store = AuthStore::default();
+    if store.remove()? {
+        println!("Removed the saved credentials");
+    } else {
+        println!("Not logged in");
+    }
+    Ok(())
+}
@@ -1,5 +1,22 @@
+mod cli;
+mod commit;
+mod completions;
+mod doctor;
+mod generate;
+mod history;
+mod lint;
+mod login;
+mod merge;
+mod plan;
+mod privacy;
+mod project;
+mod replay;
+mod sessions;
+mod stats;
+
+use clap::Parser;
 use slide_arg0::arg0_dispatch_or_else;
-use slide_tui::Cli as TuiCli;
+use slide_common::SlideConfig;
 use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
@@ -7,32 +24,103 @@ use std::time::Duration;
 use tiny_http::{Response, Server};
 use webbrowser;
 
+use crate::cli::{MultitoolCli, Subcommand};
+
+/// Address of the local log viewer started alongside the TUI.
+const LOG_VIEWER_ADDR: &str = "127.0.0.1:6060";
+
 fn main() -> anyhow::Result<()> {
     // Check if we're in Slide mode via environment variable
     let is_slide_mode = std::env::var("SLIDE_APP").is_ok();
-    // Load env.local if present (OPENAI_API_KEY etc.)
-    try_load_env_local();
 
-    arg0_dispatch_or_else(|slide_linux_sandbox_exe| async move {
-        cli_main(slide_linux_sandbox_exe, is_slide_mode).await?;
-        Ok(())
+    arg0_dispatch_or_else(|slide_linux_sandbox_exe| {
+        // Parsed after dispatch: as the sandbox helper, argv is not ours
+        let cli = parse_cli();
+        async move {
+            cli_main(cli?, slide_linux_sandbox_exe, is_slide_mode).await?;
+            Ok(())
+        }
     })
 }
 
+/// Parse the command line and apply the global flags, before the runtime
+/// starts any threads.
+fn parse_cli() -> anyhow::Result<MultitoolCli> {
+    let mut cli = MultitoolCli::parse();
+    for warning in cli.interactive.apply_globals()? {
+        eprintln!("warning: {warning}");
+    }
+    // Before any screen enters raw mode, so a panic leaves the terminal usable
+    slide_tui::install_panic_hook();
+    Ok(cli)
+}
+
 async fn cli_main(
+    cli: MultitoolCli,
     slide_linux_sandbox_exe: Option<PathBuf>,
     is_slide_mode: bool,
 ) -> anyhow::Result<()> {
+    let config = SlideConfig::load().await.unwrap_or_default();
+    slide_common::privacy::set_active(config.privacy.clone());
+    let cwd = std::env::current_dir().unwrap_or_default();
+    match slide_common::secrets::Redactor::new(&config.redaction, &cwd) {
+        Ok(redactor) => slide_common::secrets::set_active(redactor),
+        Err(e) => eprintln!("warning: {e:#}; using the built-in secret patterns"),
+    }
+    slide_common::i18n::set_active(slide_common::i18n::Language::detect(config.language));
+    // Flushes spans and counters when the command returns
+    let _telemetry = slide_core::telemetry::init().unwrap_or_else(|e| {
+        eprintln!("warning: telemetry disabled: {e:#}");
+        None
+    });
+
+    if let Some(subcommand) = cli.subcommand {
+        return match subcommand {
+            Subcommand::Commit(cmd) => commit::run(cmd, cli.interactive.model.clone()).await,
+            Subcommand::Completions(cmd) => completions::run_completions(cmd),
+            Subcommand::Doctor(cmd) => doctor::run(cmd, &config).await,
+            Subcommand::Generate(cmd) => {
+                generate::run(cmd, cli.interactive.model.clone(), &config).await
+            }
+            Subcommand::History(cmd) => history::run(cmd, &config).await,
+            Subcommand::Lint(cmd) => lint::run(cmd).await,
+            Subcommand::List => project::run_list(&config),
+            Subcommand::Login(cmd) => login::run_login(cmd).await,
+            Subcommand::Logout => login::run_logout(),
+            Subcommand::Man(cmd) => completions::run_man(cmd),
+            Subcommand::Merge(cmd) => merge::run(cmd).await,
+            Subcommand::Plan(cmd) => plan::run(cmd).await,
+            Subcommand::Preview(cmd) => project::run_preview(cmd, &config).await,
+            Subcommand::Privacy(cmd) => privacy::run(cmd, &config),
+            Subcommand::Regenerate(cmd) => {
+                generate::run_regenerate(cmd, cli.interactive.model.clone(), &config).await
+            }
+            Subcommand::Replay(cmd) => replay::run(cmd).await,
+            Subcommand::Sessions(cmd) => sessions::run(cmd).await,
+            Subcommand::Stats(cmd) => stats::run(cmd, &config).await,
+        };
+    }
+
     println!("Slide CLI v0.0.1");
 
     if is_slide_mode {
         println!("Running in Slide mode");
     }
 
-    // Start a tiny local log viewer HTTP server in background
-    // - serves / to show tail of /tmp/slide.log
+    if config.privacy.web_viewer_enabled() {
+        start_log_viewer();
+    }
+
+    slide_tui::run_main(cli.interactive, slide_linux_sandbox_exe).await?;
+
+    Ok(())
+}
+
+/// Start a tiny local log viewer HTTP server in background and open it in the
+/// browser. Serves / to show the tail of /tmp/slide.log.
+fn start_log_viewer() {
     thread::spawn(|| {
-        let server = match Server::http("127.0.0.1:6060") {
+        let server = match Server::http(LOG_VIEWER_ADDR) {
             Ok(s) => s,
             Err(_) => return, // port in use; skip
         };
@@ -103,81 +191,5 @@ async fn cli_main(
     });
 
     // Open browser to log page (best-effort)
-    let _ = webbrowser::open("http://127.0.0.1:6060/");
-
-    // For now, just run the TUI
-    let tui_cli = TuiCli::default();
-    slide_tui::run_main(tui_cli, slide_linux_sandbox_exe).await?;
-
-    Ok(())
-}
-
-fn try_load_env_local() {
-    if std::env::var("OPENAI_API_KEY").is_ok() {
-        return;
-    }
-    // Search for env.local relative to current working directory and the executable location
-    let mut paths: Vec<std::path::PathBuf> = Vec::new();
-    if let Ok(cwd) = std::env::current_dir() {
-        paths.push(cwd.clone());
-        if let Some(p) = cwd.parent() {
-            paths.push(p.to_path_buf());
-        }
-        if let Some(p) = cwd.parent().and_then(|q| q.parent()) {
-            paths.push(p.to_path_buf());
-        }
-    }
-    if let Ok(exe) = std::env::current_exe() {
-        if let Some(dir) = exe.parent() {
-            paths.push(dir.to_path_buf()); // e.g., target/release
-            if let Some(p) = dir.parent() {
-                paths.push(p.to_path_buf());
-            } // e.g., target
-            if let Some(p) = dir.parent().and_then(|q| q.parent()) {
-                paths.push(p.to_path_buf());
-            } // e.g., slide-rs
-            if let Some(p) = dir
-                .parent()
-                .and_then(|q| q.parent())
-                .and_then(|r| r.parent())
-            {
-                paths.push(p.to_path_buf());
-            } // e.g., repo root
-        }
-    }
-    // Deduplicate while preserving order
-    paths.dedup();
-    let candidates: Vec<std::path::PathBuf> = paths;
-    for base in candidates.into_iter() {
-        for name in ["env.local", ".env.local"] {
-            let path = base.join(name);
-            if path.exists() {
-                if let Ok(content) = std::fs::read_to_string(&path) {
-                    for line in content.lines() {
-                        let line = line.trim();
-                        if line.is_empty() || line.starts_with('#') {
-                            continue;
-                        }
-                        if let Some((k, v)) = parse_env_line(line) {
-                            if std::env::var(&k).is_err() {
-                                std::env::set_var(k, v);
-                            }
-                        }
-                    }
-                }
-                return;
-            }
-        }
-    }
-}
-
-fn parse_env_line(line: &str) -> Option<(String, String)> {
-    let mut parts = line.splitn(2, '=');
-    let key = parts.next()?.trim();
-    let val_raw = parts.next()?.trim();
-    let val = val_raw.trim_matches('"').trim_matches('\'');
-    if key.is_empty() {
-        return None;
-    }
-    Some((key.to_string(), val.to_string()))
+    let _ = webbrowser::open(&format!("http://{LOG_VIEWER_ADDR}/"));
 }
@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+use slide_common::merge::{self, SourceDeck};
+
+#[derive(Debug, Args)]
+pub struct MergeCommand {
+    /// Markdown decks to combine, in order
+    #[arg(required = true)]
+    decks: Vec<PathBuf>,
+
+    /// Where to write the combined deck
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// File of slide titles, one per line, giving the order of the slides
+    #[arg(long, value_name = "FILE")]
+    outline: Option<PathBuf>,
+}
+
+pub async fn run(cmd: MergeCommand) -> anyhow::Result<()> {
+    let mut sources = Vec::new();
+    for path in &cmd.decks {
+        let markdown = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        sources.push(SourceDeck {
+            dir: parent_dir(path)?,
+            markdown,
+        });
+    }
+    let outline = match &cmd.outline {
+        Some(path) => Some(merge::parse_outline(
+            &tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("failed to read {}", path.display()))?,
+        )),
+        None => None,
+    };
+
+    let merged = merge::merge(&sources, &parent_dir(&cmd.output)?, outline.as_deref());
+    tokio::fs::write(&cmd.output, &merged.markdown)
+        .await
+        .with_context(|| format!("failed to write {}", cmd.output.display()))?;
+    println!(
+        "Merged {} decks into {} ({} slides)",
+        cmd.decks.len(),
+        cmd.output.display(),
+        merged.slides
+    );
+    if merged.dropped_titles > 0 {
+        println!(
+            "Kept the first title slide; left out {}",
+            merged.dropped_titles
+        );
+    }
+    for entry in &merged.missing {
+        eprintln!("warning: no slide titled '{entry}' in the outline's decks");
+    }
+    if merged.unplaced > 0 {
+        println!(
+            "{} slides not in the outline were added at the end",
+            merged.unplaced
+        );
+    }
+    Ok(())
+}
+
+/// Absolute directory of `file`, which relative asset paths start from.
+fn parent_dir(file: &Path) -> anyhow::Result<PathBuf> {
+    let file = std::path::absolute(file)
+        .with_context(|| format!("failed to resolve {}", file.display()))?;
+    Ok(file.parent().map(Path::to_path_buf).unwrap_or_default())
+}
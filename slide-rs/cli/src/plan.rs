@@ -0,0 +1,51 @@
+use anyhow::anyhow;
+use clap::Args;
+use slide_core::codex::StepStatus;
+use slide_core::plan_tool::PlanStore;
+
+#[derive(Debug, Args)]
+pub struct PlanCommand {
+    #[clap(subcommand)]
+    action: PlanAction,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum PlanAction {
+    /// List the plans saved in .slide/plans, most recent first
+    List,
+
+    /// Continue a saved plan from its first step that is not completed
+    Resume {
+        /// Plan id from `slide plan list`
+        id: String,
+    },
+}
+
+pub async fn run(cmd: PlanCommand) -> anyhow::Result<()> {
+    let store = PlanStore::default();
+    match cmd.action {
+        PlanAction::List => {
+            let plans = store.list();
+            if plans.is_empty() {
+                println!("No plans in {}", store.dir().display());
+            }
+            for plan in plans {
+                let done = plan
+                    .steps
+                    .iter()
+                    .filter(|s| s.status == StepStatus::Completed)
+                    .count();
+                let title = plan.task.lines().next().unwrap_or("");
+                println!("{}  {done}/{} done  {title}", plan.id, plan.steps.len());
+            }
+            Ok(())
+        }
+        PlanAction::Resume { id } => {
+            let record = store.load(&id)?;
+            if record.is_finished() {
+                return Err(anyhow!("plan {id} is already finished"));
+            }
+            slide_tui::run_plan_resume(record).await
+        }
+    }
+}
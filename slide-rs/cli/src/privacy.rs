@@ -0,0 +1,49 @@
+use clap::Args;
+use slide_common::privacy::{DataFlow, DataFlowRegistry, DataSink};
+use slide_common::SlideConfig;
+
+use crate::LOG_VIEWER_ADDR;
+
+#[derive(Debug, Args)]
+pub struct PrivacyCommand {
+    #[clap(subcommand)]
+    action: PrivacyAction,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum PrivacyAction {
+    /// List what data the current configuration writes, and where
+    Report,
+}
+
+pub fn run(cmd: PrivacyCommand, config: &SlideConfig) -> anyhow::Result<()> {
+    match cmd.action {
+        PrivacyAction::Report => {
+            let registry = data_flow_registry(config);
+            if config.privacy.enabled {
+                println!("Privacy mode: on");
+            } else {
+                println!("Privacy mode: off");
+            }
+            if let Ok(path) = SlideConfig::config_path() {
+                println!("Config: {}\n", path.display());
+            }
+            print!("{}", registry.render_report());
+            Ok(())
+        }
+    }
+}
+
+/// Collect the data flows of every subsystem linked into the binary.
+fn data_flow_registry(config: &SlideConfig) -> DataFlowRegistry {
+    let mut registry = DataFlowRegistry::new();
+    registry.register(DataFlow {
+        subsystem: "cli.log-viewer",
+        description: "Serves the session log over local HTTP and opens it in the browser",
+        sink: DataSink::Network(format!("http://{LOG_VIEWER_ADDR}/")),
+        active: config.privacy.web_viewer_enabled(),
+    });
+    slide_core::codex::register_data_flows(&mut registry, config);
+    slide_tui::register_data_flows(&mut registry, config);
+    registry
+}
@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+use clap::Args;
+use slide_common::{Project, SlideConfig};
+
+#[derive(Debug, Args)]
+pub struct PreviewCommand {
+    /// Deck name from slide.toml, or a path to a markdown file
+    deck: String,
+    /// Open in presenter mode: next slide, speaker notes and timers
+    #[arg(long)]
+    presenter: bool,
+    /// Record a rehearsal; the time spent on each slide is saved next to
+    /// the deck when the preview closes
+    #[arg(long)]
+    rehearse: bool,
+    /// Advance slides after the times of the last rehearsal
+    #[arg(long)]
+    auto_advance: bool,
+}
+
+/// `slide list`: decks of the project in the output directory.
+pub fn run_list(config: &SlideConfig) -> anyhow::Result<()> {
+    let project = Project::open(&config.output_dir)?;
+    if !project.has_manifest {
+        println!(
+            "No {} in {}; listing markdown files",
+            slide_common::project::MANIFEST_FILE,
+            project.root.display()
+        );
+    } else if let Some(name) = &project.manifest.project.name {
+        println!("{name}");
+    }
+    if project.manifest.decks.is_empty() {
+        println!("No decks found");
+        return Ok(());
+    }
+
+    let width = project
+        .manifest
+        .decks
+        .iter()
+        .map(|d| d.name.len())
+        .max()
+        .unwrap_or(0);
+    for deck in &project.manifest.decks {
+        let mut line = format!(
+            "{:<width$}  {}",
+            deck.name,
+            project.deck_path(deck).display()
+        );
+        if let Some(title) = &deck.title {
+            line.push_str(&format!("  \"{title}\""));
+        }
+        if let Some(theme) = project.theme_for(deck) {
+            line.push_str(&format!("  [{}]", theme.name));
+        }
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// `slide preview <deck>`: open a deck in the slide preview.
+pub async fn run_preview(cmd: PreviewCommand, config: &SlideConfig) -> anyhow::Result<()> {
+    let project = Project::open(&config.output_dir)?;
+    let path = project
+        .resolve(&cmd.deck)
+        .ok_or_else(|| anyhow!("unknown deck: {} (see `slide list`)", cmd.deck))?;
+    let options = slide_tui::PreviewOptions {
+        presenter: cmd.presenter,
+        rehearse: cmd.rehearse,
+        auto_advance: cmd.auto_advance,
+    };
+    if let Some(timings) = slide_tui::run_preview_with(path, options).await? {
+        println!("Rehearsal timings saved to {}", timings.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::{Args, ValueEnum};
+use slide_core::event_log::{self, EventLog, ExportFormat};
+
+#[derive(Debug, Args)]
+pub struct ReplayCommand {
+    /// Session id or path to a recorded .jsonl file (default: the latest session)
+    session: Option<String>,
+
+    /// Re-render in the TUI, or export the transcript
+    #[clap(long, value_enum, default_value_t = ReplayFormat::Tui)]
+    format: ReplayFormat,
+
+    /// Write the export here instead of stdout
+    #[clap(long, short, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReplayFormat {
+    Tui,
+    Markdown,
+    Html,
+}
+
+pub async fn run(cmd: ReplayCommand) -> anyhow::Result<()> {
+    let path = session_path(&EventLog::default(), cmd.session)?;
+    let entries = event_log::load(&path)?;
+    let title = session_title(&path);
+
+    let format = match cmd.format {
+        ReplayFormat::Tui => return slide_tui::run_replay(entries, title).await,
+        ReplayFormat::Markdown => ExportFormat::Markdown,
+        ReplayFormat::Html => ExportFormat::Html,
+    };
+    write_export(&format.render(&title, &entries), cmd.output).await
+}
+
+/// The log of a session given by id or path; the latest session by default.
+pub(crate) fn session_path(log: &EventLog, session: Option<String>) -> anyhow::Result<PathBuf> {
+    Ok(match session {
+        Some(session) if Path::new(&session).is_file() => PathBuf::from(session),
+        Some(id) => {
+            let path = log.session_path(&id);
+            if !path.is_file() {
+                return Err(anyhow!(
+                    "no recorded session {id} in {}",
+                    log.dir().display()
+                ));
+            }
+            path
+        }
+        None => {
+            let id = log
+                .latest_session()
+                .ok_or_else(|| anyhow!("no recorded sessions in {}", log.dir().display()))?;
+            log.session_path(&id)
+        }
+    })
+}
+
+/// "Session <id>", from the log's file name.
+pub(crate) fn session_title(path: &Path) -> String {
+    let id = path.file_stem().map_or_else(
+        || path.display().to_string(),
+        |s| s.to_string_lossy().into_owned(),
+    );
+    format!("Session {id}")
+}
+
+/// Write an export to `output`, or to stdout.
+pub(crate) async fn write_export(rendered: &str, output: Option<PathBuf>) -> anyhow::Result<()> {
+    match output {
+        Some(output) => {
+            tokio::fs::write(&output, rendered).await?;
+            println!("Wrote {}", output.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use slide_core::event_log::{self, EventLog, ExportFormat, SessionEntry};
+
+use crate::replay::{session_path, session_title, write_export};
+
+#[derive(Debug, Args)]
+pub struct SessionsCommand {
+    #[clap(subcommand)]
+    action: SessionsAction,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum SessionsAction {
+    /// List the recorded sessions, most recent first
+    List,
+
+    /// Render a session (messages, commands, diffs, approvals) as a report
+    Export {
+        /// Session id from `slide sessions list`, or path to a recorded .jsonl file
+        id: String,
+
+        /// Report format (default: from the output's extension, else markdown)
+        #[clap(long, value_enum)]
+        format: Option<SessionFormat>,
+
+        /// Write the report here instead of stdout
+        #[clap(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SessionFormat {
+    Markdown,
+    Html,
+}
+
+pub async fn run(cmd: SessionsCommand) -> anyhow::Result<()> {
+    let log = EventLog::default();
+    match cmd.action {
+        SessionsAction::List => {
+            let sessions = log.sessions();
+            if sessions.is_empty() {
+                println!("No recorded sessions in {}", log.dir().display());
+            }
+            for id in sessions {
+                let entries = event_log::load(&log.session_path(&id)).unwrap_or_default();
+                let first = entries.iter().find_map(|recorded| match &recorded.entry {
+                    SessionEntry::User { text } => text.lines().next(),
+                    _ => None,
+                });
+                println!("{id}  {}", first.unwrap_or(""));
+            }
+            Ok(())
+        }
+        SessionsAction::Export { id, format, output } => {
+            let path = session_path(&log, Some(id))?;
+            let format = match format {
+                Some(SessionFormat::Markdown) => ExportFormat::Markdown,
+                Some(SessionFormat::Html) => ExportFormat::Html,
+                None => output
+                    .as_deref()
+                    .and_then(ExportFormat::from_path)
+                    .unwrap_or(ExportFormat::Markdown),
+            };
+            let entries = event_log::load(&path)?;
+            let rendered = format.render(&session_title(&path), &entries);
+            write_export(&rendered, output).await
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use slide_common::{Deck, DeckStats, SlideConfig, TimingReport};
+
+#[derive(Debug, Args)]
+pub struct StatsCommand {
+    /// Markdown deck to analyse
+    file: PathBuf,
+
+    /// Speaking rate in words per minute (default from config)
+    #[clap(long)]
+    wpm: Option<u32>,
+
+    /// Flag slides whose estimated time exceeds this many seconds
+    #[clap(long, value_name = "SECS")]
+    budget: Option<u32>,
+}
+
+pub async fn run(cmd: StatsCommand, config: &SlideConfig) -> anyhow::Result<()> {
+    let content = tokio::fs::read_to_string(&cmd.file)
+        .await
+        .with_context(|| format!("failed to read {}", cmd.file.display()))?;
+    let mut presentation = config.presentation.clone();
+    if let Some(wpm) = cmd.wpm {
+        presentation.words_per_minute = wpm;
+    }
+    if cmd.budget.is_some() {
+        presentation.slide_budget_secs = cmd.budget;
+    }
+
+    let mut stats = DeckStats::compute(&Deck::parse(&content), &presentation);
+    // Recorded by a rehearsal in the preview
+    if let Some(report) = TimingReport::load(&cmd.file) {
+        stats = stats.with_rehearsal(&report);
+    }
+    println!("{}", cmd.file.display());
+    for line in stats.render_lines() {
+        println!("{line}");
+    }
+    Ok(())
+}
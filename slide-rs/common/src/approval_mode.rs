@@ -1,53 +1,137 @@
-use serde::{Deserialize, Serialize};
+//! Approval policy of a session: when the user is asked before the agent
+//! runs a command. The one type behind `--approval`, `/approvals`, the
+//! `approval_mode` config key and [`APPROVAL_MODE_ENV`].
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+/// Environment variable the policy picked on the command line reaches the
+/// agent through; read when the session starts
+pub const APPROVAL_MODE_ENV: &str = "SLIDE_APPROVAL_MODE";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum ApprovalMode {
-    /// Always prompt for approval
-    Suggest,
-    /// Auto-approve edits, prompt for new files
-    AutoEdit,
-    /// Auto-approve everything
-    FullAuto,
+    /// Ask for approval unless the command is explicitly trusted
+    UnlessTrusted,
+    /// Ask for approval only when a command fails and needs escalation
+    OnFailure,
+    /// Ask for approval on every request that requires elevated permissions
+    #[default]
+    OnRequest,
+    /// Never ask for approval (auto-approve everything)
+    Never,
 }
 
-impl Default for ApprovalMode {
-    fn default() -> Self {
-        ApprovalMode::Suggest
+impl ApprovalMode {
+    pub const ALL: [ApprovalMode; 4] = [
+        ApprovalMode::UnlessTrusted,
+        ApprovalMode::OnFailure,
+        ApprovalMode::OnRequest,
+        ApprovalMode::Never,
+    ];
+
+    /// Names of [`ApprovalMode::ALL`], as written on the command line and in
+    /// the config
+    pub const NAMES: &'static [&'static str] = &["untrusted", "on-failure", "on-request", "never"];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApprovalMode::UnlessTrusted => "untrusted",
+            ApprovalMode::OnFailure => "on-failure",
+            ApprovalMode::OnRequest => "on-request",
+            ApprovalMode::Never => "never",
+        }
+    }
+
+    /// Policy set in [`APPROVAL_MODE_ENV`]; `None` when unset or invalid.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(APPROVAL_MODE_ENV).ok()?.parse().ok()
     }
 }
 
-impl ApprovalMode {
-    pub fn from_str(s: &str) -> Result<Self, &'static str> {
-        match s.to_lowercase().as_str() {
-            "suggest" => Ok(ApprovalMode::Suggest),
-            "auto-edit" => Ok(ApprovalMode::AutoEdit),
-            "full-auto" => Ok(ApprovalMode::FullAuto),
-            _ => Err("Invalid approval mode. Use: suggest, auto-edit, full-auto"),
-        }
+impl fmt::Display for ApprovalMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
+}
 
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            ApprovalMode::Suggest => "suggest",
-            ApprovalMode::AutoEdit => "auto-edit",
-            ApprovalMode::FullAuto => "full-auto",
-        }
+/// A string that names no [`ApprovalMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseApprovalModeError(String);
+
+impl fmt::Display for ParseApprovalModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid approval mode `{}`; expected one of: {}",
+            self.0,
+            ApprovalMode::NAMES.join(", ")
+        )
     }
+}
 
-    pub fn should_prompt_for_new_file(&self) -> bool {
-        match self {
-            ApprovalMode::Suggest => true,
-            ApprovalMode::AutoEdit => true,
-            ApprovalMode::FullAuto => false,
+impl std::error::Error for ParseApprovalModeError {}
+
+impl FromStr for ApprovalMode {
+    type Err = ParseApprovalModeError;
+
+    /// Case-insensitive. Also takes `unless-trusted`, and the modes of
+    /// earlier versions still found in config files: `suggest`, `auto-edit`
+    /// and `full-auto`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "untrusted" | "unless-trusted" | "suggest" => Ok(ApprovalMode::UnlessTrusted),
+            "on-failure" | "full-auto" => Ok(ApprovalMode::OnFailure),
+            "on-request" | "auto-edit" => Ok(ApprovalMode::OnRequest),
+            "never" => Ok(ApprovalMode::Never),
+            _ => Err(ParseApprovalModeError(s.to_string())),
         }
     }
+}
 
-    pub fn should_prompt_for_edit(&self) -> bool {
-        match self {
-            ApprovalMode::Suggest => true,
-            ApprovalMode::AutoEdit => false,
-            ApprovalMode::FullAuto => false,
+impl Serialize for ApprovalMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ApprovalMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_round_trip_and_errors_list_the_names() {
+        for (mode, name) in ApprovalMode::ALL.iter().zip(ApprovalMode::NAMES) {
+            assert_eq!(mode.to_string(), *name);
+            assert_eq!(name.parse(), Ok(*mode));
+            let json = serde_json::to_string(mode).unwrap_or_default();
+            assert_eq!(json, format!("\"{name}\""));
+            assert_eq!(
+                serde_json::from_str::<ApprovalMode>(&json).ok(),
+                Some(*mode)
+            );
         }
+        assert_eq!("Never".parse(), Ok(ApprovalMode::Never));
+        assert_eq!("suggest".parse(), Ok(ApprovalMode::UnlessTrusted));
+
+        let error = "sometimes".parse::<ApprovalMode>().err();
+        assert_eq!(
+            error.map(|e| e.to_string()).as_deref(),
+            Some(
+                "invalid approval mode `sometimes`; expected one of: untrusted, on-failure, on-request, never"
+            )
+        );
+        let error = serde_json::from_str::<ApprovalMode>("\"always\"")
+            .err()
+            .map(|e| e.to_string())
+            .unwrap_or_default();
+        assert!(error.contains("expected one of: untrusted"), "{error}");
     }
 }
@@ -0,0 +1,293 @@
+//! Credentials saved by `slide login`: in the OS keyring (macOS Keychain,
+//! Secret Service, Windows Credential Manager) when one is available,
+//! otherwise in `~/.slide/auth.json`.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Providers `slide login` can store a key for, as (id, display name).
+pub const LOGIN_PROVIDERS: &[(&str, &str)] = &[("openai", "OpenAI")];
+
+/// Environment variable that takes precedence over the stored OpenAI key.
+pub const OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Set to `file` to keep credentials out of the OS keyring.
+pub const CREDENTIAL_STORE_ENV: &str = "SLIDE_CREDENTIAL_STORE";
+
+/// Keyring service the credentials are filed under.
+const KEYRING_SERVICE: &str = "slide";
+const KEYRING_ACCOUNT: &str = "auth";
+
+/// Stored credentials; `auth.json` holds the same JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthDotJson {
+    /// Provider id from [`LOGIN_PROVIDERS`]
+    pub provider: String,
+    pub api_key: String,
+}
+
+/// A place credentials can be kept.
+pub trait CredentialStore {
+    /// Human-readable location, for messages
+    fn location(&self) -> String;
+
+    /// `Ok(None)` when nothing is stored.
+    fn load(&self) -> Result<Option<AuthDotJson>>;
+
+    fn save(&self, auth: &AuthDotJson) -> Result<()>;
+
+    /// Delete the credentials. Returns whether there were any.
+    fn remove(&self) -> Result<bool>;
+}
+
+/// Plaintext JSON file readable by the owner only.
+#[derive(Debug, Clone)]
+pub struct FileCredentialStore {
+    path: PathBuf,
+}
+
+impl FileCredentialStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CredentialStore for FileCredentialStore {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<Option<AuthDotJson>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to read {}", self.path.display()))
+            }
+        };
+        let auth = serde_json::from_str(&content)
+            .with_context(|| format!("invalid credentials in {}", self.path.display()))?;
+        Ok(Some(auth))
+    }
+
+    fn save(&self, auth: &AuthDotJson) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(auth)?;
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            opts.mode(0o600);
+        }
+        let mut file = opts
+            .open(&self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        // An existing file keeps its mode on open; tighten it as well
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    fn remove(&self) -> Result<bool> {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("failed to remove {}", self.path.display())),
+        }
+    }
+}
+
+/// Entry in the platform's credential manager.
+#[derive(Debug, Clone)]
+pub struct KeyringCredentialStore {
+    service: String,
+    account: String,
+}
+
+impl Default for KeyringCredentialStore {
+    fn default() -> Self {
+        Self::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
+    }
+}
+
+impl KeyringCredentialStore {
+    pub fn new(service: &str, account: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            account: account.to_string(),
+        }
+    }
+
+    fn entry(&self) -> Result<keyring::Entry> {
+        keyring::Entry::new(&self.service, &self.account).context("OS keyring unavailable")
+    }
+}
+
+impl CredentialStore for KeyringCredentialStore {
+    fn location(&self) -> String {
+        format!("the OS keyring ({}/{})", self.service, self.account)
+    }
+
+    fn load(&self) -> Result<Option<AuthDotJson>> {
+        match self.entry()?.get_password() {
+            Ok(json) => Ok(Some(
+                serde_json::from_str(&json).context("invalid credentials in the OS keyring")?,
+            )),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("failed to read the OS keyring"),
+        }
+    }
+
+    fn save(&self, auth: &AuthDotJson) -> Result<()> {
+        let json = serde_json::to_string(auth)?;
+        self.entry()?
+            .set_password(&json)
+            .context("failed to write the OS keyring")
+    }
+
+    fn remove(&self) -> Result<bool> {
+        match self.entry()?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(e).context("failed to remove from the OS keyring"),
+        }
+    }
+}
+
+/// The keyring when there is one, with `auth.json` as the fallback.
+#[derive(Debug, Clone)]
+pub struct AuthStore {
+    keyring: Option<KeyringCredentialStore>,
+    file: FileCredentialStore,
+}
+
+impl Default for AuthStore {
+    fn default() -> Self {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let file_only = std::env::var(CREDENTIAL_STORE_ENV).is_ok_and(|v| v == "file");
+        // Other platforms get keyring's in-memory mock, which would lose the key
+        let has_keyring = cfg!(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "windows"
+        ));
+        Self {
+            keyring: (has_keyring && !file_only).then(KeyringCredentialStore::default),
+            file: FileCredentialStore::new(home.join(".slide").join("auth.json")),
+        }
+    }
+}
+
+impl AuthStore {
+    /// Store that only uses the file at `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            keyring: None,
+            file: FileCredentialStore::new(path),
+        }
+    }
+
+    /// Path of the fallback file.
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Stored credentials; `None` when logged out or nothing is readable.
+    pub fn load(&self) -> Option<AuthDotJson> {
+        if let Some(auth) = self.keyring.as_ref().and_then(|k| k.load().ok().flatten()) {
+            return Some(auth);
+        }
+        self.file.load().ok().flatten()
+    }
+
+    /// Save the credentials in the keyring, or in the file when the keyring
+    /// fails. Returns where they went.
+    pub fn save(&self, auth: &AuthDotJson) -> Result<String> {
+        if let Some(keyring) = &self.keyring {
+            if keyring.save(auth).is_ok() {
+                // Drop a plaintext copy left by an earlier login
+                self.file.remove()?;
+                return Ok(keyring.location());
+            }
+        }
+        self.file.save(auth)?;
+        Ok(self.file.location())
+    }
+
+    /// Delete the credentials from every store. Returns whether there were any.
+    pub fn remove(&self) -> Result<bool> {
+        let from_keyring = match &self.keyring {
+            Some(keyring) => keyring.remove().unwrap_or(false),
+            None => false,
+        };
+        let from_file = self.file.remove()?;
+        Ok(from_keyring || from_file)
+    }
+
+    /// Key for `provider`: the provider's environment variable, else the stored one.
+    pub fn api_key(&self, provider: &str) -> Option<String> {
+        if provider == "openai" {
+            if let Some(key) = std::env::var(OPENAI_API_KEY_ENV)
+                .ok()
+                .filter(|k| !k.is_empty())
+            {
+                return Some(key);
+            }
+        }
+        self.load()
+            .filter(|auth| auth.provider == provider)
+            .map(|auth| auth.api_key)
+    }
+}
+
+/// OpenAI API key from the environment or `slide login`.
+pub fn openai_api_key() -> Option<String> {
+    AuthStore::default().api_key("openai")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_round_trip_with_private_permissions() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-auth-{}", std::process::id()));
+        let store = AuthStore::new(dir.join("auth.json"));
+        assert_eq!(store.load(), None);
+        assert!(!store.remove()?);
+
+        let auth = AuthDotJson {
+            provider: "openai".to_string(),
+            api_key: "sk-test".to_string(),
+        };
+        assert_eq!(store.save(&auth)?, store.path().display().to_string());
+        assert_eq!(store.load(), Some(auth));
+        assert_eq!(store.api_key("anthropic"), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(store.path())?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(store.remove()?);
+        assert_eq!(store.load(), None);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
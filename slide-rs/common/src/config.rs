@@ -2,13 +2,251 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::approval_mode::ApprovalMode;
+use crate::deck::PresentationConfig;
+use crate::i18n::Language;
+use crate::moderation::ModerationConfig;
+use crate::privacy::PrivacyConfig;
+use crate::secrets::RedactionConfig;
+
+/// Environment variable naming the config profile in use
+pub const PROFILE_ENV: &str = "SLIDE_PROFILE";
+
 /// Slide configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlideConfig {
     pub api_key: Option<String>,
     pub model: String,
-    pub approval_mode: String,
+    pub approval_mode: ApprovalMode,
     pub output_dir: PathBuf,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub presentation: PresentationConfig,
+    #[serde(default)]
+    pub editor: EditorConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub shell: ShellConfig,
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+    #[serde(default)]
+    pub guardrails: GuardrailConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub transcript: TranscriptConfig,
+    #[serde(default)]
+    pub moderation: ModerationConfig,
+    /// UI language (`en`, `ja`); follows the locale when unset
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+/// `editor` section: behaviour of the TUI composer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Where killed text (Ctrl-K/Ctrl-U/Ctrl-W) is copied
+    pub clipboard: ClipboardMode,
+    /// Key bindings of the composer
+    pub keymap: Keymap,
+    /// Whether the TUI captures the mouse
+    pub mouse: MouseMode,
+    /// Ghost-text completion of the message being typed
+    pub suggestions: SuggestionConfig,
+}
+
+/// `editor.suggestions` section: a small model proposes the rest of the
+/// message, shown dimmed after the cursor and accepted with Tab.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuggestionConfig {
+    /// Off by default: every pause in typing sends the text to the model
+    pub enabled: bool,
+    /// Model asked for suggestions; a fast, cheap one keeps up with typing
+    pub model: String,
+    /// Typing pause before a suggestion is requested
+    pub debounce_ms: u64,
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: "gpt-4o-mini".to_string(),
+            debounce_ms: 400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Keymap {
+    /// Readline/Emacs-style editing
+    #[default]
+    Default,
+    /// Modal editing (normal/insert/visual)
+    Vim,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseMode {
+    /// Clicks and the wheel go to the TUI (popups, approval diffs, transcript)
+    #[default]
+    Capture,
+    /// Leave the mouse to the terminal for native text selection and scrollback
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMode {
+    /// Copy through the terminal with the OSC 52 escape sequence (works over SSH)
+    #[default]
+    Osc52,
+    /// Copy to and yank from the system clipboard
+    System,
+    /// Keep killed text in the kill ring only
+    Off,
+}
+
+/// `notifications` section: alerts when the agent needs attention while the
+/// terminal is in the background.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub method: NotificationMethod,
+    /// Completed tasks notify only when they ran at least this long
+    pub min_task_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            method: NotificationMethod::default(),
+            min_task_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationMethod {
+    /// Bell plus an OSC 9 desktop notification (iTerm2, WezTerm, Windows Terminal)
+    #[default]
+    Osc9,
+    /// Bell plus an OSC 777 desktop notification (rxvt, foot, Ghostty)
+    Osc777,
+    /// Terminal bell only
+    Bell,
+    Off,
+}
+
+/// `sandbox` section: where the agent's file tools may write.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Directories writable in addition to the workspace and the temp dirs;
+    /// relative paths are taken from the working directory
+    pub writable_roots: Vec<PathBuf>,
+}
+
+/// `shell` section: how the agent's commands are started.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    /// Run commands through the user's shell as a login shell (`zsh -lc`,
+    /// `bash -lc`, ...) so tools set up by rc files (nvm, pyenv, cargo env)
+    /// are on PATH
+    pub login_shell: bool,
+}
+
+/// `approvals` section: what happens to approval requests nobody answers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// Seconds to wait for an answer; unset waits forever
+    pub timeout_secs: Option<u64>,
+    /// What an unanswered request turns into
+    pub on_timeout: ApprovalTimeoutAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalTimeoutAction {
+    /// Deny the request; the agent is told and the turn goes on
+    #[default]
+    Deny,
+    /// Deny the request and stop the turn
+    AbortTurn,
+}
+
+/// `guardrails` section: limits on what the agent's tools may do in one
+/// turn. The turn stops when one is reached; `0` removes a limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardrailConfig {
+    /// Tool calls per turn
+    pub max_tool_calls: u32,
+    /// Seconds commands and exec sessions may run in total per turn
+    pub max_exec_secs: u64,
+    /// Bytes the file tools may write per turn
+    pub max_bytes_written: u64,
+}
+
+impl Default for GuardrailConfig {
+    fn default() -> Self {
+        Self {
+            max_tool_calls: 100,
+            max_exec_secs: 900,
+            max_bytes_written: 10 * 1024 * 1024,
+        }
+    }
+}
+
+/// `retry` section: how a turn's model request is retried when the
+/// provider fails in a way that may pass, such as a dropped stream or a
+/// 5xx or 429 answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries after the first attempt; `0` ends the turn on the first
+    /// failure
+    pub max_retries: u32,
+    /// Wait before the first retry; doubled for each one after it
+    pub initial_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay_ms: 1000,
+        }
+    }
+}
+
+/// `transcript` section: how much of the session's scrollback the TUI keeps
+/// in memory for the transcript pager.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptConfig {
+    /// Lines kept in memory; older ones are moved to a file next to the
+    /// session and read back when the pager is scrolled up to them
+    pub max_lines: usize,
+}
+
+impl Default for TranscriptConfig {
+    fn default() -> Self {
+        Self { max_lines: 5_000 }
+    }
 }
 
 impl Default for SlideConfig {
@@ -16,21 +254,38 @@ impl Default for SlideConfig {
         Self {
             api_key: None,
             model: "gpt-5".to_string(),
-            approval_mode: "suggest".to_string(),
+            approval_mode: ApprovalMode::default(),
             output_dir: PathBuf::from("slides"),
+            privacy: PrivacyConfig::default(),
+            presentation: PresentationConfig::default(),
+            editor: EditorConfig::default(),
+            notifications: NotificationConfig::default(),
+            sandbox: SandboxConfig::default(),
+            shell: ShellConfig::default(),
+            approvals: ApprovalConfig::default(),
+            guardrails: GuardrailConfig::default(),
+            retry: RetryConfig::default(),
+            redaction: RedactionConfig::default(),
+            transcript: TranscriptConfig::default(),
+            moderation: ModerationConfig::default(),
+            language: None,
         }
     }
 }
 
 impl SlideConfig {
-    /// Get config file path
+    /// Get config file path: `profiles/<name>.json` when a profile is
+    /// selected with `--profile` (`SLIDE_PROFILE`), else `config.json`
     pub fn config_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?
             .join("slide");
 
         std::fs::create_dir_all(&config_dir)?;
-        Ok(config_dir.join("config.json"))
+        match std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty()) {
+            Some(profile) => Ok(config_dir.join("profiles").join(format!("{profile}.json"))),
+            None => Ok(config_dir.join("config.json")),
+        }
     }
 
     /// Load configuration from file
@@ -49,6 +304,9 @@ impl SlideConfig {
     /// Save configuration to file
     pub async fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
         let content = serde_json::to_string_pretty(self)?;
         tokio::fs::write(&path, content).await?;
         Ok(())
@@ -0,0 +1,616 @@
+use serde::{Deserialize, Serialize};
+
+use crate::frontmatter::{self, DeckSettings};
+use crate::rehearsal::TimingReport;
+
+/// Characters of CJK text that count as one spoken word.
+const CJK_CHARS_PER_WORD: usize = 2;
+
+/// Marp directives, written as HTML comments but not speaker notes
+pub(crate) const DIRECTIVES: &[&str] = &[
+    "marp",
+    "theme",
+    "paginate",
+    "header",
+    "footer",
+    "class",
+    "size",
+    "style",
+    "color",
+    "backgroundColor",
+    "backgroundImage",
+    "headingDivider",
+];
+
+/// A single slide of a markdown deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slide {
+    /// Text of the `## ` (or deck `# `) heading of the slide, if any
+    pub title: Option<String>,
+    /// Raw markdown of the slide, heading included
+    pub content: String,
+}
+
+impl Slide {
+    /// Number of words a presenter would read out: headings and bullets
+    /// count, code blocks, images and HTML comments do not.
+    pub fn spoken_words(&self) -> usize {
+        let mut words = 0;
+        let mut in_code = false;
+        let mut in_comment = false;
+        for line in self.content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                in_code = !in_code;
+                continue;
+            }
+            if in_code {
+                continue;
+            }
+            if in_comment || trimmed.starts_with("<!--") {
+                in_comment = !trimmed.ends_with("-->");
+                continue;
+            }
+            if trimmed.starts_with("![") {
+                continue;
+            }
+            let text = trimmed
+                .trim_start_matches('#')
+                .trim_start_matches(['-', '*', '+', '>'])
+                .trim_start();
+            words += count_words(text);
+        }
+        words
+    }
+
+    /// Speaker notes: the HTML comments of the slide (`<!-- … -->`), as Marp
+    /// reads them, one paragraph each. Directives such as `<!-- _class: lead -->`
+    /// are left out.
+    pub fn notes(&self) -> Option<String> {
+        let (_, notes) = split_notes(&self.content);
+        (!notes.is_empty()).then(|| notes.join("\n\n"))
+    }
+
+    /// Markdown of the slide as shown to the audience: without its comments.
+    pub fn visible_content(&self) -> String {
+        split_notes(&self.content).0
+    }
+}
+
+/// Content of `markdown` outside HTML comments, and the comments that are
+/// notes. Comments inside code blocks are content.
+fn split_notes(markdown: &str) -> (String, Vec<String>) {
+    let mut visible = String::new();
+    let mut notes = Vec::new();
+    let mut note: Option<String> = None;
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let fence = note.is_none() && line.trim_start().starts_with("```");
+        if fence {
+            in_code = !in_code;
+        }
+        if fence || in_code {
+            visible.push_str(line);
+            visible.push('\n');
+            continue;
+        }
+        let mut shown = String::new();
+        let mut rest = line;
+        loop {
+            match note.as_mut() {
+                Some(text) => match rest.find("-->") {
+                    Some(end) => {
+                        text.push_str(&rest[..end]);
+                        let text = text.trim().to_string();
+                        if !text.is_empty() && !is_directive(&text) {
+                            notes.push(text);
+                        }
+                        note = None;
+                        rest = &rest[end + 3..];
+                    }
+                    None => {
+                        text.push_str(rest);
+                        text.push('\n');
+                        break;
+                    }
+                },
+                None => match rest.find("<!--") {
+                    Some(start) => {
+                        shown.push_str(&rest[..start]);
+                        note = Some(String::new());
+                        rest = &rest[start + 4..];
+                    }
+                    None => {
+                        shown.push_str(rest);
+                        break;
+                    }
+                },
+            }
+        }
+        // A line that held only a comment is dropped
+        if line.trim().is_empty() || !shown.trim().is_empty() {
+            visible.push_str(shown.trim_end());
+            visible.push('\n');
+        }
+    }
+    (visible.trim_end().to_string(), notes)
+}
+
+/// `name: value` comments that configure Marp rather than note anything.
+fn is_directive(comment: &str) -> bool {
+    comment.lines().all(|line| {
+        line.split_once(':').is_some_and(|(name, _)| {
+            let name = name.trim();
+            DIRECTIVES.contains(&name.trim_start_matches('_')) && !name.contains(' ')
+        })
+    })
+}
+
+/// A markdown deck split into slides at `## ` headings.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Deck {
+    pub slides: Vec<Slide>,
+    /// From the frontmatter, which is not part of any slide
+    pub settings: DeckSettings,
+}
+
+impl Deck {
+    pub fn parse(markdown: &str) -> Self {
+        let (settings, _) = DeckSettings::parse(markdown);
+        let markdown = frontmatter::split(markdown).1;
+        let mut slides = Vec::new();
+        let mut current = String::new();
+
+        for line in markdown.lines() {
+            if line.starts_with("## ") && !current.is_empty() {
+                slides.push(Slide::from_markdown(current.trim()));
+                current = String::new();
+            }
+            current.push_str(line);
+            current.push('\n');
+        }
+
+        if !current.trim().is_empty() {
+            slides.push(Slide::from_markdown(current.trim()));
+        }
+
+        if slides.is_empty() {
+            slides.push(Slide::from_markdown(markdown));
+        }
+
+        Self { slides, settings }
+    }
+
+    /// `markdown` with its slides, as [`Deck::parse`] splits them, put in
+    /// `order` (indices of the current slides). The text of each slide is
+    /// kept as written; slides are separated by one blank line. A slide
+    /// without a `## ` heading only stays a slide of its own first; the
+    /// frontmatter stays on top.
+    pub fn reorder(markdown: &str, order: &[usize]) -> String {
+        let parts: Vec<DeckPart> = order.iter().copied().map(DeckPart::Slide).collect();
+        Self::assemble(markdown, &parts)
+    }
+
+    /// `markdown` rebuilt from `parts`: slides of it, kept as written, and
+    /// new slide text, separated by one blank line. The frontmatter stays on
+    /// top; see [`Deck::reorder`].
+    pub fn assemble(markdown: &str, parts: &[DeckPart]) -> String {
+        let body = frontmatter::split(markdown).1;
+        let segments = slide_segments(body);
+        let mut out = String::new();
+        for part in parts {
+            let text = match part {
+                DeckPart::Slide(index) => match segments.get(*index) {
+                    Some(segment) => segment.trim_start_matches(['\n', '\r']),
+                    None => continue,
+                },
+                DeckPart::Text(text) => text.as_str(),
+            };
+            if !out.is_empty() {
+                out.push_str("\n\n");
+            }
+            out.push_str(text.trim_end());
+        }
+        if markdown.is_empty() || markdown.ends_with('\n') {
+            out.push('\n');
+        }
+        format!("{}{out}", &markdown[..markdown.len() - body.len()])
+    }
+
+    /// Slides an outline has entries for: all but a leading title slide.
+    pub fn outline_slides(&self) -> std::ops::Range<usize> {
+        let first = usize::from(self.slides.first().is_some_and(Slide::is_title));
+        first..self.slides.len()
+    }
+
+    /// Slide written for outline entry `position` titled `title`: the slide
+    /// at that place among [`Deck::outline_slides`] while its title
+    /// matches; after slides were added or moved, the first one with that
+    /// title.
+    pub fn slide_for_entry(&self, position: usize, title: &str) -> Option<usize> {
+        let range = self.outline_slides();
+        let titled = |index: &usize| self.slides[*index].title.as_deref() == Some(title);
+        Some(range.start + position)
+            .filter(|index| range.contains(index))
+            .filter(titled)
+            .or_else(|| range.clone().find(titled))
+    }
+
+    /// Title of slide `index`, or `Slide N` for one without a heading.
+    pub fn slide_title(&self, index: usize) -> String {
+        self.slides
+            .get(index)
+            .and_then(|slide| slide.title.clone())
+            .unwrap_or_else(|| format!("Slide {}", index + 1))
+    }
+
+    /// Raw markdown of each slide, in order.
+    pub fn slide_texts(&self) -> Vec<String> {
+        self.slides.iter().map(|s| s.content.clone()).collect()
+    }
+
+    /// 1-based line of `markdown` where each slide of [`Deck::parse`] starts:
+    /// its first non-blank line.
+    pub fn slide_start_lines(markdown: &str) -> Vec<usize> {
+        let body = frontmatter::split(markdown).1;
+        // Lines of the frontmatter and the blank lines after it
+        let skipped = markdown[..markdown.len() - body.len()]
+            .matches('\n')
+            .count();
+        let mut starts = Vec::new();
+        // First line and first non-blank line of the slide being read
+        let mut current: Option<(usize, Option<usize>)> = None;
+        for (index, line) in body.lines().enumerate() {
+            let number = skipped + index + 1;
+            if line.starts_with("## ") {
+                if let Some((first, content)) = current.take() {
+                    starts.push(content.unwrap_or(first));
+                }
+            }
+            let (_, content) = current.get_or_insert((number, None));
+            if content.is_none() && !line.trim().is_empty() {
+                *content = Some(number);
+            }
+        }
+        if let Some((_, Some(content))) = current {
+            starts.push(content);
+        }
+        if starts.is_empty() {
+            starts.push(skipped + 1);
+        }
+        starts
+    }
+}
+
+/// A piece of a deck rebuilt by [`Deck::assemble`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckPart {
+    /// Slide of the original markdown, by index
+    Slide(usize),
+    /// Markdown of a new slide
+    Text(String),
+}
+
+impl Slide {
+    /// A slide headed `# ` rather than `## `: the title slide of a deck
+    /// when it comes first.
+    pub fn is_title(&self) -> bool {
+        self.title.is_some() && !self.content.lines().any(|line| line.starts_with("## "))
+    }
+
+    fn from_markdown(content: &str) -> Self {
+        let title = content
+            .lines()
+            .find_map(|l| l.strip_prefix("## ").or_else(|| l.strip_prefix("# ")))
+            .map(|t| t.trim().to_string());
+        Self {
+            title,
+            content: content.to_string(),
+        }
+    }
+}
+
+/// Text of each slide of `markdown`, split where [`Deck::parse`] splits.
+fn slide_segments(markdown: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("## ") && pos > start {
+            segments.push(&markdown[start..pos]);
+            start = pos;
+        }
+        pos += line.len();
+    }
+    if segments.is_empty() || !markdown[start..].trim().is_empty() {
+        segments.push(&markdown[start..]);
+    }
+    segments
+}
+
+/// Whitespace-separated words, with runs of CJK characters counted per
+/// [`CJK_CHARS_PER_WORD`] since they are written without spaces.
+fn count_words(text: &str) -> usize {
+    let mut words = 0;
+    for token in text.split_whitespace() {
+        let cjk = token.chars().filter(|c| is_cjk(*c)).count();
+        if cjk == 0 {
+            if token.chars().any(char::is_alphanumeric) {
+                words += 1;
+            }
+        } else {
+            words += cjk.div_ceil(CJK_CHARS_PER_WORD);
+            if token.chars().any(|c| c.is_ascii_alphanumeric()) {
+                words += 1;
+            }
+        }
+    }
+    words
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}' // Hangul
+        | '\u{f900}'..='\u{faff}')
+}
+
+/// Presentation settings (`presentation` section of the config).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresentationConfig {
+    /// Speaking rate used for timing estimates
+    pub words_per_minute: u32,
+    /// Per-slide time budget in seconds; slides over it are flagged
+    pub slide_budget_secs: Option<u32>,
+}
+
+impl Default for PresentationConfig {
+    fn default() -> Self {
+        Self {
+            words_per_minute: 130,
+            slide_budget_secs: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlideStats {
+    pub title: String,
+    pub words: usize,
+    pub seconds: u32,
+    pub over_budget: bool,
+    /// Time the last rehearsal spent on the slide
+    pub rehearsed_secs: Option<u32>,
+}
+
+/// Word counts and speaking-time estimates for a deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckStats {
+    pub slides: Vec<SlideStats>,
+    pub total_words: usize,
+    pub total_seconds: u32,
+    pub words_per_minute: u32,
+    pub slide_budget_secs: Option<u32>,
+    /// When the rehearsal of [`DeckStats::with_rehearsal`] was recorded
+    pub rehearsed_at: Option<String>,
+}
+
+impl DeckStats {
+    pub fn compute(deck: &Deck, config: &PresentationConfig) -> Self {
+        let wpm = config.words_per_minute.max(1);
+        let slides: Vec<SlideStats> = deck
+            .slides
+            .iter()
+            .enumerate()
+            .map(|(i, slide)| {
+                let words = slide.spoken_words();
+                let seconds = ((words as u64 * 60).div_ceil(wpm as u64)) as u32;
+                SlideStats {
+                    title: deck.slide_title(i),
+                    words,
+                    seconds,
+                    over_budget: config.slide_budget_secs.is_some_and(|b| seconds > b),
+                    rehearsed_secs: None,
+                }
+            })
+            .collect();
+        Self {
+            total_words: slides.iter().map(|s| s.words).sum(),
+            total_seconds: slides.iter().map(|s| s.seconds).sum(),
+            slides,
+            words_per_minute: wpm,
+            slide_budget_secs: config.slide_budget_secs,
+            rehearsed_at: None,
+        }
+    }
+
+    /// Add the times of a rehearsal next to the estimates.
+    pub fn with_rehearsal(mut self, report: &TimingReport) -> Self {
+        for (i, slide) in self.slides.iter_mut().enumerate() {
+            slide.rehearsed_secs = report.seconds_for(i, &slide.title);
+        }
+        self.rehearsed_at = Some(report.recorded_at.clone());
+        self
+    }
+
+    /// Sum of the rehearsed slide times
+    pub fn rehearsed_seconds(&self) -> u32 {
+        self.slides.iter().filter_map(|s| s.rehearsed_secs).sum()
+    }
+
+    pub fn average_words(&self) -> usize {
+        if self.slides.is_empty() {
+            0
+        } else {
+            self.total_words / self.slides.len()
+        }
+    }
+
+    pub fn over_budget_count(&self) -> usize {
+        self.slides.iter().filter(|s| s.over_budget).count()
+    }
+
+    /// Plain-text table used by `slide stats` and the preview panel.
+    pub fn render_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        lines.push(format!(
+            "{} slides · {} words ({} per slide) · {} total at {} wpm",
+            self.slides.len(),
+            self.total_words,
+            self.average_words(),
+            format_duration(self.total_seconds),
+            self.words_per_minute
+        ));
+        if let Some(budget) = self.slide_budget_secs {
+            lines.push(format!(
+                "Budget {} per slide · {} over budget",
+                format_duration(budget),
+                self.over_budget_count()
+            ));
+        }
+        if let Some(at) = &self.rehearsed_at {
+            lines.push(format!(
+                "Rehearsed {} on {at}",
+                format_duration(self.rehearsed_seconds())
+            ));
+        }
+        lines.push(String::new());
+        let rehearsed = self.rehearsed_at.is_some();
+        lines.push(if rehearsed {
+            "  #  words   time  rehearsed  title".to_string()
+        } else {
+            "  #  words   time  title".to_string()
+        });
+        for (i, s) in self.slides.iter().enumerate() {
+            let rehearsed = match (rehearsed, s.rehearsed_secs) {
+                (false, _) => String::new(),
+                (true, Some(secs)) => format!("{:>9}  ", format_duration(secs)),
+                (true, None) => format!("{:>9}  ", "-"),
+            };
+            lines.push(format!(
+                "{:>3}  {:>5}  {:>5}  {rehearsed}{}{}",
+                i + 1,
+                s.words,
+                format_duration(s.seconds),
+                s.title,
+                if s.over_budget {
+                    "  ⚠ over budget"
+                } else {
+                    ""
+                }
+            ));
+        }
+        lines
+    }
+}
+
+/// Format seconds as `m:ss`.
+pub fn format_duration(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_on_level_two_headings() {
+        let deck = Deck::parse("# Deck\n\n## One\n- a\n\n## Two\n- b\n");
+        assert_eq!(deck.slides.len(), 3);
+        assert_eq!(deck.slides[0].title.as_deref(), Some("Deck"));
+        assert_eq!(deck.slides[1].title.as_deref(), Some("One"));
+        assert_eq!(deck.slides[2].content, "## Two\n- b");
+        assert_eq!(
+            Deck::slide_start_lines("\n# Deck\n\n## One\n- a\n\n## Two\n- b\n"),
+            [2, 4, 7]
+        );
+        assert_eq!(Deck::slide_start_lines(""), [1]);
+
+        // The frontmatter is not a slide
+        let markdown = "---\ntheme: gaia\n---\n\n# Deck\n\n## One\n- a\n";
+        let deck = Deck::parse(markdown);
+        assert_eq!(deck.slides.len(), 2);
+        assert_eq!(deck.slides[0].content, "# Deck");
+        assert_eq!(deck.settings.theme.as_deref(), Some("gaia"));
+        assert_eq!(Deck::slide_start_lines(markdown), [5, 7]);
+    }
+
+    #[test]
+    fn reorder_moves_slides_and_keeps_their_text() {
+        let markdown = "# Deck\n\n## One\n- a\n\n\n## Two\n  - b\n";
+        assert_eq!(
+            Deck::reorder(markdown, &[0, 2, 1]),
+            "# Deck\n\n## Two\n  - b\n\n## One\n- a\n"
+        );
+        let deck = Deck::parse(markdown);
+        let reordered = Deck::parse(&Deck::reorder(markdown, &[0, 2, 1]));
+        assert_eq!(reordered.slides[1], deck.slides[2]);
+        assert_eq!(reordered.slides[2], deck.slides[1]);
+        assert_eq!(
+            Deck::reorder("---\npaginate: true\n---\n\n## A\n\n## B\n", &[1, 0]),
+            "---\npaginate: true\n---\n\n## B\n\n## A\n"
+        );
+    }
+
+    #[test]
+    fn spoken_words_skip_code_and_images() {
+        let slide = Slide::from_markdown(
+            "## Hello world\n- one two\n![img](a.png)\n```\nlet x = 1;\n```\n",
+        );
+        assert_eq!(slide.spoken_words(), 4);
+    }
+
+    #[test]
+    fn comments_are_speaker_notes() {
+        let slide = Slide::from_markdown(
+            "## Plan\n<!-- _class: lead -->\n- ship it <!-- say the date -->\n<!--\nThank the team.\nTake questions.\n-->\n```html\n<!-- markup -->\n```",
+        );
+        assert_eq!(
+            slide.notes().as_deref(),
+            Some("say the date\n\nThank the team.\nTake questions.")
+        );
+        assert_eq!(
+            slide.visible_content(),
+            "## Plan\n- ship it\n```html\n<!-- markup -->\n```"
+        );
+        assert_eq!(Slide::from_markdown("## Plain\n- a").notes(), None);
+    }
+
+    #[test]
+    fn cjk_text_is_counted() {
+        assert_eq!(count_words("日本語です"), 3);
+        assert_eq!(count_words("Rustの話"), 2);
+    }
+
+    #[test]
+    fn stats_flag_slides_over_budget() {
+        let deck = Deck::parse("## A\none two three four\n## B\none\n");
+        let stats = DeckStats::compute(
+            &deck,
+            &PresentationConfig {
+                words_per_minute: 60,
+                slide_budget_secs: Some(3),
+            },
+        );
+        assert_eq!(stats.slides[0].seconds, 5);
+        assert!(stats.slides[0].over_budget);
+        assert!(!stats.slides[1].over_budget);
+        assert_eq!(stats.total_seconds, 7);
+        assert_eq!(format_duration(stats.total_seconds), "0:07");
+
+        let report = TimingReport {
+            recorded_at: "2026-10-01 09:30".to_string(),
+            slides: vec![crate::rehearsal::SlideTiming {
+                title: "A".to_string(),
+                seconds: 95,
+            }],
+        };
+        let lines = stats.with_rehearsal(&report).render_lines();
+        assert!(lines.contains(&"Rehearsed 1:35 on 2026-10-01 09:30".to_string()));
+        assert!(lines.contains(&"  1      5   0:05       1:35  A  ⚠ over budget".to_string()));
+        assert!(lines.contains(&"  2      2   0:02          -  B".to_string()));
+    }
+}
@@ -0,0 +1,248 @@
+//! Settings of a deck from its YAML frontmatter, the `---` fenced block at
+//! the top of the file as Marp writes it:
+//!
+//! ```markdown
+//! ---
+//! theme: gaia
+//! size: 16:9
+//! lang: ja
+//! footer: "ACME Corp"
+//! paginate: true
+//! ---
+//! ```
+//!
+//! Only flat `key: value` lines are read. Marp directives and the usual
+//! metadata keys are accepted and left alone; other keys and values that
+//! do not fit the schema are reported as [`SettingError`]s.
+use std::fmt;
+
+use crate::deck::DIRECTIVES;
+
+/// Frontmatter keys accepted besides the Marp directives
+const METADATA: &[&str] = &[
+    "title",
+    "description",
+    "author",
+    "keywords",
+    "url",
+    "image",
+    "math",
+    "transition",
+];
+
+/// Settings a deck gives itself; unset ones fall back to the defaults of
+/// whoever shows the deck.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeckSettings {
+    pub theme: Option<String>,
+    pub aspect_ratio: Option<AspectRatio>,
+    /// BCP 47 tag, such as `en` or `ja-JP`
+    pub language: Option<String>,
+    /// Shown at the bottom of every slide
+    pub footer: Option<String>,
+    /// Number the slides (Marp's `paginate`)
+    pub paginate: bool,
+}
+
+/// Width and height of a slide, as in `16:9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl fmt::Display for AspectRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.width, self.height)
+    }
+}
+
+/// A frontmatter line that does not fit the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingError {
+    /// 1-based line of the deck file
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SettingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl DeckSettings {
+    /// Settings from the frontmatter of `markdown`, and what was wrong with
+    /// it; defaults when there is none. Invalid values are left unset.
+    pub fn parse(markdown: &str) -> (Self, Vec<SettingError>) {
+        let mut settings = Self::default();
+        let mut errors = Vec::new();
+        let Some(block) = split(markdown).0 else {
+            return (settings, errors);
+        };
+        let mut seen: Vec<String> = Vec::new();
+        // Key of the line before, for indented continuation lines
+        let mut last_key: Option<String> = None;
+        // The opening fence is line 1
+        for (index, raw) in block.lines().enumerate().skip(1) {
+            let line = index + 1;
+            let mut error = |message: String| errors.push(SettingError { line, message });
+            if raw.trim_end() == "---" {
+                break;
+            }
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if raw.starts_with([' ', '\t']) {
+                if last_key.as_deref().is_some_and(is_setting) {
+                    let key = last_key.as_deref().unwrap_or_default();
+                    error(format!("`{key}` takes a single value"));
+                }
+                continue;
+            }
+            let Some((key, value)) = trimmed.split_once(':') else {
+                error(format!("expected `key: value`, found `{trimmed}`"));
+                continue;
+            };
+            let key = key.trim().to_string();
+            let value = unquote(value.trim());
+            last_key = Some(key.clone());
+            // `size` and `lang` are Marp's names for the same settings
+            let canonical = match key.as_str() {
+                "size" => "aspect_ratio",
+                "lang" => "language",
+                other => other,
+            };
+            if seen.iter().any(|k| k == canonical) {
+                error(format!("`{key}` repeats a setting made above"));
+                continue;
+            }
+            seen.push(canonical.to_string());
+            match key.as_str() {
+                "theme" if value.is_empty() => error("`theme` needs a theme name".to_string()),
+                "theme" => settings.theme = Some(value.to_string()),
+                "size" | "aspect_ratio" => match parse_aspect_ratio(value) {
+                    Some(ratio) => settings.aspect_ratio = Some(ratio),
+                    None => error(format!(
+                        "`{key}` should be a ratio such as 16:9, found `{value}`"
+                    )),
+                },
+                "lang" | "language" if is_language_tag(value) => {
+                    settings.language = Some(value.to_string())
+                }
+                "lang" | "language" => error(format!(
+                    "`{key}` should be a language tag such as en or ja-JP, found `{value}`"
+                )),
+                "footer" => settings.footer = Some(value.to_string()).filter(|f| !f.is_empty()),
+                "paginate" => match value {
+                    "true" => settings.paginate = true,
+                    "false" => settings.paginate = false,
+                    _ => error(format!(
+                        "`paginate` should be true or false, found `{value}`"
+                    )),
+                },
+                other if DIRECTIVES.contains(&other) || METADATA.contains(&other) => {}
+                other => error(format!("unknown setting `{other}`")),
+            }
+        }
+        (settings, errors)
+    }
+}
+
+/// The frontmatter block of `markdown`, fences included, and the text
+/// after it with its leading blank lines. `None` when the file does not
+/// open with a closed `---` block.
+pub fn split(markdown: &str) -> (Option<&str>, &str) {
+    if !(markdown.starts_with("---\n") || markdown.starts_with("---\r\n")) {
+        return (None, markdown);
+    }
+    let mut offset = 0;
+    for (index, line) in markdown.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        if index > 0 && line.trim_end() == "---" {
+            let block = &markdown[..offset];
+            let rest = &markdown[offset..];
+            let body = rest.trim_start_matches(['\r', '\n']);
+            let block_end = offset + rest.len() - body.len();
+            return (Some(block), &markdown[block_end..]);
+        }
+    }
+    // No closing fence: not frontmatter after all
+    (None, markdown)
+}
+
+/// Settings this module reads, as opposed to keys it lets through.
+fn is_setting(key: &str) -> bool {
+    matches!(
+        key,
+        "theme" | "size" | "aspect_ratio" | "lang" | "language" | "footer" | "paginate"
+    )
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+fn parse_aspect_ratio(value: &str) -> Option<AspectRatio> {
+    let (width, height) = value.split_once(':')?;
+    let ratio = AspectRatio {
+        width: width.trim().parse().ok()?,
+        height: height.trim().parse().ok()?,
+    };
+    (ratio.width > 0 && ratio.height > 0).then_some(ratio)
+}
+
+/// A primary language subtag of 2 or 3 letters, then subtags of 2 to 8
+/// letters or digits.
+fn is_language_tag(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let primary = parts.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontmatter_sets_the_deck_settings_and_reports_bad_lines() {
+        let markdown = "---\nmarp: true\ntheme: gaia\nsize: 16:9\nlang: ja-JP\nfooter: \"ACME\"\npaginate: true\nstyle: |\n  section { color: red; }\n---\n\n# Deck\n";
+        let (settings, errors) = DeckSettings::parse(markdown);
+        assert_eq!(errors, []);
+        assert_eq!(
+            settings,
+            DeckSettings {
+                theme: Some("gaia".to_string()),
+                aspect_ratio: Some(AspectRatio {
+                    width: 16,
+                    height: 9
+                }),
+                language: Some("ja-JP".to_string()),
+                footer: Some("ACME".to_string()),
+                paginate: true,
+            }
+        );
+        assert_eq!(split(markdown).1, "# Deck\n");
+
+        let (settings, errors) = DeckSettings::parse(
+            "---\ntheme:\nsize: wide\nlang: japanese\npaginate: yes\ncolour: red\nfooter\ntheme: x\n---\n",
+        );
+        assert_eq!(settings, DeckSettings::default());
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(errors[4].to_string(), "line 6: unknown setting `colour`");
+
+        // A file that only looks like it opens with frontmatter
+        let (settings, errors) = DeckSettings::parse("---\n# Deck\n");
+        assert_eq!((settings, errors), (DeckSettings::default(), vec![]));
+    }
+}
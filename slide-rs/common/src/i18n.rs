@@ -0,0 +1,250 @@
+//! Message catalogs for user-facing text in the TUI and CLI.
+//!
+//! Each [`Msg`] has an English and a Japanese text. Texts may hold `{name}`
+//! placeholders which [`tf`] fills in. The language is chosen once per
+//! process with [`set_active`]: the `language` config field when set, else
+//! the locale (`SLIDE_LANG`, `LC_ALL`, `LC_MESSAGES`, `LANG`).
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Language of the UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Ja,
+}
+
+/// Environment variables consulted, in order, when no language is configured
+pub const LOCALE_ENV: &[&str] = &["SLIDE_LANG", "LC_ALL", "LC_MESSAGES", "LANG"];
+
+impl Language {
+    /// Language of a locale name such as `ja_JP.UTF-8` or `en`; `None` for
+    /// locales without a catalog (and for `C`/`POSIX`).
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let lang = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "ja" => Some(Self::Ja),
+            "en" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    /// The configured language, else the first locale variable that is set.
+    pub fn detect(configured: Option<Language>) -> Self {
+        configured
+            .or_else(|| {
+                LOCALE_ENV
+                    .iter()
+                    .filter_map(|name| std::env::var(name).ok())
+                    .find(|value| !value.is_empty())
+                    .and_then(|locale| Self::from_locale(&locale))
+            })
+            .unwrap_or_default()
+    }
+}
+
+static ACTIVE: OnceLock<Language> = OnceLock::new();
+
+/// Install the UI language for this process. Only the first call wins.
+pub fn set_active(language: Language) {
+    let _ = ACTIVE.set(language);
+}
+
+/// UI language in effect for this process (English when never set).
+pub fn active() -> Language {
+    ACTIVE.get().copied().unwrap_or_default()
+}
+
+/// Text of `msg` in the active language.
+pub fn t(msg: Msg) -> &'static str {
+    msg.text(active())
+}
+
+/// Text of `msg` in the active language with `{name}` placeholders filled.
+pub fn tf(msg: Msg, args: &[(&str, &dyn Display)]) -> String {
+    fill(msg.text(active()), args)
+}
+
+fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+macro_rules! catalog {
+    ($($key:ident => $en:literal, $ja:literal;)*) => {
+        /// Key of a user-facing text.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($key,)*
+        }
+
+        impl Msg {
+            pub const ALL: &'static [Msg] = &[$(Msg::$key,)*];
+
+            pub fn text(self, language: Language) -> &'static str {
+                match (self, language) {
+                    $(
+                        (Msg::$key, Language::En) => $en,
+                        (Msg::$key, Language::Ja) => $ja,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+catalog! {
+    // Status bar
+    StatusIdle => "Idle", "待機中";
+    StatusRunning => "Running…", "実行中…";
+    StatusError => "Error", "エラー";
+    StatusQueued => "queued ({count})", "待機中の入力 ({count})";
+    StatusPaused => "paused", "一時停止中";
+    StatusApproval => "approval: {policy}", "承認: {policy}";
+    StatusSandbox => "sandbox: {policy}", "サンドボックス: {policy}";
+    StatusCwd => "cwd: {cwd}", "作業ディレクトリ: {cwd}";
+    StatusTokens => "{count} tokens", "{count} トークン";
+    StatusReadOnly => "READ-ONLY", "読み取り専用";
+    StatusGitDirty => "{count} changed", "変更 {count} 件";
+    StatusGitDetached => "detached HEAD", "detached HEAD";
+
+    // Approval modal
+    ApprovalTitle => "Approval Required", "承認が必要です";
+    ApprovalRunCommand => "Run command", "コマンドの実行";
+    ApprovalEscalated => "needs permissions outside the sandbox", "サンドボックス外の権限が必要";
+    ApprovalRisk => "risk: {reason}", "リスク: {reason}";
+    ApprovalDirtyFiles => "uncommitted changes in {files}", "{files} に未コミットの変更があります";
+    ApprovalApproveSession => "approve for the session", "このセッション中は承認";
+    ApprovalAllowPrefix => "allow `{prefix} …` for the session", "このセッション中は `{prefix} …` を許可";
+    ApprovalRunCommands => "Run commands", "コマンドの実行";
+    ApprovalCommandsSelected => "[{selected}/{total} selected]", "[{selected}/{total} 件を選択]";
+    ApprovalPatchChanges => "apply_patch changes", "apply_patch による変更";
+    ApprovalFilesSelected => "[{selected}/{total} files selected]", "[{selected}/{total} ファイルを選択]";
+    ApprovalApprove => "approve", "承認";
+    ApprovalApplySelected => "apply selected", "選択分を適用";
+    ApprovalRunSelected => "run selected", "選択分を実行";
+    ApprovalDeny => "deny", "拒否";
+    ApprovalClose => "close", "閉じる";
+    ApprovalTimeLeft => "{secs}s left to answer", "回答期限まで {secs} 秒";
+    ApprovalPatchKeys =>
+        "Tab/S-Tab: file  Space/a/d: toggle/keep/drop  j/k PgUp/PgDn g/G: scroll",
+        "Tab/S-Tab: ファイル  Space/a/d: 切替/残す/外す  j/k PgUp/PgDn g/G: スクロール";
+    ApprovalPlanKeys => "↑/↓ j/k: move  Space: toggle", "↑/↓ j/k: 移動  Space: 切替";
+
+    // Modals
+    ModalCreated => "Created", "作成しました";
+    ModalCreatedSlide => "Created new slide: {path}", "新しいスライドを作成しました: {path}";
+    ModalCreateFailed => "Failed to create slide: {error}", "スライドを作成できませんでした: {error}";
+    ModalSaved => "Saved", "保存しました";
+    ModalSavedTo => "Saved to {path}", "{path} に保存しました";
+    ModalSaveFailed => "Failed to save draft: {error}", "下書きを保存できませんでした: {error}";
+    ModalError => "Error", "エラー";
+
+    // Transcript
+    PatchFileChanged => "1 file changed", "1 ファイルを変更";
+    PatchFilesChanged => "{count} files changed", "{count} ファイルを変更";
+
+    // Help
+    HelpTitle => "Help", "ヘルプ";
+    HelpSession => "Session", "セッション";
+    HelpKeys => "Keys", "キー";
+    HelpComposer => "Composer", "入力欄";
+    HelpVim => "Vim normal and visual mode", "Vim ノーマル/ビジュアルモード";
+    HelpPrompts => "Prompt templates", "プロンプトテンプレート";
+    HelpSearchHint => "/: search  n/N: next/previous match  q: close", "/: 検索  n/N: 次/前の一致  q: 閉じる";
+    HelpCommands => "Commands", "コマンド";
+    KeyShowHelp => "show this help", "このヘルプを表示";
+    KeyClearHistory => "clear messages", "メッセージを消去";
+    KeyTranscript => "open the transcript", "トランスクリプトを開く";
+    KeyToggleExecOutput => "expand/collapse command output", "コマンド出力の展開/折りたたみ";
+    KeyFullExecOutput => "full command output", "コマンド出力の全文";
+    KeyCopyReply => "copy the last reply", "直前の返答をコピー";
+    KeySuspend => "suspend to the shell (fg resumes)", "シェルに戻る (fg で再開)";
+    KeyInterrupt => "stop the running turn", "実行中のターンを止める";
+    KeyCloseOrQuit => "close the dialog, else quit", "ダイアログを閉じる (なければ終了)";
+    KeySend => "send the message", "メッセージを送信";
+    KeyNewline => "insert a newline", "改行を挿入";
+    KeyHistory => "previous/next message", "前/次のメッセージ";
+    KeyComplete => "complete a command or file", "コマンドやファイルを補完";
+    KeyExpandPaste => "expand pasted text", "貼り付けたテキストを展開";
+    KeyLineStartEnd => "go to the start/end of the line", "行頭/行末へ移動";
+    KeyKill => "cut a word, to the line start, to the line end", "単語/行頭まで/行末までを切り取り";
+    KeyYank => "paste cut text, cycle older cuts", "切り取ったテキストを貼り付け、古いものに切り替え";
+    KeyUndoRedo => "undo/redo", "元に戻す/やり直す";
+    KeyVimNormal => "normal mode", "ノーマルモード";
+    KeyVimInsert => "insert mode", "挿入モード";
+    KeyVimVisual => "visual mode", "ビジュアルモード";
+    KeyVimMotion => "move the cursor", "カーソルを移動";
+    KeyVimLines => "move by line, history at the edges", "行単位で移動 (端では履歴)";
+    KeyVimOperators => "delete, change, yank", "削除・変更・ヤンク";
+    KeyVimPutUndo => "put, undo, redo", "貼り付け・元に戻す・やり直す";
+    CmdNew => "create a new deck from the template", "テンプレートから新しいデッキを作成";
+    CmdPreview => "open a deck in the preview", "デッキをプレビューで開く";
+    CmdOpen => "search the workspace for a file to preview", "プレビューするファイルをワークスペースから検索";
+    CmdModel => "switch the model for the next turns", "以降のターンで使うモデルを切り替え";
+    CmdApprovals => "set the approval policy", "承認ポリシーを設定";
+    CmdDiff => "show the last change to a deck", "デッキの直前の変更を表示";
+    CmdUndo => "restore the previous version of a deck", "デッキを前のバージョンに戻す";
+    CmdUndoTurn => "put back every file the last turn changed", "直前のターンで変更したファイルをすべて元に戻す";
+    CmdSplit => "show a deck beside the chat while it is edited", "編集中のデッキをチャットの横に表示";
+    CmdSessions => "resume a saved session or start a new one", "保存したセッションを再開、または新規開始";
+    CmdCommit => "draft a commit message for the current diff (or commit with the given one)", "現在の差分からコミットメッセージを作成 (または指定したメッセージでコミット)";
+    CmdRetry => "resubmit the last prompt (or an edited one)", "直前のプロンプト (または編集したもの) を再送信";
+    CmdCd => "run the agent's commands in another directory", "エージェントのコマンドを別のディレクトリで実行";
+    CmdExportChat => "save the session as a markdown or HTML report", "セッションを Markdown または HTML のレポートとして保存";
+    CmdCopy => "copy the last reply, a command's output or the turn diff", "直前の返答、コマンド出力、またはターンの差分をコピー";
+    CmdPause => "hold the agent before its next tool call", "次のツール呼び出しの前でエージェントを止める";
+    CmdResume => "let a paused agent go on", "一時停止中のエージェントを再開";
+    CmdPrompts => "list saved prompt templates", "保存したプロンプトテンプレートを一覧表示";
+    CmdHelp => "list slash commands", "スラッシュコマンドを一覧表示";
+
+    // CLI
+    DoctorFailed => "{failed} check(s) failed, {warned} warning(s)", "{failed} 件のチェックが失敗、警告 {warned} 件";
+    DoctorReadyWithWarnings => "Ready, with {warned} warning(s)", "準備完了 (警告 {warned} 件)";
+    DoctorAllGood => "Everything looks good", "問題は見つかりませんでした";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn catalogs_agree_on_placeholders() {
+        for msg in Msg::ALL {
+            let en = msg.text(Language::En);
+            let ja = msg.text(Language::Ja);
+            assert!(!ja.is_empty(), "{msg:?} has no Japanese text");
+            assert_eq!(placeholders(en), placeholders(ja), "{msg:?}");
+        }
+    }
+
+    #[test]
+    fn locale_names_select_a_language() {
+        assert_eq!(Language::from_locale("ja_JP.UTF-8"), Some(Language::Ja));
+        assert_eq!(Language::from_locale("en-US"), Some(Language::En));
+        assert_eq!(Language::from_locale("C.UTF-8"), None);
+        assert_eq!(Language::detect(Some(Language::Ja)), Language::Ja);
+        assert_eq!(
+            fill(Msg::StatusCwd.text(Language::Ja), &[("cwd", &"./slides")]),
+            "作業ディレクトリ: ./slides"
+        );
+    }
+}
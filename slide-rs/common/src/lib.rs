@@ -1,7 +1,31 @@
 pub mod approval_mode;
+pub mod auth;
 pub mod config;
+pub mod deck;
 pub mod file_utils;
+pub mod frontmatter;
+pub mod i18n;
+pub mod merge;
+pub mod moderation;
+pub mod outline;
+pub mod privacy;
+pub mod project;
+pub mod rehearsal;
+pub mod secrets;
+pub mod sources;
+pub mod versions;
+pub mod workspace_state;
 
 pub use approval_mode::*;
 pub use config::*;
+pub use deck::{Deck, DeckStats, PresentationConfig, Slide};
 pub use file_utils::*;
+pub use frontmatter::DeckSettings;
+pub use moderation::ModerationConfig;
+pub use outline::Outline;
+pub use privacy::PrivacyConfig;
+pub use project::{Project, ProjectManifest};
+pub use rehearsal::{Rehearsal, TimingReport};
+pub use sources::SourceMap;
+pub use versions::VersionStore;
+pub use workspace_state::WorkspaceState;
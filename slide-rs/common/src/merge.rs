@@ -0,0 +1,286 @@
+//! Combining decks into one (`slide merge`).
+//!
+//! Slides are taken through [`Deck::parse`] and kept as written, with three
+//! changes:
+//!
+//! - only the first title slide (a leading slide with a `# ` heading) stays;
+//!   a later one would otherwise run into the slide before it;
+//! - numbered headings (`## 3. Results`) are numbered again in order;
+//! - relative image and link targets are rewritten against the directory of
+//!   the merged deck;
+//! - the frontmatter of the first deck that has one sets up the merged deck.
+//!
+//! With an outline the slides are put in its order, matched by title; the
+//! slides it does not name follow in deck order.
+use std::path::{Component, Path, PathBuf};
+
+use crate::deck::{Deck, Slide};
+use crate::frontmatter;
+
+/// A deck to merge and the directory its relative paths start from.
+#[derive(Debug, Clone)]
+pub struct SourceDeck {
+    pub dir: PathBuf,
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedDeck {
+    pub markdown: String,
+    pub slides: usize,
+    /// Title slides left out after the first
+    pub dropped_titles: usize,
+    /// Outline entries no slide matched
+    pub missing: Vec<String>,
+    /// Slides the outline did not name, appended after the outlined ones
+    pub unplaced: usize,
+}
+
+/// Merge `sources` into a deck written to `out_dir`, in deck order or in
+/// the order of `outline` (titles, see [`parse_outline`]).
+pub fn merge(sources: &[SourceDeck], out_dir: &Path, outline: Option<&[String]>) -> MergedDeck {
+    let mut title_slide = None;
+    let mut dropped_titles = 0;
+    let mut slides = Vec::new();
+    for source in sources {
+        let deck = Deck::parse(&source.markdown);
+        for (i, slide) in deck.slides.into_iter().enumerate() {
+            let content = rebase_paths(&slide.content, &source.dir, out_dir);
+            let slide = Slide { content, ..slide };
+            if i == 0 && slide.is_title() {
+                if title_slide.is_none() {
+                    title_slide = Some(slide);
+                } else {
+                    dropped_titles += 1;
+                }
+                continue;
+            }
+            slides.push(slide);
+        }
+    }
+
+    let mut missing = Vec::new();
+    let mut unplaced = 0;
+    if let Some(outline) = outline {
+        let mut remaining: Vec<Option<Slide>> = slides.into_iter().map(Some).collect();
+        slides = Vec::new();
+        for entry in outline {
+            let key = title_key(entry);
+            let found = remaining.iter_mut().find(|slot| {
+                slot.as_ref()
+                    .and_then(|slide| slide.title.as_deref())
+                    .is_some_and(|title| title_key(title) == key)
+            });
+            match found.and_then(Option::take) {
+                Some(slide) => slides.push(slide),
+                None => missing.push(entry.clone()),
+            }
+        }
+        let rest: Vec<Slide> = remaining.into_iter().flatten().collect();
+        unplaced = rest.len();
+        slides.extend(rest);
+    }
+
+    let mut number = 0;
+    let mut parts: Vec<String> = title_slide.map(|slide| slide.content).into_iter().collect();
+    for slide in &slides {
+        parts.push(renumber(&slide.content, &mut number));
+    }
+    let mut markdown = parts.join("\n\n");
+    markdown.push('\n');
+    if let Some(block) = sources
+        .iter()
+        .find_map(|source| frontmatter::split(&source.markdown).0)
+    {
+        markdown = format!("{}\n\n{markdown}", block.trim_end());
+    }
+    MergedDeck {
+        markdown,
+        slides: parts.len(),
+        dropped_titles,
+        missing,
+        unplaced,
+    }
+}
+
+/// Slide titles of an outline file, one per line; bullets, heading marks
+/// and numbering are left out.
+pub fn parse_outline(text: &str) -> Vec<String> {
+    text.lines()
+        .map(strip_marks)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn strip_marks(line: &str) -> &str {
+    let line = line
+        .trim()
+        .trim_start_matches('#')
+        .trim_start_matches(['-', '*', '+'])
+        .trim_start();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match line[digits..].strip_prefix(['.', ')']) {
+        Some(rest) if digits > 0 => rest.trim_start(),
+        _ => line,
+    }
+}
+
+/// Title compared with outline entries: without numbering, case folded.
+fn title_key(title: &str) -> String {
+    strip_marks(title).to_lowercase()
+}
+
+/// `content` with the number of a numbered heading replaced by the next
+/// number; the first numbered slide of the merged deck is `1`.
+fn renumber(content: &str, number: &mut usize) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut renumbered = false;
+    for line in content.split_inclusive('\n') {
+        let rest = line.strip_prefix("## ").filter(|_| !renumbered);
+        let digits = rest.map_or(0, |rest| {
+            rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len()
+        });
+        match rest {
+            Some(rest)
+                if digits > 0
+                    && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) =>
+            {
+                *number += 1;
+                renumbered = true;
+                out.push_str(&format!("## {number}{}", &rest[digits..]));
+            }
+            _ => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// `content` with relative link, image and `src="…"` targets written
+/// relative to `to` instead of `from`.
+fn rebase_paths(content: &str, from: &Path, to: &Path) -> String {
+    let from = normalize(from);
+    let to = normalize(to);
+    if from == to {
+        return content.to_string();
+    }
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = ["](", "src=\""]
+        .iter()
+        .filter_map(|marker| rest.find(marker).map(|at| at + marker.len()))
+        .min()
+    {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| c == ')' || c == '"' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let target = &rest[..end];
+        if is_relative_target(target) {
+            let rebased = relative_path(&normalize(&from.join(target)), &to);
+            out.push_str(&rebased.to_string_lossy().replace('\\', "/"));
+        } else {
+            out.push_str(target);
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_relative_target(target: &str) -> bool {
+    !(target.is_empty()
+        || target.starts_with('/')
+        || target.starts_with('#')
+        || target.contains("://")
+        || target.starts_with("data:")
+        || target.starts_with("mailto:"))
+}
+
+/// `path` with `.` and `..` resolved without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) {
+                    out.pop();
+                } else if !out.has_root() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `path` relative to the directory `base`; both normalized.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut out = PathBuf::new();
+    for _ in common..base.len() {
+        out.push("..");
+    }
+    for component in &path[common..] {
+        out.push(component);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(dir: &str, markdown: &str) -> SourceDeck {
+        SourceDeck {
+            dir: PathBuf::from(dir),
+            markdown: markdown.to_string(),
+        }
+    }
+
+    #[test]
+    fn decks_merge_with_one_title_renumbered_sections_and_rebased_assets() {
+        let sources = [
+            source(
+                "/talks",
+                "# Q3 Review\n\n## 1. Results\n- up\n\n## 2. Costs\n![chart](img/costs.png)\n",
+            ),
+            source(
+                "/talks/team",
+                "---\ntheme: gaia\n---\n# Team Update\n\n## 1. Hiring\n<img src=\"../shared/logo.svg\">\n\n## Thanks\n[site](https://example.com) [notes](notes.md)\n",
+            ),
+        ];
+        let merged = merge(&sources, Path::new("/talks"), None);
+        assert_eq!(
+            merged.markdown,
+            "---\ntheme: gaia\n---\n\n# Q3 Review\n\n## 1. Results\n- up\n\n## 2. Costs\n![chart](img/costs.png)\n\n\
+             ## 3. Hiring\n<img src=\"shared/logo.svg\">\n\n\
+             ## Thanks\n[site](https://example.com) [notes](team/notes.md)\n"
+        );
+        assert_eq!(merged.slides, 5);
+        assert_eq!(merged.dropped_titles, 1);
+
+        // An outline interleaves the decks; unnamed slides go last
+        let outline = parse_outline("- Hiring\n- 2) costs\n- Budget\n1. Results\n");
+        assert_eq!(outline, ["Hiring", "costs", "Budget", "Results"]);
+        let merged = merge(&sources, Path::new("/out"), Some(&outline));
+        let titles: Vec<Option<String>> = Deck::parse(&merged.markdown)
+            .slides
+            .into_iter()
+            .map(|slide| slide.title)
+            .collect();
+        assert_eq!(
+            titles,
+            ["Q3 Review", "1. Hiring", "2. Costs", "3. Results", "Thanks"]
+                .map(|t| Some(t.to_string()))
+        );
+        assert_eq!(merged.missing, ["Budget"]);
+        assert_eq!(merged.unplaced, 1);
+        assert!(merged.markdown.contains("![chart](../talks/img/costs.png)"));
+    }
+}
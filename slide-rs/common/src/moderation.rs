@@ -0,0 +1,116 @@
+//! Content safety check of what the agent writes into decks.
+//!
+//! The `moderation` section of the config turns the check on. Like every
+//! section it is set per profile (`--profile`), so a classroom profile can
+//! block what a personal one lets through. Content is checked against the
+//! configured terms, or by the OpenAI moderation endpoint; what it flags is
+//! either written with a warning or not written at all.
+use serde::{Deserialize, Serialize};
+
+/// `moderation` section of the config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationConfig {
+    /// What happens to flagged content
+    pub action: ModerationAction,
+    /// What checks the content
+    pub provider: ModerationProvider,
+    /// Words and phrases that flag content, matched as whole words without
+    /// regard to case. Checked with either provider
+    pub blocked_terms: Vec<String>,
+    /// Provider categories that flag content, such as `violence`; empty
+    /// takes every category the provider flags
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// No check
+    #[default]
+    Off,
+    /// Write flagged content and tell the user
+    Flag,
+    /// Refuse to write flagged content
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationProvider {
+    /// Only `blocked_terms`; nothing leaves the machine
+    #[default]
+    Local,
+    /// The OpenAI moderation endpoint as well, with the OpenAI API key
+    Openai,
+}
+
+impl ModerationConfig {
+    pub fn enabled(&self) -> bool {
+        self.action != ModerationAction::Off
+    }
+
+    /// Whether deck content is sent to the OpenAI moderation endpoint.
+    pub fn sends_content(&self) -> bool {
+        self.enabled() && self.provider == ModerationProvider::Openai
+    }
+
+    /// Of the categories a provider flagged, those this config counts.
+    pub fn counted_categories(&self, flagged: Vec<String>) -> Vec<String> {
+        if self.categories.is_empty() {
+            return flagged;
+        }
+        flagged
+            .into_iter()
+            .filter(|category| self.categories.iter().any(|c| c == category))
+            .collect()
+    }
+}
+
+/// The `blocked_terms` found in `text`, quoted, in config order. Terms of
+/// letters and digits only match whole words; others, such as Japanese
+/// terms, match anywhere.
+pub fn matched_terms(text: &str, terms: &[String]) -> Vec<String> {
+    let text = text.to_lowercase();
+    terms
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty() && contains_term(&text, &term.to_lowercase()))
+        .map(|term| format!("\"{term}\""))
+        .collect()
+}
+
+fn contains_term(text: &str, term: &str) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let ascii_word = term.chars().all(|c| is_word(c) || c == ' ' || c == '-');
+    text.match_indices(term).any(|(at, _)| {
+        !ascii_word
+            || (!text[..at].chars().next_back().is_some_and(is_word)
+                && !text[at + term.len()..].chars().next().is_some_and(is_word))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_match_whole_words_and_categories_can_be_narrowed() {
+        let terms = vec!["Gore".to_string(), "暴力".to_string(), " ".to_string()];
+        assert_eq!(
+            matched_terms("No GORE here, 暴力的な表現", &terms),
+            ["\"Gore\"", "\"暴力\""]
+        );
+        assert_eq!(matched_terms("Al Gore's talk", &terms[..1]), ["\"Gore\""]);
+        assert!(matched_terms("gorey details", &terms[..1]).is_empty());
+
+        let config = ModerationConfig {
+            categories: vec!["violence".to_string()],
+            ..Default::default()
+        };
+        assert!(!config.enabled());
+        assert!(!config.sends_content());
+        let flagged = vec!["harassment".to_string(), "violence".to_string()];
+        assert_eq!(config.counted_categories(flagged), ["violence"]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Outline of a deck: a title and a short summary for each slide.
+//!
+//! `slide generate` writes the outline next to the deck (`talk.md` →
+//! `talk.outline.md`) for the user to edit, and keeps the outline the deck
+//! was last written from in `talk.outline.json`. `slide regenerate`
+//! compares the two to find the slides that have to be written again.
+//!
+//! An outline file is a deck itself: an optional `# ` title, then one
+//! `## ` heading per slide with its summary below.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::deck::Deck;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutlineEntry {
+    pub title: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Outline {
+    /// Title of the deck
+    #[serde(default)]
+    pub title: Option<String>,
+    pub entries: Vec<OutlineEntry>,
+}
+
+impl Outline {
+    /// Outline in the markdown form of an outline file.
+    pub fn parse(markdown: &str) -> Self {
+        let deck = Deck::parse(markdown);
+        let entries = deck
+            .outline_slides()
+            .map(|index| {
+                let slide = &deck.slides[index];
+                OutlineEntry {
+                    title: deck.slide_title(index),
+                    summary: body_lines(&slide.content).collect::<Vec<_>>().join("\n"),
+                }
+            })
+            .collect();
+        Self {
+            title: deck_title(&deck),
+            entries,
+        }
+    }
+
+    /// Outline of a written deck: the first line of each slide below its
+    /// heading is the summary.
+    pub fn from_deck(deck: &Deck) -> Self {
+        let entries = deck
+            .outline_slides()
+            .map(|index| OutlineEntry {
+                title: deck.slide_title(index),
+                summary: body_lines(&deck.slides[index].visible_content())
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+            .collect();
+        Self {
+            title: deck_title(deck),
+            entries,
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            out.push_str(&format!("# {title}\n\n"));
+        }
+        for entry in &self.entries {
+            out.push_str(&format!("## {}\n", entry.title));
+            if !entry.summary.is_empty() {
+                out.push_str(&entry.summary);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+        out.push('\n');
+        out
+    }
+
+    /// Outline file of `deck`, for the user to edit.
+    pub fn path_for(deck: &Path) -> PathBuf {
+        deck.with_extension("outline.md")
+    }
+
+    /// Where the outline `deck` was last written from is kept.
+    pub fn snapshot_path_for(deck: &Path) -> PathBuf {
+        deck.with_extension("outline.json")
+    }
+
+    /// Outline `deck` was last written from, if it is known.
+    pub fn load_snapshot(deck: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::snapshot_path_for(deck)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record this as the outline `deck` was written from; returns where.
+    pub fn save_snapshot(&self, deck: &Path) -> Result<PathBuf> {
+        let path = Self::snapshot_path_for(deck);
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Whether `path` is an outline file rather than a deck.
+pub fn is_outline_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(".outline.md"))
+}
+
+fn deck_title(deck: &Deck) -> Option<String> {
+    deck.slides
+        .first()
+        .filter(|slide| slide.is_title())
+        .and_then(|slide| slide.title.clone())
+}
+
+/// Non-blank lines of a slide below its heading, trimmed.
+fn body_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .skip_while(|line| !line.starts_with('#'))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlines_round_trip_and_map_to_slides() -> Result<()> {
+        let markdown = "# Q3\n\n## Results\nRevenue is up\n\n## Costs\nWhere it went\nand why\n";
+        let outline = Outline::parse(markdown);
+        assert_eq!(outline.title.as_deref(), Some("Q3"));
+        assert_eq!(outline.entries.len(), 2);
+        assert_eq!(outline.entries[1].summary, "Where it went\nand why");
+        assert_eq!(Outline::parse(&outline.to_markdown()), outline);
+
+        let deck = Deck::parse("# Q3\n\n## Costs\n- travel\n- rent\n\n## Results\n- up\n");
+        let from_deck = Outline::from_deck(&deck);
+        assert_eq!(from_deck.entries[0].summary, "- travel");
+        // Entries follow their slide when it moved
+        assert_eq!(deck.slide_for_entry(0, "Results"), Some(2));
+        assert_eq!(deck.slide_for_entry(1, "Results"), Some(2));
+        assert_eq!(deck.slide_for_entry(1, "Hiring"), None);
+
+        let dir = tempfile::tempdir()?;
+        let deck_path = dir.path().join("talk.md");
+        assert_eq!(
+            outline.save_snapshot(&deck_path)?,
+            dir.path().join("talk.outline.json")
+        );
+        assert_eq!(Outline::load_snapshot(&deck_path), Some(outline));
+        assert!(is_outline_file(&Outline::path_for(&deck_path)));
+        assert!(!is_outline_file(&deck_path));
+        Ok(())
+    }
+}
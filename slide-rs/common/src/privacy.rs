@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// `privacy` section of the Slide configuration.
+///
+/// Every sink can be switched off individually; `enabled` is the master
+/// switch that turns all of them off at once.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Privacy mode: disables every optional sink listed below
+    pub enabled: bool,
+    /// Do not read or write the composer history file
+    pub disable_history: bool,
+    /// Do not start the local log viewer HTTP server
+    pub disable_web_viewer: bool,
+    /// Do not export spans and metrics over OTLP
+    pub disable_telemetry: bool,
+}
+
+impl PrivacyConfig {
+    pub fn history_enabled(&self) -> bool {
+        !(self.enabled || self.disable_history)
+    }
+
+    pub fn web_viewer_enabled(&self) -> bool {
+        !(self.enabled || self.disable_web_viewer)
+    }
+
+    pub fn telemetry_enabled(&self) -> bool {
+        !(self.enabled || self.disable_telemetry)
+    }
+}
+
+static ACTIVE: OnceLock<PrivacyConfig> = OnceLock::new();
+
+/// Install the privacy settings for this process. Only the first call wins.
+pub fn set_active(config: PrivacyConfig) {
+    let _ = ACTIVE.set(config);
+}
+
+/// Privacy settings in effect for this process (defaults when never set).
+pub fn active() -> PrivacyConfig {
+    ACTIVE.get().cloned().unwrap_or_default()
+}
+
+/// Where a subsystem sends data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataSink {
+    File(PathBuf),
+    Network(String),
+}
+
+impl std::fmt::Display for DataSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataSink::File(path) => write!(f, "file    {}", path.display()),
+            DataSink::Network(endpoint) => write!(f, "network {endpoint}"),
+        }
+    }
+}
+
+/// A single data flow declared by a subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataFlow {
+    pub subsystem: &'static str,
+    pub description: &'static str,
+    pub sink: DataSink,
+    /// Whether the flow is active under the current configuration
+    pub active: bool,
+}
+
+/// Collects the data flows registered by each subsystem.
+#[derive(Debug, Clone, Default)]
+pub struct DataFlowRegistry {
+    flows: Vec<DataFlow>,
+}
+
+impl DataFlowRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, flow: DataFlow) {
+        self.flows.push(flow);
+    }
+
+    pub fn flows(&self) -> &[DataFlow] {
+        &self.flows
+    }
+
+    /// Human readable report grouped into active and disabled flows.
+    pub fn render_report(&self) -> String {
+        let mut out = String::new();
+        for (heading, active) in [("Active data flows", true), ("Disabled data flows", false)] {
+            out.push_str(heading);
+            out.push_str(":\n");
+            let mut any = false;
+            for flow in self.flows.iter().filter(|f| f.active == active) {
+                any = true;
+                out.push_str(&format!(
+                    "  [{}] {}\n      {}\n",
+                    flow.subsystem, flow.sink, flow.description
+                ));
+            }
+            if !any {
+                out.push_str("  (none)\n");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn privacy_mode_disables_every_sink() {
+        let cfg = PrivacyConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(!cfg.history_enabled());
+        assert!(!cfg.web_viewer_enabled());
+        assert!(!cfg.telemetry_enabled());
+    }
+
+    #[test]
+    fn granular_flags_only_disable_their_sink() {
+        let cfg = PrivacyConfig {
+            disable_history: true,
+            ..Default::default()
+        };
+        assert!(!cfg.history_enabled());
+        assert!(cfg.web_viewer_enabled());
+    }
+
+    #[test]
+    fn report_groups_flows() {
+        let mut registry = DataFlowRegistry::new();
+        registry.register(DataFlow {
+            subsystem: "history",
+            description: "composer history",
+            sink: DataSink::File(PathBuf::from("/tmp/h.jsonl")),
+            active: false,
+        });
+        let report = registry.render_report();
+        assert!(report.contains("Active data flows:\n  (none)"));
+        assert!(report.contains("[history] file    /tmp/h.jsonl"));
+    }
+}
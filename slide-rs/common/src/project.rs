@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Manifest file name inside the slides directory.
+pub const MANIFEST_FILE: &str = "slide.toml";
+
+/// `slides/slide.toml`: the decks, shared assets and themes of a project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectManifest {
+    #[serde(default)]
+    pub project: ProjectInfo,
+    #[serde(default, rename = "deck")]
+    pub decks: Vec<DeckEntry>,
+    #[serde(default, rename = "theme")]
+    pub themes: Vec<ThemeEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectInfo {
+    pub name: Option<String>,
+    /// Shared assets directory, relative to the slides directory
+    pub assets: PathBuf,
+    /// Theme used by decks that do not name one
+    pub default_theme: Option<String>,
+}
+
+impl Default for ProjectInfo {
+    fn default() -> Self {
+        Self {
+            name: None,
+            assets: PathBuf::from("assets"),
+            default_theme: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeckEntry {
+    pub name: String,
+    /// Markdown file, relative to the slides directory
+    pub path: PathBuf,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeEntry {
+    pub name: String,
+    /// Theme file, relative to the slides directory
+    pub path: PathBuf,
+}
+
+/// A slides directory together with its manifest.
+///
+/// When `slide.toml` is missing the manifest is synthesised from the
+/// markdown files found under the directory, so callers always go through
+/// the same deck list.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+    pub manifest: ProjectManifest,
+    /// Whether `manifest` was read from disk
+    pub has_manifest: bool,
+}
+
+impl Project {
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let manifest_path = root.join(MANIFEST_FILE);
+        if manifest_path.exists() {
+            let content = std::fs::read_to_string(&manifest_path)
+                .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+            let manifest: ProjectManifest = toml::from_str(&content)
+                .with_context(|| format!("invalid {}", manifest_path.display()))?;
+            return Ok(Self {
+                root,
+                manifest,
+                has_manifest: true,
+            });
+        }
+        Ok(Self {
+            manifest: ProjectManifest {
+                decks: discover_decks(&root),
+                ..Default::default()
+            },
+            root,
+            has_manifest: false,
+        })
+    }
+
+    pub fn deck(&self, name: &str) -> Option<&DeckEntry> {
+        self.manifest.decks.iter().find(|d| d.name == name)
+    }
+
+    pub fn deck_path(&self, deck: &DeckEntry) -> PathBuf {
+        self.root.join(&deck.path)
+    }
+
+    /// Paths of every deck, in manifest order.
+    pub fn deck_paths(&self) -> Vec<PathBuf> {
+        self.manifest
+            .decks
+            .iter()
+            .map(|d| self.deck_path(d))
+            .collect()
+    }
+
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join(&self.manifest.project.assets)
+    }
+
+    /// Theme of a deck, falling back to the project default.
+    pub fn theme_for(&self, deck: &DeckEntry) -> Option<&ThemeEntry> {
+        let name = deck
+            .theme
+            .as_ref()
+            .or(self.manifest.project.default_theme.as_ref())?;
+        self.manifest.themes.iter().find(|t| &t.name == name)
+    }
+
+    /// Resolve a deck by manifest name, falling back to a file path.
+    pub fn resolve(&self, name_or_path: &str) -> Option<PathBuf> {
+        if let Some(deck) = self.deck(name_or_path) {
+            return Some(self.deck_path(deck));
+        }
+        let path = PathBuf::from(name_or_path);
+        path.exists().then_some(path)
+    }
+}
+
+/// Markdown files under `root` other than outlines, sorted, named after
+/// their file stem.
+fn discover_decks(root: &Path) -> Vec<DeckEntry> {
+    let mut files = Vec::new();
+    walk_markdown(root, root, &mut files);
+    files.sort();
+    files
+        .into_iter()
+        .map(|rel| DeckEntry {
+            name: rel.with_extension("").to_string_lossy().replace('\\', "/"),
+            path: rel,
+            title: None,
+            theme: None,
+        })
+        .collect()
+}
+
+fn walk_markdown(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(ft) = entry.file_type() else {
+            continue;
+        };
+        if ft.is_dir() {
+            walk_markdown(root, &path, out);
+        } else if ft.is_file()
+            && path.extension().is_some_and(|e| e == "md")
+            && !crate::outline::is_outline_file(&path)
+        {
+            if let Ok(rel) = path.strip_prefix(root) {
+                out.push(rel.to_path_buf());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_manifest() -> anyhow::Result<()> {
+        let manifest: ProjectManifest = toml::from_str(
+            r#"
+[project]
+name = "Talks"
+default_theme = "dark"
+
+[[deck]]
+name = "intro"
+path = "intro.md"
+title = "Introduction"
+
+[[deck]]
+name = "deep-dive"
+path = "talks/deep.md"
+theme = "light"
+
+[[theme]]
+name = "dark"
+path = "themes/dark.css"
+"#,
+        )?;
+        assert_eq!(manifest.decks.len(), 2);
+        assert_eq!(manifest.project.assets, PathBuf::from("assets"));
+
+        let project = Project {
+            root: PathBuf::from("slides"),
+            manifest,
+            has_manifest: true,
+        };
+        assert_eq!(
+            project.resolve("intro"),
+            Some(PathBuf::from("slides/intro.md"))
+        );
+        let theme = |name: &str| {
+            project
+                .deck(name)
+                .and_then(|d| project.theme_for(d))
+                .map(|t| t.name.clone())
+        };
+        assert_eq!(theme("intro").as_deref(), Some("dark"));
+        assert_eq!(theme("deep-dive"), None);
+        Ok(())
+    }
+}
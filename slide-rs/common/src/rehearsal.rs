@@ -0,0 +1,158 @@
+//! Rehearsal timings of a deck: how long each slide took in a practice run.
+//!
+//! The preview records a [`Rehearsal`] while the presenter practises and
+//! writes a [`TimingReport`] next to the deck (`talk.md` →
+//! `talk.timings.json`). `slide stats` shows the rehearsed times beside the
+//! estimates, and the preview can auto-advance slides after them.
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::deck::Deck;
+
+/// Time spent on one slide during a rehearsal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlideTiming {
+    pub title: String,
+    /// `0` for a slide the rehearsal skipped
+    pub seconds: u32,
+}
+
+/// Timings of the last rehearsal of a deck.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingReport {
+    /// Local time the rehearsal ended, `YYYY-MM-DD HH:MM`
+    pub recorded_at: String,
+    pub slides: Vec<SlideTiming>,
+}
+
+impl TimingReport {
+    /// Report file of `deck`: its name with `.timings.json` for the extension.
+    pub fn path_for(deck: &Path) -> PathBuf {
+        deck.with_extension("timings.json")
+    }
+
+    /// Report of the last rehearsal of `deck`, if there was one.
+    pub fn load(deck: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path_for(deck)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Write the report next to `deck`; returns where.
+    pub fn save(&self, deck: &Path) -> Result<PathBuf> {
+        let path = Self::path_for(deck);
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn total_seconds(&self) -> u32 {
+        self.slides.iter().map(|s| s.seconds).sum()
+    }
+
+    /// Rehearsed time of slide `index` titled `title`. The slide at the same
+    /// position counts while its title matches; after slides were added or
+    /// moved, the first one with that title does. `None` when the rehearsal
+    /// skipped it or has no such slide.
+    pub fn seconds_for(&self, index: usize, title: &str) -> Option<u32> {
+        let timing = self
+            .slides
+            .get(index)
+            .filter(|s| s.title == title)
+            .or_else(|| self.slides.iter().find(|s| s.title == title))?;
+        (timing.seconds > 0).then_some(timing.seconds)
+    }
+}
+
+/// Time spent on each slide while the deck is being rehearsed. Going back
+/// to a slide adds to its time.
+#[derive(Debug, Clone)]
+pub struct Rehearsal {
+    spent: Vec<Duration>,
+    /// Slide on screen and since when
+    current: usize,
+    since: Instant,
+}
+
+impl Rehearsal {
+    pub fn start(slide_count: usize, current: usize, now: Instant) -> Self {
+        Self {
+            spent: vec![Duration::ZERO; slide_count],
+            current,
+            since: now,
+        }
+    }
+
+    /// The presenter moved to slide `index` at `now`.
+    pub fn enter(&mut self, index: usize, now: Instant) {
+        self.stop_clock(now);
+        self.current = index;
+    }
+
+    /// Stop at `now` and report the time of each slide of `deck`.
+    pub fn finish(mut self, deck: &Deck, now: Instant) -> TimingReport {
+        self.stop_clock(now);
+        let slides = (0..deck.slides.len())
+            .map(|i| SlideTiming {
+                title: deck.slide_title(i),
+                seconds: self
+                    .spent
+                    .get(i)
+                    .map_or(0, |spent| spent.as_secs_f64().round() as u32),
+            })
+            .collect();
+        TimingReport {
+            recorded_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            slides,
+        }
+    }
+
+    fn stop_clock(&mut self, now: Instant) {
+        let spent = now.saturating_duration_since(self.since);
+        if let Some(slot) = self.spent.get_mut(self.current) {
+            *slot += spent;
+        }
+        self.since = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rehearsals_time_each_slide_and_round_trip() -> Result<()> {
+        let deck = Deck::parse("## Intro\n- a\n\n## Plan\n- b\n\n## Close\n- c\n");
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut rehearsal = Rehearsal::start(deck.slides.len(), 0, start);
+        rehearsal.enter(1, at(40));
+        rehearsal.enter(0, at(100));
+        rehearsal.enter(1, at(110));
+        let report = rehearsal.finish(&deck, at(130));
+        let seconds: Vec<(&str, u32)> = report
+            .slides
+            .iter()
+            .map(|s| (s.title.as_str(), s.seconds))
+            .collect();
+        assert_eq!(seconds, [("Intro", 50), ("Plan", 80), ("Close", 0)]);
+        assert_eq!(report.total_seconds(), 130);
+
+        assert_eq!(report.seconds_for(1, "Plan"), Some(80));
+        // A slide inserted before it moves it down
+        assert_eq!(report.seconds_for(2, "Plan"), Some(80));
+        assert_eq!(report.seconds_for(2, "Close"), None);
+        assert_eq!(report.seconds_for(0, "New"), None);
+
+        let dir = tempfile::tempdir()?;
+        let deck_path = dir.path().join("talk.md");
+        let path = report.save(&deck_path)?;
+        assert_eq!(path, dir.path().join("talk.timings.json"));
+        assert_eq!(TimingReport::load(&deck_path), Some(report));
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+//! Where the facts of a deck come from.
+//!
+//! A slide cites a page by linking to it, usually at the end of the bullet
+//! (`- Sales grew 12% ([source](https://…))`). Whenever the agent writes a
+//! deck, the cited pages are recorded next to it (`talk.md` →
+//! `talk.sources.json`), per slide and per bullet, with the titles of the
+//! pages `web_fetch` read, and a generated References slide listing them is
+//! kept at the end of the deck. `slide lint` warns about bullets that look
+//! like factual claims but cite nothing.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::deck::{Deck, DeckPart, Slide};
+
+/// Directive marking the generated References slide
+const REFERENCES_CLASS: &str = "<!-- _class: references -->";
+const REFERENCES_HEADING: &str = "References";
+
+/// Lowercased words that usually introduce a claim someone has to back up
+const CLAIM_WORDS: &[&str] = &[
+    "according to",
+    "study",
+    "studies",
+    "survey",
+    "research",
+    "statistic",
+    "percent",
+    "million",
+    "billion",
+    "trillion",
+    "によると",
+    "調査",
+    "研究",
+    "統計",
+    "億",
+    "兆",
+];
+
+/// A page a deck cites.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+    pub url: String,
+    /// Title of the page, when it was fetched
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Local date the page was fetched, `YYYY-MM-DD`
+    #[serde(default)]
+    pub fetched_at: Option<String>,
+}
+
+impl Source {
+    /// A page fetched just now.
+    pub fn fetched(url: impl Into<String>, title: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            title,
+            fetched_at: Some(Local::now().format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+/// A bullet that cites pages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulletSources {
+    pub text: String,
+    /// Numbers of the pages on the References slide, from 1
+    pub sources: Vec<usize>,
+}
+
+/// A slide that cites pages, in its bullets or elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlideSources {
+    pub title: String,
+    /// Numbers of all pages the slide cites
+    pub sources: Vec<usize>,
+    pub bullets: Vec<BulletSources>,
+}
+
+/// Pages a deck cites, numbered in the order the deck first cites them.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub sources: Vec<Source>,
+    pub slides: Vec<SlideSources>,
+}
+
+/// A line of a deck that looks like a factual claim without a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsourcedClaim {
+    /// 1-based line of the deck file
+    pub line: usize,
+    pub text: String,
+}
+
+impl SourceMap {
+    /// Sources file of `deck`: its name with `.sources.json` for the extension.
+    pub fn path_for(deck: &Path) -> PathBuf {
+        deck.with_extension("sources.json")
+    }
+
+    /// Sources recorded for `deck`, if any were.
+    pub fn load(deck: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path_for(deck)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Write the map next to `deck`; returns where.
+    pub fn save(&self, deck: &Path) -> Result<PathBuf> {
+        let path = Self::path_for(deck);
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Pages `markdown` cites. Titles and fetch dates come from the first
+    /// entry of `known` with the same URL; the References slide itself is
+    /// left out.
+    pub fn collect(markdown: &str, known: &[Source]) -> Self {
+        let deck = Deck::parse(markdown);
+        let mut map = Self::default();
+        for (index, slide) in deck.slides.iter().enumerate() {
+            if is_references(slide) {
+                continue;
+            }
+            let mut cited = SlideSources {
+                title: deck.slide_title(index),
+                sources: Vec::new(),
+                bullets: Vec::new(),
+            };
+            for (_, line) in text_lines(&slide.content) {
+                let numbers: Vec<usize> = cited_urls(line)
+                    .into_iter()
+                    .map(|url| map.number_for(url, known))
+                    .collect();
+                if numbers.is_empty() {
+                    continue;
+                }
+                for number in &numbers {
+                    if !cited.sources.contains(number) {
+                        cited.sources.push(*number);
+                    }
+                }
+                if is_bullet(line) {
+                    cited.bullets.push(BulletSources {
+                        text: line.trim().to_string(),
+                        sources: numbers,
+                    });
+                }
+            }
+            if !cited.sources.is_empty() {
+                map.slides.push(cited);
+            }
+        }
+        map
+    }
+
+    /// Number of the page at `url`, added when it is new.
+    fn number_for(&mut self, url: &str, known: &[Source]) -> usize {
+        if let Some(at) = self.sources.iter().position(|s| s.url == url) {
+            return at + 1;
+        }
+        let source = known
+            .iter()
+            .find(|s| s.url == url)
+            .cloned()
+            .unwrap_or_else(|| Source {
+                url: url.to_string(),
+                title: None,
+                fetched_at: None,
+            });
+        self.sources.push(source);
+        self.sources.len()
+    }
+
+    /// Markdown of the References slide headed `heading`.
+    pub fn references_slide(&self, heading: &str) -> String {
+        let mut out = format!("## {heading}\n{REFERENCES_CLASS}\n");
+        for (i, source) in self.sources.iter().enumerate() {
+            let entry = match &source.title {
+                Some(title) => format!("[{}]({})", title.replace(['[', ']'], ""), source.url),
+                None => format!("<{}>", source.url),
+            };
+            out.push_str(&format!("\n{}. {entry}", i + 1));
+        }
+        out
+    }
+}
+
+/// `markdown` with its References slide listing the sources of `map`: the
+/// generated slide is replaced and moved to the end, added when the deck
+/// cites something, and dropped when it cites nothing. A heading the user
+/// gave the slide is kept.
+pub fn with_references(markdown: &str, map: &SourceMap) -> String {
+    let deck = Deck::parse(markdown);
+    let generated = deck.slides.iter().position(is_references);
+    if generated.is_none() && map.sources.is_empty() {
+        return markdown.to_string();
+    }
+    let heading = generated
+        .and_then(|index| deck.slides[index].title.clone())
+        .unwrap_or_else(|| REFERENCES_HEADING.to_string());
+    let mut parts: Vec<DeckPart> = (0..deck.slides.len())
+        .filter(|index| Some(*index) != generated)
+        .map(DeckPart::Slide)
+        .collect();
+    if !map.sources.is_empty() {
+        parts.push(DeckPart::Text(map.references_slide(&heading)));
+    }
+    Deck::assemble(markdown, &parts)
+}
+
+/// Bullets and paragraphs of `markdown` that state figures or studies but
+/// neither cite a page themselves nor sit on a slide that does. A
+/// heuristic: it misses claims without numbers and flags some harmless
+/// figures.
+pub fn unsourced_claims(markdown: &str) -> Vec<UnsourcedClaim> {
+    let deck = Deck::parse(markdown);
+    let starts = Deck::slide_start_lines(markdown);
+    let mut claims = Vec::new();
+    for (slide, start) in deck.slides.iter().zip(starts) {
+        if is_references(slide) {
+            continue;
+        }
+        let lines: Vec<(usize, &str)> = text_lines(&slide.content).collect();
+        if lines.iter().any(|(_, line)| !cited_urls(line).is_empty()) {
+            continue;
+        }
+        for (offset, line) in lines {
+            if looks_factual(line) {
+                claims.push(UnsourcedClaim {
+                    line: start + offset,
+                    text: line.trim().to_string(),
+                });
+            }
+        }
+    }
+    claims
+}
+
+fn is_references(slide: &Slide) -> bool {
+    slide
+        .content
+        .lines()
+        .any(|line| line.trim() == REFERENCES_CLASS)
+}
+
+/// Bullets and paragraph lines of a slide with their offset in it; headings,
+/// tables, images, code and comments are left out.
+fn text_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut in_code = false;
+    let mut in_comment = false;
+    content.lines().enumerate().filter(move |(_, line)| {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            return false;
+        }
+        if in_code {
+            return false;
+        }
+        if in_comment || trimmed.starts_with("<!--") {
+            in_comment = !trimmed.ends_with("-->");
+            return false;
+        }
+        !(trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('|')
+            || trimmed.starts_with("!["))
+    })
+}
+
+fn is_bullet(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    trimmed.starts_with(['-', '*', '+']) || (digits > 0 && trimmed[digits..].starts_with(". "))
+}
+
+/// `http(s)` URLs in `line`, as links, autolinks or bare text.
+fn cited_urls(line: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = line;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, ')' | '>' | ']' | '"'))
+            .unwrap_or(rest.len());
+        let url = rest[..end].trim_end_matches(['.', ',', ';', ':']);
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        rest = &rest[end..];
+    }
+    urls
+}
+
+/// Percentages, amounts of money, large numbers and words such as
+/// "survey"; years and small counts do not count.
+fn looks_factual(line: &str) -> bool {
+    let text = line
+        .trim_start()
+        .trim_start_matches(['-', '*', '+'])
+        .trim_start();
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    // Without the number of a numbered list
+    let text = match text[digits..].strip_prefix(". ") {
+        Some(rest) if digits > 0 => rest,
+        _ => text,
+    };
+    let lower = text.to_lowercase();
+    if CLAIM_WORDS.iter().any(|word| lower.contains(word)) {
+        return true;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == ',' || chars[i] == '.') {
+            i += 1;
+        }
+        let number: String = chars[start..i]
+            .iter()
+            .filter(|c| c.is_ascii_digit())
+            .collect();
+        let before = start.checked_sub(1).map(|at| chars[at]);
+        let after = chars.get(i).copied();
+        let is_year = number.len() == 4 && (number.starts_with("19") || number.starts_with("20"));
+        if matches!(after, Some('%' | '％' | 'x' | '倍'))
+            || matches!(before, Some('$' | '€' | '£' | '¥' | '￥'))
+            || (number.len() >= 3 && !is_year && before != Some(':') && after != Some(':'))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cited_pages_are_recorded_and_listed_on_a_references_slide() -> Result<()> {
+        let markdown = "# Market\n\n## Growth\n- Sales grew 12% ([source](https://example.com/q3))\n- Churn fell\n\nSee also <https://example.org/blog>.\n\n## Outlook\n- Up by 2026\n- 40% of buyers are new\n";
+        let known = [Source::fetched(
+            "https://example.com/q3",
+            Some("Q3 [report]".to_string()),
+        )];
+        let map = SourceMap::collect(markdown, &known);
+        assert_eq!(map.sources.len(), 2);
+        assert_eq!(map.sources[0].title.as_deref(), Some("Q3 [report]"));
+        assert_eq!(map.sources[1].title, None);
+        assert_eq!(map.slides.len(), 1);
+        assert_eq!(map.slides[0].title, "Growth");
+        assert_eq!(map.slides[0].sources, [1, 2]);
+        assert_eq!(map.slides[0].bullets.len(), 1);
+        assert_eq!(map.slides[0].bullets[0].sources, [1]);
+
+        let with_slide = with_references(markdown, &map);
+        assert!(with_slide.ends_with(
+            "## References\n<!-- _class: references -->\n\n1. [Q3 report](https://example.com/q3)\n2. <https://example.org/blog>\n"
+        ));
+        // Regenerating is stable, and the slide does not cite itself
+        assert_eq!(SourceMap::collect(&with_slide, &known), map);
+        assert_eq!(with_references(&with_slide, &map), with_slide);
+        // A renamed slide keeps its heading; without sources it goes away
+        let renamed = with_slide.replace("## References", "## 参考文献");
+        assert!(with_references(&renamed, &map).contains("## 参考文献\n"));
+        assert_eq!(
+            with_references(&with_slide, &SourceMap::default()),
+            markdown
+        );
+
+        // Only the Outlook slide cites nothing; 2026 is a year
+        let claims = unsourced_claims(&with_slide);
+        assert_eq!(
+            claims,
+            [UnsourcedClaim {
+                line: 11,
+                text: "- 40% of buyers are new".to_string()
+            }]
+        );
+        assert!(looks_factual("- A survey of teams"));
+        assert!(looks_factual("- Costs $3 per seat"));
+        assert!(!looks_factual("1. Ship v2 at 10:30"));
+
+        let dir = tempfile::tempdir()?;
+        let deck = dir.path().join("talk.md");
+        assert_eq!(map.save(&deck)?, dir.path().join("talk.sources.json"));
+        assert_eq!(SourceMap::load(&deck), Some(map));
+        Ok(())
+    }
+}
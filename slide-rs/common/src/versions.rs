@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
+use similar::{ChangeTag, TextDiff};
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory, relative to the workspace.
+pub const VERSIONS_DIR: &str = ".slide/versions";
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// One stored snapshot of a deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckVersion {
+    pub path: PathBuf,
+    pub created: NaiveDateTime,
+}
+
+impl DeckVersion {
+    pub fn read(&self) -> Result<String> {
+        std::fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))
+    }
+
+    pub fn label(&self) -> String {
+        self.created.format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+/// Snapshots of decks under `.slide/versions/<deck>/<timestamp>.md`.
+#[derive(Debug, Clone)]
+pub struct VersionStore {
+    workspace: PathBuf,
+    root: PathBuf,
+}
+
+impl VersionStore {
+    pub fn new(workspace: impl AsRef<Path>) -> Self {
+        Self {
+            workspace: workspace.as_ref().to_path_buf(),
+            root: workspace.as_ref().join(VERSIONS_DIR),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory holding the snapshots of `deck`, named after its path in
+    /// the workspace without the extension and with separators escaped
+    /// (`slides%2Ftalk`), so decks sharing a file name keep apart. Decks at
+    /// the top of the workspace keep the plain stem.
+    pub fn deck_dir(&self, deck: &Path) -> PathBuf {
+        let deck = resolve(deck);
+        let workspace = resolve(&self.workspace);
+        let relative = deck.strip_prefix(&workspace).unwrap_or(&deck);
+        let name = escape_separators(&relative.with_extension("").to_string_lossy());
+        if name.is_empty() {
+            return self.root.join("deck");
+        }
+        self.root.join(name)
+    }
+
+    /// Store the current content of `deck` unless it matches the latest
+    /// snapshot. Returns the new snapshot, if one was written.
+    pub fn snapshot(&self, deck: &Path) -> Result<Option<DeckVersion>> {
+        let content = std::fs::read_to_string(deck)
+            .with_context(|| format!("failed to read {}", deck.display()))?;
+        if let Some(latest) = self.list(deck)?.pop() {
+            if latest.read()? == content {
+                return Ok(None);
+            }
+        }
+
+        let dir = self.deck_dir(deck);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let created = Local::now().naive_local();
+        let path = dir.join(format!("{}.md", created.format(TIMESTAMP_FORMAT)));
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(Some(DeckVersion { path, created }))
+    }
+
+    /// Snapshots of `deck`, oldest first.
+    pub fn list(&self, deck: &Path) -> Result<Vec<DeckVersion>> {
+        let dir = self.deck_dir(deck);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", dir.display())),
+        };
+        let mut versions: Vec<DeckVersion> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let stem = path.file_stem()?.to_str()?;
+                let created = NaiveDateTime::parse_from_str(stem, TIMESTAMP_FORMAT).ok()?;
+                Some(DeckVersion { path, created })
+            })
+            .collect();
+        versions.sort_by_key(|v| v.created);
+        Ok(versions)
+    }
+
+    /// Replace `deck` with `version`, snapshotting the current content first
+    /// so the restore itself can be undone.
+    pub fn restore(&self, deck: &Path, version: &DeckVersion) -> Result<()> {
+        if deck.exists() {
+            self.snapshot(deck)?;
+        }
+        let content = version.read()?;
+        std::fs::write(deck, content).with_context(|| format!("failed to write {}", deck.display()))
+    }
+}
+
+/// Absolute form of `path` with symlinks followed as far as it exists, so
+/// `talk.md` and `./slides/../talk.md` name the same deck.
+fn resolve(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Ok(canonical) = absolute.canonicalize() {
+        return canonical;
+    }
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or(absolute),
+        _ => absolute,
+    }
+}
+
+/// `%` and path separators as `%XX`, so a relative path is one file name.
+fn escape_separators(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            '%' => out.push_str("%25"),
+            '/' => out.push_str("%2F"),
+            '\\' => out.push_str("%5C"),
+            ':' => out.push_str("%3A"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Line diff from `old` to `new` with three lines of context, in unified
+/// format without file headers.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .to_string()
+}
+
+/// Number of added and removed lines from `old` to `new`.
+pub fn diff_stat(old: &str, new: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_skip_unchanged_content_and_restore() -> Result<()> {
+        let tmp = std::env::temp_dir().join(format!("slide-versions-{}", std::process::id()));
+        let deck = tmp.join("talk.md");
+        std::fs::create_dir_all(&tmp)?;
+        let store = VersionStore::new(&tmp);
+
+        std::fs::write(&deck, "## One\n")?;
+        assert!(store.snapshot(&deck)?.is_some());
+        assert!(store.snapshot(&deck)?.is_none());
+        // Timestamps have millisecond resolution.
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        std::fs::write(&deck, "## One\n## Two\n")?;
+        assert!(store.snapshot(&deck)?.is_some());
+
+        let versions = store.list(&deck)?;
+        assert_eq!(versions.len(), 2);
+        assert!(store.deck_dir(&deck).ends_with(".slide/versions/talk"));
+
+        store.restore(&deck, &versions[0])?;
+        assert_eq!(std::fs::read_to_string(&deck)?, "## One\n");
+        assert_eq!(store.list(&deck)?.len(), 2);
+
+        std::fs::remove_dir_all(&tmp)?;
+        Ok(())
+    }
+
+    #[test]
+    fn decks_sharing_a_file_name_keep_separate_histories() -> Result<()> {
+        let tmp = std::env::temp_dir().join(format!("slide-versions-stem-{}", std::process::id()));
+        let a = tmp.join("slides/a/talk.md");
+        let b = tmp.join("slides/b/talk.md");
+        std::fs::create_dir_all(tmp.join("slides/a"))?;
+        std::fs::create_dir_all(tmp.join("slides/b"))?;
+        let store = VersionStore::new(&tmp);
+
+        std::fs::write(&a, "## A\n")?;
+        std::fs::write(&b, "## B\n")?;
+        assert!(store.snapshot(&a)?.is_some());
+        assert!(store.snapshot(&b)?.is_some());
+        assert_ne!(store.deck_dir(&a), store.deck_dir(&b));
+        assert!(store
+            .deck_dir(&a)
+            .ends_with(".slide/versions/slides%2Fa%2Ftalk"));
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        std::fs::write(&b, "## B edited\n")?;
+        let versions = store.list(&b)?;
+        assert_eq!(versions.len(), 1);
+        store.restore(&b, &versions[0])?;
+        assert_eq!(std::fs::read_to_string(&b)?, "## B\n");
+        assert_eq!(std::fs::read_to_string(&a)?, "## A\n");
+
+        std::fs::remove_dir_all(&tmp)?;
+        Ok(())
+    }
+
+    #[test]
+    fn diff_counts_changed_lines() {
+        let old = "a\nb\nc\n";
+        let new = "a\nB\nc\nd\n";
+        assert_eq!(diff_stat(old, new), (2, 1));
+        assert!(unified_diff(old, new).contains("-b\n+B\n"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Per-project UI state kept across runs in `.slide/state.json`: recently
+//! used decks, the agent's last directory and model, the deck shown in the
+//! split layout and the slide each deck was left at.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// State file, relative to the workspace.
+pub const STATE_FILE: &str = ".slide/state.json";
+
+/// Decks remembered in [`WorkspaceState::recent_files`]
+pub const MAX_RECENT_FILES: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceState {
+    /// Decks opened or edited, most recent first
+    pub recent_files: Vec<String>,
+    /// Directory the agent's commands ran in, when not the workspace
+    pub cwd: Option<PathBuf>,
+    /// Model picked with `/model`
+    pub model: Option<String>,
+    /// Deck shown beside the composer (`/split`)
+    pub open_deck: Option<PathBuf>,
+    /// Slide each deck was left at in the preview or the split layout
+    pub slide_positions: BTreeMap<String, usize>,
+}
+
+impl WorkspaceState {
+    pub fn path(workspace: impl AsRef<Path>) -> PathBuf {
+        workspace.as_ref().join(STATE_FILE)
+    }
+
+    /// State saved for `workspace`; empty when there is none, when the file
+    /// cannot be read or when history is disabled by the privacy settings.
+    pub fn load(workspace: impl AsRef<Path>) -> Self {
+        if !crate::privacy::active().history_enabled() {
+            return Self::default();
+        }
+        std::fs::read_to_string(Self::path(workspace))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state for `workspace`. Does nothing when history is
+    /// disabled by the privacy settings.
+    pub fn save(&self, workspace: impl AsRef<Path>) -> Result<()> {
+        if !crate::privacy::active().history_enabled() {
+            return Ok(());
+        }
+        let path = Self::path(workspace);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Move `file` to the front of the recent files.
+    pub fn touch(&mut self, file: impl Into<String>) {
+        let file = file.into();
+        self.recent_files.retain(|f| f != &file);
+        self.recent_files.insert(0, file);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Slide `deck` was left at, 0 when unknown.
+    pub fn slide_position(&self, deck: &Path) -> usize {
+        self.slide_positions
+            .get(&deck.display().to_string())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_slide_position(&mut self, deck: &Path, slide: usize) {
+        let key = deck.display().to_string();
+        if slide == 0 {
+            self.slide_positions.remove(&key);
+        } else {
+            self.slide_positions.insert(key, slide);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_round_trips_and_keeps_the_latest_files_first() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-state-{}", std::process::id()));
+        assert_eq!(WorkspaceState::load(&dir), WorkspaceState::default());
+
+        let mut state = WorkspaceState {
+            model: Some("gpt-4o".to_string()),
+            open_deck: Some(PathBuf::from("slides/talk.md")),
+            ..Default::default()
+        };
+        for i in 0..=MAX_RECENT_FILES {
+            state.touch(format!("slides/{i}.md"));
+        }
+        state.touch("slides/3.md");
+        state.set_slide_position(Path::new("slides/talk.md"), 4);
+        state.save(&dir)?;
+
+        let loaded = WorkspaceState::load(&dir);
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.recent_files.len(), MAX_RECENT_FILES);
+        assert_eq!(loaded.recent_files[0], "slides/3.md");
+        assert_eq!(loaded.recent_files[1], "slides/10.md");
+        assert_eq!(loaded.slide_position(Path::new("slides/talk.md")), 4);
+        assert_eq!(loaded.slide_position(Path::new("slides/other.md")), 0);
+
+        std::fs::write(WorkspaceState::path(&dir), "not json")?;
+        assert_eq!(WorkspaceState::load(&dir), WorkspaceState::default());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
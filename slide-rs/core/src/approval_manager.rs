@@ -1,26 +1,17 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use slide_apply_patch::ApplyPatchFileChange;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
+use std::path::PathBuf;
 
-/// Approval policy for AI commands and tool usage
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AskForApproval {
-    /// Ask for approval unless the command is explicitly trusted
-    UnlessTrusted,
-    /// Ask for approval only when a command fails and needs escalation
-    OnFailure,
-    /// Ask for approval on every request that requires elevated permissions
-    OnRequest,
-    /// Never ask for approval (auto-approve everything)
-    Never,
-}
+use crate::is_safe_command::shell_script;
+use crate::shell_analysis::analyze;
 
-impl Default for AskForApproval {
-    fn default() -> Self {
-        AskForApproval::OnRequest
-    }
-}
+/// Approval policy for AI commands and tool usage; parsed and shown by the
+/// shared [`ApprovalMode`](slide_common::ApprovalMode)
+pub use slide_common::ApprovalMode as AskForApproval;
 
 /// Manages the approval workflow for commands and operations
 #[derive(Debug, Clone)]
@@ -28,6 +19,8 @@ pub struct ApprovalManager {
     policy: AskForApproval,
     approved_commands: HashSet<Vec<String>>,
     trusted_commands: HashSet<String>,
+    /// Commands starting with one of these run without asking
+    allowed_prefixes: Vec<Vec<String>>,
 }
 
 impl Default for ApprovalManager {
@@ -46,6 +39,7 @@ impl Default for ApprovalManager {
             policy: AskForApproval::default(),
             approved_commands: HashSet::new(),
             trusted_commands,
+            allowed_prefixes: Vec::new(),
         }
     }
 }
@@ -63,6 +57,9 @@ impl ApprovalManager {
         if command.is_empty() {
             return true;
         }
+        if self.is_pre_approved(command) || self.matches_allowed_prefix(command) {
+            return false;
+        }
 
         match self.policy {
             AskForApproval::Never => false,
@@ -100,6 +97,33 @@ impl ApprovalManager {
         self.trusted_commands.insert(command);
     }
 
+    /// Run every command starting with `prefix` without asking
+    pub fn allow_prefix(&mut self, prefix: Vec<String>) {
+        if !prefix.is_empty() && !self.allowed_prefixes.contains(&prefix) {
+            self.allowed_prefixes.push(prefix);
+        }
+    }
+
+    /// Whether `command` starts with an allowed prefix. A shell script
+    /// matches when each of its commands does and it does nothing else
+    /// (substitutions, redirects to files, ...).
+    pub fn matches_allowed_prefix(&self, command: &[String]) -> bool {
+        let allowed = |words: &[String]| {
+            self.allowed_prefixes
+                .iter()
+                .any(|prefix| words.starts_with(prefix))
+        };
+        match shell_script(command) {
+            Some((script, dialect)) => analyze(script, dialect).is_ok_and(|portions| {
+                !portions.is_empty()
+                    && portions
+                        .iter()
+                        .all(|portion| portion.concerns.is_empty() && allowed(&portion.words))
+            }),
+            None => allowed(command),
+        }
+    }
+
     /// Remove a command from the approved list
     pub fn revoke_approval(&mut self, command: &[String]) {
         self.approved_commands.remove(command);
@@ -181,6 +205,29 @@ impl ApprovalRequest {
     }
 }
 
+/// Prefix offered for "always allow": the program and, for tools with
+/// subcommands (`git status`, `cargo test`), the subcommand. `None` for a
+/// shell script that does more than run one plain command.
+pub fn approval_prefix(command: &[String]) -> Option<Vec<String>> {
+    let words = match shell_script(command) {
+        Some((script, dialect)) => match analyze(script, dialect).ok()?.as_slice() {
+            [portion] if portion.concerns.is_empty() => portion.words.clone(),
+            _ => return None,
+        },
+        None => command.to_vec(),
+    };
+    let mut prefix = vec![words.first()?.clone()];
+    if let Some(subcommand) = words.get(1).filter(|word| {
+        !word.starts_with('-')
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }) {
+        prefix.push(subcommand.clone());
+    }
+    Some(prefix)
+}
+
 /// Response to an approval request
 #[derive(Debug, Clone, PartialEq)]
 pub enum ApprovalResponse {
@@ -190,10 +237,60 @@ pub enum ApprovalResponse {
     Denied,
     /// User approved and wants to trust this command going forward
     ApprovedAndTrust,
+    /// User approved every command starting with this prefix for the session
+    ApprovedPrefix(Vec<String>),
     /// User wants to modify the approval policy
     ChangePolicy(AskForApproval),
 }
 
+/// A proposed `apply_patch` edit, keyed by the absolute path of each file.
+#[derive(Debug, Clone)]
+pub struct PatchApprovalRequest {
+    pub changes: HashMap<PathBuf, ApplyPatchFileChange>,
+    pub reason: Option<String>,
+}
+
+/// Response to a [`PatchApprovalRequest`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchApprovalResponse {
+    /// Apply every file in the patch
+    Approved,
+    /// Apply only these files; the rest of the patch is dropped
+    ApprovedFiles(Vec<PathBuf>),
+    /// Apply nothing
+    Denied,
+}
+
+/// Several commands proposed in one response, approved together.
+#[derive(Debug, Clone)]
+pub struct PlanApprovalRequest {
+    pub commands: Vec<Vec<String>>,
+    pub cwd: PathBuf,
+}
+
+/// Response to a [`PlanApprovalRequest`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanApprovalResponse {
+    /// Run every command
+    Approved,
+    /// Run only the commands at these indices; the rest are skipped
+    ApprovedCommands(Vec<usize>),
+    /// Run nothing that needs approval
+    Denied,
+}
+
+/// Asks the user to decide on an [`ApprovalRequest`] and waits for the answer.
+#[async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    async fn request_approval(&self, request: ApprovalRequest) -> ApprovalResponse;
+
+    /// Lists the commands of a plan and waits for a per-command decision.
+    async fn request_plan_approval(&self, request: PlanApprovalRequest) -> PlanApprovalResponse;
+
+    /// Shows the diff of each file in a patch and waits for a per-file decision.
+    async fn request_patch_approval(&self, request: PatchApprovalRequest) -> PatchApprovalResponse;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +318,41 @@ mod tests {
         assert!(manager.needs_approval(&["ls".to_string()], true)); // escalated permissions
     }
 
+    #[test]
+    fn session_approvals_and_prefixes_skip_the_prompt() {
+        let argv = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        let mut manager = ApprovalManager::new(AskForApproval::OnRequest);
+        manager.approve_command(argv(&["make", "deck"]));
+        assert!(!manager.needs_approval(&argv(&["make", "deck"]), false));
+        assert!(manager.needs_approval(&argv(&["make", "clean"]), false));
+
+        assert_eq!(
+            approval_prefix(&argv(&["bash", "-lc", "git status --short"])),
+            Some(argv(&["git", "status"]))
+        );
+        assert_eq!(
+            approval_prefix(&argv(&["python3", "build.py"])),
+            Some(argv(&["python3"]))
+        );
+        assert_eq!(
+            approval_prefix(&argv(&["bash", "-lc", "git add . && git commit"])),
+            None
+        );
+
+        manager.allow_prefix(argv(&["git", "status"]));
+        assert!(!manager.needs_approval(&argv(&["git", "status", "-s"]), false));
+        assert!(!manager.needs_approval(&argv(&["bash", "-lc", "git status | git status"]), false));
+        assert!(manager.needs_approval(&argv(&["bash", "-lc", "git status > out.txt"]), false));
+        assert!(manager.needs_approval(&argv(&["git", "push"]), false));
+    }
+
+    #[test]
+    fn test_policy_labels() {
+        assert_eq!("untrusted".parse(), Ok(AskForApproval::UnlessTrusted));
+        assert_eq!("never".parse(), Ok(AskForApproval::Never));
+        assert!("sometimes".parse::<AskForApproval>().is_err());
+    }
+
     #[test]
     fn test_command_approval() {
         let mut manager = ApprovalManager::default();
@@ -1,6 +1,9 @@
+use crate::mock_provider::MockProvider;
 use anyhow::Result;
 use async_trait::async_trait;
+use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
+use tracing::warn;
 
 #[derive(Debug, Clone)]
 pub enum ResponseEvent {
@@ -12,6 +15,16 @@ pub enum ResponseEvent {
 #[async_trait]
 pub trait ModelClient {
     async fn stream(&self, prompt: String) -> Result<Receiver<ResponseEvent>>;
+
+    /// Model name shown in the status bar
+    fn model(&self) -> &str {
+        "stub"
+    }
+
+    /// Provider serving the model
+    fn provider(&self) -> &str {
+        "local"
+    }
 }
 
 /// A very small stub client for testing the flow.
@@ -66,4 +79,183 @@ impl ModelClient for OpenAiAdapter {
         });
         Ok(rx)
     }
+
+    fn model(&self) -> &str {
+        &self.inner.model
+    }
+
+    fn provider(&self) -> &str {
+        "openai"
+    }
+}
+
+/// Pick the model client from the environment: OpenAI when `OPENAI_API_KEY`
+/// is set or `slide login` stored a key (model from `model` or
+/// `SLIDE_MODEL`), otherwise the stub.
+/// `SLIDE_FORCE_STUB=1` forces the stub for offline/dev/demo use.
+/// A model named `mock:<fixture>` replays a script
+/// ([`MockProvider`](crate::mock_provider::MockProvider)) and needs no key.
+pub fn client_from_env(model: Option<String>) -> Arc<dyn ModelClient + Send + Sync> {
+    let model = model.or_else(|| std::env::var("SLIDE_MODEL").ok());
+    if let Some(mock) = model.as_deref().and_then(MockProvider::from_model) {
+        return match mock {
+            Ok(mock) => Arc::new(mock),
+            Err(e) => {
+                warn!(error = %e, "cannot load the mock model");
+                Arc::new(MockProvider::failing("unavailable", format!("{e:#}")))
+            }
+        };
+    }
+    if stub_forced() {
+        return Arc::new(StubClient);
+    }
+    match slide_common::auth::openai_api_key() {
+        Some(key) => match model {
+            Some(m) => Arc::new(OpenAiAdapter::new_with_model(key, m)),
+            None => Arc::new(OpenAiAdapter::new(key)),
+        },
+        None => Arc::new(StubClient),
+    }
+}
+
+/// [`client_from_env`] for commands that write their output to disk: the
+/// stub is used only when asked for (`SLIDE_FORCE_STUB` or a `mock:` model),
+/// never in place of a missing key.
+pub fn configured_client_from_env(
+    model: Option<String>,
+) -> Result<Arc<dyn ModelClient + Send + Sync>> {
+    let model = model.or_else(|| std::env::var("SLIDE_MODEL").ok());
+    let mock = model
+        .as_deref()
+        .is_some_and(|m| m.starts_with(crate::mock_provider::MOCK_PREFIX));
+    if !mock && !stub_forced() && slide_common::auth::openai_api_key().is_none() {
+        anyhow::bail!("no API key: run `slide login` or set OPENAI_API_KEY");
+    }
+    Ok(client_from_env(model))
+}
+
+/// `SLIDE_FORCE_STUB=1` (or `true`)
+fn stub_forced() -> bool {
+    std::env::var("SLIDE_FORCE_STUB").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Check `api_key` against `provider` before `slide login` stores it.
+pub async fn validate_api_key(provider: &str, api_key: &str) -> Result<()> {
+    match provider {
+        "openai" => slide_chatgpt::validate_api_key(api_key).await,
+        other => Err(anyhow::anyhow!("unsupported provider: {other}")),
+    }
+}
+
+/// Longest suggestion kept, in characters
+const MAX_SUGGESTION_CHARS: usize = 120;
+
+/// Proposes the rest of the message being typed in the composer, for the
+/// ghost text accepted with Tab. Uses its own (small) model so suggestions
+/// do not compete with the agent's turns.
+pub struct SuggestionService {
+    client: Arc<dyn ModelClient + Send + Sync>,
+}
+
+impl SuggestionService {
+    /// Where the text being typed is sent
+    pub const ENDPOINT: &'static str = slide_chatgpt::client::CHAT_COMPLETIONS_URL;
+
+    pub fn new(client: Arc<dyn ModelClient + Send + Sync>) -> Self {
+        Self { client }
+    }
+
+    /// Service asking `model`; `None` without an API key or with
+    /// `SLIDE_FORCE_STUB`, since the stub can only echo.
+    pub fn from_env(model: &str) -> Option<Self> {
+        let force_stub = std::env::var("SLIDE_FORCE_STUB")
+            .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        if force_stub {
+            return None;
+        }
+        let key = slide_common::auth::openai_api_key()?;
+        Some(Self::new(Arc::new(OpenAiAdapter::new_with_model(
+            key,
+            model.to_string(),
+        ))))
+    }
+
+    /// Text to append to `text`, if the model proposes any.
+    pub async fn suggest(&self, text: &str) -> Result<Option<String>> {
+        let mut rx = self.client.stream(suggestion_prompt(text)).await?;
+        let mut reply = String::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                ResponseEvent::TextDelta(delta) => reply.push_str(&delta),
+                ResponseEvent::Completed => break,
+                ResponseEvent::Error(message) => return Err(anyhow::anyhow!(message)),
+            }
+        }
+        Ok(clean_suggestion(text, &reply))
+    }
+}
+
+fn suggestion_prompt(text: &str) -> String {
+    format!(
+        "You complete messages a user is typing to an assistant that writes slide decks.\n\
+         Reply with only the words that continue the message below, on one line, \
+         without repeating it. Reply with nothing if it looks finished.\n\n\
+         Message so far:\n{text}"
+    )
+}
+
+/// First line of `reply` without an echo of `text` or surrounding quotes.
+/// Leading whitespace is kept: it tells a new word from the end of one.
+fn clean_suggestion(text: &str, reply: &str) -> Option<String> {
+    let line = reply.trim_start_matches(['\r', '\n']).lines().next()?;
+    let line = line.trim_end().trim_matches(|c| c == '"' || c == '`');
+    // Some models repeat the message before continuing it
+    let typed = text.trim();
+    let line = match line.trim_start().strip_prefix(typed) {
+        Some(rest) if !typed.is_empty() => rest,
+        _ => line,
+    };
+    let line = if text.ends_with(char::is_whitespace) {
+        line.trim_start()
+    } else {
+        line
+    };
+    let suggestion: String = line.chars().take(MAX_SUGGESTION_CHARS).collect();
+    (!suggestion.trim().is_empty()).then_some(suggestion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Canned(&'static str);
+
+    #[async_trait]
+    impl ModelClient for Canned {
+        async fn stream(&self, _prompt: String) -> Result<Receiver<ResponseEvent>> {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            let _ = tx.send(ResponseEvent::TextDelta(self.0.to_string())).await;
+            let _ = tx.send(ResponseEvent::Completed).await;
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn suggestions_keep_the_first_line_of_the_continuation() -> Result<()> {
+        let service = SuggestionService::new(Arc::new(Canned(" about Rust\nand more")));
+        assert_eq!(
+            service.suggest("Make a deck").await?.as_deref(),
+            Some(" about Rust")
+        );
+        assert_eq!(
+            clean_suggestion("Make a deck ", "\"Make a deck about Rust\""),
+            Some("about Rust".to_string())
+        );
+        assert_eq!(
+            clean_suggestion("Make a sli", "des about Rust"),
+            Some("des about Rust".to_string())
+        );
+        assert_eq!(clean_suggestion("Thanks!", "\n  \n"), None);
+        Ok(())
+    }
 }
@@ -1,27 +1,190 @@
 use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tokio::sync::Mutex;
+use tokio::sync::Notify;
 
+use crate::approval_manager::{
+    approval_prefix, ApprovalHandler, ApprovalRequest, ApprovalResponse, PatchApprovalRequest,
+    PatchApprovalResponse, PlanApprovalRequest, PlanApprovalResponse,
+};
 use crate::client::{ModelClient, ResponseEvent};
+use crate::custom_prompts::{default_prompts_dir, discover_prompts_in};
+use crate::git_status::{GitStatus, GIT_STATUS_INTERVAL};
+use crate::guardrails::{GuardrailLimit, GuardrailTrip};
+use crate::is_safe_command::describe_risk;
 use crate::openai_tools::{render_tools_instructions, ToolsConfig, ToolsConfigParams};
-use crate::tool_executor::ToolExecutor;
-use slide_chatgpt::client::{ChatGptClient, SlideRequest};
-use tracing::info;
+use crate::plan_tool::{step_changes, PlanGate, PlanManager, PlanStore, StepChange};
+use crate::project_doc::{compose_instructions, discover_project_docs};
+use crate::slide_generate::{generate_slides, SlideGenerateOptions};
+use crate::telemetry;
+use crate::tool_env_info::EnvInfoCache;
+use crate::tool_web_fetch::FetchedSources;
+use crate::tool_executor::{ExecProgress, ToolExecutor};
+use crate::turn_retry::TurnRetry;
+use crate::turn_snapshot::{TurnSnapshot, MAX_TURN_SNAPSHOTS};
+use crate::workspace_context::collect_workspace_context;
+use crate::workspace_guard::resolve_cwd;
+use protocol::custom_prompts::CustomPrompt;
+use slide_common::privacy::{DataFlow, DataFlowRegistry, DataSink};
+use slide_common::ApprovalTimeoutAction;
+use tracing::{info, warn};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub use crate::approval_manager::AskForApproval;
+pub use crate::parse_command::{parse_command, ParsedCommand};
+pub use crate::plan_tool::{PlanItem, PlanRecord, StepStatus, UpdatePlanArgs};
+pub use crate::tool_executor::ExecOutputStream;
+pub use protocol::config_types::SandboxPolicy;
+pub use slide_apply_patch::{ApplyPatchFileChange, DiffStat, FileDiffStat};
+
+/// Register the data the agent loop writes on its own (tool writes are
+/// approved per call and therefore not listed).
+pub fn register_data_flows(registry: &mut DataFlowRegistry, config: &slide_common::SlideConfig) {
+    slide_chatgpt::client::register_data_flows(registry, config);
+    registry.register(DataFlow {
+        subsystem: "core.slide-draft",
+        description: "Markdown produced by the `/slide` shortcut",
+        sink: DataSink::File(PathBuf::from("slides").join("draft.md")),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "core.image-assets",
+        description: "Images downloaded or generated by the `image` tool (after approval)",
+        sink: DataSink::File(PathBuf::from(crate::tool_image::ASSETS_DIR)),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "core.deck-versions",
+        description: "A copy of each deck the agent writes, for `slide history` and `/undo`",
+        sink: DataSink::File(PathBuf::from(slide_common::versions::VERSIONS_DIR)),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "core.turn-revert",
+        description: "Decks as they were before `/undo-turn` put back the turn that changed them",
+        sink: DataSink::File(PathBuf::from(slide_common::versions::VERSIONS_DIR)),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "core.web-fetch",
+        description: "Requests for pages read by the `web_fetch` tool (after approval)",
+        sink: DataSink::Network("the http(s) URL named in the tool call".to_string()),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "core.plans",
+        description: "Plan steps, their status and tool output, for `slide plan resume`",
+        sink: DataSink::File(PathBuf::from(crate::plan_tool::PLANS_DIR)),
+        active: true,
+    });
+    if let Some(endpoint) = telemetry::endpoint() {
+        registry.register(DataFlow {
+            subsystem: "core.telemetry",
+            description: "Spans and counters (models, tools, tokens, cost, approval answers)",
+            sink: DataSink::Network(endpoint),
+            active: true,
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     Approved,
+    /// Run this exact command again without asking, for the session
     ApprovedForSession,
+    /// Run every command starting with the request's `prefix` without
+    /// asking, for the session
+    ApprovedPrefix,
+    /// Apply only these files of a patch; the others are skipped
+    ApprovedFiles(Vec<PathBuf>),
+    /// Run only these commands of a plan, by index; the others are skipped
+    ApprovedCommands(Vec<usize>),
     Denied,
     Abort,
 }
 
-#[derive(Debug, Clone)]
+/// Token usage of one turn. The chat stream does not report usage, so the
+/// counts are estimated from the prompt and reply text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn estimate(prompt: &str, reply: &str) -> Self {
+        Self {
+            input_tokens: estimate_tokens(prompt),
+            output_tokens: estimate_tokens(reply),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Spend for this usage in USD; `None` for models without a known
+    /// price.
+    pub fn estimated_cost_usd(&self, provider: &str, model: &str) -> Option<f64> {
+        match provider {
+            "local" | "mock" => Some(0.0),
+            "openai" => OPENAI_PRICES
+                .iter()
+                .find(|(prefix, _, _)| model.starts_with(prefix))
+                .map(|(_, input, output)| {
+                    (self.input_tokens as f64 * input + self.output_tokens as f64 * output)
+                        / 1_000_000.0
+                }),
+            _ => None,
+        }
+    }
+}
+
+/// USD per million input and output tokens for the OpenAI models we know.
+/// Longer prefixes come first so `gpt-5-mini` does not match `gpt-5`.
+const OPENAI_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-5-nano", 0.05, 0.40),
+    ("gpt-5-mini", 0.25, 2.00),
+    ("gpt-5", 1.25, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("o4-mini", 1.10, 4.40),
+    ("o3", 2.00, 8.00),
+];
+
+/// Roughly four characters per token for English text and code.
+fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
-    SessionConfigured {},
+    /// Sent at startup and whenever the model changes
+    SessionConfigured {
+        model: String,
+        provider: String,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+        /// `SLIDE.md`/`AGENTS.md` files merged into the instructions, in
+        /// merge order
+        #[serde(default)]
+        instruction_files: Vec<PathBuf>,
+        /// Started with `slide --read-only`: the sandbox is read-only and no
+        /// tool that writes files is offered
+        #[serde(default)]
+        read_only: bool,
+    },
     TaskStarted,
     AgentMessageDelta {
         delta: String,
@@ -30,34 +193,172 @@ pub enum Event {
         message: String,
     },
     ExecCommandBegin {
+        call_id: String,
         command: Vec<String>,
         cwd: PathBuf,
     },
+    /// A line of output from a running command
+    ExecCommandOutputDelta {
+        call_id: String,
+        stream: ExecOutputStream,
+        chunk: String,
+    },
     ExecCommandEnd {
+        call_id: String,
         exit_code: i32,
     },
     ApplyPatchApprovalRequest {
         id: String,
-        changes: HashMap<PathBuf, String>,
+        changes: HashMap<PathBuf, ApplyPatchFileChange>,
         reason: Option<String>,
+        /// Seconds left to answer before [`Event::ApprovalTimedOut`]
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+        /// Files of the patch that already have uncommitted changes
+        #[serde(default)]
+        dirty_files: Vec<PathBuf>,
     },
     PatchApplyBegin {},
     PatchApplyEnd {
         success: bool,
+        /// Lines added and removed in each applied file, in patch order
+        #[serde(default)]
+        files: Vec<FileDiffStat>,
+        #[serde(default)]
+        total: DiffStat,
     },
     TurnDiff {
         unified_diff: String,
     },
+    /// Files created or changed by `apply_patch` or `write_file`, for the
+    /// workspace's recent files
+    FilesTouched {
+        paths: Vec<PathBuf>,
+    },
+    /// Content the agent wrote to a deck was flagged by the `moderation`
+    /// config; when `blocked` it was not written
+    ContentFlagged {
+        path: PathBuf,
+        /// Matched terms, quoted, and categories the provider flagged
+        reasons: Vec<String>,
+        blocked: bool,
+    },
+    /// The agent's task plan changed
+    PlanUpdate(UpdatePlanArgs),
+    /// Branch and uncommitted files of the repository the session runs in;
+    /// sent at startup, when they change and after each patch
+    GitStatus(GitStatus),
+    /// A plan step went in progress; follows the `PlanUpdate` that started it
+    PlanStepBegin {
+        index: usize,
+        step: String,
+    },
+    /// A plan step completed or failed
+    PlanStepEnd {
+        index: usize,
+        step: String,
+        status: StepStatus,
+    },
+    /// Tool calls wait until `Op::ResumePlan`
+    PlanPaused,
+    PlanResumed,
+    /// Commands now run in `cwd`; also sent at startup
+    CwdChanged {
+        cwd: PathBuf,
+    },
+    /// An `Op::SetCwd` that was refused
+    CwdRejected {
+        path: PathBuf,
+        reason: String,
+    },
+    /// A limit of the `guardrails` config was reached; the remaining tool
+    /// calls of the turn were not run
+    GuardrailTripped {
+        limit: GuardrailLimit,
+        /// Calls, seconds or bytes used in the turn
+        used: u64,
+        max: u64,
+        /// Which limit was hit and how to go on
+        message: String,
+    },
+    /// The model request failed in a way that may pass; the same prompt is
+    /// sent again after `delay_ms`. Text streamed before the failure is
+    /// replaced by the new reply. `Error` follows once `max_retries` are
+    /// used up.
+    TurnRetrying {
+        /// 1 for the first retry
+        attempt: u32,
+        max_retries: u32,
+        delay_ms: u64,
+        error: String,
+    },
+    /// Tokens spent by the turn that just finished
+    TokenCount {
+        usage: TokenUsage,
+    },
     TaskComplete,
+    /// The running turn was stopped by `Op::Interrupt`; no `TaskComplete` follows
+    TurnAborted,
+    /// Answer to `Op::RevertTurn`. Both lists are empty when no turn was
+    /// left to revert.
+    TurnReverted {
+        /// Files put back to their content before the turn, or removed when
+        /// the turn created them
+        restored: Vec<PathBuf>,
+        /// Why files could not be put back
+        #[serde(default)]
+        failed: Vec<String>,
+    },
     Error {
         message: String,
     },
+    /// Answer to `Op::Shutdown`: the running turn and its commands are
+    /// stopped and the plan state is saved. No event follows.
     ShutdownComplete,
     ExecApprovalRequest {
         id: String,
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        /// What the command does, for the summary in the approval modal
+        #[serde(default)]
+        parsed_cmd: Vec<ParsedCommand>,
+        /// Sandbox policy the command would run under
+        #[serde(default)]
+        sandbox: Option<String>,
+        /// The command needs permissions the sandbox policy does not grant
+        #[serde(default)]
+        escalated: bool,
+        /// Why the safety checks did not let the command run on its own
+        #[serde(default)]
+        risk: Option<String>,
+        /// Prefix approved by `ReviewDecision::ApprovedPrefix`; `None` when
+        /// the command cannot be allowed by prefix
+        #[serde(default)]
+        prefix: Option<Vec<String>>,
+        /// Seconds left to answer before [`Event::ApprovalTimedOut`]
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    /// Several commands from one response, approved together. Answered with
+    /// `Op::ExecApproval`.
+    ExecPlanApprovalRequest {
+        id: String,
+        commands: Vec<Vec<String>>,
+        cwd: PathBuf,
+        /// Seconds left to answer before [`Event::ApprovalTimedOut`]
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    /// Nobody answered approval request `id` within `approvals.timeout_secs`;
+    /// it was denied, and with `abort_turn` the turn stops
+    ApprovalTimedOut {
+        id: String,
+        action: ApprovalTimeoutAction,
+    },
+    /// Answer to `Op::ListCustomPrompts`, sorted by name
+    ListCustomPromptsResponse {
+        custom_prompts: Vec<CustomPrompt>,
     },
 }
 
@@ -66,7 +367,34 @@ pub enum Op {
     UserInput {
         text: String,
     },
+    /// Stop the running turn, if any
     Interrupt,
+    /// Hold the agent before its next tool call
+    PausePlan,
+    ResumePlan,
+    /// Use another model for the following turns
+    OverrideModel {
+        model: String,
+    },
+    /// Ask for approval by another policy from the following turns on
+    OverrideApprovalPolicy {
+        approval_policy: AskForApproval,
+    },
+    /// Load a saved plan and start a turn that continues from its first step
+    /// that is not completed.
+    ContinuePlan {
+        record: PlanRecord,
+    },
+    /// Replace the conversation memory, e.g. when resuming a saved session.
+    /// Messages are (role, text), oldest first.
+    RestoreConversation {
+        messages: Vec<(String, String)>,
+    },
+    /// Run the following turns' commands in `path` (relative to the current
+    /// directory). Leaving the workspace needs the user's approval.
+    SetCwd {
+        path: PathBuf,
+    },
     ExecApproval {
         id: String,
         decision: ReviewDecision,
@@ -75,6 +403,16 @@ pub enum Op {
         id: String,
         decision: ReviewDecision,
     },
+    /// Put the files changed by the last turn that changed any back as they
+    /// were before it, after the running turn; answered with
+    /// `TurnReverted`. Repeating it reverts earlier turns. Only files written
+    /// by tools are restored, not changes made by shell commands.
+    RevertTurn,
+    /// Load the prompt templates in `~/.slide/prompts`; answered with
+    /// `ListCustomPromptsResponse`.
+    ListCustomPrompts,
+    /// Stop the running turn and its commands, drop queued input, save the
+    /// plan state and end the session; answered with `ShutdownComplete`.
     Shutdown,
 }
 
@@ -99,302 +437,275 @@ impl Codex {
         let (tx_submit, mut rx_submit) = mpsc::channel::<Op>(64);
         let (tx_event, rx_event) = mpsc::channel::<Event>(256);
 
+        let workspace = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let approval_policy = AskForApproval::from_env();
         // Send initial configured event to signal readiness
-        let _ = tx_event.send(Event::SessionConfigured {}).await;
+        let _ = tx_event
+            .send(session_configured(
+                client.as_ref(),
+                &workspace,
+                approval_policy.unwrap_or_default(),
+            ))
+            .await;
 
-        // Background task processing submissions
+        // Turns run one at a time on their own task so the submission loop
+        // stays free to route approval decisions while a turn is waiting.
+        let (tx_turn, mut rx_turn) = mpsc::channel::<TurnInput>(16);
+        let turn_tx_event = tx_event.clone();
+        let interrupt = Arc::new(Notify::new());
+        let turn_interrupt = interrupt.clone();
+
+        let approval_config = slide_common::SlideConfig::load()
+            .await
+            .map(|config| config.approvals)
+            .unwrap_or_default();
+        let pending_approvals: PendingApprovals = Arc::new(Mutex::new(HashMap::new()));
+        let approvals: Arc<dyn ApprovalHandler> = Arc::new(EventApprovalHandler {
+            tx_event: tx_event.clone(),
+            pending: pending_approvals.clone(),
+            timeout: approval_config.timeout_secs.map(Duration::from_secs),
+            on_timeout: approval_config.on_timeout,
+            interrupt: interrupt.clone(),
+            workspace: workspace.clone(),
+        });
+        let git_refresh = Arc::new(Notify::new());
+        watch_git_status(workspace.clone(), tx_event.clone(), git_refresh.clone());
+        // notify_one keeps the signal for a turn task that is not waiting yet
+        let shutdown = Arc::new(Notify::new());
+        let turn_shutdown = shutdown.clone();
+        let gate = PlanGate::default();
+        let turn_gate = gate.clone();
+        let _ = tx_event
+            .send(Event::CwdChanged {
+                cwd: workspace.clone(),
+            })
+            .await;
+        let context_dir = workspace.clone();
+        let workspace_context =
+            tokio::task::spawn_blocking(move || collect_workspace_context(&context_dir))
+                .await
+                .ok()
+                .flatten();
+        let mut session = SessionContext {
+            approvals,
+            approval_policy,
+            cwd: workspace.clone(),
+            workspace,
+            workspace_context,
+            git_refresh,
+            env_info: EnvInfoCache::default(),
+            fetched_sources: FetchedSources::default(),
+        };
         tokio::spawn(async move {
-            let api_key = std::env::var("OPENAI_API_KEY").unwrap_or_default();
-            let slide_client = ChatGptClient::new(api_key);
+            let mut client = client;
             // Keep recent conversation messages (role, text). Oldest first.
             let mut convo: Vec<(String, String)> = Vec::new();
+            let mut plan = PlanManager::with_store(PlanStore::default());
+            // Files as they were before each turn that changed any, oldest first
+            let mut turn_snapshots: Vec<TurnSnapshot> = Vec::new();
+            loop {
+                let input = tokio::select! {
+                    biased;
+                    () = turn_shutdown.notified() => break,
+                    input = rx_turn.recv() => match input {
+                        Some(input) => input,
+                        None => break,
+                    },
+                };
+                let text = match input {
+                    TurnInput::User(text) => text,
+                    TurnInput::ContinuePlan(record) => {
+                        let prompt = record.resume_prompt();
+                        let update = plan.restore(record);
+                        let _ = turn_tx_event.send(Event::PlanUpdate(update)).await;
+                        prompt
+                    }
+                    TurnInput::Restore(messages) => {
+                        convo = messages;
+                        continue;
+                    }
+                    TurnInput::SetCwd(path) => {
+                        let event = match session.set_cwd(&path).await {
+                            Ok(cwd) => Event::CwdChanged { cwd },
+                            Err(reason) => Event::CwdRejected { path, reason },
+                        };
+                        let _ = turn_tx_event.send(event).await;
+                        continue;
+                    }
+                    TurnInput::Revert => {
+                        let report = turn_snapshots
+                            .pop()
+                            .map(|snapshot| snapshot.restore(&session.workspace))
+                            .unwrap_or_default();
+                        if !report.restored.is_empty() {
+                            // Tell the model its last edits are gone
+                            let files: Vec<String> = report
+                                .restored
+                                .iter()
+                                .map(|path| {
+                                    let path =
+                                        path.strip_prefix(&session.workspace).unwrap_or(path);
+                                    path.display().to_string()
+                                })
+                                .collect();
+                            convo.push((
+                                "user".to_string(),
+                                format!(
+                                    "(I reverted the file changes of your previous turn: {})",
+                                    files.join(", ")
+                                ),
+                            ));
+                        }
+                        let _ = turn_tx_event
+                            .send(Event::TurnReverted {
+                                restored: report.restored,
+                                failed: report.failed,
+                            })
+                            .await;
+                        continue;
+                    }
+                    TurnInput::Model(model) => {
+                        client = crate::client::client_from_env(Some(model));
+                        let _ = turn_tx_event
+                            .send(session_configured(
+                                client.as_ref(),
+                                &session.cwd,
+                                session.policies().0,
+                            ))
+                            .await;
+                        continue;
+                    }
+                    TurnInput::ApprovalPolicy(approval_policy) => {
+                        session.approval_policy = Some(approval_policy);
+                        let _ = turn_tx_event
+                            .send(session_configured(
+                                client.as_ref(),
+                                &session.cwd,
+                                approval_policy,
+                            ))
+                            .await;
+                        continue;
+                    }
+                };
+                // An interrupted exchange is forgotten so a retry starts clean
+                let saved = convo.clone();
+                let snapshot = Arc::new(std::sync::Mutex::new(TurnSnapshot::default()));
+                // Dropping the turn drops its model stream, pending approvals
+                // and child processes, which are killed on drop.
+                let aborted = tokio::select! {
+                    () = turn_shutdown.notified() => break,
+                    () = run_turn(
+                        &client,
+                        &turn_tx_event,
+                        &mut convo,
+                        &mut plan,
+                        &turn_gate,
+                        &session,
+                        &snapshot,
+                        text,
+                    ) => false,
+                    () = turn_interrupt.notified() => true,
+                };
+                // An interrupted turn may have changed files too
+                if let Ok(mut snapshot) = snapshot.lock() {
+                    if !snapshot.is_empty() {
+                        turn_snapshots.push(std::mem::take(&mut *snapshot));
+                        if turn_snapshots.len() > MAX_TURN_SNAPSHOTS {
+                            turn_snapshots.remove(0);
+                        }
+                    }
+                }
+                if aborted {
+                    convo = saved;
+                    let _ = turn_tx_event.send(Event::TurnAborted).await;
+                    // A pause ends with the turn so the next one does not hang
+                    if turn_gate.resume() {
+                        let _ = turn_tx_event.send(Event::PlanResumed).await;
+                    }
+                }
+            }
+            // A plan stopped halfway stays resumable with `slide plan resume`
+            if let Err(e) = plan.save() {
+                warn!(target: "slide.plans", error = %e, "failed to save plan");
+            }
+            let _ = turn_tx_event.send(Event::ShutdownComplete).await;
+        });
+
+        // Background task processing submissions
+        tokio::spawn(async move {
             while let Some(op) = rx_submit.recv().await {
                 match op {
                     Op::UserInput { text } => {
-                        let _ = tx_event.send(Event::TaskStarted).await;
-                        if let Some(prompt) = text.strip_prefix("/slide ") {
-                            match slide_client
-                                .generate_slides(SlideRequest {
-                                    prompt: prompt.to_string(),
-                                    num_slides: 6,
-                                    language: "ja".to_string(),
-                                })
-                                .await
-                            {
-                                Ok(resp) => {
-                                    for line in resp.markdown.lines() {
-                                        let delta = format!("{}\n", line);
-                                        let _ =
-                                            tx_event.send(Event::AgentMessageDelta { delta }).await;
-                                    }
-                                    let save_path = PathBuf::from("slides").join("draft.md");
-                                    if let Some(parent) = save_path.parent() {
-                                        let _ = std::fs::create_dir_all(parent);
-                                    }
-                                    if let Err(e) =
-                                        std::fs::write(&save_path, resp.markdown.as_bytes())
-                                    {
-                                        let _ = tx_event
-                                            .send(Event::Error {
-                                                message: format!("failed to save slides: {e}"),
-                                            })
-                                            .await;
-                                    } else {
-                                        let _ = tx_event
-                                            .send(Event::AgentMessage {
-                                                message: format!(
-                                                    "Saved to {}",
-                                                    save_path.display()
-                                                ),
-                                            })
-                                            .await;
-                                    }
-                                    let _ = tx_event.send(Event::TaskComplete).await;
-                                }
-                                Err(e) => {
-                                    let _ = tx_event
-                                        .send(Event::Error {
-                                            message: e.to_string(),
-                                        })
-                                        .await;
-                                }
-                            }
-                            continue;
+                        if tx_turn.send(TurnInput::User(text)).await.is_err() {
+                            break;
                         }
-                        // Prefix prompt with tool instructions so the model can propose edits/execs.
-                        let approval_hint = std::env::var("SLIDE_APPROVAL_MODE").ok();
-                        let tools_cfg = ToolsConfig::new(&ToolsConfigParams {
-                            include_plan_tool: true,
-                            include_apply_patch_tool: true,
-                            include_view_image_tool: false,
-                            include_web_search_request: false,
-                            use_streamable_shell_tool: true,
-                            include_slides_tools: true,
-                            approval_policy: crate::approval_manager::AskForApproval::default(),
-                            sandbox_policy: crate::seatbelt::SandboxPolicy::default(),
-                        });
-                        let tool_instructions =
-                            render_tools_instructions(&tools_cfg, approval_hint.as_deref());
-                        // Append user message to conversation memory
-                        convo.push(("user".to_string(), text.clone()));
-                        // Cap memory to recent N entries to fit token budget
-                        const MAX_HISTORY_MESSAGES: usize = 12; // messages, not turns
-                        if convo.len() > MAX_HISTORY_MESSAGES {
-                            let drop = convo.len() - MAX_HISTORY_MESSAGES;
-                            convo.drain(0..drop);
+                    }
+                    Op::OverrideModel { model } => {
+                        // Queued behind pending turns so a running turn keeps its model.
+                        if tx_turn.send(TurnInput::Model(model)).await.is_err() {
+                            break;
                         }
-                        // Render recent conversation as plain lines
-                        let mut history_block = String::new();
-                        if !convo.is_empty() {
-                            history_block.push_str("\n\nConversation so far:\n");
-                            for (role, msg) in &convo {
-                                let tag = if role == "assistant" {
-                                    "Assistant"
-                                } else {
-                                    "User"
-                                };
-                                history_block.push_str(tag);
-                                history_block.push_str(": ");
-                                history_block.push_str(msg);
-                                if !msg.ends_with('\n') {
-                                    history_block.push('\n');
-                                }
-                            }
+                    }
+                    Op::OverrideApprovalPolicy { approval_policy } => {
+                        // Queued behind pending turns like the model
+                        if tx_turn
+                            .send(TurnInput::ApprovalPolicy(approval_policy))
+                            .await
+                            .is_err()
+                        {
+                            break;
                         }
-                        let composed =
-                            format!("{}{}\n\nUser: {}", tool_instructions, history_block, text);
-                        // ツール実行エンジンを作成（ToolsConfigParamsから設定を取得）
-                        let approval_policy = crate::approval_manager::AskForApproval::default();
-                        let sandbox_policy = crate::seatbelt::SandboxPolicy::default();
-                        let mut tool_executor = ToolExecutor::new(
-                            approval_policy,
-                            sandbox_policy,
-                            std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
-                            crate::config_types::ShellEnvironmentPolicy::default(),
-                        );
-
-                        match client.stream(composed).await {
-                            Ok(mut rx) => {
-                                let mut assembled_resp = String::new();
-                                while let Some(ev) = rx.recv().await {
-                                    match ev {
-                                        ResponseEvent::TextDelta(delta) => {
-                                            assembled_resp.push_str(&delta);
-                                            let _ = tx_event
-                                                .send(Event::AgentMessageDelta { delta })
-                                                .await;
-                                        }
-                                        ResponseEvent::Completed => {
-                                            // AIレスポンス完了時にツール実行を処理
-                                            match tool_executor.extract_tool_calls(&assembled_resp)
-                                            {
-                                                Ok(tool_calls) => {
-                                                    if tool_calls.is_empty() {
-                                                        if !assembled_resp.is_empty() {
-                                                            convo.push((
-                                                                "assistant".to_string(),
-                                                                assembled_resp.clone(),
-                                                            ));
-                                                            if convo.len() > MAX_HISTORY_MESSAGES {
-                                                                let drop = convo.len()
-                                                                    - MAX_HISTORY_MESSAGES;
-                                                                convo.drain(0..drop);
-                                                            }
-                                                        }
-                                                    } else {
-                                                        let mut appended = String::new();
-
-                                                        for tool_call in tool_calls {
-                                                            // 入力詳細を生成
-                                                            let input_details = match &tool_call {
-                                                                crate::tool_executor::ToolCall::Shell { command, working_dir, with_escalated_permissions, justification, timeout_ms } => {
-                                                                    format!(
-                                                                        "tool=shell\ncommand={}\ncwd={}\nescalated={}\njustification={}\ntimeout_ms={}",
-                                                                        command.join(" "),
-                                                                        working_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "(default)".to_string()),
-                                                                        with_escalated_permissions,
-                                                                        justification.clone().unwrap_or_default(),
-                                                                        timeout_ms.map(|v| v.to_string()).unwrap_or_else(|| "(none)".to_string()),
-                                                                    )
-                                                                }
-                                                                crate::tool_executor::ToolCall::ReadFile { path } => {
-                                                                    format!("tool=read_file\npath={}", path.display())
-                                                                }
-                                                                crate::tool_executor::ToolCall::WriteFile { path, content } => {
-                                                                    format!("tool=write_file\npath={}\ncontent_bytes={}", path.display(), content.len())
-                                                                }
-                                                                crate::tool_executor::ToolCall::ApplyPatch { input } => {
-                                                                    format!("tool=apply_patch\npatch_bytes={}", input.len())
-                                                                }
-                                                                crate::tool_executor::ToolCall::ListFiles { path } => {
-                                                                    format!("tool=list_files\npath={}", path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()))
-                                                                }
-                                                                crate::tool_executor::ToolCall::SearchFiles { query, path } => {
-                                                                    format!("tool=search_files\nquery='{}'\npath={}", query, path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()))
-                                                                }
-                                                            };
-
-                                                            let announce = format!(
-                                                                "\n\n[Tool Execution]\n▶ {}\n\n[Tool Input]\n{}",
-                                                                tool_call.summary(),
-                                                                input_details
-                                                            );
-                                                            // 画面表示
-                                                            let _ = tx_event
-                                                                .send(Event::AgentMessageDelta {
-                                                                    delta: announce.clone(),
-                                                                })
-                                                                .await;
-                                                            appended.push_str(&announce);
-                                                            // ファイルログ
-                                                            info!(target: "slide.tools", input = %input_details, summary = %tool_call.summary(), "tool execution begin");
-
-                                                            match tool_executor
-                                                                .execute_tool_call(tool_call)
-                                                                .await
-                                                            {
-                                                                Ok(exec_output) => {
-                                                                    // 画面表示
-                                                                    let block = format!(
-                                                                        "\n\n[Tool Output]\n{}",
-                                                                        exec_output
-                                                                    );
-                                                                    let _ = tx_event
-                                                                        .send(Event::AgentMessageDelta { delta: block.clone() })
-                                                                        .await;
-                                                                    appended.push_str(&block);
-                                                                    // ファイルログ
-                                                                    info!(target: "slide.tools", output = %exec_output, "tool execution end (ok)");
-                                                                }
-                                                                Err(err) => {
-                                                                    let err_text = err.to_string();
-                                                                    // 画面表示
-                                                                    let block = format!("\n\n[Tool Output]\nFailed: {}", err_text);
-                                                                    let _ = tx_event
-                                                                        .send(Event::AgentMessageDelta { delta: block.clone() })
-                                                                        .await;
-                                                                    let _ = tx_event
-                                                                        .send(Event::Error { message: format!("Tool execution failed: {}", err_text) })
-                                                                        .await;
-                                                                    appended.push_str(&block);
-                                                                    // ファイルログ
-                                                                    info!(target: "slide.tools", error = %err_text, "tool execution end (error)");
-                                                                    break;
-                                                                }
-                                                            }
-                                                        }
-
-                                                        let enriched = format!(
-                                                            "{}{}",
-                                                            assembled_resp, appended
-                                                        );
-
-                                                        if !enriched.is_empty() {
-                                                            convo.push((
-                                                                "assistant".to_string(),
-                                                                enriched,
-                                                            ));
-                                                            if convo.len() > MAX_HISTORY_MESSAGES {
-                                                                let drop = convo.len()
-                                                                    - MAX_HISTORY_MESSAGES;
-                                                                convo.drain(0..drop);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    let _ = tx_event
-                                                        .send(Event::Error {
-                                                            message: format!(
-                                                                "Tool parsing failed: {}",
-                                                                e
-                                                            ),
-                                                        })
-                                                        .await;
-
-                                                    if !assembled_resp.is_empty() {
-                                                        convo.push((
-                                                            "assistant".to_string(),
-                                                            assembled_resp.clone(),
-                                                        ));
-                                                        if convo.len() > MAX_HISTORY_MESSAGES {
-                                                            let drop =
-                                                                convo.len() - MAX_HISTORY_MESSAGES;
-                                                            convo.drain(0..drop);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            let _ = tx_event.send(Event::TaskComplete).await;
-                                            break;
-                                        }
-                                        ResponseEvent::Error(message) => {
-                                            let _ = tx_event.send(Event::Error { message }).await;
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                let _ = tx_event
-                                    .send(Event::Error {
-                                        message: e.to_string(),
-                                    })
-                                    .await;
-                            }
+                    }
+                    Op::ContinuePlan { record } => {
+                        if tx_turn.send(TurnInput::ContinuePlan(record)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Op::SetCwd { path } => {
+                        // Queued behind pending turns so a running turn keeps its directory.
+                        if tx_turn.send(TurnInput::SetCwd(path)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Op::RevertTurn => {
+                        // Queued behind pending turns so the running one is reverted too
+                        if tx_turn.send(TurnInput::Revert).await.is_err() {
+                            break;
+                        }
+                    }
+                    Op::RestoreConversation { messages } => {
+                        if tx_turn.send(TurnInput::Restore(messages)).await.is_err() {
+                            break;
                         }
                     }
                     Op::Interrupt => {
-                        // Minimal implementation: no-op for now
+                        // Only a turn waiting on the signal is stopped; queued ones still run
+                        interrupt.notify_waiters();
+                    }
+                    Op::PausePlan => {
+                        if gate.pause() {
+                            let _ = tx_event.send(Event::PlanPaused).await;
+                        }
+                    }
+                    Op::ResumePlan => {
+                        if gate.resume() {
+                            let _ = tx_event.send(Event::PlanResumed).await;
+                        }
                     }
-                    Op::ExecApproval { .. } => {
-                        // Minimal placeholder: in full core this would resolve a pending approval
+                    Op::ExecApproval { id, decision } | Op::PatchApproval { id, decision } => {
+                        if let Some(tx) = pending_approvals.lock().await.remove(&id) {
+                            let _ = tx.send(decision);
+                        }
                     }
-                    Op::PatchApproval { .. } => {
-                        // Minimal placeholder
+                    Op::ListCustomPrompts => {
+                        let custom_prompts = discover_prompts_in(&default_prompts_dir()).await;
+                        let _ = tx_event
+                            .send(Event::ListCustomPromptsResponse { custom_prompts })
+                            .await;
                     }
                     Op::Shutdown => {
-                        let _ = tx_event.send(Event::ShutdownComplete).await;
+                        // The turn task answers once the running turn is dropped
+                        shutdown.notify_one();
                         break;
                     }
                 }
@@ -424,3 +735,1032 @@ impl Codex {
         rx.recv().await
     }
 }
+
+fn session_configured(
+    client: &(dyn ModelClient + Send + Sync),
+    cwd: &Path,
+    approval_policy: AskForApproval,
+) -> Event {
+    Event::SessionConfigured {
+        model: client.model().to_string(),
+        provider: client.provider().to_string(),
+        approval_policy,
+        sandbox_policy: session_sandbox_policy(),
+        instruction_files: discover_project_docs(cwd)
+            .into_iter()
+            .map(|doc| doc.path)
+            .collect(),
+        read_only: read_only(),
+    }
+}
+
+/// Set by `slide --read-only`
+pub const READ_ONLY_ENV: &str = "SLIDE_READ_ONLY";
+
+/// Whether the session may not change files. Read-only sessions run every
+/// command in the read-only sandbox, whatever `--sandbox` or `/approvals`
+/// say, and are not offered the tools that write files.
+pub fn read_only() -> bool {
+    std::env::var(READ_ONLY_ENV).is_ok_and(|v| v == "1")
+}
+
+/// Sandbox picked with `slide --sandbox`. It reaches the agent through the
+/// environment like the model.
+fn session_sandbox_policy() -> SandboxPolicy {
+    if read_only() {
+        SandboxPolicy::ReadOnly
+    } else {
+        std::env::var("SLIDE_SANDBOX")
+            .ok()
+            .and_then(|mode| SandboxPolicy::from_label(&mode))
+            .unwrap_or_default()
+    }
+}
+
+/// Work queued for the turn task, processed in order.
+enum TurnInput {
+    User(String),
+    ContinuePlan(PlanRecord),
+    Model(String),
+    ApprovalPolicy(AskForApproval),
+    Restore(Vec<(String, String)>),
+    SetCwd(PathBuf),
+    Revert,
+}
+
+/// State that outlives a turn: how approvals reach the user, and where
+/// commands run.
+struct SessionContext {
+    approvals: Arc<dyn ApprovalHandler>,
+    /// Picked with `slide --approval`, then `/approvals`; `None` keeps the
+    /// default
+    approval_policy: Option<AskForApproval>,
+    /// Directory the session started in
+    workspace: PathBuf,
+    cwd: PathBuf,
+    /// Decks, git status and files of the workspace when the session
+    /// started, added to the instructions of every turn
+    workspace_context: Option<String>,
+    /// Checks the git status again, after a patch
+    git_refresh: Arc<Notify>,
+    /// Tool versions reported by `env_info`, looked up once
+    env_info: EnvInfoCache,
+    /// Pages read with `web_fetch`, for the sources of the decks
+    fetched_sources: FetchedSources,
+}
+
+impl SessionContext {
+    /// Approval and sandbox policies of the next turn
+    fn policies(&self) -> (AskForApproval, SandboxPolicy) {
+        (
+            self.approval_policy.unwrap_or_default(),
+            session_sandbox_policy(),
+        )
+    }
+
+    /// Move to `path`; a directory outside the workspace needs approval.
+    async fn set_cwd(&mut self, path: &Path) -> std::result::Result<PathBuf, String> {
+        let (dir, inside) =
+            resolve_cwd(&self.workspace, &self.cwd, path).map_err(|e| e.to_string())?;
+        if !inside {
+            let request = ApprovalRequest::new(
+                vec!["cd".to_string(), dir.display().to_string()],
+                Some(&self.cwd),
+                Some(format!("leaves the workspace {}", self.workspace.display())),
+                false,
+                session_sandbox_policy().label().to_string(),
+            );
+            match self.approvals.request_approval(request).await {
+                ApprovalResponse::Approved
+                | ApprovalResponse::ApprovedAndTrust
+                | ApprovalResponse::ApprovedPrefix(_) => {}
+                _ => {
+                    return Err(format!(
+                        "{} is outside the workspace and was not approved",
+                        dir.display()
+                    ))
+                }
+            }
+        }
+        self.cwd = dir.clone();
+        Ok(dir)
+    }
+}
+
+/// Tell the client which guardrail stopped the turn; returns the note kept
+/// in the conversation so the model knows why its calls were not run.
+async fn report_guardrail_trip(tx_event: &mpsc::Sender<Event>, trip: GuardrailTrip) -> String {
+    let message = trip.to_string();
+    warn!(limit = ?trip.limit, used = trip.used, max = trip.max, "guardrail tripped");
+    let _ = tx_event
+        .send(Event::GuardrailTripped {
+            limit: trip.limit,
+            used: trip.used,
+            max: trip.max,
+            message: message.clone(),
+        })
+        .await;
+    format!("\n\n[Guardrail]\n{message}")
+}
+
+/// Show the tool calls of a reply that could not be parsed, even after
+/// repairing their JSON; returns the note kept in the conversation so the
+/// model can fix and resend them.
+async fn report_tool_call_errors(tx_event: &mpsc::Sender<Event>, errors: &[String]) -> String {
+    if errors.is_empty() {
+        return String::new();
+    }
+    warn!(count = errors.len(), "unparseable tool calls");
+    let mut block = String::from("\n\n[Tool Call Error]\nThese tool calls were not run:\n");
+    for error in errors {
+        block.push_str(&format!("- {error}\n"));
+    }
+    block.push_str("Fix the call and send it again as one JSON object.");
+    let _ = tx_event
+        .send(Event::AgentMessageDelta {
+            delta: block.clone(),
+        })
+        .await;
+    block
+}
+
+/// Shared map of approval requests waiting for a user decision, keyed by id.
+type PendingApprovals = Arc<Mutex<HashMap<String, oneshot::Sender<ReviewDecision>>>>;
+
+/// Surfaces approval requests as [`Event::ExecApprovalRequest`],
+/// [`Event::ExecPlanApprovalRequest`] or [`Event::ApplyPatchApprovalRequest`]
+/// and waits for the matching op.
+struct EventApprovalHandler {
+    tx_event: mpsc::Sender<Event>,
+    pending: PendingApprovals,
+    /// How long a request waits for an answer; `None` waits forever
+    timeout: Option<Duration>,
+    on_timeout: ApprovalTimeoutAction,
+    /// Stops the running turn when a timeout aborts it
+    interrupt: Arc<Notify>,
+    /// Repository checked for uncommitted changes in patched files
+    workspace: PathBuf,
+}
+
+impl EventApprovalHandler {
+    fn timeout_secs(&self) -> Option<u64> {
+        self.timeout.map(|timeout| timeout.as_secs())
+    }
+
+    /// Wait for the answer to request `id`. `None` when the session ended or
+    /// the timeout ran out first, which counts as a denial.
+    async fn wait_for_decision(
+        &self,
+        id: &str,
+        rx: oneshot::Receiver<ReviewDecision>,
+    ) -> Option<ReviewDecision> {
+        let Some(timeout) = self.timeout else {
+            return rx.await.ok();
+        };
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(decision) => decision.ok(),
+            Err(_) => {
+                self.pending.lock().await.remove(id);
+                let _ = self
+                    .tx_event
+                    .send(Event::ApprovalTimedOut {
+                        id: id.to_string(),
+                        action: self.on_timeout,
+                    })
+                    .await;
+                if self.on_timeout == ApprovalTimeoutAction::AbortTurn {
+                    self.interrupt.notify_waiters();
+                }
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ApprovalHandler for EventApprovalHandler {
+    async fn request_approval(&self, request: ApprovalRequest) -> ApprovalResponse {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), tx);
+        let cwd = request
+            .working_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let prefix = approval_prefix(&request.command);
+        let event = Event::ExecApprovalRequest {
+            id: id.clone(),
+            command: request.command.clone(),
+            cwd,
+            reason: request.justification.clone(),
+            parsed_cmd: parse_command(&request.command),
+            sandbox: Some(request.sandbox_policy.clone()),
+            escalated: request.with_escalated_permissions,
+            risk: describe_risk(&request.command),
+            prefix: prefix.clone(),
+            timeout_secs: self.timeout_secs(),
+        };
+        if self.tx_event.send(event).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            return ApprovalResponse::Denied;
+        }
+        let decision = self.wait_for_decision(&id, rx).await;
+        telemetry::record_approval("exec", decision.as_ref());
+        match decision {
+            Some(ReviewDecision::Approved) => ApprovalResponse::Approved,
+            Some(ReviewDecision::ApprovedForSession) => ApprovalResponse::ApprovedAndTrust,
+            Some(ReviewDecision::ApprovedPrefix) => match prefix {
+                Some(prefix) => ApprovalResponse::ApprovedPrefix(prefix),
+                None => ApprovalResponse::Approved,
+            },
+            Some(_) | None => ApprovalResponse::Denied,
+        }
+    }
+
+    async fn request_plan_approval(&self, request: PlanApprovalRequest) -> PlanApprovalResponse {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), tx);
+        let event = Event::ExecPlanApprovalRequest {
+            id: id.clone(),
+            commands: request.commands,
+            cwd: request.cwd,
+            timeout_secs: self.timeout_secs(),
+        };
+        if self.tx_event.send(event).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            return PlanApprovalResponse::Denied;
+        }
+        let decision = self.wait_for_decision(&id, rx).await;
+        telemetry::record_approval("plan", decision.as_ref());
+        match decision {
+            Some(ReviewDecision::Approved | ReviewDecision::ApprovedForSession) => {
+                PlanApprovalResponse::Approved
+            }
+            Some(ReviewDecision::ApprovedCommands(indices)) if !indices.is_empty() => {
+                PlanApprovalResponse::ApprovedCommands(indices)
+            }
+            Some(_) | None => PlanApprovalResponse::Denied,
+        }
+    }
+
+    async fn request_patch_approval(&self, request: PatchApprovalRequest) -> PatchApprovalResponse {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), tx);
+        let paths: Vec<PathBuf> = request.changes.keys().cloned().collect();
+        let workspace = self.workspace.clone();
+        let mut dirty_files = tokio::task::spawn_blocking(move || {
+            GitStatus::read(&workspace)
+                .map(|status| status.dirty_among(&workspace, &paths))
+                .unwrap_or_default()
+        })
+        .await
+        .unwrap_or_default();
+        dirty_files.sort();
+        let event = Event::ApplyPatchApprovalRequest {
+            id: id.clone(),
+            changes: request.changes,
+            reason: request.reason,
+            timeout_secs: self.timeout_secs(),
+            dirty_files,
+        };
+        if self.tx_event.send(event).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            return PatchApprovalResponse::Denied;
+        }
+        let decision = self.wait_for_decision(&id, rx).await;
+        telemetry::record_approval("patch", decision.as_ref());
+        match decision {
+            Some(ReviewDecision::Approved | ReviewDecision::ApprovedForSession) => {
+                PatchApprovalResponse::Approved
+            }
+            Some(ReviewDecision::ApprovedFiles(files)) if !files.is_empty() => {
+                PatchApprovalResponse::ApprovedFiles(files)
+            }
+            Some(_) | None => PatchApprovalResponse::Denied,
+        }
+    }
+}
+
+/// Sends [`Event::GitStatus`] for the repository holding `dir` at startup
+/// and whenever it changed, checked every [`GIT_STATUS_INTERVAL`] and when
+/// `refresh` is notified. Ends with the session.
+fn watch_git_status(dir: PathBuf, tx_event: mpsc::Sender<Event>, refresh: Arc<Notify>) {
+    tokio::spawn(async move {
+        let mut last: Option<GitStatus> = None;
+        while !tx_event.is_closed() {
+            let repo = dir.clone();
+            let status = tokio::task::spawn_blocking(move || GitStatus::read(&repo))
+                .await
+                .ok()
+                .flatten();
+            if let Some(status) = status.filter(|status| last.as_ref() != Some(status)) {
+                if tx_event
+                    .send(Event::GitStatus(status.clone()))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                last = Some(status);
+            }
+            tokio::select! {
+                () = tokio::time::sleep(GIT_STATUS_INTERVAL) => {}
+                () = refresh.notified() => {}
+            }
+        }
+    });
+}
+
+/// Relays command progress and written files from the tool executor as
+/// events, and has the git status checked again after a patch. Ends when the
+/// executor is dropped at the end of the turn.
+fn forward_exec_progress(
+    mut rx: mpsc::UnboundedReceiver<ExecProgress>,
+    tx_event: mpsc::Sender<Event>,
+    git_refresh: Arc<Notify>,
+) {
+    tokio::spawn(async move {
+        while let Some(progress) = rx.recv().await {
+            let event = match progress {
+                ExecProgress::Begin {
+                    call_id,
+                    command,
+                    cwd,
+                } => Event::ExecCommandBegin {
+                    call_id,
+                    command,
+                    cwd,
+                },
+                ExecProgress::OutputDelta {
+                    call_id,
+                    stream,
+                    chunk,
+                } => Event::ExecCommandOutputDelta {
+                    call_id,
+                    stream,
+                    chunk,
+                },
+                ExecProgress::End { call_id, exit_code } => {
+                    Event::ExecCommandEnd { call_id, exit_code }
+                }
+                ExecProgress::FilesTouched { paths } => Event::FilesTouched { paths },
+                ExecProgress::PatchApplyBegin => Event::PatchApplyBegin {},
+                ExecProgress::PatchApplyEnd {
+                    success,
+                    files,
+                    total,
+                } => {
+                    git_refresh.notify_one();
+                    Event::PatchApplyEnd {
+                        success,
+                        files,
+                        total,
+                    }
+                }
+                ExecProgress::TurnDiff { unified_diff } => Event::TurnDiff { unified_diff },
+                ExecProgress::ContentFlagged {
+                    path,
+                    reasons,
+                    blocked,
+                } => Event::ContentFlagged {
+                    path,
+                    reasons,
+                    blocked,
+                },
+            };
+            if tx_event.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Write the plan's execution state. Best-effort: a failed write is logged
+/// and the turn goes on.
+fn save_plan(plan: &PlanManager) {
+    if let Err(e) = plan.save() {
+        warn!(target: "slide.plans", error = %e, "failed to save plan");
+    }
+}
+
+/// Send the new plan, followed by the steps that began or ended with it.
+async fn publish_plan(
+    tx_event: &mpsc::Sender<Event>,
+    update: UpdatePlanArgs,
+    changes: Vec<StepChange>,
+) {
+    let _ = tx_event.send(Event::PlanUpdate(update)).await;
+    for change in changes {
+        let event = match change {
+            StepChange::Begin { index, step } => Event::PlanStepBegin { index, step },
+            StepChange::End {
+                index,
+                step,
+                status,
+            } => Event::PlanStepEnd {
+                index,
+                step,
+                status,
+            },
+        };
+        let _ = tx_event.send(event).await;
+    }
+}
+
+/// Tool executor of one turn, sharing the session's approvals, caches and
+/// turn snapshot.
+async fn turn_executor(
+    session: &SessionContext,
+    snapshot: &Arc<std::sync::Mutex<TurnSnapshot>>,
+    tx_event: &mpsc::Sender<Event>,
+    config: &slide_common::SlideConfig,
+    approval_policy: AskForApproval,
+    sandbox_policy: SandboxPolicy,
+    read_only: bool,
+) -> ToolExecutor {
+    let shell_environment_policy = crate::config_types::ShellEnvironmentPolicy::default()
+        .with_project_overrides(&PathBuf::from("slides"))
+        .unwrap_or_else(|e| {
+            warn!(error = %e, "ignoring project shell environment");
+            crate::config_types::ShellEnvironmentPolicy::default()
+        });
+    let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+    forward_exec_progress(progress_rx, tx_event.clone(), session.git_refresh.clone());
+    // 書き込み可能なディレクトリはワークスペース基準のまま、コマンドと相対パスはセッションの cwd で扱う
+    let mut tool_executor = ToolExecutor::new(
+        approval_policy,
+        sandbox_policy,
+        session.workspace.clone(),
+        shell_environment_policy,
+    )
+    .with_approval_handler(session.approvals.clone())
+    .with_writable_roots(&config.sandbox.writable_roots)
+    .with_progress_sender(progress_tx)
+    .with_turn_snapshot(snapshot.clone())
+    .with_guardrails(config.guardrails.clone())
+    .with_env_info_cache(session.env_info.clone())
+    .with_fetched_sources(session.fetched_sources.clone())
+    .with_moderation(config.moderation.clone());
+    tool_executor.update_working_directory(session.cwd.clone());
+    if read_only {
+        tool_executor = tool_executor.read_only();
+    }
+    if config.shell.login_shell {
+        tool_executor = tool_executor.with_login_shell(crate::shell::default_user_shell().await);
+    }
+    tool_executor
+}
+
+/// Run a single user turn: stream the model answer and execute any tool calls.
+async fn run_turn(
+    client: &Arc<dyn ModelClient + Send + Sync>,
+    tx_event: &mpsc::Sender<Event>,
+    convo: &mut Vec<(String, String)>,
+    plan: &mut PlanManager,
+    gate: &PlanGate,
+    session: &SessionContext,
+    snapshot: &Arc<std::sync::Mutex<TurnSnapshot>>,
+    text: String,
+) {
+    let _ = tx_event.send(Event::TaskStarted).await;
+    plan.start_turn(&text);
+    let read_only = read_only();
+    if let Some(prompt) = text.strip_prefix("/slide ") {
+        if read_only {
+            let _ = tx_event
+                .send(Event::Error {
+                    message: "/slide writes slides/draft.md; not available in read-only mode"
+                        .to_string(),
+                })
+                .await;
+            return;
+        }
+        let opts = SlideGenerateOptions {
+            prompt: prompt.to_string(),
+            ..Default::default()
+        };
+        match generate_slides(client.as_ref(), &opts, |_| {}).await {
+            Ok(markdown) => {
+                for line in markdown.lines() {
+                    let delta = format!("{}\n", line);
+                    let _ = tx_event.send(Event::AgentMessageDelta { delta }).await;
+                }
+                let save_path = PathBuf::from("slides").join("draft.md");
+                let usage = TokenUsage::estimate(prompt, &markdown);
+                let _ = tx_event.send(Event::TokenCount { usage }).await;
+                // Saved like any write_file call: workspace guard, content
+                // safety, approval, turn snapshot and deck history
+                let config = slide_common::SlideConfig::load().await.unwrap_or_default();
+                let (approval_policy, sandbox_policy) = session.policies();
+                let mut tool_executor = turn_executor(
+                    session,
+                    snapshot,
+                    tx_event,
+                    &config,
+                    approval_policy,
+                    sandbox_policy,
+                    read_only,
+                )
+                .await;
+                let saved = tool_executor
+                    .execute_tool_call(crate::tool_executor::ToolCall::WriteFile {
+                        path: save_path.clone(),
+                        content: markdown,
+                    })
+                    .await;
+                match saved {
+                    Ok(result) if result.starts_with("Change Approved") => {
+                        let mut message = format!("Saved to {}", save_path.display());
+                        for note in result.lines().skip(2) {
+                            message.push('\n');
+                            message.push_str(note);
+                        }
+                        let _ = tx_event.send(Event::AgentMessage { message }).await;
+                    }
+                    Ok(result) => {
+                        let _ = tx_event
+                            .send(Event::Error {
+                                message: format!("slides were not saved: {result}"),
+                            })
+                            .await;
+                    }
+                    Err(e) => {
+                        let _ = tx_event
+                            .send(Event::Error {
+                                message: format!("failed to save slides: {e}"),
+                            })
+                            .await;
+                    }
+                }
+                let _ = tx_event.send(Event::TaskComplete).await;
+            }
+            Err(e) => {
+                let _ = tx_event
+                    .send(Event::Error {
+                        message: e.to_string(),
+                    })
+                    .await;
+            }
+        }
+        return;
+    }
+    // Prefix prompt with tool instructions so the model can propose edits/execs.
+    let approval_hint = session.approval_policy;
+    let (approval_policy, sandbox_policy) = session.policies();
+    let tools_cfg = ToolsConfig::new(&ToolsConfigParams {
+        include_plan_tool: true,
+        include_apply_patch_tool: !read_only,
+        include_view_image_tool: false,
+        include_web_search_request: slide_chatgpt::web::search_api_key().is_some(),
+        use_streamable_shell_tool: true,
+        include_slides_tools: !read_only,
+        include_image_tool: !read_only,
+        include_read_file_tool: true,
+        include_web_fetch_tool: true,
+        approval_policy,
+        sandbox_policy: sandbox_policy.clone(),
+    });
+    // Built-in instructions and the workspace summary first, then the
+    // project's SLIDE.md/AGENTS.md files
+    let mut base_instructions = render_tools_instructions(&tools_cfg, approval_hint.map(AskForApproval::as_str));
+    if let Some(context) = &session.workspace_context {
+        base_instructions.push_str("\n\n");
+        base_instructions.push_str(context);
+    }
+    let tool_instructions =
+        compose_instructions(&base_instructions, &discover_project_docs(&session.cwd));
+    // Append user message to conversation memory
+    convo.push(("user".to_string(), text.clone()));
+    // Cap memory to recent N entries to fit token budget
+    const MAX_HISTORY_MESSAGES: usize = 12; // messages, not turns
+    if convo.len() > MAX_HISTORY_MESSAGES {
+        let drop = convo.len() - MAX_HISTORY_MESSAGES;
+        convo.drain(0..drop);
+    }
+    // Render recent conversation as plain lines
+    let mut history_block = String::new();
+    if !convo.is_empty() {
+        history_block.push_str("\n\nConversation so far:\n");
+        for (role, msg) in convo.iter() {
+            let tag = if role == "assistant" {
+                "Assistant"
+            } else {
+                "User"
+            };
+            history_block.push_str(tag);
+            history_block.push_str(": ");
+            history_block.push_str(msg);
+            if !msg.ends_with('\n') {
+                history_block.push('\n');
+            }
+        }
+    }
+    let composed = format!("{}{}\n\nUser: {}", tool_instructions, history_block, text);
+    // 会話履歴やユーザー入力に紛れた秘密情報をモデルへ送らない
+    let composed = slide_common::secrets::redact(&composed).into_owned();
+    // ツール実行エンジンを作成（ToolsConfigParamsから設定を取得）
+    let config = slide_common::SlideConfig::load().await.unwrap_or_default();
+    let mut tool_executor = turn_executor(
+        session,
+        snapshot,
+        tx_event,
+        &config,
+        approval_policy,
+        sandbox_policy,
+        read_only,
+    )
+    .await;
+
+    let prompt_tokens = estimate_tokens(&composed);
+    let mut retry = TurnRetry::new(config.retry.clone());
+    loop {
+        let mut request_span = Some(telemetry::model_request_span(
+            client.model(),
+            client.provider(),
+        ));
+        let failure = match client.stream(composed.clone()).await {
+            Ok(mut rx) => {
+                let mut assembled_resp = String::new();
+                let mut failure = None;
+                while let Some(ev) = rx.recv().await {
+                    match ev {
+                        ResponseEvent::TextDelta(delta) => {
+                            assembled_resp.push_str(&delta);
+                            let _ = tx_event.send(Event::AgentMessageDelta { delta }).await;
+                        }
+                        ResponseEvent::Completed => {
+                            let usage = TokenUsage {
+                                input_tokens: prompt_tokens,
+                                output_tokens: estimate_tokens(&assembled_resp),
+                            };
+                            if let Some(span) = request_span.take() {
+                                telemetry::end_model_request(span, Ok(usage));
+                            }
+                            telemetry::record_usage(client.provider(), client.model(), usage);
+                            let _ = tx_event.send(Event::TokenCount { usage }).await;
+                            // AIレスポンス完了時にツール実行を処理
+                            let extracted = tool_executor.extract_tool_calls_checked(&assembled_resp);
+                            let call_errors = report_tool_call_errors(tx_event, &extracted.errors).await;
+                            let tool_calls = extracted.calls;
+                            if tool_calls.is_empty() {
+                                let reply = format!("{assembled_resp}{call_errors}");
+                                if !reply.is_empty() {
+                                    convo.push(("assistant".to_string(), reply));
+                                    if convo.len() > MAX_HISTORY_MESSAGES {
+                                        let drop = convo.len() - MAX_HISTORY_MESSAGES;
+                                        convo.drain(0..drop);
+                                    }
+                                }
+                            } else {
+                                let mut appended = call_errors;
+                                tool_executor.approve_plan(&tool_calls).await;
+
+                                for tool_call in tool_calls {
+                                    // A paused plan holds here; Op::Interrupt still ends the turn
+                                    gate.wait_until_resumed().await;
+                                    if let Err(trip) = tool_executor.check_guardrails() {
+                                        appended.push_str(
+                                            &report_guardrail_trip(tx_event, trip).await,
+                                        );
+                                        break;
+                                    }
+                                    // 入力詳細を生成
+                                    let input_details = match &tool_call {
+                                        crate::tool_executor::ToolCall::Shell {
+                                            command,
+                                            working_dir,
+                                            with_escalated_permissions,
+                                            justification,
+                                            timeout_ms,
+                                        } => {
+                                            format!(
+                                                "tool=shell\ncommand={}\ncwd={}\nescalated={}\njustification={}\ntimeout_ms={}",
+                                                command.join(" "),
+                                                working_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "(default)".to_string()),
+                                                with_escalated_permissions,
+                                                justification.clone().unwrap_or_default(),
+                                                timeout_ms.map(|v| v.to_string()).unwrap_or_else(|| "(none)".to_string()),
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ReadFile { input } => {
+                                            format!(
+                                                "tool=read_file\npath={}\noffset={}\nlimit={}",
+                                                input.path.display(),
+                                                input.offset.unwrap_or(1),
+                                                input
+                                                    .limit
+                                                    .map(|v| v.to_string())
+                                                    .unwrap_or_else(|| "(default)".to_string()),
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::WriteFile {
+                                            path,
+                                            content,
+                                        } => {
+                                            format!(
+                                                "tool=write_file\npath={}\ncontent_bytes={}",
+                                                path.display(),
+                                                content.len()
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ApplyPatch {
+                                            input,
+                                        } => {
+                                            format!(
+                                                "tool=apply_patch\npatch_bytes={}",
+                                                input.len()
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ListFiles { path } => {
+                                            format!(
+                                                "tool=list_files\npath={}",
+                                                path.as_ref()
+                                                    .map(|p| p.display().to_string())
+                                                    .unwrap_or_else(|| ".".to_string())
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::SearchFiles {
+                                            query,
+                                            path,
+                                        } => {
+                                            format!(
+                                                "tool=search_files\nquery='{}'\npath={}",
+                                                query,
+                                                path.as_ref()
+                                                    .map(|p| p.display().to_string())
+                                                    .unwrap_or_else(|| ".".to_string())
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::UpdatePlan { args } => {
+                                            let changes = step_changes(plan.current(), args);
+                                            let update = plan.update(args.clone());
+                                            save_plan(plan);
+                                            publish_plan(tx_event, update, changes).await;
+                                            format!(
+                                                "tool=update_plan\nsteps={}",
+                                                args.plan.len()
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::Image { input } => {
+                                            format!(
+                                                "tool=image\nsource={:?}\ndeck={}\nslide={}",
+                                                input.source,
+                                                input
+                                                    .deck
+                                                    .as_ref()
+                                                    .map(|p| p.display().to_string())
+                                                    .unwrap_or_else(|| "(none)".to_string()),
+                                                input.slide.clone().unwrap_or_default(),
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::WebFetch { input } => {
+                                            format!("tool=web_fetch\nurl={}", input.url)
+                                        }
+                                        crate::tool_executor::ToolCall::WebSearch { input } => {
+                                            format!("tool=web_search\nquery='{}'", input.query)
+                                        }
+                                        crate::tool_executor::ToolCall::ExecSessionOpen {
+                                            input,
+                                        } => {
+                                            format!(
+                                                "tool=exec_session_open\ncommand={}",
+                                                input.command.join(" ")
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ExecSessionWrite {
+                                            input,
+                                        } => {
+                                            format!(
+                                                "tool=exec_session_write\nsession_id={}\nchars={:?}",
+                                                input.session_id, input.chars
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ExecSessionRead {
+                                            input,
+                                        } => {
+                                            format!(
+                                                "tool=exec_session_read\nsession_id={}",
+                                                input.session_id
+                                            )
+                                        }
+                                        crate::tool_executor::ToolCall::ExecSessionClose {
+                                            session_id,
+                                        } => {
+                                            format!("tool=exec_session_close\nsession_id={session_id}")
+                                        }
+                                        crate::tool_executor::ToolCall::EnvInfo => {
+                                            "tool=env_info".to_string()
+                                        }
+                                    };
+                                    let input_details = slide_common::secrets::redact(&input_details).into_owned();
+
+                                    let announce = format!(
+                                        "\n\n[Tool Execution]\n▶ {}\n\n[Tool Input]\n{}",
+                                        tool_call.summary(),
+                                        input_details
+                                    );
+                                    // 画面表示
+                                    let _ = tx_event
+                                        .send(Event::AgentMessageDelta {
+                                            delta: announce.clone(),
+                                        })
+                                        .await;
+                                    appended.push_str(&announce);
+                                    // ファイルログ
+                                    info!(target: "slide.tools", input = %input_details, summary = %tool_call.summary(), "tool execution begin");
+
+                                    match tool_executor.execute_tool_call(tool_call).await {
+                                        Ok(exec_output) => {
+                                            // 画面表示
+                                            let block =
+                                                format!("\n\n[Tool Output]\n{}", exec_output);
+                                            let _ = tx_event
+                                                .send(Event::AgentMessageDelta {
+                                                    delta: block.clone(),
+                                                })
+                                                .await;
+                                            appended.push_str(&block);
+                                            if plan.record_output(&exec_output) {
+                                                save_plan(plan);
+                                            }
+                                            // ファイルログ
+                                            info!(target: "slide.tools", output = %exec_output, "tool execution end (ok)");
+                                        }
+                                        Err(err) => {
+                                            let err_text = err.to_string();
+                                            // 画面表示
+                                            let block = format!(
+                                                "\n\n[Tool Output]\nFailed: {}",
+                                                err_text
+                                            );
+                                            let _ = tx_event
+                                                .send(Event::AgentMessageDelta {
+                                                    delta: block.clone(),
+                                                })
+                                                .await;
+                                            let _ = tx_event
+                                                .send(Event::Error {
+                                                    message: format!(
+                                                        "Tool execution failed: {}",
+                                                        err_text
+                                                    ),
+                                                })
+                                                .await;
+                                            appended.push_str(&block);
+                                            // ファイルログ
+                                            info!(target: "slide.tools", error = %err_text, "tool execution end (error)");
+                                            plan.record_output(&format!("Failed: {err_text}"));
+                                            let before = plan.current().cloned();
+                                            if let Some(update) = plan.fail_in_progress() {
+                                                save_plan(plan);
+                                                let changes =
+                                                    step_changes(before.as_ref(), &update);
+                                                publish_plan(tx_event, update, changes).await;
+                                            }
+                                            break;
+                                        }
+                                    }
+                                }
+
+                                let enriched = format!("{}{}", assembled_resp, appended);
+
+                                if !enriched.is_empty() {
+                                    convo.push(("assistant".to_string(), enriched));
+                                    if convo.len() > MAX_HISTORY_MESSAGES {
+                                        let drop = convo.len() - MAX_HISTORY_MESSAGES;
+                                        convo.drain(0..drop);
+                                    }
+                                }
+                            }
+                            let _ = tx_event.send(Event::TaskComplete).await;
+                            return;
+                        }
+                        ResponseEvent::Error(message) => {
+                            failure = Some(message);
+                            break;
+                        }
+                    }
+                }
+                failure.unwrap_or_else(|| "stream closed before the reply completed".to_string())
+            }
+            Err(e) => e.to_string(),
+        };
+        if let Some(span) = request_span.take() {
+            telemetry::end_model_request(span, Err(&failure));
+        }
+        // The same prompt is sent again, with the tool results already in it
+        let Some(delay) = retry.next_delay(&failure) else {
+            let message = match retry.retries() {
+                0 => failure,
+                n => format!("{failure} (gave up after {n} retries)"),
+            };
+            let _ = tx_event.send(Event::Error { message }).await;
+            return;
+        };
+        warn!(error = %failure, attempt = retry.retries(), ?delay, "model request failed; retrying");
+        let _ = tx_event
+            .send(Event::TurnRetrying {
+                attempt: retry.retries(),
+                max_retries: retry.max_retries(),
+                delay_ms: delay.as_millis() as u64,
+                error: failure,
+            })
+            .await;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed_handler(
+        timeout: Duration,
+        on_timeout: ApprovalTimeoutAction,
+    ) -> (EventApprovalHandler, mpsc::Receiver<Event>) {
+        let (tx_event, rx_event) = mpsc::channel(8);
+        let handler = EventApprovalHandler {
+            tx_event,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            timeout: Some(timeout),
+            on_timeout,
+            interrupt: Arc::new(Notify::new()),
+            workspace: PathBuf::from("/work"),
+        };
+        (handler, rx_event)
+    }
+
+    #[tokio::test]
+    async fn unanswered_approvals_time_out() {
+        let (handler, mut rx) =
+            timed_handler(Duration::from_millis(20), ApprovalTimeoutAction::Deny);
+        let request = PlanApprovalRequest {
+            commands: vec![vec!["ls".to_string()]],
+            cwd: PathBuf::from("/work"),
+        };
+        assert_eq!(
+            handler.request_plan_approval(request).await,
+            PlanApprovalResponse::Denied
+        );
+        let Some(Event::ExecPlanApprovalRequest {
+            id, timeout_secs, ..
+        }) = rx.recv().await
+        else {
+            panic!("expected a plan approval request");
+        };
+        assert_eq!(timeout_secs, Some(0));
+        assert!(matches!(
+            rx.recv().await,
+            Some(Event::ApprovalTimedOut { id: timed_out, action: ApprovalTimeoutAction::Deny })
+                if timed_out == id
+        ));
+        assert!(handler.pending.lock().await.is_empty());
+
+        // Aborting also stops the turn waiting on the interrupt
+        let (handler, _rx) =
+            timed_handler(Duration::from_millis(20), ApprovalTimeoutAction::AbortTurn);
+        let interrupted = handler.interrupt.clone();
+        let stopped = interrupted.notified();
+        tokio::pin!(stopped);
+        stopped.as_mut().enable();
+        let request = PatchApprovalRequest {
+            changes: HashMap::new(),
+            reason: None,
+        };
+        assert_eq!(
+            handler.request_patch_approval(request).await,
+            PatchApprovalResponse::Denied
+        );
+        assert!(tokio::time::timeout(Duration::from_secs(1), stopped)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn privacy_report_lists_the_moderation_endpoint_when_it_is_used() {
+        use slide_common::moderation::{ModerationAction, ModerationProvider};
+
+        let report = |config: &slide_common::SlideConfig| {
+            let mut registry = DataFlowRegistry::new();
+            register_data_flows(&mut registry, config);
+            registry.render_report()
+        };
+        let mut config = slide_common::SlideConfig::default();
+        config.moderation.provider = ModerationProvider::Openai;
+        let off = report(&config);
+        let disabled = off.split("Disabled data flows:").nth(1).unwrap_or_default();
+        assert!(disabled.contains("[chatgpt.moderation]"), "{off}");
+
+        config.moderation.action = ModerationAction::Flag;
+        let on = report(&config);
+        let active = on.split("Disabled data flows:").next().unwrap_or_default();
+        assert!(
+            active.contains("[chatgpt.moderation] network https://api.openai.com/v1/moderations"),
+            "{on}"
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Unix commands on Windows: the model mostly writes `ls -al`, `cat` or
+//! `rm -rf`, which only work there when a unix toolchain (Git Bash, MSYS) is
+//! on PATH. Common ones are rewritten to PowerShell cmdlets; for other
+//! unix-only tools the result carries an advisory for the model instead.
+//! On other platforms commands run unchanged.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Translation {
+    /// Run `command` in place of the one the model wrote
+    Rewritten { command: Vec<String>, note: String },
+    /// No equivalent is known; the note tells the model what to do instead
+    Advisory { note: String },
+}
+
+impl Translation {
+    pub fn note(&self) -> &str {
+        match self {
+            Translation::Rewritten { note, .. } | Translation::Advisory { note } => note,
+        }
+    }
+}
+
+/// How a unix command maps to a cmdlet
+struct Cmdlet {
+    unix: &'static str,
+    cmdlet: &'static str,
+    /// Short flags and the cmdlet parameter each becomes (`""` for flags
+    /// that change only the output format)
+    flags: &'static [(char, &'static str)],
+}
+
+const fn cmdlet(
+    unix: &'static str,
+    cmdlet: &'static str,
+    flags: &'static [(char, &'static str)],
+) -> Cmdlet {
+    Cmdlet {
+        unix,
+        cmdlet,
+        flags,
+    }
+}
+
+const CMDLETS: &[Cmdlet] = &[
+    cmdlet(
+        "ls",
+        "Get-ChildItem",
+        &[
+            ('a', "-Force"),
+            ('A', "-Force"),
+            ('R', "-Recurse"),
+            ('l', ""),
+            ('h', ""),
+            ('1', ""),
+        ],
+    ),
+    cmdlet("cat", "Get-Content", &[]),
+    cmdlet("pwd", "Get-Location", &[]),
+    cmdlet(
+        "rm",
+        "Remove-Item",
+        &[('r', "-Recurse"), ('R', "-Recurse"), ('f', "-Force")],
+    ),
+    cmdlet(
+        "cp",
+        "Copy-Item",
+        &[('r', "-Recurse"), ('R', "-Recurse"), ('f', "-Force")],
+    ),
+    cmdlet("mv", "Move-Item", &[('f', "-Force")]),
+    cmdlet("mkdir", "New-Item -ItemType Directory -Force", &[('p', "")]),
+    cmdlet("which", "Get-Command", &[]),
+    cmdlet("echo", "Write-Output", &[]),
+    cmdlet(
+        "grep",
+        "Select-String -CaseSensitive -Pattern",
+        &[('i', ""), ('n', "")],
+    ),
+    cmdlet("head", "Get-Content -TotalCount 10", &[]),
+    cmdlet("tail", "Get-Content -Tail 10", &[]),
+];
+
+/// Unix tools with no rewrite here
+const UNIX_ONLY: &[&str] = &[
+    "sed", "awk", "find", "xargs", "touch", "chmod", "chown", "ln", "wc", "sort", "uniq", "du",
+    "df", "ps", "kill", "tar", "open", "xdg-open",
+];
+
+/// Translation for the platform this build runs on; `None` when the command
+/// runs as written.
+pub fn translate_for_platform(command: &[String]) -> Option<Translation> {
+    #[cfg(windows)]
+    {
+        translate_for_windows(command, "powershell.exe", &on_path)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = command;
+        None
+    }
+}
+
+/// Rewrite `command` to run under the PowerShell `powershell_exe` unless
+/// `available` reports its program is installed.
+pub fn translate_for_windows(
+    command: &[String],
+    powershell_exe: &str,
+    available: &dyn Fn(&str) -> bool,
+) -> Option<Translation> {
+    let (program, args) = command.split_first()?;
+    if available(program) {
+        return None;
+    }
+    let original = command.join(" ");
+    let Some(Cmdlet { cmdlet, flags, .. }) = CMDLETS.iter().find(|c| c.unix == program) else {
+        return UNIX_ONLY
+            .contains(&program.as_str())
+            .then(|| Translation::Advisory {
+                note: format!(
+                    "`{program}` is a unix command and is not installed here; \
+                     this is Windows, so use PowerShell cmdlets instead"
+                ),
+            });
+    };
+
+    let unsupported = || Translation::Advisory {
+        note: format!(
+            "`{original}` is a unix command and is not installed here; \
+             this is Windows, so use the PowerShell `{}` cmdlet instead",
+            cmdlet.split(' ').next().unwrap_or(cmdlet)
+        ),
+    };
+
+    let mut cmdlet = cmdlet.to_string();
+    let mut params: Vec<&str> = Vec::new();
+    let mut positional: Vec<&str> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) else {
+            positional.push(arg);
+            positional.extend(iter.by_ref().map(String::as_str));
+            break;
+        };
+        if short == "-" {
+            positional.extend(iter.by_ref().map(String::as_str));
+            break;
+        }
+        // `head -n 5`, `head -5`
+        if program == "head" || program == "tail" {
+            let count = if short == "n" {
+                iter.next().map(String::as_str)
+            } else {
+                Some(short)
+            };
+            match count.filter(|n| n.parse::<u64>().is_ok()) {
+                Some(n) => cmdlet = cmdlet.replace("10", n),
+                None => return Some(unsupported()),
+            }
+            continue;
+        }
+        for flag in short.chars() {
+            let Some(&(_, param)) = flags.iter().find(|(f, _)| *f == flag) else {
+                return Some(unsupported());
+            };
+            if program == "grep" && flag == 'i' {
+                cmdlet = cmdlet.replace(" -CaseSensitive", "");
+            }
+            if !param.is_empty() && !params.contains(&param) {
+                params.push(param);
+            }
+        }
+    }
+
+    let mut script = cmdlet;
+    for part in params
+        .into_iter()
+        .map(str::to_string)
+        .chain(positional.into_iter().map(quote))
+    {
+        script.push(' ');
+        script.push_str(&part);
+    }
+    Some(Translation::Rewritten {
+        note: format!("`{original}` was run as PowerShell `{script}` (Windows)"),
+        command: vec![
+            powershell_exe.to_string(),
+            "-NoProfile".to_string(),
+            "-Command".to_string(),
+            script,
+        ],
+    })
+}
+
+/// Quote an argument for PowerShell unless it is a plain word.
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./\\:*?".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "''"))
+    }
+}
+
+/// Whether `program` resolves through PATH and PATHEXT.
+#[cfg(windows)]
+fn on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    let exts = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    std::env::split_paths(&path).any(|dir| {
+        dir.join(program).is_file()
+            || exts
+                .split(';')
+                .any(|ext| dir.join(format!("{program}{ext}")).is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(command: &str) -> Option<Translation> {
+        let command: Vec<String> = command.split(' ').map(String::from).collect();
+        translate_for_windows(&command, "pwsh", &|_| false)
+    }
+
+    fn script(command: &str) -> String {
+        match translate(command) {
+            Some(Translation::Rewritten { command, .. }) => command[3].clone(),
+            other => panic!("expected a rewrite of {command}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn common_commands_become_cmdlets() {
+        assert_eq!(script("ls -al"), "Get-ChildItem -Force");
+        assert_eq!(script("ls -la slides"), "Get-ChildItem -Force slides");
+        assert_eq!(script("rm -rf build"), "Remove-Item -Recurse -Force build");
+        assert_eq!(
+            script("mkdir -p slides/assets"),
+            "New-Item -ItemType Directory -Force slides/assets"
+        );
+        assert_eq!(
+            script("head -n 5 deck.md"),
+            "Get-Content -TotalCount 5 deck.md"
+        );
+        assert_eq!(script("tail -20 log.txt"), "Get-Content -Tail 20 log.txt");
+        assert_eq!(
+            script("grep TODO deck.md"),
+            "Select-String -CaseSensitive -Pattern TODO deck.md"
+        );
+        assert_eq!(
+            script("grep -i todo deck.md"),
+            "Select-String -Pattern todo deck.md"
+        );
+        assert_eq!(script("cat it's.md"), "Get-Content 'it''s.md'");
+    }
+
+    #[test]
+    fn the_rewrite_runs_through_powershell() {
+        let Some(Translation::Rewritten { command, note }) = translate("pwd") else {
+            panic!("expected a rewrite");
+        };
+        assert_eq!(command, ["pwsh", "-NoProfile", "-Command", "Get-Location"]);
+        assert_eq!(note, "`pwd` was run as PowerShell `Get-Location` (Windows)");
+    }
+
+    #[test]
+    fn other_commands_are_left_alone_or_advised() {
+        assert!(matches!(
+            translate("ls --color=auto"),
+            Some(Translation::Advisory { .. })
+        ));
+        assert!(matches!(
+            translate("sed -i s/a/b/ deck.md"),
+            Some(Translation::Advisory { .. })
+        ));
+        assert_eq!(translate("npm run build"), None);
+
+        let ls = ["ls".to_string(), "-al".to_string()];
+        assert_eq!(translate_for_windows(&ls, "pwsh", &|p| p == "ls"), None);
+    }
+}
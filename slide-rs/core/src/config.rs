@@ -1,6 +1,6 @@
 use crate::approval_manager::AskForApproval;
 use crate::config_types::{CoreConfig, ShellEnvironmentPolicy};
-use crate::seatbelt::SandboxPolicy;
+use protocol::config_types::SandboxPolicy;
 use slide_common::ApprovalMode;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
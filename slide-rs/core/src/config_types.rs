@@ -1,6 +1,9 @@
-use crate::seatbelt::SandboxPolicy;
+use anyhow::Context;
+use protocol::config_types::SandboxPolicy;
 use serde::{Deserialize, Serialize};
+use slide_common::project::MANIFEST_FILE;
 use slide_common::ApprovalMode;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct CoreConfig {
@@ -21,8 +24,22 @@ impl Default for CoreConfig {
     }
 }
 
-/// Controls which environment variables are passed to shell commands
+/// Controls which environment variables are passed to shell commands.
+///
+/// [`crate::exec_env::create_env`] applies the fields in this order, so a
+/// later step wins over an earlier one:
+///
+/// 1. `inherit` picks the starting set from the parent environment.
+/// 2. Unless `ignore_default_excludes`, names matching `*KEY*`, `*SECRET*`
+///    or `*TOKEN*` (any case) are dropped.
+/// 3. Names matching `exclude` are dropped.
+/// 4. Parent variables matching `include` are added back, even when an
+///    exclude or the `inherit` mode removed them.
+/// 5. `set` entries are added; `${VAR}` in a value is replaced with the
+///    parent's `VAR` (empty when unset).
+/// 6. Names in `unset` are removed.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct ShellEnvironmentPolicy {
     /// Strategy for inheriting environment variables from the parent process
     pub inherit: ShellEnvironmentPolicyInherit,
@@ -32,8 +49,10 @@ pub struct ShellEnvironmentPolicy {
     pub exclude: Vec<EnvironmentVariablePattern>,
     /// Additional patterns to explicitly include (overrides excludes)
     pub include: Vec<EnvironmentVariablePattern>,
-    /// Additional variables to set directly
+    /// Additional variables to set directly; values may use `${VAR}`
     pub set: std::collections::HashMap<String, String>,
+    /// Variables removed after everything else
+    pub unset: Vec<String>,
 }
 
 impl Default for ShellEnvironmentPolicy {
@@ -44,10 +63,69 @@ impl Default for ShellEnvironmentPolicy {
             exclude: Vec::new(),
             include: Vec::new(),
             set: std::collections::HashMap::new(),
+            unset: Vec::new(),
         }
     }
 }
 
+impl ShellEnvironmentPolicy {
+    /// Apply the `[shell_environment]` table of the project manifest in
+    /// `slides_dir`, if there is one.
+    pub fn with_project_overrides(self, slides_dir: &Path) -> anyhow::Result<Self> {
+        let path = slides_dir.join(MANIFEST_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let manifest: ProjectShellEnvironment = toml::from_str(&content)
+            .with_context(|| format!("invalid [shell_environment] in {}", path.display()))?;
+        Ok(match manifest.shell_environment {
+            Some(overrides) => overrides.apply(self),
+            None => self,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectShellEnvironment {
+    shell_environment: Option<ShellEnvironmentOverrides>,
+}
+
+/// A project's changes to the shell environment policy. Modes replace the
+/// base policy's; patterns and variables are added to it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ShellEnvironmentOverrides {
+    pub inherit: Option<ShellEnvironmentPolicyInherit>,
+    pub ignore_default_excludes: Option<bool>,
+    /// Case-insensitive glob patterns
+    pub exclude: Vec<String>,
+    /// Case-insensitive glob patterns
+    pub include: Vec<String>,
+    pub set: std::collections::HashMap<String, String>,
+    pub unset: Vec<String>,
+}
+
+impl ShellEnvironmentOverrides {
+    pub fn apply(self, mut policy: ShellEnvironmentPolicy) -> ShellEnvironmentPolicy {
+        if let Some(inherit) = self.inherit {
+            policy.inherit = inherit;
+        }
+        if let Some(ignore) = self.ignore_default_excludes {
+            policy.ignore_default_excludes = ignore;
+        }
+        let patterns = |p: &String| EnvironmentVariablePattern::new_case_insensitive(p);
+        policy.exclude.extend(self.exclude.iter().map(patterns));
+        policy.include.extend(self.include.iter().map(patterns));
+        // A project `set` of a name the base policy unsets brings it back
+        policy.unset.retain(|name| !self.set.contains_key(name));
+        policy.set.extend(self.set);
+        policy.unset.extend(self.unset);
+        policy
+    }
+}
+
 /// Strategy for inheriting environment variables
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -91,25 +169,32 @@ impl EnvironmentVariablePattern {
     }
 }
 
+/// `*` matches any run of characters and `?` a single one.
 fn glob_match(pattern: &str, text: &str) -> bool {
-    if pattern == "*" {
-        return true;
-    }
-
-    if pattern.starts_with('*') && pattern.ends_with('*') {
-        let middle = &pattern[1..pattern.len() - 1];
-        return text.contains(middle);
-    }
-
-    if pattern.starts_with('*') {
-        let suffix = &pattern[1..];
-        return text.ends_with(suffix);
-    }
-
-    if pattern.ends_with('*') {
-        let prefix = &pattern[..pattern.len() - 1];
-        return text.starts_with(prefix);
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it is tried from
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((after_star, from)) => {
+                    p = after_star;
+                    t = from + 1;
+                    backtrack = Some((after_star, from + 1));
+                }
+                None => return false,
+            },
+        }
     }
-
-    pattern == text
+    pattern[p..].iter().all(|&c| c == '*')
 }
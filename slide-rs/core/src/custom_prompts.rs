@@ -1,23 +1,32 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CustomPrompt {
-    pub id: String,
-    pub name: String,
-    pub prompt: String,
-    pub description: Option<String>,
-}
+//! Prompt templates saved as `~/.slide/prompts/<name>.md` and sent with
+//! `/<name> [args]`.
+//!
+//! A template may start with frontmatter:
+//!
+//! ```text
+//! ---
+//! description: Review a file for one concern
+//! args: file, focus
+//! ---
+//! Review {{file}} with a focus on {{focus}}.
+//! ```
+//!
+//! `{{arg}}` placeholders are filled from `name=value` arguments or, in
+//! order, from positional ones; the last argument takes the remaining words.
+//! `$ARGUMENTS` stands for the whole argument text.
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+
+pub use protocol::custom_prompts::CustomPrompt;
+use thiserror::Error;
 use tokio::fs;
 
-/// Return the default prompts directory: `$CODEX_HOME/prompts`.
-/// If `CODEX_HOME` cannot be resolved, returns `None`.
-pub fn default_prompts_dir() -> Option<PathBuf> {
-    crate::config::find_codex_home()
-        .ok()
-        .map(|home| home.join("prompts"))
+/// Return the default prompts directory: `~/.slide/prompts`.
+pub fn default_prompts_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map_or_else(|| PathBuf::from("."), PathBuf::from);
+    home.join(".slide").join("prompts")
 }
 
 /// Discover prompt files in the given directory, returning entries sorted by name.
@@ -51,41 +60,195 @@ pub async fn discover_prompts_in_excluding(
         let is_md = path
             .extension()
             .and_then(|s| s.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("md"))
-            .unwrap_or(false);
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
         if !is_md {
             continue;
         }
-        let Some(name) = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .map(|s| s.to_string())
-        else {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
             continue;
         };
-        if exclude.contains(&name) {
-            continue;
-        }
-        let content = match fs::read_to_string(&path).await {
+        let text = match fs::read_to_string(&path).await {
             Ok(s) => s,
             Err(_) => continue,
         };
-        out.push(CustomPrompt {
-            name,
-            path,
-            content,
-        });
+        let prompt = parse_prompt(stem, path.clone(), &text);
+        if exclude.contains(&prompt.name) {
+            continue;
+        }
+        out.push(prompt);
     }
     out.sort_by(|a, b| a.name.cmp(&b.name));
+    out.dedup_by(|a, b| a.name == b.name);
     out
 }
 
+/// Build a prompt from a template file; frontmatter `name` overrides `stem`.
+pub fn parse_prompt(stem: &str, path: PathBuf, text: &str) -> CustomPrompt {
+    let (fields, body) = split_frontmatter(text);
+    let args = fields
+        .get("args")
+        .map(|value| {
+            value
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split([',', ' '])
+                .map(|arg| unquote(arg.trim()).to_string())
+                .filter(|arg| !arg.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    CustomPrompt {
+        name: fields
+            .get("name")
+            .map(|name| unquote(name).to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| stem.to_string()),
+        path,
+        content: body.to_string(),
+        description: fields
+            .get("description")
+            .map(|d| unquote(d).to_string())
+            .filter(|d| !d.is_empty()),
+        args,
+    }
+}
+
+/// `key: value` lines between leading `---` fences, and the text after them.
+fn split_frontmatter(text: &str) -> (HashMap<String, String>, &str) {
+    let mut fields = HashMap::new();
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (fields, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" {
+            return (fields, rest[offset..].trim_start_matches(['\r', '\n']));
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            fields.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    // No closing fence: not frontmatter after all
+    (HashMap::new(), text)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PromptArgsError {
+    #[error("/{name} is missing {missing}; usage: /{name} {usage}")]
+    Missing {
+        name: String,
+        missing: String,
+        usage: String,
+    },
+    #[error("/{name} has no argument `{arg}`; usage: /{name} {usage}")]
+    Unknown {
+        name: String,
+        arg: String,
+        usage: String,
+    },
+    #[error("/{name}: {message}")]
+    Malformed { name: String, message: String },
+}
+
+/// Argument hint for `prompt`, e.g. `<file> <focus>`.
+pub fn prompt_usage(prompt: &CustomPrompt) -> String {
+    prompt
+        .args
+        .iter()
+        .map(|arg| format!("<{arg}>"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The text sent as the user turn for `/<prompt.name> <args>`.
+pub fn render_prompt(prompt: &CustomPrompt, args: &str) -> Result<String, PromptArgsError> {
+    let usage = prompt_usage(prompt);
+    let words = shlex::split(args).ok_or_else(|| PromptArgsError::Malformed {
+        name: prompt.name.clone(),
+        message: "unterminated quote".to_string(),
+    })?;
+
+    let mut values: HashMap<&str, String> = HashMap::new();
+    let mut positional = Vec::new();
+    for word in &words {
+        match word.split_once('=') {
+            Some((key, value)) if prompt.args.iter().any(|arg| arg == key) => {
+                values.insert(key, value.to_string());
+            }
+            Some((key, _)) if !prompt.args.is_empty() && is_arg_name(key) => {
+                return Err(PromptArgsError::Unknown {
+                    name: prompt.name.clone(),
+                    arg: key.to_string(),
+                    usage,
+                });
+            }
+            _ => positional.push(word.as_str()),
+        }
+    }
+    let unfilled: Vec<&str> = prompt
+        .args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| !values.contains_key(arg))
+        .collect();
+    let mut positional = positional.into_iter();
+    for (i, arg) in unfilled.iter().enumerate() {
+        let value = if i + 1 == unfilled.len() {
+            positional.by_ref().collect::<Vec<_>>().join(" ")
+        } else {
+            positional.next().unwrap_or_default().to_string()
+        };
+        if value.is_empty() {
+            return Err(PromptArgsError::Missing {
+                name: prompt.name.clone(),
+                missing: unfilled[i..]
+                    .iter()
+                    .map(|arg| format!("<{arg}>"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                usage,
+            });
+        }
+        values.insert(arg, value);
+    }
+
+    let mut text = prompt.content.replace("$ARGUMENTS", args.trim());
+    for (arg, value) in values {
+        text = text.replace(&format!("{{{{{arg}}}}}"), &value);
+    }
+    Ok(text.trim().to_string())
+}
+
+fn is_arg_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use tempfile::tempdir;
 
+    fn prompt(text: &str) -> CustomPrompt {
+        parse_prompt("review", PathBuf::from("review.md"), text)
+    }
+
     #[tokio::test]
     async fn empty_when_dir_missing() {
         let tmp = tempdir().expect("create TempDir");
@@ -133,4 +296,68 @@ mod tests {
         let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
         assert_eq!(names, vec!["valid".to_string()]);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn frontmatter_names_are_discovered_and_excluded() {
+        let tmp = tempdir().expect("create TempDir");
+        let dir = tmp.path();
+        fs::write(dir.join("cr.md"), b"---\nname: code-review\n---\nReview").unwrap();
+        fs::write(dir.join("code-review.md"), b"Also review").unwrap();
+        fs::write(dir.join("plan.md"), b"Plan").unwrap();
+
+        let found = discover_prompts_in(dir).await;
+        let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["code-review".to_string(), "plan".to_string()]);
+
+        let exclude = HashSet::from(["code-review".to_string()]);
+        let found = discover_prompts_in_excluding(dir, &exclude).await;
+        let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["plan".to_string()]);
+    }
+
+    #[test]
+    fn frontmatter_sets_description_and_args() {
+        let review = prompt(
+            "---\nname: code-review\ndescription: \"Review a file\"\nargs: [file, focus]\n---\n\nReview {{file}} for {{focus}}.\n",
+        );
+        assert_eq!(review.name, "code-review");
+        assert_eq!(review.description.as_deref(), Some("Review a file"));
+        assert_eq!(review.args, vec!["file", "focus"]);
+        assert_eq!(review.content, "Review {{file}} for {{focus}}.\n");
+
+        let plain = prompt("---\nnot frontmatter");
+        assert_eq!(plain.name, "review");
+        assert_eq!(plain.content, "---\nnot frontmatter");
+    }
+
+    #[test]
+    fn arguments_fill_placeholders() {
+        let review = prompt("---\nargs: file, focus\n---\nReview {{file}} for {{focus}}.");
+        assert_eq!(
+            render_prompt(&review, "src/main.rs error handling"),
+            Ok("Review src/main.rs for error handling.".to_string())
+        );
+        assert_eq!(
+            render_prompt(&review, "focus=speed 'my deck.md'"),
+            Ok("Review my deck.md for speed.".to_string())
+        );
+        assert_eq!(
+            render_prompt(&review, "src/main.rs"),
+            Err(PromptArgsError::Missing {
+                name: "review".to_string(),
+                missing: "<focus>".to_string(),
+                usage: "<file> <focus>".to_string(),
+            })
+        );
+        assert!(matches!(
+            render_prompt(&review, "a b colour=red"),
+            Err(PromptArgsError::Unknown { .. })
+        ));
+
+        let free = prompt("Summarize: $ARGUMENTS");
+        assert_eq!(
+            render_prompt(&free, " the last turn "),
+            Ok("Summarize: the last turn".to_string())
+        );
+    }
+}
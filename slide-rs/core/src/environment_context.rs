@@ -1,4 +1,4 @@
-use crate::seatbelt::SandboxPolicy;
+use protocol::config_types::SandboxPolicy;
 use crate::shell::Shell;
 use slide_common::ApprovalMode;
 use std::path::PathBuf;
@@ -33,7 +33,7 @@ impl EnvironmentContext {
             approval_policy,
             sandbox_mode: match sandbox_policy {
                 Some(SandboxPolicy::ReadOnly) => Some(SandboxMode::ReadOnly),
-                Some(SandboxPolicy::WorkspaceWrite) => Some(SandboxMode::WorkspaceWrite),
+                Some(SandboxPolicy::WorkspaceWrite { .. }) => Some(SandboxMode::WorkspaceWrite),
                 Some(SandboxPolicy::DangerFullAccess) => Some(SandboxMode::DangerFullAccess),
                 None => None,
             },
@@ -0,0 +1,872 @@
+//! Every event of a session, what the user typed and how they answered
+//! approvals, as one JSON line each under
+//! `~/.slide/sessions/events/<session>.jsonl`, so a past session can be
+//! replayed in the TUI or exported as markdown or HTML (`slide replay`,
+//! `slide sessions export`, `/export-chat`).
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use crate::codex2::{ApplyPatchFileChange, DiffStat, Event, FileDiffStat, ReviewDecision};
+use crate::plan_tool::StepStatus;
+use slide_common::ApprovalTimeoutAction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEntry {
+    /// A message the user submitted
+    User {
+        text: String,
+    },
+    /// How the user answered the approval request `id`
+    Approval {
+        id: String,
+        decision: ReviewDecision,
+    },
+    Event(Event),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEntry {
+    /// Milliseconds since the Unix epoch
+    pub ts_ms: u64,
+    pub entry: SessionEntry,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    dir: PathBuf,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        let home = std::env::var_os("HOME").map_or_else(|| PathBuf::from("."), PathBuf::from);
+        Self::new(home.join(".slide").join("sessions").join("events"))
+    }
+}
+
+impl EventLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{session_id}.jsonl"))
+    }
+
+    /// Append an entry to the session's log. Nothing is written while the
+    /// privacy settings disable history.
+    pub fn record(&self, session_id: &str, entry: &SessionEntry) -> Result<()> {
+        if !slide_common::privacy::active().history_enabled() {
+            return Ok(());
+        }
+        let ts_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut value = serde_json::to_value(RecordedEntry {
+            ts_ms,
+            entry: entry.clone(),
+        })?;
+        slide_common::secrets::active().redact_json(&mut value);
+        let mut line = serde_json::to_string(&value)?;
+        line.push('\n');
+
+        std::fs::create_dir_all(&self.dir)?;
+        let mut opts = OpenOptions::new();
+        opts.append(true).create(true);
+        #[cfg(unix)]
+        {
+            opts.mode(0o600);
+        }
+        let mut f = opts.open(self.session_path(session_id))?;
+        f.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// The most recently updated session, if any was recorded.
+    pub fn latest_session(&self) -> Option<String> {
+        self.sessions().into_iter().next()
+    }
+
+    /// Ids of the recorded sessions, most recently updated first.
+    pub fn sessions(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<(SystemTime, String)> = entries
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|e| {
+                let modified = e.metadata().ok()?.modified().ok()?;
+                Some((modified, e.path().file_stem()?.to_str()?.to_string()))
+            })
+            .collect();
+        sessions.sort_by(|a, b| b.cmp(a));
+        sessions.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+/// Document formats a session can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// `markdown`/`md` or `html`/`htm`, in any case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// The format a file name asks for by its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(path.extension()?.to_str()?)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn render(self, title: &str, entries: &[RecordedEntry]) -> String {
+        match self {
+            Self::Markdown => to_markdown(title, entries),
+            Self::Html => to_html(title, entries),
+        }
+    }
+}
+
+/// Read a recorded session. Lines that do not parse, e.g. events of a newer
+/// version, are skipped.
+pub fn load(path: &Path) -> Result<Vec<RecordedEntry>> {
+    let f =
+        std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    Ok(std::io::BufReader::new(f)
+        .lines()
+        .map_while(std::result::Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// One block of a rendered session
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    User(String),
+    Agent(String),
+    Command {
+        command: String,
+        output: String,
+        exit_code: Option<i32>,
+    },
+    Plan(Vec<(StepStatus, String)>),
+    /// Commands waiting for approval, and the answer once given
+    Approval {
+        id: String,
+        commands: Vec<String>,
+        decision: Option<ReviewDecision>,
+    },
+    /// A patch waiting for approval: each file with its diff
+    Patch {
+        id: String,
+        files: Vec<(String, String)>,
+        decision: Option<ReviewDecision>,
+    },
+    /// Lines changed by an applied patch
+    Applied {
+        files: Vec<FileDiffStat>,
+        total: DiffStat,
+    },
+    /// Everything the turn changed, as a unified diff
+    Diff(String),
+    Notice(String),
+}
+
+/// Group the entries into what a reader sees: messages, commands with their
+/// output, plans and notices.
+fn blocks(entries: &[RecordedEntry]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut agent = String::new();
+    let flush = |agent: &mut String, blocks: &mut Vec<Block>| {
+        let text = std::mem::take(agent);
+        if !text.trim().is_empty() {
+            blocks.push(Block::Agent(text.trim_end().to_string()));
+        }
+    };
+    for recorded in entries {
+        let event = match &recorded.entry {
+            SessionEntry::User { text } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::User(text.clone()));
+                continue;
+            }
+            SessionEntry::Approval { id, decision } => {
+                answer_approval(&mut blocks, id, decision.clone());
+                continue;
+            }
+            SessionEntry::Event(event) => event,
+        };
+        match event {
+            Event::AgentMessageDelta { delta } => agent.push_str(delta),
+            Event::AgentMessage { message } => {
+                if !agent.is_empty() && !agent.ends_with('\n') {
+                    agent.push('\n');
+                }
+                agent.push_str(message);
+            }
+            Event::ExecCommandBegin { command, .. } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Command {
+                    command: command.join(" "),
+                    output: String::new(),
+                    exit_code: None,
+                });
+            }
+            Event::ExecCommandOutputDelta { chunk, .. } => {
+                if let Some(Block::Command { output, .. }) = blocks.last_mut() {
+                    output.push_str(chunk);
+                }
+            }
+            Event::ExecCommandEnd { exit_code, .. } => {
+                if let Some(Block::Command {
+                    exit_code: code, ..
+                }) = blocks.last_mut()
+                {
+                    *code = Some(*exit_code);
+                }
+            }
+            Event::PlanUpdate(args) => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Plan(
+                    args.plan
+                        .iter()
+                        .map(|item| (item.status, item.step.clone()))
+                        .collect(),
+                ));
+            }
+            Event::ApplyPatchApprovalRequest { id, changes, .. } => {
+                flush(&mut agent, &mut blocks);
+                let mut files: Vec<(String, String)> = changes
+                    .iter()
+                    .map(|(path, change)| (path.display().to_string(), change_diff(change)))
+                    .collect();
+                files.sort();
+                blocks.push(Block::Patch {
+                    id: id.clone(),
+                    files,
+                    decision: None,
+                });
+            }
+            Event::PatchApplyEnd {
+                success: true,
+                files,
+                total,
+            } if !files.is_empty() => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Applied {
+                    files: files.clone(),
+                    total: *total,
+                });
+            }
+            Event::TurnDiff { unified_diff } if !unified_diff.trim().is_empty() => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Diff(unified_diff.clone()));
+            }
+            Event::ExecApprovalRequest { id, command, .. } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Approval {
+                    id: id.clone(),
+                    commands: vec![command.join(" ")],
+                    decision: None,
+                });
+            }
+            Event::ExecPlanApprovalRequest { id, commands, .. } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Approval {
+                    id: id.clone(),
+                    commands: commands.iter().map(|command| command.join(" ")).collect(),
+                    decision: None,
+                });
+            }
+            Event::ApprovalTimedOut { id, action } => {
+                answer_approval(&mut blocks, id, timeout_decision(*action));
+                blocks.push(Block::Notice("Approval timed out".to_string()));
+            }
+            Event::CwdChanged { cwd } => {
+                blocks.push(Block::Notice(format!(
+                    "Working directory: {}",
+                    cwd.display()
+                )));
+            }
+            Event::TurnAborted => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Notice("Interrupted".to_string()));
+            }
+            Event::TurnRetrying {
+                attempt,
+                max_retries,
+                delay_ms,
+                error,
+            } => {
+                // The partial reply is sent again from the start
+                agent.clear();
+                blocks.push(Block::Notice(retry_text(
+                    *attempt,
+                    *max_retries,
+                    *delay_ms,
+                    error,
+                )));
+            }
+            Event::GuardrailTripped { message, .. } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Notice(message.clone()));
+            }
+            Event::ContentFlagged {
+                path,
+                reasons,
+                blocked,
+            } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Notice(content_flag_text(path, reasons, *blocked)));
+            }
+            Event::Error { message } => {
+                flush(&mut agent, &mut blocks);
+                blocks.push(Block::Notice(format!("Error: {message}")));
+            }
+            Event::TaskComplete => flush(&mut agent, &mut blocks),
+            _ => {}
+        }
+    }
+    flush(&mut agent, &mut blocks);
+    blocks
+}
+
+/// Record the answer to the approval block of request `id`.
+fn answer_approval(blocks: &mut [Block], id: &str, decision: ReviewDecision) {
+    let answered = blocks.iter_mut().rev().find_map(|block| match block {
+        Block::Approval {
+            id: asked,
+            decision: answer,
+            ..
+        }
+        | Block::Patch {
+            id: asked,
+            decision: answer,
+            ..
+        } if asked == id => Some(answer),
+        _ => None,
+    });
+    if let Some(answer) = answered {
+        *answer = Some(decision);
+    }
+}
+
+/// A file change as a unified diff; new files show their content as added
+/// lines.
+fn change_diff(change: &ApplyPatchFileChange) -> String {
+    match change {
+        ApplyPatchFileChange::Add { content } => {
+            content.lines().map(|line| format!("+{line}\n")).collect()
+        }
+        ApplyPatchFileChange::Delete => "(deleted)\n".to_string(),
+        ApplyPatchFileChange::Update {
+            unified_diff,
+            move_path,
+            ..
+        } => match move_path {
+            Some(to) => format!("(moved to {})\n{unified_diff}", to.display()),
+            None => unified_diff.clone(),
+        },
+    }
+}
+
+/// Notice for a model request sent again, e.g. "Model request failed
+/// (openai http 503 …); retry 1/3 in 1.0s".
+pub fn retry_text(attempt: u32, max_retries: u32, delay_ms: u64, error: &str) -> String {
+    format!(
+        "Model request failed ({error}); retry {attempt}/{max_retries} in {:.1}s",
+        delay_ms as f64 / 1000.0
+    )
+}
+
+/// Notice for deck content the `moderation` config flagged, e.g.
+/// "talk.md: blocked by the content safety filter (violence)".
+pub fn content_flag_text(path: &Path, reasons: &[String], blocked: bool) -> String {
+    let outcome = if blocked {
+        "blocked by the content safety filter"
+    } else {
+        "written, flagged by the content safety filter"
+    };
+    format!("{}: {outcome} ({})", path.display(), reasons.join(", "))
+}
+
+/// The answer an approval request that timed out counts as.
+pub fn timeout_decision(action: ApprovalTimeoutAction) -> ReviewDecision {
+    match action {
+        ApprovalTimeoutAction::Deny => ReviewDecision::Denied,
+        ApprovalTimeoutAction::AbortTurn => ReviewDecision::Abort,
+    }
+}
+
+/// How the user answered an approval, e.g. "approved for the session".
+pub fn decision_text(decision: &ReviewDecision) -> String {
+    match decision {
+        ReviewDecision::Approved => "approved".to_string(),
+        ReviewDecision::ApprovedForSession => "approved for the session".to_string(),
+        ReviewDecision::ApprovedPrefix => "approved by prefix for the session".to_string(),
+        ReviewDecision::ApprovedFiles(files) => {
+            let files: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+            format!("approved only {}", files.join(", "))
+        }
+        ReviewDecision::ApprovedCommands(indices) => {
+            let numbers: Vec<String> = indices.iter().map(|i| (i + 1).to_string()).collect();
+            format!("approved only commands {}", numbers.join(", "))
+        }
+        ReviewDecision::Denied => "denied".to_string(),
+        ReviewDecision::Abort => "denied, turn stopped".to_string(),
+    }
+}
+
+/// "approval requested" or the answer, for the heading of an approval.
+fn approval_status(decision: Option<&ReviewDecision>) -> String {
+    decision.map_or_else(|| "approval requested".to_string(), decision_text)
+}
+
+fn stat_text(stat: DiffStat) -> String {
+    format!("+{} -{}", stat.added, stat.removed)
+}
+
+fn plan_marker(status: StepStatus) -> &'static str {
+    match status {
+        StepStatus::Completed => "[x]",
+        StepStatus::InProgress => "[~]",
+        StepStatus::Failed => "[!]",
+        StepStatus::Pending => "[ ]",
+    }
+}
+
+/// The session as a markdown document.
+pub fn to_markdown(title: &str, entries: &[RecordedEntry]) -> String {
+    let mut out = format!("# {title}\n");
+    for block in blocks(entries) {
+        out.push('\n');
+        match block {
+            Block::User(text) => {
+                let _ = writeln!(out, "## You\n\n{text}");
+            }
+            Block::Agent(text) => {
+                let _ = writeln!(out, "## Agent\n\n{text}");
+            }
+            Block::Command {
+                command,
+                output,
+                exit_code,
+            } => {
+                let fence = if output.contains("```") { "~~~" } else { "```" };
+                let _ = writeln!(out, "{fence}console\n$ {command}");
+                out.push_str(&output);
+                if !output.is_empty() && !output.ends_with('\n') {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "{fence}");
+                if let Some(code) = exit_code.filter(|code| *code != 0) {
+                    let _ = writeln!(out, "\nExited with code {code}");
+                }
+            }
+            Block::Plan(steps) => {
+                out.push_str("**Plan**\n\n");
+                for (status, step) in steps {
+                    let _ = writeln!(out, "- {} {step}", plan_marker(status));
+                }
+            }
+            Block::Approval {
+                commands, decision, ..
+            } => {
+                let status = approval_status(decision.as_ref());
+                match commands.as_slice() {
+                    [command] => {
+                        let _ = writeln!(out, "> Command `{command}`: {status}");
+                    }
+                    _ => {
+                        let _ = writeln!(out, "> {} commands: {status}", commands.len());
+                        for command in commands {
+                            let _ = writeln!(out, "> - `{command}`");
+                        }
+                    }
+                }
+            }
+            Block::Patch {
+                files, decision, ..
+            } => {
+                let _ = writeln!(
+                    out,
+                    "**Proposed changes** ({})",
+                    approval_status(decision.as_ref())
+                );
+                for (file, diff) in files {
+                    let _ = writeln!(out, "\n`{file}`\n");
+                    push_fenced(&mut out, "diff", &diff);
+                }
+            }
+            Block::Applied { files, total } => {
+                let _ = writeln!(
+                    out,
+                    "**Applied** {} file(s), {}\n",
+                    files.len(),
+                    stat_text(total)
+                );
+                for file in files {
+                    let _ = writeln!(out, "- `{}` {}", file.path.display(), stat_text(file.stat));
+                }
+            }
+            Block::Diff(diff) => {
+                out.push_str("**Changes of the turn**\n\n");
+                push_fenced(&mut out, "diff", &diff);
+            }
+            Block::Notice(text) => {
+                let _ = writeln!(out, "> {text}");
+            }
+        }
+    }
+    out
+}
+
+/// `text` in a fenced code block that its own backticks cannot close.
+fn push_fenced(out: &mut String, lang: &str, text: &str) {
+    let fence = if text.contains("```") { "~~~" } else { "```" };
+    let _ = writeln!(out, "{fence}{lang}");
+    out.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.push('\n');
+    }
+    let _ = writeln!(out, "{fence}");
+}
+
+/// The session as a standalone HTML page.
+pub fn to_html(title: &str, entries: &[RecordedEntry]) -> String {
+    let mut body = String::new();
+    for block in blocks(entries) {
+        match block {
+            Block::User(text) => {
+                let _ = writeln!(
+                    body,
+                    "<section class=\"user\"><h2>You</h2><pre>{}</pre></section>",
+                    escape_html(&text)
+                );
+            }
+            Block::Agent(text) => {
+                let _ = writeln!(
+                    body,
+                    "<section class=\"agent\"><h2>Agent</h2><pre>{}</pre></section>",
+                    escape_html(&text)
+                );
+            }
+            Block::Command {
+                command,
+                output,
+                exit_code,
+            } => {
+                let status = match exit_code {
+                    Some(0) | None => String::new(),
+                    Some(code) => format!("<p class=\"exit\">Exited with code {code}</p>"),
+                };
+                let _ = writeln!(
+                    body,
+                    "<section class=\"command\"><pre><b>$ {}</b>\n{}</pre>{status}</section>",
+                    escape_html(&command),
+                    escape_html(&output)
+                );
+            }
+            Block::Plan(steps) => {
+                body.push_str("<section class=\"plan\"><h3>Plan</h3><ul>");
+                for (status, step) in steps {
+                    let _ = write!(
+                        body,
+                        "<li>{} {}</li>",
+                        plan_marker(status),
+                        escape_html(&step)
+                    );
+                }
+                body.push_str("</ul></section>\n");
+            }
+            Block::Approval {
+                commands, decision, ..
+            } => {
+                let status = approval_status(decision.as_ref());
+                body.push_str("<section class=\"approval\"><ul>");
+                for command in commands {
+                    let _ = write!(body, "<li><code>{}</code></li>", escape_html(&command));
+                }
+                let _ = writeln!(body, "</ul><p class=\"notice\">{status}</p></section>");
+            }
+            Block::Patch {
+                files, decision, ..
+            } => {
+                let _ = write!(
+                    body,
+                    "<section class=\"patch\"><h3>Proposed changes ({})</h3>",
+                    approval_status(decision.as_ref())
+                );
+                for (file, diff) in files {
+                    let _ = write!(
+                        body,
+                        "<h4><code>{}</code></h4>{}",
+                        escape_html(&file),
+                        diff_html(&diff)
+                    );
+                }
+                body.push_str("</section>\n");
+            }
+            Block::Applied { files, total } => {
+                let _ = write!(
+                    body,
+                    "<section class=\"applied\"><h3>Applied {} file(s), {}</h3><ul>",
+                    files.len(),
+                    stat_text(total)
+                );
+                for file in files {
+                    let _ = write!(
+                        body,
+                        "<li><code>{}</code> {}</li>",
+                        escape_html(&file.path.display().to_string()),
+                        stat_text(file.stat)
+                    );
+                }
+                body.push_str("</ul></section>\n");
+            }
+            Block::Diff(diff) => {
+                let _ = writeln!(
+                    body,
+                    "<section class=\"diff\"><h3>Changes of the turn</h3>{}</section>",
+                    diff_html(&diff)
+                );
+            }
+            Block::Notice(text) => {
+                let _ = writeln!(body, "<p class=\"notice\">{}</p>", escape_html(&text));
+            }
+        }
+    }
+    let title = escape_html(title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; max-width: 56rem; margin: 2rem auto; padding: 0 1rem; }}\n\
+         pre {{ white-space: pre-wrap; }}\n\
+         .user h2 {{ color: #0a7ea4; }}\n\
+         .command pre, pre.diff {{ background: #f4f4f4; padding: .5rem; }}\n\
+         .add {{ color: #1a7f37; }}\n\
+         .del {{ color: #cf222e; }}\n\
+         .hunk {{ color: #8250df; }}\n\
+         .notice, .exit {{ color: #777; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n{body}</body>\n</html>\n"
+    )
+}
+
+/// A unified diff with added, removed and hunk header lines coloured.
+fn diff_html(diff: &str) -> String {
+    let mut out = String::from("<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            None
+        } else if line.starts_with('+') {
+            Some("add")
+        } else if line.starts_with('-') {
+            Some("del")
+        } else if line.starts_with("@@") {
+            Some("hunk")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => {
+                let _ = writeln!(out, "<span class=\"{class}\">{}</span>", escape_html(line));
+            }
+            None => {
+                let _ = writeln!(out, "{}", escape_html(line));
+            }
+        }
+    }
+    out.push_str("</pre>");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex2::ExecOutputStream;
+
+    fn entry(entry: SessionEntry) -> RecordedEntry {
+        RecordedEntry { ts_ms: 0, entry }
+    }
+
+    fn event(event: Event) -> RecordedEntry {
+        entry(SessionEntry::Event(event))
+    }
+
+    fn session() -> Vec<RecordedEntry> {
+        vec![
+            entry(SessionEntry::User {
+                text: "list the decks".to_string(),
+            }),
+            event(Event::TaskStarted),
+            event(Event::AgentMessageDelta {
+                delta: "Checking ".to_string(),
+            }),
+            event(Event::AgentMessageDelta {
+                delta: "<slides>".to_string(),
+            }),
+            event(Event::ExecCommandBegin {
+                call_id: "1".to_string(),
+                command: vec!["ls".to_string(), "slides".to_string()],
+                cwd: PathBuf::from("/work"),
+            }),
+            event(Event::ExecCommandOutputDelta {
+                call_id: "1".to_string(),
+                stream: ExecOutputStream::Stdout,
+                chunk: "talk.md\n".to_string(),
+            }),
+            event(Event::ExecCommandEnd {
+                call_id: "1".to_string(),
+                exit_code: 0,
+            }),
+            event(Event::TaskComplete),
+        ]
+    }
+
+    #[test]
+    fn sessions_round_trip_through_the_log() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-events-{}", std::process::id()));
+        let log = EventLog::new(dir.clone());
+        for recorded in session() {
+            log.record("1-1", &recorded.entry)?;
+        }
+        assert_eq!(log.latest_session().as_deref(), Some("1-1"));
+
+        let loaded = load(&log.session_path("1-1"))?;
+        assert_eq!(
+            to_markdown("Session", &loaded),
+            to_markdown("Session", &session())
+        );
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn sessions_render_as_markdown_and_html() {
+        let markdown = to_markdown("Session 1-1", &session());
+        assert_eq!(
+            markdown,
+            "# Session 1-1\n\n## You\n\nlist the decks\n\n## Agent\n\nChecking <slides>\n\n\
+             ```console\n$ ls slides\ntalk.md\n```\n"
+        );
+
+        let html = to_html("Session 1-1", &session());
+        assert!(html.contains("<pre>Checking &lt;slides&gt;</pre>"));
+        assert!(html.contains("<pre><b>$ ls slides</b>\ntalk.md\n</pre>"));
+    }
+
+    #[test]
+    fn exports_show_approvals_with_their_answer_and_diffs() {
+        let deck = PathBuf::from("slides/talk.md");
+        let entries = vec![
+            event(Event::ExecApprovalRequest {
+                id: "a".to_string(),
+                command: vec!["rm".to_string(), "old.md".to_string()],
+                cwd: PathBuf::from("/work"),
+                reason: None,
+                parsed_cmd: Vec::new(),
+                sandbox: None,
+                escalated: false,
+                risk: None,
+                prefix: None,
+                timeout_secs: None,
+            }),
+            entry(SessionEntry::Approval {
+                id: "a".to_string(),
+                decision: ReviewDecision::Denied,
+            }),
+            event(Event::ApplyPatchApprovalRequest {
+                id: "p".to_string(),
+                changes: [(
+                    deck.clone(),
+                    ApplyPatchFileChange::Update {
+                        unified_diff: "@@ -1 +1 @@\n-# Old\n+# New\n".to_string(),
+                        move_path: None,
+                        new_content: "# New\n".to_string(),
+                    },
+                )]
+                .into(),
+                reason: None,
+                timeout_secs: None,
+                dirty_files: Vec::new(),
+            }),
+            entry(SessionEntry::Approval {
+                id: "p".to_string(),
+                decision: ReviewDecision::ApprovedForSession,
+            }),
+            event(Event::PatchApplyEnd {
+                success: true,
+                files: vec![FileDiffStat {
+                    path: deck,
+                    stat: DiffStat {
+                        added: 1,
+                        removed: 1,
+                    },
+                }],
+                total: DiffStat {
+                    added: 1,
+                    removed: 1,
+                },
+            }),
+        ];
+
+        let markdown = ExportFormat::Markdown.render("Session", &entries);
+        assert_eq!(
+            markdown,
+            "# Session\n\n> Command `rm old.md`: denied\n\n\
+             **Proposed changes** (approved for the session)\n\n`slides/talk.md`\n\n\
+             ```diff\n@@ -1 +1 @@\n-# Old\n+# New\n```\n\n\
+             **Applied** 1 file(s), +1 -1\n\n- `slides/talk.md` +1 -1\n"
+        );
+
+        let html = ExportFormat::Html.render("Session", &entries);
+        assert!(html.contains("<span class=\"del\">-# Old</span>"));
+        assert!(html.contains("<span class=\"add\">+# New</span>"));
+        assert!(html.contains("<p class=\"notice\">denied</p>"));
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out/session.HTML")),
+            Some(ExportFormat::Html)
+        );
+    }
+}
@@ -16,7 +16,7 @@ use tokio::process::{Child, Command};
 
 use crate::approval_manager::{ApprovalManager, ApprovalRequest, ApprovalResponse, AskForApproval};
 use crate::is_safe_command::{explain_safety_concern, is_known_safe_command};
-use crate::seatbelt::SandboxPolicy;
+use protocol::config_types::SandboxPolicy;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 
@@ -155,7 +155,7 @@ pub async fn process_exec_tool_call(
     params: ExecParams,
     sandbox_type: SandboxType,
     sandbox_policy: &SandboxPolicy,
-    _codex_linux_sandbox_exe: &Option<PathBuf>,
+    slide_linux_sandbox_exe: &Option<PathBuf>,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
@@ -187,6 +187,7 @@ pub async fn process_exec_tool_call(
                 #[cfg(target_os = "linux")]
                 {
                     spawn_command_under_linux_sandbox(
+                        slide_linux_sandbox_exe.as_deref(),
                         params.command.clone(),
                         sandbox_policy,
                         params.cwd.clone(),
@@ -553,22 +554,15 @@ pub async fn spawn_command_under_seatbelt(
     timeout: Duration,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
-    use crate::seatbelt::build_seatbelt_policy;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
+    use crate::seatbelt::{build_seatbelt_policy, SeatbeltOptions};
 
     let start = Instant::now();
 
-    // Create seatbelt policy file
-    let policy_content = build_seatbelt_policy(sandbox_policy.clone(), Some(&cwd));
-    let mut policy_file = NamedTempFile::new()?;
-    policy_file.write_all(policy_content.as_bytes())?;
-    policy_file.flush()?;
+    let policy = build_seatbelt_policy(sandbox_policy, &SeatbeltOptions::new(&cwd));
 
     // Build sandboxed command
     let mut cmd = Command::new("sandbox-exec");
-    cmd.arg("-f");
-    cmd.arg(policy_file.path());
+    cmd.args(policy.sandbox_exec_args());
     cmd.arg(&command[0]);
     if command.len() > 1 {
         cmd.args(&command[1..]);
@@ -617,9 +611,12 @@ pub async fn spawn_command_under_seatbelt(
     }
 }
 
-/// Linux Landlock/Seccomp sandbox implementation
+/// Linux Landlock/Seccomp sandbox implementation: re-runs `slide` as the
+/// `slide-linux-sandbox` helper when its executable is known, else falls back
+/// to bubblewrap.
 #[cfg(target_os = "linux")]
 pub async fn spawn_command_under_linux_sandbox(
+    slide_linux_sandbox_exe: Option<&std::path::Path>,
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: PathBuf,
@@ -627,45 +624,22 @@ pub async fn spawn_command_under_linux_sandbox(
     timeout: Duration,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
-    // For now, implement basic bwrap sandbox
-    // In production, this would use proper Landlock/Seccomp
-    use std::path::Path;
-
     let start = Instant::now();
 
-    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(&cwd);
-    let allows_network = sandbox_policy.allows_network();
-
-    // Build bwrap command for basic sandboxing
-    let mut cmd = Command::new("bwrap");
-
-    // Basic sandbox setup
-    cmd.arg("--unshare-all");
-    cmd.arg("--ro-bind").arg("/").arg("/");
-
-    // Add writable mounts
-    for root in &writable_roots {
-        if root.exists() {
-            cmd.arg("--bind").arg(root).arg(root);
+    let mut cmd = match slide_linux_sandbox_exe {
+        Some(exe) => {
+            let mut cmd = Command::new(exe);
+            cmd.arg0(slide_linux_sandbox::ARG0);
+            cmd.args(slide_linux_sandbox::create_linux_sandbox_command_args(
+                &command,
+                sandbox_policy,
+                &cwd,
+            )?);
+            cmd.current_dir(&cwd);
+            cmd
         }
-    }
-
-    // Network policy
-    if allows_network {
-        cmd.arg("--share-net");
-    } else {
-        cmd.arg("--unshare-net");
-    }
-
-    // Working directory
-    cmd.arg("--chdir").arg(&cwd);
-
-    // Add the actual command
-    cmd.arg("--");
-    cmd.arg(&command[0]);
-    if command.len() > 1 {
-        cmd.args(&command[1..]);
-    }
+        None => bwrap_command(&command, sandbox_policy, &cwd),
+    };
 
     cmd.envs(&env)
         .stdout(std::process::Stdio::piped())
@@ -708,3 +682,39 @@ pub async fn spawn_command_under_linux_sandbox(
         }
     }
 }
+
+/// `bwrap` invocation confining `command` to `sandbox_policy`.
+#[cfg(target_os = "linux")]
+fn bwrap_command(
+    command: &[String],
+    sandbox_policy: &SandboxPolicy,
+    cwd: &std::path::Path,
+) -> Command {
+    let mut cmd = Command::new("bwrap");
+
+    // Basic sandbox setup
+    cmd.arg("--unshare-all");
+    cmd.arg("--ro-bind").arg("/").arg("/");
+
+    // Add writable mounts
+    for root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+        if root.root.exists() {
+            cmd.arg("--bind").arg(&root.root).arg(&root.root);
+        }
+    }
+
+    // Network policy
+    if sandbox_policy.has_full_network_access() {
+        cmd.arg("--share-net");
+    } else {
+        cmd.arg("--unshare-net");
+    }
+
+    // Working directory
+    cmd.arg("--chdir").arg(cwd);
+
+    // Add the actual command
+    cmd.arg("--");
+    cmd.args(command);
+    cmd
+}
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 
 use crate::is_safe_command::{explain_safety_concern, is_known_safe_command};
-use crate::seatbelt::SandboxPolicy;
+use protocol::config_types::SandboxPolicy;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 const TIMEOUT_CODE: i32 = 64;
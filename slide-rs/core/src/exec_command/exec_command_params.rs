@@ -1,6 +1,33 @@
-#[derive(Debug, Clone)]
+use super::SessionId;
+use std::path::PathBuf;
+
+/// Arguments of `exec_session_open`: the long-running command, e.g. `bash` or
+/// `python3 -i`.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExecCommandParams {
-    pub cmd: String,
-    pub yield_time_ms: u64,
+    pub command: Vec<String>,
+    pub workdir: Option<PathBuf>,
+    /// How long to wait for the first output; clamped to [`MAX_YIELD_TIME_MS`]
+    ///
+    /// [`MAX_YIELD_TIME_MS`]: super::MAX_YIELD_TIME_MS
+    pub yield_time_ms: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Arguments of `exec_session_write`: `chars` go to the session's stdin as is,
+/// so a command needs its trailing newline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteStdinParams {
+    pub session_id: SessionId,
+    pub chars: String,
+    pub yield_time_ms: Option<u64>,
+    pub max_output_bytes: Option<usize>,
 }
 
+/// Arguments of `exec_session_read`: output produced since the last call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadOutputParams {
+    pub session_id: SessionId,
+    pub yield_time_ms: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+}
@@ -1,3 +1,145 @@
-#[derive(Debug, Clone)]
-pub struct ExecCommandSession { pub id: String }
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
 
+use anyhow::{Context, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+
+/// Chunks buffered per session before the process blocks on its pipes.
+const OUTPUT_CHANNEL_CAPACITY: usize = 256;
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// A process the agent keeps talking to across tool calls. It runs on pipes,
+/// not a terminal, so REPLs need their interactive flag (`python3 -i`).
+pub struct ExecCommandSession {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    /// stdout and stderr interleaved as they arrive; closed once both end
+    output_rx: mpsc::Receiver<Vec<u8>>,
+    /// Read from the pipes but not yet returned, because of the byte cap
+    pending: Vec<u8>,
+    pipes_closed: bool,
+}
+
+impl ExecCommandSession {
+    pub fn spawn(command: &[String], cwd: &Path, env: HashMap<String, String>) -> Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .context("exec session needs a command")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(cwd)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to start {}", command.join(" ")))?;
+
+        let (tx, output_rx) = mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+        if let Some(stdout) = child.stdout.take() {
+            forward(stdout, tx.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward(stderr, tx);
+        }
+        Ok(Self {
+            stdin: child.stdin.take(),
+            child,
+            output_rx,
+            pending: Vec::new(),
+            pipes_closed: false,
+        })
+    }
+
+    pub async fn write(&mut self, chars: &str) -> Result<()> {
+        let stdin = self.stdin.as_mut().context("session stdin is closed")?;
+        stdin.write_all(chars.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Output produced until `yield_time` passes or the process closes its
+    /// pipes, at most `max_bytes` of it; the rest waits for the next call.
+    /// Returns the text and whether output is still pending.
+    pub async fn collect_output(
+        &mut self,
+        yield_time: Duration,
+        max_bytes: usize,
+    ) -> (String, bool) {
+        let deadline = Instant::now() + yield_time;
+        while self.pending.len() < max_bytes && !self.pipes_closed {
+            tokio::select! {
+                biased;
+                chunk = self.output_rx.recv() => match chunk {
+                    Some(chunk) => self.pending.extend_from_slice(&chunk),
+                    None => self.pipes_closed = true,
+                },
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+        let take = char_boundary(&self.pending, max_bytes);
+        let rest = self.pending.split_off(take);
+        let output =
+            String::from_utf8_lossy(&std::mem::replace(&mut self.pending, rest)).into_owned();
+        (output, !self.pending.is_empty())
+    }
+
+    /// Exit code once the process has exited and all its output was read.
+    pub async fn exit_code(&mut self) -> Option<i32> {
+        if !self.pipes_closed || !self.pending.is_empty() {
+            return None;
+        }
+        // The pipes close as the process exits; give it a moment to be reaped
+        let status = tokio::time::timeout(Duration::from_millis(500), self.child.wait()).await;
+        match status {
+            Ok(Ok(status)) => Some(status.code().unwrap_or(-1)),
+            _ => None,
+        }
+    }
+
+    pub async fn kill(mut self) {
+        let _ = self.child.kill().await;
+    }
+}
+
+fn forward<R>(mut reader: R, tx: mpsc::Sender<Vec<u8>>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut buf = vec![0; READ_CHUNK_SIZE];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tx.send(buf[..n].to_vec()).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Largest length `<= max` that does not split a UTF-8 sequence of `bytes`.
+fn char_boundary(bytes: &[u8], max: usize) -> usize {
+    if bytes.len() <= max {
+        return bytes.len();
+    }
+    let mut end = max;
+    while end > 0 && (bytes[end] & 0xC0) == 0x80 {
+        end -= 1;
+    }
+    // Not UTF-8 to begin with; cut anywhere rather than return nothing
+    if end == 0 {
+        max
+    } else {
+        end
+    }
+}
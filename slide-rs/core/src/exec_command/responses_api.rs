@@ -1,3 +1,31 @@
-#[derive(Debug, Clone)]
-pub struct ExecCommandOutput { pub output: String, pub exited: bool }
+use super::SessionId;
 
+/// What one session call returns to the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecCommandOutput {
+    pub session_id: SessionId,
+    pub output: String,
+    /// Set once the process has exited; the session is gone by then
+    pub exit_code: Option<i32>,
+    /// Output left unread because of the byte cap
+    pub pending: bool,
+}
+
+impl ExecCommandOutput {
+    pub fn message(&self) -> String {
+        let mut message = match self.exit_code {
+            Some(code) => format!("Session {} exited with code {code}", self.session_id),
+            None => format!("Session {} is running", self.session_id),
+        };
+        if self.output.is_empty() {
+            message.push_str("\n\n(no new output)");
+        } else {
+            message.push_str("\n\nOUTPUT:\n");
+            message.push_str(self.output.trim_end());
+        }
+        if self.pending {
+            message.push_str("\n\n(more output pending; call exec_session_read)");
+        }
+        message
+    }
+}
@@ -1,3 +1,11 @@
-#[derive(Debug, Clone)]
-pub struct SessionId(pub String);
+use std::fmt;
 
+/// Number the model uses to address an open exec session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionId(pub u32);
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -1,15 +1,218 @@
 use std::collections::HashMap;
-use super::{ExecCommandSession, SessionId};
+use std::path::Path;
 
+use anyhow::{bail, Context, Result};
+use tokio::time::Duration;
+
+use super::{
+    ExecCommandOutput, ExecCommandParams, ExecCommandSession, ReadOutputParams, SessionId,
+    WriteStdinParams,
+};
+
+/// Sessions one agent may keep open at a time.
+pub const MAX_SESSIONS: usize = 4;
+pub const DEFAULT_YIELD_TIME_MS: u64 = 1_000;
+/// Longest a single call waits for output.
+pub const MAX_YIELD_TIME_MS: u64 = 30_000;
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 16 * 1024;
+/// Largest `max_output_bytes` a call may ask for.
+pub const MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// The exec sessions of one agent. A session ends when its process exits,
+/// on `close`, or when the manager is dropped.
 #[derive(Default)]
-pub struct SessionManager { sessions: HashMap<String, ExecCommandSession> }
+pub struct SessionManager {
+    next_id: u32,
+    sessions: HashMap<SessionId, ExecCommandSession>,
+}
 
 impl SessionManager {
-    pub fn new() -> Self { Self::default() }
-    pub fn create(&mut self, id: String) -> SessionId {
-        let s = ExecCommandSession { id: id.clone() };
-        self.sessions.insert(id.clone(), s);
-        SessionId(id)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Start `params.command` in `cwd` and return its first output.
+    pub async fn open(
+        &mut self,
+        params: &ExecCommandParams,
+        cwd: &Path,
+        env: HashMap<String, String>,
+    ) -> Result<ExecCommandOutput> {
+        if self.sessions.len() >= MAX_SESSIONS {
+            bail!("{MAX_SESSIONS} exec sessions are already open; close one first");
+        }
+        let session = ExecCommandSession::spawn(&params.command, cwd, env)?;
+        self.next_id += 1;
+        let id = SessionId(self.next_id);
+        self.sessions.insert(id, session);
+        self.collect(id, params.yield_time_ms, params.max_output_bytes)
+            .await
+    }
+
+    /// Send `params.chars` to the session's stdin and return what it printed.
+    pub async fn write(&mut self, params: &WriteStdinParams) -> Result<ExecCommandOutput> {
+        self.session(params.session_id)?
+            .write(&params.chars)
+            .await
+            .with_context(|| format!("failed to write to session {}", params.session_id))?;
+        self.collect(
+            params.session_id,
+            params.yield_time_ms,
+            params.max_output_bytes,
+        )
+        .await
+    }
+
+    pub async fn read(&mut self, params: &ReadOutputParams) -> Result<ExecCommandOutput> {
+        self.session(params.session_id)?;
+        self.collect(
+            params.session_id,
+            params.yield_time_ms,
+            params.max_output_bytes,
+        )
+        .await
+    }
+
+    /// Kill the session's process; output it had not returned is dropped.
+    pub async fn close(&mut self, id: SessionId) -> Result<()> {
+        let session = self
+            .sessions
+            .remove(&id)
+            .with_context(|| format!("no exec session {id}"))?;
+        session.kill().await;
+        Ok(())
+    }
+
+    fn session(&mut self, id: SessionId) -> Result<&mut ExecCommandSession> {
+        self.sessions
+            .get_mut(&id)
+            .with_context(|| format!("no exec session {id}; it may have exited"))
+    }
+
+    async fn collect(
+        &mut self,
+        id: SessionId,
+        yield_time_ms: Option<u64>,
+        max_output_bytes: Option<usize>,
+    ) -> Result<ExecCommandOutput> {
+        let yield_time = yield_time_ms
+            .unwrap_or(DEFAULT_YIELD_TIME_MS)
+            .min(MAX_YIELD_TIME_MS);
+        let max_bytes = max_output_bytes
+            .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES)
+            .clamp(1, MAX_OUTPUT_BYTES);
+        let session = self.session(id)?;
+        let (output, pending) = session
+            .collect_output(Duration::from_millis(yield_time), max_bytes)
+            .await;
+        let exit_code = session.exit_code().await;
+        if exit_code.is_some() {
+            self.sessions.remove(&id);
+        }
+        Ok(ExecCommandOutput {
+            session_id: id,
+            output,
+            exit_code,
+            pending,
+        })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_params(command: &[&str]) -> ExecCommandParams {
+        ExecCommandParams {
+            command: command.iter().map(|s| s.to_string()).collect(),
+            workdir: None,
+            yield_time_ms: Some(100),
+            max_output_bytes: None,
+        }
+    }
+
+    fn write_params(id: SessionId, chars: &str) -> WriteStdinParams {
+        WriteStdinParams {
+            session_id: id,
+            chars: chars.to_string(),
+            yield_time_ms: Some(500),
+            max_output_bytes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_shell_session_keeps_its_state_until_it_exits() -> Result<()> {
+        let mut manager = SessionManager::new();
+        let cwd = std::env::temp_dir();
+        let opened = manager
+            .open(&open_params(&["sh"]), &cwd, HashMap::new())
+            .await?;
+        assert_eq!(opened.exit_code, None);
+        let id = opened.session_id;
+
+        manager.write(&write_params(id, "GREETING=hello\n")).await?;
+        let echoed = manager.write(&write_params(id, "echo $GREETING\n")).await?;
+        assert_eq!(echoed.output, "hello\n");
+        let errors = manager.write(&write_params(id, "echo oops >&2\n")).await?;
+        assert_eq!(errors.output, "oops\n");
+
+        let exited = manager.write(&write_params(id, "exit 3\n")).await?;
+        assert_eq!(exited.exit_code, Some(3));
+        assert!(manager.is_empty());
+        assert!(manager
+            .read(&ReadOutputParams {
+                session_id: id,
+                yield_time_ms: None,
+                max_output_bytes: None,
+            })
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn output_over_the_cap_waits_for_the_next_read() -> Result<()> {
+        let mut manager = SessionManager::new();
+        let cwd = std::env::temp_dir();
+        let id = manager
+            .open(&open_params(&["sh"]), &cwd, HashMap::new())
+            .await?
+            .session_id;
+        let mut params = write_params(id, "echo 0123456789\n");
+        params.max_output_bytes = Some(4);
+        let first = manager.write(&params).await?;
+        assert_eq!((first.output.as_str(), first.pending), ("0123", true));
+
+        let rest = manager
+            .read(&ReadOutputParams {
+                session_id: id,
+                yield_time_ms: Some(0),
+                max_output_bytes: None,
+            })
+            .await?;
+        assert_eq!((rest.output.as_str(), rest.pending), ("456789\n", false));
+        manager.close(id).await?;
+        assert!(manager.close(id).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sessions_are_capped() -> Result<()> {
+        let mut manager = SessionManager::new();
+        let cwd = std::env::temp_dir();
+        for _ in 0..MAX_SESSIONS {
+            manager
+                .open(&open_params(&["sh"]), &cwd, HashMap::new())
+                .await?;
+        }
+        assert!(manager
+            .open(&open_params(&["sh"]), &cwd, HashMap::new())
+            .await
+            .is_err());
+        Ok(())
+    }
+}
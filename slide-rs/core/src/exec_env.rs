@@ -19,18 +19,22 @@ fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String,
 where
     I: IntoIterator<Item = (String, String)>,
 {
+    let parent: HashMap<String, String> = vars.into_iter().collect();
+
     // Step 1 – determine the starting set of variables based on the
     // `inherit` strategy.
     let mut env_map: HashMap<String, String> = match policy.inherit {
-        ShellEnvironmentPolicyInherit::All => vars.into_iter().collect(),
+        ShellEnvironmentPolicyInherit::All => parent.clone(),
         ShellEnvironmentPolicyInherit::None => HashMap::new(),
         ShellEnvironmentPolicyInherit::Core => {
             const CORE_VARS: &[&str] = &[
                 "HOME", "LOGNAME", "PATH", "SHELL", "USER", "USERNAME", "TMPDIR", "TEMP", "TMP",
             ];
             let allow: HashSet<&str> = CORE_VARS.iter().copied().collect();
-            vars.into_iter()
+            parent
+                .iter()
                 .filter(|(k, _)| allow.contains(k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
                 .collect()
         }
     };
@@ -55,25 +59,45 @@ where
         env_map.retain(|k, _| !matches_any(k, &policy.exclude));
     }
 
-    // Step 4 – Apply user-provided overrides.
-    for (key, val) in &policy.set {
-        env_map.insert(key.clone(), val.clone());
+    // Step 4 – Apply custom includes (these override excludes).
+    for (key, val) in &parent {
+        if matches_any(key, &policy.include) {
+            env_map.insert(key.clone(), val.clone());
+        }
     }
 
-    // Step 5 – Apply custom includes (these override excludes).
-    if !policy.include.is_empty() {
-        // First collect variables that match include patterns
-        let included_vars: HashMap<String, String> = std::env::vars()
-            .filter(|(k, _)| matches_any(k, &policy.include))
-            .collect();
+    // Step 5 – Apply user-provided overrides, expanding `${VAR}` from the
+    // parent environment.
+    for (key, val) in &policy.set {
+        env_map.insert(key.clone(), expand_template(val, &parent));
+    }
 
-        // Add included variables back
-        env_map.extend(included_vars);
+    // Step 6 – Remove explicitly unset variables.
+    for key in &policy.unset {
+        env_map.remove(key);
     }
 
     env_map
 }
 
+/// Replace each `${VAR}` in `value` with `VAR` from `vars`, or with nothing
+/// when it is not set. An unterminated `${` is kept as written.
+fn expand_template(value: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        out.push_str(vars.get(name).map(String::as_str).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,4 +220,103 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn later_steps_take_precedence() {
+        let vars = make_vars(&[
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/user"),
+            ("GITHUB_TOKEN", "gh"),
+            ("DEBUG", "1"),
+            ("LANG", "C"),
+        ]);
+
+        let policy = ShellEnvironmentPolicy {
+            // core-only drops DEBUG and LANG, the default excludes GITHUB_TOKEN
+            inherit: ShellEnvironmentPolicyInherit::Core,
+            exclude: vec![EnvironmentVariablePattern::new("HOME")],
+            // includes bring back what inherit and the excludes removed
+            include: vec![
+                EnvironmentVariablePattern::new_case_insensitive("lang"),
+                EnvironmentVariablePattern::new("GITHUB_*"),
+            ],
+            // set wins over include, and unset over everything
+            set: hashmap! {
+                "GITHUB_TOKEN".to_string() => "redacted".to_string(),
+                "PATH".to_string() => "/opt/slide/bin:${PATH}".to_string(),
+                "DEBUG".to_string() => "${DEBUG}${MISSING}-on".to_string(),
+            },
+            unset: vec!["LANG".to_string()],
+            ..Default::default()
+        };
+
+        let result = populate_env(vars, &policy);
+        let expected: HashMap<String, String> = hashmap! {
+            "PATH".to_string() => "/opt/slide/bin:/usr/bin".to_string(),
+            "GITHUB_TOKEN".to_string() => "redacted".to_string(),
+            "DEBUG".to_string() => "1-on".to_string(),
+        };
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn templates_expand_only_complete_references() {
+        let vars = hashmap! { "A".to_string() => "x".to_string() };
+        assert_eq!(expand_template("${A}/${B}/$A/${A", &vars), "x//$A/${A");
+    }
+
+    #[test]
+    fn glob_patterns_match_anywhere_in_the_name() {
+        assert!(EnvironmentVariablePattern::new("AWS_*_ID").matches("AWS_ACCESS_KEY_ID"));
+        assert!(EnvironmentVariablePattern::new("LC_?").matches("LC_X"));
+        assert!(!EnvironmentVariablePattern::new("LC_?").matches("LC_ALL"));
+        assert!(EnvironmentVariablePattern::new_case_insensitive("*path").matches("MANPATH"));
+        assert!(!EnvironmentVariablePattern::new("*path").matches("MANPATH"));
+    }
+
+    #[test]
+    fn project_overrides_extend_the_policy() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-env-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("slide.toml"),
+            r#"
+[project]
+name = "talk"
+
+[shell_environment]
+inherit = "all"
+include = ["npm_config_*"]
+unset = ["DEBUG"]
+
+[shell_environment.set]
+NODE_ENV = "production"
+"#,
+        )?;
+
+        let base = ShellEnvironmentPolicy {
+            unset: vec!["NODE_ENV".to_string()],
+            ..Default::default()
+        };
+        let policy = base.with_project_overrides(&dir)?;
+        assert_eq!(policy.inherit, ShellEnvironmentPolicyInherit::All);
+        assert_eq!(
+            policy.include,
+            [EnvironmentVariablePattern::new_case_insensitive(
+                "npm_config_*"
+            )]
+        );
+        assert_eq!(
+            policy.set.get("NODE_ENV").map(String::as_str),
+            Some("production")
+        );
+        assert_eq!(policy.unset, ["DEBUG"]);
+
+        let missing =
+            ShellEnvironmentPolicy::default().with_project_overrides(&dir.join("none"))?;
+        assert_eq!(missing, ShellEnvironmentPolicy::default());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
 }
@@ -1,8 +1,11 @@
 use crate::approval_manager::{ApprovalRequest, ApprovalResponse, AskForApproval};
+use crate::command_translation::{translate_for_platform, Translation};
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::exec_env::create_env;
 use crate::safety::{assess_command_safety_v2, SafetyCheck};
-use crate::seatbelt::{build_seatbelt_policy, SandboxPolicy};
+#[cfg(target_os = "macos")]
+use crate::seatbelt::{build_seatbelt_policy, SeatbeltOptions};
+use protocol::config_types::SandboxPolicy;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -42,12 +45,21 @@ pub struct ExecResult {
     pub exit_code: i32,
     pub duration_ms: u64,
     pub used_escalated_permissions: bool,
+    /// What was changed about the command to run it on this platform, or
+    /// advice for the model when it could not be
+    pub translation: Option<String>,
 }
 
 /// Platform-specific sandbox execution engine
 pub struct SandboxedExecutor {
     approval_manager: crate::approval_manager::ApprovalManager,
     sandbox_policy: SandboxPolicy,
+    /// Directory for commands whose `working_dir` is unset
+    cwd: PathBuf,
+    /// Writable under `WorkspaceWrite` besides the policy's own roots
+    extra_writable_roots: Vec<PathBuf>,
+    /// `slide` executable re-run as the Linux sandbox helper
+    linux_sandbox_exe: Option<PathBuf>,
 }
 
 impl SandboxedExecutor {
@@ -55,9 +67,33 @@ impl SandboxedExecutor {
         Self {
             approval_manager: crate::approval_manager::ApprovalManager::new(approval_policy),
             sandbox_policy,
+            cwd: PathBuf::from("."),
+            extra_writable_roots: Vec::new(),
+            linux_sandbox_exe: None,
         }
     }
 
+    /// Let sandboxed commands also write to `roots`, e.g. `sandbox.writable_roots`
+    /// from the config.
+    pub fn with_writable_roots(mut self, roots: &[PathBuf]) -> Self {
+        self.extra_writable_roots = roots.to_vec();
+        self
+    }
+
+    /// Sandbox commands on Linux by re-running `exe` (the `slide` binary, as
+    /// handed out by `slide_arg0`) as `slide-linux-sandbox`.
+    pub fn with_linux_sandbox_exe(mut self, exe: Option<PathBuf>) -> Self {
+        self.linux_sandbox_exe = exe;
+        self
+    }
+
+    /// Run commands without a `working_dir` in `cwd`, e.g. the session's
+    /// current directory.
+    pub fn with_cwd(mut self, cwd: PathBuf) -> Self {
+        self.cwd = cwd;
+        self
+    }
+
     /// Execute a command with sandbox and approval controls
     pub async fn execute(&mut self, params: ExecParams) -> Result<ExecResult, ExecError> {
         let start_time = std::time::Instant::now();
@@ -86,6 +122,9 @@ impl SandboxedExecutor {
                         self.approval_manager
                             .approve_command(params.command.clone());
                     }
+                    ApprovalResponse::ApprovedPrefix(prefix) => {
+                        self.approval_manager.allow_prefix(prefix);
+                    }
                     ApprovalResponse::Denied => {
                         return Err(ExecError::ApprovalDenied);
                     }
@@ -101,7 +140,14 @@ impl SandboxedExecutor {
             }
         }
 
-        // 3. Execute command with appropriate sandbox
+        // 3. Translate unix commands for this platform
+        let translation = translate_for_platform(&params.command);
+        let mut params = params;
+        if let Some(Translation::Rewritten { command, .. }) = &translation {
+            params.command = command.clone();
+        }
+
+        // 4. Execute command with appropriate sandbox
         let with_escalated = params.with_escalated_permissions;
         let result = if with_escalated {
             self.execute_with_escalated_permissions(&params).await?
@@ -116,6 +162,7 @@ impl SandboxedExecutor {
             exit_code: result.exit_code,
             duration_ms,
             used_escalated_permissions: with_escalated,
+            translation: translation.map(|t| t.note().to_string()),
         })
     }
 
@@ -123,7 +170,7 @@ impl SandboxedExecutor {
     async fn request_approval(&self, params: &ExecParams) -> Result<ApprovalResponse, ExecError> {
         let request = ApprovalRequest::new(
             params.command.clone(),
-            params.working_dir.as_deref(),
+            Some(params.working_dir.as_deref().unwrap_or(&self.cwd)),
             params.justification.clone(),
             params.with_escalated_permissions,
             format!("{:?}", self.sandbox_policy),
@@ -149,10 +196,7 @@ impl SandboxedExecutor {
 
     /// Execute command within sandbox constraints
     async fn execute_sandboxed(&self, params: &ExecParams) -> Result<BasicExecResult, ExecError> {
-        let working_dir = params
-            .working_dir
-            .as_deref()
-            .unwrap_or_else(|| Path::new("."));
+        let working_dir = params.working_dir.as_deref().unwrap_or(&self.cwd);
 
         // Prepare environment variables
         let env_vars = create_env(&params.environment_policy);
@@ -193,10 +237,7 @@ impl SandboxedExecutor {
         &self,
         params: &ExecParams,
     ) -> Result<BasicExecResult, ExecError> {
-        let working_dir = params
-            .working_dir
-            .as_deref()
-            .unwrap_or_else(|| Path::new("."));
+        let working_dir = params.working_dir.as_deref().unwrap_or(&self.cwd);
 
         let env_vars = create_env(&params.environment_policy);
 
@@ -366,19 +407,13 @@ impl SandboxedExecutor {
         timeout_ms: Option<u64>,
         sandbox_policy: &SandboxPolicy,
     ) -> Result<BasicExecResult, ExecError> {
-        let policy = build_seatbelt_policy(sandbox_policy.clone(), Some(working_dir));
-
-        // Create a temporary policy file
-        let policy_file =
-            std::env::temp_dir().join(format!("slide_policy_{}.sbpl", std::process::id()));
-        std::fs::write(&policy_file, policy).map_err(|e| ExecError::SandboxError {
-            message: e.to_string(),
-        })?;
+        let options =
+            SeatbeltOptions::new(working_dir).with_writable_roots(&self.extra_writable_roots);
+        let policy = build_seatbelt_policy(sandbox_policy, &options);
 
         let mut sandbox_cmd = Command::new("sandbox-exec");
         sandbox_cmd
-            .arg("-f")
-            .arg(&policy_file)
+            .args(policy.sandbox_exec_args())
             .args(&command)
             .current_dir(working_dir)
             .env_clear()
@@ -393,8 +428,6 @@ impl SandboxedExecutor {
         let result = tokio::time::timeout(timeout, async {
             tokio::task::spawn_blocking(move || {
                 let output = sandbox_cmd.output()?;
-                // Clean up policy file
-                let _ = std::fs::remove_file(&policy_file);
                 Ok::<_, std::io::Error>(output)
             })
             .await
@@ -413,7 +446,7 @@ impl SandboxedExecutor {
         })
     }
 
-    /// Execute command with Linux Landlock sandbox (placeholder)
+    /// Execute command with Linux Landlock sandbox
     #[cfg(target_os = "linux")]
     async fn execute_with_landlock(
         &self,
@@ -421,13 +454,60 @@ impl SandboxedExecutor {
         working_dir: &Path,
         env_vars: HashMap<String, String>,
         timeout_ms: Option<u64>,
-        _sandbox_policy: &SandboxPolicy,
+        sandbox_policy: &SandboxPolicy,
     ) -> Result<BasicExecResult, ExecError> {
-        // For now, fall back to basic execution
-        // Real implementation would use landlock/seccomp
-        tracing::warn!("Landlock sandbox not yet implemented, falling back to basic execution");
-        self.execute_basic(command, working_dir, env_vars, timeout_ms)
-            .await
+        use std::os::unix::process::CommandExt;
+
+        let Some(exe) = &self.linux_sandbox_exe else {
+            tracing::warn!("no sandbox helper executable, falling back to basic execution");
+            return self
+                .execute_basic(command, working_dir, env_vars, timeout_ms)
+                .await;
+        };
+
+        let mut sandbox_policy = sandbox_policy.clone();
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut sandbox_policy {
+            writable_roots.extend(self.extra_writable_roots.iter().cloned());
+        }
+        let args = slide_linux_sandbox::create_linux_sandbox_command_args(
+            &command,
+            &sandbox_policy,
+            working_dir,
+        )
+        .map_err(|e| ExecError::SandboxError {
+            message: e.to_string(),
+        })?;
+
+        let mut sandbox_cmd = Command::new(exe);
+        sandbox_cmd
+            .arg0(slide_linux_sandbox::ARG0)
+            .args(args)
+            .current_dir(working_dir)
+            .env_clear()
+            .envs(env_vars)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let timeout = timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_secs(30));
+
+        let output = tokio::time::timeout(timeout, async {
+            tokio::task::spawn_blocking(move || sandbox_cmd.output())
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?
+        })
+        .await
+        .map_err(|_| ExecError::Timeout {
+            timeout_ms: timeout.as_millis() as u64,
+        })?
+        .map_err(|e| ExecError::Io { source: e })?;
+
+        Ok(BasicExecResult {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            exit_code: output.status.code().unwrap_or(-1),
+        })
     }
 }
 
@@ -0,0 +1,256 @@
+//! Structural outline of a source file or deck, for `read_file` on files too
+//! large to return whole.
+//!
+//! Code is parsed with tree-sitter (Rust, Python, JavaScript and
+//! TypeScript) and lists its functions, types and impls; markdown is split
+//! into slides with the [`Deck`] model. Each entry carries its line range so
+//! the model can ask `read_file` for just that part.
+use slide_common::Deck;
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+/// A function, type or slide and the lines it spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// 1-based first and last line
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Nesting level: methods of an impl or class are at 1
+    pub depth: usize,
+    /// Signature line, or `Slide N: title`
+    pub label: String,
+}
+
+/// Characters of a signature kept in an entry
+const MAX_LABEL_CHARS: usize = 100;
+
+/// Outline of `text`, picked by the extension of `path`; `None` for other
+/// kinds of files, or when there are fewer than two entries to show.
+pub fn outline(path: &Path, text: &str) -> Option<Vec<OutlineEntry>> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let entries = match extension.as_str() {
+        "md" | "markdown" => deck_outline(text),
+        _ => code_outline(Grammar::for_extension(&extension)?, text)?,
+    };
+    (entries.len() >= 2).then_some(entries)
+}
+
+fn deck_outline(markdown: &str) -> Vec<OutlineEntry> {
+    let deck = Deck::parse(markdown);
+    let starts = Deck::slide_start_lines(markdown);
+    let total_lines = markdown.lines().count().max(1);
+    deck.slides
+        .iter()
+        .zip(&starts)
+        .enumerate()
+        .map(|(index, (slide, &start_line))| {
+            let end_line = starts
+                .get(index + 1)
+                .map_or(total_lines, |next| next.saturating_sub(1).max(start_line));
+            let title = slide.title.as_deref().unwrap_or("(untitled)");
+            OutlineEntry {
+                start_line,
+                end_line,
+                depth: 0,
+                label: format!("Slide {}: {title}", index + 1),
+            }
+        })
+        .collect()
+}
+
+/// A tree-sitter grammar and the node kinds it lists.
+struct Grammar {
+    language: Language,
+    /// Definitions listed without their contents
+    items: &'static [&'static str],
+    /// Definitions listed with the definitions inside them, one level deeper
+    containers: &'static [&'static str],
+}
+
+impl Grammar {
+    fn for_extension(extension: &str) -> Option<Self> {
+        let (language, items, containers): (Language, _, _) = match extension {
+            "rs" => (
+                tree_sitter_rust::LANGUAGE.into(),
+                RUST_ITEMS,
+                RUST_CONTAINERS,
+            ),
+            "py" | "pyi" => (
+                tree_sitter_python::LANGUAGE.into(),
+                PYTHON_ITEMS,
+                PYTHON_CONTAINERS,
+            ),
+            "js" | "jsx" | "mjs" | "cjs" => (
+                tree_sitter_javascript::LANGUAGE.into(),
+                SCRIPT_ITEMS,
+                SCRIPT_CONTAINERS,
+            ),
+            "ts" | "mts" | "cts" => (
+                tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+                SCRIPT_ITEMS,
+                SCRIPT_CONTAINERS,
+            ),
+            "tsx" => (
+                tree_sitter_typescript::LANGUAGE_TSX.into(),
+                SCRIPT_ITEMS,
+                SCRIPT_CONTAINERS,
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            language,
+            items,
+            containers,
+        })
+    }
+}
+
+const RUST_ITEMS: &[&str] = &[
+    "function_item",
+    "function_signature_item",
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "type_item",
+    "const_item",
+    "static_item",
+    "macro_definition",
+];
+const RUST_CONTAINERS: &[&str] = &["impl_item", "trait_item", "mod_item"];
+const PYTHON_ITEMS: &[&str] = &["function_definition"];
+const PYTHON_CONTAINERS: &[&str] = &["class_definition"];
+const SCRIPT_ITEMS: &[&str] = &[
+    "function_declaration",
+    "generator_function_declaration",
+    "method_definition",
+    "abstract_method_signature",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+];
+const SCRIPT_CONTAINERS: &[&str] = &[
+    "class_declaration",
+    "abstract_class_declaration",
+    "internal_module",
+];
+
+fn code_outline(grammar: Grammar, text: &str) -> Option<Vec<OutlineEntry>> {
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(text, None)?;
+    let mut entries = Vec::new();
+    collect(&grammar, tree.root_node(), text, 0, &mut entries);
+    Some(entries)
+}
+
+fn collect(grammar: &Grammar, node: Node, text: &str, depth: usize, out: &mut Vec<OutlineEntry>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let kind = child.kind();
+        if grammar.items.contains(&kind) || is_function_binding(child) {
+            out.push(entry(child, text, depth));
+        } else if grammar.containers.contains(&kind) {
+            out.push(entry(child, text, depth));
+            collect(grammar, child, text, depth + 1, out);
+        } else {
+            collect(grammar, child, text, depth, out);
+        }
+    }
+}
+
+/// `const name = (…) => …` and `const name = function …` in scripts.
+fn is_function_binding(node: Node) -> bool {
+    node.kind() == "variable_declarator"
+        && node.child_by_field_name("value").is_some_and(|value| {
+            matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function"
+            )
+        })
+}
+
+fn entry(node: Node, text: &str, depth: usize) -> OutlineEntry {
+    let signature = text
+        .get(node.start_byte()..node.end_byte())
+        .and_then(|source| source.lines().next())
+        .unwrap_or_default()
+        .trim()
+        .trim_end_matches('{')
+        .trim_end();
+    let mut label: String = signature.chars().take(MAX_LABEL_CHARS).collect();
+    if label.len() < signature.len() {
+        label.push('…');
+    }
+    OutlineEntry {
+        start_line: node.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+        depth,
+        label,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(entries: &[OutlineEntry]) -> Vec<(usize, usize, usize, &str)> {
+        entries
+            .iter()
+            .map(|e| (e.start_line, e.end_line, e.depth, e.label.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn code_outlines_list_definitions_with_their_lines() {
+        let rust = "use std::fmt;\n\npub struct Deck {\n    slides: Vec<String>,\n}\n\nimpl Deck {\n    pub fn new() -> Self {\n        Self { slides: Vec::new() }\n    }\n}\n";
+        let entries = outline(Path::new("src/deck.rs"), rust).unwrap_or_default();
+        assert_eq!(
+            labels(&entries),
+            [
+                (3, 5, 0, "pub struct Deck"),
+                (7, 11, 0, "impl Deck"),
+                (8, 10, 1, "pub fn new() -> Self"),
+            ]
+        );
+
+        let python = "import os\n\nclass Deck:\n    def render(self):\n        pass\n\ndef main():\n    Deck().render()\n";
+        let entries = outline(Path::new("deck.py"), python).unwrap_or_default();
+        assert_eq!(
+            labels(&entries),
+            [
+                (3, 5, 0, "class Deck:"),
+                (4, 5, 1, "def render(self):"),
+                (7, 8, 0, "def main():"),
+            ]
+        );
+
+        let script =
+            "export function render(deck) {\n  return deck;\n}\nconst slide = (n) => n + 1;\n";
+        let entries = outline(Path::new("deck.ts"), script).unwrap_or_default();
+        assert_eq!(
+            labels(&entries),
+            [
+                (1, 3, 0, "function render(deck)"),
+                (4, 4, 0, "slide = (n) => n + 1"),
+            ]
+        );
+
+        assert_eq!(outline(Path::new("notes.txt"), "a\nb\n"), None);
+    }
+
+    #[test]
+    fn decks_are_outlined_by_slide() {
+        let markdown = "# Launch\n\n## Why\n- speed\n\n## How\n- plan\n";
+        let entries = outline(Path::new("talk.md"), markdown).unwrap_or_default();
+        assert_eq!(
+            labels(&entries),
+            [
+                (1, 2, 0, "Slide 1: Launch"),
+                (3, 5, 0, "Slide 2: Why"),
+                (6, 7, 0, "Slide 3: How"),
+            ]
+        );
+        // A single slide is no outline
+        assert_eq!(outline(Path::new("talk.md"), "## Only\n- one\n"), None);
+    }
+}
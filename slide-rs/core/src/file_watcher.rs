@@ -0,0 +1,321 @@
+//! Debounced file system changes in the workspace, shared by everything that
+//! shows files as they change: the split deck pane and the slide preview
+//! reload their deck, the `@` file search keeps its index current.
+//!
+//! One watcher per process ([`workspace_watcher`]) watches the working
+//! directory recursively and publishes each batch of changed paths to every
+//! subscriber. Changes inside [`IGNORED_DIRS`] are dropped, so Slide's own
+//! writes to `.slide/` (snapshots, plans, state) do not wake anyone up.
+use anyhow::{Context, Result};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+use tracing::warn;
+
+/// Changes closer together than this arrive as one batch
+pub const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Batches a subscriber may fall behind before it misses some
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Directories whose changes are not published
+pub const IGNORED_DIRS: &[&str] = &[".git", ".slide", "target", "node_modules"];
+
+/// Paths that changed in the watched tree, absolute and with symlinks of
+/// the watched root resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsChange {
+    pub paths: Vec<PathBuf>,
+    /// The subscriber fell behind and missed batches; anything may have
+    /// changed
+    pub overflow: bool,
+}
+
+impl FsChange {
+    /// Whether `path` may have changed. `path` is compared in its canonical
+    /// form, so relative paths and symlinks are fine.
+    pub fn touches(&self, path: &Path) -> bool {
+        if self.overflow {
+            return true;
+        }
+        let path = canonical(path);
+        self.paths.iter().any(|changed| changed == &path)
+    }
+
+    fn merge(&mut self, other: FsChange) {
+        self.overflow |= other.overflow;
+        for path in other.paths {
+            if !self.paths.contains(&path) {
+                self.paths.push(path);
+            }
+        }
+    }
+}
+
+/// `path` made absolute with symlinks resolved. A path that does not exist
+/// (any more) keeps its file name under its canonical parent.
+pub fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = std::fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            canonical(parent).join(name)
+        }
+        _ => std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// Watches a directory tree and publishes debounced changes.
+pub struct FileWatcher {
+    root: PathBuf,
+    tx: broadcast::Sender<FsChange>,
+    /// Stops watching when dropped
+    _debouncer: Arc<Mutex<Debouncer<RecommendedWatcher>>>,
+}
+
+impl FileWatcher {
+    /// Watch `root` and every directory below it except [`IGNORED_DIRS`],
+    /// which are never registered, so a large `target/` or `node_modules/`
+    /// costs no watches. Directories created directly in `root` later are
+    /// watched from the moment they appear.
+    pub fn new(root: impl AsRef<Path>) -> Result<Self> {
+        let root = canonical(root.as_ref());
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let (new_dirs_tx, new_dirs_rx) = std::sync::mpsc::channel::<PathBuf>();
+        let publish = tx.clone();
+        let handler_root = root.clone();
+        let debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!(target: "slide.watcher", error = %e, "file watcher error");
+                    return;
+                }
+            };
+            let mut change = FsChange::default();
+            for event in events {
+                if is_ignored(&handler_root, &event.path) || change.paths.contains(&event.path) {
+                    continue;
+                }
+                if event.path.parent() == Some(handler_root.as_path()) && event.path.is_dir() {
+                    let _ = new_dirs_tx.send(event.path.clone());
+                }
+                change.paths.push(event.path);
+            }
+            if !change.paths.is_empty() {
+                // No subscribers is fine
+                let _ = publish.send(change);
+            }
+        })
+        .context("failed to start the file watcher")?;
+        let debouncer = Arc::new(Mutex::new(debouncer));
+        {
+            let mut guard = debouncer
+                .lock()
+                .map_err(|_| anyhow::anyhow!("file watcher lock poisoned"))?;
+            let watcher = guard.watcher();
+            watcher
+                .watch(&root, RecursiveMode::NonRecursive)
+                .with_context(|| format!("failed to watch {}", root.display()))?;
+            for dir in watched_dirs(&root) {
+                watch_tree(watcher, &dir);
+            }
+        }
+
+        // Watches are added from another thread: the handler runs on the
+        // debouncer's own thread
+        let weak = Arc::downgrade(&debouncer);
+        std::thread::spawn(move || {
+            for dir in new_dirs_rx {
+                let Some(debouncer) = weak.upgrade() else {
+                    break;
+                };
+                let Ok(mut guard) = debouncer.lock() else {
+                    break;
+                };
+                watch_tree(guard.watcher(), &dir);
+            }
+        });
+
+        Ok(Self {
+            root,
+            tx,
+            _debouncer: debouncer,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Receive every batch published from now on.
+    pub fn subscribe(&self) -> Subscription {
+        Subscription {
+            rx: self.tx.subscribe(),
+        }
+    }
+}
+
+/// Changes seen by one subscriber.
+pub struct Subscription {
+    rx: broadcast::Receiver<FsChange>,
+}
+
+impl Subscription {
+    /// Everything published since the last call, merged into one change;
+    /// `None` when nothing changed.
+    pub fn try_recv(&mut self) -> Option<FsChange> {
+        let mut merged: Option<FsChange> = None;
+        loop {
+            let change = match self.rx.try_recv() {
+                Ok(change) => change,
+                Err(TryRecvError::Lagged(_)) => FsChange {
+                    paths: Vec::new(),
+                    overflow: true,
+                },
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return merged,
+            };
+            match merged.as_mut() {
+                Some(merged) => merged.merge(change),
+                None => merged = Some(change),
+            }
+        }
+    }
+
+    /// Wait for the next change.
+    pub async fn recv(&mut self) -> Option<FsChange> {
+        match self.rx.recv().await {
+            Ok(change) => Some(change),
+            Err(broadcast::error::RecvError::Lagged(_)) => Some(FsChange {
+                paths: Vec::new(),
+                overflow: true,
+            }),
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+/// Directories directly in `root` that are not ignored.
+fn watched_dirs(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| !is_ignored(root, path))
+        .collect()
+}
+
+/// Best-effort: a directory that cannot be watched is logged and skipped.
+fn watch_tree(watcher: &mut dyn Watcher, dir: &Path) {
+    if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+        warn!(target: "slide.watcher", error = %e, dir = %dir.display(), "cannot watch");
+    }
+}
+
+fn is_ignored(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| matches!(c, Component::Normal(name) if IGNORED_DIRS.iter().any(|d| name == *d)))
+}
+
+static WORKSPACE: OnceLock<Option<FileWatcher>> = OnceLock::new();
+
+/// The watcher of the working directory, started on first use. `None` when
+/// the platform watcher cannot start (e.g. out of inotify watches); callers
+/// then fall back to checking modification times.
+pub fn workspace_watcher() -> Option<&'static FileWatcher> {
+    WORKSPACE
+        .get_or_init(|| {
+            let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            FileWatcher::new(&cwd)
+                .inspect_err(|e| warn!(target: "slide.watcher", error = %e, "no file watcher"))
+                .ok()
+        })
+        .as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Batches received until one touches `path`, merged.
+    async fn changes_until(sub: &mut Subscription, path: &Path) -> Result<FsChange> {
+        let wait = async {
+            let mut seen = FsChange::default();
+            while let Some(change) = sub.recv().await {
+                seen.merge(change);
+                if seen.touches(path) {
+                    return Ok(seen);
+                }
+            }
+            anyhow::bail!("watcher closed")
+        };
+        tokio::time::timeout(Duration::from_secs(5), wait).await?
+    }
+
+    #[tokio::test]
+    async fn changes_are_published_except_in_ignored_dirs() -> Result<()> {
+        let tmp = tempdir()?;
+        let watcher = FileWatcher::new(tmp.path())?;
+        let mut sub = watcher.subscribe();
+        let deck = tmp.path().join("talk.md");
+
+        std::fs::create_dir_all(tmp.path().join(".slide"))?;
+        std::fs::write(tmp.path().join(".slide").join("state.json"), "{}")?;
+        std::fs::write(&deck, "# Title")?;
+
+        let change = changes_until(&mut sub, &deck).await?;
+        assert!(!change.touches(&tmp.path().join(".slide").join("state.json")));
+        assert!(!change.paths.iter().any(|p| is_ignored(watcher.root(), p)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn directories_created_later_are_watched() -> Result<()> {
+        let tmp = tempdir()?;
+        std::fs::create_dir_all(tmp.path().join("target").join("debug"))?;
+        let watcher = FileWatcher::new(tmp.path())?;
+        let mut sub = watcher.subscribe();
+
+        let slides = tmp.path().join("slides");
+        std::fs::create_dir(&slides)?;
+        changes_until(&mut sub, &slides).await?;
+        // Let the new watch be registered
+        tokio::time::sleep(DEBOUNCE).await;
+        std::fs::write(tmp.path().join("target").join("debug").join("out"), "")?;
+        std::fs::write(slides.join("talk.md"), "# Title")?;
+        let change = changes_until(&mut sub, &slides.join("talk.md")).await?;
+        assert!(!change
+            .paths
+            .iter()
+            .any(|p| p.starts_with(watcher.root().join("target"))));
+        Ok(())
+    }
+
+    #[test]
+    fn missed_batches_touch_everything() {
+        let mut change = FsChange {
+            paths: vec![PathBuf::from("/a")],
+            overflow: false,
+        };
+        assert!(!change.touches(Path::new("/b")));
+        change.merge(FsChange {
+            paths: vec![PathBuf::from("/a")],
+            overflow: true,
+        });
+        assert_eq!(change.paths.len(), 1);
+        assert!(change.touches(Path::new("/b")));
+    }
+}
@@ -0,0 +1,218 @@
+//! Commit messages drafted by the model, for `slide commit` and `/commit`.
+//!
+//! [`CommitDiff::collect`] takes the staged changes, or the changes to
+//! tracked files when nothing is staged, and [`generate_commit_message`]
+//! asks the model for a Conventional Commits message describing them. The
+//! caller shows the draft for editing and approval before
+//! [`create_commit`] records it; when nothing was staged the commit takes
+//! every tracked change, like `git commit -a`.
+use crate::client::{ModelClient, ResponseEvent};
+use crate::slide_generate::strip_code_fence;
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Bytes of the diff sent to the model; the rest is cut and noted
+pub const MAX_DIFF_BYTES: usize = 24_000;
+
+/// Changes the commit would record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDiff {
+    /// `true` when the changes are the staged ones; otherwise every
+    /// tracked change is committed
+    pub staged: bool,
+    /// Output of `git diff --stat`
+    pub stat: String,
+    /// Unified diff, at most [`MAX_DIFF_BYTES`]
+    pub diff: String,
+    /// Whether `diff` was cut short
+    pub truncated: bool,
+}
+
+impl CommitDiff {
+    /// Changes of the repository containing `dir`; an error when there is
+    /// nothing to commit.
+    pub fn collect(dir: &Path) -> Result<Self> {
+        for staged in [true, false] {
+            let scope: &[&str] = if staged { &["--cached"] } else { &[] };
+            let diff = git(
+                dir,
+                &[&["diff", "--no-color", "--no-ext-diff"], scope].concat(),
+            )?;
+            if diff.trim().is_empty() {
+                continue;
+            }
+            let stat = git(dir, &[&["diff", "--no-color", "--stat"], scope].concat())?;
+            let (diff, truncated) = truncate(diff, MAX_DIFF_BYTES);
+            return Ok(Self {
+                staged,
+                stat,
+                diff,
+                truncated,
+            });
+        }
+        bail!("nothing to commit: no staged or modified tracked files")
+    }
+}
+
+fn truncate(mut text: String, max: usize) -> (String, bool) {
+    if text.len() <= max {
+        return (text, false);
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    (text, true)
+}
+
+pub fn build_commit_prompt(diff: &CommitDiff) -> String {
+    let mut prompt = String::from(
+        "Write a git commit message for the changes below, following the \
+         Conventional Commits format:\n\
+         - Subject line `<type>(<optional scope>): <summary>`, at most 72 characters, \
+         imperative mood, no trailing period. Types: feat, fix, docs, style, refactor, \
+         perf, test, build, ci, chore.\n\
+         - When the change needs explaining, a blank line and a body wrapped at 72 \
+         characters saying what changed and why.\n\
+         Reply with the message only, without code fences or commentary.\n\n",
+    );
+    prompt.push_str("<stat>\n");
+    prompt.push_str(diff.stat.trim_end());
+    prompt.push_str("\n</stat>\n\n<diff>\n");
+    prompt.push_str(diff.diff.trim_end());
+    if diff.truncated {
+        prompt.push_str("\n[diff truncated]");
+    }
+    prompt.push_str("\n</diff>\n");
+    prompt
+}
+
+/// Ask the model for a message describing `diff`.
+pub async fn generate_commit_message(
+    client: &(dyn ModelClient + Send + Sync),
+    diff: &CommitDiff,
+) -> Result<String> {
+    let mut rx = client.stream(build_commit_prompt(diff)).await?;
+    let mut reply = String::new();
+    while let Some(ev) = rx.recv().await {
+        match ev {
+            ResponseEvent::TextDelta(delta) => reply.push_str(&delta),
+            ResponseEvent::Completed => break,
+            ResponseEvent::Error(message) => return Err(anyhow!(message)),
+        }
+    }
+    let message = strip_code_fence(&reply);
+    if message.trim().is_empty() {
+        bail!("model returned an empty commit message");
+    }
+    Ok(message)
+}
+
+/// Commit with `message` in the repository containing `dir`, taking every
+/// tracked change unless `staged`. Returns the summary line git prints,
+/// e.g. `[main 1a2b3c4] feat: add the agenda slide`.
+pub fn create_commit(dir: &Path, message: &str, staged: bool) -> Result<String> {
+    if message.trim().is_empty() {
+        bail!("empty commit message");
+    }
+    let mut command = Command::new("git");
+    command.arg("commit");
+    if !staged {
+        command.arg("--all");
+    }
+    let mut child = command
+        .args(["--file", "-"])
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "git commit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_provider::{MockProvider, MockResponse};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn tracked_changes_are_committed_with_the_drafted_message() -> Result<()> {
+        let tmp = tempdir()?;
+        let dir = tmp.path();
+        git(dir, &["init", "--quiet"])?;
+        git(dir, &["config", "user.name", "Test"])?;
+        git(dir, &["config", "user.email", "test@example.com"])?;
+        fs::write(dir.join("talk.md"), "# Talk\n")?;
+        assert!(CommitDiff::collect(dir).is_err());
+        git(dir, &["add", "talk.md"])?;
+        git(dir, &["commit", "--quiet", "-m", "init"])?;
+
+        fs::write(dir.join("talk.md"), "# Talk\n\n## Agenda\n")?;
+        let diff = CommitDiff::collect(dir)?;
+        assert!(!diff.staged);
+        assert!(diff.diff.contains("+## Agenda"));
+        assert!(diff.stat.contains("talk.md"));
+
+        let client = MockProvider::new(
+            "commit",
+            vec![MockResponse {
+                text: "```\nfeat(talk): add the agenda slide\n```".to_string(),
+                ..Default::default()
+            }],
+        );
+        let message = generate_commit_message(&client, &diff).await?;
+        assert_eq!(message, "feat(talk): add the agenda slide\n");
+        assert!(client.prompts()[0].contains("Conventional Commits"));
+
+        let summary = create_commit(dir, &message, diff.staged)?;
+        assert!(
+            summary.ends_with("feat(talk): add the agenda slide"),
+            "{summary}"
+        );
+        let log = git(dir, &["log", "-1", "--format=%s"])?;
+        assert_eq!(log.trim(), "feat(talk): add the agenda slide");
+        assert!(CommitDiff::collect(dir).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn long_diffs_are_truncated_on_a_char_boundary() {
+        let (text, truncated) = truncate("ab€".to_string(), 3);
+        assert_eq!((text.as_str(), truncated), ("ab", true));
+        let (text, truncated) = truncate("ab".to_string(), 3);
+        assert_eq!((text.as_str(), truncated), ("ab", false));
+    }
+}
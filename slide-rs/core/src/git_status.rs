@@ -0,0 +1,164 @@
+//! Branch and working tree state of the repository the session runs in.
+//!
+//! [`GitStatus::read`] runs `git status --porcelain=v2 --branch` and keeps
+//! what the status bar and the patch approval need: the branch, how far it
+//! is ahead of and behind its upstream, and the files with uncommitted
+//! changes. The agent sends it as [`Event::GitStatus`](crate::codex2::Event)
+//! when the session starts, every [`GIT_STATUS_INTERVAL`] when it changed,
+//! and after each patch.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// How often the status is checked between patches
+pub const GIT_STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitStatus {
+    /// Checked-out branch; `None` on a detached HEAD
+    pub branch: Option<String>,
+    /// Commits not pushed to the upstream branch
+    #[serde(default)]
+    pub ahead: u32,
+    /// Commits of the upstream branch not merged yet
+    #[serde(default)]
+    pub behind: u32,
+    /// Repository root
+    pub root: PathBuf,
+    /// Files with uncommitted changes, untracked ones included, relative to
+    /// `root`
+    #[serde(default)]
+    pub dirty: Vec<PathBuf>,
+}
+
+impl GitStatus {
+    /// Status of the repository containing `dir`; `None` outside a
+    /// repository or when git is not installed.
+    pub fn read(dir: &Path) -> Option<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let status = git(dir, &["status", "--porcelain=v2", "--branch"])?;
+        let mut parsed = Self::parse(&status);
+        parsed.root = PathBuf::from(root.trim_end());
+        Some(parsed)
+    }
+
+    /// Parse the output of `git status --porcelain=v2 --branch`.
+    fn parse(output: &str) -> Self {
+        let mut status = Self::default();
+        for line in output.lines() {
+            if let Some(head) = line.strip_prefix("# branch.head ") {
+                status.branch = (head != "(detached)").then(|| head.to_string());
+            } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                for count in ab.split_whitespace() {
+                    if let Some(n) = count.strip_prefix('+') {
+                        status.ahead = n.parse().unwrap_or_default();
+                    } else if let Some(n) = count.strip_prefix('-') {
+                        status.behind = n.parse().unwrap_or_default();
+                    }
+                }
+            } else if let Some(path) = changed_path(line) {
+                status.dirty.push(PathBuf::from(path));
+            }
+        }
+        status
+    }
+
+    /// Files among `paths` that have uncommitted changes. Relative paths are
+    /// taken from `cwd`.
+    pub fn dirty_among(&self, cwd: &Path, paths: &[PathBuf]) -> Vec<PathBuf> {
+        paths
+            .iter()
+            .filter(|path| {
+                let absolute = cwd.join(path);
+                absolute
+                    .strip_prefix(&self.root)
+                    .is_ok_and(|relative| self.dirty.iter().any(|dirty| dirty == relative))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Path of a changed-file line: ordinary (`1`), renamed or copied (`2`, the
+/// new path), unmerged (`u`) or untracked (`?`) entries.
+fn changed_path(line: &str) -> Option<&str> {
+    let (fields, path) = match line.split_at_checked(2)? {
+        ("1 ", rest) => (7, rest),
+        ("2 ", rest) => (8, rest),
+        ("u ", rest) => (9, rest),
+        ("? ", rest) => return Some(rest),
+        _ => return None,
+    };
+    let path = path.splitn(fields + 1, ' ').nth(fields)?;
+    // A rename is followed by a tab and the original path
+    Some(path.split('\t').next().unwrap_or(path))
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_v2_output_is_parsed() {
+        let status = GitStatus::parse(concat!(
+            "# branch.oid 1234567890abcdef\n",
+            "# branch.head feature/deck\n",
+            "# branch.upstream origin/feature/deck\n",
+            "# branch.ab +2 -1\n",
+            "1 .M N... 100644 100644 100644 abc abc slides/talk.md\n",
+            "2 R. N... 100644 100644 100644 abc abc R100 slides/new name.md\tslides/old.md\n",
+            "u UU N... 100644 100644 100644 100644 a b c notes.md\n",
+            "? slides/draft.md\n",
+            "! target/\n",
+        ));
+        assert_eq!(status.branch.as_deref(), Some("feature/deck"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(
+            status.dirty,
+            [
+                "slides/talk.md",
+                "slides/new name.md",
+                "notes.md",
+                "slides/draft.md"
+            ]
+            .map(PathBuf::from)
+        );
+
+        let detached = GitStatus::parse("# branch.oid abc\n# branch.head (detached)\n");
+        assert_eq!(detached.branch, None);
+    }
+
+    #[test]
+    fn patch_files_are_matched_against_the_dirty_files() {
+        let status = GitStatus {
+            root: PathBuf::from("/work"),
+            dirty: vec![PathBuf::from("slides/talk.md")],
+            ..Default::default()
+        };
+        let paths = [
+            PathBuf::from("/work/slides/talk.md"),
+            PathBuf::from("talk.md"),
+            PathBuf::from("intro.md"),
+        ];
+        assert_eq!(
+            status.dirty_among(Path::new("/work/slides"), &paths),
+            [
+                PathBuf::from("/work/slides/talk.md"),
+                PathBuf::from("talk.md")
+            ]
+        );
+    }
+}
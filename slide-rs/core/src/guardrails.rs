@@ -0,0 +1,201 @@
+//! Per-turn limits on the agent's tools.
+//!
+//! [`TurnGuardrails`] counts the tool calls of a turn, the time commands and
+//! exec sessions ran, and the bytes the file tools wrote, against the
+//! `guardrails` section of the config. The [`ToolExecutor`] checks it before
+//! each call and refuses with a [`GuardrailTrip`] once a limit is reached;
+//! the turn loop then sends [`Event::GuardrailTripped`](crate::codex2::Event)
+//! and stops. Commands are also killed when they would run past the time
+//! left, while a write crossing the byte limit completes and only the next
+//! call is refused.
+//!
+//! [`ToolExecutor`]: crate::tool_executor::ToolExecutor
+use serde::{Deserialize, Serialize};
+use slide_common::GuardrailConfig;
+use std::fmt;
+use std::time::Duration;
+
+/// Which limit stopped the turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailLimit {
+    ToolCalls,
+    ExecTime,
+    BytesWritten,
+}
+
+impl GuardrailLimit {
+    /// Config key that sets the limit
+    pub fn config_key(self) -> &'static str {
+        match self {
+            GuardrailLimit::ToolCalls => "guardrails.max_tool_calls",
+            GuardrailLimit::ExecTime => "guardrails.max_exec_secs",
+            GuardrailLimit::BytesWritten => "guardrails.max_bytes_written",
+        }
+    }
+}
+
+/// A limit reached in the turn, with how much was used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailTrip {
+    pub limit: GuardrailLimit,
+    /// Calls, seconds or bytes used so far
+    pub used: u64,
+    /// The configured limit, in the same unit
+    pub max: u64,
+}
+
+impl fmt::Display for GuardrailTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.limit {
+            GuardrailLimit::ToolCalls => format!("{} tool calls were made", self.used),
+            GuardrailLimit::ExecTime => {
+                format!(
+                    "commands ran for {}s of the {}s allowed",
+                    self.used, self.max
+                )
+            }
+            GuardrailLimit::BytesWritten => format!(
+                "{} bytes were written, over the {} allowed",
+                self.used, self.max
+            ),
+        };
+        write!(
+            f,
+            "Turn stopped by a guardrail: {what} (limit {} = {}). \
+             Continue in a new message or raise the limit in the config.",
+            self.limit.config_key(),
+            self.max
+        )
+    }
+}
+
+impl std::error::Error for GuardrailTrip {}
+
+/// What the tools of one turn used so far.
+#[derive(Debug, Clone, Default)]
+pub struct TurnGuardrails {
+    config: GuardrailConfig,
+    tool_calls: u32,
+    exec_time: Duration,
+    bytes_written: u64,
+}
+
+impl TurnGuardrails {
+    pub fn new(config: GuardrailConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Count a tool call; the reached limit when the turn may not make it.
+    pub fn begin_tool_call(&mut self) -> Result<(), GuardrailTrip> {
+        self.check()?;
+        self.tool_calls += 1;
+        Ok(())
+    }
+
+    /// The first limit already used up, so that no further call may run.
+    pub fn check(&self) -> Result<(), GuardrailTrip> {
+        let max_calls = self.config.max_tool_calls;
+        if max_calls > 0 && self.tool_calls >= max_calls {
+            return Err(GuardrailTrip {
+                limit: GuardrailLimit::ToolCalls,
+                used: u64::from(self.tool_calls),
+                max: u64::from(max_calls),
+            });
+        }
+        let max_exec = self.config.max_exec_secs;
+        if max_exec > 0 && self.exec_time >= Duration::from_secs(max_exec) {
+            return Err(GuardrailTrip {
+                limit: GuardrailLimit::ExecTime,
+                used: self.exec_time.as_secs(),
+                max: max_exec,
+            });
+        }
+        let max_bytes = self.config.max_bytes_written;
+        if max_bytes > 0 && self.bytes_written > max_bytes {
+            return Err(GuardrailTrip {
+                limit: GuardrailLimit::BytesWritten,
+                used: self.bytes_written,
+                max: max_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn record_exec(&mut self, elapsed: Duration) {
+        self.exec_time += elapsed;
+    }
+
+    pub fn record_write(&mut self, bytes: u64) {
+        self.bytes_written = self.bytes_written.saturating_add(bytes);
+    }
+
+    /// Time commands may still run; `None` without a limit.
+    pub fn remaining_exec_time(&self) -> Option<Duration> {
+        let max = self.config.max_exec_secs;
+        (max > 0).then(|| Duration::from_secs(max).saturating_sub(self.exec_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_limit_stops_the_turn_once_reached() {
+        let mut guardrails = TurnGuardrails::new(GuardrailConfig {
+            max_tool_calls: 2,
+            max_exec_secs: 10,
+            max_bytes_written: 100,
+        });
+        assert_eq!(guardrails.begin_tool_call(), Ok(()));
+        assert_eq!(guardrails.begin_tool_call(), Ok(()));
+        let trip = guardrails.begin_tool_call().err();
+        assert_eq!(
+            trip,
+            Some(GuardrailTrip {
+                limit: GuardrailLimit::ToolCalls,
+                used: 2,
+                max: 2
+            })
+        );
+        assert!(trip
+            .map(|t| t.to_string())
+            .unwrap_or_default()
+            .contains("guardrails.max_tool_calls = 2"));
+
+        let mut guardrails = TurnGuardrails::new(GuardrailConfig {
+            max_tool_calls: 0,
+            max_exec_secs: 10,
+            max_bytes_written: 100,
+        });
+        guardrails.record_exec(Duration::from_secs(4));
+        assert_eq!(
+            guardrails.remaining_exec_time(),
+            Some(Duration::from_secs(6))
+        );
+        guardrails.record_write(100);
+        assert_eq!(guardrails.begin_tool_call(), Ok(()));
+        guardrails.record_write(1);
+        assert_eq!(
+            guardrails.check().map_err(|t| t.limit),
+            Err(GuardrailLimit::BytesWritten)
+        );
+
+        let mut guardrails = TurnGuardrails::new(GuardrailConfig {
+            max_tool_calls: 0,
+            max_exec_secs: 10,
+            max_bytes_written: 0,
+        });
+        guardrails.record_exec(Duration::from_secs(12));
+        guardrails.record_write(u64::MAX);
+        assert_eq!(guardrails.remaining_exec_time(), Some(Duration::ZERO));
+        assert_eq!(
+            guardrails.begin_tool_call().map_err(|t| (t.limit, t.used)),
+            Err((GuardrailLimit::ExecTime, 12))
+        );
+    }
+}
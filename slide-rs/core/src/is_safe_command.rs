@@ -1,28 +1,45 @@
-use crate::parse_command::parse_command_string;
+use crate::shell_analysis::{analyze, Dialect};
 
 pub fn is_known_safe_command(command: &[String]) -> bool {
     if is_safe_to_call_with_exec(command) {
         return true;
     }
 
-    // Support `bash -lc "..."` where the script consists solely of one or
-    // more "plain" commands (only bare words / quoted strings) combined with
-    // a conservative allow‑list of shell operators that themselves do not
-    // introduce side effects ( "&&", "||", ";", and "|" ). If every
-    // individual command in the script is itself a known‑safe command, then
-    // the composite expression is considered safe.
-    if command.len() == 3 && command[0] == "bash" && command[1] == "-lc" {
-        let script = &command[2];
-        // Parse the script and check if all commands are safe
-        let parsed_script = parse_command_string(script);
-        if !parsed_script.is_empty() && is_safe_to_call_with_exec(&parsed_script) {
-            return true;
-        }
+    // Support `bash -lc "..."` (and the other shells a login-shell
+    // invocation may use) when every command of the script is itself known
+    // safe and nothing else is attached to it: no substitutions, expansions,
+    // writing redirects, subshells, background jobs or env assignments.
+    if let Some((script, dialect)) = shell_script(command) {
+        return is_safe_script(script, dialect);
     }
 
     false
 }
 
+/// The script and its dialect when `command` is `<shell> -c|-lc <script>`.
+pub(crate) fn shell_script(command: &[String]) -> Option<(&str, Dialect)> {
+    match command {
+        [shell, flag, script] if matches!(flag.as_str(), "-lc" | "-c") => {
+            Dialect::for_shell(shell).map(|dialect| (script.as_str(), dialect))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `script` only runs known-safe commands, joined by the operators
+/// that add no side effects of their own (`&&`, `||`, `;`, `|`).
+pub fn is_safe_script(script: &str, dialect: Dialect) -> bool {
+    match analyze(script, dialect) {
+        Ok(portions) => {
+            !portions.is_empty()
+                && portions.iter().all(|portion| {
+                    portion.concerns.is_empty() && is_safe_to_call_with_exec(&portion.words)
+                })
+        }
+        Err(_) => false,
+    }
+}
+
 fn is_safe_to_call_with_exec(command: &[String]) -> bool {
     let cmd0 = command.first().map(String::as_str);
 
@@ -116,18 +133,25 @@ fn is_safe_to_call_with_exec(command: &[String]) -> bool {
         }
 
         // Common safe utilities
-        Some("whoami" | "date" | "uptime" | "uname" | "env" | "printenv") => true,
+        Some("whoami" | "date" | "uptime" | "uname" | "printenv") => true,
+
+        // `env` with arguments runs them as a command
+        Some("env") => command.len() == 1,
 
         // File inspection tools
         Some("file" | "stat" | "du" | "df") => true,
 
-        // Text processing (read-only)
-        Some("sort" | "uniq" | "cut" | "awk" | "sed") => {
+        // Text processing (read-only). awk is not here: its programs can
+        // run commands with system() and write files with print >
+        Some("sort" | "uniq" | "cut") => {
             // These are generally safe for reading, but we should be careful
             // about write operations. For now, allow them.
             true
         }
 
+        // Unless it edits files in place, writes files or runs commands
+        Some("sed") => is_safe_sed(&command[1..]),
+
         // Network tools (read-only)
         Some("ping" | "traceroute" | "nslookup" | "dig") => {
             // These tools make network requests but don't modify the system
@@ -138,6 +162,166 @@ fn is_safe_to_call_with_exec(command: &[String]) -> bool {
     }
 }
 
+/// Whether a sed invocation only prints: no `-i`, and every script passes
+/// [`is_safe_sed_script`]. Scripts read from a file (`-f`) can't be checked.
+fn is_safe_sed(args: &[String]) -> bool {
+    let mut scripts = Vec::new();
+    let mut operands = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            operands.extend(iter.by_ref());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            match long.split_once('=') {
+                Some(("expression", script)) => scripts.push(script),
+                Some(("line-length", _)) => {}
+                None if long == "expression" => match iter.next() {
+                    Some(script) => scripts.push(script.as_str()),
+                    None => return false,
+                },
+                None if long == "line-length" => {
+                    iter.next();
+                }
+                None if matches!(
+                    long,
+                    "quiet"
+                        | "silent"
+                        | "regexp-extended"
+                        | "separate"
+                        | "null-data"
+                        | "unbuffered"
+                        | "posix"
+                        | "debug"
+                        | "sandbox"
+                ) => {}
+                // --in-place, --file and anything unknown
+                _ => return false,
+            }
+            continue;
+        }
+        let Some(flags) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) else {
+            operands.push(arg);
+            continue;
+        };
+        for (at, flag) in flags.char_indices() {
+            let rest = &flags[at + flag.len_utf8()..];
+            match flag {
+                'n' | 'E' | 'r' | 's' | 'z' | 'u' => continue,
+                'e' if rest.is_empty() => match iter.next() {
+                    Some(script) => scripts.push(script.as_str()),
+                    None => return false,
+                },
+                'e' => scripts.push(rest),
+                'l' => {
+                    if rest.is_empty() {
+                        iter.next();
+                    }
+                }
+                // -i, -f and anything unknown
+                _ => return false,
+            }
+            break;
+        }
+    }
+    if scripts.is_empty() {
+        match operands.first() {
+            Some(script) => scripts.push(script.as_str()),
+            None => return false,
+        }
+    }
+    scripts.into_iter().all(is_safe_sed_script)
+}
+
+/// Whether a sed script leaves files and processes alone: no `w`/`W`
+/// (write a file), `e` (run a command) or `s` with the `w`/`e` flags.
+/// Anything the scan doesn't understand counts as unsafe.
+fn is_safe_sed_script(script: &str) -> bool {
+    let mut chars = script.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ';').is_some() {}
+        if chars.peek().is_none() {
+            return true;
+        }
+        // Addresses: line numbers, `$`, `first~step`, `addr,+N` and regexes
+        while let Some(&c) = chars.peek() {
+            match c {
+                '0'..='9' | '$' | ',' | '~' | '+' | '!' | ' ' | '\t' => {
+                    chars.next();
+                }
+                '/' | '\\' => {
+                    chars.next();
+                    let delimiter = if c == '/' { Some('/') } else { chars.next() };
+                    match delimiter {
+                        Some(delimiter) if skip_sed_part(&mut chars, delimiter) => {}
+                        _ => return false,
+                    }
+                    while chars.next_if(|c| matches!(c, 'I' | 'M')).is_some() {}
+                }
+                _ => break,
+            }
+        }
+        let Some(command) = chars.next() else {
+            return false;
+        };
+        match command {
+            '{' | '}' | '=' | 'd' | 'D' | 'g' | 'G' | 'h' | 'H' | 'n' | 'N' | 'p' | 'P' | 'x'
+            | 'z' | 'F' => {}
+            // Optional exit code or line length
+            'q' | 'Q' | 'l' | 'L' => {
+                while chars
+                    .next_if(|c| c.is_ascii_whitespace() || c.is_ascii_digit())
+                    .is_some()
+                {}
+            }
+            // Text, labels and files to read run to the end of the line
+            // (labels also end at `;`)
+            'a' | 'i' | 'c' | 'r' | 'R' | 'b' | 't' | 'T' | ':' => {
+                let ends_at_semicolon = matches!(command, 'b' | 't' | 'T' | ':');
+                let mut escaped = false;
+                while let Some(c) =
+                    chars.next_if(|c| escaped || (*c != '\n' && !(ends_at_semicolon && *c == ';')))
+                {
+                    escaped = !escaped && c == '\\';
+                }
+            }
+            's' | 'y' => {
+                let Some(delimiter) = chars.next() else {
+                    return false;
+                };
+                if delimiter == '\n' || delimiter == '\\' {
+                    return false;
+                }
+                if !skip_sed_part(&mut chars, delimiter) || !skip_sed_part(&mut chars, delimiter) {
+                    return false;
+                }
+                if command == 's' {
+                    while let Some(flag) = chars.next_if(|c| !matches!(c, ';' | '\n' | '}')) {
+                        if !matches!(flag, 'g' | 'p' | 'i' | 'I' | 'm' | 'M' | '0'..='9' | ' ') {
+                            return false;
+                        }
+                    }
+                }
+            }
+            // w, W, e and anything unknown
+            _ => return false,
+        }
+    }
+}
+
+/// Skip past the next unescaped `delimiter`; false if the script ends first.
+fn skip_sed_part(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, delimiter: char) -> bool {
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == delimiter {
+            return true;
+        }
+    }
+    false
+}
+
 /// Check if a command contains any potentially dangerous patterns
 pub fn has_dangerous_patterns(command: &[String]) -> bool {
     let command_str = command.join(" ");
@@ -173,6 +357,24 @@ pub fn explain_safety_concern(command: &[String]) -> Option<String> {
         return Some("Empty command".to_string());
     }
 
+    if let Some((script, dialect)) = shell_script(command) {
+        return match analyze(script, dialect) {
+            Ok(portions) => portions
+                .iter()
+                .find_map(|portion| match portion.concerns.first() {
+                    Some(concern) => Some(format!("Script {concern}")),
+                    None if is_safe_to_call_with_exec(&portion.words) => None,
+                    None => explain_safety_concern(&portion.words).or_else(|| {
+                        Some(format!(
+                            "`{}` is not a known safe command",
+                            portion.words.join(" ")
+                        ))
+                    }),
+                }),
+            Err(err) => Some(format!("Script could not be analyzed: {err}")),
+        };
+    }
+
     let cmd0 = command.first().map(String::as_str);
 
     match cmd0 {
@@ -200,173 +402,22 @@ pub fn explain_safety_concern(command: &[String]) -> Option<String> {
     }
 }
 
-// Legacy shell parsing functions (keep for compatibility)
-
-// A conservative parser that accepts a very small, safe subset of shell.
-// Rules (aligned with the reference spec):
-// - Allow words (letters/digits/_-.//) and quoted strings without expansions.
-// - Allow operators: &&, ||, ;, | (no redirections, background, subshells).
-// - Reject: >, <, >>, <<, $, ``, $(), (), & (background), env-assignment prefixes (FOO=bar ls).
-// - Reject trailing operators (e.g., `ls &&`).
-pub fn is_known_safe(input: &str) -> bool {
-    parse_seq(input).is_some()
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Tok {
-    Word(String),
-    OpAnd,
-    OpOr,
-    OpSeq,
-    OpPipe,
-}
-
-fn parse_seq(s: &str) -> Option<Vec<Vec<String>>> {
-    let toks = tokenize(s)?;
-    if toks.is_empty() {
-        return None;
-    }
-    // Split by ;, &&, || while keeping pipelines inside each command sequence
-    let mut seqs: Vec<Vec<Tok>> = vec![Vec::new()];
-    for t in toks {
-        match t {
-            Tok::OpSeq | Tok::OpAnd | Tok::OpOr => {
-                // new sequence
-                if seqs.last().map(|v| v.is_empty()).unwrap_or(true) {
-                    return None;
-                } // trailing op
-                seqs.push(Vec::new());
-            }
-            other => seqs.last_mut().unwrap().push(other),
-        }
-    }
-    if seqs.last().map(|v| v.is_empty()).unwrap_or(true) {
-        return None;
-    }
-
-    // Each seq must be a pipeline of words and | between
-    let mut result = Vec::new();
-    for seq in seqs {
-        if seq.is_empty() {
-            return None;
-        }
-        let mut cmd: Vec<String> = Vec::new();
-        let mut cmds: Vec<Vec<String>> = Vec::new();
-        for t in seq {
-            match t {
-                Tok::Word(w) => cmd.push(w),
-                Tok::OpPipe => {
-                    if cmd.is_empty() {
-                        return None;
-                    }
-                    cmds.push(std::mem::take(&mut cmd));
-                }
-                Tok::OpAnd | Tok::OpOr | Tok::OpSeq => unreachable!(),
-            }
-        }
-        if cmd.is_empty() {
-            return None;
-        }
-        cmds.push(cmd);
-        result.extend(cmds);
-    }
-    Some(result)
-}
-
-fn tokenize(s: &str) -> Option<Vec<Tok>> {
-    let mut toks = Vec::new();
-    let mut buf = String::new();
-    let mut chars = s.chars().peekable();
-    while let Some(&c) = chars.peek() {
-        match c {
-            ' ' | '\t' | '\n' => {
-                chars.next();
-                flush_word(&mut buf, &mut toks)?;
-            }
-            '|' => {
-                chars.next();
-                flush_word(&mut buf, &mut toks)?;
-                toks.push(Tok::OpPipe);
-            }
-            ';' => {
-                chars.next();
-                flush_word(&mut buf, &mut toks)?;
-                toks.push(Tok::OpSeq);
-            }
-            '&' => {
-                // allow only &&
-                chars.next();
-                if chars.peek() == Some(&'&') {
-                    chars.next();
-                    flush_word(&mut buf, &mut toks)?;
-                    toks.push(Tok::OpAnd);
-                } else {
-                    return None;
-                }
-            }
-            '|' if matches!(chars.clone().nth(1), Some('|')) => {
-                // handled in '|' branch; but this pattern overlaps, so skip
-                unreachable!()
-            }
-            '>' | '<' => return None, // redirections not allowed
-            '(' | ')' => return None, // subshells not allowed
-            '$' | '`' => return None, // expansions not allowed
-            '"' | '\'' => {
-                // quoted string without expansions
-                let quote = c;
-                chars.next();
-                let mut q = String::new();
-                while let Some(ch) = chars.next() {
-                    if ch == quote {
-                        break;
-                    }
-                    if ch == '$' || ch == '`' {
-                        return None;
-                    }
-                    q.push(ch);
-                }
-                if !matches!(
-                    chars.peek(),
-                    Some(' ') | Some('\t') | Some('\n') | Some('|') | Some(';') | Some('&') | None
-                ) {
-                    // quotes must end at token boundary
-                }
-                if !buf.is_empty() {
-                    buf.push(' ');
-                }
-                buf.push_str(&q);
-            }
-            _ => {
-                // Only allow safe word chars
-                if is_safe_char(c) {
-                    buf.push(c);
-                    chars.next();
-                } else {
-                    return None;
-                }
-            }
-        }
-    }
-    flush_word(&mut buf, &mut toks)?;
-    // reject assignment prefix: first token like NAME=VALUE
-    if let Some(Tok::Word(w)) = toks.first() {
-        if w.contains('=') && !w.starts_with("./") {
-            return None;
-        }
-    }
-    Some(toks)
+/// Why `command` needs the user's approval, as shown in the approval
+/// prompt: [`explain_safety_concern`], else that it is not known to be safe.
+pub fn describe_risk(command: &[String]) -> Option<String> {
+    explain_safety_concern(command).or_else(|| {
+        let program = command
+            .first()
+            .filter(|_| !is_known_safe_command(command))?;
+        Some(format!("`{program}` is not a known safe command"))
+    })
 }
 
-fn flush_word(buf: &mut String, toks: &mut Vec<Tok>) -> Option<()> {
-    if !buf.trim().is_empty() {
-        toks.push(Tok::Word(buf.trim().to_string()));
-        buf.clear();
-    }
-    Some(())
-}
+// Legacy shell parsing functions (keep for compatibility)
 
-fn is_safe_char(c: char) -> bool {
-    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '=')
+/// Whether a bash script only runs known-safe commands; see [`is_safe_script`].
+pub fn is_known_safe(input: &str) -> bool {
+    is_safe_script(input, Dialect::Posix)
 }
 
 #[cfg(test)]
@@ -433,6 +484,84 @@ mod tests {
         assert!(!is_known_safe_command(&cmd));
     }
 
+    #[test]
+    fn test_login_shell_scripts() {
+        let zsh = |script: &str| {
+            vec![
+                "/bin/zsh".to_string(),
+                "-lc".to_string(),
+                script.to_string(),
+            ]
+        };
+        assert!(is_known_safe_command(&zsh("ls -la")));
+        assert!(!is_known_safe_command(&zsh("rm -rf /")));
+        let fish = vec!["fish".to_string(), "-c".to_string(), "pwd".to_string()];
+        assert!(is_known_safe_command(&fish));
+        let python = vec!["python".to_string(), "-c".to_string(), "pwd".to_string()];
+        assert!(!is_known_safe_command(&python));
+    }
+
+    #[test]
+    fn test_tricky_shell_scripts() {
+        #[rustfmt::skip]
+        let cases: &[(&str, &str, bool)] = &[
+            ("bash", "ls && pwd; git status | head -n 5", true),
+            ("bash", "rg -n 'a|b;c' src 2>/dev/null | wc -l", true),
+            ("bash", "grep -c \"\\$HOME\" notes.txt", true),
+            ("bash", "cat <<'EOF' | grep x\n$(rm -rf /)\nEOF", true),
+            ("bash", "echo $'a\\tb'", true),
+            ("bash", "ls 2>&1 | head", true),
+            ("zsh", "echo 'x' > /dev/null", true),
+            ("fish", "ls; and echo 'it\\'s'", true),
+            ("fish", "echo \"$(pwd)\"", false),
+            ("fish", "echo (rm -rf /)", false),
+            ("fish", "cat <<EOF", false),
+            ("bash", "ls && rm -rf /", false),
+            ("bash", "ls | xargs rm", false),
+            ("bash", "cat $(rm -rf /)", false),
+            ("bash", "echo \"`touch x`\"", false),
+            ("bash", "cat <(rm -rf /)", false),
+            ("bash", "cat <<EOF\n$(rm -rf /)\nEOF", false),
+            ("bash", "echo $HOME", false),
+            ("bash", "ls > listing.txt", false),
+            ("bash", "echo hi >>~/.bashrc", false),
+            ("bash", "ls &", false),
+            ("bash", "(rm -rf /)", false),
+            ("bash", "PATH=/tmp/evil ls", false),
+            ("bash", "env rm -rf /", false),
+            ("bash", "sed -i 's/a/b/' f.txt", false),
+            ("bash", "sed -n '1,5p' f.txt", true),
+            ("bash", "sed -e 's/a/b/g' -e '/x/d' f.txt", true),
+            ("bash", "sed -n '/start/,/end/{p;}' f.txt", true),
+            ("bash", "sed 's/a/b/w /tmp/x' f.txt", false),
+            ("bash", "sed 'w out.txt' f.txt", false),
+            ("bash", "sed -n '1W out.txt' f.txt", false),
+            ("bash", "sed '1e date' f.txt", false),
+            ("bash", "sed 's/a/b/e' f.txt", false),
+            ("bash", "sed -ne 'p;w out.txt' f.txt", false),
+            ("bash", "sed -f script.sed f.txt", false),
+            ("bash", "awk '{print $1}' f.txt", false),
+            ("bash", "awk 'BEGIN{system(\"rm -rf x\")}'", false),
+            ("bash", "ls &&", false),
+            ("bash", "echo 'unterminated", false),
+            ("zsh", "ls *(e:'rm -rf /':)", false),
+            ("zsh", "cat =(rm -rf /)", false),
+        ];
+        for (shell, script, safe) in cases {
+            let command = vec![shell.to_string(), "-lc".to_string(), script.to_string()];
+            assert_eq!(
+                is_known_safe_command(&command),
+                *safe,
+                "{shell} -lc {script:?}"
+            );
+            assert_eq!(
+                explain_safety_concern(&command).is_none(),
+                *safe,
+                "{shell} -lc {script:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_dangerous_patterns() {
         let dangerous_cmd = vec![
@@ -0,0 +1,268 @@
+//! Lenient reading of the JSON tool calls a model writes into its reply.
+//!
+//! Models often get the JSON almost right: a trailing comma, single-quoted
+//! strings, a raw newline inside a string, unquoted keys, a comment, or an
+//! object cut off at the end of the reply. [`parse_lenient`] tries strict
+//! JSON first and then a [`repair`] pass that fixes those, JSON5-style, so
+//! the call still runs. [`tool_call_candidates`] finds the objects to try,
+//! including ones spread over several lines.
+use serde_json::Value;
+
+/// JSON of a tool call; whether it had to be repaired to parse.
+pub fn parse_lenient(text: &str) -> Result<(Value, bool), serde_json::Error> {
+    match serde_json::from_str(text) {
+        Ok(value) => Ok((value, false)),
+        Err(strict) => serde_json::from_str(&repair(text))
+            .map(|value| (value, true))
+            // The error of the original text is the one worth reporting
+            .map_err(|_| strict),
+    }
+}
+
+/// Rewrite near-JSON as JSON: single-quoted strings become double-quoted,
+/// control characters inside strings are escaped, unquoted keys are quoted,
+/// trailing commas and comments are dropped, Python's `True`/`False`/`None`
+/// become `true`/`false`/`null`, and unclosed strings and brackets are
+/// closed.
+pub fn repair(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut open: Vec<char> = Vec::new();
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        match c {
+            '"' | '\'' => i = repair_string(&chars, i, &mut out),
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while chars.get(i).is_some_and(|&c| c != '\n') {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if !matches!(next, None | Some('}') | Some(']')) {
+                    out.push(',');
+                }
+                i += 1;
+            }
+            '{' | '[' => {
+                open.push(if c == '{' { '}' } else { ']' });
+                out.push(c);
+                i += 1;
+            }
+            '}' | ']' => {
+                if open.last() == Some(&c) {
+                    open.pop();
+                }
+                out.push(c);
+                i += 1;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|&c| c.is_alphanumeric() || c == '_' || c == '$')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                // The exponent of a number such as `1e5`
+                if out.ends_with(|c: char| c.is_ascii_digit() || c == '.') {
+                    out.push_str(&word);
+                    continue;
+                }
+                let is_key = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+                match word.as_str() {
+                    _ if is_key => out.push_str(&format!("\"{word}\"")),
+                    "true" | "True" => out.push_str("true"),
+                    "false" | "False" => out.push_str("false"),
+                    "null" | "None" => out.push_str("null"),
+                    _ => out.push_str(&format!("\"{word}\"")),
+                }
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    while let Some(close) = open.pop() {
+        out.push(close);
+    }
+    out
+}
+
+/// Copy the string starting at `chars[start]` as a JSON string; returns the
+/// index after its closing quote.
+fn repair_string(chars: &[char], start: usize, out: &mut String) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    out.push('"');
+    while let Some(&c) = chars.get(i) {
+        i += 1;
+        match c {
+            '\\' => match chars.get(i) {
+                Some('\'') => {
+                    out.push('\'');
+                    i += 1;
+                }
+                Some(&e @ ('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u')) => {
+                    out.push('\\');
+                    out.push(e);
+                    i += 1;
+                }
+                // Not an escape JSON knows: keep the backslash itself
+                _ => out.push_str("\\\\"),
+            },
+            c if c == quote => {
+                out.push('"');
+                return i;
+            }
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    // Cut off at the end of the reply
+    out.push('"');
+    i
+}
+
+/// Objects of `response` that may be tool calls: starting a line or
+/// following `<tool_call>`, and mentioning a `tool` key. An object runs to
+/// its closing brace, which may be lines later; one left open ends at
+/// `</tool_call>`, a code fence or the end of the reply.
+pub fn tool_call_candidates(response: &str) -> Vec<&str> {
+    let mut candidates = Vec::new();
+    let mut pos = 0;
+    while pos < response.len() {
+        let line_end = response[pos..]
+            .find('\n')
+            .map_or(response.len(), |n| pos + n + 1);
+        let line = &response[pos..line_end];
+        let start = match line.find("<tool_call>") {
+            Some(tag) => line[tag + "<tool_call>".len()..]
+                .find('{')
+                .map(|brace| pos + tag + "<tool_call>".len() + brace),
+            None => line
+                .trim_start()
+                .starts_with('{')
+                .then(|| pos + line.len() - line.trim_start().len()),
+        };
+        let Some(start) = start else {
+            pos = line_end;
+            continue;
+        };
+        let end = object_end(response, start);
+        let candidate = response[start..end].trim_end();
+        if ["\"tool\"", "'tool'", "tool:", "tool :"]
+            .iter()
+            .any(|key| candidate.contains(key))
+        {
+            candidates.push(candidate);
+        }
+        pos = end.max(line_end);
+    }
+    candidates
+}
+
+/// Byte index after the object starting at `start`.
+fn object_end(text: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (offset, c) in text[start..].char_indices() {
+        let index = start + offset;
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return index + c.len_utf8();
+                }
+            }
+            '<' if text[index..].starts_with("</tool_call>") => return index,
+            '\n' if text[index + 1..].trim_start().starts_with("```") => return index,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn near_json_is_repaired() {
+        let cases = [
+            (
+                r#"{"tool": "shell", "command": ["ls",],}"#,
+                json!({"tool": "shell", "command": ["ls"]}),
+            ),
+            (
+                "{'tool': 'write_file', 'path': 'a.md', 'content': 'it\\'s \"fine\"'}",
+                json!({"tool": "write_file", "path": "a.md", "content": "it's \"fine\""}),
+            ),
+            (
+                "{\"tool\": \"write_file\", \"content\": \"# Title\n\n- one\"}",
+                json!({"tool": "write_file", "content": "# Title\n\n- one"}),
+            ),
+            (
+                "{tool: \"read_file\", path: \"a.md\", // the deck\n offset: 3}",
+                json!({"tool": "read_file", "path": "a.md", "offset": 3}),
+            ),
+            (
+                r#"{"tool": "shell", "command": ["ls"], "escalated": False, "cwd": None"#,
+                json!({"tool": "shell", "command": ["ls"], "escalated": false, "cwd": null}),
+            ),
+            (
+                r#"{"tool": "shell", "command": "echo \d"}"#,
+                json!({"tool": "shell", "command": "echo \\d"}),
+            ),
+        ];
+        for (text, expected) in cases {
+            let parsed = parse_lenient(text).map_err(|e| e.to_string());
+            assert_eq!(parsed, Ok((expected, true)), "{text}");
+        }
+        assert_eq!(
+            parse_lenient(r#"{"tool": "list_files"}"#).map_err(|e| e.to_string()),
+            Ok((json!({"tool": "list_files"}), false))
+        );
+        assert!(parse_lenient(r#"{"tool": "shell", "command": ["ls" "-la"]}"#).is_err());
+    }
+
+    #[test]
+    fn candidates_span_lines_and_tags() {
+        let response = "Let me look.\n\n{\"tool\": \"write_file\",\n \"path\": \"a.md\",\n \"content\": \"{not a call}\"}\nThen\n<tool_call>{\"tool\": \"list_files\"}</tool_call>\n{\"just\": \"data\"}\n```json\n{'tool': 'shell', 'command': ['ls'\n```\n";
+        assert_eq!(
+            tool_call_candidates(response),
+            [
+                "{\"tool\": \"write_file\",\n \"path\": \"a.md\",\n \"content\": \"{not a call}\"}",
+                "{\"tool\": \"list_files\"}",
+                "{'tool': 'shell', 'command': ['ls'",
+            ]
+        );
+    }
+}
@@ -4,19 +4,46 @@
 pub mod approval_manager;
 pub mod client;
 pub mod codex2;
+pub mod command_translation;
 pub mod config_types;
+pub mod custom_prompts;
 pub mod error;
+pub mod event_log;
 pub mod exec_basic;
+pub mod exec_command;
 pub mod exec_env;
 pub mod exec_sandboxed;
+pub mod file_outline;
+pub mod file_watcher;
+pub mod git_commit;
+pub mod git_status;
+pub mod guardrails;
 pub mod is_safe_command;
+pub mod json_repair;
+pub mod mock_provider;
+pub mod moderation;
 pub mod openai_tools;
 pub mod parse_command;
+pub mod plan_tool;
+pub mod project_doc;
 pub mod safety;
 pub mod seatbelt;
 pub mod shell;
+pub mod shell_analysis;
+pub mod slide_generate;
+pub mod slide_regenerate;
+pub mod telemetry;
 pub mod tool_apply_patch;
+pub mod tool_env_info;
 pub mod tool_executor;
+pub mod tool_image;
+pub mod tool_read_file;
+pub mod tool_registry;
+pub mod tool_web_fetch;
+pub mod turn_retry;
+pub mod turn_snapshot;
+pub mod workspace_context;
+pub mod workspace_guard;
 
 // Re-export exec_basic as exec for compatibility
 pub use exec_basic as exec;
@@ -1,14 +1,15 @@
 use crate::approval_manager::{ApprovalManager, AskForApproval};
 use crate::exec::{process_exec_tool_call, ExecParams, SandboxType, StdoutStream, Event, EventMsg};
 use crate::protocol::{EventDispatcher, SessionManager};
-use crate::seatbelt::SandboxPolicy;
-use crate::tool_executor::ToolExecutor;
+use protocol::config_types::SandboxPolicy;
+use crate::tool_executor::{ToolCall, ToolExecutor};
+use crate::tool_registry::{ShellTool, Tool};
 use anyhow::{Context, Result};
 use async_channel::{Receiver, Sender};
 use mcp_types::{
     CallToolRequest, CallToolResult, ContentBlock, RequestId, TextContent, ToolInfo,
 };
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -49,116 +50,16 @@ impl MCPToolServer {
 
     /// List available tools
     pub async fn list_tools(&self) -> Vec<ToolInfo> {
-        vec![
-            ToolInfo {
-                name: "shell".to_string(),
-                description: Some("Execute shell commands with optional sandbox restrictions".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "command": {
-                            "type": "array",
-                            "items": {"type": "string"},
-                            "description": "Command and arguments to execute"
-                        },
-                        "working_dir": {
-                            "type": "string",
-                            "description": "Working directory for command execution"
-                        },
-                        "timeout_ms": {
-                            "type": "number",
-                            "description": "Timeout in milliseconds"
-                        },
-                        "with_escalated_permissions": {
-                            "type": "boolean",
-                            "description": "Request escalated permissions"
-                        },
-                        "justification": {
-                            "type": "string",
-                            "description": "Justification for command execution"
-                        }
-                    },
-                    "required": ["command"]
-                }),
-            },
-            ToolInfo {
-                name: "read_file".to_string(),
-                description: Some("Read the contents of a file".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "path": {
-                            "type": "string",
-                            "description": "Path to the file to read"
-                        }
-                    },
-                    "required": ["path"]
-                }),
-            },
-            ToolInfo {
-                name: "write_file".to_string(),
-                description: Some("Write content to a file".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "path": {
-                            "type": "string",
-                            "description": "Path to the file to write"
-                        },
-                        "content": {
-                            "type": "string",
-                            "description": "Content to write to the file"
-                        }
-                    },
-                    "required": ["path", "content"]
-                }),
-            },
-            ToolInfo {
-                name: "apply_patch".to_string(),
-                description: Some("Apply a unified diff patch to files".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "input": {
-                            "type": "string",
-                            "description": "Unified diff patch content"
-                        }
-                    },
-                    "required": ["input"]
-                }),
-            },
-            ToolInfo {
-                name: "list_files".to_string(),
-                description: Some("List files in a directory".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "path": {
-                            "type": "string",
-                            "description": "Directory path to list (optional, defaults to current directory)"
-                        }
-                    }
-                }),
-            },
-            ToolInfo {
-                name: "search_files".to_string(),
-                description: Some("Search for files by name pattern".to_string()),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "query": {
-                            "type": "string",
-                            "description": "Search query pattern"
-                        },
-                        "path": {
-                            "type": "string",
-                            "description": "Directory path to search in (optional)"
-                        }
-                    },
-                    "required": ["query"]
-                }),
-            },
-        ]
+        let executor = self.tool_executor.lock().await;
+        executor
+            .tools()
+            .tools()
+            .map(|tool| ToolInfo {
+                name: tool.name().to_string(),
+                description: Some(tool.description()),
+                input_schema: tool.input_schema(),
+            })
+            .collect()
     }
 
     /// Handle a tool call request
@@ -184,18 +85,29 @@ impl MCPToolServer {
             tx_event: self.event_dispatcher.get_event_sender(),
         });
 
-        match tool_name {
-            "shell" => self.handle_shell_tool(call_id, arguments, stdout_stream).await,
-            "read_file" => self.handle_read_file_tool(arguments).await,
-            "write_file" => self.handle_write_file_tool(arguments).await,
-            "apply_patch" => self.handle_apply_patch_tool(arguments).await,
-            "list_files" => self.handle_list_files_tool(arguments).await,
-            "search_files" => self.handle_search_files_tool(arguments).await,
-            _ => Ok(CallToolResult {
+        if tool_name == "shell" {
+            return self.handle_shell_tool(call_id, arguments, stdout_stream).await;
+        }
+
+        let mut executor = self.tool_executor.lock().await;
+        let Some(tool) = executor.tools().get(tool_name) else {
+            return Ok(CallToolResult {
                 content: vec![ContentBlock::Text(TextContent {
                     text: format!("Unknown tool: {}", tool_name),
                 })],
                 is_error: Some(true),
+            });
+        };
+        match tool.execute(&mut executor, arguments).await {
+            Ok(text) => Ok(CallToolResult {
+                content: vec![ContentBlock::Text(TextContent { text })],
+                is_error: Some(false),
+            }),
+            Err(e) => Ok(CallToolResult {
+                content: vec![ContentBlock::Text(TextContent {
+                    text: format!("{} failed: {}", tool_name, e),
+                })],
+                is_error: Some(true),
             }),
         }
     }
@@ -206,12 +118,16 @@ impl MCPToolServer {
         arguments: &Value,
         stdout_stream: Option<StdoutStream>,
     ) -> Result<CallToolResult> {
-        let command = arguments["command"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Missing or invalid command array"))?
-            .iter()
-            .map(|v| v.as_str().unwrap_or_default().to_string())
-            .collect::<Vec<_>>();
+        let ToolCall::Shell {
+            command,
+            working_dir,
+            with_escalated_permissions,
+            justification,
+            timeout_ms,
+        } = ShellTool::default().parse(arguments)?
+        else {
+            return Err(anyhow::anyhow!("shell arguments did not parse to a shell call"));
+        };
 
         if command.is_empty() {
             return Ok(CallToolResult {
@@ -222,17 +138,9 @@ impl MCPToolServer {
             });
         }
 
-        let working_dir = arguments["working_dir"]
-            .as_str()
-            .map(PathBuf::from)
+        let working_dir = working_dir
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
-        let timeout_ms = arguments["timeout_ms"].as_u64();
-        let with_escalated_permissions = arguments["with_escalated_permissions"]
-            .as_bool()
-            .unwrap_or(false);
-        let justification = arguments["justification"].as_str().map(String::from);
-
         let params = ExecParams {
             command: command.clone(),
             cwd: working_dir,
@@ -313,185 +221,6 @@ impl MCPToolServer {
         }
     }
 
-    async fn handle_read_file_tool(&self, arguments: &Value) -> Result<CallToolResult> {
-        let path = arguments["path"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing path argument"))?;
-
-        let path_buf = PathBuf::from(path);
-        match tokio::fs::read_to_string(&path_buf).await {
-            Ok(content) => Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("File: {}\n\n{}", path, content),
-                })],
-                is_error: Some(false),
-            }),
-            Err(e) => Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("Failed to read file {}: {}", path, e),
-                })],
-                is_error: Some(true),
-            }),
-        }
-    }
-
-    async fn handle_write_file_tool(&self, arguments: &Value) -> Result<CallToolResult> {
-        let path = arguments["path"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing path argument"))?;
-        let content = arguments["content"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing content argument"))?;
-
-        let path_buf = PathBuf::from(path);
-        if let Some(parent) = path_buf.parent() {
-            if let Err(e) = tokio::fs::create_dir_all(parent).await {
-                return Ok(CallToolResult {
-                    content: vec![ContentBlock::Text(TextContent {
-                        text: format!("Failed to create directory {}: {}", parent.display(), e),
-                    })],
-                    is_error: Some(true),
-                });
-            }
-        }
-
-        match tokio::fs::write(&path_buf, content).await {
-            Ok(_) => Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("Successfully wrote {} bytes to {}", content.len(), path),
-                })],
-                is_error: Some(false),
-            }),
-            Err(e) => Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("Failed to write file {}: {}", path, e),
-                })],
-                is_error: Some(true),
-            }),
-        }
-    }
-
-    async fn handle_apply_patch_tool(&self, arguments: &Value) -> Result<CallToolResult> {
-        let patch_input = arguments["input"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing patch input"))?;
-
-        use crate::tool_apply_patch::{tool_apply_patch, ApplyPatchInput};
-        let result = tool_apply_patch(
-            ApplyPatchInput {
-                patch: patch_input.to_string(),
-            },
-            true,
-        );
-
-        Ok(CallToolResult {
-            content: vec![ContentBlock::Text(TextContent {
-                text: if result.applied {
-                    format!("✅ Patch applied successfully: {}", result.message)
-                } else {
-                    format!("❌ Patch failed: {}", result.message)
-                },
-            })],
-            is_error: Some(!result.applied),
-        })
-    }
-
-    async fn handle_list_files_tool(&self, arguments: &Value) -> Result<CallToolResult> {
-        let path = arguments["path"]
-            .as_str()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-
-        match tokio::fs::read_dir(&path).await {
-            Ok(mut entries) => {
-                let mut files = Vec::new();
-                while let Some(entry) = entries.next_entry().await.unwrap_or(None) {
-                    if let Ok(name) = entry.file_name().into_string() {
-                        let file_type = if entry.path().is_dir() { "📁" } else { "📄" };
-                        files.push(format!("{} {}", file_type, name));
-                    }
-                }
-                files.sort();
-
-                Ok(CallToolResult {
-                    content: vec![ContentBlock::Text(TextContent {
-                        text: format!(
-                            "Files in {}:\n\n{}",
-                            path.display(),
-                            files.join("\n")
-                        ),
-                    })],
-                    is_error: Some(false),
-                })
-            }
-            Err(e) => Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("Failed to list files in {}: {}", path.display(), e),
-                })],
-                is_error: Some(true),
-            }),
-        }
-    }
-
-    async fn handle_search_files_tool(&self, arguments: &Value) -> Result<CallToolResult> {
-        let query = arguments["query"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing query argument"))?;
-        let search_path = arguments["path"]
-            .as_str()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-
-        let results = self.search_files_recursive(&search_path, query).await?;
-
-        if results.is_empty() {
-            Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!("No files found matching '{}' in {}", query, search_path.display()),
-                })],
-                is_error: Some(false),
-            })
-        } else {
-            Ok(CallToolResult {
-                content: vec![ContentBlock::Text(TextContent {
-                    text: format!(
-                        "Found {} files matching '{}':\n\n{}",
-                        results.len(),
-                        query,
-                        results.join("\n")
-                    ),
-                })],
-                is_error: Some(false),
-            })
-        }
-    }
-
-    async fn search_files_recursive(&self, dir: &PathBuf, query: &str) -> Result<Vec<String>> {
-        let mut results = Vec::new();
-        let mut stack = vec![dir.clone()];
-
-        while let Some(current_dir) = stack.pop() {
-            if let Ok(mut entries) = tokio::fs::read_dir(&current_dir).await {
-                while let Some(entry) = entries.next_entry().await.unwrap_or(None) {
-                    let path = entry.path();
-                    let file_name = entry.file_name();
-
-                    if let Some(name_str) = file_name.to_str() {
-                        if name_str.contains(query) {
-                            results.push(path.to_string_lossy().to_string());
-                        }
-                    }
-
-                    if path.is_dir() {
-                        stack.push(path);
-                    }
-                }
-            }
-        }
-
-        Ok(results)
-    }
-
     /// Get event dispatcher for external subscription
     pub fn get_event_dispatcher(&self) -> EventDispatcher {
         self.event_dispatcher.clone()
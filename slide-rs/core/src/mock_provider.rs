@@ -0,0 +1,256 @@
+//! Scripted model for tests and demos, selected with
+//! `SLIDE_MODEL=mock:<fixture>`. Each request to the model gets the next
+//! response of the fixture, so the agent loop, approvals and patching run
+//! end to end without network access and give the same result every time.
+//!
+//! A fixture is a JSON file (the `.json` extension may be left out):
+//!
+//! ```json
+//! {
+//!   "responses": [
+//!     {
+//!       "text": "Adding the title slide.",
+//!       "tool_calls": [{"tool": "apply_patch", "input": "*** Begin Patch\n..."}]
+//!     },
+//!     {"deltas": ["Done", "."]},
+//!     {"error": "rate limited"}
+//!   ]
+//! }
+//! ```
+//!
+//! Tool calls are appended to the reply as one JSON line each, the form
+//! [`ToolExecutor::extract_tool_calls`](crate::tool_executor::ToolExecutor::extract_tool_calls)
+//! reads. A request past the end of the script fails.
+//!
+//! A turn sends its request again after an `error` that may pass (see
+//! [`is_transient`](crate::turn_retry::is_transient)), so the retry takes
+//! the next response of the script.
+use crate::client::{ModelClient, ResponseEvent};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::mpsc::Receiver;
+
+/// Model names starting with this pick the mock provider
+pub const MOCK_PREFIX: &str = "mock:";
+
+/// One scripted reply.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MockResponse {
+    /// Reply streamed in these pieces, before `text`
+    pub deltas: Vec<String>,
+    /// Reply streamed as one piece
+    pub text: String,
+    /// Tool calls, each sent as a JSON line after the text
+    pub tool_calls: Vec<serde_json::Value>,
+    /// Fail the request with this message, after streaming what comes
+    /// before it, so a reply can break off midway
+    pub error: Option<String>,
+}
+
+impl MockResponse {
+    /// Pieces of the reply as they are streamed.
+    fn chunks(&self) -> Result<Vec<String>> {
+        let mut chunks: Vec<String> = self.deltas.clone();
+        if !self.text.is_empty() {
+            chunks.push(self.text.clone());
+        }
+        for call in &self.tool_calls {
+            let line = serde_json::to_string(call)?;
+            let after_text = chunks.last().is_some_and(|c| !c.ends_with('\n'));
+            chunks.push(if after_text {
+                format!("\n{line}\n")
+            } else {
+                format!("{line}\n")
+            });
+        }
+        Ok(chunks)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MockScript {
+    responses: Vec<MockResponse>,
+}
+
+pub struct MockProvider {
+    /// `mock:<fixture name>`, shown as the model
+    model: String,
+    responses: Mutex<VecDeque<MockResponse>>,
+    /// Prompts received, oldest first
+    prompts: Mutex<Vec<String>>,
+    /// Why the fixture could not be loaded; every request fails with it
+    load_error: Option<String>,
+}
+
+impl MockProvider {
+    pub fn new(name: &str, responses: Vec<MockResponse>) -> Self {
+        Self {
+            model: format!("{MOCK_PREFIX}{name}"),
+            responses: Mutex::new(responses.into()),
+            prompts: Mutex::new(Vec::new()),
+            load_error: None,
+        }
+    }
+
+    /// Provider whose requests all fail with `error`, so a fixture that
+    /// cannot be loaded shows up in the conversation instead of falling
+    /// back to a real model.
+    pub fn failing(name: &str, error: String) -> Self {
+        Self {
+            load_error: Some(error),
+            ..Self::new(name, Vec::new())
+        }
+    }
+
+    /// Load the script in `path`.
+    pub fn from_fixture(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read mock fixture {}", path.display()))?;
+        let script: MockScript = serde_json::from_str(&text)
+            .with_context(|| format!("invalid mock fixture {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self::new(&name, script.responses))
+    }
+
+    /// Provider for a model name such as `mock:fixtures/deck`; `None` when
+    /// `model` does not start with [`MOCK_PREFIX`].
+    pub fn from_model(model: &str) -> Option<Result<Self>> {
+        let fixture = model.strip_prefix(MOCK_PREFIX)?;
+        Some(Self::from_fixture(&fixture_path(fixture)))
+    }
+
+    /// Prompts sent to the model so far, oldest first.
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    /// Responses not used yet.
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().map(|r| r.len()).unwrap_or_default()
+    }
+}
+
+/// `fixture`, with `.json` added when it has no extension.
+fn fixture_path(fixture: &str) -> PathBuf {
+    let path = PathBuf::from(fixture);
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension("json")
+    }
+}
+
+#[async_trait]
+impl ModelClient for MockProvider {
+    async fn stream(&self, prompt: String) -> Result<Receiver<ResponseEvent>> {
+        if let Ok(mut prompts) = self.prompts.lock() {
+            prompts.push(prompt);
+        }
+        if let Some(error) = &self.load_error {
+            anyhow::bail!("{error}");
+        }
+        let response = self
+            .responses
+            .lock()
+            .map_err(|_| anyhow::anyhow!("mock script lock poisoned"))?
+            .pop_front()
+            .with_context(|| format!("{} has no response left", self.model))?;
+        let chunks = response.chunks()?;
+        let (tx, rx) = tokio::sync::mpsc::channel(chunks.len() + 1);
+        for chunk in chunks {
+            let _ = tx.send(ResponseEvent::TextDelta(chunk)).await;
+        }
+        let end = match response.error {
+            Some(message) => ResponseEvent::Error(message),
+            None => ResponseEvent::Completed,
+        };
+        let _ = tx.send(end).await;
+        Ok(rx)
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn provider(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_executor::ToolCall;
+    use tempfile::tempdir;
+
+    async fn reply(provider: &MockProvider) -> Result<Vec<ResponseEvent>> {
+        let mut rx = provider.stream("prompt".to_string()).await?;
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[tokio::test]
+    async fn fixtures_are_replayed_in_order() -> Result<()> {
+        let tmp = tempdir()?;
+        std::fs::write(
+            tmp.path().join("deck.json"),
+            r#"{"responses": [
+                {"deltas": ["Hel", "lo"]},
+                {"text": "Listing.", "tool_calls": [{"tool": "shell", "command": ["ls"]}]},
+                {"error": "rate limited"}
+            ]}"#,
+        )?;
+        let spec = format!("mock:{}", tmp.path().join("deck").display());
+        let provider = MockProvider::from_model(&spec).context("not a mock model")??;
+        assert_eq!(provider.model(), "mock:deck");
+        assert_eq!(provider.remaining(), 3);
+
+        let events = reply(&provider).await?;
+        assert!(matches!(
+            events.as_slice(),
+            [ResponseEvent::TextDelta(a), ResponseEvent::TextDelta(b), ResponseEvent::Completed]
+                if a == "Hel" && b == "lo"
+        ));
+
+        let text: String = reply(&provider)
+            .await?
+            .into_iter()
+            .filter_map(|event| match event {
+                ResponseEvent::TextDelta(delta) => Some(delta),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            text,
+            "Listing.\n{\"command\":[\"ls\"],\"tool\":\"shell\"}\n"
+        );
+        let executor = crate::tool_executor::ToolExecutor::new(
+            Default::default(),
+            Default::default(),
+            tmp.path().to_path_buf(),
+            Default::default(),
+        );
+        let calls = executor.extract_tool_calls(&text)?;
+        assert!(
+            matches!(calls.as_slice(), [ToolCall::Shell { command, .. }] if command == &["ls"])
+        );
+
+        let events = reply(&provider).await?;
+        assert!(matches!(events.as_slice(), [ResponseEvent::Error(m)] if m == "rate limited"));
+        assert!(provider.stream("again".to_string()).await.is_err());
+        assert_eq!(provider.prompts().len(), 4);
+        assert!(MockProvider::from_model("gpt-4o").is_none());
+        Ok(())
+    }
+}
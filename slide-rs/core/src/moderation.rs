@@ -0,0 +1,82 @@
+//! Content safety check of deck text before it is written; see
+//! [`slide_common::moderation`] for the settings.
+use slide_apply_patch::ApplyPatchFileChange;
+use slide_chatgpt::moderation::moderate;
+use slide_common::moderation::{matched_terms, ModerationConfig, ModerationProvider};
+use tracing::warn;
+
+/// Why `text` is flagged under `config`: the blocked terms it contains and
+/// the categories the provider flagged. Empty when it passes or the check
+/// is off. A provider that cannot be asked flags the text as well, so a
+/// blocking profile never lets unchecked text through.
+pub async fn check_content(config: &ModerationConfig, text: &str) -> Vec<String> {
+    if !config.enabled() || text.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut reasons = matched_terms(text, &config.blocked_terms);
+    if config.provider == ModerationProvider::Openai {
+        let flagged = match slide_common::auth::openai_api_key() {
+            Some(api_key) => moderate(&api_key, text).await,
+            None => Err(anyhow::anyhow!("no OpenAI API key")),
+        };
+        match flagged {
+            Ok(categories) => reasons.extend(config.counted_categories(categories)),
+            Err(e) => {
+                warn!(error = %e, "moderation request failed");
+                reasons.push(format!("not checked: {e}"));
+            }
+        }
+    }
+    reasons
+}
+
+/// Text `change` adds to a file: all of a new file, the added lines of an
+/// update.
+pub fn added_text(change: &ApplyPatchFileChange) -> String {
+    match change {
+        ApplyPatchFileChange::Add { content } => content.clone(),
+        ApplyPatchFileChange::Delete => String::new(),
+        ApplyPatchFileChange::Update { unified_diff, .. } => added_lines(unified_diff),
+    }
+}
+
+/// Added lines of a unified diff.
+pub fn added_lines(unified_diff: &str) -> String {
+    unified_diff
+        .lines()
+        .filter(|line| !line.starts_with("+++"))
+        .filter_map(|line| line.strip_prefix('+'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slide_common::moderation::ModerationAction;
+
+    #[tokio::test]
+    async fn only_added_text_is_checked_against_the_terms() {
+        let change = ApplyPatchFileChange::Update {
+            unified_diff:
+                "--- a/talk.md\n+++ b/talk.md\n@@ -1,2 +1,2 @@\n # Deck\n-- gore\n+- graphic gore\n"
+                    .to_string(),
+            move_path: None,
+            new_content: "# Deck\n- graphic gore\n".to_string(),
+        };
+        assert_eq!(added_text(&change), "- graphic gore");
+
+        let mut config = ModerationConfig {
+            blocked_terms: vec!["gore".to_string()],
+            ..Default::default()
+        };
+        // Off unless an action is set
+        assert!(check_content(&config, "gore").await.is_empty());
+        config.action = ModerationAction::Block;
+        assert_eq!(
+            check_content(&config, &added_text(&change)).await,
+            ["\"gore\""]
+        );
+        assert!(check_content(&config, "# Deck").await.is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::approval_manager::AskForApproval;
-use crate::seatbelt::SandboxPolicy;
+use crate::tool_registry::ToolRegistry;
+use protocol::config_types::SandboxPolicy;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::BTreeMap;
@@ -45,6 +46,69 @@ pub enum JsonSchema {
     },
 }
 
+impl JsonSchema {
+    /// Ways `value` does not match the schema: missing required properties
+    /// and values of the wrong type. Properties the schema does not list are
+    /// not reported.
+    pub fn validate(&self, value: &JsonValue) -> Vec<String> {
+        let mut problems = Vec::new();
+        self.validate_at("", value, &mut problems);
+        problems
+    }
+
+    fn validate_at(&self, path: &str, value: &JsonValue, problems: &mut Vec<String>) {
+        match (self, value) {
+            (JsonSchema::Boolean { .. }, JsonValue::Bool(_))
+            | (JsonSchema::String { .. }, JsonValue::String(_))
+            | (JsonSchema::Number { .. }, JsonValue::Number(_)) => {}
+            (JsonSchema::Array { items, .. }, JsonValue::Array(values)) => {
+                for (index, value) in values.iter().enumerate() {
+                    items.validate_at(&format!("{path}[{index}]"), value, problems);
+                }
+            }
+            (
+                JsonSchema::Object {
+                    properties,
+                    required,
+                    ..
+                },
+                JsonValue::Object(map),
+            ) => {
+                let key_path = |key: &str| {
+                    if path.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{path}.{key}")
+                    }
+                };
+                for key in required.iter().flatten() {
+                    if map.get(key).is_none_or(JsonValue::is_null) {
+                        problems.push(format!("missing required `{}`", key_path(key)));
+                    }
+                }
+                for (key, value) in map {
+                    if let Some(schema) = properties.get(key) {
+                        schema.validate_at(&key_path(key), value, problems);
+                    }
+                }
+            }
+            // An optional property left null counts as absent
+            (_, JsonValue::Null) if !path.is_empty() => {}
+            (schema, _) => {
+                let expected = match schema {
+                    JsonSchema::Boolean { .. } => "a boolean",
+                    JsonSchema::String { .. } => "a string",
+                    JsonSchema::Number { .. } => "a number",
+                    JsonSchema::Array { .. } => "an array",
+                    JsonSchema::Object { .. } => "an object",
+                };
+                let name = if path.is_empty() { "arguments" } else { path };
+                problems.push(format!("`{name}` must be {expected}"));
+            }
+        }
+    }
+}
+
 /// Tool definition that matches OpenAI function calling format
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct ResponsesApiTool {
@@ -101,6 +165,9 @@ pub struct ToolsConfig {
     pub include_view_image_tool: bool,
     pub include_web_search_request: bool,
     pub include_slides_tools: bool,
+    pub include_image_tool: bool,
+    pub include_read_file_tool: bool,
+    pub include_web_fetch_tool: bool,
 }
 
 pub struct ToolsConfigParams {
@@ -112,6 +179,9 @@ pub struct ToolsConfigParams {
     pub include_web_search_request: bool,
     pub use_streamable_shell_tool: bool,
     pub include_slides_tools: bool,
+    pub include_image_tool: bool,
+    pub include_read_file_tool: bool,
+    pub include_web_fetch_tool: bool,
 }
 
 impl ToolsConfig {
@@ -135,258 +205,16 @@ impl ToolsConfig {
             include_view_image_tool: params.include_view_image_tool,
             include_web_search_request: params.include_web_search_request,
             include_slides_tools: params.include_slides_tools,
+            include_image_tool: params.include_image_tool,
+            include_read_file_tool: params.include_read_file_tool,
+            include_web_fetch_tool: params.include_web_fetch_tool,
         }
     }
 }
 
-/// Create the basic shell tool
-fn create_shell_tool() -> OpenAiTool {
-    let mut properties = BTreeMap::new();
-    properties.insert(
-        "command".to_string(),
-        JsonSchema::Array {
-            items: Box::new(JsonSchema::String { description: None }),
-            description: Some("The command to execute".to_string()),
-        },
-    );
-    properties.insert(
-        "workdir".to_string(),
-        JsonSchema::String {
-            description: Some("The working directory to execute the command in".to_string()),
-        },
-    );
-    properties.insert(
-        "timeout_ms".to_string(),
-        JsonSchema::Number {
-            description: Some("The timeout for the command in milliseconds".to_string()),
-        },
-    );
-
-    OpenAiTool::Function(ResponsesApiTool {
-        name: "shell".to_string(),
-        description: "Runs a shell command and returns its output".to_string(),
-        strict: false,
-        parameters: JsonSchema::Object {
-            properties,
-            required: Some(vec!["command".to_string()]),
-            additional_properties: Some(false),
-        },
-    })
-}
-
-/// Create the sandbox-aware shell tool with approval support
-fn create_shell_tool_for_sandbox(sandbox_policy: &SandboxPolicy) -> OpenAiTool {
-    let mut properties = BTreeMap::new();
-    properties.insert(
-        "command".to_string(),
-        JsonSchema::Array {
-            items: Box::new(JsonSchema::String { description: None }),
-            description: Some("The command to execute".to_string()),
-        },
-    );
-    properties.insert(
-        "workdir".to_string(),
-        JsonSchema::String {
-            description: Some("The working directory to execute the command in".to_string()),
-        },
-    );
-    properties.insert(
-        "timeout_ms".to_string(),
-        JsonSchema::Number {
-            description: Some("The timeout for the command in milliseconds".to_string()),
-        },
-    );
-
-    // Add escalated permissions support for workspace-write mode
-    if matches!(sandbox_policy, SandboxPolicy::WorkspaceWrite { .. }) {
-        properties.insert(
-            "with_escalated_permissions".to_string(),
-            JsonSchema::Boolean {
-                description: Some("Whether to request escalated permissions. Set to true if command needs to be run without sandbox restrictions".to_string()),
-            },
-        );
-        properties.insert(
-            "justification".to_string(),
-            JsonSchema::String {
-                description: Some("Justification for why this command needs to be run".to_string()),
-            },
-        );
-    }
-
-    let required = vec!["command".to_string()];
-    if matches!(sandbox_policy, SandboxPolicy::WorkspaceWrite { .. }) {
-        // justification is required when with_escalated_permissions is used
-    }
-
-    OpenAiTool::Function(ResponsesApiTool {
-        name: "shell".to_string(),
-        description: format!(
-            "Runs a shell command with sandbox policy: {}. {}",
-            match sandbox_policy {
-                SandboxPolicy::ReadOnly => "read-only",
-                SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
-                SandboxPolicy::DangerFullAccess => "danger-full-access",
-            },
-            if matches!(sandbox_policy, SandboxPolicy::WorkspaceWrite { .. }) {
-                "Use with_escalated_permissions=true for commands that need to access outside workspace."
-            } else {
-                ""
-            }
-        ),
-        strict: false,
-        parameters: JsonSchema::Object {
-            properties,
-            required: Some(required),
-            additional_properties: Some(false),
-        },
-    })
-}
-
-/// Create the plan tool
-fn create_plan_tool() -> OpenAiTool {
-    let mut plan_item_props = BTreeMap::new();
-    plan_item_props.insert("step".to_string(), JsonSchema::String { description: None });
-    plan_item_props.insert(
-        "status".to_string(),
-        JsonSchema::String {
-            description: Some("One of: pending, in_progress, completed".to_string()),
-        },
-    );
-
-    let plan_items_schema = JsonSchema::Array {
-        description: Some("The list of steps".to_string()),
-        items: Box::new(JsonSchema::Object {
-            properties: plan_item_props,
-            required: Some(vec!["step".to_string(), "status".to_string()]),
-            additional_properties: Some(false),
-        }),
-    };
-
-    let mut properties = BTreeMap::new();
-    properties.insert(
-        "explanation".to_string(),
-        JsonSchema::String { description: None },
-    );
-    properties.insert("plan".to_string(), plan_items_schema);
-
-    OpenAiTool::Function(ResponsesApiTool {
-        name: "update_plan".to_string(),
-        description: "Updates the task plan. Provide an explanation and a list of plan items."
-            .to_string(),
-        strict: false,
-        parameters: JsonSchema::Object {
-            properties,
-            required: Some(vec!["plan".to_string()]),
-            additional_properties: Some(false),
-        },
-    })
-}
-
 /// Create tools based on configuration
 pub fn create_tools(config: &ToolsConfig, _mcp_tools: Option<Vec<String>>) -> Vec<OpenAiTool> {
-    let mut tools = Vec::new();
-
-    // Add shell tool based on configuration
-    match &config.shell_type {
-        ConfigShellToolType::DefaultShell => {
-            tools.push(create_shell_tool());
-        }
-        ConfigShellToolType::ShellWithRequest { sandbox_policy } => {
-            tools.push(create_shell_tool_for_sandbox(sandbox_policy));
-        }
-        ConfigShellToolType::LocalShell => {
-            // For now, same as default shell
-            tools.push(create_shell_tool());
-        }
-        ConfigShellToolType::StreamableShell => {
-            // Add streamable shell tools (simplified)
-            tools.push(create_shell_tool());
-        }
-    }
-
-    if config.include_plan_tool {
-        tools.push(create_plan_tool());
-    }
-
-    // Add apply_patch tool if enabled
-    if config.include_apply_patch_tool {
-        tools.push(create_apply_patch_tool());
-    }
-
-    // Note: Other tools (view_image, etc.) would be implemented similarly
-
-    tools
-}
-
-/// Create the apply_patch tool
-fn create_apply_patch_tool() -> OpenAiTool {
-    let mut properties = BTreeMap::new();
-    properties.insert(
-        "input".to_string(),
-        JsonSchema::String {
-            description: Some(r#"The entire contents of the apply_patch command"#.to_string()),
-        },
-    );
-
-    OpenAiTool::Function(ResponsesApiTool {
-        name: "apply_patch".to_string(),
-        description: r#"Use the `apply_patch` tool to edit files.
-Your patch language is a stripped‑down, file‑oriented diff format designed to be easy to parse and safe to apply. You can think of it as a high‑level envelope:
-
-*** Begin Patch
-[ one or more file sections ]
-*** End Patch
-
-Within that envelope, you get a sequence of file operations.
-You MUST include a header to specify the action you are taking.
-Each operation starts with one of three headers:
-
-*** Add File: <path> - create a new file. Every following line is a + line (the initial contents).
-*** Delete File: <path> - delete a file (no more lines after this header).
-*** Update File: <path> - edit an existing file. This supports:
-  1. Context lines (starting with a space).
-  2. Additions (starting with +).
-  3. Deletions (starting with -).
-  4. Optional context markers (@@ ... @@).
-  5. Optional end‑of‑file marker (*** End of File).
-
-Examples:
-
-*** Begin Patch
-*** Add File: hello.py
-+print("Hello, world!")
-+print("This is a new file")
-*** End Patch
-
-*** Begin Patch
-*** Update File: main.py
- def main():
--    print("Old message")
-+    print("New message")
-     return 0
-*** End Patch
-
-*** Begin Patch
-*** Delete File: obsolete.py
-*** End Patch
-
-*** Begin Patch
-*** Update File: config.json
-@@ Adding new configuration @@
- {
-   "version": "1.0",
-+  "debug": true,
-   "name": "myapp"
- }
-*** End Patch
-"#.to_string(),
-        strict: false,
-        parameters: JsonSchema::Object {
-            properties,
-            required: Some(vec!["input".to_string()]),
-            additional_properties: Some(false),
-        },
-    })
+    ToolRegistry::for_config(config).openai_tools()
 }
 
 /// Legacy function for compatibility - returns tool names
@@ -413,7 +241,10 @@ pub fn render_tools_instructions(cfg: &ToolsConfig, approval_mode_hint: Option<&
     // Shell tool description based on configuration
     match &cfg.shell_type {
         ConfigShellToolType::StreamableShell => {
-            lines.push("- exec_command: run a shell command. Always explain why and prefer read-only commands (ls, cat, rg).".to_string());
+            lines.push("- shell: run a shell command. Always explain why and prefer read-only commands (ls, cat, rg).".to_string());
+            lines.push("- exec_session_open: {\"tool\": \"exec_session_open\", \"command\": [\"python3\", \"-i\"]} starts a persistent shell or REPL and returns its session_id; use it when later commands need earlier state.".to_string());
+            lines.push("- exec_session_write / exec_session_read: {\"tool\": \"exec_session_write\", \"session_id\": ..., \"chars\": \"...\\n\"} sends input (end commands with a newline) and returns new output; exec_session_read only collects output.".to_string());
+            lines.push("- exec_session_close: {\"tool\": \"exec_session_close\", \"session_id\": ...} ends a session you no longer need.".to_string());
         }
         ConfigShellToolType::ShellWithRequest { sandbox_policy } => {
             let policy_desc = match sandbox_policy {
@@ -446,18 +277,45 @@ pub fn render_tools_instructions(cfg: &ToolsConfig, approval_mode_hint: Option<&
                 .to_string(),
         );
     }
+    if cfg.include_image_tool {
+        lines.push(
+            "- image: {\"tool\": \"image\", \"url\"|\"prompt\": ..., \"alt\": ..., \"deck\": ..., \"slide\": ...} saves an image under slides/assets/ and references it from the deck (needs approval)."
+                .to_string(),
+        );
+    }
+    if cfg.include_read_file_tool {
+        lines.push(
+            "- read_file: {\"tool\": \"read_file\", \"path\": ..., \"offset\": ..., \"limit\": ..., \"max_bytes\": ...} reads a window of lines (offset is 1-based); page through large files with offset instead of reading them whole. Large code files and decks read without offset/limit return an outline with line ranges to pick from."
+                .to_string(),
+        );
+    }
     if cfg.include_plan_tool {
-        lines.push("- update_plan: refine your task plan concisely.".to_string());
+        lines.push(
+            "- update_plan: {\"tool\": \"update_plan\", \"explanation\": ..., \"plan\": [{\"step\": ..., \"status\": \"pending\"|\"in_progress\"|\"completed\"|\"failed\"}]} keeps your task checklist up to date; send the whole plan each time. For multi-step work, send the plan first, keep one step in_progress, and mark it completed as soon as it is done."
+                .to_string(),
+        );
     }
     if cfg.include_view_image_tool {
         lines.push("- view_image: request to view an image by path.".to_string());
     }
+    if cfg.include_web_fetch_tool {
+        lines.push(
+            "- web_fetch: {\"tool\": \"web_fetch\", \"url\": ..., \"max_bytes\": ...} downloads a page as markdown so slides can cite current information (needs approval and network access). Cite a fact by linking its bullet to the page, e.g. `([source](URL))`; the References slide at the end of the deck is generated, do not edit it."
+                .to_string(),
+        );
+    }
     if cfg.include_web_search_request {
         lines.push(
-            "- web_search_request: request a web search when strictly necessary.".to_string(),
+            "- web_search: {\"tool\": \"web_search\", \"query\": ..., \"count\": ...} lists web results with their URLs; search only when strictly necessary, then web_fetch the pages you cite (needs approval)."
+                .to_string(),
         );
     }
 
+    lines.push(
+        "- env_info: {\"tool\": \"env_info\"} reports the OS, shell, working directory, sandbox and approval policies, git branch and node/python/cargo versions; call it instead of guessing the environment."
+            .to_string(),
+    );
+
     if let Some(mode) = approval_mode_hint {
         lines.push(format!(
             "Approval policy: {mode}. Destructive or ambiguous actions may require user approval."
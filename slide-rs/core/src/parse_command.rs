@@ -3,6 +3,8 @@ use serde::Serialize;
 use shlex::split as shlex_split;
 use shlex::try_join as shlex_try_join;
 
+use crate::shell_analysis::{analyze, Dialect};
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ParsedCommand {
     Read {
@@ -39,6 +41,39 @@ pub enum ParsedCommand {
     },
 }
 
+impl ParsedCommand {
+    /// One-line gist such as "Read notes.md" or "Search `todo` in src";
+    /// `None` for commands that do nothing.
+    pub fn summary(&self) -> Option<String> {
+        let with_tool = |verb: &str, tool: &Option<String>, targets: &Option<Vec<String>>| {
+            let mut text = verb.to_string();
+            if let Some(targets) = targets {
+                text.push_str(&format!(" {}", targets.join(" ")));
+            }
+            if let Some(tool) = tool {
+                text.push_str(&format!(" with {tool}"));
+            }
+            text
+        };
+        Some(match self {
+            ParsedCommand::Read { name, .. } => format!("Read {name}"),
+            ParsedCommand::ListFiles { path, .. } => {
+                format!("List files in {}", path.as_deref().unwrap_or("."))
+            }
+            ParsedCommand::Search { query, path, .. } => match (query, path) {
+                (Some(query), Some(path)) => format!("Search `{query}` in {path}"),
+                (Some(query), None) => format!("Search `{query}`"),
+                (None, _) => "Search files".to_string(),
+            },
+            ParsedCommand::Format { tool, targets, .. } => with_tool("Format", tool, targets),
+            ParsedCommand::Test { .. } => "Run tests".to_string(),
+            ParsedCommand::Lint { tool, targets, .. } => with_tool("Lint", tool, targets),
+            ParsedCommand::Noop { .. } => return None,
+            ParsedCommand::Unknown { cmd } => format!("Run `{cmd}`"),
+        })
+    }
+}
+
 fn shlex_join(tokens: &[String]) -> String {
     shlex_try_join(tokens.iter().map(|s| s.as_str()))
         .unwrap_or_else(|_| "<command included NUL byte>".to_string())
@@ -75,10 +110,21 @@ fn parse_command_impl(command: &[String]) -> Vec<ParsedCommand> {
 
     let joined = shlex_join(command);
 
-    // Check for `bash -lc "..."` pattern without using unstable slice patterns.
-    if command.len() == 3 && command[0] == "bash" && command[1] == "-lc" {
-        if let Some(inner_commands) = shlex_split(&command[2]) {
-            return parse_command_impl(&inner_commands);
+    // `bash -lc "..."` and the like: summarize each command of the script.
+    if let [shell, flag, script] = command {
+        if let (Some(dialect), "-lc" | "-c") = (Dialect::for_shell(shell), flag.as_str()) {
+            if let Ok(portions) = analyze(script, dialect) {
+                let parsed: Vec<ParsedCommand> = portions
+                    .iter()
+                    .filter(|portion| !portion.words.is_empty())
+                    .flat_map(|portion| parse_command_impl(&portion.words))
+                    .collect();
+                if !parsed.is_empty() {
+                    return parsed;
+                }
+            } else if let Some(inner_commands) = shlex_split(script) {
+                return parse_command_impl(&inner_commands);
+            }
         }
     }
 
@@ -245,6 +291,20 @@ pub fn summarize(cmd: &str) -> CommandKind {
 mod tests {
     use super::*;
 
+    #[test]
+    fn summaries_read_like_the_command() {
+        let script = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "rg todo src && cargo test && true".to_string(),
+        ];
+        let summaries: Vec<String> = parse_command(&script)
+            .iter()
+            .filter_map(ParsedCommand::summary)
+            .collect();
+        assert_eq!(summaries, vec!["Search `todo` in src", "Run tests"]);
+    }
+
     #[test]
     fn test_parse_cat_command() {
         let command = vec!["cat".to_string(), "file.txt".to_string()];
@@ -310,6 +370,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_shell_script_per_command() {
+        let command = vec![
+            "zsh".to_string(),
+            "-lc".to_string(),
+            "ls src && rg 'fn main' src | head -n 5".to_string(),
+        ];
+        let result = parse_command(&command);
+
+        assert_eq!(result.len(), 3);
+        assert!(
+            matches!(&result[0], ParsedCommand::ListFiles { path, .. } if path.as_deref() == Some("src"))
+        );
+        assert!(
+            matches!(&result[1], ParsedCommand::Search { query, .. } if query.as_deref() == Some("fn main"))
+        );
+        assert!(matches!(&result[2], ParsedCommand::Read { .. }));
+    }
+
     #[test]
     fn test_parse_test_command() {
         let command = vec!["cargo".to_string(), "test".to_string()];
@@ -1,17 +1,611 @@
-#[derive(Debug, Clone, Default)]
+//! `update_plan` tool: the checklist the agent keeps while working on a task.
+//! Every change is saved to `.slide/plans/<id>.json` so an interrupted run
+//! can be continued with `slide plan resume <id>`.
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    InProgress,
+    #[serde(alias = "done")]
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PlanItem {
     pub step: String,
-    pub status: String,
+    pub status: StepStatus,
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct PlanUpdate {
+/// Arguments of `update_plan`; each call replaces the whole plan.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdatePlanArgs {
+    #[serde(default)]
     pub explanation: Option<String>,
-    pub items: Vec<PlanItem>,
+    pub plan: Vec<PlanItem>,
+}
+
+impl UpdatePlanArgs {
+    pub fn completed_count(&self) -> usize {
+        self.plan
+            .iter()
+            .filter(|item| item.status == StepStatus::Completed)
+            .count()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.plan
+            .iter()
+            .all(|item| item.status == StepStatus::Completed)
+    }
+}
+
+/// A step that started or ended between two versions of the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepChange {
+    Begin {
+        index: usize,
+        step: String,
+    },
+    /// The step completed or failed
+    End {
+        index: usize,
+        step: String,
+        status: StepStatus,
+    },
+}
+
+/// Steps whose status moved to in progress, completed or failed from
+/// `before` to `after`. A step renamed at the same index counts as new.
+pub fn step_changes(before: Option<&UpdatePlanArgs>, after: &UpdatePlanArgs) -> Vec<StepChange> {
+    after
+        .plan
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let previous = before
+                .and_then(|p| p.plan.get(index))
+                .filter(|old| old.step == item.step)
+                .map_or(StepStatus::Pending, |old| old.status);
+            if previous == item.status {
+                return None;
+            }
+            let step = item.step.clone();
+            match item.status {
+                StepStatus::Pending => None,
+                StepStatus::InProgress => Some(StepChange::Begin { index, step }),
+                status => Some(StepChange::End {
+                    index,
+                    step,
+                    status,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Directory of saved plans, relative to the project
+pub const PLANS_DIR: &str = ".slide/plans";
+
+/// Characters of tool output kept per output in a saved step
+const MAX_STEP_OUTPUT_CHARS: usize = 4000;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
-pub fn update_plan(mut current: Vec<PlanItem>, explanation: Option<String>) -> PlanUpdate {
-    // Stub: echo back
-    PlanUpdate { explanation, items: current.drain(..).collect() }
+/// A step of a saved plan and what happened while it ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub step: String,
+    pub status: StepStatus,
+    /// Seconds since the epoch
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    /// Tool output produced while the step was in progress
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+/// Execution state of a plan, saved after every change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanRecord {
+    pub id: String,
+    /// User message of the turn that created the plan
+    pub task: String,
+    #[serde(default)]
+    pub explanation: Option<String>,
+    /// Seconds since the epoch
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub steps: Vec<StepRecord>,
 }
 
+impl PlanRecord {
+    fn new(task: &str, now: u64) -> Self {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            id: format!("{now}-{}", &suffix[..8]),
+            task: task.to_string(),
+            explanation: None,
+            created_at: now,
+            updated_at: now,
+            steps: Vec::new(),
+        }
+    }
+
+    /// The plan as the `update_plan` arguments that produced it.
+    pub fn args(&self) -> UpdatePlanArgs {
+        UpdatePlanArgs {
+            explanation: self.explanation.clone(),
+            plan: self
+                .steps
+                .iter()
+                .map(|s| PlanItem {
+                    step: s.step.clone(),
+                    status: s.status,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.iter().all(|s| s.status == StepStatus::Completed)
+    }
+
+    /// Step a resumed run continues from.
+    pub fn first_open_step(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|s| s.status != StepStatus::Completed)
+    }
+
+    /// Message asking the agent to carry on from the first step that is not
+    /// completed.
+    pub fn resume_prompt(&self) -> String {
+        let mut prompt = format!(
+            "Resume the interrupted plan for this task:\n{}\n\nPlan:\n",
+            self.task
+        );
+        for (i, step) in self.steps.iter().enumerate() {
+            let status = match step.status {
+                StepStatus::Pending => "pending",
+                StepStatus::InProgress => "interrupted",
+                StepStatus::Completed => "completed",
+                StepStatus::Failed => "failed",
+            };
+            prompt.push_str(&format!("{}. [{status}] {}\n", i + 1, step.step));
+        }
+        if let Some(i) = self.first_open_step() {
+            prompt.push_str(&format!(
+                "\nDo not redo completed steps. Continue from step {}: {}, and keep the plan up to date with update_plan.",
+                i + 1,
+                self.steps[i].step
+            ));
+        }
+        prompt
+    }
+
+    /// Take over the statuses of `args`; steps that start or end are stamped
+    /// with `now`. A step renamed at the same index starts over.
+    fn apply(&mut self, args: &UpdatePlanArgs, now: u64) {
+        let previous = std::mem::take(&mut self.steps);
+        self.steps = args
+            .plan
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let mut record = previous
+                    .get(i)
+                    .filter(|old| old.step == item.step)
+                    .cloned()
+                    .unwrap_or_else(|| StepRecord {
+                        step: item.step.clone(),
+                        status: StepStatus::Pending,
+                        started_at: None,
+                        finished_at: None,
+                        outputs: Vec::new(),
+                    });
+                if record.status != item.status {
+                    match item.status {
+                        StepStatus::Pending => {
+                            record.started_at = None;
+                            record.finished_at = None;
+                        }
+                        StepStatus::InProgress => {
+                            record.started_at = Some(now);
+                            record.finished_at = None;
+                        }
+                        StepStatus::Completed | StepStatus::Failed => {
+                            record.started_at.get_or_insert(now);
+                            record.finished_at = Some(now);
+                        }
+                    }
+                    record.status = item.status;
+                }
+                record
+            })
+            .collect();
+        self.explanation = args.explanation.clone();
+        self.updated_at = now;
+    }
+
+    fn same_steps(&self, args: &UpdatePlanArgs) -> bool {
+        self.steps.len() == args.plan.len()
+            && self
+                .steps
+                .iter()
+                .zip(&args.plan)
+                .all(|(record, item)| record.step == item.step)
+    }
+}
+
+/// Saved plans of the project.
+#[derive(Debug, Clone)]
+pub struct PlanStore {
+    dir: PathBuf,
+}
+
+impl Default for PlanStore {
+    fn default() -> Self {
+        Self::new(PathBuf::from(PLANS_DIR))
+    }
+}
+
+impl PlanStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    pub fn save(&self, record: &PlanRecord) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.path(&record.id);
+        std::fs::write(&path, serde_json::to_string_pretty(record)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn load(&self, id: &str) -> anyhow::Result<PlanRecord> {
+        let path = self.path(id);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("no saved plan {id} in {}", self.dir.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("invalid plan file {}", path.display()))
+    }
+
+    /// Saved plans, most recently updated first. Unreadable files are skipped.
+    pub fn list(&self) -> Vec<PlanRecord> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut plans: Vec<PlanRecord> = entries
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        plans.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
+        plans
+    }
+}
+
+/// Latest plan of the session.
+#[derive(Debug, Default)]
+pub struct PlanManager {
+    current: Option<UpdatePlanArgs>,
+    /// Execution state of the current plan
+    record: Option<PlanRecord>,
+    /// Message of the running turn, recorded with a plan it creates
+    task: String,
+    /// Where [`PlanManager::save`] writes; `None` keeps plans in memory
+    store: Option<PlanStore>,
+}
+
+impl PlanManager {
+    pub fn with_store(store: PlanStore) -> Self {
+        Self {
+            store: Some(store),
+            ..Self::default()
+        }
+    }
+
+    pub fn current(&self) -> Option<&UpdatePlanArgs> {
+        self.current.as_ref()
+    }
+
+    pub fn record(&self) -> Option<&PlanRecord> {
+        self.record.as_ref()
+    }
+
+    /// Remember the message of the turn that is starting.
+    pub fn start_turn(&mut self, task: &str) {
+        self.task = task.to_string();
+    }
+
+    /// Replace the plan. An update without explanation keeps the previous one.
+    /// Once a plan is finished, an update with other steps starts a new record.
+    pub fn update(&mut self, mut args: UpdatePlanArgs) -> UpdatePlanArgs {
+        if args.explanation.is_none() {
+            args.explanation = self.current.as_ref().and_then(|p| p.explanation.clone());
+        }
+        let now = now_secs();
+        let mut record = match self.record.take() {
+            Some(r) if !r.is_finished() || r.same_steps(&args) => r,
+            _ => PlanRecord::new(&self.task, now),
+        };
+        record.apply(&args, now);
+        self.record = Some(record);
+        self.current = Some(args.clone());
+        args
+    }
+
+    /// Mark the steps in progress as failed, e.g. after a tool error.
+    /// Returns the updated plan when a step changed.
+    pub fn fail_in_progress(&mut self) -> Option<UpdatePlanArgs> {
+        let plan = self.current.as_mut()?;
+        let mut changed = false;
+        for item in &mut plan.plan {
+            if item.status == StepStatus::InProgress {
+                item.status = StepStatus::Failed;
+                changed = true;
+            }
+        }
+        if changed {
+            if let Some(record) = &mut self.record {
+                record.apply(plan, now_secs());
+            }
+        }
+        changed.then(|| plan.clone())
+    }
+
+    /// Keep tool output with the steps in progress. Returns whether any step
+    /// took it.
+    pub fn record_output(&mut self, output: &str) -> bool {
+        let Some(record) = &mut self.record else {
+            return false;
+        };
+        let output: String = output.chars().take(MAX_STEP_OUTPUT_CHARS).collect();
+        let mut recorded = false;
+        for step in &mut record.steps {
+            if step.status == StepStatus::InProgress {
+                step.outputs.push(output.clone());
+                recorded = true;
+            }
+        }
+        if recorded {
+            record.updated_at = now_secs();
+        }
+        recorded
+    }
+
+    /// Write the execution state of the current plan, if there is one.
+    pub fn save(&self) -> anyhow::Result<()> {
+        match (&self.store, &self.record) {
+            (Some(store), Some(record)) => store.save(record),
+            _ => Ok(()),
+        }
+    }
+
+    /// Continue a saved plan; returns it as the current plan.
+    pub fn restore(&mut self, record: PlanRecord) -> UpdatePlanArgs {
+        let args = record.args();
+        self.task = record.task.clone();
+        self.current = Some(args.clone());
+        self.record = Some(record);
+        args
+    }
+}
+
+/// Lets the user hold the agent between tool calls of a running plan.
+#[derive(Debug, Clone)]
+pub struct PlanGate {
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl Default for PlanGate {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(watch::Sender::new(false)),
+        }
+    }
+}
+
+impl PlanGate {
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Returns whether the gate was open.
+    pub fn pause(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| !std::mem::replace(paused, true))
+    }
+
+    /// Returns whether the gate was paused.
+    pub fn resume(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| std::mem::replace(paused, false))
+    }
+
+    /// Wait until the gate is open; returns at once when it is.
+    pub async fn wait_until_resumed(&self) {
+        let mut rx = self.paused.subscribe();
+        let _ = rx.wait_for(|paused| !*paused).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_tool_fails_the_running_step() -> anyhow::Result<()> {
+        let args: UpdatePlanArgs = serde_json::from_str(
+            r#"{"explanation":"Build the deck","plan":[
+                {"step":"Outline","status":"done"},
+                {"step":"Write slides","status":"in_progress"},
+                {"step":"Review","status":"pending"}]}"#,
+        )?;
+        let mut manager = PlanManager::default();
+        assert_eq!(manager.update(args).completed_count(), 1);
+
+        let failed = manager.fail_in_progress();
+        let statuses: Vec<StepStatus> = failed
+            .iter()
+            .flat_map(|p| p.plan.iter().map(|i| i.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StepStatus::Completed,
+                StepStatus::Failed,
+                StepStatus::Pending
+            ]
+        );
+        assert_eq!(manager.fail_in_progress(), None);
+        let before = manager.current().cloned();
+
+        // The explanation carries over to updates without one
+        let next = manager.update(UpdatePlanArgs {
+            explanation: None,
+            plan: Vec::new(),
+        });
+        assert_eq!(next.explanation.as_deref(), Some("Build the deck"));
+        assert!(next.is_finished());
+        assert_eq!(step_changes(before.as_ref(), &next), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn step_changes_report_started_and_ended_steps() {
+        let item = |step: &str, status| PlanItem {
+            step: step.to_string(),
+            status,
+        };
+        let before = UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                item("Outline", StepStatus::InProgress),
+                item("Write slides", StepStatus::Pending),
+                item("Review", StepStatus::Pending),
+            ],
+        };
+        let after = UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                item("Outline", StepStatus::Completed),
+                item("Write slides", StepStatus::InProgress),
+                item("Export", StepStatus::Pending),
+            ],
+        };
+        assert_eq!(
+            step_changes(Some(&before), &after),
+            vec![
+                StepChange::End {
+                    index: 0,
+                    step: "Outline".to_string(),
+                    status: StepStatus::Completed,
+                },
+                StepChange::Begin {
+                    index: 1,
+                    step: "Write slides".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            step_changes(None, &before),
+            vec![StepChange::Begin {
+                index: 0,
+                step: "Outline".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn saved_plan_resumes_at_the_first_open_step() -> anyhow::Result<()> {
+        let item = |step: &str, status| PlanItem {
+            step: step.to_string(),
+            status,
+        };
+        let dir = std::env::temp_dir().join(format!("slide-plans-{}", std::process::id()));
+        let store = PlanStore::new(dir.clone());
+        let mut manager = PlanManager::with_store(store.clone());
+        manager.start_turn("Make a deck about Rust");
+        manager.update(UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                item("Outline", StepStatus::InProgress),
+                item("Write slides", StepStatus::Pending),
+            ],
+        });
+        assert!(manager.record_output("outline.md written"));
+        manager.update(UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                item("Outline", StepStatus::Completed),
+                item("Write slides", StepStatus::InProgress),
+            ],
+        });
+
+        manager.save()?;
+        let record = manager.record().cloned().context("no plan record")?;
+        let loaded = store.load(&record.id)?;
+        assert_eq!(loaded, record);
+        assert_eq!(store.list().len(), 1);
+        std::fs::remove_dir_all(dir)?;
+
+        assert_eq!(loaded.task, "Make a deck about Rust");
+        assert_eq!(loaded.steps[0].outputs, vec!["outline.md written"]);
+        assert!(loaded.steps[0].finished_at.is_some());
+        assert_eq!(loaded.steps[1].finished_at, None);
+        assert_eq!(loaded.first_open_step(), Some(1));
+        assert!(loaded
+            .resume_prompt()
+            .contains("Continue from step 2: Write slides"));
+
+        let mut resumed = PlanManager::default();
+        assert_eq!(
+            resumed.restore(loaded).plan[1].status,
+            StepStatus::InProgress
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn paused_gate_holds_until_resumed() {
+        let gate = PlanGate::default();
+        gate.wait_until_resumed().await;
+        assert!(gate.pause());
+        assert!(!gate.pause());
+
+        let waiting = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait_until_resumed().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        assert!(gate.resume());
+        assert!(!gate.resume());
+        assert!(waiting.await.is_ok());
+    }
+}
@@ -1,71 +1,166 @@
-use crate::config::Config;
-use std::fs;
+//! Project instruction files (`SLIDE.md`, `AGENTS.md`) merged into the
+//! agent's instructions.
+//!
+//! Order, earliest first, so that later text takes precedence:
+//! 1. the built-in instructions (tools, approval policy);
+//! 2. `~/.slide/AGENTS.md`, the user's instructions for every project;
+//! 3. the files of each directory from the repository root (the nearest
+//!    ancestor of the working directory holding `.git`, or the working
+//!    directory itself outside a repository) down to the working
+//!    directory, `SLIDE.md` before `AGENTS.md` within one directory.
+//!
+//! Together the files are capped at [`PROJECT_DOC_MAX_BYTES`]; files past
+//! the cap are cut or left out.
 use std::path::Path;
-use tracing::error;
+use std::path::PathBuf;
+
+/// File names read in every directory, in merge order.
+pub const PROJECT_DOC_FILENAMES: &[&str] = &["SLIDE.md", "AGENTS.md"];
+
+/// Combined size of all instruction files sent to the model.
+pub const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024;
 
 const PROJECT_DOC_SEPARATOR: &str = "\n\n---\n\n";
 
-pub fn generate_overview_doc() -> String {
-    "Core overview (stub)".into()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectDoc {
+    pub path: PathBuf,
+    pub content: String,
+    /// Cut to fit [`PROJECT_DOC_MAX_BYTES`]
+    pub truncated: bool,
 }
 
-/// Get user instructions by combining project documentation with existing instructions
-pub async fn get_user_instructions(config: &Config) -> Option<String> {
-    match read_project_docs(config).await {
-        Ok(Some(project_doc)) => match &config.user_instructions {
-            Some(original_instructions) => Some(format!(
-                "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
-            )),
-            None => Some(project_doc),
-        },
-        Ok(None) => config.user_instructions.clone(),
-        Err(e) => {
-            error!("error trying to find project doc: {e:#}");
-            config.user_instructions.clone()
-        }
-    }
+/// Instruction files that apply in `cwd`, in merge order.
+pub fn discover_project_docs(cwd: &Path) -> Vec<ProjectDoc> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    discover_project_docs_with_home(cwd, home.as_deref())
 }
 
-/// Read project documentation from AGENTS.md files
-async fn read_project_docs(config: &Config) -> Result<Option<String>, std::io::Error> {
-    let mut docs = Vec::new();
-    
-    // Check for AGENTS.md in the current directory
-    let agents_path = config.cwd.join("AGENTS.md");
-    if agents_path.exists() {
-        if let Ok(content) = fs::read_to_string(&agents_path) {
-            let trimmed = content.trim();
-            if !trimmed.is_empty() {
-                let truncated = if trimmed.len() > config.project_doc_max_bytes {
-                    &trimmed[..config.project_doc_max_bytes]
-                } else {
-                    trimmed
-                };
-                docs.push(truncated.to_string());
-            }
-        }
+/// [`discover_project_docs`] with the home directory given explicitly.
+pub fn discover_project_docs_with_home(cwd: &Path, home: Option<&Path>) -> Vec<ProjectDoc> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(home) = home {
+        candidates.push(home.join(".slide").join("AGENTS.md"));
+    }
+    let root = cwd
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(cwd);
+    let mut dirs: Vec<&Path> = cwd
+        .ancestors()
+        .take_while(|dir| dir.starts_with(root))
+        .collect();
+    dirs.reverse();
+    for dir in dirs {
+        candidates.extend(PROJECT_DOC_FILENAMES.iter().map(|name| dir.join(name)));
     }
 
-    // Check for CLAUDE.md in the current directory
-    let claude_path = config.cwd.join("CLAUDE.md");
-    if claude_path.exists() {
-        if let Ok(content) = fs::read_to_string(&claude_path) {
-            let trimmed = content.trim();
-            if !trimmed.is_empty() {
-                let truncated = if trimmed.len() > config.project_doc_max_bytes {
-                    &trimmed[..config.project_doc_max_bytes]
-                } else {
-                    trimmed
-                };
-                docs.push(truncated.to_string());
-            }
+    let mut docs = Vec::new();
+    let mut remaining = PROJECT_DOC_MAX_BYTES;
+    for path in candidates {
+        if remaining == 0 {
+            break;
+        }
+        if !path.is_file() || docs.iter().any(|doc: &ProjectDoc| doc.path == path) {
+            continue;
         }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let content = content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let cut = floor_char_boundary(content, remaining);
+        remaining -= cut;
+        docs.push(ProjectDoc {
+            path,
+            content: content[..cut].to_string(),
+            truncated: cut < content.len(),
+        });
     }
+    docs
+}
 
+/// `base` followed by the instruction files, each headed by its path.
+pub fn compose_instructions(base: &str, docs: &[ProjectDoc]) -> String {
     if docs.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(docs.join(PROJECT_DOC_SEPARATOR)))
+        return base.to_string();
     }
+    let files: Vec<String> = docs
+        .iter()
+        .map(|doc| {
+            format!(
+                "Instructions from {}:\n\n{}",
+                doc.path.display(),
+                doc.content
+            )
+        })
+        .collect();
+    format!(
+        "{base}\n\nProject instructions follow; when they disagree, later ones take precedence.{PROJECT_DOC_SEPARATOR}{}",
+        files.join(PROJECT_DOC_SEPARATOR)
+    )
 }
 
+fn floor_char_boundary(s: &str, max: usize) -> usize {
+    if s.len() <= max {
+        return s.len();
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn files_are_merged_from_home_and_repo_root_down() {
+        let tmp = tempdir().expect("create TempDir");
+        let home = tmp.path().join("home");
+        let repo = tmp.path().join("repo");
+        let deck = repo.join("slides").join("talk");
+        fs::create_dir_all(home.join(".slide")).unwrap();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(&deck).unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "outside the repo").unwrap();
+        fs::write(home.join(".slide").join("AGENTS.md"), "be brief").unwrap();
+        fs::write(repo.join("AGENTS.md"), "repo agents").unwrap();
+        fs::write(repo.join("SLIDE.md"), "repo slide").unwrap();
+        fs::write(deck.join("AGENTS.md"), "  \n").unwrap();
+        fs::write(deck.join("SLIDE.md"), "deck slide\n").unwrap();
+
+        let docs = discover_project_docs_with_home(&deck, Some(&home));
+        let contents: Vec<&str> = docs.iter().map(|d| d.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["be brief", "repo slide", "repo agents", "deck slide"]
+        );
+
+        let composed = compose_instructions("BASE", &docs);
+        assert!(composed.starts_with("BASE\n\n"));
+        let at = |text: &str| composed.find(text).unwrap();
+        assert!(at("be brief") < at("repo slide") && at("repo agents") < at("deck slide"));
+        assert_eq!(compose_instructions("BASE", &[]), "BASE");
+    }
+
+    #[test]
+    fn outside_a_repo_only_the_working_directory_is_read() {
+        let tmp = tempdir().expect("create TempDir");
+        let cwd = tmp.path().join("work");
+        fs::create_dir_all(&cwd).unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "parent").unwrap();
+        fs::write(cwd.join("AGENTS.md"), "é".repeat(PROJECT_DOC_MAX_BYTES)).unwrap();
+
+        let docs = discover_project_docs_with_home(&cwd, None);
+        assert_eq!(docs.len(), 1);
+        assert!(docs[0].truncated);
+        assert_eq!(docs[0].content.len(), PROJECT_DOC_MAX_BYTES);
+    }
+}
@@ -4,7 +4,7 @@ pub use protocol::*;
 
 // Export our enhanced types for compatibility
 pub use crate::approval_manager::{AskForApproval as CoreAskForApproval, ApprovalManager, ApprovalRequest, ApprovalResponse};
-pub use crate::seatbelt::SandboxPolicy as CoreSandboxPolicy;
+pub use protocol::config_types::SandboxPolicy as CoreSandboxPolicy;
 pub use crate::exec_sandboxed::{SandboxedExecutor, ExecParams as CoreExecParams, ExecResult as CoreExecResult};
 
 // Re-export exec event types for unified access
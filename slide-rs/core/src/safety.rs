@@ -1,5 +1,5 @@
 use crate::approval_manager::ApprovalManager;
-use crate::seatbelt::SandboxPolicy;
+use protocol::config_types::SandboxPolicy;
 use slide_common::ApprovalMode;
 use std::collections::HashSet;
 use std::path::Path;
@@ -29,9 +29,8 @@ pub fn assess_patch_safety(
     }
 
     match policy {
-        ApprovalMode::FullAuto => SafetyCheck::AutoApprove,
-        ApprovalMode::AutoEdit => SafetyCheck::AskUser,
-        ApprovalMode::Suggest => SafetyCheck::AskUser,
+        ApprovalMode::Never => SafetyCheck::AutoApprove,
+        _ => SafetyCheck::AskUser,
     }
 }
 
@@ -56,7 +55,7 @@ pub fn assess_command_safety(
     // Basic command safety
     if is_known_safe_command(command) {
         match approval_policy {
-            ApprovalMode::FullAuto => SafetyCheck::AutoApprove,
+            ApprovalMode::Never => SafetyCheck::AutoApprove,
             _ => SafetyCheck::AskUser,
         }
     } else {
@@ -1,170 +1,280 @@
-use serde::{Deserialize, Serialize};
+use protocol::config_types::{SandboxPolicy, WritableRoot};
 use std::collections::HashSet;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl");
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum SandboxPolicy {
-    /// No restrictions whatsoever. Use with caution.
-    #[serde(rename = "danger-full-access")]
-    DangerFullAccess,
-    /// Read-only access to the entire file-system.
-    #[serde(rename = "read-only")]
-    ReadOnly,
-    /// Same as `ReadOnly` but additionally grants write access to the current
-    /// working directory ("workspace").
-    #[serde(rename = "workspace-write")]
-    WorkspaceWrite {
-        /// Additional folders (beyond cwd and possibly TMPDIR) that should be
-        /// writable from within the sandbox.
-        #[serde(default, skip_serializing_if = "Vec::is_empty")]
-        writable_roots: Vec<PathBuf>,
-        /// When set to `true`, outbound network access is allowed. `false` by
-        /// default.
-        #[serde(default)]
-        network_access: bool,
-        /// When set to `true`, will NOT include the per-user `TMPDIR`
-        /// environment variable among the default writable roots. Defaults to
-        /// `false`.
-        #[serde(default)]
-        exclude_tmpdir_env_var: bool,
-        /// When set to `true`, will NOT include the `/tmp` among the default
-        /// writable roots on UNIX. Defaults to `false`.
-        #[serde(default)]
-        exclude_system_tmp: bool,
-    },
+/// Credentials under the home directory no sandboxed command may read, even
+/// where the policy lets it read the rest of the disk.
+const SENSITIVE_HOME_DIRS: &[&str] = &[".ssh", ".aws"];
+
+/// Inputs of [`build_seatbelt_policy`] besides the policy itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatbeltOptions {
+    /// Directory the command runs in; writable under `WorkspaceWrite`
+    pub cwd: PathBuf,
+    /// Writable under `WorkspaceWrite` in addition to the policy's own roots,
+    /// e.g. `sandbox.writable_roots` from the config or the slides assets dir
+    pub extra_writable_roots: Vec<PathBuf>,
+    /// Neither readable nor writable under any restricted policy
+    pub deny_read: Vec<PathBuf>,
 }
 
-impl Default for SandboxPolicy {
-    fn default() -> Self {
-        SandboxPolicy::WorkspaceWrite {
-            writable_roots: Vec::new(),
-            network_access: false,
-            exclude_tmpdir_env_var: false,
-            exclude_system_tmp: false,
+impl SeatbeltOptions {
+    /// Options for commands run in `cwd`, denying the usual credential dirs.
+    pub fn new(cwd: &Path) -> Self {
+        let home = std::env::var_os("HOME").filter(|home| !home.is_empty());
+        Self {
+            cwd: cwd.to_path_buf(),
+            extra_writable_roots: Vec::new(),
+            deny_read: home
+                .map(|home| {
+                    SENSITIVE_HOME_DIRS
+                        .iter()
+                        .map(|dir| Path::new(&home).join(dir))
+                        .collect()
+                })
+                .unwrap_or_default(),
         }
     }
+
+    /// Also let commands write to `roots`; relative roots resolve against `cwd`.
+    pub fn with_writable_roots(mut self, roots: &[PathBuf]) -> Self {
+        self.extra_writable_roots.extend_from_slice(roots);
+        self
+    }
 }
 
-impl SandboxPolicy {
-    /// Get writable directories for this policy given a working directory
-    pub fn get_writable_roots_with_cwd(&self, cwd: &std::path::Path) -> Vec<PathBuf> {
-        match self {
-            SandboxPolicy::DangerFullAccess => {
-                // Full access, no restrictions
-                vec![PathBuf::from("/")]
-            }
-            SandboxPolicy::ReadOnly => {
-                // No writable roots
-                Vec::new()
-            }
-            SandboxPolicy::WorkspaceWrite {
-                writable_roots,
-                exclude_tmpdir_env_var,
-                exclude_system_tmp,
-                ..
-            } => {
-                let mut roots = vec![cwd.to_path_buf()];
-
-                // Add custom writable roots
-                roots.extend(writable_roots.clone());
-
-                // Add temp directories unless excluded
-                if !exclude_tmpdir_env_var {
-                    if let Ok(tmpdir) = std::env::var("TMPDIR") {
-                        roots.push(PathBuf::from(tmpdir));
-                    }
-                }
-
-                if !exclude_system_tmp {
-                    #[cfg(unix)]
-                    roots.push(PathBuf::from("/tmp"));
-                    #[cfg(windows)]
-                    if let Ok(temp) = std::env::var("TEMP") {
-                        roots.push(PathBuf::from(temp));
-                    }
-                }
-
-                roots
-            }
+/// A seatbelt profile whose paths are passed as `-D` parameters rather than
+/// spliced into the SBPL, so no path needs quoting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatbeltPolicy {
+    pub profile: String,
+    pub params: Vec<(String, PathBuf)>,
+}
+
+impl SeatbeltPolicy {
+    /// Arguments of `sandbox-exec` before the command to run.
+    pub fn sandbox_exec_args(&self) -> Vec<OsString> {
+        let mut args = vec![OsString::from("-p"), OsString::from(&self.profile)];
+        for (name, path) in &self.params {
+            let mut arg = OsString::from(format!("-D{name}="));
+            arg.push(path);
+            args.push(arg);
         }
+        args
     }
 
-    /// Check if network access is allowed
-    pub fn allows_network(&self) -> bool {
-        match self {
-            SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
-            SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
-        }
+    fn param(&mut self, name: String, path: PathBuf) -> String {
+        let reference = format!("(param \"{name}\")");
+        self.params.push((name, path));
+        reference
     }
 }
 
-pub fn build_seatbelt_policy(policy: SandboxPolicy, workspace_root: Option<&Path>) -> String {
-    match policy {
-        SandboxPolicy::DangerFullAccess => {
-            // No restrictions
-            "(version 1)\n(allow default)".to_string()
+pub fn build_seatbelt_policy(policy: &SandboxPolicy, options: &SeatbeltOptions) -> SeatbeltPolicy {
+    let mut out = SeatbeltPolicy {
+        profile: String::new(),
+        params: Vec::new(),
+    };
+    if matches!(policy, SandboxPolicy::DangerFullAccess) {
+        out.profile = "(version 1)\n(allow default)\n".to_string();
+        return out;
+    }
+
+    let mut profile = String::from(MACOS_SEATBELT_BASE_POLICY);
+    profile.push_str(
+        "\n; reads are allowed everywhere but the denied paths below\n(allow file-read*)\n(allow process-info*)\n(allow system-info)\n(allow mach-lookup)\n",
+    );
+
+    let mut writable = policy.get_writable_roots_with_cwd(&options.cwd);
+    if matches!(policy, SandboxPolicy::WorkspaceWrite { .. }) {
+        writable.extend(
+            options
+                .extra_writable_roots
+                .iter()
+                .map(|root| WritableRoot {
+                    root: options.cwd.join(root),
+                    read_only_subpaths: Vec::new(),
+                }),
+        );
+    }
+    let mut seen = HashSet::new();
+    let mut rules = Vec::new();
+    for root in writable {
+        let canonical = canonicalize(&root.root);
+        if !seen.insert(canonical.clone()) {
+            continue;
+        }
+        let index = seen.len() - 1;
+        let root_param = out.param(format!("WRITABLE_ROOT_{index}"), canonical);
+        if root.read_only_subpaths.is_empty() {
+            rules.push(format!("(subpath {root_param})"));
+            continue;
         }
-        SandboxPolicy::ReadOnly => {
-            let mut policy = String::from(MACOS_SEATBELT_BASE_POLICY);
-            policy.push_str(
-                "\n; allow read-only file operations\n(allow file-read*)\n(allow process-info*)\n(allow system-info)\n(allow mach-lookup)\n",
+        let mut rule = format!("(require-all (subpath {root_param})");
+        for (i, subpath) in root.read_only_subpaths.iter().enumerate() {
+            let ro_param = out.param(
+                format!("WRITABLE_ROOT_{index}_RO_{i}"),
+                canonicalize(subpath),
             );
-            policy
+            rule.push_str(&format!(" (require-not (subpath {ro_param}))"));
+        }
+        rule.push(')');
+        rules.push(rule);
+    }
+    if !rules.is_empty() {
+        profile.push_str("\n; writable roots\n(allow file-write*\n");
+        for rule in rules {
+            profile.push_str(&format!("  {rule}\n"));
         }
+        profile.push_str(")\n");
+    }
+
+    if policy.has_full_network_access() {
+        profile.push_str(
+            "\n; network\n(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)\n",
+        );
+    }
+
+    if !options.deny_read.is_empty() {
+        // Later rules win, so this overrides the reads and writes allowed above
+        profile.push_str("\n; sensitive paths\n(deny file-read* file-write*\n");
+        for (i, path) in options.deny_read.iter().enumerate() {
+            let param = out.param(format!("DENY_READ_{i}"), canonicalize(path));
+            profile.push_str(&format!("  (subpath {param})\n"));
+        }
+        profile.push_str(")\n");
+    }
+
+    out.profile = profile;
+    out
+}
+
+/// Seatbelt matches resolved paths (`/tmp` is `/private/tmp` on macOS);
+/// paths that do not exist are kept as given.
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> SeatbeltOptions {
+        SeatbeltOptions {
+            cwd: PathBuf::from("/nonexistent/work"),
+            extra_writable_roots: vec![PathBuf::from("/nonexistent/assets")],
+            deny_read: vec![PathBuf::from("/nonexistent/home/.ssh")],
+        }
+    }
+
+    fn workspace_write(network_access: bool) -> SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
-            writable_roots,
+            writable_roots: vec![PathBuf::from("/nonexistent/data")],
             network_access,
-            exclude_tmpdir_env_var,
-            exclude_system_tmp,
-        } => {
-            let mut policy = String::from(MACOS_SEATBELT_BASE_POLICY);
-            policy.push_str(
-                "\n; allow read/write operations within workspace roots\n(allow file-read*)\n(allow process-info*)\n(allow system-info)\n(allow mach-lookup)\n",
-            );
-
-            let mut roots: Vec<PathBuf> = Vec::new();
-            if let Some(root) = workspace_root {
-                roots.push(root.to_path_buf());
-            }
-            roots.extend(writable_roots.clone());
-
-            if !exclude_system_tmp {
-                let tmp = PathBuf::from("/tmp");
-                if tmp.is_dir() {
-                    roots.push(tmp);
-                }
-            }
-
-            if !exclude_tmpdir_env_var {
-                if let Some(tmpdir) = std::env::var_os("TMPDIR") {
-                    if !tmpdir.is_empty() {
-                        roots.push(PathBuf::from(tmpdir));
-                    }
-                }
-            }
-
-            let mut seen = HashSet::new();
-            for root in roots {
-                let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
-                if seen.insert(canonical_root.clone()) {
-                    policy.push_str(&format!(
-                        "(allow file-write* (subpath \"{}\"))\n",
-                        canonical_root.display()
-                    ));
-                }
-            }
-
-            if network_access {
-                policy.push_str(
-                    "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)\n",
-                );
-            }
-
-            policy
+            exclude_tmpdir_env_var: true,
+            exclude_system_tmp: true,
         }
     }
+
+    #[test]
+    fn workspace_write_profile_lists_every_root_as_a_param() {
+        let policy = build_seatbelt_policy(&workspace_write(false), &options());
+        assert_eq!(
+            policy.params,
+            [
+                (
+                    "WRITABLE_ROOT_0".to_string(),
+                    PathBuf::from("/nonexistent/work")
+                ),
+                (
+                    "WRITABLE_ROOT_1".to_string(),
+                    PathBuf::from("/nonexistent/data")
+                ),
+                (
+                    "WRITABLE_ROOT_2".to_string(),
+                    PathBuf::from("/nonexistent/assets")
+                ),
+                (
+                    "DENY_READ_0".to_string(),
+                    PathBuf::from("/nonexistent/home/.ssh")
+                ),
+            ]
+        );
+        assert!(policy.profile.starts_with(MACOS_SEATBELT_BASE_POLICY));
+        assert!(policy.profile.contains(
+            "(allow file-write*\n  (subpath (param \"WRITABLE_ROOT_0\"))\n  (subpath (param \"WRITABLE_ROOT_1\"))\n  (subpath (param \"WRITABLE_ROOT_2\"))\n)\n"
+        ));
+        assert!(policy
+            .profile
+            .ends_with("(deny file-read* file-write*\n  (subpath (param \"DENY_READ_0\"))\n)\n"));
+        assert!(!policy.profile.contains("/nonexistent"));
+        assert!(!policy.profile.contains("network-outbound"));
+
+        let networked = build_seatbelt_policy(&workspace_write(true), &options());
+        assert!(networked.profile.contains("(allow network-outbound)"));
+
+        assert_eq!(
+            policy.sandbox_exec_args()[2..],
+            [
+                OsString::from("-DWRITABLE_ROOT_0=/nonexistent/work"),
+                OsString::from("-DWRITABLE_ROOT_1=/nonexistent/data"),
+                OsString::from("-DWRITABLE_ROOT_2=/nonexistent/assets"),
+                OsString::from("-DDENY_READ_0=/nonexistent/home/.ssh"),
+            ]
+        );
+    }
+
+    #[test]
+    fn git_dirs_stay_read_only_and_duplicates_collapse() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-seatbelt-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(".git"))?;
+        let dir = dir.canonicalize()?;
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![dir.clone()],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_system_tmp: true,
+        };
+        let options = SeatbeltOptions {
+            cwd: dir.clone(),
+            extra_writable_roots: Vec::new(),
+            deny_read: Vec::new(),
+        };
+        let built = build_seatbelt_policy(&policy, &options);
+        assert_eq!(
+            built.params,
+            [
+                ("WRITABLE_ROOT_0".to_string(), dir.clone()),
+                ("WRITABLE_ROOT_0_RO_0".to_string(), dir.join(".git")),
+            ]
+        );
+        assert!(built.profile.contains(
+            "  (require-all (subpath (param \"WRITABLE_ROOT_0\")) (require-not (subpath (param \"WRITABLE_ROOT_0_RO_0\"))))\n"
+        ));
+        assert!(!built.profile.contains("(deny file-read* file-write*"));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_and_full_access_profiles() {
+        let read_only = build_seatbelt_policy(&SandboxPolicy::ReadOnly, &options());
+        assert!(!read_only.profile.contains("(allow file-write*"));
+        assert!(read_only.profile.contains("(allow file-read*)"));
+        assert_eq!(
+            read_only.params,
+            [(
+                "DENY_READ_0".to_string(),
+                PathBuf::from("/nonexistent/home/.ssh")
+            )]
+        );
+
+        let full = build_seatbelt_policy(&SandboxPolicy::DangerFullAccess, &options());
+        assert_eq!(full.profile, "(version 1)\n(allow default)\n");
+        assert!(full.params.is_empty());
+    }
 }
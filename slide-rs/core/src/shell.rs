@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use serde::Serialize;
 use shlex;
+use std::path::Path;
 use std::path::PathBuf;
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -9,6 +10,17 @@ pub struct ZshShell {
     zshrc_path: String,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BashShell {
+    shell_path: String,
+    bashrc_path: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct FishShell {
+    shell_path: String,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct PowerShellConfig {
     exe: String, // Executable name or path, e.g. "pwsh" or "powershell.exe".
@@ -18,6 +30,8 @@ pub struct PowerShellConfig {
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Shell {
     Zsh(ZshShell),
+    Bash(BashShell),
+    Fish(FishShell),
     PowerShell(PowerShellConfig),
     Unknown,
 }
@@ -43,6 +57,27 @@ impl Shell {
                 }
                 Some(result)
             }
+            Shell::Bash(bash) => {
+                // A login bash reads .bash_profile, which often skips .bashrc
+                // where tools like nvm install themselves.
+                let joined = strip_bash_lc(&command)
+                    .or_else(|| shlex::try_join(command.iter().map(|s| s.as_str())).ok())?;
+                let script = if Path::new(&bash.bashrc_path).exists() {
+                    format!("source {} && ({joined})", bash.bashrc_path)
+                } else {
+                    joined
+                };
+                Some(vec![bash.shell_path.clone(), "-lc".to_string(), script])
+            }
+            Shell::Fish(fish) => {
+                // fish reads its config on every start, but does not speak bash:
+                // scripts written for bash keep running under bash.
+                if strip_bash_lc(&command).is_some() {
+                    return None;
+                }
+                let joined = shlex::try_join(command.iter().map(|s| s.as_str())).ok()?;
+                Some(vec![fish.shell_path.clone(), "-lc".to_string(), joined])
+            }
             Shell::PowerShell(ps) => {
                 // If model generated a bash command, prefer a detected bash fallback
                 if let Some(script) = strip_bash_lc(&command) {
@@ -97,6 +132,12 @@ impl Shell {
             Shell::Zsh(zsh) => std::path::Path::new(&zsh.shell_path)
                 .file_name()
                 .map(|s| s.to_string_lossy().to_string()),
+            Shell::Bash(bash) => Path::new(&bash.shell_path)
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string()),
+            Shell::Fish(fish) => Path::new(&fish.shell_path)
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string()),
             Shell::PowerShell(ps) => Some(ps.exe.clone()),
             Shell::Unknown => None,
         }
@@ -122,6 +163,73 @@ impl Default for Shell {
     }
 }
 
+/// Detect the user's shell: `$SHELL` on unix, PowerShell on Windows.
+pub async fn default_user_shell() -> Shell {
+    #[cfg(windows)]
+    {
+        let has_pwsh = tokio::process::Command::new("pwsh")
+            .args(["-NoProfile", "-Command", "$PSVersionTable.PSVersion.Major"])
+            .output()
+            .await
+            .map(|out| out.status.success())
+            .unwrap_or(false);
+        Shell::PowerShell(PowerShellConfig {
+            exe: if has_pwsh {
+                "pwsh.exe"
+            } else {
+                "powershell.exe"
+            }
+            .to_string(),
+            bash_exe_fallback: find_in_path("bash.exe"),
+        })
+    }
+    #[cfg(not(windows))]
+    {
+        let home = std::env::var("HOME").unwrap_or_default();
+        match std::env::var("SHELL") {
+            Ok(shell_path) if !shell_path.is_empty() => {
+                shell_from_path(&shell_path, Path::new(&home))
+            }
+            _ => Shell::Unknown,
+        }
+    }
+}
+
+/// Map a shell executable to the shell it runs; rc files are looked up in
+/// `home`.
+pub fn shell_from_path(shell_path: &str, home: &Path) -> Shell {
+    let name = Path::new(shell_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match name.as_str() {
+        "zsh" => Shell::Zsh(ZshShell {
+            shell_path: shell_path.to_string(),
+            zshrc_path: home.join(".zshrc").to_string_lossy().to_string(),
+        }),
+        "bash" => Shell::Bash(BashShell {
+            shell_path: shell_path.to_string(),
+            bashrc_path: home.join(".bashrc").to_string_lossy().to_string(),
+        }),
+        "fish" => Shell::Fish(FishShell {
+            shell_path: shell_path.to_string(),
+        }),
+        "pwsh" | "powershell" => Shell::PowerShell(PowerShellConfig {
+            exe: shell_path.to_string(),
+            bash_exe_fallback: None,
+        }),
+        _ => Shell::Unknown,
+    }
+}
+
+#[cfg(windows)]
+fn find_in_path(exe: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(exe))
+        .find(|candidate| candidate.is_file())
+}
+
 fn strip_bash_lc(command: &[String]) -> Option<String> {
     if command.len() == 3 && command[0] == "bash" && command[1] == "-lc" {
         Some(command[2].clone())
@@ -163,6 +271,68 @@ mod tests {
         std::fs::remove_file("/tmp/test_zshrc").ok();
     }
 
+    #[test]
+    fn shells_are_detected_from_their_path() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            shell_from_path("/usr/local/bin/zsh", home)
+                .name()
+                .as_deref(),
+            Some("zsh")
+        );
+        assert_eq!(
+            shell_from_path("/bin/bash", home),
+            Shell::Bash(BashShell {
+                shell_path: "/bin/bash".to_string(),
+                bashrc_path: "/home/user/.bashrc".to_string(),
+            })
+        );
+        assert_eq!(
+            shell_from_path("/usr/bin/fish", home).name().as_deref(),
+            Some("fish")
+        );
+        assert!(matches!(
+            shell_from_path("/opt/microsoft/powershell/7/pwsh", home),
+            Shell::PowerShell(_)
+        ));
+        assert_eq!(shell_from_path("/bin/tcsh", home), Shell::Unknown);
+    }
+
+    #[test]
+    fn login_shells_wrap_commands() {
+        let bash = Shell::Bash(BashShell {
+            shell_path: "/bin/bash".to_string(),
+            bashrc_path: "/nonexistent/.bashrc".to_string(),
+        });
+        let command = vec!["npm".to_string(), "run".to_string(), "build".to_string()];
+        assert_eq!(
+            bash.format_default_shell_invocation(command.clone()),
+            Some(vec![
+                "/bin/bash".to_string(),
+                "-lc".to_string(),
+                "npm run build".to_string()
+            ])
+        );
+
+        let fish = Shell::Fish(FishShell {
+            shell_path: "/usr/bin/fish".to_string(),
+        });
+        assert_eq!(
+            fish.format_default_shell_invocation(command),
+            Some(vec![
+                "/usr/bin/fish".to_string(),
+                "-lc".to_string(),
+                "npm run build".to_string()
+            ])
+        );
+        let bash_script = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "ls && pwd".to_string(),
+        ];
+        assert_eq!(fish.format_default_shell_invocation(bash_script), None);
+    }
+
     #[test]
     fn test_bash_lc_detection() {
         let command = vec!["bash".to_string(), "-lc".to_string(), "ls".to_string()];
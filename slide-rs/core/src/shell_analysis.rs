@@ -0,0 +1,760 @@
+//! Splits a shell script into the simple commands it runs and flags every
+//! construct that can do more than the command's words say: substitutions,
+//! expansions, writing redirects, subshells, background jobs and
+//! environment-assignment prefixes. Understands sh/bash/zsh quoting and
+//! heredocs, and fish quoting.
+use std::fmt;
+use thiserror::Error;
+
+/// Quoting rules a script is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// sh, bash and zsh
+    Posix,
+    Fish,
+}
+
+impl Dialect {
+    /// Dialect of a shell program such as `/bin/zsh`; `None` for programs
+    /// that are not shells.
+    pub fn for_shell(program: &str) -> Option<Self> {
+        let name = std::path::Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        match name.as_ref() {
+            "bash" | "zsh" | "sh" | "dash" => Some(Dialect::Posix),
+            "fish" => Some(Dialect::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Something in a script that its command words alone do not show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Concern {
+    /// `$(...)`, backticks, `<(...)`, or `(...)` in fish
+    CommandSubstitution,
+    /// `$VAR`, `${...}`, `$((...))`
+    Expansion,
+    /// Output redirected to a file other than `/dev/null`
+    WritingRedirect(String),
+    Background,
+    /// `(...)`, including zsh glob qualifiers and bash extglobs
+    Subshell,
+    /// `NAME=value` before the command
+    Assignment(String),
+}
+
+impl fmt::Display for Concern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Concern::CommandSubstitution => write!(f, "runs a nested command substitution"),
+            Concern::Expansion => write!(f, "uses a shell expansion whose value is not known"),
+            Concern::WritingRedirect(target) => write!(f, "writes to {target} through a redirect"),
+            Concern::Background => write!(f, "runs a command in the background"),
+            Concern::Subshell => write!(f, "uses a subshell or parenthesized pattern"),
+            Concern::Assignment(assignment) => {
+                write!(f, "sets `{assignment}` for the command")
+            }
+        }
+    }
+}
+
+/// One simple command of a script: its words after quote removal and what
+/// else it does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Portion {
+    pub words: Vec<String>,
+    pub concerns: Vec<Concern>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    #[error("unterminated quote")]
+    UnterminatedQuote,
+    #[error("unterminated substitution")]
+    UnterminatedSubstitution,
+    #[error("heredoc `{0}` is not terminated")]
+    UnterminatedHeredoc(String),
+    #[error("`{0}` is missing a command")]
+    MissingCommand(String),
+    #[error("redirect `{0}` is missing its target")]
+    MissingRedirectTarget(String),
+    #[error("unmatched `)`")]
+    UnmatchedParen,
+    #[error("fish has no heredocs")]
+    FishHeredoc,
+}
+
+/// The simple commands of `script` in order. Control flow and function
+/// definitions come out as ordinary words (`if`, `for`, ...).
+pub fn analyze(script: &str, dialect: Dialect) -> Result<Vec<Portion>, ParseError> {
+    Lexer::new(script, dialect).run()
+}
+
+struct PendingHeredoc {
+    delimiter: String,
+    /// Unquoted delimiters leave expansions in the body active
+    expands: bool,
+    /// `<<-` strips leading tabs
+    strip_tabs: bool,
+    portion: usize,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    dialect: Dialect,
+    portions: Vec<Portion>,
+    current: Portion,
+    /// The current portion has a redirect, so it runs even without words
+    has_redirect: bool,
+    word: Option<String>,
+    /// The word used quotes or escapes
+    word_quoted: bool,
+    /// The word so far is an unquoted `NAME=`
+    word_assignment: bool,
+    /// The last operator (`|`, `&&`, `||`) still needs its command
+    pending_operator: Option<&'static str>,
+    heredocs: Vec<PendingHeredoc>,
+}
+
+impl Lexer {
+    fn new(script: &str, dialect: Dialect) -> Self {
+        Self {
+            chars: script.chars().collect(),
+            pos: 0,
+            dialect,
+            portions: Vec::new(),
+            current: Portion::default(),
+            has_redirect: false,
+            word: None,
+            word_quoted: false,
+            word_assignment: false,
+            pending_operator: None,
+            heredocs: Vec::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn run(mut self) -> Result<Vec<Portion>, ParseError> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                ' ' | '\t' => {
+                    self.finish_word();
+                    self.pos += 1;
+                }
+                '\n' => {
+                    self.finish_word();
+                    self.end_portion();
+                    self.pos += 1;
+                    self.read_heredocs()?;
+                }
+                '\\' if self.peek(1) == Some('\n') => self.pos += 2,
+                '#' if self.word.is_none() => {
+                    while self.peek(0).is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                ';' => {
+                    self.pos += 1;
+                    self.separator(";")?;
+                }
+                '&' => match self.peek(1) {
+                    Some('&') => {
+                        self.pos += 2;
+                        self.operator("&&")?;
+                    }
+                    Some('>') => self.redirect()?,
+                    Some('|') if self.dialect == Dialect::Fish => {
+                        self.pos += 2;
+                        self.operator("&|")?;
+                    }
+                    _ => {
+                        self.pos += 1;
+                        self.current.concerns.push(Concern::Background);
+                        self.separator("&")?;
+                    }
+                },
+                '|' => match self.peek(1) {
+                    Some('|') => {
+                        self.pos += 2;
+                        self.operator("||")?;
+                    }
+                    Some('&') if self.dialect == Dialect::Posix => {
+                        self.pos += 2;
+                        self.operator("|&")?;
+                    }
+                    _ => {
+                        self.pos += 1;
+                        self.operator("|")?;
+                    }
+                },
+                '<' | '>' => self.redirect()?,
+                '(' | ')' if self.dialect == Dialect::Posix => {
+                    self.finish_word();
+                    self.current.concerns.push(Concern::Subshell);
+                    self.pos += 1;
+                }
+                ')' => return Err(ParseError::UnmatchedParen),
+                _ => self.word_part()?,
+            }
+        }
+        self.finish_word();
+        self.end_portion();
+        if let Some(doc) = self.heredocs.first() {
+            return Err(ParseError::UnterminatedHeredoc(doc.delimiter.clone()));
+        }
+        if let Some(op) = self.pending_operator {
+            return Err(ParseError::MissingCommand(op.to_string()));
+        }
+        Ok(self.portions)
+    }
+
+    fn portion_is_empty(&self) -> bool {
+        self.current.words.is_empty() && self.current.concerns.is_empty() && !self.has_redirect
+    }
+
+    /// `;` or `&`: ends a command, which must exist
+    fn separator(&mut self, op: &'static str) -> Result<(), ParseError> {
+        self.finish_word();
+        if self.portion_is_empty() {
+            return Err(ParseError::MissingCommand(op.to_string()));
+        }
+        self.end_portion();
+        Ok(())
+    }
+
+    /// `|`, `&&`, `||`: joins two commands
+    fn operator(&mut self, op: &'static str) -> Result<(), ParseError> {
+        self.separator(op)?;
+        self.pending_operator = Some(op);
+        Ok(())
+    }
+
+    fn end_portion(&mut self) {
+        if self.portion_is_empty() {
+            return;
+        }
+        self.portions.push(std::mem::take(&mut self.current));
+        self.has_redirect = false;
+        self.pending_operator = None;
+    }
+
+    fn finish_word(&mut self) {
+        let Some(word) = self.word.take() else {
+            return;
+        };
+        let quoted = std::mem::take(&mut self.word_quoted);
+        let assignment = std::mem::take(&mut self.word_assignment);
+        if self.current.words.is_empty() {
+            if assignment {
+                self.current.concerns.push(Concern::Assignment(word));
+                return;
+            }
+            // Negation and fish's chaining keywords only prefix the command
+            let prefix = match self.dialect {
+                Dialect::Posix => word == "!",
+                Dialect::Fish => matches!(word.as_str(), "!" | "not" | "and" | "or"),
+            };
+            if prefix && !quoted {
+                return;
+            }
+        }
+        self.current.words.push(word);
+    }
+
+    fn push(&mut self, c: char) {
+        let word = self.word.get_or_insert_with(String::new);
+        if c == '='
+            && !self.word_quoted
+            && !self.word_assignment
+            && is_name(word)
+            && self.current.words.is_empty()
+        {
+            self.word_assignment = true;
+        }
+        word.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.word.get_or_insert_with(String::new).push_str(s);
+    }
+
+    /// Mark the word as quoted, creating it if this starts it (`""` is a word)
+    fn quoted(&mut self) {
+        self.word.get_or_insert_with(String::new);
+        self.word_quoted = true;
+    }
+
+    /// One piece of a word: a character, quoted string, escape or expansion.
+    fn word_part(&mut self) -> Result<(), ParseError> {
+        let Some(c) = self.peek(0) else {
+            return Ok(());
+        };
+        match (c, self.dialect) {
+            ('\'', _) => self.single_quoted(),
+            ('"', _) => self.double_quoted(),
+            ('\\', _) => {
+                self.quoted();
+                if let Some(next) = self.peek(1) {
+                    self.push(next);
+                }
+                self.pos += 2;
+                Ok(())
+            }
+            ('$', _) => self.dollar(false),
+            ('`', Dialect::Posix) => self.backticks(),
+            ('(', Dialect::Fish) => {
+                self.current.concerns.push(Concern::CommandSubstitution);
+                let raw = self.balanced('(', ')')?;
+                self.push_str(&raw);
+                Ok(())
+            }
+            _ => {
+                self.push(c);
+                self.pos += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn single_quoted(&mut self) -> Result<(), ParseError> {
+        self.quoted();
+        self.pos += 1;
+        loop {
+            let c = self.peek(0).ok_or(ParseError::UnterminatedQuote)?;
+            self.pos += 1;
+            match c {
+                '\'' => return Ok(()),
+                // fish lets single quotes escape themselves and backslashes
+                '\\' if self.dialect == Dialect::Fish
+                    && matches!(self.peek(0), Some('\'' | '\\')) =>
+                {
+                    if let Some(next) = self.peek(0) {
+                        self.push_str(&next.to_string());
+                    }
+                    self.pos += 1;
+                }
+                c => self.push_str(&c.to_string()),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self) -> Result<(), ParseError> {
+        self.quoted();
+        self.pos += 1;
+        loop {
+            let c = self.peek(0).ok_or(ParseError::UnterminatedQuote)?;
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                '\\' => {
+                    let escapable: &[char] = match self.dialect {
+                        Dialect::Posix => &['$', '`', '"', '\\', '\n'],
+                        Dialect::Fish => &['$', '"', '\\', '\n'],
+                    };
+                    match self.peek(1) {
+                        Some('\n') => {}
+                        Some(next) if escapable.contains(&next) => self.push_str(&next.to_string()),
+                        Some(next) => self.push_str(&format!("\\{next}")),
+                        None => return Err(ParseError::UnterminatedQuote),
+                    }
+                    self.pos += 2;
+                }
+                '$' => self.dollar(true)?,
+                '`' if self.dialect == Dialect::Posix => self.backticks()?,
+                c => {
+                    self.push_str(&c.to_string());
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// `$` and what follows it; a `$` that starts nothing stays literal.
+    fn dollar(&mut self, in_double_quotes: bool) -> Result<(), ParseError> {
+        let start = self.pos;
+        match (self.peek(1), self.dialect) {
+            (Some('('), Dialect::Posix) if self.peek(2) == Some('(') => {
+                self.pos += 1;
+                self.current.concerns.push(Concern::Expansion);
+                let raw = self.balanced('(', ')')?;
+                self.push_str(&format!("${raw}"));
+            }
+            (Some('('), _) => {
+                self.pos += 1;
+                self.current.concerns.push(Concern::CommandSubstitution);
+                let raw = self.balanced('(', ')')?;
+                self.push_str(&format!("${raw}"));
+            }
+            (Some('{'), Dialect::Posix) => {
+                self.pos += 1;
+                self.current.concerns.push(Concern::Expansion);
+                let raw = self.balanced('{', '}')?;
+                self.push_str(&format!("${raw}"));
+            }
+            (Some('\''), Dialect::Posix) if !in_double_quotes => {
+                self.pos += 1;
+                self.ansi_c_quoted()?;
+            }
+            (Some('"'), Dialect::Posix) if !in_double_quotes => {
+                // $"..." is a translatable string, otherwise a plain one
+                self.pos += 1;
+                self.double_quoted()?;
+            }
+            (Some(c), _) if c.is_ascii_alphanumeric() || c == '_' => {
+                self.current.concerns.push(Concern::Expansion);
+                self.pos += 1;
+                while self
+                    .peek(0)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    self.pos += 1;
+                }
+                let raw: String = self.chars[start..self.pos].iter().collect();
+                self.push_str(&raw);
+            }
+            (Some('?' | '@' | '*' | '#' | '!' | '$' | '-'), Dialect::Posix) => {
+                self.current.concerns.push(Concern::Expansion);
+                self.pos += 2;
+                let raw: String = self.chars[start..self.pos].iter().collect();
+                self.push_str(&raw);
+            }
+            _ => {
+                if in_double_quotes {
+                    self.push_str("$");
+                } else {
+                    self.push('$');
+                }
+                self.pos += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// bash/zsh `$'...'`: backslash escapes, no expansions
+    fn ansi_c_quoted(&mut self) -> Result<(), ParseError> {
+        self.quoted();
+        self.pos += 1;
+        loop {
+            let c = self.peek(0).ok_or(ParseError::UnterminatedQuote)?;
+            self.pos += 1;
+            match c {
+                '\'' => return Ok(()),
+                '\\' => {
+                    let next = self.peek(0).ok_or(ParseError::UnterminatedQuote)?;
+                    self.pos += 1;
+                    let unescaped = match next {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    };
+                    self.push_str(&unescaped.to_string());
+                }
+                c => self.push_str(&c.to_string()),
+            }
+        }
+    }
+
+    fn backticks(&mut self) -> Result<(), ParseError> {
+        self.current.concerns.push(Concern::CommandSubstitution);
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek(0) {
+                None => return Err(ParseError::UnterminatedSubstitution),
+                Some('\\') => self.pos += 2,
+                Some('`') => break,
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        self.push_str(&raw);
+        Ok(())
+    }
+
+    /// Skip from `open` at the cursor to its matching `close`, stepping over
+    /// quoted text; returns the raw text including both.
+    fn balanced(&mut self, open: char, close: char) -> Result<String, ParseError> {
+        let start = self.pos;
+        let mut depth = 0;
+        let mut quote = None;
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            match (c, quote) {
+                ('\\', Some('\'')) => {}
+                ('\\', _) => self.pos += 1,
+                (q, None) if q == '\'' || q == '"' => quote = Some(q),
+                (q, Some(open_quote)) if q == open_quote => quote = None,
+                (_, Some(_)) => {}
+                (c, None) if c == open => depth += 1,
+                (c, None) if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(self.chars[start..self.pos].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(ParseError::UnterminatedSubstitution)
+    }
+
+    fn redirect(&mut self) -> Result<(), ParseError> {
+        // A number right before the operator is the descriptor it applies to
+        if !self.word_quoted
+            && self
+                .word
+                .as_ref()
+                .is_some_and(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_digit()))
+        {
+            self.word = None;
+            self.word_assignment = false;
+        }
+        self.finish_word();
+
+        const OPERATORS: &[&str] = &[
+            "&>>", "&>", "<<<", "<<-", "<<", "<>", "<&", "<(", ">>", ">|", ">&", ">(", "<", ">",
+        ];
+        let rest: String = self.chars[self.pos..].iter().take(3).collect();
+        let op = OPERATORS
+            .iter()
+            .copied()
+            .find(|op| rest.starts_with(op))
+            .unwrap_or(">");
+
+        if matches!(op, "<(" | ">(") && self.dialect == Dialect::Posix {
+            self.pos += 1;
+            self.current.concerns.push(Concern::CommandSubstitution);
+            let raw = self.balanced('(', ')')?;
+            self.push_str(&format!("{}{raw}", &op[..1]));
+            self.finish_word();
+            return Ok(());
+        }
+        self.pos += op.chars().count().min(op.trim_end_matches('(').len());
+        self.has_redirect = true;
+
+        while matches!(self.peek(0), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+        let (target, target_quoted) = self.redirect_target()?;
+        let Some(target) = target else {
+            return Err(ParseError::MissingRedirectTarget(op.to_string()));
+        };
+        match op {
+            "<<" | "<<-" => {
+                if self.dialect == Dialect::Fish {
+                    return Err(ParseError::FishHeredoc);
+                }
+                self.heredocs.push(PendingHeredoc {
+                    delimiter: target,
+                    expands: !target_quoted,
+                    strip_tabs: op == "<<-",
+                    portion: self.portions.len(),
+                });
+            }
+            ">&" | "<&" if target == "-" || target.chars().all(|c| c.is_ascii_digit()) => {}
+            "<" | "<<<" | "<&" => {}
+            _ if target == "/dev/null" => {}
+            _ => self.current.concerns.push(Concern::WritingRedirect(target)),
+        }
+        Ok(())
+    }
+
+    /// The word after a redirect operator and whether it was quoted
+    fn redirect_target(&mut self) -> Result<(Option<String>, bool), ParseError> {
+        const BOUNDARY: &[char] = &[' ', '\t', '\n', ';', '&', '|', '<', '>', '(', ')'];
+        while let Some(c) = self.peek(0) {
+            if BOUNDARY.contains(&c) && !(c == '(' && self.dialect == Dialect::Fish) {
+                break;
+            }
+            self.word_part()?;
+        }
+        // Never an assignment, and not a word of the command
+        self.word_assignment = false;
+        Ok((self.word.take(), std::mem::take(&mut self.word_quoted)))
+    }
+
+    fn read_heredocs(&mut self) -> Result<(), ParseError> {
+        for doc in std::mem::take(&mut self.heredocs) {
+            loop {
+                if self.pos >= self.chars.len() {
+                    return Err(ParseError::UnterminatedHeredoc(doc.delimiter));
+                }
+                let start = self.pos;
+                while self.peek(0).is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+                let line: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1;
+                let compared = if doc.strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line.as_str()
+                };
+                if compared == doc.delimiter {
+                    break;
+                }
+                if doc.expands {
+                    if let Some(concern) = heredoc_line_concern(&line) {
+                        if let Some(portion) = self.portions.get_mut(doc.portion) {
+                            portion.concerns.push(concern);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Expansions still active in a heredoc body line
+fn heredoc_line_concern(line: &str) -> Option<Concern> {
+    let mut concern = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '`' => return Some(Concern::CommandSubstitution),
+            '$' => match chars.peek() {
+                Some('(') => {
+                    chars.next();
+                    if chars.peek() != Some(&'(') {
+                        return Some(Concern::CommandSubstitution);
+                    }
+                    concern = Some(Concern::Expansion);
+                }
+                Some(c) if c.is_ascii_alphanumeric() || "_{?@*#!$-".contains(*c) => {
+                    concern = Some(Concern::Expansion);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    concern
+}
+
+fn is_name(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Concern::*;
+
+    fn words(portions: &[Portion]) -> Vec<Vec<&str>> {
+        portions
+            .iter()
+            .map(|p| p.words.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn scripts_split_into_portions() {
+        #[rustfmt::skip]
+        let cases: &[(&str, Dialect, &[&[&str]])] = &[
+            ("ls -la", Dialect::Posix, &[&["ls", "-la"]]),
+            ("ls && pwd; echo 'hi there' | wc -l", Dialect::Posix, &[&["ls"], &["pwd"], &["echo", "hi there"], &["wc", "-l"]]),
+            ("grep -n \"a b\" f.txt || true", Dialect::Posix, &[&["grep", "-n", "a b", "f.txt"], &["true"]]),
+            ("echo \"\" x", Dialect::Posix, &[&["echo", "", "x"]]),
+            ("echo a\\ b", Dialect::Posix, &[&["echo", "a b"]]),
+            ("echo $'a\\tb'", Dialect::Posix, &[&["echo", "a\tb"]]),
+            ("echo \"it's \\\"ok\\\"\"", Dialect::Posix, &[&["echo", "it's \"ok\""]]),
+            ("ls \\\n  -la", Dialect::Posix, &[&["ls", "-la"]]),
+            ("ls # list files\npwd", Dialect::Posix, &[&["ls"], &["pwd"]]),
+            ("! grep -q x f", Dialect::Posix, &[&["grep", "-q", "x", "f"]]),
+            ("rg foo 2>/dev/null | head -n 5", Dialect::Posix, &[&["rg", "foo"], &["head", "-n", "5"]]),
+            ("cat <<'EOF'\n$(rm -rf /)\nEOF\nwc -l f", Dialect::Posix, &[&["cat"], &["wc", "-l", "f"]]),
+            ("cat <<-EOF | grep x\n\tplain\n\tEOF", Dialect::Posix, &[&["cat"], &["grep", "x"]]),
+            ("cat <<< 'hello'", Dialect::Posix, &[&["cat"]]),
+            ("echo 'it\\'s'", Dialect::Fish, &[&["echo", "it's"]]),
+            ("ls; and pwd; or not true", Dialect::Fish, &[&["ls"], &["pwd"], &["true"]]),
+            ("echo \"a\\$b\"", Dialect::Fish, &[&["echo", "a$b"]]),
+            ("echo `x`", Dialect::Fish, &[&["echo", "`x`"]]),
+        ];
+        for (script, dialect, expected) in cases {
+            let portions = analyze(script, *dialect)
+                .unwrap_or_else(|e| panic!("{script:?} failed to parse: {e}"));
+            assert_eq!(words(&portions), *expected, "words of {script:?}");
+            assert!(
+                portions.iter().all(|p| p.concerns.is_empty()),
+                "{script:?} should have no concerns: {portions:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hidden_effects_are_flagged() {
+        let redirect = |target: &str| WritingRedirect(target.to_string());
+        #[rustfmt::skip]
+        let cases: Vec<(&str, Dialect, Concern)> = vec![
+            ("echo $(rm -rf /)", Dialect::Posix, CommandSubstitution),
+            ("echo \"$(whoami)\"", Dialect::Posix, CommandSubstitution),
+            ("echo `id`", Dialect::Posix, CommandSubstitution),
+            ("diff <(ls a) <(ls b)", Dialect::Posix, CommandSubstitution),
+            ("cat <<EOF\n$(touch x)\nEOF", Dialect::Posix, CommandSubstitution),
+            ("echo (rm -rf /)", Dialect::Fish, CommandSubstitution),
+            ("echo \"$(id)\"", Dialect::Fish, CommandSubstitution),
+            ("echo $HOME", Dialect::Posix, Expansion),
+            ("echo \"${PATH}\"", Dialect::Posix, Expansion),
+            ("echo $((1+2))", Dialect::Posix, Expansion),
+            ("cat <<EOF\n$USER\nEOF", Dialect::Posix, Expansion),
+            ("echo $fish_pid", Dialect::Fish, Expansion),
+            ("ls > out.txt", Dialect::Posix, redirect("out.txt")),
+            ("ls 2>>err.log", Dialect::Posix, redirect("err.log")),
+            ("ls &> 'all out'", Dialect::Posix, redirect("all out")),
+            ("ls >& file", Dialect::Posix, redirect("file")),
+            ("ls > out.txt", Dialect::Fish, redirect("out.txt")),
+            ("sleep 10 &", Dialect::Posix, Background),
+            ("(cd / && ls)", Dialect::Posix, Subshell),
+            ("ls *(e:'rm -rf /':)", Dialect::Posix, Subshell),
+            ("FOO=bar ls", Dialect::Posix, Assignment("FOO=bar".to_string())),
+            ("PAGER='less -R' git log", Dialect::Fish, Assignment("PAGER=less -R".to_string())),
+        ];
+        for (script, dialect, concern) in cases {
+            let portions = analyze(script, dialect)
+                .unwrap_or_else(|e| panic!("{script:?} failed to parse: {e}"));
+            assert!(
+                portions.iter().any(|p| p.concerns.contains(&concern)),
+                "{script:?} should be flagged with {concern:?}: {portions:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_scripts_are_errors() {
+        #[rustfmt::skip]
+        let cases: &[(&str, Dialect, ParseError)] = &[
+            ("ls &&", Dialect::Posix, ParseError::MissingCommand("&&".to_string())),
+            ("| wc", Dialect::Posix, ParseError::MissingCommand("|".to_string())),
+            ("ls;;", Dialect::Posix, ParseError::MissingCommand(";".to_string())),
+            ("echo 'open", Dialect::Posix, ParseError::UnterminatedQuote),
+            ("echo \"open", Dialect::Fish, ParseError::UnterminatedQuote),
+            ("echo $(ls", Dialect::Posix, ParseError::UnterminatedSubstitution),
+            ("cat <<EOF\nbody", Dialect::Posix, ParseError::UnterminatedHeredoc("EOF".to_string())),
+            ("ls >", Dialect::Posix, ParseError::MissingRedirectTarget(">".to_string())),
+            ("cat <<EOF", Dialect::Fish, ParseError::FishHeredoc),
+            ("ls)", Dialect::Fish, ParseError::UnmatchedParen),
+        ];
+        for (script, dialect, error) in cases {
+            assert_eq!(analyze(script, *dialect).as_ref(), Err(error), "{script:?}");
+        }
+    }
+}
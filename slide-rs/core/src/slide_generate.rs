@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Result};
+
+use crate::client::{ModelClient, ResponseEvent};
+
+/// Options controlling a one-shot deck generation.
+#[derive(Debug, Clone)]
+pub struct SlideGenerateOptions {
+    pub prompt: String,
+    pub num_slides: usize,
+    pub language: String,
+    /// Only produce slide titles with a one-line summary each
+    pub outline_only: bool,
+    /// Markdown deck whose structure and tone the output should follow
+    pub template: Option<String>,
+}
+
+impl Default for SlideGenerateOptions {
+    fn default() -> Self {
+        Self {
+            prompt: String::new(),
+            num_slides: 6,
+            language: "ja".to_string(),
+            outline_only: false,
+            template: None,
+        }
+    }
+}
+
+/// Progress reported while the model streams the deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateProgress {
+    pub bytes: usize,
+    pub slides: usize,
+}
+
+/// Build the instruction sent to the model for a generation request.
+pub fn build_generation_prompt(opts: &SlideGenerateOptions) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("You are a presentation writer. Produce a Markdown slide deck.\n");
+    prompt.push_str(&format!("Topic: {}\n", opts.prompt.trim()));
+    prompt.push_str(&format!("Number of slides: {}\n", opts.num_slides));
+    prompt.push_str(&format!(
+        "Write every slide in language: {}\n",
+        opts.language
+    ));
+    prompt.push_str("Rules:\n");
+    prompt.push_str("- Start with a single `# Title` line for the deck.\n");
+    prompt.push_str("- Begin each slide with a `## ` heading.\n");
+    if opts.outline_only {
+        prompt.push_str(
+            "- Outline only: under each heading write exactly one line summarising the slide.\n",
+        );
+    } else {
+        prompt.push_str("- Use concise bullet points (3-5 per slide).\n");
+    }
+    prompt.push_str("- Output only the Markdown, without code fences or commentary.\n");
+    if let Some(template) = &opts.template {
+        prompt.push_str("\nFollow the structure, heading style and tone of this template deck:\n");
+        prompt.push_str("<template>\n");
+        prompt.push_str(template.trim_end());
+        prompt.push_str("\n</template>\n");
+    }
+    prompt
+}
+
+/// Stream a deck from the model, reporting progress as slides arrive.
+pub async fn generate_slides<F>(
+    client: &(dyn ModelClient + Send + Sync),
+    opts: &SlideGenerateOptions,
+    mut on_progress: F,
+) -> Result<String>
+where
+    F: FnMut(GenerateProgress),
+{
+    let markdown = stream_text(client, build_generation_prompt(opts), |markdown| {
+        on_progress(GenerateProgress {
+            bytes: markdown.len(),
+            slides: count_slides(markdown),
+        })
+    })
+    .await?;
+    let markdown = strip_code_fence(&markdown);
+    if markdown.trim().is_empty() {
+        return Err(anyhow!("model returned an empty deck"));
+    }
+    Ok(markdown)
+}
+
+/// Whole reply of the model to `prompt`; `on_text` sees the text so far
+/// after each delta.
+pub(crate) async fn stream_text<F>(
+    client: &(dyn ModelClient + Send + Sync),
+    prompt: String,
+    mut on_text: F,
+) -> Result<String>
+where
+    F: FnMut(&str),
+{
+    let mut rx = client.stream(prompt).await?;
+    let mut text = String::new();
+    while let Some(ev) = rx.recv().await {
+        match ev {
+            ResponseEvent::TextDelta(delta) => {
+                text.push_str(&delta);
+                on_text(&text);
+            }
+            ResponseEvent::Completed => break,
+            ResponseEvent::Error(message) => return Err(anyhow!(message)),
+        }
+    }
+    Ok(text)
+}
+
+fn count_slides(markdown: &str) -> usize {
+    markdown
+        .lines()
+        .filter(|line| line.starts_with("## "))
+        .count()
+}
+
+/// Models occasionally wrap the whole deck in a ```markdown fence; drop it.
+pub(crate) fn strip_code_fence(markdown: &str) -> String {
+    let trimmed = markdown.trim();
+    if let Some(rest) = trimmed.strip_prefix("```") {
+        let body = rest.split_once('\n').map(|(_, b)| b).unwrap_or("");
+        let body = body.trim_end().strip_suffix("```").unwrap_or(body);
+        return format!("{}\n", body.trim());
+    }
+    format!("{trimmed}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_reflects_options() {
+        let opts = SlideGenerateOptions {
+            prompt: "Rust async".to_string(),
+            num_slides: 4,
+            language: "en".to_string(),
+            outline_only: true,
+            template: Some("# T\n## A\n".to_string()),
+        };
+        let prompt = build_generation_prompt(&opts);
+        assert!(prompt.contains("Topic: Rust async"));
+        assert!(prompt.contains("Number of slides: 4"));
+        assert!(prompt.contains("language: en"));
+        assert!(prompt.contains("Outline only"));
+        assert!(prompt.contains("<template>\n# T\n## A\n</template>"));
+    }
+
+    #[test]
+    fn strips_markdown_fence() {
+        assert_eq!(
+            strip_code_fence("```markdown\n# T\n## A\n```"),
+            "# T\n## A\n"
+        );
+        assert_eq!(strip_code_fence("# T\n"), "# T\n");
+    }
+}
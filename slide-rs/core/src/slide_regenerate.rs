@@ -0,0 +1,243 @@
+//! Writing again only the slides of a deck whose outline entries changed.
+//!
+//! [`plan_regeneration`] compares the outline a deck was written from with
+//! the edited one. Slides whose entry is unchanged, or only moved, are kept
+//! as written; new and edited entries are written by the model, one request
+//! per slide with the whole outline for context. Slides that were never in
+//! the outline (added by hand) stay after the slide they followed.
+use std::collections::HashSet;
+
+use anyhow::Result;
+use slide_common::deck::{Deck, DeckPart};
+use slide_common::outline::{Outline, OutlineEntry};
+use slide_common::Slide;
+
+use crate::client::ModelClient;
+use crate::slide_generate::{stream_text, strip_code_fence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedSlide {
+    /// Slide of the current deck, kept as written
+    Keep(usize),
+    /// Slide for this entry of the edited outline, written by the model
+    Generate(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RegenerationPlan {
+    /// Slides of the new deck, in order
+    pub slides: Vec<PlannedSlide>,
+    /// Slides of the current deck whose entry was edited or removed
+    pub dropped: Vec<usize>,
+}
+
+impl RegenerationPlan {
+    /// Entries of the edited outline the model has to write.
+    pub fn to_generate(&self) -> Vec<usize> {
+        self.slides
+            .iter()
+            .filter_map(|slide| match slide {
+                PlannedSlide::Generate(entry) => Some(*entry),
+                PlannedSlide::Keep(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Which slides of `deck`, written from the outline `written_from`, to keep
+/// and which to write for the `edited` outline.
+pub fn plan_regeneration(
+    deck: &Deck,
+    written_from: &Outline,
+    edited: &Outline,
+) -> RegenerationPlan {
+    // Slides of the deck, each with the entry it was written for
+    let mut linked_slides = HashSet::new();
+    let mut linked: Vec<Option<(&OutlineEntry, usize)>> = Vec::new();
+    for (position, entry) in written_from.entries.iter().enumerate() {
+        if let Some(slide) = deck
+            .slide_for_entry(position, &entry.title)
+            .filter(|slide| linked_slides.insert(*slide))
+        {
+            linked.push(Some((entry, slide)));
+        }
+    }
+
+    let has_title = deck.slides.first().is_some_and(Slide::is_title);
+    let mut slides = Vec::new();
+    if has_title {
+        slides.push(PlannedSlide::Keep(0));
+    }
+    for (index, entry) in edited.entries.iter().enumerate() {
+        let kept = linked
+            .iter_mut()
+            .find(|link| link.is_some_and(|(written, _)| written == entry))
+            .and_then(Option::take);
+        slides.push(match kept {
+            Some((_, slide)) => PlannedSlide::Keep(slide),
+            None => PlannedSlide::Generate(index),
+        });
+    }
+    let mut dropped: Vec<usize> = linked.into_iter().flatten().map(|(_, s)| s).collect();
+    dropped.sort_unstable();
+
+    let outline_slides = deck.outline_slides();
+    for index in outline_slides.clone() {
+        if linked_slides.contains(&index) || deck.slides[index].content.trim().is_empty() {
+            continue;
+        }
+        let after = (outline_slides.start..index)
+            .rev()
+            .find_map(|before| slides.iter().position(|s| *s == PlannedSlide::Keep(before)));
+        let at = after.map_or(usize::from(has_title), |at| at + 1);
+        slides.insert(at, PlannedSlide::Keep(index));
+    }
+    RegenerationPlan { slides, dropped }
+}
+
+/// Instruction for writing the slide of entry `index` of `outline`.
+pub fn build_slide_prompt(outline: &Outline, index: usize, language: &str) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("You are a presentation writer. Write one slide of a Markdown deck.\n");
+    if let Some(title) = &outline.title {
+        prompt.push_str(&format!("Deck: {title}\n"));
+    }
+    prompt.push_str(&format!("Write the slide in language: {language}\n"));
+    prompt.push_str("Outline of the deck; the slide to write is marked with →:\n");
+    for (i, entry) in outline.entries.iter().enumerate() {
+        let marker = if i == index { "→" } else { " " };
+        let summary = entry.summary.lines().collect::<Vec<_>>().join(" ");
+        prompt.push_str(&format!("{marker} {}. {}: {summary}\n", i + 1, entry.title));
+    }
+    if let Some(entry) = outline.entries.get(index) {
+        prompt.push_str("Rules:\n");
+        prompt.push_str(&format!("- Start with the heading `## {}`.\n", entry.title));
+        prompt.push_str("- Use concise bullet points (3-5).\n");
+        prompt.push_str(
+            "- Output only the Markdown of this one slide, without code fences or commentary.\n",
+        );
+    }
+    prompt
+}
+
+/// `markdown` rebuilt after `plan`: kept slides as they are, the others
+/// written by the model. `on_slide` is told the title of each slide before
+/// it is requested.
+pub async fn regenerate_deck<F>(
+    client: &(dyn ModelClient + Send + Sync),
+    markdown: &str,
+    plan: &RegenerationPlan,
+    edited: &Outline,
+    language: &str,
+    mut on_slide: F,
+) -> Result<String>
+where
+    F: FnMut(&str),
+{
+    let mut parts = Vec::new();
+    // A deck written from scratch gets the outline's title
+    if markdown.trim().is_empty() {
+        if let Some(title) = &edited.title {
+            parts.push(DeckPart::Text(format!("# {title}")));
+        }
+    }
+    for planned in &plan.slides {
+        match *planned {
+            PlannedSlide::Keep(index) => parts.push(DeckPart::Slide(index)),
+            PlannedSlide::Generate(index) => {
+                let Some(entry) = edited.entries.get(index) else {
+                    continue;
+                };
+                on_slide(&entry.title);
+                let prompt = build_slide_prompt(edited, index, language);
+                let reply = stream_text(client, prompt, |_| {}).await?;
+                parts.push(DeckPart::Text(slide_from_reply(&reply, &entry.title)));
+            }
+        }
+    }
+    Ok(Deck::assemble(markdown, &parts))
+}
+
+/// The slide in a reply, headed with the outline's `title` whatever
+/// heading the model gave it.
+fn slide_from_reply(reply: &str, title: &str) -> String {
+    let text = strip_code_fence(reply);
+    let deck = Deck::parse(&text);
+    let body = match deck.slides.iter().find(|s| s.content.starts_with("## ")) {
+        Some(slide) => slide.content.split_once('\n').map_or("", |(_, body)| body),
+        None if text.starts_with('#') => text.split_once('\n').map_or("", |(_, body)| body),
+        None => text.as_str(),
+    };
+    format!("## {title}\n{}", body.trim())
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_provider::{MockProvider, MockResponse};
+
+    fn reply(text: &str) -> MockResponse {
+        MockResponse {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn only_new_and_edited_entries_are_written_again() -> Result<()> {
+        let markdown =
+            "# Q3\n\n## Results\n- up\n\n## Costs\n- rent  \n- food\n\n## Extra\nby hand\n";
+        let deck = Deck::parse(markdown);
+        let written_from = Outline::parse(
+            "# Q3\n\n## Results\nRevenue\n\n## Costs\nSpending\n\n## Team\nPeople\n",
+        );
+        let edited = Outline::parse(
+            "# Q3\n\n## Costs\nSpending\n\n## Results\nRevenue by region\n\n## Hiring\nOpen roles\n",
+        );
+
+        let plan = plan_regeneration(&deck, &written_from, &edited);
+        use PlannedSlide::{Generate, Keep};
+        // Costs moved and is kept; Extra stays after it; Team had no slide
+        assert_eq!(
+            plan.slides,
+            [Keep(0), Keep(2), Keep(3), Generate(1), Generate(2)]
+        );
+        assert_eq!(plan.dropped, [1]);
+        assert_eq!(plan.to_generate(), [1, 2]);
+
+        let client = MockProvider::new(
+            "regen",
+            vec![
+                reply("```markdown\n## Results!\n- north up\n- south flat\n```"),
+                reply("- two engineers"),
+            ],
+        );
+        let mut requested = Vec::new();
+        let out = regenerate_deck(&client, markdown, &plan, &edited, "en", |title| {
+            requested.push(title.to_string())
+        })
+        .await?;
+        assert_eq!(requested, ["Results", "Hiring"]);
+        assert_eq!(
+            out,
+            "# Q3\n\n## Costs\n- rent  \n- food\n\n## Extra\nby hand\n\n\
+             ## Results\n- north up\n- south flat\n\n## Hiring\n- two engineers\n"
+        );
+        let prompts = client.prompts();
+        assert!(prompts[0].contains("→ 2. Results: Revenue by region"));
+        assert!(prompts[0].contains("language: en"));
+
+        // Nothing edited: the deck comes back as it was
+        let unchanged = Outline {
+            entries: written_from.entries[..2].to_vec(),
+            ..written_from
+        };
+        let plan = plan_regeneration(&deck, &unchanged, &unchanged);
+        assert!(plan.to_generate().is_empty());
+        let same = regenerate_deck(&client, markdown, &plan, &unchanged, "en", |_| {}).await?;
+        assert_eq!(same, markdown);
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+//! Spans and counters for teams running slide-code in shared environments.
+//!
+//! The agent always opens `tracing` spans for model requests, tool calls and
+//! patch applications (target [`TARGET`]). With the `otel` feature,
+//! [`init`] exports them over OTLP (HTTP/protobuf) together with counters
+//! for tokens, estimated cost, commands run and approval answers. The
+//! exporter is configured with the standard `OTEL_EXPORTER_OTLP_*`
+//! variables and only starts when an endpoint is set, `OTEL_SDK_DISABLED`
+//! is not `true` and privacy mode allows it. Without the feature the
+//! counters are no-ops.
+use crate::codex2::{ReviewDecision, TokenUsage};
+use tracing::field::Empty;
+use tracing::Span;
+
+/// Target of the exported spans
+pub const TARGET: &str = "slide.telemetry";
+
+/// Service name reported to the collector
+pub const SERVICE_NAME: &str = "slide-code";
+
+/// Span covering one model request, from sending the prompt to the end of
+/// the reply. Close it with [`end_model_request`].
+pub fn model_request_span(model: &str, provider: &str) -> Span {
+    tracing::info_span!(
+        target: TARGET,
+        "model_request",
+        model,
+        provider,
+        input_tokens = Empty,
+        output_tokens = Empty,
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+    )
+}
+
+/// Close a model request span, with the usage of a complete reply or the
+/// error that ended it.
+pub fn end_model_request(span: Span, result: Result<TokenUsage, &str>) {
+    match result {
+        Ok(usage) => {
+            span.record("input_tokens", usage.input_tokens);
+            span.record("output_tokens", usage.output_tokens);
+        }
+        Err(message) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("otel.status_message", message);
+        }
+    }
+}
+
+/// Span covering one tool call.
+pub fn tool_span(tool: &str) -> Span {
+    tracing::info_span!(target: TARGET, "tool", tool)
+}
+
+/// Span covering the application of a patch to `files` files; record its
+/// outcome with [`end_patch`].
+pub fn patch_span(files: usize) -> Span {
+    tracing::info_span!(
+        target: TARGET,
+        "apply_patch",
+        files,
+        success = Empty,
+        otel.status_code = Empty,
+    )
+}
+
+pub fn end_patch(span: &Span, success: bool) {
+    span.record("success", success);
+    if !success {
+        span.record("otel.status_code", "ERROR");
+    }
+}
+
+/// Name of an approval answer in the `slide.approvals` counter.
+pub fn decision_label(decision: Option<&ReviewDecision>) -> &'static str {
+    match decision {
+        Some(ReviewDecision::Approved) => "approved",
+        Some(ReviewDecision::ApprovedForSession) => "approved_for_session",
+        Some(ReviewDecision::ApprovedPrefix) => "approved_prefix",
+        Some(ReviewDecision::ApprovedFiles(_)) => "approved_files",
+        Some(ReviewDecision::ApprovedCommands(_)) => "approved_commands",
+        Some(ReviewDecision::Denied) => "denied",
+        Some(ReviewDecision::Abort) => "abort",
+        // The session ended before the user answered
+        None => "unanswered",
+    }
+}
+
+#[cfg(feature = "otel")]
+pub use otel::{endpoint, init, record_approval, record_exec, record_usage, TelemetryGuard};
+
+#[cfg(not(feature = "otel"))]
+pub use noop::{endpoint, init, record_approval, record_exec, record_usage, TelemetryGuard};
+
+#[cfg(not(feature = "otel"))]
+mod noop {
+    use super::*;
+
+    /// Nothing to flush without the `otel` feature.
+    pub struct TelemetryGuard;
+
+    /// Without the `otel` feature nothing is exported.
+    pub fn init() -> anyhow::Result<Option<TelemetryGuard>> {
+        Ok(None)
+    }
+
+    pub fn endpoint() -> Option<String> {
+        None
+    }
+
+    pub fn record_usage(_provider: &str, _model: &str, _usage: TokenUsage) {}
+
+    pub fn record_exec(_exit_code: Option<i32>) {}
+
+    pub fn record_approval(_kind: &'static str, _decision: Option<&ReviewDecision>) {}
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::*;
+    use anyhow::Context;
+    use opentelemetry::metrics::Counter;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use std::sync::OnceLock;
+    use tracing::warn;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+    use tracing_subscriber::Layer as _;
+
+    /// Flushes and stops the exporters when dropped; keep it until exit.
+    pub struct TelemetryGuard {
+        tracer_provider: SdkTracerProvider,
+        meter_provider: SdkMeterProvider,
+    }
+
+    impl Drop for TelemetryGuard {
+        fn drop(&mut self) {
+            if let Err(e) = self.tracer_provider.shutdown() {
+                warn!(error = %e, "failed to flush spans");
+            }
+            if let Err(e) = self.meter_provider.shutdown() {
+                warn!(error = %e, "failed to flush metrics");
+            }
+        }
+    }
+
+    /// Collector the data goes to, when export is on.
+    pub fn endpoint() -> Option<String> {
+        let disabled = std::env::var("OTEL_SDK_DISABLED").is_ok_and(|v| v.trim() == "true");
+        if disabled || !slide_common::privacy::active().telemetry_enabled() {
+            return None;
+        }
+        [
+            "OTEL_EXPORTER_OTLP_ENDPOINT",
+            "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+            "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+        ]
+        .into_iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+    }
+
+    /// Start exporting spans and counters; `None` when no collector is
+    /// configured. Call once at startup, before any span is opened.
+    pub fn init() -> anyhow::Result<Option<TelemetryGuard>> {
+        if endpoint().is_none() {
+            return Ok(None);
+        }
+        let resource = Resource::builder()
+            .with_service_name(SERVICE_NAME)
+            .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+            .build();
+
+        let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+            .context("failed to create the OTLP span exporter")?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_resource(resource.clone())
+            .with_batch_exporter(span_exporter)
+            .build();
+
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .build()
+            .context("failed to create the OTLP metric exporter")?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_resource(resource)
+            .with_periodic_exporter(metric_exporter)
+            .build();
+        opentelemetry::global::set_meter_provider(meter_provider.clone());
+
+        let layer =
+            tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(SERVICE_NAME));
+        tracing_subscriber::registry()
+            .with(layer.with_filter(Targets::new().with_target(TARGET, tracing::Level::INFO)))
+            .try_init()
+            .context("a tracing subscriber is already installed")?;
+
+        Ok(Some(TelemetryGuard {
+            tracer_provider,
+            meter_provider,
+        }))
+    }
+
+    struct Instruments {
+        tokens: Counter<u64>,
+        cost: Counter<f64>,
+        execs: Counter<u64>,
+        approvals: Counter<u64>,
+    }
+
+    /// Created on first use, after `init` installed the meter provider.
+    fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = opentelemetry::global::meter(SERVICE_NAME);
+            Instruments {
+                tokens: meter
+                    .u64_counter("slide.tokens")
+                    .with_description("Tokens sent to and received from the model (estimated)")
+                    .build(),
+                cost: meter
+                    .f64_counter("slide.cost")
+                    .with_unit("USD")
+                    .with_description("Estimated spend on model requests")
+                    .build(),
+                execs: meter
+                    .u64_counter("slide.exec.count")
+                    .with_description("Shell commands run by the agent")
+                    .build(),
+                approvals: meter
+                    .u64_counter("slide.approvals")
+                    .with_description("Answers to approval requests")
+                    .build(),
+            }
+        })
+    }
+
+    pub fn record_usage(provider: &str, model: &str, usage: TokenUsage) {
+        let instruments = instruments();
+        for (direction, tokens) in [
+            ("input", usage.input_tokens),
+            ("output", usage.output_tokens),
+        ] {
+            instruments.tokens.add(
+                tokens,
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("direction", direction),
+                ],
+            );
+        }
+        if let Some(cost) = usage.estimated_cost_usd(provider, model) {
+            instruments
+                .cost
+                .add(cost, &[KeyValue::new("model", model.to_string())]);
+        }
+    }
+
+    /// Count a command; `None` when it timed out.
+    pub fn record_exec(exit_code: Option<i32>) {
+        let outcome = match exit_code {
+            Some(0) => "success",
+            Some(_) => "failure",
+            None => "timeout",
+        };
+        instruments()
+            .execs
+            .add(1, &[KeyValue::new("outcome", outcome)]);
+    }
+
+    /// Count the answer to an `exec`, `plan` or `patch` approval request.
+    pub fn record_approval(kind: &'static str, decision: Option<&ReviewDecision>) {
+        instruments().approvals.add(
+            1,
+            &[
+                KeyValue::new("kind", kind),
+                KeyValue::new("decision", decision_label(decision)),
+            ],
+        );
+    }
+}
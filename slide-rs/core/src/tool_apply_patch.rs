@@ -161,315 +161,10 @@ Examples:
     })
 }
 
-/// Parse a patch string and extract file operations
-pub fn parse_patch(patch_content: &str) -> Result<Vec<FileOperation>, String> {
-    let lines: Vec<&str> = patch_content.lines().collect();
-    let mut operations = Vec::new();
-    let mut i = 0;
-
-    // Find "*** Begin Patch"
-    while i < lines.len() && !lines[i].starts_with("*** Begin Patch") {
-        i += 1;
-    }
-
-    if i >= lines.len() {
-        return Err("Patch must start with '*** Begin Patch'".to_string());
-    }
-
-    i += 1; // Skip "*** Begin Patch"
-
-    while i < lines.len() {
-        let line = lines[i].trim();
-
-        if line.starts_with("*** End Patch") {
-            break;
-        }
-
-        if line.starts_with("*** Add File: ") {
-            let path = line.strip_prefix("*** Add File: ").unwrap().to_string();
-            i += 1;
-
-            let mut content = Vec::new();
-            while i < lines.len() {
-                let line = lines[i];
-                if line.starts_with("*** ") {
-                    break;
-                }
-                if line.starts_with("+") {
-                    content.push(line.strip_prefix("+").unwrap_or(line).to_string());
-                }
-                i += 1;
-            }
-
-            operations.push(FileOperation::Add {
-                path,
-                content: content.join("\n"),
-            });
-            continue;
-        }
-
-        if line.starts_with("*** Delete File: ") {
-            let path = line.strip_prefix("*** Delete File: ").unwrap().to_string();
-            operations.push(FileOperation::Delete { path });
-            i += 1;
-            continue;
-        }
-
-        if line.starts_with("*** Update File: ") {
-            let path = line.strip_prefix("*** Update File: ").unwrap().to_string();
-            i += 1;
-
-            let mut changes = Vec::new();
-            while i < lines.len() {
-                let line = lines[i];
-                if line.starts_with("*** ") {
-                    break;
-                }
-
-                if line.starts_with(" ") {
-                    changes.push(ChangeOperation::Context {
-                        line: line.strip_prefix(" ").unwrap_or(line).to_string(),
-                    });
-                } else if line.starts_with("+") {
-                    changes.push(ChangeOperation::Add {
-                        line: line.strip_prefix("+").unwrap_or(line).to_string(),
-                    });
-                } else if line.starts_with("-") {
-                    changes.push(ChangeOperation::Remove {
-                        line: line.strip_prefix("-").unwrap_or(line).to_string(),
-                    });
-                } else if line.starts_with("@@") {
-                    changes.push(ChangeOperation::Context {
-                        line: format!("# {}", line),
-                    });
-                }
-                i += 1;
-            }
-
-            operations.push(FileOperation::Update { path, changes });
-            continue;
-        }
-
-        i += 1;
-    }
-
-    Ok(operations)
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum FileOperation {
-    Add {
-        path: String,
-        content: String,
-    },
-    Delete {
-        path: String,
-    },
-    Update {
-        path: String,
-        changes: Vec<ChangeOperation>,
-    },
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum ChangeOperation {
-    Context { line: String },
-    Add { line: String },
-    Remove { line: String },
-}
-
-/// Apply a file operation to the filesystem
-pub fn apply_file_operation(operation: &FileOperation) -> Result<String, String> {
-    match operation {
-        FileOperation::Add { path, content } => {
-            std::fs::write(path, content)
-                .map_err(|e| format!("Failed to create file {}: {}", path, e))?;
-            Ok(format!("Created file: {}", path))
-        }
-        FileOperation::Delete { path } => {
-            std::fs::remove_file(path)
-                .map_err(|e| format!("Failed to delete file {}: {}", path, e))?;
-            Ok(format!("Deleted file: {}", path))
-        }
-        FileOperation::Update { path, changes } => {
-            let existing_content = std::fs::read_to_string(path)
-                .map_err(|e| format!("Failed to read file {}: {}", path, e))?;
-
-            let updated_content = apply_changes(&existing_content, changes)?;
-
-            std::fs::write(path, updated_content)
-                .map_err(|e| format!("Failed to update file {}: {}", path, e))?;
-
-            Ok(format!("Updated file: {}", path))
-        }
-    }
-}
-
-/// Apply changes to file content
-fn apply_changes(content: &str, changes: &[ChangeOperation]) -> Result<String, String> {
-    let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-    let mut result = Vec::new();
-    let mut change_idx = 0;
-    let mut line_idx = 0;
-
-    while change_idx < changes.len() || line_idx < lines.len() {
-        if change_idx >= changes.len() {
-            // Add remaining original lines
-            result.extend_from_slice(&lines[line_idx..]);
-            break;
-        }
-
-        match &changes[change_idx] {
-            ChangeOperation::Context { line } => {
-                // Skip context lines (they're just for reference)
-                if line_idx < lines.len() && lines[line_idx].trim() == line.trim() {
-                    result.push(lines[line_idx].clone());
-                    line_idx += 1;
-                }
-                change_idx += 1;
-            }
-            ChangeOperation::Add { line } => {
-                result.push(line.clone());
-                change_idx += 1;
-            }
-            ChangeOperation::Remove { line: _ } => {
-                // Skip the line in the original file
-                if line_idx < lines.len() {
-                    line_idx += 1;
-                }
-                change_idx += 1;
-            }
-        }
-    }
-
-    Ok(result.join("\n"))
-}
-
-// Legacy compatibility structures
-#[derive(Debug, Clone)]
-pub struct ApplyPatchInput {
-    pub patch: String,
-}
-
-#[derive(Debug, Clone)]
-pub struct ApplyPatchResult {
-    pub applied: bool,
-    pub message: String,
-}
-
-pub fn tool_apply_patch(input: ApplyPatchInput, _workspace_write: bool) -> ApplyPatchResult {
-    match parse_patch(&input.patch) {
-        Ok(operations) => {
-            let mut results = Vec::new();
-            let mut all_applied = true;
-
-            for operation in operations {
-                match apply_file_operation(&operation) {
-                    Ok(message) => results.push(message),
-                    Err(error) => {
-                        all_applied = false;
-                        results.push(format!("Error: {}", error));
-                    }
-                }
-            }
-
-            ApplyPatchResult {
-                applied: all_applied,
-                message: results.join("\n"),
-            }
-        }
-        Err(error) => ApplyPatchResult {
-            applied: false,
-            message: format!("Failed to parse patch: {}", error),
-        },
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_add_file_patch() {
-        let patch = r#"*** Begin Patch
-*** Add File: test.txt
-+Hello World
-+This is a test file
-*** End Patch"#;
-
-        let operations = parse_patch(patch).unwrap();
-        assert_eq!(operations.len(), 1);
-
-        match &operations[0] {
-            FileOperation::Add { path, content } => {
-                assert_eq!(path, "test.txt");
-                assert_eq!(content, "Hello World\nThis is a test file");
-            }
-            _ => panic!("Expected Add operation"),
-        }
-    }
-
-    #[test]
-    fn test_parse_update_file_patch() {
-        let patch = r#"*** Begin Patch
-*** Update File: main.rs
- fn main() {
--    println!("Hello");
-+    println!("Hello, World!");
- }
-*** End Patch"#;
-
-        let operations = parse_patch(patch).unwrap();
-        assert_eq!(operations.len(), 1);
-
-        match &operations[0] {
-            FileOperation::Update { path, changes } => {
-                assert_eq!(path, "main.rs");
-                assert_eq!(changes.len(), 4);
-            }
-            _ => panic!("Expected Update operation"),
-        }
-    }
-
-    #[test]
-    fn test_parse_delete_file_patch() {
-        let patch = r#"*** Begin Patch
-*** Delete File: obsolete.txt
-*** End Patch"#;
-
-        let operations = parse_patch(patch).unwrap();
-        assert_eq!(operations.len(), 1);
-
-        match &operations[0] {
-            FileOperation::Delete { path } => {
-                assert_eq!(path, "obsolete.txt");
-            }
-            _ => panic!("Expected Delete operation"),
-        }
-    }
-
-    #[test]
-    fn test_apply_changes() {
-        let original = "line1\nline2\nline3";
-        let changes = vec![
-            ChangeOperation::Context {
-                line: "line1".to_string(),
-            },
-            ChangeOperation::Remove {
-                line: "line2".to_string(),
-            },
-            ChangeOperation::Add {
-                line: "new_line2".to_string(),
-            },
-            ChangeOperation::Context {
-                line: "line3".to_string(),
-            },
-        ];
-
-        let result = apply_changes(original, &changes).unwrap();
-        assert_eq!(result, "line1\nnew_line2\nline3");
-    }
-
     #[test]
     fn test_create_tools() {
         let freeform_tool = create_apply_patch_freeform_tool();
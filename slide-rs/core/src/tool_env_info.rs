@@ -0,0 +1,212 @@
+//! `env_info` tool: the environment commands run in, so the model does not
+//! have to guess it from failed commands. Reports the OS, shell, working
+//! directory, sandbox and approval policies, git branch and the versions of
+//! common toolchains. Versions are looked up once per session through an
+//! [`EnvInfoCache`]; everything else is read on each call since `/cd` and
+//! `/approvals` change it.
+use futures::future::join_all;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::git_status::GitStatus;
+
+/// Commands asked for their version, with the arguments that print it
+const VERSION_COMMANDS: &[(&str, &[&str])] = &[
+    ("node", &["--version"]),
+    ("npm", &["--version"]),
+    ("python3", &["--version"]),
+    ("cargo", &["--version"]),
+    ("git", &["--version"]),
+];
+
+/// How long a version command may take before it counts as missing
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolVersion {
+    pub name: &'static str,
+    /// First line the command printed; `None` when it is not installed
+    pub version: Option<String>,
+}
+
+/// Tool versions shared by the turns of a session.
+#[derive(Debug, Clone, Default)]
+pub struct EnvInfoCache(Arc<OnceCell<Vec<ToolVersion>>>);
+
+impl EnvInfoCache {
+    pub async fn versions(&self) -> &[ToolVersion] {
+        self.0.get_or_init(tool_versions).await
+    }
+}
+
+async fn tool_versions() -> Vec<ToolVersion> {
+    join_all(VERSION_COMMANDS.iter().map(|(name, args)| async move {
+        ToolVersion {
+            name,
+            version: version_of(name, args).await,
+        }
+    }))
+    .await
+}
+
+async fn version_of(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).kill_on_drop(true).output();
+    let output = tokio::time::timeout(VERSION_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Some tools (older pythons) print the version on stderr
+    let version = [&output.stdout, &output.stderr]
+        .into_iter()
+        .find_map(|stream| {
+            String::from_utf8_lossy(stream)
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        });
+    version
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvInfo {
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// Name of the user's shell
+    pub shell: Option<String>,
+    /// Whether commands run through the shell as a login shell, or are
+    /// started directly
+    pub login_shell: bool,
+    pub cwd: PathBuf,
+    pub sandbox: String,
+    pub approval: String,
+    /// `None` outside a git repository
+    pub git: Option<GitStatus>,
+    pub tools: Vec<ToolVersion>,
+}
+
+impl EnvInfo {
+    /// Read the environment of commands run in `cwd`.
+    pub async fn collect(
+        cwd: &Path,
+        shell: Option<String>,
+        login_shell: bool,
+        sandbox: String,
+        approval: String,
+        cache: &EnvInfoCache,
+    ) -> Self {
+        let dir = cwd.to_path_buf();
+        let git = tokio::task::spawn_blocking(move || GitStatus::read(&dir))
+            .await
+            .ok()
+            .flatten();
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            shell: shell.or_else(user_shell),
+            login_shell,
+            cwd: cwd.to_path_buf(),
+            sandbox,
+            approval,
+            git,
+            tools: cache.versions().await.to_vec(),
+        }
+    }
+
+    /// Result in the executor's `Explored` format.
+    pub fn message(&self) -> String {
+        let mut message = String::from("Explored\n- Environment\n\n");
+        message.push_str(&format!("OS: {} ({})\n", self.os, self.arch));
+        let shell = self.shell.as_deref().unwrap_or("unknown");
+        if self.login_shell {
+            message.push_str(&format!(
+                "Shell: {shell}; commands run through it as a login shell\n"
+            ));
+        } else {
+            message.push_str(&format!(
+                "Shell: {shell}; commands are started directly, so wrap pipes and redirections in `{shell} -c`\n"
+            ));
+        }
+        message.push_str(&format!("Working directory: {}\n", self.cwd.display()));
+        message.push_str(&format!("Sandbox: {}\n", self.sandbox));
+        message.push_str(&format!("Approval policy: {}\n", self.approval));
+        match &self.git {
+            Some(git) => {
+                let branch = git.branch.as_deref().unwrap_or("detached HEAD");
+                let dirty = git.dirty.len();
+                let plural = if dirty == 1 { "" } else { "s" };
+                message.push_str(&format!(
+                    "Git: {branch}, {dirty} uncommitted file{plural}\n"
+                ));
+            }
+            None => message.push_str("Git: not a repository\n"),
+        }
+        message.push_str("\nTools:\n");
+        for tool in &self.tools {
+            let version = tool.version.as_deref().unwrap_or("not found");
+            message.push_str(&format!("- {}: {version}\n", tool.name));
+        }
+        message
+    }
+}
+
+/// Shell named by `$SHELL`, or PowerShell on Windows.
+fn user_shell() -> Option<String> {
+    if cfg!(windows) {
+        return Some("powershell".to_string());
+    }
+    let shell = std::env::var_os("SHELL")?;
+    Path::new(&shell)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn environment_is_reported_with_cached_versions() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let cache = EnvInfoCache::default();
+        let info = EnvInfo::collect(
+            tmp.path(),
+            Some("zsh".to_string()),
+            false,
+            "read-only".to_string(),
+            "on-request".to_string(),
+            &cache,
+        )
+        .await;
+        let message = info.message();
+        assert!(
+            message.starts_with("Explored\n- Environment\n"),
+            "{message}"
+        );
+        assert!(message.contains("Shell: zsh; commands are started directly"));
+        assert!(message.contains(&format!("Working directory: {}\n", tmp.path().display())));
+        assert!(message.contains("Sandbox: read-only\nApproval policy: on-request\n"));
+        assert!(message.contains("Git: not a repository\n"), "{message}");
+        assert!(message.contains("- cargo: "), "{message}");
+        assert_eq!(info.tools.len(), VERSION_COMMANDS.len());
+
+        // The second call reuses the versions looked up by the first
+        let first = cache.versions().await.as_ptr();
+        assert_eq!(cache.clone().versions().await.as_ptr(), first);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn missing_commands_have_no_version() {
+        assert_eq!(
+            version_of("slide-no-such-command", &["--version"]).await,
+            None
+        );
+    }
+}
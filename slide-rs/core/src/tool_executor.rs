@@ -1,33 +1,314 @@
-use crate::approval_manager::AskForApproval;
+use crate::approval_manager::{
+    ApprovalHandler, ApprovalManager, ApprovalRequest, ApprovalResponse, AskForApproval,
+    PatchApprovalRequest, PatchApprovalResponse, PlanApprovalRequest, PlanApprovalResponse,
+};
+use crate::command_translation::{translate_for_platform, Translation};
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::exec_command::{
+    ExecCommandParams, ReadOutputParams, SessionId, SessionManager, WriteStdinParams,
+};
 use crate::exec_env::create_env;
-use crate::seatbelt::SandboxPolicy;
-use crate::tool_apply_patch::{tool_apply_patch, ApplyPatchInput};
+use crate::guardrails::{GuardrailTrip, TurnGuardrails};
+use crate::json_repair::{parse_lenient, tool_call_candidates};
+use crate::moderation::{added_text, check_content};
+use crate::plan_tool::{StepStatus, UpdatePlanArgs};
+use crate::shell::Shell;
+use crate::telemetry;
+use crate::tool_env_info::{EnvInfo, EnvInfoCache};
+use crate::tool_image::{tool_image, ImageSource, ImageToolInput, ASSETS_DIR};
+use crate::tool_read_file::{tool_read_file, ReadFileInput};
+use crate::tool_registry::ToolRegistry;
+use crate::tool_web_fetch::{
+    tool_web_fetch, tool_web_search, FetchedSources, WebFetchInput, WebSearchInput,
+};
+use crate::turn_snapshot::TurnSnapshot;
+use crate::workspace_guard::WorkspaceGuard;
 use anyhow::{Context, Result};
+use protocol::config_types::SandboxPolicy;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::PathBuf;
+use slide_apply_patch::{
+    ApplyPatchFileChange, DiffStat, FileDiffStat, FileOutcome, Hunk, PatchQueue, PatchReport,
+    PatchRequest,
+};
+use slide_common::moderation::{ModerationAction, ModerationConfig};
+use slide_common::outline::is_outline_file;
+use slide_common::secrets;
+use slide_common::sources::{self, SourceMap};
+use slide_common::versions::unified_diff;
+use slide_common::{GuardrailConfig, VersionStore};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
-use tokio::time::{timeout, Duration};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration, Instant};
+use tracing::Instrument;
+
+/// 実行中のシェルコマンドの進行状況と、ツールが書き込んだファイル
+#[derive(Debug, Clone)]
+pub enum ExecProgress {
+    Begin {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+    },
+    OutputDelta {
+        call_id: String,
+        stream: ExecOutputStream,
+        chunk: String,
+    },
+    End {
+        call_id: String,
+        exit_code: i32,
+    },
+    /// apply_patch や write_file で作成・変更されたファイル
+    FilesTouched {
+        paths: Vec<PathBuf>,
+    },
+    /// apply_patch・write_file の適用開始
+    PatchApplyBegin,
+    /// apply_patch・write_file の結果と、適用したファイルごとの追加・削除行数
+    PatchApplyEnd {
+        success: bool,
+        files: Vec<FileDiffStat>,
+        total: DiffStat,
+    },
+    /// ターン開始からのファイル変更全体の unified diff
+    TurnDiff {
+        unified_diff: String,
+    },
+    /// moderation 設定に引っかかったデッキの変更。`blocked` なら書き込んでいない
+    ContentFlagged {
+        path: PathBuf,
+        reasons: Vec<String>,
+        blocked: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecOutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// レスポンスから抽出したツール呼び出しと、解釈できなかった呼び出しの理由
+#[derive(Debug, Default)]
+pub struct ExtractedToolCalls {
+    pub calls: Vec<ToolCall>,
+    /// 呼び出しの先頭部分と、解釈できなかった理由。モデルに返して直させる
+    pub errors: Vec<String>,
+}
 
 /// ツール実行を管理する統合実行エンジン
 pub struct ToolExecutor {
+    /// 作成時の cwd。書き込み可能なディレクトリとデッキの履歴の基準
+    workspace: PathBuf,
     cwd: PathBuf,
     shell_environment_policy: ShellEnvironmentPolicy,
+    /// コマンドをログインシェル経由で実行する場合のユーザーのシェル
+    login_shell: Option<Shell>,
+    sandbox_policy: SandboxPolicy,
+    /// 書き込みを許可するディレクトリの境界
+    workspace_guard: WorkspaceGuard,
+    /// ツール呼び出しのパースに使う定義
+    tools: ToolRegistry,
+    approval_manager: ApprovalManager,
+    approval_handler: Option<Arc<dyn ApprovalHandler>>,
+    /// まとめて承認されたコマンドの判断（true = 実行可）。実行時に消費する
+    plan_decisions: HashMap<Vec<String>, bool>,
+    progress_tx: Option<mpsc::UnboundedSender<ExecProgress>>,
+    /// exec_session_* で開いた対話セッション。エンジンと共に終了する
+    exec_sessions: SessionManager,
+    /// ターン中に書き込んだファイルの変更前の内容 (`Op::RevertTurn` 用)
+    turn_snapshot: Option<Arc<Mutex<TurnSnapshot>>>,
+    /// 同じターンの apply_patch を順に適用し、古い文脈のパッチを先の変更に載せ直す
+    patch_queue: PatchQueue,
+    /// ターン中のツール呼び出し数・コマンド実行時間・書き込みバイト数の上限
+    guardrails: TurnGuardrails,
+    /// env_info が返すツールのバージョン。セッション中は使い回す
+    env_info_cache: EnvInfoCache,
+    /// web_fetch で読んだページ。デッキの出典と References スライドに使う
+    fetched_sources: FetchedSources,
+    /// デッキに書き込む内容のチェック (プロファイルごとの `moderation` 設定)
+    moderation: ModerationConfig,
 }
 
 impl ToolExecutor {
     pub fn new(
-        _approval_policy: AskForApproval,
-        _sandbox_policy: SandboxPolicy,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
         cwd: PathBuf,
         shell_environment_policy: ShellEnvironmentPolicy,
     ) -> Self {
         Self {
+            workspace_guard: WorkspaceGuard::new(&sandbox_policy, &cwd, &[]),
+            tools: ToolRegistry::builtin(&sandbox_policy),
+            workspace: cwd.clone(),
             cwd,
             shell_environment_policy,
+            login_shell: None,
+            sandbox_policy,
+            approval_manager: ApprovalManager::new(approval_policy),
+            approval_handler: None,
+            plan_decisions: HashMap::new(),
+            progress_tx: None,
+            exec_sessions: SessionManager::new(),
+            turn_snapshot: None,
+            patch_queue: PatchQueue::new(),
+            guardrails: TurnGuardrails::default(),
+            env_info_cache: EnvInfoCache::default(),
+            fetched_sources: FetchedSources::default(),
+            moderation: ModerationConfig::default(),
         }
     }
 
+    /// 承認が必要な操作をユーザーに確認するハンドラを設定
+    pub fn with_approval_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.approval_handler = Some(handler);
+        self
+    }
+
+    /// サンドボックスポリシーの書き込み可能ディレクトリに追加するディレクトリを設定
+    pub fn with_writable_roots(mut self, extra_roots: &[PathBuf]) -> Self {
+        self.workspace_guard =
+            WorkspaceGuard::new(&self.sandbox_policy, &self.workspace, extra_roots);
+        self.workspace_guard.set_cwd(&self.cwd);
+        self
+    }
+
+    /// コマンドを `shell` のログインシェル (`zsh -lc` など) 経由で実行し、
+    /// rc ファイルで設定されるツール (nvm, pyenv など) を使えるようにする
+    pub fn with_login_shell(mut self, shell: Shell) -> Self {
+        self.login_shell = Some(shell);
+        self
+    }
+
+    /// ファイルを書き込むツールを外す (`slide --read-only`)
+    pub fn read_only(mut self) -> Self {
+        self.tools = self.tools.read_only();
+        self
+    }
+
+    /// このエンジンが実行できるツール
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// ファイルを書き込むすべての経路で使う書き込み境界
+    pub fn workspace_guard(&self) -> &WorkspaceGuard {
+        &self.workspace_guard
+    }
+
+    /// シェルコマンドの開始・出力・終了を逐次送る先を設定
+    pub fn with_progress_sender(mut self, tx: mpsc::UnboundedSender<ExecProgress>) -> Self {
+        self.progress_tx = Some(tx);
+        self
+    }
+
+    /// env_info のバージョン情報をセッションで共有する
+    pub fn with_env_info_cache(mut self, cache: EnvInfoCache) -> Self {
+        self.env_info_cache = cache;
+        self
+    }
+
+    /// web_fetch で読んだページをセッションで共有する
+    pub fn with_fetched_sources(mut self, sources: FetchedSources) -> Self {
+        self.fetched_sources = sources;
+        self
+    }
+
+    /// デッキに書き込む前に内容をチェックする
+    pub fn with_moderation(mut self, config: ModerationConfig) -> Self {
+        self.moderation = config;
+        self
+    }
+
+    /// 次のツール呼び出しを止める上限があればそれを返す
+    pub fn check_guardrails(&self) -> std::result::Result<(), GuardrailTrip> {
+        self.guardrails.check()
+    }
+
+    /// ファイルを書き込む前の内容を記録する先を設定
+    pub fn with_turn_snapshot(mut self, snapshot: Arc<Mutex<TurnSnapshot>>) -> Self {
+        self.turn_snapshot = Some(snapshot);
+        self
+    }
+
+    /// ターンごとの上限を設定
+    pub fn with_guardrails(mut self, config: GuardrailConfig) -> Self {
+        self.guardrails = TurnGuardrails::new(config);
+        self
+    }
+
+    /// `path` を書き込む前に、ターン開始時の内容を記録する
+    fn record_for_turn(&self, path: &Path) {
+        if let Some(snapshot) = &self.turn_snapshot {
+            if let Ok(mut snapshot) = snapshot.lock() {
+                snapshot.record(path);
+            }
+        }
+    }
+
+    fn send_progress(&self, progress: ExecProgress) {
+        if let Some(tx) = &self.progress_tx {
+            let _ = tx.send(progress);
+        }
+    }
+
+    /// デッキが引用するページを `<deck>.sources.json` に記録し、末尾の References
+    /// スライドを作り直す。引用がなく記録もないファイルには触れない。
+    /// 変わったときはモデルに伝える一行を返す
+    fn update_deck_sources(&self, path: &Path) -> Option<String> {
+        if path.extension().is_none_or(|e| e != "md") || is_outline_file(path) {
+            return None;
+        }
+        let markdown = std::fs::read_to_string(path).ok()?;
+        let previous = SourceMap::load(path);
+        let mut known = self.fetched_sources.all();
+        known.extend(previous.iter().flat_map(|map| map.sources.clone()));
+        let map = SourceMap::collect(&markdown, &known);
+        if map.sources.is_empty() && previous.is_none() {
+            return None;
+        }
+        let updated = sources::with_references(&markdown, &map);
+        if updated == markdown && previous.as_ref() == Some(&map) {
+            return None;
+        }
+        let shown = path.strip_prefix(&self.cwd).unwrap_or(path).display();
+        let result = std::fs::write(path, &updated)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| map.save(path));
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "failed to update the sources of {shown}");
+            return None;
+        }
+        Some(if map.sources.is_empty() {
+            format!("Sources: {shown} cites no pages; removed its References slide")
+        } else {
+            format!(
+                "Sources: {shown} cites {} page(s), listed on its References slide",
+                map.sources.len()
+            )
+        })
+    }
+
+    /// ターン開始からの変更全体を送る
+    fn send_turn_diff(&self) {
+        let Some(snapshot) = &self.turn_snapshot else {
+            return;
+        };
+        let unified_diff = match snapshot.lock() {
+            Ok(snapshot) => snapshot.unified_diff(&self.cwd),
+            Err(_) => return,
+        };
+        self.send_progress(ExecProgress::TurnDiff { unified_diff });
+    }
+
     /// AIレスポンスからツール呼び出しを検出・実行
     pub async fn process_response(&mut self, response: &str) -> Result<String> {
         let mut result = response.to_string();
@@ -64,176 +345,128 @@ impl ToolExecutor {
 
     /// OpenAI Function Calling形式のツール実行
     pub async fn execute_function_call(&mut self, name: &str, arguments: &str) -> Result<String> {
-        let call = self.parse_function_call(name, arguments)?;
-        self.execute_tool_call(call).await
+        let value: Value = serde_json::from_str(arguments)?;
+        let tool = self
+            .tools
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown tool: {name}"))?;
+        tool.execute(self, &value).await
     }
 
     /// レスポンスからツール呼び出しを抽出
     pub fn extract_tool_calls(&self, response: &str) -> Result<Vec<ToolCall>> {
-        let mut tool_calls = Vec::new();
-
-        // JSON形式のツール呼び出しパターンを検索
-        for line in response.lines() {
-            let line = line.trim();
-
-            // {"tool": "shell", "command": ["ls", "-la"]} 形式を検出
-            if line.starts_with('{') && line.contains("\"tool\"") {
-                if let Ok(call) = self.parse_tool_call(line) {
-                    tool_calls.push(call);
-                }
-            }
+        Ok(self.extract_tool_calls_checked(response).calls)
+    }
 
-            // <tool_call>...</tool_call> XML形式も対応
-            if line.contains("<tool_call>") {
-                if let Some(extracted) = self.extract_xml_tool_call(line) {
-                    if let Ok(call) = self.parse_tool_call(&extracted) {
-                        tool_calls.push(call);
-                    }
-                }
+    /// レスポンスからツール呼び出しを抽出し、解釈できなかった呼び出しの
+    /// 理由も返す。少し崩れた JSON（末尾のカンマ、シングルクォート、
+    /// 文字列内の改行など）は修復して解釈する
+    pub fn extract_tool_calls_checked(&self, response: &str) -> ExtractedToolCalls {
+        let mut extracted = ExtractedToolCalls::default();
+        // {"tool": "shell", ...} 形式（複数行にまたがってもよい）と
+        // <tool_call>...</tool_call> 形式を検出
+        for candidate in tool_call_candidates(response) {
+            match self.parse_tool_call(candidate) {
+                Ok(call) => extracted.calls.push(call),
+                Err(e) => extracted
+                    .errors
+                    .push(format!("{}: {e}", snippet(candidate))),
             }
         }
-
-        Ok(tool_calls)
+        extracted
     }
 
     /// JSON形式のツール呼び出しをパース
     fn parse_tool_call(&self, json_str: &str) -> Result<ToolCall> {
-        let value: Value = serde_json::from_str(json_str)?;
+        let (value, repaired) =
+            parse_lenient(json_str).map_err(|e| anyhow::anyhow!("invalid JSON ({e})"))?;
 
         let tool_name = value["tool"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing tool name"))?;
+        let tool = self
+            .tools
+            .get(tool_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown tool: {tool_name}"))?;
 
-        match tool_name {
-            "shell" => {
-                let command = if let Some(cmd_array) = value["command"].as_array() {
-                    cmd_array
-                        .iter()
-                        .map(|v| v.as_str().unwrap_or_default().to_string())
-                        .collect()
-                } else if let Some(cmd_str) = value["command"].as_str() {
-                    // シンプルな文字列の場合は分割
-                    crate::parse_command::parse_command_string(cmd_str)
-                } else {
-                    return Err(anyhow::anyhow!("Invalid command format"));
-                };
-
-                let working_dir = value["working_dir"].as_str().map(PathBuf::from);
-                let with_escalated_permissions = value["with_escalated_permissions"]
-                    .as_bool()
-                    .unwrap_or(false);
-                let justification = value["justification"].as_str().map(String::from);
-                let timeout_ms = value["timeout_ms"].as_u64();
+        let call = tool.parse(&value).map_err(|e| {
+            let problems = tool.parameters().validate(&value);
+            if problems.is_empty() {
+                e
+            } else {
+                anyhow::anyhow!("{e} ({})", problems.join("; "))
+            }
+        })?;
+        if repaired {
+            tracing::info!(tool = tool_name, "repaired malformed tool call JSON");
+        }
+        Ok(call)
+    }
 
-                Ok(ToolCall::Shell {
+    /// 承認が必要なコマンドを複数含む応答は、実行前にまとめて確認する。
+    /// 却下されたコマンドは実行時にスキップとして報告される
+    pub async fn approve_plan(&mut self, calls: &[ToolCall]) {
+        let commands: Vec<Vec<String>> = calls
+            .iter()
+            .filter_map(|call| match call {
+                ToolCall::Shell {
                     command,
-                    working_dir,
-                    with_escalated_permissions,
-                    justification,
-                    timeout_ms,
-                })
-            }
-            "read_file" => {
-                let path = value["path"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing file path"))?;
-                Ok(ToolCall::ReadFile {
-                    path: PathBuf::from(path),
-                })
-            }
-            "write_file" => {
-                let path = value["path"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing file path"))?;
-                let content = value["content"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing file content"))?;
-                Ok(ToolCall::WriteFile {
-                    path: PathBuf::from(path),
-                    content: content.to_string(),
-                })
-            }
-            "apply_patch" => {
-                let input = value["input"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing patch input"))?;
-                Ok(ToolCall::ApplyPatch {
-                    input: input.to_string(),
-                })
-            }
-            "list_files" => {
-                let path = value["path"].as_str().map(PathBuf::from);
-                Ok(ToolCall::ListFiles { path })
-            }
-            "search_files" => {
-                let query = value["query"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing search query"))?;
-                let path = value["path"].as_str().map(PathBuf::from);
-                Ok(ToolCall::SearchFiles {
-                    query: query.to_string(),
-                    path,
-                })
-            }
-            _ => Err(anyhow::anyhow!("Unknown tool: {}", tool_name)),
-        }
-    }
-
-    /// XML形式のツール呼び出しからJSONを抽出
-    fn extract_xml_tool_call(&self, line: &str) -> Option<String> {
-        if let Some(start) = line.find("<tool_call>") {
-            if let Some(end) = line.find("</tool_call>") {
-                let content = &line[start + 11..end];
-                return Some(content.to_string());
-            }
-        }
-        None
-    }
-
-    /// OpenAI Function Call形式をToolCallに変換
-    fn parse_function_call(&self, name: &str, arguments: &str) -> Result<ToolCall> {
-        let value: Value = serde_json::from_str(arguments)?;
-
-        match name {
-            "shell" => {
-                let command = if let Some(cmd_array) = value["command"].as_array() {
-                    cmd_array
-                        .iter()
-                        .map(|v| v.as_str().unwrap_or_default().to_string())
-                        .collect()
-                } else {
-                    return Err(anyhow::anyhow!("Invalid command format"));
+                    with_escalated_permissions: false,
+                    ..
+                } if !command.is_empty()
+                    && self.approval_manager.needs_approval(command, false) =>
+                {
+                    Some(command.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        if commands.len() < 2 {
+            return;
+        }
+        let Some(handler) = self.approval_handler.clone() else {
+            return;
+        };
+        let request = PlanApprovalRequest {
+            commands: commands.clone(),
+            cwd: self.cwd.clone(),
+        };
+        let response = handler.request_plan_approval(request).await;
+        self.plan_decisions = commands
+            .into_iter()
+            .enumerate()
+            .map(|(i, command)| {
+                let approved = match &response {
+                    PlanApprovalResponse::Approved => true,
+                    PlanApprovalResponse::ApprovedCommands(indices) => indices.contains(&i),
+                    PlanApprovalResponse::Denied => false,
                 };
+                (command, approved)
+            })
+            .collect();
+    }
 
-                let working_dir = value["workdir"].as_str().map(PathBuf::from);
-                let timeout_ms = value["timeout_ms"].as_u64();
-                let with_escalated_permissions = value["with_escalated_permissions"]
-                    .as_bool()
-                    .unwrap_or(false);
-                let justification = value["justification"].as_str().map(String::from);
-
-                Ok(ToolCall::Shell {
-                    command,
-                    working_dir,
-                    with_escalated_permissions,
-                    justification,
-                    timeout_ms,
-                })
-            }
-            "apply_patch" => {
-                let input = value["input"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Missing patch input"))?;
-                Ok(ToolCall::ApplyPatch {
-                    input: input.to_string(),
-                })
-            }
-            _ => Err(anyhow::anyhow!("Unknown function: {}", name)),
+    /// 個別のツール呼び出しを実行。上限に達していれば [`GuardrailTrip`] のエラーを返す
+    pub async fn execute_tool_call(&mut self, call: ToolCall) -> Result<String> {
+        self.guardrails.begin_tool_call()?;
+        let runs_process = matches!(
+            call,
+            ToolCall::Shell { .. }
+                | ToolCall::ExecSessionOpen { .. }
+                | ToolCall::ExecSessionWrite { .. }
+                | ToolCall::ExecSessionRead { .. }
+        );
+        let started = Instant::now();
+        let span = telemetry::tool_span(call.name());
+        let result = self.dispatch_tool_call(call).instrument(span).await;
+        if runs_process {
+            self.guardrails.record_exec(started.elapsed());
         }
+        // 結果はモデルに送られ、画面とセッションログにも残る
+        result.map(|output| secrets::redact(&output).into_owned())
     }
 
-    /// 個別のツール呼び出しを実行
-    pub async fn execute_tool_call(&mut self, call: ToolCall) -> Result<String> {
+    async fn dispatch_tool_call(&mut self, call: ToolCall) -> Result<String> {
         match call {
             ToolCall::Shell {
                 command,
@@ -242,6 +475,14 @@ impl ToolExecutor {
                 justification,
                 timeout_ms,
             } => {
+                // 残りの実行時間を超えるコマンドは打ち切る
+                let timeout_ms = match self.guardrails.remaining_exec_time() {
+                    Some(left) => {
+                        let left = u64::try_from(left.as_millis()).unwrap_or(u64::MAX);
+                        Some(timeout_ms.map_or(left, |ms| ms.min(left)))
+                    }
+                    None => timeout_ms,
+                };
                 self.execute_shell_command(
                     command,
                     working_dir,
@@ -251,64 +492,104 @@ impl ToolExecutor {
                 )
                 .await
             }
-            ToolCall::ReadFile { path } => {
-                let full_path = if path.is_absolute() {
-                    path
-                } else {
-                    self.cwd.join(path)
-                };
-
-                match tokio::fs::read_to_string(&full_path).await {
-                    Ok(content) => Ok(format!(
-                        "Explored\n- Read {}\n\nFile content:\n{}",
-                        full_path.display(),
-                        content
-                    )),
-                    Err(e) => Ok(format!(
-                        "Explored\n- Failed to read file {}: {}",
-                        full_path.display(),
-                        e
-                    )),
+            ToolCall::ReadFile { input } => match tool_read_file(&input, &self.cwd).await {
+                Ok(out) => Ok(out.message()),
+                Err(e) => Ok(format!(
+                    "Explored\n- Failed to read file {}: {e:#}",
+                    input.path.display()
+                )),
+            },
+            ToolCall::WriteFile { path, content } => self.write_file(&path, &content).await,
+            ToolCall::ApplyPatch { input } => {
+                if let Err(e) = self.workspace_guard.check_patch(&input) {
+                    return Ok(format!("Proposed Change rejected\n{e}"));
                 }
-            }
-            ToolCall::WriteFile { path, content } => {
-                let full_path = if path.is_absolute() {
-                    path
-                } else {
-                    self.cwd.join(path)
-                };
-
-                // ディレクトリが存在しない場合は作成
-                if let Some(parent) = full_path.parent() {
-                    if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                let request = match PatchRequest::parse(&input, &self.cwd) {
+                    Ok(request) => request,
+                    Err(e) => {
                         return Ok(format!(
-                            "Change Approved\nFailed to create directory {}: {}",
-                            parent.display(),
-                            e
-                        ));
+                            "Proposed Change failed\nFailed to parse patch: {e}"
+                        ))
+                    }
+                };
+                let flagged = match self.moderate_patch(&request).await {
+                    Ok(flagged) => flagged,
+                    Err(reason) => return Ok(format!("Proposed Change rejected\n{reason}")),
+                };
+                let approved = match self.authorize_patch(&request).await {
+                    Ok(approved) => approved,
+                    Err(reason) => return Ok(format!("Proposed Change rejected\n{reason}")),
+                };
+                let keep = |path: &Path| {
+                    approved
+                        .as_ref()
+                        .is_none_or(|paths| paths.iter().any(|p| p == path))
+                };
+                // 変更前のデッキも残し、エージェントの最初の編集も元に戻せるようにする
+                for hunk in request.hunks() {
+                    let path = hunk.resolve_path(&self.cwd);
+                    if !keep(&path) {
+                        continue;
+                    }
+                    if path.is_file() {
+                        record_deck_version(&self.workspace, &path);
+                    }
+                    self.record_for_turn(&path);
+                    if let Hunk::UpdateFile {
+                        move_path: Some(dest),
+                        ..
+                    } = hunk
+                    {
+                        self.record_for_turn(&self.cwd.join(dest));
                     }
                 }
-
-                match tokio::fs::write(&full_path, content).await {
-                    Ok(_) => Ok(format!(
-                        "Change Approved {}\n☑ Successfully wrote to {}",
-                        full_path.display(),
-                        full_path.display()
-                    )),
-                    Err(e) => Ok(format!(
-                        "Change Approved {}\nFailed to write file {}: {}",
-                        full_path.display(),
-                        full_path.display(),
-                        e
-                    )),
+                self.send_progress(ExecProgress::PatchApplyBegin);
+                let span = telemetry::patch_span(request.hunks().len());
+                let report = span.in_scope(|| self.patch_queue.apply(&request, keep));
+                telemetry::end_patch(&span, report.is_success());
+                self.send_progress(ExecProgress::PatchApplyEnd {
+                    success: report.is_success(),
+                    files: report.file_stats(),
+                    total: report.total_stat(),
+                });
+                let mut touched = Vec::new();
+                let mut written = Vec::new();
+                let mut cited = Vec::new();
+                for file in &report.files {
+                    if matches!(
+                        file.outcome,
+                        FileOutcome::Added | FileOutcome::Modified { .. }
+                    ) {
+                        written.push(file.path.clone());
+                    }
+                    let path = match &file.outcome {
+                        FileOutcome::Added | FileOutcome::Modified { moved_to: None } => &file.path,
+                        FileOutcome::Modified {
+                            moved_to: Some(dest),
+                        } => dest,
+                        _ => continue,
+                    };
+                    cited.extend(self.update_deck_sources(path));
+                    record_deck_version(&self.workspace, path);
+                    let written = std::fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+                    self.guardrails.record_write(written);
+                    touched.push(path.clone());
                 }
-            }
-            ToolCall::ApplyPatch { input } => {
-                let result = tool_apply_patch(ApplyPatchInput { patch: input }, true);
-                if result.applied {
-                    Ok(format!("Change Approved\n☑ {}", result.message))
+                if !touched.is_empty() {
+                    self.send_progress(ExecProgress::FilesTouched { paths: touched });
+                }
+                self.send_turn_diff();
+                let mut message = describe_patch_report(&report, &self.cwd);
+                let flagged =
+                    self.report_flagged(flagged, |path| written.iter().any(|w| w == path));
+                for note in flagged.into_iter().chain(cited) {
+                    message.push('\n');
+                    message.push_str(&note);
+                }
+                if report.is_success() {
+                    Ok(format!("Change Approved\n☑ {message}"))
                 } else {
-                    Ok(format!("Proposed Change failed\n{}", result.message))
+                    Ok(format!("Proposed Change failed\n{message}"))
                 }
             }
             ToolCall::ListFiles { path } => {
@@ -369,13 +650,98 @@ impl ToolExecutor {
                     Err(e) => Ok(format!("Explored\n- Search for '{}' failed: {}", query, e)),
                 }
             }
+            ToolCall::Image { input } => self.execute_image(input).await,
+            ToolCall::WebFetch { input } => {
+                let command = input.approval_command();
+                let justification = format!("Fetch {} for the slide deck", input.url);
+                if let Err(reason) = self.authorize_network(command, justification).await {
+                    return Ok(format!("Web request rejected\n{reason}"));
+                }
+                match tool_web_fetch(&input).await {
+                    Ok(output) => {
+                        self.fetched_sources.record(output.source);
+                        Ok(output.message)
+                    }
+                    Err(e) => Ok(format!("Explored\n- Failed to fetch {}: {e}", input.url)),
+                }
+            }
+            ToolCall::WebSearch { input } => {
+                let command = input.approval_command();
+                let justification = format!("Search the web for '{}'", input.query);
+                if let Err(reason) = self.authorize_network(command, justification).await {
+                    return Ok(format!("Web request rejected\n{reason}"));
+                }
+                match tool_web_search(&input).await {
+                    Ok(message) => Ok(message),
+                    Err(e) => Ok(format!(
+                        "Explored\n- Web search '{}' failed: {e}",
+                        input.query
+                    )),
+                }
+            }
+            ToolCall::ExecSessionOpen { input } => self.open_exec_session(input).await,
+            ToolCall::ExecSessionWrite { input } => {
+                // 書き込む内容はそのままコマンドとして実行されるので承認を通す
+                let command = crate::parse_command::parse_command_string(input.chars.trim());
+                if !command.is_empty() && self.approval_handler.is_some() {
+                    let reason = format!("input to exec session {}", input.session_id);
+                    if let Err(reason) = self.authorize(command, reason, false).await {
+                        return Ok(format!(
+                            "Session input skipped\n`{}` was not sent: {reason}",
+                            input.chars.trim()
+                        ));
+                    }
+                }
+                match self.exec_sessions.write(&input).await {
+                    Ok(out) => Ok(out.message()),
+                    Err(e) => Ok(format!("Session request failed\n{e:#}")),
+                }
+            }
+            ToolCall::ExecSessionRead { input } => match self.exec_sessions.read(&input).await {
+                Ok(out) => Ok(out.message()),
+                Err(e) => Ok(format!("Session request failed\n{e:#}")),
+            },
+            ToolCall::EnvInfo => {
+                let info = EnvInfo::collect(
+                    &self.cwd,
+                    self.login_shell.as_ref().and_then(Shell::name),
+                    self.login_shell.is_some(),
+                    self.sandbox_policy.label().to_string(),
+                    self.approval_manager.policy().to_string(),
+                    &self.env_info_cache,
+                )
+                .await;
+                Ok(info.message())
+            }
+            ToolCall::ExecSessionClose { session_id } => {
+                match self.exec_sessions.close(session_id).await {
+                    Ok(()) => Ok(format!("Session {session_id} closed")),
+                    Err(e) => Ok(format!("Session request failed\n{e:#}")),
+                }
+            }
+            // 計画の保持と表示は呼び出し側（codex）が行う
+            ToolCall::UpdatePlan { args } => {
+                let mut output = format!(
+                    "Plan updated ({}/{} steps completed)",
+                    args.completed_count(),
+                    args.plan.len()
+                );
+                if let Some(item) = args
+                    .plan
+                    .iter()
+                    .find(|i| i.status == StepStatus::InProgress)
+                {
+                    output.push_str(&format!("; in progress: {}", item.step));
+                }
+                Ok(output)
+            }
         }
     }
 }
 
 impl ToolExecutor {
     async fn execute_shell_command(
-        &self,
+        &mut self,
         command: Vec<String>,
         working_dir: Option<PathBuf>,
         with_escalated_permissions: bool,
@@ -393,8 +759,49 @@ impl ToolExecutor {
             );
         }
 
-        let mut cmd = Command::new(&command[0]);
-        cmd.args(&command[1..]);
+        // Windows では unix コマンドを PowerShell に置き換え、設定があれば
+        // ログインシェル経由で実行する
+        let translation = translate_for_platform(&command);
+        let translated = match &translation {
+            Some(Translation::Rewritten { command, .. }) => command.clone(),
+            _ => command.clone(),
+        };
+        let invocation = self
+            .login_shell
+            .as_ref()
+            .and_then(|shell| shell.format_default_shell_invocation(translated.clone()))
+            .unwrap_or_else(|| translated.clone());
+
+        // 承認はモデルが書いたコマンドに対して行い、実際の実行方法は理由に添える
+        let mut notes = Vec::new();
+        if let Some(Translation::Rewritten { note, .. }) = &translation {
+            notes.push(note.clone());
+        }
+        if invocation != translated {
+            if let Some(shell) = self.login_shell.as_ref().and_then(Shell::name) {
+                notes.push(format!("runs in a {shell} login shell"));
+            }
+        }
+
+        // 対話セッションでは承認ポリシーに従って確認する
+        if self.approval_handler.is_some() {
+            let mut reason = justification.clone().unwrap_or_default();
+            for note in &notes {
+                if !reason.is_empty() {
+                    reason.push(' ');
+                }
+                reason.push_str(&format!("({note})"));
+            }
+            if let Err(reason) = self.authorize(command.clone(), reason, false).await {
+                return Ok(format!(
+                    "Command skipped\n`{}` was not run: {reason}",
+                    command.join(" ")
+                ));
+            }
+        }
+
+        let mut cmd = Command::new(&invocation[0]);
+        cmd.args(&invocation[1..]);
 
         let cwd = working_dir.unwrap_or_else(|| self.cwd.clone());
         cmd.current_dir(&cwd);
@@ -402,24 +809,55 @@ impl ToolExecutor {
         let env_map = create_env(&self.shell_environment_policy);
         cmd.env_clear();
         cmd.envs(env_map);
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to execute command: {:?}", command))?;
+
+        // 出力は行ごとに進行状況として流しつつ、結果用にも蓄積する
+        let call_id = uuid::Uuid::new_v4().to_string();
+        self.send_progress(ExecProgress::Begin {
+            call_id: call_id.clone(),
+            command: command.clone(),
+            cwd: cwd.clone(),
+        });
+        let stdout_task = self.stream_output(
+            child.stdout.take(),
+            ExecOutputStream::Stdout,
+            call_id.clone(),
+        );
+        let stderr_task = self.stream_output(
+            child.stderr.take(),
+            ExecOutputStream::Stderr,
+            call_id.clone(),
+        );
 
-        let output_future = cmd.output();
-        let output = if let Some(ms) = timeout_ms {
-            match timeout(Duration::from_millis(ms), output_future).await {
-                Ok(result) => {
-                    result.with_context(|| format!("Failed to execute command: {:?}", command))?
+        let status = if let Some(ms) = timeout_ms {
+            match timeout(Duration::from_millis(ms), child.wait()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let _ = child.kill().await;
+                    telemetry::record_exec(None);
+                    self.send_progress(ExecProgress::End {
+                        call_id,
+                        exit_code: -1,
+                    });
+                    return Ok(format!("Command timed out after {ms} ms"));
                 }
-                Err(_) => return Ok(format!("Command timed out after {ms} ms")),
             }
         } else {
-            output_future
-                .await
-                .with_context(|| format!("Failed to execute command: {:?}", command))?
-        };
+            child.wait().await
+        }
+        .with_context(|| format!("Failed to execute command: {:?}", command))?;
 
-        let exit_code = output.status.code().unwrap_or_default();
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let exit_code = status.code().unwrap_or_default();
+        telemetry::record_exec(Some(exit_code));
+        let stdout = stdout_task.await.unwrap_or_default();
+        let stderr = stderr_task.await.unwrap_or_default();
+        self.send_progress(ExecProgress::End { call_id, exit_code });
 
         let mut message = format!(
             "Change Approved\n☑ Command `{}` exited with code {}",
@@ -427,6 +865,10 @@ impl ToolExecutor {
             exit_code
         );
 
+        if let Some(translation) = &translation {
+            message.push_str(&format!("\n\nNote: {}", translation.note()));
+        }
+
         if !stdout.trim().is_empty() {
             message.push_str("\n\nSTDOUT:\n");
             message.push_str(stdout.trim_end());
@@ -446,6 +888,416 @@ impl ToolExecutor {
         Ok(message)
     }
 
+    /// パイプを行単位で読み、各行を進行状況として送る。読み終えた全文を返す
+    fn stream_output<R>(
+        &self,
+        reader: Option<R>,
+        stream: ExecOutputStream,
+        call_id: String,
+    ) -> JoinHandle<String>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let tx = self.progress_tx.clone();
+        tokio::spawn(async move {
+            let mut out = String::new();
+            let Some(reader) = reader else {
+                return out;
+            };
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        let chunk = String::from_utf8_lossy(&buf).into_owned();
+                        if let Some(tx) = &tx {
+                            let _ = tx.send(ExecProgress::OutputDelta {
+                                call_id: call_id.clone(),
+                                stream,
+                                chunk: secrets::redact(&chunk).into_owned(),
+                            });
+                        }
+                        out.push_str(&chunk);
+                    }
+                }
+            }
+            out
+        })
+    }
+
+    /// 対話セッションを開く。起動するコマンドは shell と同じく承認を通す
+    async fn open_exec_session(&mut self, input: ExecCommandParams) -> Result<String> {
+        if input.command.is_empty() {
+            return Ok("exec_session_open did not include a command.".to_string());
+        }
+        if self.approval_handler.is_some() {
+            let reason = "opens an interactive session".to_string();
+            if let Err(reason) = self.authorize(input.command.clone(), reason, false).await {
+                return Ok(format!(
+                    "Command skipped\n`{}` was not run: {reason}",
+                    input.command.join(" ")
+                ));
+            }
+        }
+        let cwd = match &input.workdir {
+            Some(dir) => self.cwd.join(dir),
+            None => self.cwd.clone(),
+        };
+        let env = create_env(&self.shell_environment_policy);
+        match self.exec_sessions.open(&input, &cwd, env).await {
+            Ok(out) => Ok(out.message()),
+            Err(e) => Ok(format!("Session request failed\n{e:#}")),
+        }
+    }
+
+    /// write_file も apply_patch と同じく、差分を確認してから書き込み、
+    /// 適用結果とターンの差分を送る
+    async fn write_file(&mut self, path: &Path, content: &str) -> Result<String> {
+        let full_path = match self.workspace_guard.check_write(path) {
+            Ok(full_path) => full_path,
+            Err(e) => return Ok(format!("Proposed Change rejected\n{e}")),
+        };
+        let original = match tokio::fs::read_to_string(&full_path).await {
+            Ok(original) => Some(original),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Ok(format!(
+                    "Proposed Change failed\nFailed to read {}: {e}",
+                    full_path.display()
+                ))
+            }
+        };
+        if original.as_deref() == Some(content) {
+            return Ok(format!(
+                "Change Approved {}\n☑ {} already has this content",
+                full_path.display(),
+                full_path.display()
+            ));
+        }
+        let change = match &original {
+            Some(original) => ApplyPatchFileChange::Update {
+                unified_diff: unified_diff(original, content),
+                move_path: None,
+                new_content: content.to_string(),
+            },
+            None => ApplyPatchFileChange::Add {
+                content: content.to_string(),
+            },
+        };
+        let changes = HashMap::from([(full_path.clone(), change)]);
+        let flagged = match self.moderate_changes(&changes).await {
+            Ok(flagged) => flagged,
+            Err(reason) => return Ok(format!("Proposed Change rejected\n{reason}")),
+        };
+        let approved = self.authorize_changes(changes).await;
+        match approved {
+            Ok(Some(paths)) if !paths.contains(&full_path) => {
+                return Ok(format!(
+                    "Proposed Change rejected\nSkipped file: {} (rejected by user)",
+                    full_path.display()
+                ))
+            }
+            Ok(_) => {}
+            Err(reason) => return Ok(format!("Proposed Change rejected\n{reason}")),
+        }
+
+        if full_path.is_file() {
+            record_deck_version(&self.workspace, &full_path);
+        }
+        self.record_for_turn(&full_path);
+        self.send_progress(ExecProgress::PatchApplyBegin);
+        let span = telemetry::patch_span(1);
+        let written = async {
+            // ディレクトリが存在しない場合は作成
+            if let Some(parent) = full_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&full_path, content).await
+        }
+        .instrument(span.clone())
+        .await;
+        telemetry::end_patch(&span, written.is_ok());
+        let (files, total) = match &written {
+            Ok(()) => {
+                let stat = DiffStat::between(original.as_deref().unwrap_or_default(), content);
+                let file = FileDiffStat {
+                    path: full_path.clone(),
+                    stat,
+                };
+                (vec![file], stat)
+            }
+            Err(_) => (Vec::new(), DiffStat::default()),
+        };
+        self.send_progress(ExecProgress::PatchApplyEnd {
+            success: written.is_ok(),
+            files,
+            total,
+        });
+        if let Err(e) = written {
+            return Ok(format!(
+                "Change Approved {}\nFailed to write file {}: {e}",
+                full_path.display(),
+                full_path.display()
+            ));
+        }
+        let cited = self.update_deck_sources(&full_path);
+        let flagged = self.report_flagged(flagged, |_| true);
+        record_deck_version(&self.workspace, &full_path);
+        self.guardrails.record_write(content.len() as u64);
+        self.send_progress(ExecProgress::FilesTouched {
+            paths: vec![full_path.clone()],
+        });
+        self.send_turn_diff();
+        let mut message = format!(
+            "Change Approved {}\n☑ Successfully wrote to {}",
+            full_path.display(),
+            full_path.display()
+        );
+        for note in flagged.into_iter().chain(cited) {
+            message.push('\n');
+            message.push_str(&note);
+        }
+        Ok(message)
+    }
+
+    async fn execute_image(&mut self, input: ImageToolInput) -> Result<String> {
+        let command = input.approval_command();
+        let justification = "Fetch an image for the slide deck".to_string();
+        if let Err(reason) = self.authorize_network(command, justification).await {
+            return Ok(format!("Image request rejected\n{reason}"));
+        }
+
+        let deck = input.deck.iter().map(|deck| deck.as_path());
+        for path in std::iter::once(std::path::Path::new(ASSETS_DIR)).chain(deck) {
+            if let Err(e) = self.workspace_guard.check_write(path) {
+                return Ok(format!("Image request rejected\n{e}"));
+            }
+        }
+
+        match tool_image(&input, &self.cwd).await {
+            Ok(out) => {
+                let written = std::fs::metadata(&out.asset_path)
+                    .map(|m| m.len())
+                    .unwrap_or_default();
+                self.guardrails.record_write(written);
+                if let Some(snapshot) = &self.turn_snapshot {
+                    if let Ok(mut snapshot) = snapshot.lock() {
+                        snapshot.record_created(&out.asset_path);
+                    }
+                }
+                let mut message = format!(
+                    "Change Approved\n☑ Saved image to {}\nMarkdown: {}",
+                    out.asset_path.display(),
+                    out.markdown
+                );
+                // デッキへの挿入は write_file と同じ経路 (境界・moderation・承認・履歴) で書き込む
+                if let Some(edit) = out.deck {
+                    let written = self.write_file(&edit.path, &edit.content).await?;
+                    if written.starts_with("Change Approved") {
+                        message.push_str(&format!("\nInserted into {}", edit.path.display()));
+                        for note in written.lines().skip(2) {
+                            message.push('\n');
+                            message.push_str(note);
+                        }
+                    } else {
+                        message.push_str(&format!(
+                            "\nNot inserted into {}:\n{written}",
+                            edit.path.display()
+                        ));
+                    }
+                }
+                Ok(message)
+            }
+            Err(e) => Ok(format!("Image request failed\n{e}")),
+        }
+    }
+
+    /// ネットワークを使う操作の許可。サンドボックスポリシーが許さない場合は
+    /// 権限の昇格として扱う
+    async fn authorize_network(
+        &mut self,
+        command: Vec<String>,
+        justification: String,
+    ) -> std::result::Result<(), String> {
+        let network_allowed = self.sandbox_policy.has_full_network_access();
+        let justification = if network_allowed {
+            justification
+        } else {
+            format!("{justification} (needs network access outside the sandbox policy)")
+        };
+        self.authorize(command, justification, !network_allowed)
+            .await
+    }
+
+    /// 承認ポリシーとネットワークポリシーに従って操作を許可するか判定
+    async fn authorize(
+        &mut self,
+        command: Vec<String>,
+        justification: String,
+        escalated: bool,
+    ) -> std::result::Result<(), String> {
+        if let Some(approved) = self.plan_decisions.remove(&command) {
+            return if approved {
+                Ok(())
+            } else {
+                Err("not approved in the plan".to_string())
+            };
+        }
+        if escalated && matches!(self.approval_manager.policy(), AskForApproval::Never) {
+            return Err("network access is disabled by the sandbox policy".to_string());
+        }
+        if !self.approval_manager.needs_approval(&command, escalated) {
+            return Ok(());
+        }
+        let Some(handler) = self.approval_handler.clone() else {
+            return Err("approval required but no approval handler is available".to_string());
+        };
+        let request = ApprovalRequest::new(
+            command.clone(),
+            Some(&self.cwd),
+            Some(justification),
+            escalated,
+            self.sandbox_policy.label().to_string(),
+        );
+        match handler.request_approval(request).await {
+            ApprovalResponse::Approved => Ok(()),
+            ApprovalResponse::ApprovedAndTrust => {
+                self.approval_manager.approve_command(command);
+                Ok(())
+            }
+            ApprovalResponse::ApprovedPrefix(prefix) => {
+                self.approval_manager.allow_prefix(prefix);
+                Ok(())
+            }
+            ApprovalResponse::ChangePolicy(policy) => {
+                self.approval_manager.set_policy(policy);
+                Ok(())
+            }
+            ApprovalResponse::Denied => Err("denied by user".to_string()),
+        }
+    }
+
+    /// apply_patch の差分をファイルごとに確認し、適用してよいファイルを返す
+    /// (`None` はパッチ全体)
+    async fn authorize_patch(
+        &self,
+        request: &PatchRequest,
+    ) -> std::result::Result<Option<Vec<PathBuf>>, String> {
+        if matches!(self.approval_manager.policy(), AskForApproval::Never)
+            || self.approval_handler.is_none()
+        {
+            return Ok(None);
+        }
+        // 先のパッチに載せ直す場合は、載せ直した結果を確認してもらう
+        let changes = self
+            .patch_queue
+            .changes(request)
+            .map_err(|e| e.to_string())?;
+        self.authorize_changes(changes).await
+    }
+
+    /// パッチがデッキに加える内容をチェックする。当てられないパッチは適用時に失敗する
+    async fn moderate_patch(
+        &self,
+        request: &PatchRequest,
+    ) -> std::result::Result<Vec<FlaggedContent>, String> {
+        if !self.moderation.enabled() {
+            return Ok(Vec::new());
+        }
+        match self.patch_queue.changes(request) {
+            Ok(changes) => self.moderate_changes(&changes).await,
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// デッキ (`.md`) に加わる内容を moderation 設定でチェックする。止める場合は
+    /// UI に知らせてその理由を返す。警告だけの場合は引っかかったファイルを返し、
+    /// 承認されて書き込まれたものだけを [`Self::report_flagged`] で知らせる
+    async fn moderate_changes(
+        &self,
+        changes: &HashMap<PathBuf, ApplyPatchFileChange>,
+    ) -> std::result::Result<Vec<FlaggedContent>, String> {
+        if !self.moderation.enabled() {
+            return Ok(Vec::new());
+        }
+        let blocked = self.moderation.action == ModerationAction::Block;
+        let mut paths: Vec<&PathBuf> = changes.keys().collect();
+        paths.sort();
+        let mut flagged = Vec::new();
+        for path in paths {
+            if path.extension().is_none_or(|e| e != "md") {
+                continue;
+            }
+            let reasons = check_content(&self.moderation, &added_text(&changes[path])).await;
+            if reasons.is_empty() {
+                continue;
+            }
+            flagged.push(FlaggedContent {
+                path: path.clone(),
+                reasons,
+            });
+        }
+        if blocked && !flagged.is_empty() {
+            let notes: Vec<String> = flagged.iter().map(|f| f.note(&self.cwd)).collect();
+            for flag in flagged {
+                self.send_progress(ExecProgress::ContentFlagged {
+                    path: flag.path,
+                    reasons: flag.reasons,
+                    blocked,
+                });
+            }
+            return Err(format!(
+                "{}\nThe content safety filter blocks this content; rewrite it without the flagged content",
+                notes.join("\n")
+            ));
+        }
+        Ok(flagged)
+    }
+
+    /// 警告付きで書き込まれたデッキを UI に知らせ、モデルに伝える行を返す。
+    /// 拒否されたり書き込めなかったりしたファイルは知らせない
+    fn report_flagged(
+        &self,
+        flagged: Vec<FlaggedContent>,
+        written: impl Fn(&Path) -> bool,
+    ) -> Vec<String> {
+        let mut notes = Vec::new();
+        for flag in flagged.into_iter().filter(|flag| written(&flag.path)) {
+            notes.push(flag.note(&self.cwd));
+            self.send_progress(ExecProgress::ContentFlagged {
+                path: flag.path,
+                reasons: flag.reasons,
+                blocked: false,
+            });
+        }
+        notes
+    }
+
+    /// ファイルの変更をまとめて確認し、適用してよいファイルを返す (`None` は全体)
+    async fn authorize_changes(
+        &self,
+        changes: HashMap<PathBuf, ApplyPatchFileChange>,
+    ) -> std::result::Result<Option<Vec<PathBuf>>, String> {
+        if matches!(self.approval_manager.policy(), AskForApproval::Never) {
+            return Ok(None);
+        }
+        // 確認する手段がなければ従来どおりそのまま適用する
+        let Some(handler) = self.approval_handler.clone() else {
+            return Ok(None);
+        };
+        let request = PatchApprovalRequest {
+            changes,
+            reason: None,
+        };
+        match handler.request_patch_approval(request).await {
+            PatchApprovalResponse::Approved => Ok(None),
+            PatchApprovalResponse::ApprovedFiles(paths) => Ok(Some(paths)),
+            PatchApprovalResponse::Denied => Err("denied by user".to_string()),
+        }
+    }
+
     /// ファイルを再帰的に検索
     async fn search_files_recursive(&self, dir: &PathBuf, query: &str) -> Result<Vec<String>> {
         let mut results = Vec::new();
@@ -473,8 +1325,10 @@ impl ToolExecutor {
         Ok(results)
     }
 
-    /// 設定の更新
+    /// 設定の更新。書き込み境界も相対パスを新しい cwd で解決する
+    /// (書き込み可能なディレクトリはワークスペースのまま)
     pub fn update_working_directory(&mut self, new_cwd: PathBuf) {
+        self.workspace_guard.set_cwd(&new_cwd);
         self.cwd = new_cwd;
     }
 
@@ -494,7 +1348,7 @@ pub enum ToolCall {
         timeout_ms: Option<u64>,
     },
     ReadFile {
-        path: PathBuf,
+        input: ReadFileInput,
     },
     WriteFile {
         path: PathBuf,
@@ -510,9 +1364,55 @@ pub enum ToolCall {
         query: String,
         path: Option<PathBuf>,
     },
+    Image {
+        input: ImageToolInput,
+    },
+    UpdatePlan {
+        args: UpdatePlanArgs,
+    },
+    WebFetch {
+        input: WebFetchInput,
+    },
+    WebSearch {
+        input: WebSearchInput,
+    },
+    ExecSessionOpen {
+        input: ExecCommandParams,
+    },
+    ExecSessionWrite {
+        input: WriteStdinParams,
+    },
+    ExecSessionRead {
+        input: ReadOutputParams,
+    },
+    ExecSessionClose {
+        session_id: SessionId,
+    },
+    EnvInfo,
 }
 
 impl ToolCall {
+    /// Name of the tool, as the model calls it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolCall::Shell { .. } => "shell",
+            ToolCall::ReadFile { .. } => "read_file",
+            ToolCall::WriteFile { .. } => "write_file",
+            ToolCall::ApplyPatch { .. } => "apply_patch",
+            ToolCall::ListFiles { .. } => "list_files",
+            ToolCall::SearchFiles { .. } => "search_files",
+            ToolCall::Image { .. } => "image",
+            ToolCall::UpdatePlan { .. } => "update_plan",
+            ToolCall::WebFetch { .. } => "web_fetch",
+            ToolCall::WebSearch { .. } => "web_search",
+            ToolCall::ExecSessionOpen { .. } => "exec_session_open",
+            ToolCall::ExecSessionWrite { .. } => "exec_session_write",
+            ToolCall::ExecSessionRead { .. } => "exec_session_read",
+            ToolCall::ExecSessionClose { .. } => "exec_session_close",
+            ToolCall::EnvInfo => "env_info",
+        }
+    }
+
     /// Short human-readable summary for logging or UI display.
     pub fn summary(&self) -> String {
         match self {
@@ -528,8 +1428,12 @@ impl ToolCall {
                     format!("shell {}", joined)
                 }
             }
-            ToolCall::ReadFile { path } => {
-                format!("read_file {}", path.display())
+            ToolCall::ReadFile { input } => {
+                let mut summary = format!("read_file {}", input.path.display());
+                if let Some(offset) = input.offset {
+                    summary.push_str(&format!(" from line {offset}"));
+                }
+                summary
             }
             ToolCall::WriteFile { path, content } => {
                 format!("write_file {} ({} bytes)", path.display(), content.len())
@@ -551,15 +1455,97 @@ impl ToolCall {
                     .unwrap_or_else(|| ".".to_string());
                 format!("search_files '{}' in {}", query, target)
             }
+            ToolCall::Image { input } => match &input.source {
+                ImageSource::Url(url) => format!("image download {url}"),
+                ImageSource::Generate { prompt } => format!("image generate '{prompt}'"),
+            },
+            ToolCall::UpdatePlan { args } => format!("update_plan ({} steps)", args.plan.len()),
+            ToolCall::WebFetch { input } => format!("web_fetch {}", input.url),
+            ToolCall::WebSearch { input } => format!("web_search '{}'", input.query),
+            ToolCall::ExecSessionOpen { input } => {
+                format!("exec_session_open {}", input.command.join(" "))
+            }
+            ToolCall::ExecSessionWrite { input } => format!(
+                "exec_session_write {} ({} bytes)",
+                input.session_id,
+                input.chars.len()
+            ),
+            ToolCall::ExecSessionRead { input } => {
+                format!("exec_session_read {}", input.session_id)
+            }
+            ToolCall::ExecSessionClose { session_id } => {
+                format!("exec_session_close {session_id}")
+            }
+            ToolCall::EnvInfo => "env_info".to_string(),
         }
     }
 }
 
+/// エラー報告に載せる呼び出しの先頭部分
+fn snippet(candidate: &str) -> String {
+    const MAX_CHARS: usize = 80;
+    let flat = candidate.split_whitespace().collect::<Vec<_>>().join(" ");
+    match flat.char_indices().nth(MAX_CHARS) {
+        Some((cut, _)) => format!("`{}…`", &flat[..cut]),
+        None => format!("`{flat}`"),
+    }
+}
+
+/// apply_patch の結果をファイルごとに一行で報告する
+fn describe_patch_report(report: &PatchReport, cwd: &Path) -> String {
+    let shown = |path: &Path| path.strip_prefix(cwd).unwrap_or(path).display().to_string();
+    report
+        .files
+        .iter()
+        .map(|file| {
+            let path = shown(&file.path);
+            match &file.outcome {
+                FileOutcome::Added => format!("Created file: {path}"),
+                FileOutcome::Modified { moved_to: None } if file.rebased => {
+                    format!("Updated file: {path} (merged with an earlier patch of this turn)")
+                }
+                FileOutcome::Modified { moved_to: None } => format!("Updated file: {path}"),
+                FileOutcome::Modified {
+                    moved_to: Some(dest),
+                } => format!("Moved file: {path} -> {}", shown(dest)),
+                FileOutcome::Deleted => format!("Deleted file: {path}"),
+                FileOutcome::Skipped => format!("Skipped file: {path} (rejected by user)"),
+                FileOutcome::Failed(e) => format!("Error: {e}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// moderation で引っかかったデッキと、その理由
+struct FlaggedContent {
+    path: PathBuf,
+    reasons: Vec<String>,
+}
+
+impl FlaggedContent {
+    /// モデルに伝える行
+    fn note(&self, cwd: &Path) -> String {
+        let shown = self.path.strip_prefix(cwd).unwrap_or(&self.path).display();
+        format!(
+            "Content safety: {shown} flagged ({})",
+            self.reasons.join(", ")
+        )
+    }
+}
+
+/// Markdown のデッキを `.slide/versions` に保存する。失敗してもパッチは止めない
+fn record_deck_version(workspace: &Path, path: &Path) {
+    if path.extension().is_some_and(|e| e == "md") {
+        let _ = VersionStore::new(workspace).snapshot(path);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::approval_manager::AskForApproval;
-    use crate::seatbelt::SandboxPolicy;
+    use protocol::config_types::SandboxPolicy;
 
     #[test]
     fn test_extract_tool_calls() {
@@ -589,6 +1575,37 @@ This command will display the contents of test.txt.
         }
     }
 
+    #[test]
+    fn malformed_calls_are_repaired_or_reported() {
+        let executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::ReadOnly,
+            PathBuf::from("."),
+            ShellEnvironmentPolicy::default(),
+        );
+
+        let response = "Reading both.\n{'tool': 'read_file', 'path': 'a.md',}\n<tool_call>{\"tool\": \"shell\",\n \"command\": [\"ls\", \"-la\"]}</tool_call>\n{\"tool\": \"read_file\", \"offset\": \"ten\"}\n{\"tool\": \"fly\"}\n";
+        let extracted = executor.extract_tool_calls_checked(response);
+        let summaries: Vec<String> = extracted.calls.iter().map(ToolCall::summary).collect();
+        assert_eq!(summaries.len(), 2, "{summaries:?}");
+        assert!(matches!(
+            &extracted.calls[0],
+            ToolCall::ReadFile { input } if input.path == Path::new("a.md")
+        ));
+        assert!(matches!(
+            &extracted.calls[1],
+            ToolCall::Shell { command, .. } if command == &["ls", "-la"]
+        ));
+        assert_eq!(extracted.errors.len(), 2, "{:?}", extracted.errors);
+        assert!(
+            extracted.errors[0].contains("missing required `path`")
+                && extracted.errors[0].contains("`offset` must be a number"),
+            "{}",
+            extracted.errors[0]
+        );
+        assert!(extracted.errors[1].contains("Unknown tool: fly"));
+    }
+
     #[test]
     fn test_parse_read_file_tool() {
         let executor = ToolExecutor::new(
@@ -602,10 +1619,416 @@ This command will display the contents of test.txt.
         let call = executor.parse_tool_call(json).unwrap();
 
         match call {
-            ToolCall::ReadFile { path } => {
-                assert_eq!(path, PathBuf::from("example.txt"));
+            ToolCall::ReadFile { input } => {
+                assert_eq!(input.path, PathBuf::from("example.txt"));
             }
             _ => panic!("Expected ReadFile tool call"),
         }
     }
+
+    #[tokio::test]
+    async fn plan_tool_is_accepted_under_both_names() -> Result<()> {
+        let mut executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::ReadOnly,
+            PathBuf::from("."),
+            ShellEnvironmentPolicy::default(),
+        );
+        let json = r#"{"tool": "plan", "plan": [
+            {"step": "Outline", "status": "completed"},
+            {"step": "Write slides", "status": "in_progress"}]}"#;
+        let call = executor.parse_tool_call(json)?;
+        let function = executor.tools().parse(
+            "update_plan",
+            &serde_json::from_str(
+                r#"{"plan": [{"step": "Outline", "status": "completed"},
+                {"step": "Write slides", "status": "in_progress"}]}"#,
+            )?,
+        )?;
+        assert_eq!(call.summary(), function.summary());
+
+        let output = executor.execute_tool_call(call).await?;
+        assert_eq!(
+            output,
+            "Plan updated (1/2 steps completed); in progress: Write slides"
+        );
+        Ok(())
+    }
+
+    /// Approves only the second command of any plan.
+    struct SecondOnly;
+
+    #[async_trait::async_trait]
+    impl ApprovalHandler for SecondOnly {
+        async fn request_approval(&self, _request: ApprovalRequest) -> ApprovalResponse {
+            ApprovalResponse::Denied
+        }
+        async fn request_plan_approval(
+            &self,
+            _request: PlanApprovalRequest,
+        ) -> PlanApprovalResponse {
+            PlanApprovalResponse::ApprovedCommands(vec![1])
+        }
+        async fn request_patch_approval(
+            &self,
+            _request: PatchApprovalRequest,
+        ) -> PatchApprovalResponse {
+            PatchApprovalResponse::Denied
+        }
+    }
+
+    #[tokio::test]
+    async fn plan_runs_only_the_approved_commands() -> Result<()> {
+        let mut executor = ToolExecutor::new(
+            AskForApproval::OnRequest,
+            SandboxPolicy::ReadOnly,
+            std::env::temp_dir(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_approval_handler(Arc::new(SecondOnly));
+        let shell = |command: &str| ToolCall::Shell {
+            command: vec![command.to_string()],
+            working_dir: None,
+            with_escalated_permissions: false,
+            justification: None,
+            timeout_ms: None,
+        };
+        let calls = vec![shell("true"), shell("uname")];
+        executor.approve_plan(&calls).await;
+
+        let mut outputs = Vec::new();
+        for call in calls {
+            outputs.push(executor.execute_tool_call(call).await?);
+        }
+        assert!(outputs[0].starts_with("Command skipped"));
+        assert!(outputs[1].contains("exited with code 0"));
+        Ok(())
+    }
+
+    /// Approves every patch and keeps the changes it was shown.
+    #[derive(Default)]
+    struct ApprovePatches(Mutex<Vec<HashMap<PathBuf, ApplyPatchFileChange>>>);
+
+    #[async_trait::async_trait]
+    impl ApprovalHandler for ApprovePatches {
+        async fn request_approval(&self, _request: ApprovalRequest) -> ApprovalResponse {
+            ApprovalResponse::Denied
+        }
+        async fn request_plan_approval(
+            &self,
+            _request: PlanApprovalRequest,
+        ) -> PlanApprovalResponse {
+            PlanApprovalResponse::Denied
+        }
+        async fn request_patch_approval(
+            &self,
+            request: PatchApprovalRequest,
+        ) -> PatchApprovalResponse {
+            if let Ok(mut seen) = self.0.lock() {
+                seen.push(request.changes);
+            }
+            PatchApprovalResponse::Approved
+        }
+    }
+
+    #[tokio::test]
+    async fn write_file_goes_through_patch_approval() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let notes = tmp.path().join("notes.txt");
+        std::fs::write(&notes, "one\ntwo\n")?;
+        let handler = Arc::new(ApprovePatches::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut executor = ToolExecutor::new(
+            AskForApproval::OnRequest,
+            SandboxPolicy::DangerFullAccess,
+            tmp.path().to_path_buf(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_approval_handler(handler.clone())
+        .with_progress_sender(tx)
+        .with_turn_snapshot(Arc::new(Mutex::new(TurnSnapshot::default())));
+
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("notes.txt"),
+                content: "one\n2\n".to_string(),
+            })
+            .await?;
+        assert!(output.contains("Successfully wrote"), "{output}");
+        assert_eq!(std::fs::read_to_string(&notes)?, "one\n2\n");
+
+        let seen = handler
+            .0
+            .lock()
+            .map(|seen| seen.clone())
+            .unwrap_or_default();
+        assert!(matches!(
+            seen.as_slice(),
+            [changes] if matches!(
+                changes.get(&notes),
+                Some(ApplyPatchFileChange::Update { unified_diff, .. })
+                    if unified_diff.contains("-two\n+2\n")
+            )
+        ));
+
+        let mut progress = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            progress.push(event);
+        }
+        assert!(matches!(
+            progress.first(),
+            Some(ExecProgress::PatchApplyBegin)
+        ));
+        assert!(progress.iter().any(|event| matches!(
+            event,
+            ExecProgress::PatchApplyEnd { success: true, total, .. }
+                if *total == DiffStat { added: 1, removed: 1 }
+        )));
+        assert!(progress.iter().any(|event| matches!(
+            event,
+            ExecProgress::TurnDiff { unified_diff }
+                if unified_diff.starts_with("diff --git a/notes.txt b/notes.txt\n")
+        )));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn writes_after_a_cd_follow_the_new_cwd_within_the_workspace() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let workspace = tmp.path().join("workspace");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir_all(workspace.join("slides"))?;
+        std::fs::create_dir_all(&outside)?;
+        let mut executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: Vec::new(),
+                network_access: false,
+                exclude_tmpdir_env_var: true,
+                exclude_system_tmp: true,
+            },
+            workspace.clone(),
+            ShellEnvironmentPolicy::default(),
+        );
+        let write = |path: &str| ToolCall::WriteFile {
+            path: PathBuf::from(path),
+            content: "# Talk\n".to_string(),
+        };
+        let add = |path: &str| ToolCall::ApplyPatch {
+            input: format!("*** Begin Patch\n*** Add File: {path}\n+# Talk\n*** End Patch"),
+        };
+
+        executor.update_working_directory(workspace.join("slides"));
+        let output = executor.execute_tool_call(write("talk.md")).await?;
+        assert!(output.contains("Successfully wrote"), "{output}");
+        assert!(workspace.join("slides/talk.md").is_file());
+        assert!(!workspace.join("talk.md").exists());
+        // Decks are kept in the workspace's history, not the process cwd's
+        let versions = VersionStore::new(&workspace).list(&workspace.join("slides/talk.md"))?;
+        assert_eq!(versions.len(), 1);
+        executor.execute_tool_call(add("patched.md")).await?;
+        assert!(workspace.join("slides/patched.md").is_file());
+
+        executor.update_working_directory(outside.clone());
+        let output = executor.execute_tool_call(write("leak.md")).await?;
+        assert!(output.starts_with("Proposed Change rejected"), "{output}");
+        let output = executor.execute_tool_call(add("leak.md")).await?;
+        assert!(output.starts_with("Proposed Change rejected"), "{output}");
+        assert!(!outside.join("leak.md").exists());
+        assert!(!workspace.join("leak.md").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn decks_citing_fetched_pages_get_a_references_slide() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let sources = FetchedSources::default();
+        sources.record(slide_common::sources::Source::fetched(
+            "https://example.com/q3",
+            Some("Q3 report".to_string()),
+        ));
+        let mut executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+            tmp.path().to_path_buf(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_fetched_sources(sources);
+
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("talk.md"),
+                content: "# Q3\n\n## Growth\n- Up 12% ([source](https://example.com/q3))\n"
+                    .to_string(),
+            })
+            .await?;
+        assert!(output.ends_with("talk.md cites 1 page(s), listed on its References slide"));
+        let deck = tmp.path().join("talk.md");
+        assert!(std::fs::read_to_string(&deck)?.ends_with(
+            "## References\n<!-- _class: references -->\n\n1. [Q3 report](https://example.com/q3)\n"
+        ));
+        let cited = SourceMap::load(&deck).map(|map| map.slides.len());
+        assert_eq!(cited, Some(1));
+
+        // Files that cite nothing are left alone
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("README.md"),
+                content: "# Notes\n".to_string(),
+            })
+            .await?;
+        assert!(!output.contains("Sources:"), "{output}");
+        assert!(!SourceMap::path_for(&tmp.path().join("README.md")).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blocked_deck_content_is_not_written() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+            tmp.path().to_path_buf(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_progress_sender(tx)
+        .with_moderation(ModerationConfig {
+            action: ModerationAction::Block,
+            blocked_terms: vec!["gore".to_string()],
+            ..Default::default()
+        });
+
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("talk.md"),
+                content: "## Films\n- Gore and more\n".to_string(),
+            })
+            .await?;
+        assert!(output
+            .starts_with("Proposed Change rejected\nContent safety: talk.md flagged (\"gore\")"));
+        assert!(!tmp.path().join("talk.md").exists());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ExecProgress::ContentFlagged { reasons, blocked: true, .. }) if reasons == ["\"gore\""]
+        ));
+
+        // Other files are not decks
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("notes.txt"),
+                content: "gore".to_string(),
+            })
+            .await?;
+        assert!(output.contains("Successfully wrote"), "{output}");
+        Ok(())
+    }
+
+    /// パッチをすべて拒否する
+    struct DenyPatches;
+
+    #[async_trait::async_trait]
+    impl ApprovalHandler for DenyPatches {
+        async fn request_approval(&self, _request: ApprovalRequest) -> ApprovalResponse {
+            ApprovalResponse::Denied
+        }
+        async fn request_plan_approval(
+            &self,
+            _request: PlanApprovalRequest,
+        ) -> PlanApprovalResponse {
+            PlanApprovalResponse::Denied
+        }
+        async fn request_patch_approval(
+            &self,
+            _request: PatchApprovalRequest,
+        ) -> PatchApprovalResponse {
+            PatchApprovalResponse::Denied
+        }
+    }
+
+    #[tokio::test]
+    async fn flagged_content_is_reported_once_it_is_written() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let executor = ToolExecutor::new(
+            AskForApproval::OnRequest,
+            SandboxPolicy::DangerFullAccess,
+            tmp.path().to_path_buf(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_progress_sender(tx)
+        .with_moderation(ModerationConfig {
+            action: ModerationAction::Flag,
+            blocked_terms: vec!["gore".to_string()],
+            ..Default::default()
+        });
+        let write = || ToolCall::WriteFile {
+            path: PathBuf::from("talk.md"),
+            content: "## Films\n- Gore and more\n".to_string(),
+        };
+        let flags = |rx: &mut mpsc::UnboundedReceiver<ExecProgress>| {
+            let mut flags = Vec::new();
+            while let Ok(event) = rx.try_recv() {
+                if let ExecProgress::ContentFlagged { blocked, .. } = event {
+                    flags.push(blocked);
+                }
+            }
+            flags
+        };
+
+        let mut executor = executor.with_approval_handler(Arc::new(DenyPatches));
+        let output = executor.execute_tool_call(write()).await?;
+        assert!(output.starts_with("Proposed Change rejected"), "{output}");
+        assert!(flags(&mut rx).is_empty());
+
+        let mut executor = executor.with_approval_handler(Arc::new(ApprovePatches::default()));
+        let output = executor.execute_tool_call(write()).await?;
+        assert!(
+            output.ends_with("Content safety: talk.md flagged (\"gore\")"),
+            "{output}"
+        );
+        assert_eq!(flags(&mut rx), [false]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn writes_past_the_byte_limit_stop_the_next_call() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let mut executor = ToolExecutor::new(
+            AskForApproval::Never,
+            SandboxPolicy::DangerFullAccess,
+            tmp.path().to_path_buf(),
+            ShellEnvironmentPolicy::default(),
+        )
+        .with_guardrails(GuardrailConfig {
+            max_tool_calls: 0,
+            max_exec_secs: 0,
+            max_bytes_written: 4,
+        });
+
+        let output = executor
+            .execute_tool_call(ToolCall::WriteFile {
+                path: PathBuf::from("notes.txt"),
+                content: "12345".to_string(),
+            })
+            .await?;
+        assert!(output.contains("Successfully wrote"), "{output}");
+
+        let refused = executor
+            .execute_tool_call(ToolCall::ListFiles { path: None })
+            .await;
+        let trip = refused
+            .err()
+            .and_then(|e| e.downcast_ref::<GuardrailTrip>().copied());
+        assert_eq!(
+            trip,
+            Some(GuardrailTrip {
+                limit: crate::guardrails::GuardrailLimit::BytesWritten,
+                used: 5,
+                max: 4
+            })
+        );
+        assert_eq!(executor.check_guardrails(), trip.map_or(Ok(()), Err));
+        Ok(())
+    }
 }
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// Directory (relative to the workspace) where slide images are stored.
+pub const ASSETS_DIR: &str = "slides/assets";
+
+/// Where the image comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageSource {
+    /// Download a user-provided URL
+    Url(String),
+    /// Ask the image generation API for a new image
+    Generate { prompt: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageToolInput {
+    pub source: ImageSource,
+    /// Alt text used in the markdown reference
+    pub alt: String,
+    /// Deck to insert the reference into
+    pub deck: Option<PathBuf>,
+    /// Heading text of the slide that should receive the image (default: last slide)
+    pub slide: Option<String>,
+    /// Base file name for the asset, without extension
+    pub name: Option<String>,
+}
+
+impl ImageToolInput {
+    /// Command-like description used for approval prompts.
+    pub fn approval_command(&self) -> Vec<String> {
+        match &self.source {
+            ImageSource::Url(url) => vec!["image".into(), "download".into(), url.clone()],
+            ImageSource::Generate { prompt } => {
+                vec!["image".into(), "generate".into(), prompt.clone()]
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageToolOutput {
+    pub asset_path: PathBuf,
+    pub markdown: String,
+    /// The deck with the reference inserted, for the caller to write
+    pub deck: Option<DeckEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckEdit {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Fetch or generate the image, store it under [`ASSETS_DIR`] and, when a
+/// deck is given, return it with the reference inserted. Callers are
+/// responsible for network policy and approval checks, and for writing the
+/// deck like any other edit.
+pub async fn tool_image(input: &ImageToolInput, cwd: &Path) -> Result<ImageToolOutput> {
+    let deck = input.deck.as_ref().map(|d| {
+        if d.is_absolute() {
+            d.clone()
+        } else {
+            cwd.join(d)
+        }
+    });
+    // Read the deck before spending a download or a generation on it
+    let content = match &deck {
+        Some(deck_path) => Some(read_deck(deck_path).await?),
+        None => None,
+    };
+
+    let bytes = match &input.source {
+        ImageSource::Url(url) => slide_chatgpt::images::download_image(url).await?,
+        ImageSource::Generate { prompt } => {
+            let api_key = slide_common::auth::openai_api_key().ok_or_else(|| {
+                anyhow!("an OpenAI key is required to generate images (run `slide login`)")
+            })?;
+            slide_chatgpt::images::generate_image(&api_key, prompt, "1024x1024").await?
+        }
+    };
+    let ext = sniff_image_extension(&bytes)
+        .ok_or_else(|| anyhow!("downloaded content is not a supported image"))?;
+
+    let assets_dir = cwd.join(ASSETS_DIR);
+    tokio::fs::create_dir_all(&assets_dir).await?;
+    let stem = asset_stem(input);
+    let asset_path = unique_path(&assets_dir, &stem, ext);
+    tokio::fs::write(&asset_path, &bytes).await?;
+
+    let reference_base = deck
+        .as_ref()
+        .and_then(|d| d.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| cwd.to_path_buf());
+    let rel = relative_path(&asset_path, &reference_base);
+    let markdown = format!("![{}]({})", input.alt, rel.display());
+
+    let deck = deck.zip(content).map(|(path, content)| DeckEdit {
+        path,
+        content: insert_image_reference(&content, &markdown, input.slide.as_deref()),
+    });
+
+    Ok(ImageToolOutput {
+        asset_path,
+        markdown,
+        deck,
+    })
+}
+
+/// Content of the deck at `path`; a deck that does not exist yet is empty.
+async fn read_deck(path: &Path) -> Result<String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Insert `reference` at the end of the slide whose `## ` heading contains
+/// `slide`, or at the end of the deck when no slide matches.
+pub fn insert_image_reference(deck: &str, reference: &str, slide: Option<&str>) -> String {
+    let lines: Vec<&str> = deck.lines().collect();
+    let target = slide.and_then(|needle| {
+        let needle = needle.to_lowercase();
+        lines
+            .iter()
+            .position(|l| l.starts_with("## ") && l.to_lowercase().contains(&needle))
+    });
+    let insert_at = match target {
+        Some(start) => lines
+            .iter()
+            .enumerate()
+            .skip(start + 1)
+            .find(|(_, l)| l.starts_with("## ") || l.trim() == "---")
+            .map(|(i, _)| i)
+            .unwrap_or(lines.len()),
+        None => lines.len(),
+    };
+    // Keep a blank line around the image without piling up blank lines.
+    let mut before: Vec<&str> = lines[..insert_at].to_vec();
+    while before.last().is_some_and(|l| l.trim().is_empty()) {
+        before.pop();
+    }
+    let mut out = String::new();
+    for l in &before {
+        out.push_str(l);
+        out.push('\n');
+    }
+    if !before.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(reference);
+    out.push('\n');
+    if insert_at < lines.len() {
+        out.push('\n');
+        for l in &lines[insert_at..] {
+            out.push_str(l);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn sniff_image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() > 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+        if head.contains("<svg") {
+            Some("svg")
+        } else {
+            None
+        }
+    }
+}
+
+fn asset_stem(input: &ImageToolInput) -> String {
+    let raw = input.name.clone().unwrap_or_else(|| match &input.source {
+        ImageSource::Url(url) => url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.rsplit('/').next())
+            .and_then(|f| f.split('.').next())
+            .unwrap_or_default()
+            .to_string(),
+        ImageSource::Generate { prompt } => prompt.clone(),
+    });
+    let slug = slide_common::create_slug(&raw);
+    if slug.is_empty() {
+        "image".to_string()
+    } else {
+        slug
+    }
+}
+
+fn unique_path(dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    let mut candidate = dir.join(format!("{stem}.{ext}"));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = dir.join(format!("{stem}-{n}.{ext}"));
+        n += 1;
+    }
+    candidate
+}
+
+/// `path` expressed relative to `base`, both assumed absolute or both relative.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path
+        .iter()
+        .zip(base.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut rel = PathBuf::new();
+    for _ in common..base.len() {
+        rel.push("..");
+    }
+    for c in &path[common..] {
+        rel.push(c.as_os_str());
+    }
+    rel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserts_at_end_of_matching_slide() {
+        let deck = "# Deck\n\n## Intro\n- a\n\n## Details\n- b\n";
+        let out = insert_image_reference(deck, "![x](assets/x.png)", Some("intro"));
+        assert_eq!(
+            out,
+            "# Deck\n\n## Intro\n- a\n\n![x](assets/x.png)\n\n## Details\n- b\n"
+        );
+    }
+
+    #[test]
+    fn appends_when_no_slide_matches() {
+        let out = insert_image_reference("## A\n- a\n", "![x](x.png)", None);
+        assert_eq!(out, "## A\n- a\n\n![x](x.png)\n");
+    }
+
+    #[test]
+    fn relative_to_deck_directory() {
+        let rel = relative_path(
+            Path::new("/w/slides/assets/cat.png"),
+            Path::new("/w/slides"),
+        );
+        assert_eq!(rel, PathBuf::from("assets/cat.png"));
+        let rel = relative_path(Path::new("/w/slides/assets/cat.png"), Path::new("/w/talks"));
+        assert_eq!(rel, PathBuf::from("../slides/assets/cat.png"));
+    }
+
+    #[tokio::test]
+    async fn decks_that_cannot_be_read_are_not_replaced() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let deck = tmp.path().join("talk.md");
+        std::fs::write(&deck, b"## Intro\n\xff\xfe")?;
+        let input = ImageToolInput {
+            source: ImageSource::Url("http://127.0.0.1:9/cat.png".to_string()),
+            alt: "cat".to_string(),
+            deck: Some(PathBuf::from("talk.md")),
+            slide: None,
+            name: None,
+        };
+        let err = tool_image(&input, tmp.path()).await.err();
+        assert!(err.is_some_and(|e| e.to_string().starts_with("failed to read")));
+        assert_eq!(std::fs::read(&deck)?, b"## Intro\n\xff\xfe");
+
+        assert_eq!(read_deck(&tmp.path().join("new.md")).await?, "");
+        assert!(read_deck(tmp.path()).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_non_images() {
+        assert_eq!(sniff_image_extension(b"<html>nope</html>"), None);
+        assert_eq!(sniff_image_extension(b"\x89PNG\r\n\x1a\n...."), Some("png"));
+    }
+}
@@ -0,0 +1,335 @@
+//! `read_file` tool: a window of lines from a text file, capped in lines and
+//! bytes so large sources can be paged through, and a hex preview instead of
+//! the contents of binary files. A file read without `offset` or `limit`
+//! that does not fit is returned as an [outline](crate::file_outline) of its
+//! functions or slides when there is one, so the model can pick a range.
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::file_outline::{outline, OutlineEntry};
+
+/// Lines returned when the call gives no `limit`
+pub const DEFAULT_LINE_LIMIT: usize = 2000;
+/// Bytes of content returned when the call gives no `max_bytes`
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+/// Leading bytes inspected for NUL bytes
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+/// Bytes shown in the hex preview of a binary file
+const HEX_PREVIEW_BYTES: usize = 256;
+/// Outline entries shown before the rest is counted
+const MAX_OUTLINE_ENTRIES: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadFileInput {
+    pub path: PathBuf,
+    /// 1-based line to start from (default: 1)
+    pub offset: Option<usize>,
+    /// Maximum number of lines (default: [`DEFAULT_LINE_LIMIT`])
+    pub limit: Option<usize>,
+    /// Maximum bytes of content (default: [`DEFAULT_MAX_BYTES`])
+    pub max_bytes: Option<usize>,
+}
+
+impl ReadFileInput {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            offset: None,
+            limit: None,
+            max_bytes: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadFileContent {
+    Text {
+        /// 1-based first and last line returned; `end_line` is 0 for an
+        /// empty file
+        start_line: usize,
+        end_line: usize,
+        total_lines: usize,
+        text: String,
+        /// Whether the file continues after `end_line`, or that line was cut
+        truncated: bool,
+    },
+    Binary {
+        /// `xxd`-style dump of the first bytes
+        preview: String,
+    },
+    /// Functions, types or slides of a file too large to return whole
+    Outline {
+        total_lines: usize,
+        entries: Vec<OutlineEntry>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadFileOutput {
+    pub path: PathBuf,
+    /// File size in bytes
+    pub size: u64,
+    pub content: ReadFileContent,
+}
+
+impl ReadFileOutput {
+    /// Tool result in the executor's `Explored` format.
+    pub fn message(&self) -> String {
+        let path = self.path.display();
+        match &self.content {
+            ReadFileContent::Text {
+                start_line,
+                end_line,
+                total_lines,
+                text,
+                truncated,
+            } => {
+                let mut message = format!(
+                    "Explored\n- Read {path} (lines {start_line}-{end_line} of {total_lines}, {} bytes)\n\nFile content:\n{text}",
+                    self.size
+                );
+                if *truncated {
+                    message.push_str(&format!(
+                        "\n[truncated: continue with offset={}, or raise limit/max_bytes]",
+                        end_line + 1
+                    ));
+                }
+                message
+            }
+            ReadFileContent::Binary { preview } => format!(
+                "Explored\n- Read {path} (binary, {} bytes; contents not shown)\n\nFirst bytes:\n{preview}",
+                self.size
+            ),
+            ReadFileContent::Outline {
+                total_lines,
+                entries,
+            } => {
+                let mut message = format!(
+                    "Explored\n- Read {path} (outline of {total_lines} lines, {} bytes)\n\nThe file is too large to return whole. Outline by line range:\n",
+                    self.size
+                );
+                for entry in entries.iter().take(MAX_OUTLINE_ENTRIES) {
+                    let _ = writeln!(
+                        message,
+                        "{}{}-{}  {}",
+                        "  ".repeat(entry.depth),
+                        entry.start_line,
+                        entry.end_line,
+                        entry.label
+                    );
+                }
+                if entries.len() > MAX_OUTLINE_ENTRIES {
+                    let _ = writeln!(
+                        message,
+                        "… {} more",
+                        entries.len() - MAX_OUTLINE_ENTRIES
+                    );
+                }
+                message.push_str(
+                    "\nRead a part with offset=<first line> and limit=<number of lines>.",
+                );
+                message
+            }
+        }
+    }
+}
+
+/// Read the window of `input.path` (relative paths are taken from `cwd`).
+pub async fn tool_read_file(input: &ReadFileInput, cwd: &Path) -> Result<ReadFileOutput> {
+    let path = cwd.join(&input.path);
+    let bytes = tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let mut content = read_window(
+        &bytes,
+        input.offset.unwrap_or(1),
+        input.limit.unwrap_or(DEFAULT_LINE_LIMIT),
+        input.max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
+    )?;
+    // An explicit range is returned as asked; the whole file only when it fits
+    if let ReadFileContent::Text {
+        total_lines,
+        truncated: true,
+        ..
+    } = content
+    {
+        let whole_file = input.offset.is_none() && input.limit.is_none();
+        let entries = std::str::from_utf8(&bytes)
+            .ok()
+            .filter(|_| whole_file)
+            .and_then(|text| outline(&path, text));
+        if let Some(entries) = entries {
+            content = ReadFileContent::Outline {
+                total_lines,
+                entries,
+            };
+        }
+    }
+    Ok(ReadFileOutput {
+        path,
+        size: bytes.len() as u64,
+        content,
+    })
+}
+
+fn read_window(
+    bytes: &[u8],
+    offset: usize,
+    limit: usize,
+    max_bytes: usize,
+) -> Result<ReadFileContent> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) if !sniff.contains(&0) => text,
+        _ => {
+            return Ok(ReadFileContent::Binary {
+                preview: hex_preview(&bytes[..bytes.len().min(HEX_PREVIEW_BYTES)]),
+            })
+        }
+    };
+
+    let offset = offset.max(1);
+    let total_lines = text.lines().count();
+    if offset > total_lines.max(1) {
+        bail!("offset {offset} is past the end of the file ({total_lines} lines)");
+    }
+
+    let mut window = String::new();
+    let mut end_line = offset - 1;
+    let mut cut = false;
+    for line in text.lines().skip(offset - 1).take(limit) {
+        let needed = line.len() + usize::from(!window.is_empty());
+        if window.len() + needed > max_bytes {
+            // A single line over the budget is cut rather than skipped
+            if window.is_empty() {
+                window.push_str(truncate_at_char(line, max_bytes));
+                end_line += 1;
+            }
+            cut = true;
+            break;
+        }
+        if !window.is_empty() {
+            window.push('\n');
+        }
+        window.push_str(line);
+        end_line += 1;
+    }
+
+    Ok(ReadFileContent::Text {
+        start_line: offset,
+        end_line,
+        total_lines,
+        text: window,
+        truncated: cut || end_line < total_lines,
+    })
+}
+
+pub(crate) fn truncate_at_char(s: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "{:08x}  {:<47}  |{ascii}|", i * 16, hex.join(" "));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_window(content: &ReadFileContent) -> (usize, usize, &str, bool) {
+        match content {
+            ReadFileContent::Text {
+                start_line,
+                end_line,
+                text,
+                truncated,
+                ..
+            } => (*start_line, *end_line, text.as_str(), *truncated),
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn windows_are_limited_by_lines_and_bytes() -> Result<()> {
+        let file = b"one\ntwo\nthree\nfour\n";
+
+        let all = read_window(file, 1, 100, 1000)?;
+        assert_eq!(text_window(&all), (1, 4, "one\ntwo\nthree\nfour", false));
+
+        let middle = read_window(file, 2, 2, 1000)?;
+        assert_eq!(text_window(&middle), (2, 3, "two\nthree", true));
+
+        let by_bytes = read_window(file, 1, 100, 8)?;
+        assert_eq!(text_window(&by_bytes), (1, 2, "one\ntwo", true));
+
+        let long_line = read_window("ééé\n".as_bytes(), 1, 100, 3)?;
+        assert_eq!(text_window(&long_line), (1, 1, "é", true));
+
+        assert!(read_window(file, 9, 1, 1000).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn binary_files_get_a_hex_preview() -> Result<()> {
+        let content = read_window(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", 1, 100, 1000)?;
+        assert_eq!(
+            content,
+            ReadFileContent::Binary {
+                preview: "00000000  89 50 4e 47 0d 0a 1a 0a 00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n"
+                    .to_string()
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn large_decks_are_outlined_unless_a_range_is_asked() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let mut deck = String::from("# Talk\n");
+        for i in 1..=3 {
+            deck.push_str(&format!("\n## Part {i}\n"));
+            for _ in 0..1000 {
+                deck.push_str("- point\n");
+            }
+        }
+        std::fs::write(tmp.path().join("talk.md"), &deck)?;
+
+        let whole = tool_read_file(&ReadFileInput::new("talk.md"), tmp.path()).await?;
+        let message = whole.message();
+        assert!(message.contains("(outline of 3007 lines"), "{message}");
+        assert!(message.contains("\n1-2  Slide 1: Talk\n3-1004  Slide 2: Part 1\n"));
+        assert!(message.contains("offset=<first line>"));
+
+        let range = ReadFileInput {
+            offset: Some(3),
+            limit: Some(2),
+            ..ReadFileInput::new("talk.md")
+        };
+        let part = tool_read_file(&range, tmp.path()).await?;
+        assert_eq!(
+            text_window(&part.content),
+            (3, 4, "## Part 1\n- point", true)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,1049 @@
+//! Every tool the agent offers, defined once: its name, JSON schema and how
+//! its arguments become a [`ToolCall`]. The function-calling tool list, the
+//! MCP server and the parser for tool calls embedded in text all read from a
+//! [`ToolRegistry`].
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::exec_command::{
+    ExecCommandParams, ReadOutputParams, SessionId, WriteStdinParams, DEFAULT_MAX_OUTPUT_BYTES,
+    DEFAULT_YIELD_TIME_MS, MAX_SESSIONS,
+};
+use crate::openai_tools::{
+    ConfigShellToolType, JsonSchema, OpenAiTool, ResponsesApiTool, ToolsConfig,
+};
+use crate::tool_executor::{ToolCall, ToolExecutor};
+use crate::tool_image::{ImageSource, ImageToolInput};
+use crate::tool_read_file::{ReadFileInput, DEFAULT_LINE_LIMIT, DEFAULT_MAX_BYTES};
+use crate::tool_web_fetch::{WebFetchInput, WebSearchInput, DEFAULT_SEARCH_RESULTS};
+use protocol::config_types::SandboxPolicy;
+
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Other names the model may use for the tool
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn description(&self) -> String;
+
+    /// Whether the tool creates or changes files; read-only sessions leave
+    /// these tools out
+    fn writes_files(&self) -> bool {
+        false
+    }
+
+    /// Schema of the arguments object
+    fn parameters(&self) -> JsonSchema;
+
+    /// Turn call arguments into a [`ToolCall`]. Calls embedded in text carry
+    /// the same keys plus `"tool"`.
+    fn parse(&self, args: &Value) -> Result<ToolCall>;
+
+    async fn execute(&self, executor: &mut ToolExecutor, args: &Value) -> Result<String> {
+        executor.execute_tool_call(self.parse(args)?).await
+    }
+
+    /// Definition sent to the Responses API
+    fn to_openai_tool(&self) -> OpenAiTool {
+        OpenAiTool::Function(ResponsesApiTool {
+            name: self.name().to_string(),
+            description: self.description(),
+            strict: false,
+            parameters: self.parameters(),
+        })
+    }
+
+    /// `parameters` as plain JSON, for MCP `tools/list`
+    fn input_schema(&self) -> Value {
+        serde_json::to_value(self.parameters()).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All built-in tools, with the shell tool described for `sandbox_policy`.
+    pub fn builtin(sandbox_policy: &SandboxPolicy) -> Self {
+        let mut registry = Self::new();
+        registry.register(ShellTool::for_sandbox(sandbox_policy.clone()));
+        registry.register(ReadFileTool);
+        registry.register(WriteFileTool);
+        registry.register(ApplyPatchTool);
+        registry.register(ListFilesTool);
+        registry.register(SearchFilesTool);
+        registry.register(ImageTool);
+        registry.register(UpdatePlanTool);
+        registry.register(WebFetchTool);
+        registry.register(WebSearchTool);
+        registry.register(EnvInfoTool);
+        registry.register_exec_session_tools();
+        registry
+    }
+
+    /// The tools offered to the model through function calling.
+    pub fn for_config(config: &ToolsConfig) -> Self {
+        let mut registry = Self::new();
+        match &config.shell_type {
+            ConfigShellToolType::ShellWithRequest { sandbox_policy } => {
+                registry.register(ShellTool::for_sandbox(sandbox_policy.clone()));
+            }
+            ConfigShellToolType::StreamableShell => {
+                registry.register(ShellTool::default());
+                registry.register_exec_session_tools();
+            }
+            ConfigShellToolType::DefaultShell | ConfigShellToolType::LocalShell => {
+                registry.register(ShellTool::default())
+            }
+        }
+        if config.include_plan_tool {
+            registry.register(UpdatePlanTool);
+        }
+        if config.include_apply_patch_tool {
+            registry.register(ApplyPatchTool);
+        }
+        if config.include_image_tool {
+            registry.register(ImageTool);
+        }
+        if config.include_read_file_tool {
+            registry.register(ReadFileTool);
+        }
+        if config.include_web_fetch_tool {
+            registry.register(WebFetchTool);
+        }
+        if config.include_web_search_request {
+            registry.register(WebSearchTool);
+        }
+        registry.register(EnvInfoTool);
+        registry
+    }
+
+    /// The same tools without those that write files, for read-only
+    /// sessions: the model is never offered them and calls to them do not
+    /// parse.
+    pub fn read_only(mut self) -> Self {
+        self.tools.retain(|t| !t.writes_files());
+        self
+    }
+
+    /// Add `tool`, replacing a registered tool of the same name.
+    pub fn register(&mut self, tool: impl Tool + 'static) {
+        self.tools.retain(|t| t.name() != tool.name());
+        self.tools.push(Arc::new(tool));
+    }
+
+    /// `exec_session_open/write/read/close`, see [`crate::exec_command`]
+    fn register_exec_session_tools(&mut self) {
+        self.register(ExecSessionOpenTool);
+        self.register(ExecSessionWriteTool);
+        self.register(ExecSessionReadTool);
+        self.register(ExecSessionCloseTool);
+    }
+
+    /// Tool registered under `name` or one of its aliases.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools
+            .iter()
+            .find(|t| t.name() == name || t.aliases().contains(&name))
+            .cloned()
+    }
+
+    pub fn tools(&self) -> impl Iterator<Item = &Arc<dyn Tool>> {
+        self.tools.iter()
+    }
+
+    pub fn parse(&self, name: &str, args: &Value) -> Result<ToolCall> {
+        self.get(name)
+            .ok_or_else(|| anyhow!("Unknown tool: {name}"))?
+            .parse(args)
+    }
+
+    pub fn openai_tools(&self) -> Vec<OpenAiTool> {
+        self.tools.iter().map(|t| t.to_openai_tool()).collect()
+    }
+}
+
+fn string_param(description: &str) -> JsonSchema {
+    JsonSchema::String {
+        description: Some(description.to_string()),
+    }
+}
+
+fn number_param(description: &str) -> JsonSchema {
+    JsonSchema::Number {
+        description: Some(description.to_string()),
+    }
+}
+
+fn object_schema(properties: Vec<(&str, JsonSchema)>, required: &[&str]) -> JsonSchema {
+    JsonSchema::Object {
+        properties: properties
+            .into_iter()
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect::<BTreeMap<_, _>>(),
+        required: Some(required.iter().map(|r| r.to_string()).collect()),
+        additional_properties: Some(false),
+    }
+}
+
+fn required_str<'a>(args: &'a Value, key: &str, what: &str) -> Result<&'a str> {
+    args[key].as_str().ok_or_else(|| anyhow!("Missing {what}"))
+}
+
+/// A command given as an argv array or as one string to split
+fn command_arg(args: &Value) -> Result<Vec<String>> {
+    if let Some(cmd_array) = args["command"].as_array() {
+        Ok(cmd_array
+            .iter()
+            .map(|v| v.as_str().unwrap_or_default().to_string())
+            .collect())
+    } else if let Some(cmd_str) = args["command"].as_str() {
+        // シンプルな文字列の場合は分割
+        Ok(crate::parse_command::parse_command_string(cmd_str))
+    } else {
+        Err(anyhow!("Invalid command format"))
+    }
+}
+
+/// `shell`; with a sandbox policy the description names it and, under
+/// workspace-write, escalation can be requested.
+#[derive(Debug, Clone, Default)]
+pub struct ShellTool {
+    sandbox_policy: Option<SandboxPolicy>,
+}
+
+impl ShellTool {
+    pub fn for_sandbox(sandbox_policy: SandboxPolicy) -> Self {
+        Self {
+            sandbox_policy: Some(sandbox_policy),
+        }
+    }
+
+    fn can_escalate(&self) -> bool {
+        matches!(
+            self.sandbox_policy,
+            Some(SandboxPolicy::WorkspaceWrite { .. })
+        )
+    }
+}
+
+impl Tool for ShellTool {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn description(&self) -> String {
+        let Some(policy) = &self.sandbox_policy else {
+            return "Runs a shell command and returns its output".to_string();
+        };
+        let label = policy.label();
+        let hint = if self.can_escalate() {
+            "Use with_escalated_permissions=true for commands that need to access outside workspace."
+        } else {
+            ""
+        };
+        format!("Runs a shell command with sandbox policy: {label}. {hint}")
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        let mut properties = vec![
+            (
+                "command",
+                JsonSchema::Array {
+                    items: Box::new(JsonSchema::String { description: None }),
+                    description: Some("The command to execute".to_string()),
+                },
+            ),
+            (
+                "workdir",
+                string_param("The working directory to execute the command in"),
+            ),
+            (
+                "timeout_ms",
+                number_param("The timeout for the command in milliseconds"),
+            ),
+        ];
+        if self.can_escalate() {
+            properties.push((
+                "with_escalated_permissions",
+                JsonSchema::Boolean {
+                    description: Some("Whether to request escalated permissions. Set to true if command needs to be run without sandbox restrictions".to_string()),
+                },
+            ));
+            properties.push((
+                "justification",
+                string_param("Justification for why this command needs to be run"),
+            ));
+        }
+        object_schema(properties, &["command"])
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        let command = command_arg(args)?;
+        // テキスト形式では working_dir が使われてきた
+        let working_dir = args["workdir"]
+            .as_str()
+            .or_else(|| args["working_dir"].as_str())
+            .map(PathBuf::from);
+
+        Ok(ToolCall::Shell {
+            command,
+            working_dir,
+            with_escalated_permissions: args["with_escalated_permissions"]
+                .as_bool()
+                .unwrap_or(false),
+            justification: args["justification"].as_str().map(String::from),
+            timeout_ms: args["timeout_ms"].as_u64(),
+        })
+    }
+}
+
+/// `read_file`, see [`crate::tool_read_file`]
+#[derive(Debug, Clone, Copy)]
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> &'static str {
+        "read_file"
+    }
+
+    fn description(&self) -> String {
+        "Reads a window of lines from a file together with its size and line count. The result says when it was truncated and which offset continues it. A large source file or deck read without offset or limit returns an outline of its functions or slides with their line ranges instead; read the part you need from it. Binary files are not returned; a hex preview of their first bytes is shown instead.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                (
+                    "path",
+                    string_param("File to read, relative to the working directory"),
+                ),
+                (
+                    "offset",
+                    number_param("1-based line to start from (default 1)"),
+                ),
+                (
+                    "limit",
+                    number_param(&format!(
+                        "Maximum number of lines to return (default {DEFAULT_LINE_LIMIT})"
+                    )),
+                ),
+                (
+                    "max_bytes",
+                    number_param(&format!(
+                        "Maximum bytes of content to return (default {DEFAULT_MAX_BYTES})"
+                    )),
+                ),
+            ],
+            &["path"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        let path = required_str(args, "path", "file path")?;
+        let count = |key: &str| args[key].as_u64().map(|n| n as usize);
+        Ok(ToolCall::ReadFile {
+            input: ReadFileInput {
+                offset: count("offset"),
+                limit: count("limit"),
+                max_bytes: count("max_bytes"),
+                ..ReadFileInput::new(path)
+            },
+        })
+    }
+}
+
+/// `write_file`: create or overwrite a whole file
+#[derive(Debug, Clone, Copy)]
+pub struct WriteFileTool;
+
+impl Tool for WriteFileTool {
+    fn name(&self) -> &'static str {
+        "write_file"
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> String {
+        "Writes content to a file inside the workspace, creating missing directories".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                ("path", string_param("Path to the file to write")),
+                ("content", string_param("Content to write to the file")),
+            ],
+            &["path", "content"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::WriteFile {
+            path: PathBuf::from(required_str(args, "path", "file path")?),
+            content: required_str(args, "content", "file content")?.to_string(),
+        })
+    }
+}
+
+/// `apply_patch` with the patch passed as the `input` argument
+#[derive(Debug, Clone, Copy)]
+pub struct ApplyPatchTool;
+
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &'static str {
+        "apply_patch"
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> String {
+        APPLY_PATCH_DESCRIPTION.to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![(
+                "input",
+                string_param("The entire contents of the apply_patch command"),
+            )],
+            &["input"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ApplyPatch {
+            input: required_str(args, "input", "patch input")?.to_string(),
+        })
+    }
+}
+
+/// `list_files`: entries of one directory
+#[derive(Debug, Clone, Copy)]
+pub struct ListFilesTool;
+
+impl Tool for ListFilesTool {
+    fn name(&self) -> &'static str {
+        "list_files"
+    }
+
+    fn description(&self) -> String {
+        "Lists the entries of a directory".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![(
+                "path",
+                string_param("Directory to list (default: the working directory)"),
+            )],
+            &[],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ListFiles {
+            path: args["path"].as_str().map(PathBuf::from),
+        })
+    }
+}
+
+/// `search_files`: file names containing a query, recursively
+#[derive(Debug, Clone, Copy)]
+pub struct SearchFilesTool;
+
+impl Tool for SearchFilesTool {
+    fn name(&self) -> &'static str {
+        "search_files"
+    }
+
+    fn description(&self) -> String {
+        "Searches a directory tree for files whose name contains the query".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                ("query", string_param("Text the file name must contain")),
+                (
+                    "path",
+                    string_param("Directory to search in (default: the working directory)"),
+                ),
+            ],
+            &["query"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::SearchFiles {
+            query: required_str(args, "query", "search query")?.to_string(),
+            path: args["path"].as_str().map(PathBuf::from),
+        })
+    }
+}
+
+/// `image`, see [`crate::tool_image`]
+#[derive(Debug, Clone, Copy)]
+pub struct ImageTool;
+
+impl Tool for ImageTool {
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> String {
+        "Downloads or generates an image, saves it under slides/assets/ and inserts a markdown reference into the deck. Requires user approval.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                (
+                    "url",
+                    string_param("URL of an image provided by the user to download"),
+                ),
+                (
+                    "prompt",
+                    string_param("Prompt for generating a new image (when no url is given)"),
+                ),
+                (
+                    "alt",
+                    string_param("Alt text for the markdown image reference"),
+                ),
+                (
+                    "deck",
+                    string_param("Markdown deck to insert the image reference into"),
+                ),
+                (
+                    "slide",
+                    string_param("Heading text of the slide that should show the image"),
+                ),
+                (
+                    "name",
+                    string_param("File name for the saved asset, without extension"),
+                ),
+            ],
+            &["alt"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        let source = match (args["url"].as_str(), args["prompt"].as_str()) {
+            (Some(url), _) => ImageSource::Url(url.to_string()),
+            (None, Some(prompt)) => ImageSource::Generate {
+                prompt: prompt.to_string(),
+            },
+            (None, None) => return Err(anyhow!("image tool needs either url or prompt")),
+        };
+        Ok(ToolCall::Image {
+            input: ImageToolInput {
+                source,
+                alt: args["alt"].as_str().unwrap_or_default().to_string(),
+                deck: args["deck"].as_str().map(PathBuf::from),
+                slide: args["slide"].as_str().map(String::from),
+                name: args["name"].as_str().map(String::from),
+            },
+        })
+    }
+}
+
+/// `update_plan`, also accepted as `plan`
+#[derive(Debug, Clone, Copy)]
+pub struct UpdatePlanTool;
+
+impl Tool for UpdatePlanTool {
+    fn name(&self) -> &'static str {
+        "update_plan"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["plan"]
+    }
+
+    fn description(&self) -> String {
+        "Updates the task plan. Provide an optional explanation and the whole list of plan items. \
+            Keep at most one step in_progress, and mark each step completed as soon as it is done, before starting the next."
+            .to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        let item = object_schema(
+            vec![
+                ("step", JsonSchema::String { description: None }),
+                (
+                    "status",
+                    string_param("One of: pending, in_progress, completed, failed"),
+                ),
+            ],
+            &["step", "status"],
+        );
+        object_schema(
+            vec![
+                (
+                    "explanation",
+                    string_param("Why the plan is what it is; kept when omitted"),
+                ),
+                (
+                    "plan",
+                    JsonSchema::Array {
+                        description: Some("The list of steps".to_string()),
+                        items: Box::new(item),
+                    },
+                ),
+            ],
+            &["plan"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::UpdatePlan {
+            args: serde_json::from_value(args.clone())?,
+        })
+    }
+}
+
+/// `web_fetch`, see [`crate::tool_web_fetch`]
+#[derive(Debug, Clone, Copy)]
+pub struct WebFetchTool;
+
+impl Tool for WebFetchTool {
+    fn name(&self) -> &'static str {
+        "web_fetch"
+    }
+
+    fn description(&self) -> String {
+        "Downloads a web page and returns it as markdown, so slides can cite current information. Cite a fact from the page by linking its bullet to the page URL, e.g. `- Sales grew 12% ([source](https://…))`; a References slide listing the cited pages is kept at the end of the deck. Requires network access and user approval.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                ("url", string_param("http(s) URL of the page")),
+                (
+                    "max_bytes",
+                    number_param(&format!(
+                        "Maximum bytes of markdown to return (default {})",
+                        crate::tool_web_fetch::DEFAULT_MAX_BYTES
+                    )),
+                ),
+            ],
+            &["url"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::WebFetch {
+            input: WebFetchInput {
+                url: required_str(args, "url", "url")?.to_string(),
+                max_bytes: args["max_bytes"].as_u64().map(|n| n as usize),
+            },
+        })
+    }
+}
+
+/// `web_search`, backed by the Brave Search API
+#[derive(Debug, Clone, Copy)]
+pub struct WebSearchTool;
+
+impl Tool for WebSearchTool {
+    fn name(&self) -> &'static str {
+        "web_search"
+    }
+
+    fn description(&self) -> String {
+        "Searches the web and lists the top results with their URLs. Use web_fetch to read a result before citing it. Requires network access and user approval.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![
+                ("query", string_param("Search query")),
+                (
+                    "count",
+                    number_param(&format!(
+                        "Number of results (default {DEFAULT_SEARCH_RESULTS})"
+                    )),
+                ),
+            ],
+            &["query"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::WebSearch {
+            input: WebSearchInput {
+                query: required_str(args, "query", "search query")?.to_string(),
+                count: args["count"].as_u64().map(|n| n as usize),
+            },
+        })
+    }
+}
+
+/// `env_info`, see [`crate::tool_env_info`]
+#[derive(Debug, Clone, Copy)]
+pub struct EnvInfoTool;
+
+impl Tool for EnvInfoTool {
+    fn name(&self) -> &'static str {
+        "env_info"
+    }
+
+    fn description(&self) -> String {
+        "Returns the OS, shell, working directory, sandbox and approval policies, git branch and the versions of node, npm, python3, cargo and git. Call it instead of guessing the environment; it is cheap and needs no approval.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(Vec::new(), &[])
+    }
+
+    fn parse(&self, _args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::EnvInfo)
+    }
+}
+
+fn session_id_arg(args: &Value) -> Result<SessionId> {
+    let id = args["session_id"]
+        .as_u64()
+        .ok_or_else(|| anyhow!("Missing session_id"))?;
+    Ok(SessionId(u32::try_from(id)?))
+}
+
+fn yield_params() -> Vec<(&'static str, JsonSchema)> {
+    vec![
+        (
+            "yield_time_ms",
+            number_param(&format!(
+                "How long to wait for output in milliseconds (default {DEFAULT_YIELD_TIME_MS})"
+            )),
+        ),
+        (
+            "max_output_bytes",
+            number_param(&format!(
+                "Maximum bytes of output to return; the rest is kept for exec_session_read (default {DEFAULT_MAX_OUTPUT_BYTES})"
+            )),
+        ),
+    ]
+}
+
+/// `exec_session_open`: start a long-running process such as a shell or REPL
+#[derive(Debug, Clone, Copy)]
+pub struct ExecSessionOpenTool;
+
+impl Tool for ExecSessionOpenTool {
+    fn name(&self) -> &'static str {
+        "exec_session_open"
+    }
+
+    fn description(&self) -> String {
+        format!("Starts a persistent process such as `bash` or `python3 -i` and returns its session_id and first output. Send input with exec_session_write and close it when done; at most {MAX_SESSIONS} sessions can be open. The process talks over pipes, not a terminal.")
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        let mut properties = vec![
+            (
+                "command",
+                JsonSchema::Array {
+                    items: Box::new(JsonSchema::String { description: None }),
+                    description: Some("The command to start".to_string()),
+                },
+            ),
+            (
+                "workdir",
+                string_param("The working directory to start the command in"),
+            ),
+        ];
+        properties.extend(yield_params());
+        object_schema(properties, &["command"])
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ExecSessionOpen {
+            input: ExecCommandParams {
+                command: command_arg(args)?,
+                workdir: args["workdir"].as_str().map(PathBuf::from),
+                yield_time_ms: args["yield_time_ms"].as_u64(),
+                max_output_bytes: args["max_output_bytes"].as_u64().map(|n| n as usize),
+            },
+        })
+    }
+}
+
+/// `exec_session_write`: send input to a session
+#[derive(Debug, Clone, Copy)]
+pub struct ExecSessionWriteTool;
+
+impl Tool for ExecSessionWriteTool {
+    fn name(&self) -> &'static str {
+        "exec_session_write"
+    }
+
+    fn description(&self) -> String {
+        "Writes characters to the stdin of an exec session and returns the output produced meanwhile. End a command with a newline.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        let mut properties = vec![
+            (
+                "session_id",
+                number_param("Session returned by exec_session_open"),
+            ),
+            ("chars", string_param("Characters to write, e.g. \"ls\\n\"")),
+        ];
+        properties.extend(yield_params());
+        object_schema(properties, &["session_id", "chars"])
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ExecSessionWrite {
+            input: WriteStdinParams {
+                session_id: session_id_arg(args)?,
+                chars: required_str(args, "chars", "chars")?.to_string(),
+                yield_time_ms: args["yield_time_ms"].as_u64(),
+                max_output_bytes: args["max_output_bytes"].as_u64().map(|n| n as usize),
+            },
+        })
+    }
+}
+
+/// `exec_session_read`: output a session produced since the last call
+#[derive(Debug, Clone, Copy)]
+pub struct ExecSessionReadTool;
+
+impl Tool for ExecSessionReadTool {
+    fn name(&self) -> &'static str {
+        "exec_session_read"
+    }
+
+    fn description(&self) -> String {
+        "Returns the output an exec session produced since the last call, waiting up to yield_time_ms for more. Reports the exit code once the process has exited.".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        let mut properties = vec![(
+            "session_id",
+            number_param("Session returned by exec_session_open"),
+        )];
+        properties.extend(yield_params());
+        object_schema(properties, &["session_id"])
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ExecSessionRead {
+            input: ReadOutputParams {
+                session_id: session_id_arg(args)?,
+                yield_time_ms: args["yield_time_ms"].as_u64(),
+                max_output_bytes: args["max_output_bytes"].as_u64().map(|n| n as usize),
+            },
+        })
+    }
+}
+
+/// `exec_session_close`: kill a session's process
+#[derive(Debug, Clone, Copy)]
+pub struct ExecSessionCloseTool;
+
+impl Tool for ExecSessionCloseTool {
+    fn name(&self) -> &'static str {
+        "exec_session_close"
+    }
+
+    fn description(&self) -> String {
+        "Ends an exec session, killing its process".to_string()
+    }
+
+    fn parameters(&self) -> JsonSchema {
+        object_schema(
+            vec![(
+                "session_id",
+                number_param("Session returned by exec_session_open"),
+            )],
+            &["session_id"],
+        )
+    }
+
+    fn parse(&self, args: &Value) -> Result<ToolCall> {
+        Ok(ToolCall::ExecSessionClose {
+            session_id: session_id_arg(args)?,
+        })
+    }
+}
+
+const APPLY_PATCH_DESCRIPTION: &str = r#"Use the `apply_patch` tool to edit files.
+Your patch language is a stripped‑down, file‑oriented diff format designed to be easy to parse and safe to apply. You can think of it as a high‑level envelope:
+
+*** Begin Patch
+[ one or more file sections ]
+*** End Patch
+
+Within that envelope, you get a sequence of file operations.
+You MUST include a header to specify the action you are taking.
+Each operation starts with one of three headers:
+
+*** Add File: <path> - create a new file. Every following line is a + line (the initial contents).
+*** Delete File: <path> - delete a file (no more lines after this header).
+*** Update File: <path> - edit an existing file. This supports:
+  1. Context lines (starting with a space).
+  2. Additions (starting with +).
+  3. Deletions (starting with -).
+  4. Optional context markers (@@ ... @@).
+  5. Optional end‑of‑file marker (*** End of File).
+
+Examples:
+
+*** Begin Patch
+*** Add File: hello.py
++print("Hello, world!")
++print("This is a new file")
+*** End Patch
+
+*** Begin Patch
+*** Update File: main.py
+ def main():
+-    print("Old message")
++    print("New message")
+     return 0
+*** End Patch
+
+*** Begin Patch
+*** Delete File: obsolete.py
+*** End Patch
+
+*** Begin Patch
+*** Update File: config.json
+@@ Adding new configuration @@
+ {
+   "version": "1.0",
++  "debug": true,
+   "name": "myapp"
+ }
+*** End Patch
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval_manager::AskForApproval;
+    use crate::openai_tools::ToolsConfigParams;
+
+    fn names(registry: &ToolRegistry) -> Vec<&'static str> {
+        registry.tools().map(|t| t.name()).collect()
+    }
+
+    #[test]
+    fn config_selects_the_function_calling_tools() {
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::default(),
+            include_plan_tool: true,
+            include_apply_patch_tool: true,
+            include_view_image_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_slides_tools: false,
+            include_image_tool: false,
+            include_read_file_tool: true,
+            include_web_fetch_tool: false,
+        });
+        let registry = ToolRegistry::for_config(&config);
+        assert_eq!(
+            names(&registry),
+            [
+                "shell",
+                "update_plan",
+                "apply_patch",
+                "read_file",
+                "env_info"
+            ]
+        );
+        let shell = registry.get("shell").map(|t| t.input_schema());
+        assert_eq!(
+            shell
+                .as_ref()
+                .map(|s| s["properties"]["justification"]["type"].clone()),
+            Some(Value::from("string"))
+        );
+    }
+
+    #[test]
+    fn read_only_registries_drop_the_writing_tools() {
+        let registry = ToolRegistry::builtin(&SandboxPolicy::ReadOnly).read_only();
+        let names = names(&registry);
+        for tool in ["write_file", "apply_patch", "image"] {
+            assert!(!names.contains(&tool), "{tool} offered");
+        }
+        assert!(names.contains(&"read_file"));
+        let patch = serde_json::json!({"input": "*** Begin Patch\n*** End Patch"});
+        assert!(registry.parse("apply_patch", &patch).is_err());
+    }
+
+    #[test]
+    fn calls_are_parsed_by_name_or_alias() -> Result<()> {
+        let registry = ToolRegistry::builtin(&SandboxPolicy::ReadOnly);
+        let plan = serde_json::json!({"plan": [{"step": "outline", "status": "pending"}]});
+        assert!(matches!(
+            registry.parse("plan", &plan)?,
+            ToolCall::UpdatePlan { .. }
+        ));
+        let shell = serde_json::json!({"command": "ls -la", "working_dir": "slides"});
+        match registry.parse("shell", &shell)? {
+            ToolCall::Shell {
+                command,
+                working_dir,
+                ..
+            } => {
+                assert_eq!(command, ["ls", "-la"]);
+                assert_eq!(working_dir, Some(PathBuf::from("slides")));
+            }
+            other => panic!("expected a shell call, got {other:?}"),
+        }
+        assert!(registry.parse("rm_rf", &Value::Null).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn exec_session_calls_carry_the_session_id() -> Result<()> {
+        let registry = ToolRegistry::builtin(&SandboxPolicy::ReadOnly);
+        let open = serde_json::json!({"command": "python3 -i", "yield_time_ms": 200});
+        match registry.parse("exec_session_open", &open)? {
+            ToolCall::ExecSessionOpen { input } => {
+                assert_eq!(input.command, ["python3", "-i"]);
+                assert_eq!(input.yield_time_ms, Some(200));
+            }
+            other => panic!("expected exec_session_open, got {other:?}"),
+        }
+        let write = serde_json::json!({"session_id": 2, "chars": "print(1)\n"});
+        match registry.parse("exec_session_write", &write)? {
+            ToolCall::ExecSessionWrite { input } => {
+                assert_eq!(
+                    (input.session_id, input.chars.as_str()),
+                    (SessionId(2), "print(1)\n")
+                );
+            }
+            other => panic!("expected exec_session_write, got {other:?}"),
+        }
+        let close = serde_json::json!({"session_id": "2"});
+        assert!(registry.parse("exec_session_close", &close).is_err());
+        Ok(())
+    }
+}
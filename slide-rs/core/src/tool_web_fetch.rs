@@ -0,0 +1,207 @@
+//! `web_fetch` and `web_search` tools: read a page as markdown, or list search
+//! results, so decks can cite current information. Callers are responsible
+//! for network policy and approval checks.
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use slide_chatgpt::web::{fetch_url, search, search_api_key, SEARCH_API_KEY_ENV};
+use slide_common::sources::Source;
+
+use crate::tool_read_file::truncate_at_char;
+
+/// Bytes of converted page returned when the call gives no `max_bytes`
+pub const DEFAULT_MAX_BYTES: usize = 32 * 1024;
+/// Results returned when the call gives no `count`
+pub const DEFAULT_SEARCH_RESULTS: usize = 5;
+const MAX_SEARCH_RESULTS: usize = 20;
+
+/// Elements that never carry the page's content
+const SKIPPED_TAGS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "nav", "footer", "form",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebFetchInput {
+    pub url: String,
+    /// Maximum bytes of markdown (default: [`DEFAULT_MAX_BYTES`])
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSearchInput {
+    pub query: String,
+    /// Number of results (default: [`DEFAULT_SEARCH_RESULTS`])
+    pub count: Option<usize>,
+}
+
+impl WebFetchInput {
+    /// Command-like description used for approval prompts.
+    pub fn approval_command(&self) -> Vec<String> {
+        vec!["web_fetch".into(), self.url.clone()]
+    }
+}
+
+impl WebSearchInput {
+    /// Command-like description used for approval prompts.
+    pub fn approval_command(&self) -> Vec<String> {
+        vec!["web_search".into(), self.query.clone()]
+    }
+}
+
+/// A page read by `web_fetch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebFetchOutput {
+    /// The page as markdown, in the executor's `Explored` format
+    pub message: String,
+    /// The page for the deck's sources, should a slide cite it
+    pub source: Source,
+}
+
+/// Pages fetched by the turns of a session, so a deck written in a later
+/// turn can still name the pages it cites.
+#[derive(Debug, Clone, Default)]
+pub struct FetchedSources(Arc<Mutex<Vec<Source>>>);
+
+impl FetchedSources {
+    /// Remember `source`; a page fetched again replaces the earlier visit.
+    pub fn record(&self, source: Source) {
+        if let Ok(mut sources) = self.0.lock() {
+            sources.retain(|s| s.url != source.url);
+            sources.push(source);
+        }
+    }
+
+    pub fn all(&self) -> Vec<Source> {
+        self.0.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// Download the page and return it as markdown.
+pub async fn tool_web_fetch(input: &WebFetchInput) -> Result<WebFetchOutput> {
+    let page = fetch_url(&input.url).await?;
+    let text = page_to_markdown(page.content_type.as_deref(), &page.body)?;
+    let title = page_title(&String::from_utf8_lossy(&page.body), &text);
+    let max_bytes = input.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+    let shown = truncate_at_char(&text, max_bytes);
+
+    let mut message = format!(
+        "Explored\n- Fetched {} ({} bytes as markdown)\n\n{shown}",
+        page.url,
+        text.len()
+    );
+    if shown.len() < text.len() || page.truncated {
+        message.push_str(&format!(
+            "\n[truncated after {} bytes; raise max_bytes to see more]",
+            shown.len()
+        ));
+    }
+    Ok(WebFetchOutput {
+        message,
+        source: Source::fetched(page.url, title),
+    })
+}
+
+/// Search the web and list the results in the executor's `Explored` format.
+pub async fn tool_web_search(input: &WebSearchInput) -> Result<String> {
+    let api_key = search_api_key()
+        .ok_or_else(|| anyhow!("web search needs {SEARCH_API_KEY_ENV} to be set"))?;
+    let count = input
+        .count
+        .unwrap_or(DEFAULT_SEARCH_RESULTS)
+        .clamp(1, MAX_SEARCH_RESULTS);
+    let results = search(&api_key, &input.query, count).await?;
+
+    let mut message = format!("Explored\n- Web search '{}'\n", input.query);
+    if results.is_empty() {
+        message.push_str("\nNo results");
+    }
+    for (i, result) in results.iter().enumerate() {
+        message.push_str(&format!("\n{}. {}\n   {}", i + 1, result.title, result.url));
+        if !result.snippet.is_empty() {
+            message.push_str(&format!("\n   {}", result.snippet));
+        }
+    }
+    Ok(message)
+}
+
+/// HTML pages become markdown; other text is returned as is.
+fn page_to_markdown(content_type: Option<&str>, body: &[u8]) -> Result<String> {
+    let mime = content_type
+        .and_then(|t| t.split(';').next())
+        .map(|t| t.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let is_text = mime.is_empty()
+        || mime.starts_with("text/")
+        || mime.ends_with("json")
+        || mime.ends_with("xml");
+    if !is_text {
+        bail!("{mime} content cannot be shown as text");
+    }
+    let text = String::from_utf8_lossy(body);
+    let looks_like_html = mime.is_empty() && text.trim_start().starts_with('<');
+    if mime == "text/html" || mime == "application/xhtml+xml" || looks_like_html {
+        let markdown = htmd::HtmlToMarkdown::builder()
+            .skip_tags(SKIPPED_TAGS.to_vec())
+            .build()
+            .convert(&text)?;
+        Ok(markdown.trim().to_string())
+    } else {
+        Ok(text.into_owned())
+    }
+}
+
+/// The `<title>` of an HTML page, or the first heading of its markdown.
+fn page_title(body: &str, markdown: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let from_html = lower.find("<title").and_then(|start| {
+        let open = start + lower[start..].find('>')? + 1;
+        let close = open + lower[open..].find("</title>")?;
+        Some(
+            body[open..close]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    });
+    from_html
+        .or_else(|| {
+            markdown
+                .lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string())
+        })
+        .filter(|title| !title.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_pages_become_markdown_without_scripts() -> Result<()> {
+        let html = r#"<html><head><title>t</title><style>p{}</style></head><body>
+            <nav><a href="/">Home</a></nav>
+            <h1>Release notes</h1>
+            <p>Version <strong>2.0</strong> is <a href="https://example.com/v2">out</a>.</p>
+            <script>track()</script>
+            <ul><li>Faster</li><li>Smaller</li></ul>
+            </body></html>"#;
+        let markdown = page_to_markdown(Some("text/html; charset=utf-8"), html.as_bytes())?;
+        assert!(markdown.starts_with("# Release notes"));
+        assert!(markdown.contains("**2.0**"));
+        assert!(markdown.contains("[out](https://example.com/v2)"));
+        assert!(markdown.contains("Faster"));
+        assert!(!markdown.contains("track()"));
+        assert!(!markdown.contains("Home"));
+
+        assert_eq!(page_to_markdown(Some("text/plain"), b"plain")?, "plain");
+        assert!(page_to_markdown(Some("image/png"), b"\x89PNG").is_err());
+
+        assert_eq!(page_title(html, &markdown).as_deref(), Some("t"));
+        assert_eq!(
+            page_title("plain", "# Notes\ntext").as_deref(),
+            Some("Notes")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+//! Retrying a turn's model request after a transient provider failure.
+//!
+//! When the request fails to start, the stream reports an error, or the
+//! stream closes before it completed, the turn sends the same prompt again
+//! after a backoff. The prompt already holds the conversation and the tool
+//! results of earlier replies, so nothing the tools did is lost. The turn
+//! only ends with an error once the `retry` budget of the config is spent,
+//! or when the failure cannot pass on its own (a rejected key, a bad
+//! request).
+use slide_common::RetryConfig;
+use std::time::Duration;
+
+/// Longest wait between two attempts
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Retries used by one turn.
+#[derive(Debug, Clone)]
+pub struct TurnRetry {
+    config: RetryConfig,
+    retries: u32,
+}
+
+impl TurnRetry {
+    pub fn new(config: RetryConfig) -> Self {
+        Self { config, retries: 0 }
+    }
+
+    /// Wait before retrying after `error`; `None` when the turn should end
+    /// with it.
+    pub fn next_delay(&mut self, error: &str) -> Option<Duration> {
+        if self.retries >= self.config.max_retries || !is_transient(error) {
+            return None;
+        }
+        self.retries += 1;
+        let factor = 2u32.saturating_pow(self.retries - 1);
+        let delay = Duration::from_millis(self.config.initial_delay_ms).saturating_mul(factor);
+        Some(delay.min(MAX_DELAY))
+    }
+
+    /// Retries made so far
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.config.max_retries
+    }
+}
+
+/// Whether a failed request may succeed when sent again. HTTP answers are
+/// reported by the client as `… http <status>: …`; client errors other than
+/// a timeout, conflict or rate limit will fail the same way again. Anything
+/// else (connection and stream errors, 5xx) counts as transient.
+pub fn is_transient(error: &str) -> bool {
+    let status = error
+        .split_once("http ")
+        .and_then(|(_, rest)| rest.get(..3))
+        .and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(code @ 400..=499) => matches!(code, 408 | 409 | 429),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_failures_back_off_until_the_budget_is_spent() {
+        let mut retry = TurnRetry::new(RetryConfig {
+            max_retries: 3,
+            initial_delay_ms: 20_000,
+        });
+        let error = "openai http 503 Service Unavailable: overloaded";
+        assert_eq!(retry.next_delay(error), Some(Duration::from_secs(20)));
+        assert_eq!(retry.next_delay(error), Some(MAX_DELAY));
+        assert_eq!(
+            retry.next_delay("stream closed before the reply completed"),
+            Some(MAX_DELAY)
+        );
+        assert_eq!(retry.next_delay(error), None);
+        assert_eq!(retry.retries(), 3);
+
+        let mut retry = TurnRetry::new(RetryConfig::default());
+        assert_eq!(
+            retry.next_delay("openai http 401 Unauthorized: bad key"),
+            None
+        );
+        assert!(retry
+            .next_delay("openai http 429 Too Many Requests")
+            .is_some());
+        assert!(retry
+            .next_delay("error sending request for url (https://api.openai.com/)")
+            .is_some());
+
+        let mut retry = TurnRetry::new(RetryConfig {
+            max_retries: 0,
+            initial_delay_ms: 10,
+        });
+        assert_eq!(retry.next_delay(error), None);
+    }
+}
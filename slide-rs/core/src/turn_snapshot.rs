@@ -0,0 +1,204 @@
+//! Files as they were before a turn changed them, so `Op::RevertTurn`
+//! (`/undo-turn`) can put a whole turn back at once instead of one deck at a
+//! time.
+//!
+//! Tools that write files (`write_file`, `apply_patch`, the image tool)
+//! record each path the first time they touch it in a turn. Changes made by
+//! shell commands are not tracked.
+use anyhow::{Context, Result};
+use slide_common::versions::unified_diff;
+use slide_common::VersionStore;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Turns that can be reverted; older ones are forgotten
+pub const MAX_TURN_SNAPSHOTS: usize = 20;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TurnSnapshot {
+    /// Content before the turn, in the order first written; `None` for
+    /// files the turn created
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+/// What reverting a turn did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RevertReport {
+    /// Files put back or removed
+    pub restored: Vec<PathBuf>,
+    /// Why files could not be put back
+    pub failed: Vec<String>,
+}
+
+impl TurnSnapshot {
+    /// Keep the current content of `path` unless it was recorded already
+    /// this turn. A file that cannot be read is not recorded: reverting must
+    /// never delete a file that existed.
+    pub fn record(&mut self, path: &Path) {
+        if self.contains(path) {
+            return;
+        }
+        let content = match std::fs::read(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!(target: "slide.turns", error = %e, path = %path.display(), "cannot snapshot");
+                return;
+            }
+        };
+        self.files.push((path.to_path_buf(), content));
+    }
+
+    /// Record `path` as created by this turn, for files written under a
+    /// name that was free.
+    pub fn record_created(&mut self, path: &Path) {
+        if !self.contains(path) {
+            self.files.push((path.to_path_buf(), None));
+        }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.iter().any(|(p, _)| p == path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Changes of the turn so far as one unified diff, with paths relative
+    /// to `workspace`; files back to their content are left out.
+    pub fn unified_diff(&self, workspace: &Path) -> String {
+        let mut out = String::new();
+        for (path, before) in &self.files {
+            let after = std::fs::read(path).ok();
+            if *before == after {
+                continue;
+            }
+            let name = path.strip_prefix(workspace).unwrap_or(path).display();
+            out.push_str(&format!("diff --git a/{name} b/{name}\n"));
+            let (Some(old), Some(new)) = (
+                as_text(before.as_deref().unwrap_or_default()),
+                as_text(after.as_deref().unwrap_or_default()),
+            ) else {
+                out.push_str(&format!("Binary files a/{name} and b/{name} differ\n"));
+                continue;
+            };
+            let old_name = match before {
+                Some(_) => format!("a/{name}"),
+                None => "/dev/null".to_string(),
+            };
+            let new_name = match after {
+                Some(_) => format!("b/{name}"),
+                None => "/dev/null".to_string(),
+            };
+            out.push_str(&format!("--- {old_name}\n+++ {new_name}\n"));
+            out.push_str(&unified_diff(old, new));
+        }
+        out
+    }
+
+    /// Put every recorded file back and remove the files the turn created.
+    /// A file that fails does not stop the others. Decks are snapshotted to
+    /// `.slide/versions` first, so `/undo` can bring the turn's version back.
+    pub fn restore(&self, workspace: &Path) -> RevertReport {
+        let versions = VersionStore::new(workspace);
+        let mut report = RevertReport::default();
+        for (path, content) in self.files.iter().rev() {
+            if path.extension().is_some_and(|e| e == "md") && path.is_file() {
+                let _ = versions.snapshot(path);
+            }
+            match restore_file(path, content.as_deref()) {
+                Ok(()) => report.restored.push(path.clone()),
+                Err(e) => report.failed.push(format!("{e:#}")),
+            }
+        }
+        report.restored.reverse();
+        report.failed.reverse();
+        report
+    }
+}
+
+/// `content` as text; `None` for binary content, which like git is taken
+/// to be anything with a NUL byte or invalid UTF-8.
+fn as_text(content: &[u8]) -> Option<&str> {
+    if content.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(content).ok()
+}
+
+fn restore_file(path: &Path, content: Option<&[u8]>) -> Result<()> {
+    let Some(content) = content else {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn restore_puts_back_changed_files_and_removes_created_ones() -> Result<()> {
+        let tmp = tempdir()?;
+        let deck = tmp.path().join("talk.md");
+        let created = tmp.path().join("slides").join("new.md");
+        let asset = tmp.path().join("assets").join("image.png");
+        std::fs::write(&deck, "# Before\n")?;
+
+        let mut snapshot = TurnSnapshot::default();
+        snapshot.record(&deck);
+        snapshot.record(&created);
+        std::fs::write(&deck, "# After\n")?;
+        // A second write in the same turn keeps the first content
+        snapshot.record(&deck);
+        std::fs::create_dir_all(created.parent().unwrap_or(tmp.path()))?;
+        std::fs::write(&created, "# New\n")?;
+        std::fs::create_dir_all(asset.parent().unwrap_or(tmp.path()))?;
+        std::fs::write(&asset, [0u8, 1, 2])?;
+        snapshot.record_created(&asset);
+
+        let diff = snapshot.unified_diff(tmp.path());
+        assert!(
+            diff.starts_with(
+                "diff --git a/talk.md b/talk.md\n--- a/talk.md\n+++ b/talk.md\n@@ -1 +1 @@\n-# Before\n+# After\n"
+            ),
+            "{diff}"
+        );
+        assert!(
+            diff.contains("--- /dev/null\n+++ b/slides/new.md\n"),
+            "{diff}"
+        );
+        assert!(diff.contains("Binary files a/assets/image.png and b/assets/image.png differ"));
+
+        let report = snapshot.restore(tmp.path());
+        assert_eq!(
+            report.restored,
+            [deck.clone(), created.clone(), asset.clone()]
+        );
+        assert!(report.failed.is_empty());
+        assert_eq!(std::fs::read_to_string(&deck)?, "# Before\n");
+        assert!(!created.exists());
+        assert!(!asset.exists());
+        assert_eq!(snapshot.unified_diff(tmp.path()), "");
+        // The reverted version of the deck stays reachable with /undo
+        let versions = VersionStore::new(tmp.path()).list(&deck)?;
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].read()?, "# After\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+//! What the agent knows about the workspace before its first tool call.
+//!
+//! When a session starts, [`collect_workspace_context`] lists the decks of
+//! the slides directory (from `slide.toml`, or the markdown files found when
+//! there is no manifest), the git status, and the files under the slides
+//! directory. The summary is added to the instructions of every turn, so the
+//! model knows the existing decks without listing them first. It is capped at
+//! [`WORKSPACE_CONTEXT_MAX_TOKENS`]; whatever does not fit is left out with a
+//! note saying how much.
+use slide_common::{Deck, Project};
+use std::path::Path;
+use std::process::Command;
+
+/// Estimated tokens the summary may take in the instructions
+pub const WORKSPACE_CONTEXT_MAX_TOKENS: usize = 1500;
+
+/// Directory holding the decks, relative to the workspace
+const SLIDES_DIR: &str = "slides";
+
+/// Lines of `git status` shown before the rest is counted
+const MAX_GIT_LINES: usize = 40;
+
+/// Directory levels shown in the file tree
+const MAX_TREE_DEPTH: usize = 3;
+
+/// Summary of the workspace at `workspace`; `None` when it has neither decks
+/// nor a git repository.
+pub fn collect_workspace_context(workspace: &Path) -> Option<String> {
+    collect_with_budget(workspace, WORKSPACE_CONTEXT_MAX_TOKENS)
+}
+
+fn collect_with_budget(workspace: &Path, max_tokens: usize) -> Option<String> {
+    let sections = [
+        deck_section(workspace),
+        git_section(workspace),
+        tree_section(workspace),
+    ];
+    let mut budget = Budget {
+        remaining: max_tokens * 4,
+    };
+    let mut out = String::new();
+    if !budget.take(&mut out, CONTEXT_HEADING) {
+        return None;
+    }
+    for (heading, lines) in sections.into_iter().flatten() {
+        if !budget.push_section(&mut out, &heading, &lines) {
+            break;
+        }
+    }
+    (out.len() > CONTEXT_HEADING.len()).then_some(out)
+}
+
+const CONTEXT_HEADING: &str = "Workspace when the session started (it may have changed since):\n";
+
+/// Characters left for the summary, at four per estimated token.
+struct Budget {
+    remaining: usize,
+}
+
+impl Budget {
+    /// Add `heading` and as many of `lines` as fit; `false` once the budget
+    /// is spent.
+    fn push_section(&mut self, out: &mut String, heading: &str, lines: &[String]) -> bool {
+        if !self.take(out, &format!("\n{heading}\n")) {
+            return false;
+        }
+        for (shown, line) in lines.iter().enumerate() {
+            // Keep room to say how many lines were left out
+            let note = format!("… {} more\n", lines.len() - shown);
+            let fits = self.remaining >= line.chars().count() + 1 + note.chars().count();
+            if !fits || !self.take(out, &format!("{line}\n")) {
+                out.push_str(&note);
+                self.remaining = 0;
+                return false;
+            }
+        }
+        true
+    }
+
+    fn take(&mut self, out: &mut String, text: &str) -> bool {
+        let len = text.chars().count();
+        if len > self.remaining {
+            return false;
+        }
+        self.remaining -= len;
+        out.push_str(text);
+        true
+    }
+}
+
+type Section = (String, Vec<String>);
+
+/// Every deck with its title and number of slides.
+fn deck_section(workspace: &Path) -> Option<Section> {
+    let root = workspace.join(SLIDES_DIR);
+    if !root.is_dir() {
+        return None;
+    }
+    let project = Project::open(&root).ok()?;
+    if project.manifest.decks.is_empty() {
+        return None;
+    }
+    let heading = if project.has_manifest {
+        format!(
+            "Decks ({SLIDES_DIR}/{}):",
+            slide_common::project::MANIFEST_FILE
+        )
+    } else {
+        format!("Decks (markdown files under {SLIDES_DIR}/):")
+    };
+    let lines = project
+        .manifest
+        .decks
+        .iter()
+        .map(|deck| {
+            let path = Path::new(SLIDES_DIR).join(&deck.path);
+            let mut line = format!("- {}: {}", deck.name, path.display());
+            match std::fs::read_to_string(project.deck_path(deck)) {
+                Ok(markdown) => {
+                    let parsed = Deck::parse(&markdown);
+                    let title = deck
+                        .title
+                        .clone()
+                        .or_else(|| parsed.slides.first().and_then(|s| s.title.clone()));
+                    if let Some(title) = title {
+                        line.push_str(&format!(" \"{title}\""));
+                    }
+                    let count = parsed.slides.len();
+                    let plural = if count == 1 { "" } else { "s" };
+                    line.push_str(&format!(", {count} slide{plural}"));
+                }
+                Err(_) => line.push_str(" (missing)"),
+            }
+            line
+        })
+        .collect();
+    Some((heading, lines))
+}
+
+/// `git status` in short form, headed by the branch.
+fn git_section(workspace: &Path) -> Option<Section> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain=v1", "--branch"])
+        .current_dir(workspace)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    let branch = lines
+        .next()
+        .and_then(|line| line.strip_prefix("## "))
+        .unwrap_or("(unknown branch)");
+    let mut changes: Vec<String> = lines.map(str::to_string).collect();
+    let heading = if changes.is_empty() {
+        format!("Git: {branch}, no uncommitted changes")
+    } else {
+        format!("Git: {branch}, uncommitted changes:")
+    };
+    if changes.len() > MAX_GIT_LINES {
+        let more = changes.len() - MAX_GIT_LINES;
+        changes.truncate(MAX_GIT_LINES);
+        changes.push(format!("… {more} more"));
+    }
+    Some((heading, changes))
+}
+
+/// Files under the slides directory, indented by depth.
+fn tree_section(workspace: &Path) -> Option<Section> {
+    let root = workspace.join(SLIDES_DIR);
+    if !root.is_dir() {
+        return None;
+    }
+    let mut lines = Vec::new();
+    walk_tree(&root, 0, &mut lines);
+    if lines.is_empty() {
+        return None;
+    }
+    Some((format!("Files under {SLIDES_DIR}/:"), lines))
+}
+
+fn walk_tree(dir: &Path, depth: usize, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    let indent = "  ".repeat(depth);
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth + 1 < MAX_TREE_DEPTH {
+                out.push(format!("{indent}{name}/"));
+                walk_tree(&entry.path(), depth + 1, out);
+            } else {
+                out.push(format!("{indent}{name}/ …"));
+            }
+        } else {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or_default();
+            out.push(format!("{indent}{name} ({})", format_size(size)));
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn decks_and_files_are_summarized_within_the_budget() -> anyhow::Result<()> {
+        let tmp = tempdir()?;
+        let slides = tmp.path().join(SLIDES_DIR);
+        fs::create_dir_all(slides.join("assets"))?;
+        fs::write(
+            slides.join("talk.md"),
+            "# Launch plan\n\n## Why\n\n- speed\n\n## How\n",
+        )?;
+        fs::write(slides.join("assets").join("logo.png"), [0u8; 2048])?;
+        fs::write(slides.join(".notes.txt"), "hidden")?;
+
+        let context = collect_workspace_context(tmp.path()).unwrap_or_default();
+        assert!(
+            context.contains(
+                "Decks (markdown files under slides/):\n- talk: slides/talk.md \"Launch plan\", 3 slides\n"
+            ),
+            "{context}"
+        );
+        assert!(
+            context
+                .contains("Files under slides/:\nassets/\n  logo.png (2.0 KB)\ntalk.md (39 B)\n"),
+            "{context}"
+        );
+        assert!(!context.contains(".notes.txt"));
+
+        // Lines past the budget are counted instead of shown
+        for i in 0..50 {
+            fs::write(slides.join(format!("deck{i:02}.md")), "# Deck\n")?;
+        }
+        let context = collect_with_budget(tmp.path(), 200).unwrap_or_default();
+        assert!(context.chars().count() <= 200 * 4, "{context}");
+        assert!(context.trim_end().ends_with("more"), "{context}");
+        assert!(!context.contains("Files under"));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_workspaces_have_no_context() -> anyhow::Result<()> {
+        let tmp = tempdir()?;
+        assert_eq!(collect_workspace_context(tmp.path()), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+//! Write boundary shared by every tool that writes files: a path may only be
+//! written when it resolves inside one of the sandbox policy's writable roots
+//! or an extra root from `sandbox.writable_roots` in the config.
+use anyhow::{bail, Result};
+use std::path::{Component, Path, PathBuf};
+
+use protocol::config_types::SandboxPolicy;
+use slide_apply_patch::PatchRequest;
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceGuard {
+    /// Relative paths are resolved against this: the session's working
+    /// directory, which `cd` may move away from the workspace
+    cwd: PathBuf,
+    /// Canonical writable roots; `None` means writes are unrestricted
+    roots: Option<Vec<PathBuf>>,
+}
+
+impl WorkspaceGuard {
+    pub fn new(policy: &SandboxPolicy, cwd: &Path, extra_roots: &[PathBuf]) -> Self {
+        let roots = match policy {
+            SandboxPolicy::DangerFullAccess => None,
+            SandboxPolicy::ReadOnly => Some(Vec::new()),
+            SandboxPolicy::WorkspaceWrite { .. } => Some(
+                policy
+                    .get_writable_roots_with_cwd(cwd)
+                    .into_iter()
+                    .map(|root| root.root)
+                    .chain(extra_roots.iter().cloned())
+                    .map(|root| resolve(&cwd.join(root)))
+                    .collect(),
+            ),
+        };
+        Self {
+            cwd: cwd.to_path_buf(),
+            roots,
+        }
+    }
+
+    /// Resolve relative paths against `cwd` from now on. The writable roots
+    /// stay those of the workspace the guard was made for.
+    pub fn set_cwd(&mut self, cwd: &Path) {
+        self.cwd = cwd.to_path_buf();
+    }
+
+    /// Roots writes are confined to; `None` when there is no restriction.
+    pub fn writable_roots(&self) -> Option<&[PathBuf]> {
+        self.roots.as_deref()
+    }
+
+    /// Resolve `path` against the working directory, following symlinks and
+    /// `..`, and return it when it lies inside a writable root.
+    pub fn check_write(&self, path: &Path) -> Result<PathBuf> {
+        let resolved = resolve(&self.cwd.join(path));
+        let Some(roots) = &self.roots else {
+            return Ok(resolved);
+        };
+        if roots.is_empty() {
+            bail!(
+                "{} is not writable: the sandbox policy is read-only",
+                path.display()
+            );
+        }
+        if roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            bail!(
+                "{} is outside the writable roots of the workspace ({})",
+                resolved.display(),
+                roots
+                    .iter()
+                    .map(|r| r.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    }
+
+    /// Check every file an apply_patch body adds, deletes, updates or moves
+    /// to. Patches that do not parse are left to the patch tool to report.
+    pub fn check_patch(&self, patch: &str) -> Result<()> {
+        let Ok(request) = PatchRequest::parse(patch, &self.cwd) else {
+            return Ok(());
+        };
+        for path in request.paths() {
+            self.check_write(&path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve the target of a `cd` against `cwd`. Returns the directory and
+/// whether it lies inside `workspace`.
+pub fn resolve_cwd(workspace: &Path, cwd: &Path, path: &Path) -> Result<(PathBuf, bool)> {
+    let dir = resolve(&cwd.join(path));
+    if !dir.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    let inside = dir.starts_with(resolve(workspace));
+    Ok((dir, inside))
+}
+
+/// Canonicalize the longest existing ancestor of `path` and append the rest
+/// lexically, so paths of files that do not exist yet resolve as well.
+fn resolve(path: &Path) -> PathBuf {
+    let components: Vec<Component> = path.components().collect();
+    let (mut resolved, rest) = (0..=components.len())
+        .rev()
+        .find_map(|i| {
+            let prefix: PathBuf = components[..i].iter().collect();
+            let base = if i == 0 {
+                PathBuf::new()
+            } else {
+                prefix.canonicalize().ok()?
+            };
+            Some((base, &components[i..]))
+        })
+        .unwrap_or_default();
+    for component in rest {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_write() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_system_tmp: true,
+        }
+    }
+
+    #[test]
+    fn writes_are_confined_to_the_writable_roots() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-guard-{}", std::process::id()));
+        let workspace = dir.join("workspace");
+        let extra = dir.join("extra");
+        std::fs::create_dir_all(&workspace)?;
+        std::fs::create_dir_all(&extra)?;
+        let guard =
+            WorkspaceGuard::new(&workspace_write(), &workspace, std::slice::from_ref(&extra));
+
+        let inside = guard.check_write(Path::new("slides/new/deck.md"))?;
+        assert_eq!(inside, workspace.canonicalize()?.join("slides/new/deck.md"));
+        assert!(guard.check_write(&extra.join("notes.md")).is_ok());
+        assert!(guard.check_write(Path::new("../outside.md")).is_err());
+        assert!(guard
+            .check_write(Path::new("slides/../../outside.md"))
+            .is_err());
+        assert!(guard.check_write(&dir.join("outside.md")).is_err());
+        assert!(guard
+            .check_patch("*** Begin Patch\n*** Add File: slides/a.md\n+# A\n*** End Patch")
+            .is_ok());
+        assert!(guard
+            .check_patch("*** Begin Patch\n*** Delete File: ../b.md\n*** End Patch")
+            .is_err());
+        assert!(guard
+            .check_patch(
+                "*** Begin Patch\n*** Update File: slides/a.md\n*** Move to: ../a.md\n@@\n-# A\n+# B\n*** End Patch"
+            )
+            .is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, workspace.join("escape"))?;
+            assert!(guard.check_write(Path::new("escape/outside.md")).is_err());
+        }
+
+        // After a `cd`, relative paths follow the new directory but the roots
+        // stay those of the workspace
+        let mut moved = guard.clone();
+        moved.set_cwd(&workspace.join("slides"));
+        assert_eq!(
+            moved.check_write(Path::new("talk.md"))?,
+            workspace.canonicalize()?.join("slides/talk.md")
+        );
+        moved.set_cwd(&dir);
+        assert!(moved.check_write(Path::new("outside.md")).is_err());
+        assert!(moved
+            .check_patch("*** Begin Patch\n*** Add File: outside.md\n+# A\n*** End Patch")
+            .is_err());
+
+        let read_only = WorkspaceGuard::new(&SandboxPolicy::ReadOnly, &workspace, &[]);
+        assert!(read_only.check_write(Path::new("deck.md")).is_err());
+        let full = WorkspaceGuard::new(&SandboxPolicy::DangerFullAccess, &workspace, &[]);
+        assert!(full.check_write(&dir.join("outside.md")).is_ok());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn cwd_changes_are_checked_against_the_workspace() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-cwd-{}", std::process::id()));
+        let workspace = dir.join("workspace");
+        std::fs::create_dir_all(workspace.join("slides"))?;
+        let workspace = workspace.canonicalize()?;
+
+        let (slides, inside) = resolve_cwd(&workspace, &workspace, Path::new("slides"))?;
+        assert_eq!((slides.clone(), inside), (workspace.join("slides"), true));
+        assert_eq!(
+            resolve_cwd(&workspace, &slides, Path::new(".."))?,
+            (workspace.clone(), true)
+        );
+        assert_eq!(
+            resolve_cwd(&workspace, &slides, Path::new("../.."))?,
+            (dir.canonicalize()?, false)
+        );
+        assert!(resolve_cwd(&workspace, &workspace, Path::new("missing")).is_err());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
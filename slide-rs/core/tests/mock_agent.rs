@@ -0,0 +1,122 @@
+//! The agent loop end to end against the scripted mock model: a patch is
+//! approved and applied, a command is denied and does not run, a transient
+//! model error is retried, one that cannot pass ends the turn, and
+//! `/approvals` changes the policy of the session.
+//!
+//! One test only: the session runs in the process's working directory.
+use anyhow::{Context, Result};
+use slide_core::client::client_from_env;
+use slide_core::codex::{AskForApproval, Codex, Event, Op, ReviewDecision};
+use std::path::Path;
+use std::time::Duration;
+
+/// Events of the turn started by `input`, answering approvals with
+/// `decision`, up to `TaskComplete` or `Error`.
+async fn run_turn(codex: &Codex, input: &str, decision: ReviewDecision) -> Result<Vec<Event>> {
+    codex
+        .submit(Op::UserInput {
+            text: input.to_string(),
+        })
+        .await?;
+    let mut events = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(10), codex.next_event())
+            .await?
+            .context("session ended")?;
+        match &event {
+            Event::ApplyPatchApprovalRequest { id, .. } => {
+                let (id, decision) = (id.clone(), decision.clone());
+                codex.submit(Op::PatchApproval { id, decision }).await?;
+            }
+            Event::ExecApprovalRequest { id, .. } => {
+                let (id, decision) = (id.clone(), decision.clone());
+                codex.submit(Op::ExecApproval { id, decision }).await?;
+            }
+            _ => {}
+        }
+        let done = matches!(event, Event::TaskComplete | Event::Error { .. });
+        events.push(event);
+        if done {
+            return Ok(events);
+        }
+    }
+}
+
+#[tokio::test]
+async fn scripted_turns_run_through_approvals_and_patching() -> Result<()> {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/deck_turns");
+    let tmp = tempfile::tempdir()?;
+    std::env::set_current_dir(tmp.path())?;
+    std::env::set_var("SLIDE_MODEL", format!("mock:{}", fixture.display()));
+    std::env::set_var("SLIDE_APPROVAL_MODE", "on-request");
+
+    let codex = Codex::spawn(client_from_env(None)).await?.codex;
+    match codex.next_event().await {
+        Some(Event::SessionConfigured {
+            model, provider, ..
+        }) => {
+            assert_eq!(model, "mock:deck_turns");
+            assert_eq!(provider, "mock");
+        }
+        other => anyhow::bail!("unexpected first event: {other:?}"),
+    }
+
+    // The patch is shown for approval, then applied
+    let events = run_turn(&codex, "Start a deck about Rust", ReviewDecision::Approved).await?;
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, Event::ApplyPatchApprovalRequest { .. })));
+    let message: String = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::AgentMessageDelta { delta } => Some(delta.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert!(message.starts_with("Adding the title slide.\n"));
+    assert!(message.contains("Created file: slides/talk.md"));
+    let deck = std::fs::read_to_string(tmp.path().join("slides/talk.md"))?;
+    assert_eq!(deck, "# Rust in five minutes\n\n---\n\n## Ownership\n");
+
+    // A denied command does not run
+    let events = run_turn(&codex, "Mark it as reviewed", ReviewDecision::Denied).await?;
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, Event::ExecApprovalRequest { command, .. } if command[0] == "touch")));
+    assert!(matches!(events.last(), Some(Event::TaskComplete)));
+    assert!(!tmp.path().join("reviewed.txt").exists());
+
+    // A transient model error is retried and the turn completes
+    let events = run_turn(&codex, "One more slide", ReviewDecision::Approved).await?;
+    assert!(events.iter().any(|e| matches!(
+        e,
+        Event::TurnRetrying { attempt: 1, error, .. } if error == "rate limited"
+    )));
+    assert!(matches!(events.last(), Some(Event::TaskComplete)));
+
+    // A rejected key is not retried
+    let events = run_turn(&codex, "And a closing slide", ReviewDecision::Approved).await?;
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, Event::TurnRetrying { .. })));
+    assert!(matches!(
+        events.last(),
+        Some(Event::Error { message }) if message == "openai http 401 Unauthorized: invalid key"
+    ));
+
+    // A new approval policy is confirmed with the session configuration
+    codex
+        .submit(Op::OverrideApprovalPolicy {
+            approval_policy: AskForApproval::Never,
+        })
+        .await?;
+    let event = tokio::time::timeout(Duration::from_secs(10), codex.next_event()).await?;
+    assert!(matches!(
+        event,
+        Some(Event::SessionConfigured {
+            approval_policy: AskForApproval::Never,
+            ..
+        })
+    ));
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Contract of the sandbox backends: canonical escapes are attempted under
+//! each `SandboxPolicy` through `SandboxedExecutor` (Seatbelt on macOS, the
+//! Landlock/seccomp helper on Linux) and must be blocked exactly where the
+//! policy says so.
+//!
+//! Behind the `sandbox-escape-tests` feature, since the kernel has to
+//! support the sandbox:
+//!
+//! ```text
+//! cargo test -p slide-core --features sandbox-escape-tests --test sandbox_escape
+//! ```
+//!
+//! The test binary has its own `main` so it can also act as the Linux
+//! sandbox helper, like `slide` does: it re-runs itself with argv[0] set to
+//! `slide-linux-sandbox`.
+use protocol::config_types::SandboxPolicy;
+use slide_core::approval_manager::AskForApproval;
+use slide_core::config_types::ShellEnvironmentPolicy;
+use slide_core::exec_sandboxed::{ExecParams, SandboxedExecutor};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Allowed,
+    Blocked,
+}
+
+use Outcome::{Allowed, Blocked};
+
+/// What the command tries, and what each policy must make of it.
+struct Attempt {
+    name: &'static str,
+    command: Vec<String>,
+    /// Expected outcome under read-only, workspace-write without network,
+    /// workspace-write with network, and full access; `None` is not checked
+    expected: [Option<Outcome>; 4],
+}
+
+/// Directories the attempts write to. Both are temporary directories, so
+/// the policies below leave `/tmp` and `$TMPDIR` out of the writable roots.
+struct Dirs {
+    workspace: tempfile::TempDir,
+    outside: tempfile::TempDir,
+}
+
+fn policies() -> [(&'static str, SandboxPolicy); 4] {
+    let workspace_write = |network_access| SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access,
+        exclude_tmpdir_env_var: true,
+        exclude_system_tmp: true,
+    };
+    [
+        ("read-only", SandboxPolicy::ReadOnly),
+        ("workspace-write", workspace_write(false)),
+        ("workspace-write+network", workspace_write(true)),
+        ("danger-full-access", SandboxPolicy::DangerFullAccess),
+    ]
+}
+
+fn sh(script: String) -> Vec<String> {
+    vec!["/bin/sh".to_string(), "-c".to_string(), script]
+}
+
+fn attempts(dirs: &Dirs, port: u16) -> Vec<Attempt> {
+    let write_to = |dir: &Path| sh(format!("echo escaped > '{}'", dir.join("probe").display()));
+    let mut attempts = vec![
+        // Control: the sandbox must not just fail everything
+        Attempt {
+            name: "write inside the workspace",
+            command: write_to(dirs.workspace.path()),
+            expected: [Some(Blocked), Some(Allowed), Some(Allowed), Some(Allowed)],
+        },
+        Attempt {
+            name: "write outside the workspace",
+            command: write_to(dirs.outside.path()),
+            expected: [Some(Blocked), Some(Blocked), Some(Blocked), Some(Allowed)],
+        },
+        Attempt {
+            name: "write through a symlink out of the workspace",
+            command: sh(format!(
+                "ln -sfn '{}' link && echo escaped > link/probe",
+                dirs.outside.path().display()
+            )),
+            expected: [Some(Blocked), Some(Blocked), Some(Blocked), Some(Allowed)],
+        },
+        Attempt {
+            name: "connect to a TCP port",
+            command: vec![
+                "/bin/bash".to_string(),
+                "-c".to_string(),
+                format!("exec 3<>/dev/tcp/127.0.0.1/{port}"),
+            ],
+            expected: [Some(Blocked), Some(Blocked), Some(Allowed), Some(Allowed)],
+        },
+    ];
+    // Seatbelt keeps its profile across setuid execs, which the cases above
+    // cover; Linux relies on no_new_privs to make setuid bits inert
+    if cfg!(target_os = "linux") {
+        attempts.push(Attempt {
+            name: "gain privileges through a setuid binary",
+            command: sh("! grep -q '^NoNewPrivs:[[:space:]]*1$' /proc/self/status".to_string()),
+            expected: [Some(Blocked), Some(Blocked), Some(Blocked), None],
+        });
+    }
+    attempts
+}
+
+/// Run `command` in the workspace under `policy`; `Allowed` when it exits
+/// successfully.
+async fn run(policy: &SandboxPolicy, workspace: &Path, command: Vec<String>) -> (Outcome, String) {
+    let mut executor = SandboxedExecutor::new(AskForApproval::Never, policy.clone())
+        .with_cwd(workspace.to_path_buf())
+        .with_linux_sandbox_exe(sandbox_exe());
+    let params = ExecParams {
+        command,
+        working_dir: None,
+        timeout_ms: Some(10_000),
+        with_escalated_permissions: false,
+        justification: None,
+        environment_policy: ShellEnvironmentPolicy::default(),
+    };
+    match executor.execute(params).await {
+        Ok(result) if result.exit_code == 0 => (Allowed, String::new()),
+        Ok(result) => (Blocked, result.stderr.trim().to_string()),
+        Err(e) => (Blocked, e.to_string()),
+    }
+}
+
+/// This binary, re-run as the Linux sandbox helper.
+fn sandbox_exe() -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        std::env::current_exe().ok()
+    } else {
+        None
+    }
+}
+
+async fn run_suite() -> std::io::Result<usize> {
+    let dirs = Dirs {
+        workspace: tempfile::tempdir()?,
+        outside: tempfile::tempdir()?,
+    };
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+
+    let mut failures = 0;
+    for (index, (label, policy)) in policies().iter().enumerate() {
+        for attempt in attempts(&dirs, port) {
+            let Some(expected) = attempt.expected[index] else {
+                continue;
+            };
+            let (outcome, detail) = run(policy, dirs.workspace.path(), attempt.command).await;
+            let status = if outcome == expected { "ok" } else { "FAILED" };
+            println!("{label:<24} {:<46} {expected:?} ... {status}", attempt.name);
+            if outcome != expected {
+                failures += 1;
+                if !detail.is_empty() {
+                    println!("    {detail}");
+                }
+            }
+            let _ = std::fs::remove_file(dirs.outside.path().join("probe"));
+        }
+    }
+    Ok(failures)
+}
+
+fn main() {
+    let argv0 = std::env::args_os().next().unwrap_or_default();
+    if Path::new(&argv0).file_name() == Some(slide_linux_sandbox::ARG0.as_ref()) {
+        slide_linux_sandbox::run_main();
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("failed to create the runtime: {e}");
+            std::process::exit(1);
+        }
+    };
+    match runtime.block_on(run_suite()) {
+        Ok(0) => println!("\nsandbox escapes: all blocked as expected"),
+        Ok(failures) => {
+            println!("\nsandbox escapes: {failures} failed");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("sandbox escapes: setup failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -9,9 +9,11 @@ use nucleo_matcher::Utf32Str;
 use serde::Serialize;
 use std::cell::UnsafeCell;
 use std::cmp::Reverse;
+use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
 use std::num::NonZero;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -210,7 +212,22 @@ pub fn run(
         }
     }
 
-    let mut raw_matches: Vec<(u32, String)> = global_heap.into_iter().map(|r| r.0).collect();
+    let raw_matches: Vec<(u32, String)> = global_heap.into_iter().map(|r| r.0).collect();
+    Ok(into_results(
+        &pattern,
+        raw_matches,
+        total_match_count,
+        compute_indices,
+    ))
+}
+
+/// Sort the best matches and compute their highlight indices.
+fn into_results(
+    pattern: &Pattern,
+    mut raw_matches: Vec<(u32, String)>,
+    total_match_count: usize,
+    compute_indices: bool,
+) -> FileSearchResults {
     sort_matches(&mut raw_matches);
 
     let mut matcher = if compute_indices {
@@ -242,10 +259,106 @@ pub fn run(
         })
         .collect();
 
-    Ok(FileSearchResults {
+    FileSearchResults {
         matches,
         total_match_count,
-    })
+    }
+}
+
+/// The files under a directory, walked once with the same rules as [`run`].
+/// [`FileIndex::update`] keeps it current from file system change events,
+/// so a query only has to match.
+pub struct FileIndex {
+    root: PathBuf,
+    files: BTreeSet<String>,
+}
+
+impl FileIndex {
+    pub fn build(root: &Path) -> Self {
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let mut index = Self {
+            root,
+            files: BTreeSet::new(),
+        };
+        index.add_tree(&index.root.clone());
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Apply changes to absolute `paths`: files that exist are added (with
+    /// everything under new directories), paths that are gone are removed
+    /// with everything under them. Hidden paths are left out, as in the walk.
+    pub fn update(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            let Some(rel) = path.strip_prefix(&self.root).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            if rel.is_empty() || rel.split('/').any(|part| part.starts_with('.')) {
+                continue;
+            }
+            let rel = rel.to_string();
+            if path.is_file() {
+                self.files.insert(rel);
+            } else if path.is_dir() {
+                self.add_tree(path);
+            } else {
+                let dir = format!("{rel}/");
+                self.files
+                    .retain(|file| file != &rel && !file.starts_with(&dir));
+            }
+        }
+    }
+
+    /// The best `limit` matches for `pattern_text`, like [`run`].
+    pub fn search(
+        &self,
+        pattern_text: &str,
+        limit: NonZero<usize>,
+        compute_indices: bool,
+    ) -> FileSearchResults {
+        let pattern = create_pattern(pattern_text);
+        let mut best = BestMatchesList::new(
+            limit.get(),
+            pattern.clone(),
+            Matcher::new(nucleo_matcher::Config::DEFAULT),
+        );
+        for file in &self.files {
+            best.insert(file);
+        }
+        let raw_matches = best.binary_heap.into_iter().map(|r| r.0).collect();
+        into_results(&pattern, raw_matches, best.num_matches, compute_indices)
+    }
+
+    fn add_tree(&mut self, dir: &Path) {
+        for entry in WalkBuilder::new(dir).build().flatten() {
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                continue;
+            }
+            if let Some(rel) = entry
+                .path()
+                .strip_prefix(&self.root)
+                .ok()
+                .and_then(Path::to_str)
+            {
+                self.files.insert(rel.to_string());
+            }
+        }
+    }
+}
+
+/// Score of `candidate` against the fuzzy `pattern_text`, ranked the way
+/// [`run`] ranks files; `None` when it does not match.
+pub fn fuzzy_score(pattern_text: &str, candidate: &str) -> Option<u32> {
+    let mut matcher = Matcher::new(nucleo_matcher::Config::DEFAULT);
+    let mut buf = Vec::new();
+    create_pattern(pattern_text).score(Utf32Str::new(candidate, &mut buf), &mut matcher)
 }
 
 fn sort_matches(matches: &mut [(u32, String)]) {
@@ -314,3 +427,35 @@ fn create_pattern(pattern: &str) -> Pattern {
         AtomKind::Fuzzy,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context as _;
+
+    #[test]
+    fn index_follows_created_and_removed_files() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-file-index-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("slides"))?;
+        std::fs::write(dir.join("slides").join("intro.md"), "# Intro")?;
+        let mut index = FileIndex::build(&dir);
+        assert_eq!(index.len(), 1);
+        let root = index.root.clone();
+
+        std::fs::create_dir_all(dir.join("talks"))?;
+        std::fs::write(dir.join("talks").join("deep.md"), "# Deep")?;
+        std::fs::remove_file(dir.join("slides").join("intro.md"))?;
+        index.update(&[root.join("talks"), root.join("slides").join("intro.md")]);
+
+        let limit = NonZero::new(8).context("limit")?;
+        let results = index.search("deep", limit, true);
+        assert_eq!(results.total_match_count, 1);
+        assert_eq!(results.matches[0].path, "talks/deep.md");
+        assert!(index.search("intro", limit, false).matches.is_empty());
+
+        std::fs::remove_dir_all(&dir)?;
+        index.update(&[root.join("talks")]);
+        assert!(index.is_empty());
+        Ok(())
+    }
+}
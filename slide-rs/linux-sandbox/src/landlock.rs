@@ -0,0 +1,111 @@
+use anyhow::{bail, Result};
+use landlock::{
+    Access, AccessFs, CompatLevel, Compatible, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    RulesetStatus, ABI,
+};
+use protocol::config_types::SandboxPolicy;
+use seccompiler::{
+    apply_filter, BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition,
+    SeccompFilter, SeccompRule, TargetArch,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Confine this thread (and everything it execs) to `policy`: seccomp takes
+/// the network away unless the policy allows it, Landlock makes the disk
+/// read-only outside the writable roots.
+///
+/// Landlock can only grant access, so the read-only subpaths of a root (its
+/// `.git`) stay writable here; the approval layer still guards them.
+pub(crate) fn apply_sandbox_policy_to_current_thread(
+    policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Result<()> {
+    if !policy.has_full_network_access() {
+        install_network_seccomp_filter()?;
+    }
+    if !policy.has_full_disk_write_access() {
+        let writable_roots = policy
+            .get_writable_roots_with_cwd(cwd)
+            .into_iter()
+            .map(|root| root.root)
+            .collect();
+        install_filesystem_landlock_rules(writable_roots)?;
+    }
+    Ok(())
+}
+
+fn install_filesystem_landlock_rules(writable_roots: Vec<PathBuf>) -> Result<()> {
+    let abi = ABI::V5;
+    let access_rw = AccessFs::from_all(abi);
+    let access_ro = AccessFs::from_read(abi);
+
+    let mut ruleset = Ruleset::default()
+        .set_compatibility(CompatLevel::BestEffort)
+        .handle_access(access_rw)?
+        .create()?
+        .add_rules(landlock::path_beneath_rules(["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(["/dev/null"], access_rw))?
+        .set_no_new_privs(true);
+    if !writable_roots.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    }
+
+    let status = ruleset.restrict_self()?;
+    if status.ruleset == RulesetStatus::NotEnforced {
+        bail!("landlock is not enabled in this kernel");
+    }
+    Ok(())
+}
+
+/// Fail every socket call but those on Unix sockets with `EPERM`.
+fn install_network_seccomp_filter() -> Result<()> {
+    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = [
+        libc::SYS_connect,
+        libc::SYS_accept,
+        libc::SYS_accept4,
+        libc::SYS_bind,
+        libc::SYS_listen,
+        libc::SYS_getpeername,
+        libc::SYS_getsockname,
+        libc::SYS_shutdown,
+        libc::SYS_sendto,
+        libc::SYS_sendmsg,
+        libc::SYS_sendmmsg,
+        libc::SYS_recvmsg,
+        libc::SYS_recvmmsg,
+        libc::SYS_getsockopt,
+        libc::SYS_setsockopt,
+        libc::SYS_ptrace,
+    ]
+    .into_iter()
+    .map(|syscall| (syscall, Vec::new()))
+    .collect();
+
+    // A rule matches (and is denied) when the domain is anything but AF_UNIX
+    let not_unix = SeccompRule::new(vec![SeccompCondition::new(
+        0,
+        SeccompCmpArgLen::Dword,
+        SeccompCmpOp::Ne,
+        libc::AF_UNIX as u64,
+    )?])?;
+    rules.insert(libc::SYS_socket, vec![not_unix.clone()]);
+    rules.insert(libc::SYS_socketpair, vec![not_unix]);
+
+    let arch = if cfg!(target_arch = "x86_64") {
+        TargetArch::x86_64
+    } else if cfg!(target_arch = "aarch64") {
+        TargetArch::aarch64
+    } else {
+        bail!("seccomp filter not supported on {}", std::env::consts::ARCH);
+    };
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        arch,
+    )?;
+    let program: BpfProgram = filter.try_into()?;
+    apply_filter(&program)?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Sandbox helper for Linux. `slide` re-executes itself with argv[0] set to
+//! [`ARG0`]; `slide-arg0` then calls [`run_main`], which confines the process
+//! with Landlock and seccomp and execs the command, so sandboxing needs no
+//! external binary.
+
+#[cfg(target_os = "linux")]
+mod landlock;
+
+use clap::Parser;
+use protocol::config_types::SandboxPolicy;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// argv[0] under which the `slide` binary acts as the sandbox helper.
+pub const ARG0: &str = "slide-linux-sandbox";
+
+/// Arguments of the sandbox helper.
+#[derive(Debug, Parser)]
+#[command(name = ARG0)]
+pub struct LandlockCommand {
+    /// Directory the policy's workspace roots are relative to
+    #[arg(long = "sandbox-policy-cwd")]
+    pub sandbox_policy_cwd: PathBuf,
+
+    /// JSON form of the policy, e.g. `{"workspace-write":{}}`
+    #[arg(long = "sandbox-policy")]
+    pub sandbox_policy: SandboxPolicy,
+
+    /// Command to run once the sandbox is in place
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// Arguments after argv[0] that make the helper run `command` under `policy`.
+pub fn create_linux_sandbox_command_args(
+    command: &[String],
+    policy: &SandboxPolicy,
+    cwd: &Path,
+) -> serde_json::Result<Vec<OsString>> {
+    let mut args = vec![
+        OsString::from("--sandbox-policy-cwd"),
+        cwd.as_os_str().to_owned(),
+        OsString::from("--sandbox-policy"),
+        OsString::from(serde_json::to_string(policy)?),
+        OsString::from("--"),
+    ];
+    args.extend(command.iter().map(OsString::from));
+    Ok(args)
+}
+
+/// Entry point of the helper: apply the policy to this process, then replace
+/// it with the command. Never runs the command unconfined.
+pub fn run_main() -> ! {
+    let LandlockCommand {
+        sandbox_policy_cwd,
+        sandbox_policy,
+        command,
+    } = LandlockCommand::parse();
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::process::CommandExt;
+
+        if let Err(e) =
+            landlock::apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd)
+        {
+            eprintln!("{ARG0}: failed to apply the sandbox: {e:#}");
+            std::process::exit(1);
+        }
+        let err = std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .exec();
+        eprintln!("{ARG0}: failed to run {}: {err}", command[0]);
+        std::process::exit(127);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (sandbox_policy_cwd, sandbox_policy, command);
+        eprintln!("{ARG0}: only supported on Linux");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helper_args_round_trip() -> anyhow::Result<()> {
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/data")],
+            network_access: true,
+            exclude_tmpdir_env_var: false,
+            exclude_system_tmp: true,
+        };
+        let command = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "echo --hi".to_string(),
+        ];
+        let args = create_linux_sandbox_command_args(&command, &policy, Path::new("/work"))?;
+
+        let parsed =
+            LandlockCommand::try_parse_from(std::iter::once(OsString::from(ARG0)).chain(args))?;
+        assert_eq!(parsed.sandbox_policy_cwd, PathBuf::from("/work"));
+        assert_eq!(parsed.sandbox_policy, policy);
+        assert_eq!(parsed.command, command);
+        Ok(())
+    }
+}
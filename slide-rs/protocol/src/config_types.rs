@@ -1,3 +1,7 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
 use serde::Deserialize;
 use serde::Serialize;
 use strum_macros::Display;
@@ -50,6 +54,155 @@ pub enum SandboxMode {
     DangerFullAccess,
 }
 
+/// Where the agent's commands may write and whether they may reach the
+/// network. Shared by the session protocol, the config and every sandbox
+/// backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxPolicy {
+    /// No restrictions whatsoever. Use with caution.
+    DangerFullAccess,
+    /// Read-only access to the entire file-system.
+    ReadOnly,
+    /// Same as `ReadOnly` but additionally grants write access to the current
+    /// working directory ("workspace").
+    WorkspaceWrite {
+        /// Additional folders (beyond cwd and possibly TMPDIR) that should be
+        /// writable from within the sandbox.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        writable_roots: Vec<PathBuf>,
+        /// When set to `true`, outbound network access is allowed. `false` by
+        /// default.
+        #[serde(default)]
+        network_access: bool,
+        /// When set to `true`, will NOT include the per-user `TMPDIR`
+        /// environment variable among the default writable roots.
+        #[serde(default)]
+        exclude_tmpdir_env_var: bool,
+        /// When set to `true`, will NOT include `/tmp` (`%TEMP%` on Windows)
+        /// among the default writable roots.
+        #[serde(default, alias = "exclude_slash_tmp")]
+        exclude_system_tmp: bool,
+    },
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self::new_workspace_write_policy()
+    }
+}
+
+/// A directory the sandbox lets commands write to, minus the subpaths that
+/// stay read-only inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WritableRoot {
+    pub root: PathBuf,
+    pub read_only_subpaths: Vec<PathBuf>,
+}
+
+impl WritableRoot {
+    pub fn is_path_writable(&self, path: &Path) -> bool {
+        path.starts_with(&self.root) && !self.read_only_subpaths.iter().any(|p| path.starts_with(p))
+    }
+}
+
+/// Parses the JSON form, e.g. `{"workspace-write":{"network_access":true}}`.
+impl FromStr for SandboxPolicy {
+    type Err = serde_json::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl SandboxPolicy {
+    pub fn new_read_only_policy() -> Self {
+        SandboxPolicy::ReadOnly
+    }
+
+    pub fn new_workspace_write_policy() -> Self {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            exclude_tmpdir_env_var: false,
+            exclude_system_tmp: false,
+        }
+    }
+
+    /// Name shown to the user, e.g. in approval requests
+    pub fn label(&self) -> &'static str {
+        match self {
+            SandboxPolicy::DangerFullAccess => "danger-full-access",
+            SandboxPolicy::ReadOnly => "read-only",
+            SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+        }
+    }
+
+    /// Parse a [`label`](Self::label), as passed to `--sandbox`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "danger-full-access" => Some(SandboxPolicy::DangerFullAccess),
+            "read-only" => Some(SandboxPolicy::ReadOnly),
+            "workspace-write" => Some(SandboxPolicy::new_workspace_write_policy()),
+            _ => None,
+        }
+    }
+
+    pub fn has_full_disk_write_access(&self) -> bool {
+        matches!(self, SandboxPolicy::DangerFullAccess)
+    }
+
+    pub fn has_full_network_access(&self) -> bool {
+        match self {
+            SandboxPolicy::DangerFullAccess => true,
+            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
+        }
+    }
+
+    /// The directories a `WorkspaceWrite` policy lets commands write to:
+    /// `cwd`, the extra `writable_roots`, then the temp directories unless
+    /// excluded. A `.git` directory inside a root stays read-only. Empty for
+    /// the other policies; check [`has_full_disk_write_access`] first.
+    ///
+    /// [`has_full_disk_write_access`]: Self::has_full_disk_write_access
+    pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
+        let SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            exclude_tmpdir_env_var,
+            exclude_system_tmp,
+            ..
+        } = self
+        else {
+            return Vec::new();
+        };
+        let mut roots = vec![cwd.to_path_buf()];
+        roots.extend(writable_roots.iter().cloned());
+        if !exclude_tmpdir_env_var {
+            if let Some(tmpdir) = std::env::var_os("TMPDIR").filter(|t| !t.is_empty()) {
+                roots.push(PathBuf::from(tmpdir));
+            }
+        }
+        if !exclude_system_tmp {
+            let tmp = if cfg!(windows) {
+                std::env::var_os("TEMP").map(PathBuf::from)
+            } else {
+                Some(PathBuf::from("/tmp"))
+            };
+            roots.extend(tmp.filter(|t| t.is_dir()));
+        }
+        roots
+            .into_iter()
+            .map(|root| {
+                let git = root.join(".git");
+                WritableRoot {
+                    read_only_subpaths: if git.is_dir() { vec![git] } else { Vec::new() },
+                    root,
+                }
+            })
+            .collect()
+    }
+}
+
 /// Collection of common configuration options that a user can define as a unit
 /// in `config.toml`. Currently only a subset of the fields are supported.
 #[derive(Deserialize, Debug, Clone, PartialEq, Serialize, TS)]
@@ -59,3 +212,62 @@ pub struct ConfigProfile {
     pub approval_policy: Option<AskForApproval>,
     pub model_reasoning_effort: Option<ReasoningEffort>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sandbox_policy_serde() -> Result<(), serde_json::Error> {
+        assert_eq!(
+            serde_json::to_string(&SandboxPolicy::ReadOnly)?,
+            r#""read-only""#
+        );
+        let policy: SandboxPolicy = r#"{"workspace-write":{"writable_roots":["/data"],"network_access":true,"exclude_slash_tmp":true}}"#.parse()?;
+        assert_eq!(
+            policy,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![PathBuf::from("/data")],
+                network_access: true,
+                exclude_tmpdir_env_var: false,
+                exclude_system_tmp: true,
+            }
+        );
+        assert!(policy.has_full_network_access());
+        assert_eq!(
+            serde_json::to_string(&SandboxPolicy::default())?,
+            r#"{"workspace-write":{"network_access":false,"exclude_tmpdir_env_var":false,"exclude_system_tmp":false}}"#
+        );
+        assert_eq!(
+            SandboxPolicy::from_label(policy.label()),
+            Some(SandboxPolicy::default())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn writable_roots_start_at_cwd() {
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/data")],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_system_tmp: true,
+        };
+        let roots: Vec<PathBuf> = policy
+            .get_writable_roots_with_cwd(Path::new("/work"))
+            .into_iter()
+            .map(|r| r.root)
+            .collect();
+        assert_eq!(roots, [PathBuf::from("/work"), PathBuf::from("/data")]);
+        assert!(SandboxPolicy::ReadOnly
+            .get_writable_roots_with_cwd(Path::new("/work"))
+            .is_empty());
+
+        let root = WritableRoot {
+            root: PathBuf::from("/work"),
+            read_only_subpaths: vec![PathBuf::from("/work/.git")],
+        };
+        assert!(root.is_path_writable(Path::new("/work/deck.md")));
+        assert!(!root.is_path_writable(Path::new("/work/.git/config")));
+    }
+}
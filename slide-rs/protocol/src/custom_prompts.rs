@@ -2,9 +2,17 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A saved prompt the user can send as `/<name> [args]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CustomPrompt {
     pub name: String,
     pub path: PathBuf,
+    /// Template body without its frontmatter
     pub content: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Names of the `{{arg}}` placeholders, in the order positional
+    /// arguments fill them
+    #[serde(default)]
+    pub args: Vec<String>,
 }
@@ -5,9 +5,7 @@
 
 use std::collections::HashMap;
 use std::fmt;
-use std::path::Path;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::time::Duration;
 
 use crate::custom_prompts::CustomPrompt;
@@ -22,6 +20,7 @@ use uuid::Uuid;
 
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::config_types::SandboxPolicy;
 use crate::message_history::HistoryEntry;
 use crate::models::ResponseItem;
 use crate::parse_command::ParsedCommand;
@@ -121,118 +120,6 @@ pub enum AskForApproval {
     Never,
 }
 
-/// Determines execution restrictions for model shell commands.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, TS)]
-#[strum(serialize_all = "kebab-case")]
-#[serde(tag = "mode", rename_all = "kebab-case")]
-pub enum SandboxPolicy {
-    #[serde(rename = "danger-full-access")]
-    DangerFullAccess,
-    #[serde(rename = "read-only")]
-    ReadOnly,
-    #[serde(rename = "workspace-write")]
-    WorkspaceWrite {
-        #[serde(default, skip_serializing_if = "Vec::is_empty")]
-        writable_roots: Vec<PathBuf>,
-        #[serde(default)]
-        network_access: bool,
-        #[serde(default)]
-        exclude_tmpdir_env_var: bool,
-        #[serde(default)]
-        exclude_slash_tmp: bool,
-    },
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct WritableRoot {
-    pub root: PathBuf,
-    pub read_only_subpaths: Vec<PathBuf>,
-}
-
-impl WritableRoot {
-    pub fn is_path_writable(&self, path: &Path) -> bool {
-        path.starts_with(&self.root) && !self.read_only_subpaths.iter().any(|p| path.starts_with(p))
-    }
-}
-
-impl FromStr for SandboxPolicy {
-    type Err = serde_json::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        serde_json::from_str(s)
-    }
-}
-
-impl SandboxPolicy {
-    pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
-    }
-    pub fn new_workspace_write_policy() -> Self {
-        SandboxPolicy::WorkspaceWrite {
-            writable_roots: vec![],
-            network_access: false,
-            exclude_tmpdir_env_var: false,
-            exclude_slash_tmp: false,
-        }
-    }
-    pub fn has_full_disk_read_access(&self) -> bool {
-        true
-    }
-    pub fn has_full_disk_write_access(&self) -> bool {
-        matches!(self, SandboxPolicy::DangerFullAccess)
-    }
-    pub fn has_full_network_access(&self) -> bool {
-        matches!(
-            self,
-            SandboxPolicy::DangerFullAccess
-                | SandboxPolicy::WorkspaceWrite {
-                    network_access: true,
-                    ..
-                }
-        )
-    }
-    pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
-        match self {
-            SandboxPolicy::DangerFullAccess | SandboxPolicy::ReadOnly => Vec::new(),
-            SandboxPolicy::WorkspaceWrite {
-                writable_roots,
-                exclude_tmpdir_env_var,
-                exclude_slash_tmp,
-                ..
-            } => {
-                let mut roots = writable_roots.clone();
-                roots.push(cwd.to_path_buf());
-                if cfg!(unix) && !exclude_slash_tmp {
-                    let p = PathBuf::from("/tmp");
-                    if p.is_dir() {
-                        roots.push(p);
-                    }
-                }
-                if !exclude_tmpdir_env_var {
-                    if let Some(tmpdir) = std::env::var_os("TMPDIR") {
-                        if !tmpdir.is_empty() {
-                            roots.push(PathBuf::from(tmpdir));
-                        }
-                    }
-                }
-                roots
-                    .into_iter()
-                    .map(|r| {
-                        let mut ro = Vec::new();
-                        let git = r.join(".git");
-                        if git.is_dir() {
-                            ro.push(git);
-                        }
-                        WritableRoot {
-                            root: r,
-                            read_only_subpaths: ro,
-                        }
-                    })
-                    .collect()
-            }
-        }
-    }
-}
-
 /// User input
 #[non_exhaustive]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -1,7 +1,5 @@
 use anyhow::Result;
-use slide_core::client::{ModelClient, OpenAiAdapter, StubClient};
 use slide_core::codex::{Codex, CodexSpawnOk, Event as CoreEvent, Op};
-use std::sync::Arc;
 use tokio::sync::mpsc;
 
 pub struct AgentHandle {
@@ -11,27 +9,7 @@ pub struct AgentHandle {
 
 impl AgentHandle {
     pub async fn spawn() -> Result<Self> {
-        // Allow forcing Stub regardless of API key (for offline/dev/demo)
-        let force_stub = std::env::var("SLIDE_FORCE_STUB")
-            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-            .unwrap_or(false);
-        // Prefer OpenAI if API key present; fallback to stub (unless forced)
-        let client: Arc<dyn ModelClient + Send + Sync> = if !force_stub {
-            if let Ok(key) = std::env::var("OPENAI_API_KEY") {
-                let model = std::env::var("SLIDE_MODEL").ok();
-                if let Some(m) = model {
-                    Arc::new(OpenAiAdapter::new_with_model(key, m))
-                } else {
-                    Arc::new(OpenAiAdapter::new(key))
-                }
-            } else {
-                Arc::new(StubClient)
-            }
-        } else {
-            Arc::new(StubClient)
-        };
-        // Read approval mode for future core wiring
-        let _approval_mode = std::env::var("SLIDE_APPROVAL_MODE").ok();
+        let client = slide_core::client::client_from_env(None);
         let CodexSpawnOk { codex, .. } = slide_core::codex::Codex::spawn(client).await?;
         // Forward events to a local channel
         let (tx, rx) = mpsc::channel(256);
@@ -52,9 +30,28 @@ impl AgentHandle {
     }
 
     pub fn submit_text_bg(&self, text: String) {
+        self.submit_op_bg(Op::UserInput { text });
+    }
+
+    /// Stop the turn in flight; answered with `TurnAborted`.
+    pub fn interrupt(&self) {
+        self.submit_op_bg(Op::Interrupt);
+    }
+
+    /// Hold the agent before its next tool call; answered with `PlanPaused`.
+    pub fn pause_plan(&self) {
+        self.submit_op_bg(Op::PausePlan);
+    }
+
+    /// Let a paused agent go on; answered with `PlanResumed`.
+    pub fn resume_plan(&self) {
+        self.submit_op_bg(Op::ResumePlan);
+    }
+
+    pub fn submit_op_bg(&self, op: Op) {
         let c = self.codex.clone();
         tokio::spawn(async move {
-            let _ = c.submit(Op::UserInput { text }).await;
+            let _ = c.submit(op).await;
         });
     }
 }
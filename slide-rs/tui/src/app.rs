@@ -1,7 +1,12 @@
 use crate::custom_terminal::{Frame, Terminal};
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind},
+    event::{
+        self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseEvent, MouseEventKind,
+    },
+    execute,
     terminal::{disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{
@@ -9,27 +14,72 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Clear, Paragraph},
+    widgets::{Clear, Paragraph, Wrap},
 };
 use std::io::Write as _;
-use std::{io, path::PathBuf, time::Instant};
+use std::{
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 use tokio::time::{sleep, Duration};
 
 use crate::agent::AgentHandle;
 use crate::app_event_sender::{AppEvent, AppEventSender};
+use crate::attachments::compose_with_attachments;
+use crate::bottom_pane::file_search_popup::FileSearchPopup;
+use crate::bottom_pane::paste_burst::PasteBurst;
+use crate::bottom_pane::vim::VimMode;
 use crate::bottom_pane::{BottomPane, BottomPaneParams};
+use crate::deck_pane::DeckPane;
+use crate::exec_cell::ExecCell;
+use crate::file_search::FileSearchManager;
+use crate::help_view::{help_lines, HelpContext};
+use crate::history_cell::HistoryCell;
 use crate::insert_history::insert_history_lines;
+use crate::keymap::{global_action, GlobalAction};
+use crate::notifier::Notifier;
+use crate::plan_cell::PlanCell;
+use crate::session_picker::{SessionChoice, SessionPicker};
+use crate::session_store::SessionStore;
+use crate::transcript_store::TranscriptStore;
+use crate::slash_command::{CopyTarget, SlashAction, SlashCommandRegistry};
 use crate::streaming::AnswerStreamState;
-use crate::user_approval_widget::ApprovalRequest;
+use crate::suggestions::SuggestionManager;
+use crate::user_approval_widget::{approval_deadline, ApprovalRequest};
 use crate::widgets::{
-    banner::{banner_history_lines, banner_message},
-    chat::ChatWidget,
+    banner::banner_history_lines,
     list_selection::ListSelection,
     modal::Modal,
-    status_bar::StatusBar,
+    status_bar::{StatusBar, StatusInfo},
 };
+use slide_common::i18n::{t, tf, Msg};
+use slide_common::versions::{unified_diff, DeckVersion};
+use slide_common::{Project, VersionStore, WorkspaceState};
 use slide_core::codex::Event as CoreEvent;
 use slide_core::codex::Op;
+use slide_core::codex::PlanRecord;
+use slide_core::codex::StepStatus;
+use slide_core::custom_prompts::{default_prompts_dir, prompt_usage, CustomPrompt};
+use slide_core::event_log::{self, EventLog, ExportFormat, RecordedEntry, SessionEntry};
+use slide_core::file_watcher::{workspace_watcher, FileWatcher, Subscription};
+use slide_core::git_commit::{create_commit, generate_commit_message, CommitDiff};
+
+/// Width share of the composer in the split layout, in percent
+const SPLIT_INPUT_PERCENT: u16 = 55;
+/// Viewport height while the deck pane is shown
+const DECK_PANE_HEIGHT: u16 = 20;
+/// Exit code shown for a command stopped by an interrupt (128 + SIGINT)
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+/// Shortest time between two frames
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+/// Redraw at least this often for what changes with time alone: the task
+/// timer in the status bar, the composer's idle hints and the approval
+/// countdown
+const IDLE_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+/// How long the loop waits for input while nothing is running
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Mode {
@@ -60,7 +110,8 @@ pub enum AppExit {
 #[derive(Debug)]
 pub struct RunResult {
     pub exit: AppExit,
-    pub recent_files: Vec<String>,
+    /// Session to resume when the app runs again
+    pub session_id: String,
 }
 
 /// Codex風に簡略化されたアプリケーション状態
@@ -69,14 +120,20 @@ pub struct App {
     mode: Mode,
     status: RunStatus,
     last_tick: Instant,
-    // Chat state (簡略化)
-    messages: Vec<String>,
+    /// Transcript of the session, one cell per message, command or diff
+    history: Vec<HistoryCell>,
     // Chat scroll state
     chat_scroll_top: usize,
     chat_follow_bottom: bool,
     chat_viewport_height: usize,
     // UI state
     show_modal: bool,
+    /// Open the transcript pager on the next loop iteration
+    show_transcript: bool,
+    /// Open the help overlay on the next loop iteration
+    show_help: bool,
+    /// Stop the process on the next loop iteration (Ctrl+Z)
+    suspend: bool,
     modal_title: String,
     modal_body: String,
     // Popup state
@@ -86,10 +143,15 @@ pub struct App {
     popup_filtered_indices: Vec<usize>,
     popup_selected: usize,
     popup_filter: String,
+    /// Matches of the open file popup, filled by [`FileSearchManager`]
+    popup_files: FileSearchPopup,
     // Next action
     preview_path: Option<PathBuf>,
-    // MRU files
-    recent_files: Vec<String>,
+    /// Recent decks, the agent's directory and model, the split deck
+    workspace_state: WorkspaceState,
+    /// Where `workspace_state` is saved; `None` while a recorded session is
+    /// replayed
+    workspace: Option<PathBuf>,
     // Agent integration
     agent: Option<AgentHandle>,
     // Bottom pane integration (Codex風の統合UI)
@@ -97,10 +159,64 @@ pub struct App {
     // App event channel
     app_event_rx: tokio::sync::mpsc::UnboundedReceiver<AppEvent>,
     app_event_tx: AppEventSender,
+    file_search: FileSearchManager,
+    /// Ghost-text suggestions for the composer
+    suggestions: SuggestionManager,
+    /// Workspace file changes for the deck pane and the file search index
+    fs_changes: Option<Subscription>,
     // Inline viewport history (pending lines to insert above)
     // pending_history_lines removed - messages now insert directly
     // Assistant応答の行単位ストリーミング状態
     answer_stream: AnswerStreamState,
+    /// Command currently running, tailed above the composer
+    exec_cell: Option<ExecCell>,
+    /// Open the output pager on the next loop iteration
+    show_exec_output: bool,
+    /// Task plan of the agent, shown until every step is done
+    plan_cell: Option<PlanCell>,
+    /// Model, policies and usage for the status bar
+    status_info: StatusInfo,
+    /// Alerts for approvals and long tasks while the terminal is unfocused
+    notifier: Notifier,
+    /// Deck shown beside the composer in the split layout
+    deck_pane: Option<DeckPane>,
+    /// Where the bottom pane was drawn last, for routing mouse events
+    bottom_pane_area: Rect,
+    /// Saved sessions and recent decks
+    sessions: SessionStore,
+    /// Session the conversation is recorded to
+    session_id: String,
+    /// Full event stream of the session, for `slide replay`
+    event_log: EventLog,
+    /// Assistant output of the running turn, recorded when it completes
+    turn_reply: String,
+    /// Last prompt sent to the agent with its attachments, for `/retry`
+    last_turn: Option<(String, Vec<PathBuf>)>,
+    /// Messages submitted while a task runs, sent one turn at a time
+    queued_turns: VecDeque<(String, Vec<PathBuf>)>,
+    /// Templates in `~/.slide/prompts`, as last listed by the agent
+    custom_prompts: Vec<CustomPrompt>,
+    /// Instruction files the agent loaded, as last announced
+    instruction_files: Vec<PathBuf>,
+    /// Open the session picker on the next loop iteration
+    show_sessions: bool,
+    /// Content of the input area changed since the last frame
+    needs_redraw: bool,
+    /// Heights of the input area for the terminal size it was computed at;
+    /// `None` until the next frame recomputes it
+    layout: Option<(Rect, InputLayout)>,
+    last_draw: Instant,
+}
+
+/// How the input area splits the inline viewport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InputLayout {
+    input_width: u16,
+    input_height: u16,
+    bottom_height: u16,
+    radar_height: u16,
+    exec_height: u16,
+    plan_height: u16,
 }
 
 impl App {
@@ -124,68 +240,133 @@ impl App {
         }
     }
     pub fn new() -> Self {
-        Self::new_with_recents(Vec::new())
+        Self::new_with_state(WorkspaceState::default())
     }
 
     fn total_chat_lines(&self) -> usize {
-        // messages rendered as: for each message add a blank line after, except last; then prompt line
-        // number of lines from messages = if empty 0 else messages*2 - 1
-        let msg_lines = if self.messages.is_empty() {
-            0
-        } else {
-            self.messages.len() * 2 - 1
-        };
+        // Rows of every cell at the terminal width
+        let width = self.layout.map_or(80, |(size, _)| size.width);
+        let msg_lines = self
+            .history
+            .iter()
+            .map(|cell| cell.desired_height(width) as usize)
+            .sum::<usize>();
         // plus one prompt line always
         msg_lines + 1
     }
 
-    pub fn new_with_recents(recent_files: Vec<String>) -> Self {
+    pub fn new_with_state(workspace_state: WorkspaceState) -> Self {
         let (app_tx_raw, app_rx) = tokio::sync::mpsc::unbounded_channel();
         let app_tx = AppEventSender::new(app_tx_raw);
+        let fs_changes = workspace_watcher().map(FileWatcher::subscribe);
+        let mut file_search = FileSearchManager::new(
+            std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            app_tx.clone(),
+        );
+        if fs_changes.is_some() {
+            file_search = file_search.with_index();
+        }
         let s = Self {
             should_quit: false,
             mode: Mode::Normal,
             status: RunStatus::Idle,
             last_tick: Instant::now(),
-            messages: vec![banner_message()],
+            history: Vec::new(),
             chat_scroll_top: 0,
             chat_follow_bottom: true,
             chat_viewport_height: 0,
             show_modal: false,
-            modal_title: "Help".into(),
-            modal_body: "Keybindings:\n- i: Insert (compose)\n- Esc: Normal\n- Enter: Send message\n- h: Toggle help modal\n- c: Clear messages\n- q: Quit".into(),
+            show_transcript: false,
+            suspend: false,
+            show_help: false,
+            modal_title: String::new(),
+            modal_body: String::new(),
             active_popup: None,
             popup_title: String::new(),
             popup_items: Vec::new(),
             popup_filtered_indices: Vec::new(),
             popup_selected: 0,
             popup_filter: String::new(),
+            popup_files: FileSearchPopup::new(),
             preview_path: None,
-            recent_files,
+            workspace_state,
+            workspace: None,
             agent: None,
-            bottom_pane: BottomPane::new(BottomPaneParams{ has_input_focus: true, placeholder_text: "Ask Slide Code to do anything".into()}),
+            bottom_pane: BottomPane::new(BottomPaneParams {
+                has_input_focus: true,
+                placeholder_text: "Ask Slide Code to do anything".into(),
+                app_event_tx: app_tx.clone(),
+            }),
             app_event_rx: app_rx,
+            file_search,
+            suggestions: SuggestionManager::disabled(app_tx.clone()),
+            fs_changes,
             app_event_tx: app_tx,
             // pending_history_lines removed
             answer_stream: AnswerStreamState::new(),
+            exec_cell: None,
+            show_exec_output: false,
+            plan_cell: None,
+            status_info: StatusInfo::new(),
+            notifier: Notifier::new(slide_common::NotificationConfig::default()),
+            deck_pane: None,
+            bottom_pane_area: Rect::default(),
+            sessions: SessionStore::default(),
+            session_id: SessionStore::new_session_id(),
+            event_log: EventLog::default(),
+            turn_reply: String::new(),
+            last_turn: None,
+            queued_turns: VecDeque::new(),
+            custom_prompts: Vec::new(),
+            instruction_files: Vec::new(),
+            show_sessions: false,
+            needs_redraw: true,
+            layout: None,
+            last_draw: Instant::now(),
         };
         // Write a small banner to the log so the browser viewer has content
         append_log("[info] Slide TUI session started");
         s
     }
 
+    /// Draw the next frame with a fresh layout, after the content changed.
+    fn request_redraw(&mut self) {
+        self.needs_redraw = true;
+        self.layout = None;
+    }
+
+    /// Whether the loop should draw now: something changed, or time-based
+    /// content is due, and the last frame is at least [`FRAME_INTERVAL`] old.
+    fn frame_due(&self) -> bool {
+        let since = self.last_draw.elapsed();
+        since >= FRAME_INTERVAL && (self.needs_redraw || since >= IDLE_REDRAW_INTERVAL)
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
 
     fn on_tick(&mut self) {
+        let deck_changed = match self.fs_changes.as_mut() {
+            Some(changes) => changes.try_recv().is_some_and(|change| {
+                self.file_search.on_fs_change(&change);
+                self.deck_pane
+                    .as_mut()
+                    .is_some_and(|pane| pane.on_fs_change(&change))
+            }),
+            // Without a watcher the deck pane checks its file's mtime
+            None => self.deck_pane.as_mut().is_some_and(DeckPane::refresh),
+        };
+        if deck_changed {
+            self.request_redraw();
+        }
         // Simulate finishing a running task after 1.5s
         if self.status == RunStatus::Running
             && self.last_tick.elapsed() > Duration::from_millis(1500)
         {
             self.status = RunStatus::Idle;
+            self.needs_redraw = true;
         }
-
     }
 
     fn submit_message<B>(&mut self, text: String, terminal: &mut Terminal<B>)
@@ -195,28 +376,218 @@ impl App {
         if text.trim().is_empty() {
             return;
         }
+        let attachments = self.bottom_pane.take_recent_submission_attachments();
+        if self.status_info.task_running() {
+            self.queued_turns.push_back((text, attachments));
+            self.status_info.set_queued(self.queued_turns.len());
+            return;
+        }
+        self.send_turn(text, attachments, terminal);
+    }
 
-        // 見出し + 本文（接頭辞なし）で履歴へ
-        let mut lines: Vec<Line<'static>> = Vec::new();
-        lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled(
-            "You",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )));
-        for l in text.lines() {
-            lines.push(Line::from(l.to_string()));
+    /// Start the next queued message once the running task has ended.
+    fn send_queued_turn<B>(&mut self, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        if let Some((text, attachments)) = self.queued_turns.pop_front() {
+            self.status_info.set_queued(self.queued_turns.len());
+            self.send_turn(text, attachments, terminal);
+        }
+    }
+
+    /// Add a cell to the transcript and show it in the scrollback.
+    fn push_history<B>(&mut self, cell: HistoryCell, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        insert_history_lines(terminal, cell.display_lines());
+        self.history.push(cell);
+    }
+
+    /// Append streamed reply text to the agent message being written, or
+    /// start one. The scrollback shows it through the answer stream.
+    fn push_agent_text(&mut self, text: &str) {
+        match self.history.last_mut() {
+            Some(HistoryCell::AgentMessage { text: reply }) => reply.push_str(text),
+            _ => self.history.push(HistoryCell::AgentMessage {
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Most recently finished command, for the output pager
+    fn last_exec(&self) -> Option<&ExecCell> {
+        self.history.iter().rev().find_map(|cell| match cell {
+            HistoryCell::Exec(cell) => Some(cell),
+            _ => None,
+        })
+    }
+
+    /// Help overlay for the current keymap, commands and session.
+    fn help_lines(&self) -> Vec<Line<'static>> {
+        help_lines(&HelpContext {
+            commands: &SlashCommandRegistry::default(),
+            prompts: &self.custom_prompts,
+            session: self.status_info.facts(),
+            vim: self.bottom_pane.vim_mode().is_some(),
+        })
+    }
+
+    /// Write the session as recorded so far (messages, commands, diffs,
+    /// approvals) to `path`, by default `session-<id>.<ext>` in the
+    /// workspace.
+    fn export_chat<B>(
+        &self,
+        format: ExportFormat,
+        path: Option<String>,
+        terminal: &mut Terminal<B>,
+    ) where
+        B: ratatui::backend::Backend,
+    {
+        let log_path = self.event_log.session_path(&self.session_id);
+        if !log_path.is_file() {
+            insert_notice(
+                terminal,
+                "Export",
+                vec!["Nothing recorded for this session yet".into()],
+                Color::Red,
+            );
+            return;
+        }
+        let path = path
+            .unwrap_or_else(|| format!("session-{}.{}", self.session_id, format.extension()));
+        let title = format!("Session {}", self.session_id);
+        let result = event_log::load(&log_path)
+            .and_then(|entries| Ok(std::fs::write(&path, format.render(&title, &entries))?));
+        match result {
+            Ok(()) => insert_notice(
+                terminal,
+                "Export",
+                vec![format!("Wrote {path}")],
+                Color::Green,
+            ),
+            Err(e) => insert_notice(terminal, "Export", vec![format!("{e:#}")], Color::Red),
+        }
+    }
+
+    /// Draft a message for the current diff in the background, or commit
+    /// with `message`. The draft lands in the composer as `/commit <draft>`,
+    /// so it is edited there and committed with Enter.
+    fn commit<B>(&mut self, message: Option<String>, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        let dir = self
+            .status_info
+            .cwd()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let Some(message) = message else {
+            let app_tx = self.app_event_tx.clone();
+            tokio::spawn(async move {
+                let draft = async {
+                    let diff = tokio::task::spawn_blocking(move || CommitDiff::collect(&dir))
+                        .await??;
+                    let client = slide_core::client::client_from_env(None);
+                    generate_commit_message(client.as_ref(), &diff).await
+                };
+                let result = draft.await.map_err(|e: anyhow::Error| format!("{e:#}"));
+                app_tx.send(AppEvent::CommitDraft(result));
+            });
+            insert_notice(
+                terminal,
+                "Commit",
+                vec!["Drafting a commit message for the current diff…".into()],
+                Color::Cyan,
+            );
+            return;
+        };
+        let result = CommitDiff::collect(&dir)
+            .and_then(|diff| create_commit(&dir, &message, diff.staged));
+        match result {
+            Ok(summary) => insert_notice(terminal, "Commit", vec![summary], Color::Green),
+            Err(e) => insert_notice(terminal, "Commit", vec![format!("{e:#}")], Color::Red),
+        }
+    }
+
+    /// Put part of the transcript on the clipboard and say what was copied.
+    fn copy<B>(&mut self, target: CopyTarget, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        let copied = match target {
+            CopyTarget::Reply => self
+                .history
+                .iter()
+                .rev()
+                .find_map(|cell| match cell {
+                    HistoryCell::AgentMessage { .. } => Some((cell.plain_text(), "the last reply")),
+                    _ => None,
+                })
+                .ok_or_else(|| "No reply to copy yet".to_string()),
+            // The running command counts as the most recent one
+            CopyTarget::Exec(n) => self
+                .exec_cell
+                .iter()
+                .chain(self.history.iter().rev().filter_map(|cell| match cell {
+                    HistoryCell::Exec(cell) => Some(cell),
+                    _ => None,
+                }))
+                .nth(n - 1)
+                .map(|cell| (cell.output_text(), "the command output"))
+                .ok_or_else(|| match n {
+                    1 => "No command output to copy yet".to_string(),
+                    n => format!("Fewer than {n} commands ran in this session"),
+                }),
+            CopyTarget::Diff => self
+                .history
+                .iter()
+                .rev()
+                .find_map(|cell| match cell {
+                    HistoryCell::Diff { .. } => Some((cell.plain_text(), "the turn diff")),
+                    _ => None,
+                })
+                .ok_or_else(|| "No changes to copy yet".to_string()),
+        };
+        match copied {
+            Ok((text, what)) => self.copy_text(&text, what, terminal),
+            Err(message) => insert_notice(terminal, "Copy", vec![message], Color::Red),
         }
-        insert_history_lines(terminal, lines);
+    }
 
-        // Keep in messages for compatibility
-        self.messages.push(text.clone());
+    fn copy_text<B>(&mut self, text: &str, what: &str, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        self.bottom_pane.copy_to_clipboard(text);
+        let body = format!("Copied {what} ({} lines)", text.lines().count());
+        insert_notice(terminal, "Copy", vec![body], Color::Cyan);
+    }
+
+    fn record(&self, entry: SessionEntry) {
+        if let Err(e) = self.event_log.record(&self.session_id, &entry) {
+            append_log(&format!("[session] failed to record event: {e}"));
+        }
+    }
+
+    fn send_turn<B>(&mut self, text: String, attachments: Vec<PathBuf>, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        // 見出し + 本文（接頭辞なし）で履歴へ
+        self.push_history(
+            HistoryCell::user(text.clone(), attachments.clone()),
+            terminal,
+        );
         append_log(&format!("You: {}", text));
+        let _ = self.sessions.append(&self.session_id, "user", &text);
+        self.record(SessionEntry::User { text: text.clone() });
 
         if let Some(agent) = &self.agent {
-            agent.submit_text_bg(text);
+            agent.submit_text_bg(compose_with_attachments(&text, &attachments));
         }
+        self.last_turn = Some((text, attachments));
 
         // Simulate agent response for now
         self.status = RunStatus::Running;
@@ -231,9 +602,29 @@ impl App {
         if key.kind != KeyEventKind::Press {
             return;
         }
+        if let Some(kind) = self.active_popup {
+            self.handle_popup_key(kind, key);
+            return;
+        }
 
         // Global shortcuts
         match key {
+            // Esc stops a running turn rather than quitting; with the Vim
+            // keymap only once the composer is back in normal mode
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } if self.status_info.task_running()
+                && !self.bottom_pane.is_intercepting_input()
+                && self
+                    .bottom_pane
+                    .vim_mode()
+                    .is_none_or(|mode| mode == VimMode::Normal) =>
+            {
+                if let Some(agent) = &self.agent {
+                    agent.interrupt();
+                }
+                return;
+            }
             KeyEvent {
                 code: KeyCode::Char('q'),
                 modifiers: KeyModifiers::CONTROL,
@@ -241,7 +632,7 @@ impl App {
             }
             | KeyEvent {
                 code: KeyCode::Esc, ..
-            } => {
+            } if key.code != KeyCode::Esc || self.bottom_pane.vim_mode().is_none() => {
                 if self.show_modal {
                     self.show_modal = false;
                 } else {
@@ -249,31 +640,34 @@ impl App {
                 }
                 return;
             }
-            KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.show_modal = !self.show_modal;
-                return;
-            }
-            KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.messages.clear();
-                return;
-            }
+            // Vim keymap: Esc and `i` belong to the composer
             KeyEvent {
                 code: KeyCode::Char('i'),
                 ..
-            } if self.mode == Mode::Normal => {
+            } if self.mode == Mode::Normal && self.bottom_pane.vim_mode().is_none() => {
                 self.mode = Mode::Insert;
                 return;
             }
             _ => {}
         }
+        if let Some(action) = global_action(&key) {
+            match action {
+                GlobalAction::ShowHelp => self.show_help = true,
+                GlobalAction::ClearHistory => self.history.clear(),
+                GlobalAction::Transcript => self.show_transcript = true,
+                GlobalAction::ToggleExecOutput => {
+                    if let Some(cell) = self.exec_cell.as_mut() {
+                        cell.toggle_expanded();
+                    }
+                }
+                GlobalAction::FullExecOutput => {
+                    self.show_exec_output = self.exec_cell.is_some() || self.last_exec().is_some();
+                }
+                GlobalAction::CopyReply => self.copy(CopyTarget::Reply, terminal),
+                GlobalAction::Suspend => self.suspend = true,
+            }
+            return;
+        }
 
         // Delegate to bottom pane for input handling
         if let Some(result) = self.bottom_pane.handle_key_event(key) {
@@ -282,31 +676,300 @@ impl App {
                 InputResult::Submitted(text) => {
                     self.submit_message(text, terminal);
                 }
+                InputResult::Command(action) => {
+                    self.dispatch_slash_action(action, terminal);
+                }
+                InputResult::CommandError(message) => {
+                    insert_notice(terminal, "Command", vec![message], Color::Red);
+                }
                 InputResult::None => {}
             }
         }
     }
 
-    fn on_mouse_wheel(&mut self, delta_lines: isize) {
-        // Mouse scroll controls chat history only; disable follow-to-bottom on user scroll
-        if delta_lines == 0 {
+    /// Bracketed paste: the whole paste arrives as one event and bypasses
+    /// the per-key shortcuts.
+    pub fn handle_paste(&mut self, pasted: String) {
+        self.bottom_pane.handle_paste(pasted);
+    }
+
+    /// Carry out a slash command entered in the composer.
+    fn dispatch_slash_action<B>(&mut self, action: SlashAction, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        append_log(&format!("[command] {action:?}"));
+        match action {
+            SlashAction::NewSlide => match create_slide_from_template() {
+                Ok(path) => {
+                    insert_notice(
+                        terminal,
+                        "New",
+                        vec![format!("Created {path}")],
+                        Color::Green,
+                    );
+                    self.mru_add(path);
+                }
+                Err(e) => insert_notice(
+                    terminal,
+                    "New",
+                    vec![format!("Failed to create slide: {e}")],
+                    Color::Red,
+                ),
+            },
+            SlashAction::Preview(deck) => {
+                let path = self.deck_or_recent(deck);
+                match path {
+                    Some(path) => {
+                        self.open_preview(path);
+                    }
+                    None => insert_notice(
+                        terminal,
+                        "Preview",
+                        vec!["No deck found".into()],
+                        Color::Red,
+                    ),
+                }
+            }
+            SlashAction::OpenFile => self.open_file_search(),
+            SlashAction::SetModel(model) => {
+                self.workspace_state.model = Some(model.clone());
+                self.save_state();
+                if let Some(agent) = &self.agent {
+                    agent.submit_op_bg(Op::OverrideModel {
+                        model: model.clone(),
+                    });
+                }
+                insert_notice(
+                    terminal,
+                    "Model",
+                    vec![format!("Using {model} for the next turns")],
+                    Color::Cyan,
+                );
+            }
+            SlashAction::SetApprovals(mode) => {
+                if let Some(agent) = &self.agent {
+                    agent.submit_op_bg(Op::OverrideApprovalPolicy {
+                        approval_policy: mode,
+                    });
+                }
+                insert_notice(
+                    terminal,
+                    "Approvals",
+                    vec![format!("Approval policy: {mode}")],
+                    Color::Cyan,
+                );
+            }
+            SlashAction::Diff(deck) => {
+                let lines = match deck_versions(deck.as_deref()) {
+                    Err(e) => Err(e),
+                    Ok((path, versions)) => {
+                        let current = std::fs::read_to_string(&path).unwrap_or_default();
+                        // Compare against the newest snapshot that differs from the deck.
+                        let base = versions
+                            .iter()
+                            .rev()
+                            .filter_map(|v| v.read().ok())
+                            .find(|content| *content != current);
+                        match base {
+                            Some(base) => Ok((path, unified_diff(&base, &current))),
+                            None => Err(format!("No earlier version of {}", path.display())),
+                        }
+                    }
+                };
+                match lines {
+                    Ok((path, diff)) => {
+                        let mut out = vec![
+                            Line::from(""),
+                            Line::from(Span::styled(
+                                format!("Diff {}", path.display()),
+                                Style::default()
+                                    .fg(Color::Cyan)
+                                    .add_modifier(Modifier::BOLD),
+                            )),
+                        ];
+                        out.extend(diff.lines().map(|l| {
+                            let color = if l.starts_with("@@") {
+                                Color::Cyan
+                            } else if l.starts_with('+') {
+                                Color::Green
+                            } else if l.starts_with('-') {
+                                Color::Red
+                            } else {
+                                Color::Reset
+                            };
+                            Line::from(Span::styled(l.to_string(), Style::default().fg(color)))
+                        }));
+                        insert_history_lines(terminal, out);
+                    }
+                    Err(e) => insert_notice(terminal, "Diff", vec![e], Color::Red),
+                }
+            }
+            SlashAction::Undo(deck) => {
+                let result = deck_versions(deck.as_deref()).and_then(|(path, versions)| {
+                    let current = std::fs::read_to_string(&path).unwrap_or_default();
+                    // The version before the newest snapshot matching the deck.
+                    let at = versions
+                        .iter()
+                        .rposition(|v| v.read().is_ok_and(|c| c == current))
+                        .unwrap_or(versions.len());
+                    let previous = at
+                        .checked_sub(1)
+                        .and_then(|i| versions.get(i))
+                        .ok_or_else(|| format!("Nothing to undo in {}", path.display()))?;
+                    VersionStore::new(".")
+                        .restore(&path, previous)
+                        .map_err(|e| e.to_string())?;
+                    Ok(format!(
+                        "Restored {} to {}",
+                        path.display(),
+                        previous.label()
+                    ))
+                });
+                match result {
+                    Ok(message) => insert_notice(terminal, "Undo", vec![message], Color::Green),
+                    Err(e) => insert_notice(terminal, "Undo", vec![e], Color::Red),
+                }
+            }
+            SlashAction::Split(None) if self.deck_pane.is_some() => {
+                self.remember_deck_pane();
+                self.deck_pane = None;
+                self.workspace_state.open_deck = None;
+                self.save_state();
+            }
+            SlashAction::Split(deck) => {
+                let path = self.deck_or_recent(deck);
+                match path {
+                    Some(path) => self.open_deck_pane(path),
+                    None => {
+                        insert_notice(terminal, "Split", vec!["No deck found".into()], Color::Red)
+                    }
+                }
+            }
+            SlashAction::Sessions => self.show_sessions = true,
+            SlashAction::Copy(target) => self.copy(target, terminal),
+            SlashAction::Commit(message) => self.commit(message, terminal),
+            SlashAction::ExportChat { format, path } => self.export_chat(format, path, terminal),
+            SlashAction::Retry(prompt) => {
+                let Some((last, attachments)) = self.last_turn.clone() else {
+                    insert_notice(
+                        terminal,
+                        "Retry",
+                        vec!["Nothing to retry yet".into()],
+                        Color::Red,
+                    );
+                    return;
+                };
+                // A retry replaces the turn still in flight
+                if self.status_info.task_running() {
+                    if let Some(agent) = &self.agent {
+                        agent.interrupt();
+                    }
+                }
+                self.send_turn(prompt.unwrap_or(last), attachments, terminal);
+            }
+            SlashAction::SetCwd(path) => {
+                let Some(agent) = &self.agent else {
+                    insert_notice(
+                        terminal,
+                        "Directory",
+                        vec!["No agent is running".into()],
+                        Color::Red,
+                    );
+                    return;
+                };
+                // Takes effect after the running turn; CwdChanged updates the status bar
+                agent.submit_op_bg(Op::SetCwd {
+                    path: PathBuf::from(path),
+                });
+            }
+            SlashAction::UndoTurn => {
+                let Some(agent) = &self.agent else {
+                    insert_notice(
+                        terminal,
+                        "Undo",
+                        vec!["No agent is running".into()],
+                        Color::Red,
+                    );
+                    return;
+                };
+                // Answered with TurnReverted once the running turn is over
+                agent.submit_op_bg(Op::RevertTurn);
+            }
+            action @ (SlashAction::Pause | SlashAction::Resume) => {
+                let Some(agent) = &self.agent else {
+                    insert_notice(
+                        terminal,
+                        "Plan",
+                        vec!["No agent is running".into()],
+                        Color::Red,
+                    );
+                    return;
+                };
+                if matches!(action, SlashAction::Pause) {
+                    agent.pause_plan();
+                } else {
+                    agent.resume_plan();
+                }
+            }
+            SlashAction::Prompts => {
+                let lines = if self.custom_prompts.is_empty() {
+                    vec![format!(
+                        "No prompts yet; save templates as {}/<name>.md",
+                        default_prompts_dir().display()
+                    )]
+                } else {
+                    self.custom_prompts
+                        .iter()
+                        .map(|p| {
+                            let head = format!("/{} {}", p.name, prompt_usage(p));
+                            format!(
+                                "{:<22} {}",
+                                head.trim_end(),
+                                p.description.as_deref().unwrap_or("")
+                            )
+                        })
+                        .collect()
+                };
+                insert_notice(terminal, "Prompts", lines, Color::Cyan);
+                // Pick up templates added since the last listing
+                if let Some(agent) = &self.agent {
+                    agent.submit_op_bg(Op::ListCustomPrompts);
+                }
+            }
+            SlashAction::Help => {
+                self.show_help = true;
+            }
+        }
+    }
+
+    /// Leave the inline app to preview `path`.
+    fn open_preview(&mut self, path: PathBuf) {
+        self.mru_add(path.display().to_string());
+        self.preview_path = Some(path);
+        self.should_quit = true;
+    }
+
+    /// Clicks and the wheel go to the bottom pane (popups, approval diff).
+    /// Elsewhere the wheel opens the transcript, since the terminal no longer
+    /// scrolls its own scrollback while the mouse is captured.
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if self
+            .bottom_pane
+            .handle_mouse_event(mouse, self.bottom_pane_area)
+        {
             return;
         }
-        if delta_lines < 0 {
-            // scroll down (towards bottom)
-            self.chat_scroll_top = self
-                .chat_scroll_top
-                .saturating_add(delta_lines.unsigned_abs() as usize);
-        } else {
-            // scroll up (towards top)
-            let dec = delta_lines as usize;
-            self.chat_scroll_top = self.chat_scroll_top.saturating_sub(dec);
+        if mouse.kind == MouseEventKind::ScrollUp {
+            self.show_transcript = true;
         }
-        self.clamp_scroll_top();
-        self.chat_follow_bottom = self.chat_scroll_top >= self.max_scroll_top();
     }
 
     fn handle_popup_key(&mut self, kind: PopupKind, key: KeyEvent) {
+        if kind == PopupKind::FileSearch {
+            self.handle_file_search_key(key);
+            return;
+        }
         match key.code {
             KeyCode::Esc => {
                 self.active_popup = None;
@@ -346,10 +1009,7 @@ impl App {
                     .get(self.popup_selected)
                     .copied()
                 {
-                    match kind {
-                        PopupKind::Command => self.exec_command_palette(idx),
-                        PopupKind::FileSearch => self.exec_file_open(idx),
-                    }
+                    self.exec_command_palette(idx);
                 }
             }
             _ => {}
@@ -368,13 +1028,44 @@ impl App {
         self.popup_selected = 0;
     }
 
+    /// Keys of the open file popup: the filter is a fuzzy query answered
+    /// in the background, so the list updates as results come in.
+    fn handle_file_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.active_popup = None,
+            KeyCode::Down => self.popup_files.move_down(),
+            KeyCode::Up => self.popup_files.move_up(),
+            KeyCode::Backspace => {
+                self.popup_filter.pop();
+                self.search_files();
+            }
+            KeyCode::Char(c)
+                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+            {
+                self.popup_filter.push(c);
+                self.search_files();
+            }
+            KeyCode::Enter => {
+                if let Some(path) = self.popup_files.selected_match().map(str::to_string) {
+                    self.exec_file_open(path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Search every file of the workspace, decks first.
     fn open_file_search(&mut self) {
         self.active_popup = Some(PopupKind::FileSearch);
-        self.popup_title = "Search slides/*.md".into();
-        self.popup_items = find_markdown_files();
+        self.popup_title = "Open file".into();
         self.popup_filter.clear();
-        self.popup_filtered_indices = (0..self.popup_items.len()).collect();
-        self.popup_selected = 0;
+        self.popup_files = FileSearchPopup::new();
+        self.search_files();
+    }
+
+    fn search_files(&mut self) {
+        self.popup_files.set_query(&self.popup_filter);
+        self.file_search.on_open_query(self.popup_filter.clone());
     }
 
     fn exec_command_palette(&mut self, idx: usize) {
@@ -383,41 +1074,36 @@ impl App {
         match cmd.as_str() {
             "New Slide from Template" => match create_slide_from_template() {
                 Ok(path) => {
-                    self.modal_title = "Created".into();
-                    self.modal_body = format!("Created new slide: {}", path);
+                    self.modal_title = t(Msg::ModalCreated).into();
+                    self.modal_body = tf(Msg::ModalCreatedSlide, &[("path", &path)]);
                     self.show_modal = true;
                     self.mru_add(path);
                 }
                 Err(e) => {
-                    self.modal_title = "Error".into();
-                    self.modal_body = format!("Failed to create slide: {}", e);
+                    self.modal_title = t(Msg::ModalError).into();
+                    self.modal_body = tf(Msg::ModalCreateFailed, &[("error", &e)]);
                     self.show_modal = true;
                 }
             },
-            "Open Slide Preview (from file)" => {
-                // TODO: Add file search back if needed
-                self.modal_title = "Not Implemented".into();
-                self.modal_body = "File search functionality not yet implemented".into();
-                self.show_modal = true;
-            }
-            "Save Chat to slides/draft.md" => match save_chat_as_draft(&self.messages) {
+            "Open Slide Preview (from file)" => self.open_file_search(),
+            "Save Chat to slides/draft.md" => match save_chat_as_draft(&self.history) {
                 Ok(path) => {
-                    self.modal_title = "Saved".into();
-                    self.modal_body = format!("Saved to {}", path);
+                    self.modal_title = t(Msg::ModalSaved).into();
+                    self.modal_body = tf(Msg::ModalSavedTo, &[("path", &path)]);
                     self.show_modal = true;
                     self.mru_add(path);
                 }
                 Err(e) => {
-                    self.modal_title = "Error".into();
-                    self.modal_body = format!("Failed to save draft: {}", e);
+                    self.modal_title = t(Msg::ModalError).into();
+                    self.modal_body = tf(Msg::ModalSaveFailed, &[("error", &e)]);
                     self.show_modal = true;
                 }
             },
             "Toggle Help" => {
-                self.show_modal = !self.show_modal;
+                self.show_help = true;
             }
             "Clear Messages" => {
-                self.messages.clear();
+                self.history.clear();
             }
             "Quit" => {
                 self.quit();
@@ -431,51 +1117,278 @@ impl App {
         }
     }
 
-    fn exec_file_open(&mut self, idx_in_items: usize) {
+    fn exec_file_open(&mut self, path: String) {
         self.active_popup = None;
-        if let Some(path) = self.popup_items.get(idx_in_items) {
-            self.preview_path = Some(PathBuf::from(path));
-            self.mru_add(path.clone());
-            self.should_quit = true; // exit app loop to launch preview
+        self.preview_path = Some(PathBuf::from(&path));
+        self.mru_add(path);
+        self.should_quit = true; // exit app loop to launch preview
+    }
+
+    /// The named deck, or the most recent one when no name is given.
+    fn deck_or_recent(&self, deck: Option<String>) -> Option<PathBuf> {
+        match deck {
+            Some(name) => resolve_deck(&name),
+            None => self
+                .workspace_state
+                .recent_files
+                .first()
+                .map(PathBuf::from)
+                .or_else(|| project_decks().into_iter().next()),
         }
     }
 
     fn mru_add(&mut self, path: String) {
-        // move-to-front unique
-        self.recent_files.retain(|p| p != &path);
-        self.recent_files.insert(0, path);
-        if self.recent_files.len() > 10 {
-            self.recent_files.truncate(10);
+        self.workspace_state.touch(path);
+        self.save_state();
+    }
+
+    /// Write `.slide/state.json`. Best-effort; failures are logged.
+    fn save_state(&self) {
+        let Some(workspace) = &self.workspace else {
+            return;
+        };
+        if let Err(e) = self.workspace_state.save(workspace) {
+            append_log(&format!("[state] failed to save workspace state: {e:#}"));
+        }
+    }
+
+    /// Show `path` beside the composer at the slide it was left at.
+    fn open_deck_pane(&mut self, path: PathBuf) {
+        self.remember_deck_pane();
+        let slide = self.workspace_state.slide_position(&path);
+        self.deck_pane = Some(DeckPane::open(path.clone()).at_slide(slide));
+        self.workspace_state.open_deck = Some(path);
+        self.save_state();
+    }
+
+    /// Keep the slide the deck pane shows for the next time the deck opens.
+    fn remember_deck_pane(&mut self) {
+        if let Some(pane) = &self.deck_pane {
+            self.workspace_state
+                .set_slide_position(pane.path(), pane.current_slide());
+        }
+    }
+
+    /// Pick up the previous run in this workspace: the model chosen with
+    /// `/model` (unless one is given on the command line), the agent's
+    /// directory and the deck shown in the split layout.
+    fn restore_workspace_state(&mut self) {
+        let state = self.workspace_state.clone();
+        if let Some(agent) = &self.agent {
+            if let Some(model) = state
+                .model
+                .filter(|_| std::env::var_os("SLIDE_MODEL").is_none())
+            {
+                agent.submit_op_bg(Op::OverrideModel { model });
+            }
+            if let Some(cwd) = state.cwd.filter(|cwd| cwd.is_dir()) {
+                agent.submit_op_bg(Op::SetCwd { path: cwd });
+            }
+        }
+        if let Some(deck) = state.open_deck.filter(|deck| deck.is_file()) {
+            self.open_deck_pane(deck);
+        }
+    }
+
+    /// A patch or `write_file` changed `paths`; decks among them become the
+    /// most recent files.
+    fn on_files_touched(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            if path.extension().is_none_or(|e| e != "md") {
+                continue;
+            }
+            let path = match &self.workspace {
+                Some(workspace) => path.strip_prefix(workspace).unwrap_or(&path),
+                None => &path,
+            };
+            self.mru_add(path.display().to_string());
+        }
+    }
+
+    /// Replay a saved session into the transcript and the agent's memory.
+    fn resume_session<B>(&mut self, id: String, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        let messages = self.sessions.load(&id);
+        for m in &messages {
+            let cell = if m.role == "user" {
+                HistoryCell::user(m.text.clone(), Vec::new())
+            } else {
+                HistoryCell::AgentMessage {
+                    text: m.text.clone(),
+                }
+            };
+            self.push_history(cell, terminal);
+        }
+        if let Some(agent) = &self.agent {
+            agent.submit_op_bg(Op::RestoreConversation {
+                messages: messages
+                    .iter()
+                    .map(|m| (m.role.clone(), m.text.clone()))
+                    .collect(),
+            });
+        }
+        self.session_id = id;
+        insert_notice(
+            terminal,
+            "Session",
+            vec![format!("Resumed session ({} messages)", messages.len())],
+            Color::Cyan,
+        );
+    }
+
+    /// Show a saved plan and let the agent continue it.
+    fn continue_plan<B>(&mut self, record: PlanRecord, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        let Some(agent) = &self.agent else {
+            insert_notice(
+                terminal,
+                "Plan",
+                vec!["No agent is running".into()],
+                Color::Red,
+            );
+            return;
+        };
+        let mut notice = vec![format!("Resuming plan {}: {}", record.id, record.task)];
+        if let Some(i) = record.first_open_step() {
+            notice.push(format!(
+                "Continuing at step {}/{}: {}",
+                i + 1,
+                record.steps.len(),
+                record.steps[i].step
+            ));
+        }
+        insert_notice(terminal, "Plan", notice, Color::Cyan);
+        let prompt = record.resume_prompt();
+        let _ = self.sessions.append(&self.session_id, "user", &prompt);
+        self.record(SessionEntry::User {
+            text: prompt.clone(),
+        });
+        self.plan_cell = Some(PlanCell::new(record.args()));
+        agent.submit_op_bg(Op::ContinuePlan { record });
+        // /retry sends the continuation as a plain prompt
+        self.last_turn = Some((prompt, Vec::new()));
+        self.status = RunStatus::Running;
+        self.last_tick = Instant::now();
+    }
+
+    /// Stop the agent before the terminal is restored: the running turn and
+    /// its commands end and the plan is saved. Events up to
+    /// `ShutdownComplete` are still recorded; after [`SHUTDOWN_TIMEOUT`] the
+    /// app exits anyway.
+    async fn shutdown_agent(&mut self) {
+        let Some(mut agent) = self.agent.take() else {
+            return;
+        };
+        if agent.codex.submit(Op::Shutdown).await.is_err() {
+            return;
+        }
+        let wait = async {
+            while let Some(ev) = agent.rx.recv().await {
+                let done = matches!(ev, CoreEvent::ShutdownComplete);
+                self.record(SessionEntry::Event(ev));
+                if done {
+                    return;
+                }
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, wait).await.is_err() {
+            append_log("[session] agent did not shut down in time");
+        } else {
+            append_log("[session] agent shut down");
+        }
+    }
+
+    fn on_session_choice<B>(&mut self, choice: SessionChoice, terminal: &mut Terminal<B>)
+    where
+        B: ratatui::backend::Backend,
+    {
+        match choice {
+            SessionChoice::Resume(id) => self.resume_session(id, terminal),
+            SessionChoice::New => {
+                if let Some(agent) = &self.agent {
+                    agent.submit_op_bg(Op::RestoreConversation {
+                        messages: Vec::new(),
+                    });
+                }
+                self.session_id = SessionStore::new_session_id();
+                insert_notice(
+                    terminal,
+                    "Session",
+                    vec!["Started a new session".into()],
+                    Color::Cyan,
+                );
+            }
+            SessionChoice::OpenDeck(path) => {
+                self.open_preview(path);
+            }
         }
     }
 }
 
-pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
+/// Run the inline app. `resume` replays a saved session into the new one;
+/// `plan` is a saved plan the agent continues right away.
+pub async fn run_app(resume: Option<String>, plan: Option<PlanRecord>) -> Result<RunResult> {
     // 通常スクリーン＋インラインビューポート（下部だけ描画）
     enable_raw_mode()?;
-    let stdout = io::stdout();
+    let mut stdout = io::stdout();
+    execute!(stdout, EnableBracketedPaste, EnableFocusChange)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::with_options(backend)?;
 
-    let mut app = App::new_with_recents(init_recent_files);
+    let workspace = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let mut app = App::new_with_state(WorkspaceState::load(&workspace));
+    app.workspace = Some(workspace);
+    let config = slide_common::SlideConfig::load().await.unwrap_or_default();
+    let scrollback = TranscriptStore::new(config.transcript.max_lines)
+        .with_spill_file(app.sessions.transcript_path(&app.session_id));
+    terminal.set_scrollback(scrollback);
+    app.bottom_pane.set_editor_config(&config.editor);
+    app.suggestions =
+        SuggestionManager::from_config(&config.editor.suggestions, app.app_event_tx.clone());
+    app.bottom_pane.set_suggestions_enabled(app.suggestions.is_enabled());
+    app.notifier = Notifier::new(config.notifications.clone());
+    if config.editor.mouse == slide_common::MouseMode::Capture {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    }
     // Spawn core agent
     match crate::agent::AgentHandle::spawn().await {
-        Ok(agent) => app.agent = Some(agent),
+        Ok(agent) => {
+            agent.submit_op_bg(Op::ListCustomPrompts);
+            app.agent = Some(agent);
+        }
         Err(_e) => {
-            app.messages
-                .push("(failed to start agent; using local demo)".into());
+            app.history.push(HistoryCell::Error {
+                message: "failed to start agent; using local demo".into(),
+            });
         }
     }
 
+    app.restore_workspace_state();
+
     // Prepare inline viewport and emit startup banner into scrollback
     draw_input_area_only(&mut terminal, &mut app)?;
     insert_history_lines(&mut terminal, banner_history_lines());
+    if let Some(id) = resume {
+        app.resume_session(id, &mut terminal);
+    }
+    if let Some(record) = plan {
+        app.continue_plan(record, &mut terminal);
+    }
 
     loop {
         // Drain app events from UI widgets
         while let Ok(ev) = app.app_event_rx.try_recv() {
+            app.request_redraw();
             match ev {
                 AppEvent::ExecApproval { id, decision } => {
+                    app.record(SessionEntry::Approval {
+                        id: id.clone(),
+                        decision: decision.clone(),
+                    });
                     if let Some(agent) = &app.agent {
                         let c = agent.codex.clone();
                         tokio::spawn(async move {
@@ -484,6 +1397,10 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
                     }
                 }
                 AppEvent::PatchApproval { id, decision } => {
+                    app.record(SessionEntry::Approval {
+                        id: id.clone(),
+                        decision: decision.clone(),
+                    });
                     if let Some(agent) = &app.agent {
                         let c = agent.codex.clone();
                         tokio::spawn(async move {
@@ -491,35 +1408,69 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
                         });
                     }
                 }
+                AppEvent::StartFileSearch(query) => {
+                    app.file_search.on_user_query(query);
+                }
+                AppEvent::FileSearchResult { query, matches } => {
+                    app.bottom_pane.on_file_search_result(query, matches);
+                }
+                AppEvent::OpenFileSearchResult { query, matches } => {
+                    app.popup_files.set_matches(&query, matches);
+                }
+                AppEvent::StartSuggestion(text) => app.suggestions.on_text_changed(text),
+                AppEvent::CancelSuggestion => app.suggestions.cancel(),
+                AppEvent::SuggestionResult { text, suggestion } => {
+                    app.bottom_pane.on_suggestion(text, suggestion);
+                    app.request_redraw();
+                }
+                AppEvent::CommitDraft(Ok(message)) => {
+                    app.bottom_pane
+                        .set_composer_text(&format!("/commit {}", message.trim_end()));
+                    insert_notice(
+                        &mut terminal,
+                        "Commit",
+                        vec!["Edit the message in the composer and press Enter to commit".into()],
+                        Color::Cyan,
+                    );
+                }
+                AppEvent::CommitDraft(Err(e)) => {
+                    insert_notice(&mut terminal, "Commit", vec![e], Color::Red);
+                }
             }
         }
 
-        // Update chat viewport height based on terminal size (header=1, status=1)
-        if let Ok(sz) = terminal.size() {
-            let h = sz.height.saturating_sub(1 + 1) as usize;
-            // Chat height handled by layout
+        if app.bottom_pane.flush_paste_burst_if_due() {
+            app.request_redraw();
         }
 
         // 下部の入力エリアのみ描画（履歴はスクロールバックに積む）
-        draw_input_area_only(&mut terminal, &mut app)?;
+        if app.frame_due() {
+            draw_input_area_only(&mut terminal, &mut app)?;
+        }
 
         // Handle events with timeout
-        if event::poll(Duration::from_millis(100))? {
+        // Poll sooner while keys are held as a possible paste so they show up
+        // promptly, and while a task streams output
+        let poll_timeout = if app.bottom_pane.is_in_paste_burst() {
+            PasteBurst::recommended_flush_delay()
+        } else if app.needs_redraw || app.status_info.task_running() {
+            FRAME_INTERVAL
+        } else {
+            IDLE_POLL_INTERVAL
+        };
+        if event::poll(poll_timeout)? {
+            app.request_redraw();
             match event::read()? {
-                Event::Mouse(mev) => match mev.kind {
-                    MouseEventKind::ScrollUp => app.on_mouse_wheel(3),
-                    MouseEventKind::ScrollDown => app.on_mouse_wheel(-3),
-                    _ => {}
-                },
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse),
                 Event::Key(key) => {
                     app.handle_key_event(key, &mut terminal);
                 }
+                Event::FocusGained => app.notifier.set_focused(true),
+                Event::FocusLost => app.notifier.set_focused(false),
+                Event::Paste(pasted) => {
+                    app.handle_paste(pasted);
+                }
                 Event::Resize(_, _) => {
-                    // Recompute viewport height and snap to bottom so latest is visible
-                    if let Ok(sz) = terminal.size() {
-                        let h = sz.height.saturating_sub(1 + 1) as usize;
-                        // Chat height handled by layout
-                    }
                     // Keep latest visible on resize only when follow-bottom is enabled
                     if app.chat_follow_bottom {
                         app.chat_scroll_top = usize::MAX; // レンダでクランプ
@@ -527,7 +1478,6 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
                         app.clamp_scroll_top();
                     }
                 }
-                _ => {}
             }
         }
 
@@ -542,7 +1492,11 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
                 }
             }
         }
+        if !drained_events.is_empty() {
+            app.request_redraw();
+        }
         for ev in drained_events {
+            app.record(SessionEntry::Event(ev.clone()));
             handle_core_event(&mut app, ev, &mut terminal);
         }
 
@@ -550,14 +1504,87 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
             break;
         }
 
-        // Tick and sleep
+        #[cfg(unix)]
+        if std::mem::take(&mut app.suspend) {
+            let mouse = config.editor.mouse == slide_common::MouseMode::Capture;
+            crate::suspend::suspend(&mut terminal, mouse)?;
+            // Focus events were off while stopped
+            app.notifier.set_focused(true);
+            app.request_redraw();
+        }
+
+        // The pagers below take over the screen; the input area is drawn
+        // again when they return
+        if app.show_transcript || app.show_help || app.show_sessions || app.show_exec_output {
+            app.request_redraw();
+        }
+
+        if std::mem::take(&mut app.show_transcript) {
+            let scrollback = terminal.scrollback();
+            let mut view =
+                crate::transcript_view::TranscriptView::new(scrollback.recent().cloned().collect())
+                    .with_earlier(scrollback.earlier());
+            if let Some(path) = view.run().await? {
+                app.open_preview(path);
+            }
+            if let Some(text) = view.take_copied() {
+                app.copy_text(&text, "the transcript", &mut terminal);
+            }
+        }
+
+        if std::mem::take(&mut app.show_help) {
+            let mut view = crate::transcript_view::TranscriptView::new(app.help_lines())
+                .with_title(t(Msg::HelpTitle))
+                .at_top();
+            view.run().await?;
+            if let Some(text) = view.take_copied() {
+                app.copy_text(&text, "the help", &mut terminal);
+            }
+        }
+
+        if std::mem::take(&mut app.show_sessions) {
+            let choice =
+                SessionPicker::new(app.sessions.clone(), &app.workspace_state.recent_files)
+                    .run()
+                    .await?;
+            if let Some(choice) = choice {
+                app.on_session_choice(choice, &mut terminal);
+            }
+        }
+
+        if std::mem::take(&mut app.show_exec_output) {
+            if let Some(cell) = app.exec_cell.as_ref().or(app.last_exec()) {
+                let mut view = crate::transcript_view::TranscriptView::new(cell.full_output())
+                    .with_title("Command output")
+                    .with_copy_text(cell.output_text());
+                if let Some(path) = view.run().await? {
+                    app.open_preview(path);
+                }
+                if let Some(text) = view.take_copied() {
+                    app.copy_text(&text, "the command output", &mut terminal);
+                }
+            }
+        }
+
         app.on_tick();
-        sleep(Duration::from_millis(16)).await;
+        // Yield to the agent's tasks between iterations
+        tokio::task::yield_now().await;
     }
 
+    app.shutdown_agent().await;
+    app.remember_deck_pane();
+    app.save_state();
+
     // Cleanup terminal (inline viewport)
+    execute!(
+        io::stdout(),
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture
+    )?;
     disable_raw_mode()?;
     terminal.show_cursor()?;
+    terminal.scrollback_mut().remove_spill_file();
 
     let exit = if let Some(path) = app.preview_path {
         AppExit::Preview(path)
@@ -566,26 +1593,224 @@ pub async fn run_app(init_recent_files: Vec<String>) -> Result<RunResult> {
     };
     Ok(RunResult {
         exit,
-        recent_files: app.recent_files,
+        session_id: app.session_id,
     })
 }
 
+/// Re-render a recorded session (`slide replay`). Events go through the same
+/// handlers as a live session, at most [`REPLAY_STEP`] apart; approvals show
+/// as notices with nothing to answer, and nothing is recorded again.
+pub async fn run_replay(entries: Vec<RecordedEntry>, title: String) -> Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnableBracketedPaste)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::with_options(backend)?;
+
+    let mut app = App::new();
+    app.sessions = SessionStore::default().read_only();
+    app.notifier = Notifier::new(slide_common::NotificationConfig {
+        method: slide_common::NotificationMethod::Off,
+        ..Default::default()
+    });
+
+    draw_input_area_only(&mut terminal, &mut app)?;
+    insert_history_lines(&mut terminal, banner_history_lines());
+    insert_notice(
+        &mut terminal,
+        "Replay",
+        vec![format!("{title} ({} entries)", entries.len())],
+        Color::Cyan,
+    );
+
+    let mut last_ts = entries.first().map_or(0, |e| e.ts_ms);
+    for RecordedEntry { ts_ms, entry } in entries {
+        let gap = Duration::from_millis(ts_ms.saturating_sub(last_ts));
+        last_ts = ts_ms;
+        sleep(gap.min(REPLAY_STEP)).await;
+        if replay_cancelled()? {
+            break;
+        }
+        match entry {
+            SessionEntry::User { text } => {
+                app.push_history(HistoryCell::user(text, Vec::new()), &mut terminal);
+            }
+            SessionEntry::Event(CoreEvent::ExecApprovalRequest {
+                command,
+                reason,
+                risk,
+                ..
+            }) => {
+                let mut body = vec![format!("$ {}", command.join(" "))];
+                body.extend(reason);
+                body.extend(risk);
+                insert_notice(&mut terminal, "Approval requested", body, Color::Yellow);
+            }
+            SessionEntry::Event(CoreEvent::ExecPlanApprovalRequest { commands, .. }) => {
+                let body = commands
+                    .iter()
+                    .map(|command| format!("$ {}", command.join(" ")))
+                    .collect();
+                insert_notice(&mut terminal, "Approval requested", body, Color::Yellow);
+            }
+            SessionEntry::Event(CoreEvent::ApplyPatchApprovalRequest {
+                changes, reason, ..
+            }) => {
+                let mut body: Vec<String> = changes
+                    .keys()
+                    .map(|path| format!("edit {}", path.display()))
+                    .collect();
+                body.extend(reason);
+                insert_notice(&mut terminal, "Approval requested", body, Color::Yellow);
+            }
+            SessionEntry::Approval { decision, .. } => {
+                let body = vec![event_log::decision_text(&decision)];
+                insert_notice(&mut terminal, "Approval", body, Color::Yellow);
+            }
+            SessionEntry::Event(ev) => handle_core_event(&mut app, ev, &mut terminal),
+        }
+        draw_input_area_only(&mut terminal, &mut app)?;
+    }
+
+    insert_notice(
+        &mut terminal,
+        "Replay",
+        vec!["End of the recorded session; press q to exit".into()],
+        Color::Cyan,
+    );
+    draw_input_area_only(&mut terminal, &mut app)?;
+    while !replay_cancelled()? {
+        sleep(Duration::from_millis(16)).await;
+    }
+
+    execute!(io::stdout(), DisableBracketedPaste)?;
+    disable_raw_mode()?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Longest wait for the agent to stop when the app exits
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Longest pause between two replayed entries
+const REPLAY_STEP: Duration = Duration::from_millis(40);
+
+/// Whether q, Esc or Ctrl-C was pressed; other input is dropped.
+fn replay_cancelled() -> Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c =
+                key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+            if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
 fn draw_input_area_only<B>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
 where
     B: ratatui::backend::Backend,
 {
     let size = terminal.size()?;
+    let size = Rect::new(0, 0, size.width, size.height);
+    // A resize also invalidates the layout
+    let layout = match app.layout {
+        Some((at, layout)) if at == size => layout,
+        _ => {
+            let layout = input_layout(app, size);
+            app.layout = Some((size, layout));
+            layout
+        }
+    };
+    let InputLayout {
+        input_width,
+        input_height,
+        bottom_height,
+        radar_height,
+        exec_height,
+        plan_height,
+    } = layout;
+
+    let viewport = Rect {
+        x: 0,
+        y: size.height.saturating_sub(input_height),
+        width: size.width,
+        height: input_height,
+    };
+
+    // Update viewport area to match current terminal size
+    terminal.set_viewport_area(viewport);
+
+    terminal.draw(|f| {
+        let [input_area, deck_area] =
+            Layout::horizontal([Constraint::Length(input_width), Constraint::Min(0)])
+                .areas(viewport);
+        draw_input_ui(
+            f,
+            app,
+            input_area,
+            bottom_height,
+            radar_height,
+            exec_height,
+            plan_height,
+        );
+        if let Some(pane) = &app.deck_pane {
+            pane.render(deck_area, f.buffer_mut());
+        }
+    })?;
+    app.needs_redraw = false;
+    app.last_draw = Instant::now();
+
+    Ok(())
+}
+
+/// Split the viewport of a terminal of `size` between the plan, the running
+/// command, the radar, the status bar and the composer. Wraps the composer
+/// and command output, so frames reuse it until the content changes.
+fn input_layout(app: &App, size: Rect) -> InputLayout {
+    // Split layout: composer on the left, deck on the right
+    let input_width = if app.deck_pane.is_some() {
+        size.width.saturating_mul(SPLIT_INPUT_PERCENT) / 100
+    } else {
+        size.width
+    };
     let status_height: u16 = 1;
     let radar_pref_height: u16 = RadarAnimation::HEIGHT as u16;
-    let desired_bottom_height = app.bottom_pane.desired_height(size.width).max(1);
-    let total_desired_height = status_height
+    let desired_bottom_height = app.bottom_pane.desired_height(input_width).max(1);
+    let exec_pref_height = app.exec_cell.as_ref().map_or(0, ExecCell::desired_height);
+    let plan_pref_height = app.plan_cell.as_ref().map_or(0, PlanCell::desired_height);
+    let mut total_desired_height = status_height
         .saturating_add(radar_pref_height)
+        .saturating_add(plan_pref_height)
+        .saturating_add(exec_pref_height)
         .saturating_add(desired_bottom_height);
+    if app.deck_pane.is_some() {
+        total_desired_height = total_desired_height.max(DECK_PANE_HEIGHT);
+    }
     let input_height = total_desired_height.min(size.height.max(1));
+    // 実行中コマンドの出力は入力欄を押し出さない範囲で表示する
+    let exec_height = exec_pref_height.min(
+        input_height
+            .saturating_sub(status_height)
+            .saturating_sub(desired_bottom_height),
+    );
+    // 計画はコマンド出力の残りに収める
+    let plan_height = plan_pref_height.min(
+        input_height
+            .saturating_sub(status_height)
+            .saturating_sub(desired_bottom_height)
+            .saturating_sub(exec_height),
+    );
+    let layout_height = input_height - exec_height - plan_height;
 
-    let mut radar_height =
-        radar_pref_height.min(input_height.saturating_sub(status_height).saturating_sub(1));
-    let mut bottom_height = input_height
+    let mut radar_height = radar_pref_height.min(
+        layout_height
+            .saturating_sub(status_height)
+            .saturating_sub(1),
+    );
+    let mut bottom_height = layout_height
         .saturating_sub(status_height)
         .saturating_sub(radar_height)
         .max(1);
@@ -593,41 +1818,48 @@ where
     let total_allocated = status_height
         .saturating_add(radar_height)
         .saturating_add(bottom_height);
-    if total_allocated > input_height {
-        bottom_height = input_height
+    if total_allocated > layout_height {
+        bottom_height = layout_height
             .saturating_sub(status_height)
             .saturating_sub(radar_height);
-        if bottom_height == 0 && input_height > status_height {
+        if bottom_height == 0 && layout_height > status_height {
             bottom_height = 1;
             let overflow = status_height
                 .saturating_add(radar_height)
                 .saturating_add(bottom_height)
-                .saturating_sub(input_height);
+                .saturating_sub(layout_height);
             if overflow > 0 {
                 radar_height = radar_height.saturating_sub(overflow);
             }
         }
     }
 
-    let input_area = Rect {
-        x: 0,
-        y: size.height.saturating_sub(input_height),
-        width: size.width,
-        height: input_height,
-    };
-
-    // Update viewport area to match current terminal size
-    terminal.set_viewport_area(input_area);
-
-    terminal.draw(|f| {
-        draw_input_ui(f, app, input_area, bottom_height, radar_height);
-    })?;
-
-    Ok(())
+    InputLayout {
+        input_width,
+        input_height,
+        bottom_height,
+        radar_height,
+        exec_height,
+        plan_height,
+    }
 }
 
-fn draw_input_ui(f: &mut Frame, app: &mut App, area: Rect, bottom_height: u16, radar_height: u16) {
+fn draw_input_ui(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    bottom_height: u16,
+    radar_height: u16,
+    exec_height: u16,
+    plan_height: u16,
+) {
     let mut constraints = Vec::new();
+    if plan_height > 0 {
+        constraints.push(Constraint::Length(plan_height));
+    }
+    if exec_height > 0 {
+        constraints.push(Constraint::Length(exec_height));
+    }
     if radar_height > 0 {
         constraints.push(Constraint::Length(radar_height));
     }
@@ -640,6 +1872,18 @@ fn draw_input_ui(f: &mut Frame, app: &mut App, area: Rect, bottom_height: u16, r
         .split(area);
 
     let mut index = 0;
+    if plan_height > 0 {
+        if let Some(cell) = &app.plan_cell {
+            cell.render(chunks[index], f.buffer_mut());
+        }
+        index += 1;
+    }
+    if exec_height > 0 {
+        if let Some(cell) = &app.exec_cell {
+            cell.render(chunks[index], f.buffer_mut());
+        }
+        index += 1;
+    }
     if radar_height > 0 {
         let radar = RadarAnimation::new(app.radar_frame);
         f.render_widget(radar, chunks[index]);
@@ -647,22 +1891,24 @@ fn draw_input_ui(f: &mut Frame, app: &mut App, area: Rect, bottom_height: u16, r
     }
 
     // Status bar
-    let status = match app.status {
-        RunStatus::Idle => "Idle",
-        RunStatus::Running => "Running…",
-        RunStatus::Error => "Error",
+    let status = t(match app.status {
+        RunStatus::Idle => Msg::StatusIdle,
+        RunStatus::Running => Msg::StatusRunning,
+        RunStatus::Error => Msg::StatusError,
+    });
+    let mode = match (app.bottom_pane.vim_mode(), app.mode) {
+        (Some(vim_mode), _) => vim_mode.label(),
+        (None, Mode::Normal) => "NORMAL",
+        (None, Mode::Insert) => "INSERT",
+        (None, Mode::Help) => "HELP",
     };
-    let mode = match app.mode {
-        Mode::Normal => "NORMAL",
-        Mode::Insert => "INSERT",
-        Mode::Help => "HELP",
-    };
-    let status_bar = StatusBar::new(mode, status, "i:insert  q:quit");
+    let status_bar = StatusBar::new(mode, status, &app.status_info);
     f.render_widget(status_bar, chunks[index]);
     index += 1;
 
     // Bottom pane (input area) using render_ref
     let bottom_rect = chunks[index];
+    app.bottom_pane_area = bottom_rect;
     app.bottom_pane.render_ref(bottom_rect, f.buffer_mut());
 
     if let Some((x, y)) = app.bottom_pane.cursor_pos(bottom_rect) {
@@ -707,9 +1953,23 @@ fn _ui_fullscreen(f: &mut Frame, app: &mut App) {
         ])
         .split(chunks[1]);
 
-    // Chat history (simplified, no custom scrollbar)
-    let chat_widget = ChatWidget::new(&app.messages);
-    f.render_widget(chat_widget, body_layout[0]);
+    // Chat history (simplified, no custom scrollbar), scrolled to the end
+    let chat_area = body_layout[0];
+    // Only the cells that reach into the area are rendered
+    let mut first = app.history.len();
+    let mut total: u16 = 0;
+    while first > 0 && total < chat_area.height {
+        first -= 1;
+        total = total.saturating_add(app.history[first].desired_height(chat_area.width));
+    }
+    let lines: Vec<Line> = app.history[first..]
+        .iter()
+        .flat_map(HistoryCell::display_lines)
+        .collect();
+    let chat = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((total.saturating_sub(chat_area.height), 0));
+    f.render_widget(chat, chat_area);
 
     // Bottom pane (integrated input)
     app.bottom_pane.render_ref(body_layout[1], f.buffer_mut());
@@ -720,17 +1980,18 @@ fn _ui_fullscreen(f: &mut Frame, app: &mut App) {
     }
 
     // Status bar
-    let status = match app.status {
-        RunStatus::Idle => "Idle",
-        RunStatus::Running => "Running…",
-        RunStatus::Error => "Error",
-    };
-    let mode = match app.mode {
-        Mode::Normal => "NORMAL",
-        Mode::Insert => "INSERT",
-        Mode::Help => "HELP",
+    let status = t(match app.status {
+        RunStatus::Idle => Msg::StatusIdle,
+        RunStatus::Running => Msg::StatusRunning,
+        RunStatus::Error => Msg::StatusError,
+    });
+    let mode = match (app.bottom_pane.vim_mode(), app.mode) {
+        (Some(vim_mode), _) => vim_mode.label(),
+        (None, Mode::Normal) => "NORMAL",
+        (None, Mode::Insert) => "INSERT",
+        (None, Mode::Help) => "HELP",
     };
-    let status_bar = StatusBar::new(mode, status, "i:insert  q:quit");
+    let status_bar = StatusBar::new(mode, status, &app.status_info);
     f.render_widget(status_bar, chunks[2]);
 
     // Modal overlay
@@ -742,13 +2003,22 @@ fn _ui_fullscreen(f: &mut Frame, app: &mut App) {
     }
 
     // Popups (render only if there is enough space to avoid stray borders at the bottom)
-    if let Some(_kind) = app.active_popup {
+    if let Some(kind) = app.active_popup {
         let screen = f.area();
         let area = centered_rect(70, 70, screen);
         // Require a minimum height and full containment within the screen
         let fits_vertically = area.height >= 6 && area.y + area.height <= screen.y + screen.height;
         let fits_horizontally = area.width >= 10 && area.x + area.width <= screen.x + screen.width;
-        if fits_vertically && fits_horizontally {
+        if fits_vertically && fits_horizontally && kind == PopupKind::FileSearch {
+            ListSelection::new(
+                &app.popup_title,
+                &app.popup_filter,
+                &[],
+                0,
+                "Type to search • Esc: close • Enter: open • ↑/↓: move",
+            )
+            .render_with(f, area, &app.popup_files);
+        } else if fits_vertically && fits_horizontally {
             // Build filtered view
             let items: Vec<String> = app
                 .popup_filtered_indices
@@ -794,27 +2064,50 @@ where
     B: ratatui::backend::Backend,
 {
     match ev {
-        CoreEvent::SessionConfigured { .. } => {}
+        CoreEvent::SessionConfigured {
+            model,
+            provider,
+            approval_policy,
+            sandbox_policy,
+            instruction_files,
+            read_only,
+        } => {
+            append_log(&format!("[session] model {model} ({provider})"));
+            app.status_info
+                .configure(model, provider, approval_policy, sandbox_policy);
+            app.status_info.set_read_only(read_only);
+            // Shown under the session header, and again only when they change
+            if instruction_files != app.instruction_files {
+                if !instruction_files.is_empty() {
+                    let cwd = std::env::current_dir().unwrap_or_default();
+                    let lines = instruction_files
+                        .iter()
+                        .map(|path| {
+                            path.strip_prefix(&cwd)
+                                .unwrap_or(path)
+                                .display()
+                                .to_string()
+                        })
+                        .collect();
+                    insert_notice(terminal, "Instructions", lines, Color::Cyan);
+                }
+                app.instruction_files = instruction_files;
+            }
+        }
         CoreEvent::TaskStarted => {
             app.status = RunStatus::Running;
+            app.turn_reply.clear();
+            app.status_info.task_started();
             append_log("[task] started");
         }
         CoreEvent::AgentMessageDelta { delta } => {
             // デルタをストリーミング状態に反映し、完成行のみ履歴へ積む
+            app.turn_reply.push_str(&delta);
             let lines = app.answer_stream.push_delta(&delta);
             if !lines.is_empty() {
                 insert_history_lines(terminal, lines);
             }
-            // 互換目的でメモリ上のメッセージも更新
-            if let Some(last) = app.messages.last_mut() {
-                if last.starts_with("Assistant:") {
-                    last.push_str(&delta);
-                } else {
-                    app.messages.push(format!("Assistant: {}", delta));
-                }
-            } else {
-                app.messages.push(format!("Assistant: {}", delta));
-            }
+            app.push_agent_text(&delta);
             append_log(&format!("AssistantΔ: {}", delta));
         }
         CoreEvent::AgentMessage { message } => {
@@ -829,83 +2122,364 @@ where
             if !pending.is_empty() {
                 insert_history_lines(terminal, pending);
             }
-            app.messages.push(format!("Assistant: {}", message));
+            if !app.turn_reply.is_empty() && !app.turn_reply.ends_with('\n') {
+                app.turn_reply.push('\n');
+            }
+            app.turn_reply.push_str(&message);
+            if matches!(app.history.last(), Some(HistoryCell::AgentMessage { text }) if !text.ends_with('\n'))
+            {
+                app.push_agent_text("\n");
+            }
+            app.push_agent_text(&message);
             append_log(&format!("Assistant: {}", message));
         }
-        CoreEvent::ExecCommandBegin { command, .. } => {
-            app.messages.push(format!("[exec] $ {}", command.join(" ")));
+        CoreEvent::ExecCommandBegin {
+            call_id, command, ..
+        } => {
             append_log(&format!("[exec] $ {}", command.join(" ")));
+            app.exec_cell = Some(ExecCell::new(call_id, command));
         }
-        CoreEvent::ExecCommandEnd { exit_code, .. } => {
-            app.messages.push(format!("[exec] exit {}", exit_code));
+        CoreEvent::ExecCommandOutputDelta {
+            call_id,
+            stream,
+            chunk,
+        } => {
+            if let Some(cell) = app.exec_cell.as_mut() {
+                if cell.call_id() == call_id {
+                    cell.push_output(stream, &chunk);
+                }
+            }
+        }
+        CoreEvent::ExecCommandEnd { call_id, exit_code } => {
             append_log(&format!("[exec] exit {}", exit_code));
+            if let Some(mut cell) = app.exec_cell.take_if(|c| c.call_id() == call_id) {
+                cell.finish(exit_code);
+                app.push_history(HistoryCell::Exec(cell), terminal);
+            }
         }
         CoreEvent::ApplyPatchApprovalRequest {
             id,
             changes,
             reason,
+            timeout_secs,
+            dirty_files,
         } => {
-            // Convert map of path->desc into a vector of display strings
-            let mut items: Vec<String> = changes
-                .into_iter()
-                .map(|(p, v)| format!("{}: {}", p.display(), v))
-                .collect();
-            items.sort();
+            // The deck pane follows the deck the agent is editing
+            if let Some(pane) = app.deck_pane.as_mut() {
+                let deck = changes.keys().find(|p| {
+                    p.extension().is_some_and(|e| e == "md") && p.as_path() != pane.path()
+                });
+                if let Some(deck) = deck {
+                    *pane = DeckPane::open(deck.clone());
+                }
+            }
+            app.notifier
+                .approval_requested(&format!("edit {} file(s)", changes.len()));
+            let cwd = std::env::current_dir().unwrap_or_default();
             let req = ApprovalRequest::Patch {
                 id,
-                changes: items,
+                changes: changes.into_iter().collect(),
                 reason,
+                deadline: approval_deadline(timeout_secs),
+                dirty_files: dirty_files
+                    .into_iter()
+                    .map(|path| path.strip_prefix(&cwd).map(Path::to_path_buf).unwrap_or(path))
+                    .collect(),
             };
             app.bottom_pane
                 .show_approval_modal(req, app.app_event_tx.clone());
             append_log("[approve] apply_patch requested");
         }
         CoreEvent::PatchApplyBegin { .. } => {
-            app.messages.push("[patch] applying...".into());
             append_log("[patch] applying...");
         }
-        CoreEvent::PatchApplyEnd { success, .. } => {
-            app.messages
-                .push(format!("[patch] {}", if success { "ok" } else { "failed" }));
+        CoreEvent::PatchApplyEnd {
+            success,
+            files,
+            total,
+        } => {
+            if !success {
+                app.history.push(HistoryCell::Error {
+                    message: "applying the patch failed".into(),
+                });
+            }
             append_log(&format!(
-                "[patch] {}",
-                if success { "ok" } else { "failed" }
+                "[patch] {} ({} files, +{} -{})",
+                if success { "ok" } else { "failed" },
+                files.len(),
+                total.added,
+                total.removed
             ));
+            if !files.is_empty() {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                let files = files
+                    .into_iter()
+                    .map(|file| {
+                        let path = file.path.strip_prefix(&cwd).unwrap_or(&file.path);
+                        (path.display().to_string(), file.stat)
+                    })
+                    .collect();
+                app.push_history(HistoryCell::PatchStat { files, total }, terminal);
+            }
         }
+        CoreEvent::FilesTouched { paths } => app.on_files_touched(paths),
         CoreEvent::TurnDiff { unified_diff } => {
-            app.messages.push(format!("[diff]\n{}", unified_diff));
+            // Kept in the transcript model; the scrollback shows the commands
+            app.history.push(HistoryCell::Diff { diff: unified_diff });
             append_log("[diff] updated");
         }
+        CoreEvent::GitStatus(status) => app.status_info.set_git_status(status),
+        CoreEvent::PlanUpdate(plan) => {
+            append_log(&format!(
+                "[plan] {}/{} steps done",
+                plan.completed_count(),
+                plan.plan.len()
+            ));
+            app.plan_cell = Some(PlanCell::new(plan));
+        }
+        CoreEvent::PlanStepBegin { index, step } => {
+            append_log(&format!("[plan] step {} started: {step}", index + 1));
+        }
+        CoreEvent::PlanStepEnd {
+            index,
+            step,
+            status,
+        } => {
+            let outcome = if status == StepStatus::Failed {
+                "failed"
+            } else {
+                "done"
+            };
+            append_log(&format!("[plan] step {} {outcome}: {step}", index + 1));
+        }
+        CoreEvent::PlanPaused => {
+            app.status_info.set_plan_paused(true);
+            insert_notice(
+                terminal,
+                "Paused",
+                vec!["The agent stops before its next tool call; /resume lets it go on".into()],
+                Color::Magenta,
+            );
+            append_log("[plan] paused");
+        }
+        CoreEvent::PlanResumed => {
+            app.status_info.set_plan_paused(false);
+            append_log("[plan] resumed");
+        }
+        CoreEvent::CwdChanged { cwd } => {
+            append_log(&format!("[session] cwd {}", cwd.display()));
+            let in_workspace = app.workspace.as_ref().is_some_and(|ws| same_dir(ws, &cwd));
+            app.workspace_state.cwd = (!in_workspace).then(|| cwd.clone());
+            app.save_state();
+            app.status_info.set_cwd(cwd);
+        }
+        CoreEvent::CwdRejected { path, reason } => {
+            insert_notice(
+                terminal,
+                "Directory",
+                vec![format!("Cannot change to {}: {reason}", path.display())],
+                Color::Red,
+            );
+            append_log(&format!("[session] cd {} rejected", path.display()));
+        }
+        CoreEvent::TokenCount { usage } => {
+            app.status_info.add_usage(usage);
+        }
         CoreEvent::TaskComplete => {
             app.status = RunStatus::Idle;
+            if let Some(elapsed) = app.status_info.task_finished() {
+                app.notifier.task_finished(elapsed);
+            }
+            let reply = std::mem::take(&mut app.turn_reply);
+            let _ = app.sessions.append(&app.session_id, "assistant", &reply);
+            // A finished plan moves from above the composer into the transcript
+            if let Some(cell) = app.plan_cell.take_if(|c| c.is_finished()) {
+                app.push_history(HistoryCell::Plan(cell), terminal);
+            }
             // 念のため残りをフラッシュ
             let tail = app.answer_stream.finalize();
             if !tail.is_empty() {
                 insert_history_lines(terminal, tail);
             }
             append_log("[task] complete");
+            app.send_queued_turn(terminal);
+        }
+        CoreEvent::TurnReverted { restored, failed } => {
+            append_log(&format!(
+                "[turn] reverted {} file(s), {} failed",
+                restored.len(),
+                failed.len()
+            ));
+            if restored.is_empty() && failed.is_empty() {
+                insert_notice(
+                    terminal,
+                    "Undo",
+                    vec!["No turn changed any files".into()],
+                    Color::Red,
+                );
+                return;
+            }
+            if !restored.is_empty() {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                let files = restored
+                    .iter()
+                    .map(|path| {
+                        path.strip_prefix(&cwd)
+                            .unwrap_or(path)
+                            .display()
+                            .to_string()
+                    })
+                    .collect();
+                app.push_history(HistoryCell::TurnReverted { files }, terminal);
+            }
+            if !failed.is_empty() {
+                app.push_history(
+                    HistoryCell::Error {
+                        message: failed.join("\n"),
+                    },
+                    terminal,
+                );
+            }
+        }
+        CoreEvent::TurnAborted => {
+            app.status = RunStatus::Idle;
+            app.status_info.task_finished();
+            app.turn_reply.clear();
+            // Lines already in the scrollback stay; the unfinished rest is grayed out
+            let tail: Vec<Line<'static>> = app
+                .answer_stream
+                .finalize()
+                .into_iter()
+                .map(|line| line.style(Style::default().add_modifier(Modifier::DIM)))
+                .collect();
+            if !tail.is_empty() {
+                insert_history_lines(terminal, tail);
+            }
+            if let Some(mut cell) = app.exec_cell.take() {
+                cell.finish(INTERRUPTED_EXIT_CODE);
+                app.push_history(HistoryCell::Exec(cell), terminal);
+            }
+            let mut notice =
+                vec!["/retry resubmits the prompt, /retry <prompt> sends an edited one".into()];
+            // Queued messages go back to the composer instead of starting a turn
+            if !app.queued_turns.is_empty() {
+                let texts: Vec<String> = app.queued_turns.drain(..).map(|(text, _)| text).collect();
+                app.status_info.set_queued(0);
+                app.bottom_pane.set_composer_text(&texts.join("\n\n"));
+                notice.push(format!(
+                    "{} queued message(s) moved back to the composer",
+                    texts.len()
+                ));
+            }
+            insert_notice(terminal, "Interrupted", notice, Color::Yellow);
+            append_log("[task] interrupted");
         }
         CoreEvent::Error { message } => {
-            app.messages.push(format!("[error] {}", message));
+            app.history.push(HistoryCell::Error {
+                message: message.clone(),
+            });
             app.status = RunStatus::Error;
+            app.status_info.task_finished();
             append_log(&format!("[error] {}", message));
+            // Later turns stay queued in core behind a turn that goes on
+            app.send_queued_turn(terminal);
         }
         CoreEvent::ShutdownComplete => {}
+        CoreEvent::ListCustomPromptsResponse { custom_prompts } => {
+            app.bottom_pane.set_custom_prompts(custom_prompts.clone());
+            app.custom_prompts = custom_prompts;
+        }
         CoreEvent::ExecApprovalRequest {
             id,
             command,
-            cwd: _,
+            cwd,
             reason,
+            parsed_cmd,
+            sandbox,
+            escalated,
+            risk,
+            prefix,
+            timeout_secs,
         } => {
+            app.notifier.approval_requested(&command.join(" "));
             let req = ApprovalRequest::Exec {
                 id,
                 command,
                 reason,
+                cwd,
+                parsed: parsed_cmd,
+                sandbox,
+                escalated,
+                risk,
+                prefix,
+                deadline: approval_deadline(timeout_secs),
             };
             app.bottom_pane
                 .show_approval_modal(req, app.app_event_tx.clone());
             append_log("[approve] exec requested");
         }
+        CoreEvent::ExecPlanApprovalRequest {
+            id,
+            commands,
+            timeout_secs,
+            ..
+        } => {
+            app.notifier
+                .approval_requested(&format!("run {} command(s)", commands.len()));
+            let req = ApprovalRequest::ExecPlan {
+                id,
+                commands,
+                deadline: approval_deadline(timeout_secs),
+            };
+            app.bottom_pane
+                .show_approval_modal(req, app.app_event_tx.clone());
+            append_log("[approve] exec plan requested");
+        }
+        CoreEvent::TurnRetrying {
+            attempt,
+            max_retries,
+            delay_ms,
+            error,
+        } => {
+            // The partial reply stays on screen but leaves the transcript;
+            // the retried reply starts over below the notice
+            let tail = app.answer_stream.finalize();
+            if !tail.is_empty() {
+                insert_history_lines(terminal, tail);
+            }
+            app.turn_reply.clear();
+            if matches!(app.history.last(), Some(HistoryCell::AgentMessage { .. })) {
+                app.history.pop();
+            }
+            let text = event_log::retry_text(attempt, max_retries, delay_ms, &error);
+            insert_notice(terminal, "Retrying", vec![text], Color::Yellow);
+            append_log(&format!("[retry] {attempt}/{max_retries}: {error}"));
+        }
+        CoreEvent::ContentFlagged {
+            path,
+            reasons,
+            blocked,
+        } => {
+            let color = if blocked { Color::Red } else { Color::Yellow };
+            let text = event_log::content_flag_text(&path, &reasons, blocked);
+            insert_notice(terminal, "Content safety", vec![text], color);
+            append_log(&format!("[moderation] {} blocked={blocked}", path.display()));
+        }
+        CoreEvent::GuardrailTripped { limit, message, .. } => {
+            insert_notice(terminal, "Guardrail", vec![message], Color::Red);
+            append_log(&format!("[guardrail] {limit:?} limit reached"));
+        }
+        CoreEvent::ApprovalTimedOut { id, action } => {
+            app.bottom_pane.dismiss_approval(&id);
+            let decision = event_log::timeout_decision(action);
+            insert_notice(
+                terminal,
+                "Approval timed out",
+                vec![event_log::decision_text(&decision)],
+                Color::Yellow,
+            );
+            append_log("[approve] timed out");
+        }
     }
 }
 
@@ -915,47 +2489,69 @@ fn append_log(line: &str) {
         .append(true)
         .open("/tmp/slide.log")
     {
-        let _ = writeln!(f, "{}", line);
+        let _ = writeln!(f, "{}", slide_common::secrets::redact(line));
     }
 }
 
-fn find_markdown_files() -> Vec<String> {
-    let mut result = Vec::new();
-    let roots = ["slides"];
-    for root in roots {
-        if let Ok(meta) = std::fs::metadata(root) {
-            if meta.is_dir() {
-                walk_md(root, &mut result);
-            }
-        }
-    }
-    result.sort();
-    result
+/// Decks listed in slides/slide.toml, or every markdown file under slides/
+/// when the project has no manifest.
+fn project_decks() -> Vec<PathBuf> {
+    Project::open("slides")
+        .map(|project| project.deck_paths())
+        .unwrap_or_default()
 }
 
-fn walk_md(dir: &str, out: &mut Vec<String>) {
-    if let Ok(rd) = std::fs::read_dir(dir) {
-        for entry in rd.flatten() {
-            let path = entry.path();
-            if let Ok(ft) = entry.file_type() {
-                if ft.is_dir() {
-                    if let Some(s) = path.to_str() {
-                        walk_md(s, out);
-                    }
-                } else if ft.is_file() {
-                    if let Some(ext) = path.extension() {
-                        if ext == "md" {
-                            if let Some(s) = path.to_str() {
-                                out.push(s.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// Whether `a` and `b` name the same directory, following symlinks.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    if a == b {
+        return true;
+    }
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
     }
 }
 
+/// Deck path for a manifest name or a file path.
+fn resolve_deck(name: &str) -> Option<PathBuf> {
+    Project::open("slides").ok()?.resolve(name)
+}
+
+/// Snapshots of the named deck, or of the deck changed most recently.
+fn deck_versions(deck: Option<&str>) -> std::result::Result<(PathBuf, Vec<DeckVersion>), String> {
+    let store = VersionStore::new(".");
+    let path = match deck {
+        Some(name) => resolve_deck(name).ok_or_else(|| format!("Unknown deck: {name}"))?,
+        None => project_decks()
+            .into_iter()
+            .filter_map(|p| {
+                let latest = store.list(&p).ok()?.pop()?;
+                Some((latest.created, p))
+            })
+            .max()
+            .map(|(_, p)| p)
+            .ok_or_else(|| "No deck has saved versions yet".to_string())?,
+    };
+    let versions = store.list(&path).map_err(|e| e.to_string())?;
+    Ok((path, versions))
+}
+
+/// Write a titled notice into the scrollback.
+fn insert_notice<B>(terminal: &mut Terminal<B>, title: &str, body: Vec<String>, color: Color)
+where
+    B: ratatui::backend::Backend,
+{
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            title.to_string(),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )),
+    ];
+    lines.extend(body.into_iter().map(Line::from));
+    insert_history_lines(terminal, lines);
+}
+
 fn create_slide_from_template() -> std::io::Result<String> {
     use std::io::Write;
     let dir = std::path::Path::new("slides");
@@ -970,7 +2566,7 @@ fn create_slide_from_template() -> std::io::Result<String> {
     Ok(path.to_string_lossy().to_string())
 }
 
-fn save_chat_as_draft(messages: &[String]) -> std::io::Result<String> {
+fn save_chat_as_draft(history: &[HistoryCell]) -> std::io::Result<String> {
     use std::io::Write;
     let dir = std::path::Path::new("slides");
     if !dir.exists() {
@@ -978,8 +2574,8 @@ fn save_chat_as_draft(messages: &[String]) -> std::io::Result<String> {
     }
     let path = dir.join("draft.md");
     let mut file = std::fs::File::create(&path)?;
-    for m in messages {
-        writeln!(file, "- {}", m)?;
+    for cell in history {
+        writeln!(file, "- {}", cell.plain_text())?;
     }
     Ok(path.to_string_lossy().to_string())
 }
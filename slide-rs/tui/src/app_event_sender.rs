@@ -1,3 +1,4 @@
+use crate::bottom_pane::file_search_popup::FileMatch;
 use slide_core::codex::ReviewDecision;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -11,6 +12,27 @@ pub enum AppEvent {
         id: String,
         decision: ReviewDecision,
     },
+    /// Search files for an `@` mention in the composer
+    StartFileSearch(String),
+    FileSearchResult {
+        query: String,
+        matches: Vec<FileMatch>,
+    },
+    /// Matches for the query typed into the open file popup
+    OpenFileSearchResult {
+        query: String,
+        matches: Vec<FileMatch>,
+    },
+    /// Ask for ghost text continuing the composer text
+    StartSuggestion(String),
+    /// The composer text changed in a way no suggestion applies to
+    CancelSuggestion,
+    SuggestionResult {
+        text: String,
+        suggestion: String,
+    },
+    /// Message drafted for `/commit`, or why there is none
+    CommitDraft(Result<String, String>),
 }
 
 #[derive(Clone, Default)]
@@ -0,0 +1,80 @@
+//! File content attached to a message through `@path` mentions.
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are sent as an excerpt.
+pub(crate) const MAX_ATTACHMENT_BYTES: usize = 16 * 1024;
+
+/// Headings kept in the outline of an excerpt.
+const MAX_OUTLINE_HEADINGS: usize = 40;
+
+/// Message text followed by the content of each attached file.
+pub(crate) fn compose_with_attachments(text: &str, files: &[PathBuf]) -> String {
+    if files.is_empty() {
+        return text.to_string();
+    }
+    let mut out = text.to_string();
+    out.push_str("\n\nAttached files:");
+    for path in files {
+        out.push_str(&format!("\n\n--- {} ---\n", path.display()));
+        out.push_str(&attachment_body(path));
+    }
+    out
+}
+
+fn attachment_body(path: &Path) -> String {
+    match std::fs::read(path) {
+        Err(e) => format!("(could not read file: {e})"),
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Err(_) => "(binary file, content not attached)".to_string(),
+            Ok(content) if content.len() <= MAX_ATTACHMENT_BYTES => content,
+            Ok(content) => excerpt(&content),
+        },
+    }
+}
+
+/// Heading outline plus the beginning of a large file.
+fn excerpt(content: &str) -> String {
+    let outline: Vec<&str> = content
+        .lines()
+        .filter(|l| l.starts_with('#'))
+        .take(MAX_OUTLINE_HEADINGS)
+        .collect();
+    let mut cut = MAX_ATTACHMENT_BYTES / 2;
+    while !content.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let mut out = String::new();
+    if !outline.is_empty() {
+        out.push_str("Outline:\n");
+        for heading in outline {
+            out.push_str(heading);
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out.push_str(&content[..cut]);
+    out.push_str(&format!(
+        "\n[... truncated, {cut} of {} bytes shown]",
+        content.len()
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_files_are_sent_as_outline_and_excerpt() {
+        let body = "text line\n".repeat(MAX_ATTACHMENT_BYTES / 5);
+        let content = format!("# Deck\n## Intro\n{body}## End\n");
+        let out = excerpt(&content);
+        assert!(out.starts_with("Outline:\n# Deck\n## Intro\n## End\n"));
+        assert!(out.len() < content.len());
+        assert!(out.ends_with(&format!(
+            "[... truncated, {} of {} bytes shown]",
+            MAX_ATTACHMENT_BYTES / 2,
+            content.len()
+        )));
+    }
+}
@@ -38,11 +38,21 @@ impl BottomPaneView for ApprovalModalView {
         self.current.handle_key_event(key_event);
         self.maybe_advance();
     }
+    fn on_mouse_scroll(&mut self, delta: isize) -> bool {
+        self.current.on_mouse_scroll(delta)
+    }
     fn on_ctrl_c(&mut self, _pane: &mut BottomPane) -> CancellationEvent {
         self.current.on_ctrl_c();
         self.queue.clear();
         CancellationEvent::Handled
     }
+    fn dismiss_approval(&mut self, id: &str) {
+        if self.current.on_timed_out(id) {
+            self.maybe_advance();
+        } else {
+            self.queue.retain(|req| req.id() != id);
+        }
+    }
     fn is_complete(&self) -> bool {
         self.current.is_complete() && self.queue.is_empty()
     }
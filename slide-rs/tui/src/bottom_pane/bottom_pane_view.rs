@@ -8,6 +8,11 @@ pub(crate) trait BottomPaneView {
     /// アクティブ中にキーイベントを処理（処理後は再描画が行われる想定）
     fn handle_key_event(&mut self, _pane: &mut BottomPane, _key_event: KeyEvent) {}
 
+    /// マウスホイール（正で下方向）。処理した場合は true を返す
+    fn on_mouse_scroll(&mut self, _delta: isize) -> bool {
+        false
+    }
+
     /// ビューが完了した場合は true を返す（ペインから取り除かれる）
     fn is_complete(&self) -> bool {
         false
@@ -18,6 +23,9 @@ pub(crate) trait BottomPaneView {
         CancellationEvent::Ignored
     }
 
+    /// エージェントが承認要求 `id` の回答を待つのをやめた（既定は無視）
+    fn dismiss_approval(&mut self, _id: &str) {}
+
     /// 望ましい高さ（行数）
     fn desired_height(&self, width: u16) -> u16;
 
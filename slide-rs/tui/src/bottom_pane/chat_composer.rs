@@ -1,23 +1,46 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, StatefulWidgetRef, WidgetRef, Wrap},
 };
 use std::cell::RefCell;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use super::{
     chat_composer_history::ChatComposerHistory,
+    command_popup::CommandPopup,
+    file_search_popup::{FileMatch, FileSearchPopup},
+    paste_burst::{CharDecision, PasteBurst},
     textarea::{TextArea, TextAreaState},
+    vim::{Vim, VimMode, VimOutcome},
 };
+use crate::app_event_sender::{AppEvent, AppEventSender};
+use crate::clipboard::Clipboard;
+use crate::slash_command::{split_command, SlashAction, SlashCommandRegistry};
+use slide_core::custom_prompts::{render_prompt, CustomPrompt};
+
+/// Pastes with more lines than this are shown as a placeholder element.
+const LARGE_PASTE_LINE_THRESHOLD: usize = 10;
+/// Pastes with more characters than this are shown as a placeholder element.
+const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
+/// Shorter text gets no ghost-text suggestion.
+const MIN_SUGGESTION_CHARS: usize = 3;
 
 /// 入力結果
 #[derive(Debug, PartialEq, Clone)]
 pub enum InputResult {
     Submitted(String),
+    /// A registered slash command was entered
+    Command(SlashAction),
+    /// A registered slash command had invalid arguments; the text is kept
+    CommandError(String),
     None,
 }
 
@@ -26,6 +49,31 @@ pub struct ChatComposer {
     textarea: TextArea,
     textarea_state: RefCell<TextAreaState>,
     history: ChatComposerHistory,
+    slash_commands: SlashCommandRegistry,
+    /// Templates sent as `/<name> [args]`, offered after the commands
+    custom_prompts: Vec<CustomPrompt>,
+    command_popup: Option<CommandPopup>,
+    /// `@` mention popup and the query last sent to the file search
+    file_popup: Option<FileSearchPopup>,
+    current_file_query: Option<String>,
+    /// Files mentioned in the text being composed
+    attached_files: Vec<PathBuf>,
+    /// Files attached to the last submitted message
+    recent_submission_attachments: Vec<PathBuf>,
+    app_event_tx: AppEventSender,
+    /// Detects pastes arriving as fast key presses (no bracketed paste)
+    paste_burst: PasteBurst,
+    /// Placeholder shown in the text and the content it stands for
+    pending_pastes: Vec<(String, String)>,
+    /// Receives killed text; source of Ctrl-Y in `system` mode
+    clipboard: Clipboard,
+    /// Modal editing state when the Vim keymap is configured
+    vim: Option<Vim>,
+    /// Whether text changes ask for ghost-text suggestions
+    suggestions_enabled: bool,
+    /// Ghost text and the composer text it continues; shown only while the
+    /// text is unchanged and the cursor is at its end
+    suggestion: Option<(String, String)>,
     has_focus: bool,
     placeholder_text: String,
     ctrl_c_quit_hint: bool,
@@ -36,11 +84,29 @@ pub struct ChatComposer {
 }
 
 impl ChatComposer {
-    pub fn new_minimal(has_input_focus: bool, placeholder_text: String) -> Self {
+    pub fn new_minimal(
+        has_input_focus: bool,
+        placeholder_text: String,
+        app_event_tx: AppEventSender,
+    ) -> Self {
         Self {
             textarea: TextArea::new(),
             textarea_state: RefCell::new(TextAreaState::default()),
             history: ChatComposerHistory::new(),
+            slash_commands: SlashCommandRegistry::default(),
+            custom_prompts: Vec::new(),
+            command_popup: None,
+            file_popup: None,
+            current_file_query: None,
+            attached_files: Vec::new(),
+            recent_submission_attachments: Vec::new(),
+            app_event_tx,
+            paste_burst: PasteBurst::default(),
+            pending_pastes: Vec::new(),
+            clipboard: Clipboard::new(slide_common::ClipboardMode::default()),
+            vim: None,
+            suggestions_enabled: false,
+            suggestion: None,
             has_focus: has_input_focus,
             placeholder_text,
             ctrl_c_quit_hint: false,
@@ -55,8 +121,9 @@ impl ChatComposer {
         has_input_focus: bool,
         placeholder_text: String,
         enhanced_keys_supported: bool,
+        app_event_tx: AppEventSender,
     ) -> Self {
-        let mut composer = Self::new_minimal(has_input_focus, placeholder_text);
+        let mut composer = Self::new_minimal(has_input_focus, placeholder_text, app_event_tx);
         composer.use_shift_enter_hint = enhanced_keys_supported;
         composer
     }
@@ -64,7 +131,556 @@ impl ChatComposer {
     pub fn desired_height(&self, width: u16) -> u16 {
         let textarea_height = self.textarea.desired_height(width.saturating_sub(1));
         let hints_height = if self.show_hints { 1 } else { 0 };
-        textarea_height.saturating_add(hints_height)
+        textarea_height
+            .saturating_add(self.popup_height())
+            .saturating_add(hints_height)
+    }
+
+    fn popup_height(&self) -> u16 {
+        if let Some(popup) = &self.file_popup {
+            return popup.calculate_required_height();
+        }
+        self.command_popup
+            .as_ref()
+            .map_or(0, |p| p.calculate_required_height())
+    }
+
+    fn sync_popups(&mut self) {
+        self.sync_command_popup();
+        self.sync_file_popup();
+    }
+
+    /// Show the file popup while the cursor is on an `@token`.
+    fn sync_file_popup(&mut self) {
+        let query = match self.current_mention_token() {
+            Some((_, query)) if self.command_popup.is_none() => query,
+            _ => {
+                self.file_popup = None;
+                self.current_file_query = None;
+                return;
+            }
+        };
+        let popup = self.file_popup.get_or_insert_with(FileSearchPopup::new);
+        if self.current_file_query.as_deref() == Some(query.as_str()) {
+            return;
+        }
+        if query.is_empty() {
+            popup.set_empty_prompt();
+        } else {
+            popup.set_query(&query);
+            self.app_event_tx
+                .send(AppEvent::StartFileSearch(query.clone()));
+        }
+        self.current_file_query = Some(query);
+    }
+
+    /// The whitespace-delimited `@word` under the cursor, unless it is an
+    /// already inserted mention.
+    fn current_mention_token(&self) -> Option<(Range<usize>, String)> {
+        let text = self.textarea.text();
+        let cursor = self.textarea.cursor().min(text.len());
+        let start = text[..cursor]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let end = text[cursor..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |i| cursor + i);
+        let query = text[start..end].strip_prefix('@')?;
+        if self.textarea.has_element_in(start..end) {
+            return None;
+        }
+        Some((start..end, query.to_string()))
+    }
+
+    pub fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
+        if let Some(popup) = self.file_popup.as_mut() {
+            popup.set_matches(&query, matches);
+        }
+    }
+
+    /// Replace the `@token` with a mention element for the selected file.
+    fn accept_file_mention(&mut self) -> bool {
+        let Some(path) = self
+            .file_popup
+            .as_ref()
+            .and_then(|p| p.selected_match())
+            .map(str::to_string)
+        else {
+            return false;
+        };
+        let Some((range, _)) = self.current_mention_token() else {
+            return false;
+        };
+        self.textarea.edit_atomically(|textarea| {
+            textarea.replace_range(range.clone(), "");
+            textarea.set_cursor(range.start);
+            textarea.insert_element(&format!("@{path}"));
+            textarea.insert_str(" ");
+        });
+        let path = PathBuf::from(path);
+        if !self.attached_files.contains(&path) {
+            self.attached_files.push(path);
+        }
+        self.file_popup = None;
+        self.current_file_query = None;
+        true
+    }
+
+    /// Files whose mention was still present in the last submitted message.
+    pub fn take_recent_submission_attachments(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.recent_submission_attachments)
+    }
+
+    /// Insert pasted text as a single undoable edit. Large pastes are shown
+    /// as a `[pasted N lines]` element and expanded on submit.
+    pub fn handle_paste(&mut self, pasted: String) -> bool {
+        let pasted = pasted.replace("\r\n", "\n").replace('\r', "\n");
+        self.paste_burst.clear_after_explicit_paste();
+        if pasted.is_empty() {
+            return false;
+        }
+        let lines = pasted.lines().count();
+        let chars = pasted.chars().count();
+        if lines > LARGE_PASTE_LINE_THRESHOLD || chars > LARGE_PASTE_CHAR_THRESHOLD {
+            let placeholder = self.paste_placeholder(lines, chars);
+            self.textarea.insert_element(&placeholder);
+            self.pending_pastes.push((placeholder, pasted));
+        } else {
+            self.textarea.insert_str(&pasted);
+        }
+        self.sync_popups();
+        self.on_text_changed();
+        true
+    }
+
+    /// Placeholder label, numbered when the same label is already in use.
+    fn paste_placeholder(&self, lines: usize, chars: usize) -> String {
+        let base = if lines > 1 {
+            format!("[pasted {lines} lines]")
+        } else {
+            format!("[pasted {chars} chars]")
+        };
+        let taken = |label: &str| self.pending_pastes.iter().any(|(p, _)| p == label);
+        if !taken(&base) {
+            return base;
+        }
+        let stem = base.trim_end_matches(']');
+        (2..)
+            .map(|n| format!("{stem} #{n}]"))
+            .find(|label| !taken(label))
+            .unwrap_or(base)
+    }
+
+    /// Replace paste placeholders in the text with their content, as one
+    /// undo step. The pastes stay registered so an undo brings them back.
+    fn expand_pending_pastes(&mut self) -> bool {
+        let pastes = &self.pending_pastes;
+        let mut expanded = false;
+        self.textarea.edit_atomically(|textarea| {
+            for (placeholder, content) in pastes {
+                if let Some(start) = textarea.text().find(placeholder.as_str()) {
+                    textarea.replace_range(start..start + placeholder.len(), content);
+                    expanded = true;
+                }
+            }
+        });
+        expanded
+    }
+
+    fn with_pastes_expanded(&self, text: &str) -> String {
+        self.pending_pastes
+            .iter()
+            .fold(text.to_string(), |acc, (placeholder, content)| {
+                acc.replacen(placeholder, content, 1)
+            })
+    }
+
+    /// Whether fast key presses are being held as a possible paste.
+    pub fn is_in_paste_burst(&self) -> bool {
+        self.paste_burst.is_active()
+    }
+
+    /// Insert a burst whose keys stopped arriving. Called on every UI tick.
+    pub fn flush_paste_burst_if_due(&mut self) -> bool {
+        match self.paste_burst.flush_if_due(Instant::now()) {
+            Some(text) => {
+                self.insert_flushed(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert whatever the burst detector holds before handling another key.
+    fn flush_paste_burst(&mut self) {
+        if let Some(text) = self.paste_burst.flush_before_modified_input() {
+            self.insert_flushed(text);
+        }
+        self.paste_burst.clear_window_after_non_char();
+    }
+
+    /// A single held char is typed input; anything longer is a paste.
+    fn insert_flushed(&mut self, text: String) {
+        if text.chars().count() == 1 {
+            self.textarea.insert_str(&text);
+            self.sync_popups();
+        } else {
+            self.handle_paste(text);
+        }
+    }
+
+    /// Route plain chars through the paste-burst detector so that terminals
+    /// without bracketed paste do not submit on every pasted newline.
+    fn handle_paste_burst_key(&mut self, key_event: KeyEvent) -> Option<(InputResult, bool)> {
+        let now = Instant::now();
+        if let Some(text) = self.paste_burst.flush_if_due(now) {
+            self.insert_flushed(text);
+        }
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                ..
+            } if !ch.is_control() => {
+                self.handle_plain_char(ch, now);
+                self.sync_popups();
+                Some((InputResult::None, true))
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } if self
+                .paste_burst
+                .newline_should_insert_instead_of_submit(now) =>
+            {
+                if !self.paste_burst.append_newline_if_active(now) {
+                    self.textarea.insert_str("\n");
+                    self.paste_burst.extend_window(now);
+                }
+                Some((InputResult::None, true))
+            }
+            _ => {
+                self.flush_paste_burst();
+                None
+            }
+        }
+    }
+
+    fn handle_plain_char(&mut self, ch: char, now: Instant) {
+        match self.paste_burst.on_plain_char(ch, now) {
+            CharDecision::BufferAppend | CharDecision::BeginBufferFromPending => {
+                self.paste_burst.append_char_to_buffer(ch, now);
+            }
+            CharDecision::RetainFirstChar => {}
+            CharDecision::BeginBuffer { retro_chars } => {
+                let cursor = self.textarea.cursor();
+                let before = &self.textarea.text()[..cursor];
+                match self
+                    .paste_burst
+                    .decide_begin_buffer(now, before, retro_chars as usize)
+                {
+                    Some(grab) => {
+                        // The grabbed chars were typed into the text already.
+                        self.textarea.replace_range(grab.start_byte..cursor, "");
+                        self.paste_burst.append_char_to_buffer(ch, now);
+                    }
+                    None => self.textarea.insert_str(&ch.to_string()),
+                }
+            }
+        }
+    }
+
+    pub fn set_clipboard_mode(&mut self, mode: slide_common::ClipboardMode) {
+        self.clipboard = Clipboard::new(mode);
+    }
+
+    /// Copy `text` on the user's request; see [`Clipboard::copy_requested`].
+    pub fn copy_to_clipboard(&mut self, text: &str) {
+        self.clipboard.copy_requested(text);
+    }
+
+    pub fn set_keymap(&mut self, keymap: slide_common::Keymap) {
+        self.vim = (keymap == slide_common::Keymap::Vim).then(Vim::new);
+    }
+
+    pub fn set_suggestions_enabled(&mut self, enabled: bool) {
+        self.suggestions_enabled = enabled;
+        self.suggestion = None;
+    }
+
+    /// A suggestion arrived for `text`; shown if the text is still the same.
+    pub fn on_suggestion(&mut self, text: String, suggestion: String) {
+        if self.wants_suggestion() && self.textarea.text() == text {
+            self.suggestion = Some((text, suggestion));
+        }
+    }
+
+    /// Ghost text to draw after the cursor.
+    fn visible_suggestion(&self) -> Option<&str> {
+        let (text, suggestion) = self.suggestion.as_ref()?;
+        (self.textarea.text() == text && self.textarea.cursor() >= text.len())
+            .then_some(suggestion.as_str())
+    }
+
+    /// Whether the text is one a suggestion could continue: typed prose
+    /// with the cursor at the end, no popup, no slash command.
+    fn wants_suggestion(&self) -> bool {
+        let text = self.textarea.text();
+        self.suggestions_enabled
+            && self.command_popup.is_none()
+            && self.file_popup.is_none()
+            && !text.starts_with('/')
+            && self.textarea.cursor() >= text.len()
+            && text.trim().chars().count() >= MIN_SUGGESTION_CHARS
+    }
+
+    /// Keep the ghost text when the user typed its beginning; otherwise
+    /// drop it and ask for a new one.
+    fn on_text_changed(&mut self) {
+        let text = self.textarea.text();
+        if let Some((before, suggestion)) = self.suggestion.take() {
+            let rest = text
+                .strip_prefix(before.as_str())
+                .and_then(|typed| suggestion.strip_prefix(typed))
+                .filter(|rest| !rest.is_empty());
+            if let Some(rest) = rest {
+                if self.textarea.cursor() >= text.len() {
+                    self.suggestion = Some((text.to_string(), rest.to_string()));
+                    return;
+                }
+            }
+        }
+        let event = if self.wants_suggestion() {
+            AppEvent::StartSuggestion(text.to_string())
+        } else {
+            AppEvent::CancelSuggestion
+        };
+        if self.suggestions_enabled {
+            self.app_event_tx.send(event);
+        }
+    }
+
+    /// Insert the ghost text (Tab).
+    fn accept_suggestion(&mut self) -> bool {
+        let Some(suggestion) = self.visible_suggestion().map(str::to_string) else {
+            return false;
+        };
+        self.suggestion = None;
+        self.textarea.insert_str(&suggestion);
+        true
+    }
+
+    pub fn vim_mode(&self) -> Option<VimMode> {
+        self.vim.as_ref().map(Vim::mode)
+    }
+
+    /// Normal and visual mode keys; `None` lets the default keymap run.
+    fn handle_vim_key(&mut self, key_event: KeyEvent) -> Option<(InputResult, bool)> {
+        let vim = self.vim.as_mut()?;
+        let result = match vim.handle_key(key_event, &mut self.textarea) {
+            VimOutcome::PassThrough => return None,
+            VimOutcome::Handled => (InputResult::None, true),
+            VimOutcome::Submit => self.submit(),
+            VimOutcome::Navigate { up } => {
+                let code = if up { KeyCode::Up } else { KeyCode::Down };
+                self.navigate_history_or_move(up, KeyEvent::new(code, KeyModifiers::NONE));
+                (InputResult::None, true)
+            }
+        };
+        self.sync_popups();
+        Some(result)
+    }
+
+    /// Replace the slash commands offered by the popup and parsed on submit.
+    pub fn set_slash_commands(&mut self, registry: SlashCommandRegistry) {
+        self.slash_commands = registry;
+        self.command_popup = None;
+        self.sync_command_popup();
+    }
+
+    /// Replace the prompt templates offered by the popup and expanded on submit.
+    pub fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+        if let Some(popup) = self.command_popup.as_mut() {
+            popup.set_prompts(prompts.clone());
+        }
+        self.custom_prompts = prompts;
+        self.sync_command_popup();
+    }
+
+    /// Show the command popup while the (single-line) text starts with `/`.
+    fn sync_command_popup(&mut self) {
+        let text = self.textarea.text();
+        // Text after an unknown command (e.g. `/slide ...`) goes to the agent.
+        let unknown_with_args = split_command(text).is_some_and(|(name, _)| {
+            text.len() > name.len() + 1 && self.slash_commands.get(name).is_none()
+        });
+        if !text.starts_with('/') || text.contains('\n') || unknown_with_args {
+            self.command_popup = None;
+            return;
+        }
+        let popup = self.command_popup.get_or_insert_with(|| {
+            CommandPopup::new(self.slash_commands.clone(), self.custom_prompts.clone())
+        });
+        popup.on_composer_text_change(text.to_string());
+    }
+
+    /// Accept the selected popup item into the composer text.
+    fn accept_completion(&mut self) -> bool {
+        let Some(text) = self.command_popup.as_ref().and_then(|p| p.completion()) else {
+            return false;
+        };
+        if text == self.textarea.text() {
+            return false;
+        }
+        self.textarea.set_text(&text);
+        self.textarea.set_cursor(text.len());
+        self.sync_command_popup();
+        true
+    }
+
+    fn handle_key_event_with_popup(&mut self, key_event: KeyEvent) -> Option<(InputResult, bool)> {
+        if let Some(popup) = self.file_popup.as_mut() {
+            return match key_event.code {
+                KeyCode::Up => {
+                    popup.move_up();
+                    Some((InputResult::None, true))
+                }
+                KeyCode::Down => {
+                    popup.move_down();
+                    Some((InputResult::None, true))
+                }
+                KeyCode::Tab => {
+                    self.accept_file_mention();
+                    Some((InputResult::None, true))
+                }
+                KeyCode::Enter
+                    if key_event.modifiers == KeyModifiers::NONE && self.accept_file_mention() =>
+                {
+                    Some((InputResult::None, true))
+                }
+                _ => None,
+            };
+        }
+        let popup = self.command_popup.as_mut()?;
+        match key_event.code {
+            KeyCode::Up => {
+                popup.move_up();
+                Some((InputResult::None, true))
+            }
+            KeyCode::Down => {
+                popup.move_down();
+                Some((InputResult::None, true))
+            }
+            KeyCode::Tab => {
+                self.accept_completion();
+                Some((InputResult::None, true))
+            }
+            KeyCode::Enter if key_event.modifiers == KeyModifiers::NONE => {
+                // Complete a partial command name or argument, then submit
+                // unless the completed command still needs an argument.
+                if popup.is_completing_argument() {
+                    let partial_argument = split_command(self.textarea.text())
+                        .is_some_and(|(_, args)| !args.is_empty());
+                    if partial_argument {
+                        self.accept_completion();
+                    }
+                } else if self.accept_completion() && self.textarea.text().ends_with(' ') {
+                    return Some((InputResult::None, true));
+                }
+                Some(self.submit())
+            }
+            _ => None,
+        }
+    }
+
+    /// Mouse over the composer drawn in `area`: the wheel moves through the
+    /// open popup and a click accepts the suggestion under the pointer.
+    /// Returns whether the event was used.
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) -> bool {
+        let position = Position::new(mouse.column, mouse.row);
+        if !area.contains(position) {
+            return false;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                if let Some(popup) = self.file_popup.as_mut() {
+                    popup.scroll(down);
+                } else if let Some(popup) = self.command_popup.as_mut() {
+                    popup.scroll(down);
+                } else {
+                    return false;
+                }
+                true
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let [_, popup_rect, _] = Layout::vertical([
+                    Constraint::Min(1),
+                    Constraint::Length(self.popup_height()),
+                    Constraint::Length(if self.show_hints { 1 } else { 0 }),
+                ])
+                .areas(area);
+                if !popup_rect.contains(position) {
+                    return false;
+                }
+                let row = (mouse.row - popup_rect.y) as usize;
+                if let Some(popup) = self.file_popup.as_mut() {
+                    if popup.select_visible_row(row) {
+                        self.accept_file_mention();
+                    }
+                } else if let Some(popup) = self.command_popup.as_mut() {
+                    if popup.select_visible_row(row) {
+                        self.accept_completion();
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn submit(&mut self) -> (InputResult, bool) {
+        let text = self
+            .with_pastes_expanded(self.textarea.text())
+            .trim()
+            .to_string();
+        if text.is_empty() {
+            return (InputResult::None, false);
+        }
+        let result = match self.slash_commands.parse(&text) {
+            Some(Err(message)) => return (InputResult::CommandError(message), true),
+            Some(Ok(action)) => InputResult::Command(action),
+            None => match self.expand_custom_prompt(&text) {
+                Some(Err(message)) => return (InputResult::CommandError(message), true),
+                Some(Ok(prompt)) => InputResult::Submitted(prompt),
+                None => InputResult::Submitted(text.clone()),
+            },
+        };
+        self.recent_submission_attachments = std::mem::take(&mut self.attached_files)
+            .into_iter()
+            .filter(|p| text.contains(&format!("@{}", p.display())))
+            .collect();
+        self.history.record_local_submission(&text);
+        self.textarea.set_text("");
+        if let Some(vim) = self.vim.as_mut() {
+            vim.reset(&mut self.textarea);
+        }
+        self.pending_pastes.clear();
+        self.command_popup = None;
+        self.file_popup = None;
+        self.current_file_query = None;
+        self.suggestion = None;
+        (result, true)
+    }
+
+    /// The rendered template when `text` is `/<prompt> [args]`.
+    fn expand_custom_prompt(&self, text: &str) -> Option<Result<String, String>> {
+        let (name, args) = split_command(text)?;
+        let prompt = self.custom_prompts.iter().find(|p| p.name == name)?;
+        Some(render_prompt(prompt, args).map_err(|e| e.to_string()))
     }
 
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
@@ -75,21 +691,44 @@ impl ChatComposer {
         self.last_activity = Instant::now();
         self.clear_hints();
 
+        let before = self.textarea.text().to_string();
+        let result = self.dispatch_key_event(key_event);
+        if self.textarea.text() != before {
+            self.on_text_changed();
+        }
+        result
+    }
+
+    fn dispatch_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if let Some(result) = self.handle_vim_key(key_event) {
+            return result;
+        }
+        if let Some(result) = self.handle_paste_burst_key(key_event) {
+            return result;
+        }
+        if key_event.code == KeyCode::Tab
+            && key_event.modifiers == KeyModifiers::NONE
+            && self.accept_suggestion()
+        {
+            self.sync_popups();
+            return (InputResult::None, true);
+        }
+        if let Some(result) = self.handle_key_event_with_popup(key_event) {
+            return result;
+        }
+
+        let result = self.handle_key_event_without_popup(key_event);
+        self.sync_popups();
+        result
+    }
+
+    fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
         match key_event {
             KeyEvent {
                 code: KeyCode::Enter,
                 modifiers: KeyModifiers::NONE,
                 ..
-            } => {
-                let text = self.textarea.text().trim().to_string();
-                if !text.is_empty() {
-                    self.history.record_local_submission(&text);
-                    self.textarea.set_text("");
-                    (InputResult::Submitted(text), true)
-                } else {
-                    (InputResult::None, false)
-                }
-            }
+            } => self.submit(),
             KeyEvent {
                 code: KeyCode::Enter,
                 modifiers: KeyModifiers::SHIFT,
@@ -106,41 +745,89 @@ impl ChatComposer {
                 self.textarea.insert_str("\n");
                 (InputResult::None, true)
             }
+            KeyEvent {
+                code: KeyCode::Char('o'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => (InputResult::None, self.expand_pending_pastes()),
             KeyEvent {
                 code: KeyCode::Up | KeyCode::Down,
                 ..
             } => {
-                let text = self.textarea.text();
-                let cursor = self.textarea.cursor();
-                if self.history.should_handle_navigation(text, cursor) {
-                    let next = if matches!(key_event.code, KeyCode::Up) {
-                        self.history.navigate_up()
-                    } else {
-                        self.history.navigate_down()
-                    };
-                    if let Some(t) = next {
-                        self.textarea.set_text(&t);
-                        self.textarea.set_cursor(t.len());
-                        return (InputResult::None, true);
-                    }
-                }
-                self.textarea.input(key_event);
+                self.navigate_history_or_move(key_event.code == KeyCode::Up, key_event);
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Char(c @ ('p' | 'n')),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                let (up, code) = if c == 'p' {
+                    (true, KeyCode::Up)
+                } else {
+                    (false, KeyCode::Down)
+                };
+                self.navigate_history_or_move(up, KeyEvent::new(code, KeyModifiers::NONE));
                 (InputResult::None, true)
             }
             other => {
+                let is_yank = matches!(
+                    other,
+                    KeyEvent {
+                        code: KeyCode::Char('y'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }
+                );
+                if is_yank {
+                    if let Some(text) = self.clipboard.paste() {
+                        self.textarea.push_kill(text);
+                    }
+                }
                 self.textarea.input(other);
+                if let Some(killed) = self.textarea.take_new_kill() {
+                    self.clipboard.copy(&killed);
+                }
                 (InputResult::None, true)
             }
         }
     }
 
+    /// Up on the first line recalls an older message starting with the
+    /// typed text; Down on the last line walks back to newer ones. Anywhere
+    /// else the key moves the cursor.
+    fn navigate_history_or_move(&mut self, up: bool, key_event: KeyEvent) {
+        let text = self.textarea.text();
+        let cursor = self.textarea.cursor().min(text.len());
+        let at_edge = if up {
+            !text[..cursor].contains('\n')
+        } else {
+            !text[cursor..].contains('\n') && self.history.is_browsing()
+        };
+        if at_edge {
+            let text = text.to_string();
+            let next = if up {
+                self.history.navigate_up(&text)
+            } else {
+                self.history.navigate_down(&text)
+            };
+            if let Some(t) = next {
+                self.textarea.set_text(&t);
+                self.textarea.set_cursor(t.len());
+                return;
+            }
+        }
+        self.textarea.input(key_event);
+    }
+
     pub fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
         if !self.has_focus {
             return None;
         }
 
-        let [textarea_rect, _] = Layout::vertical([
+        let [textarea_rect, _, _] = Layout::vertical([
             Constraint::Min(1),
+            Constraint::Length(self.popup_height()),
             Constraint::Length(if self.show_hints { 1 } else { 0 }),
         ])
         .areas(area);
@@ -178,10 +865,17 @@ impl ChatComposer {
 
     pub fn set_text(&mut self, text: &str) {
         self.textarea.set_text(text);
+        self.sync_popups();
     }
 
     pub fn clear(&mut self) {
         self.textarea.set_text("");
+        self.command_popup = None;
+        self.file_popup = None;
+        self.current_file_query = None;
+        self.attached_files.clear();
+        self.pending_pastes.clear();
+        self.suggestion = None;
     }
 
     pub fn show_ctrl_c_quit_hint(&mut self) {
@@ -225,6 +919,12 @@ impl ChatComposer {
                 hints.push(("Ctrl+J/M", "newline"));
             }
             hints.push(("↑/↓", "history"));
+            if self.visible_suggestion().is_some() {
+                hints.push(("Tab", "accept suggestion"));
+            }
+            if !self.pending_pastes.is_empty() {
+                hints.push(("Ctrl+O", "expand paste"));
+            }
         }
 
         if hints.is_empty() {
@@ -254,8 +954,9 @@ impl ChatComposer {
 
 impl WidgetRef for &ChatComposer {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let [textarea_rect, hint_rect] = Layout::vertical([
+        let [textarea_rect, popup_rect, hint_rect] = Layout::vertical([
             Constraint::Min(1),
+            Constraint::Length(self.popup_height()),
             Constraint::Length(if self.show_hints { 1 } else { 0 }),
         ])
         .areas(area);
@@ -279,7 +980,12 @@ impl WidgetRef for &ChatComposer {
             .border_type(BorderType::Plain)
             .border_style(border_style)
             .render_ref(
-                Rect::new(textarea_rect.x + 1, textarea_rect.y, 1, textarea_rect.height),
+                Rect::new(
+                    textarea_rect.x + 1,
+                    textarea_rect.y,
+                    1,
+                    textarea_rect.height,
+                ),
                 buf,
             );
 
@@ -303,6 +1009,25 @@ impl WidgetRef for &ChatComposer {
             // Render textarea with state
             let mut state = self.textarea_state.borrow_mut();
             StatefulWidgetRef::render_ref(&&self.textarea, content_area, buf, &mut *state);
+            if let Some(suggestion) = self.visible_suggestion() {
+                if let Some((x, y)) = self.textarea.cursor_pos_with_state(content_area, &state) {
+                    // Cut at the edge of the composer rather than wrapped
+                    let width = content_area.right().saturating_sub(x) as usize;
+                    buf.set_stringn(
+                        x,
+                        y,
+                        suggestion,
+                        width,
+                        Style::default().add_modifier(Modifier::DIM),
+                    );
+                }
+            }
+        }
+
+        if let Some(popup) = &self.file_popup {
+            popup.render_ref(popup_rect, buf);
+        } else if let Some(popup) = &self.command_popup {
+            popup.render_ref(popup_rect, buf);
         }
 
         // Render hints if enabled
@@ -311,3 +1036,103 @@ impl WidgetRef for &ChatComposer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_paste_is_a_placeholder_expanded_on_submit() {
+        let mut composer = ChatComposer::new_minimal(true, String::new(), AppEventSender::noop());
+        let pasted = (1..=120).map(|i| format!("line {i}\n")).collect::<String>();
+        composer.insert_str("summarize ");
+        composer.handle_paste(pasted.clone());
+        assert_eq!(composer.text(), "summarize [pasted 120 lines]");
+
+        // The whole paste is undone in one step.
+        composer.textarea.undo();
+        assert_eq!(composer.text(), "summarize ");
+
+        composer.handle_paste(pasted.clone());
+        let (result, _) = composer.submit();
+        assert_eq!(
+            result,
+            InputResult::Submitted(format!("summarize {}", pasted.trim_end()))
+        );
+    }
+
+    #[test]
+    fn saved_prompts_are_completed_and_rendered_on_submit() {
+        let mut composer = ChatComposer::new_minimal(true, String::new(), AppEventSender::noop());
+        composer.set_custom_prompts(vec![CustomPrompt {
+            name: "review".to_string(),
+            path: PathBuf::from("review.md"),
+            content: "Review {{file}} for {{focus}}.".to_string(),
+            description: Some("Review a file".to_string()),
+            args: vec!["file".to_string(), "focus".to_string()],
+        }]);
+        composer.insert_str("/rev");
+        composer.sync_popups();
+        assert!(composer.accept_completion());
+        assert_eq!(composer.text(), "/review ");
+
+        composer.insert_str("deck.md");
+        assert!(matches!(
+            composer.submit(),
+            (InputResult::CommandError(_), _)
+        ));
+        composer.insert_str(" pacing");
+        assert_eq!(
+            composer.submit().0,
+            InputResult::Submitted("Review deck.md for pacing.".to_string())
+        );
+    }
+
+    #[test]
+    fn ghost_text_follows_typing_and_is_accepted_with_tab() {
+        let mut composer = ChatComposer::new_minimal(true, String::new(), AppEventSender::noop());
+        composer.set_suggestions_enabled(true);
+        composer.insert_str("Make a deck");
+        composer.on_suggestion("Make a de".into(), "ck about Rust".into());
+        assert_eq!(composer.visible_suggestion(), None);
+        composer.on_suggestion("Make a deck".into(), " about Rust".into());
+        assert_eq!(composer.visible_suggestion(), Some(" about Rust"));
+
+        // Typing the start of the suggestion keeps the rest of it
+        composer.insert_str(" ab");
+        composer.on_text_changed();
+        assert_eq!(composer.visible_suggestion(), Some("out Rust"));
+        composer.insert_str("x");
+        composer.on_text_changed();
+        assert_eq!(composer.visible_suggestion(), None);
+
+        composer.on_suggestion("Make a deck abx".into(), " talk".into());
+        let (result, redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!((result, redraw), (InputResult::None, true));
+        assert_eq!(composer.text(), "Make a deck abx talk");
+        assert_eq!(composer.visible_suggestion(), None);
+    }
+
+    #[test]
+    fn clicking_a_suggestion_completes_the_command() {
+        let mut composer = ChatComposer::new_minimal(true, String::new(), AppEventSender::noop());
+        composer.insert_str("/he");
+        composer.sync_popups();
+        let area = Rect::new(0, 10, 60, composer.desired_height(60));
+        let [_, popup_rect, _] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(composer.popup_height()),
+            Constraint::Length(if composer.show_hints { 1 } else { 0 }),
+        ])
+        .areas(area);
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: popup_rect.x + 3,
+            row: popup_rect.y,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert!(composer.handle_mouse_event(click, area));
+        assert_eq!(composer.text(), "/help");
+    }
+}
@@ -7,22 +7,31 @@ pub(crate) struct ChatComposerHistory {
     history_entry_count: usize,
     local_history: Vec<String>,
     fetched_history: HashMap<usize, String>,
+    /// Whether the whole persistent log was read into `fetched_history`
+    fetched_all: bool,
     history_cursor: Option<isize>,
     last_history_text: Option<String>,
+    /// Text typed before browsing started; also the recall prefix
+    draft: String,
     store: HistoryStore,
 }
 
 impl ChatComposerHistory {
     pub fn new() -> Self {
-        let store = HistoryStore::default();
+        Self::with_store(HistoryStore::default())
+    }
+
+    pub fn with_store(store: HistoryStore) -> Self {
         let (log_id, count) = store.metadata();
         Self {
             history_log_id: None,
             history_entry_count: 0,
             local_history: Vec::new(),
             fetched_history: HashMap::new(),
+            fetched_all: false,
             history_cursor: None,
             last_history_text: None,
+            draft: String::new(),
             store,
         }
         .with_metadata(log_id, count)
@@ -40,12 +49,17 @@ impl ChatComposerHistory {
         self.history_log_id = Some(log_id);
         self.history_entry_count = entry_count;
         self.fetched_history.clear();
+        self.fetched_all = false;
         self.local_history.clear();
         self.history_cursor = None;
         self.last_history_text = None;
+        self.draft.clear();
     }
 
     pub fn record_local_submission(&mut self, text: &str) {
+        self.history_cursor = None;
+        self.last_history_text = None;
+        self.draft.clear();
         if text.is_empty() {
             return;
         }
@@ -56,73 +70,79 @@ impl ChatComposerHistory {
         let _ = self.store.append(text);
         // local echo for this UI session
         self.local_history.push(text.to_string());
-        self.history_cursor = None;
-        self.last_history_text = None;
     }
 
-    pub fn should_handle_navigation(&self, text: &str, cursor: usize) -> bool {
-        if self.history_entry_count == 0 && self.local_history.is_empty() {
-            return false;
-        }
-        if text.is_empty() {
-            return true;
-        }
-        if cursor != 0 {
-            return false;
-        }
-        matches!(&self.last_history_text, Some(prev) if prev == text)
+    /// Whether Up/Down currently walk the history instead of the text.
+    pub fn is_browsing(&self) -> bool {
+        self.history_cursor.is_some()
     }
 
-    pub fn navigate_up(&mut self) -> Option<String> {
+    /// Older entry starting with the text typed before browsing began.
+    /// Editing a recalled entry starts a new search with the edited text.
+    pub fn navigate_up(&mut self, current: &str) -> Option<String> {
+        if self.last_history_text.as_deref() != Some(current) {
+            self.history_cursor = None;
+        }
         let total = self.history_entry_count + self.local_history.len();
         if total == 0 {
             return None;
         }
-        let next = match self.history_cursor {
-            None => (total as isize) - 1,
-            Some(0) => return None,
-            Some(i) => i - 1,
-        };
-        self.history_cursor = Some(next);
-        self.get_by_index(next as usize)
+        if self.history_cursor.is_none() {
+            self.draft = current.to_string();
+        }
+        let start = self.history_cursor.unwrap_or(total as isize);
+        for idx in (0..start).rev() {
+            let Some(text) = self.get_by_index(idx as usize) else {
+                continue;
+            };
+            if text.starts_with(&self.draft) && text != current {
+                self.history_cursor = Some(idx);
+                self.last_history_text = Some(text.clone());
+                return Some(text);
+            }
+        }
+        None
     }
 
-    pub fn navigate_down(&mut self) -> Option<String> {
+    /// Newer matching entry; past the newest one the draft comes back.
+    pub fn navigate_down(&mut self, current: &str) -> Option<String> {
         let total = self.history_entry_count + self.local_history.len();
-        if total == 0 {
+        let cursor = self.history_cursor?;
+        if self.last_history_text.as_deref() != Some(current) {
+            self.history_cursor = None;
             return None;
         }
-        let next = match self.history_cursor {
-            None => return None,
-            Some(i) if (i as usize) + 1 >= total => return Some(String::new()),
-            Some(i) => i + 1,
-        };
-        self.history_cursor = Some(next);
-        self.get_by_index(next as usize)
+        for idx in (cursor + 1)..(total as isize) {
+            let Some(text) = self.get_by_index(idx as usize) else {
+                continue;
+            };
+            if text.starts_with(&self.draft) && text != current {
+                self.history_cursor = Some(idx);
+                self.last_history_text = Some(text.clone());
+                return Some(text);
+            }
+        }
+        self.history_cursor = None;
+        self.last_history_text = None;
+        Some(std::mem::take(&mut self.draft))
     }
 
     fn get_by_index(&mut self, idx: usize) -> Option<String> {
         if idx >= self.history_entry_count {
-            let local_idx = idx - self.history_entry_count;
-            let text = self.local_history.get(local_idx)?.clone();
-            self.last_history_text = Some(text.clone());
-            Some(text)
-        } else {
-            if let Some(text) = self.fetched_history.get(&idx).cloned() {
-                self.last_history_text = Some(text.clone());
-                Some(text)
-            } else {
-                // lazy fetch from persistent store
-                if let Some(id) = self.history_log_id {
-                    if let Some(text) = self.store.lookup(id, idx) {
-                        self.fetched_history.insert(idx, text.clone());
-                        self.last_history_text = Some(text.clone());
-                        return Some(text);
-                    }
+            return self
+                .local_history
+                .get(idx - self.history_entry_count)
+                .cloned();
+        }
+        if !self.fetched_all {
+            self.fetched_all = true;
+            if let Some(id) = self.history_log_id {
+                for (i, text) in self.store.entries(id).into_iter().enumerate() {
+                    self.fetched_history.entry(i).or_insert(text);
                 }
-                None
             }
         }
+        self.fetched_history.get(&idx).cloned()
     }
 
     pub fn on_entry_response(
@@ -143,3 +163,36 @@ impl ChatComposerHistory {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recalls_persisted_entries_by_prefix() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-history-{}", std::process::id()));
+        let store = HistoryStore::new(dir.join("history.jsonl"));
+        for text in ["make a deck", "list decks", "make it blue"] {
+            store.append(text)?;
+        }
+        let mut history = ChatComposerHistory::with_store(store);
+        history.record_local_submission("show stats");
+
+        assert_eq!(history.navigate_up("make"), Some("make it blue".into()));
+        assert_eq!(
+            history.navigate_up("make it blue"),
+            Some("make a deck".into())
+        );
+        assert_eq!(history.navigate_up("make a deck"), None);
+        assert_eq!(
+            history.navigate_down("make a deck"),
+            Some("make it blue".into())
+        );
+        // Past the newest match the typed text comes back.
+        assert_eq!(history.navigate_down("make it blue"), Some("make".into()));
+        assert!(!history.is_browsing());
+        assert_eq!(history.navigate_up(""), Some("show stats".into()));
+
+        std::fs::remove_dir_all(dir)
+    }
+}
@@ -5,62 +5,35 @@ use super::{
     scroll_state::ScrollState,
     selection_popup_common::{render_rows, GenericDisplayRow},
 };
+use crate::slash_command::{split_command, SlashCommandRegistry};
+use slide_core::custom_prompts::CustomPrompt;
 
-// 依存の簡易スタブ
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(crate) enum SlashCommand {
-    Init,
-    Compact,
-    Mention,
-}
-impl SlashCommand {
-    pub fn command(&self) -> &'static str {
-        match self {
-            SlashCommand::Init => "init",
-            SlashCommand::Compact => "compact",
-            SlashCommand::Mention => "mention",
-        }
-    }
-    pub fn description(&self) -> &'static str {
-        match self {
-            SlashCommand::Init => "initialize",
-            SlashCommand::Compact => "compact transcript",
-            SlashCommand::Mention => "insert mention",
-        }
-    }
-}
-pub(crate) fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
-    vec![
-        ("init", SlashCommand::Init),
-        ("compact", SlashCommand::Compact),
-        ("mention", SlashCommand::Mention),
-    ]
-}
-
-#[derive(Clone, Debug)]
-pub(crate) struct CustomPrompt {
-    pub name: String,
-    pub content: String,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum CommandItem {
-    Builtin(SlashCommand),
+    /// Index into the registry commands
+    Command(usize),
+    /// Completion for the argument of the typed command
+    Argument(String),
     UserPrompt(usize),
 }
 
+/// Popup shown while the composer starts with `/`: command names first,
+/// then argument completions once a known command and a space are typed.
 pub(crate) struct CommandPopup {
+    registry: SlashCommandRegistry,
     command_filter: String,
-    builtins: Vec<(&'static str, SlashCommand)>,
+    /// Command being given an argument, with the partial argument
+    argument: Option<(String, String)>,
     prompts: Vec<CustomPrompt>,
     state: ScrollState,
 }
 
 impl CommandPopup {
-    pub(crate) fn new(prompts: Vec<CustomPrompt>) -> Self {
+    pub(crate) fn new(registry: SlashCommandRegistry, prompts: Vec<CustomPrompt>) -> Self {
         Self {
+            registry,
             command_filter: String::new(),
-            builtins: built_in_slash_commands(),
+            argument: None,
             prompts,
             state: ScrollState::new(),
         }
@@ -68,20 +41,17 @@ impl CommandPopup {
     pub(crate) fn set_prompts(&mut self, prompts: Vec<CustomPrompt>) {
         self.prompts = prompts;
     }
-    pub(crate) fn prompt_name(&self, idx: usize) -> Option<&str> {
-        self.prompts.get(idx).map(|p| p.name.as_str())
-    }
-    pub(crate) fn prompt_content(&self, idx: usize) -> Option<&str> {
-        self.prompts.get(idx).map(|p| p.content.as_str())
-    }
     pub(crate) fn on_composer_text_change(&mut self, text: String) {
         let first = text.lines().next().unwrap_or("");
-        if let Some(stripped) = first.strip_prefix('/') {
-            let token = stripped.trim_start();
-            let cmd_token = token.split_whitespace().next().unwrap_or("");
-            self.command_filter = cmd_token.to_string();
-        } else {
-            self.command_filter.clear();
+        self.argument = None;
+        self.command_filter.clear();
+        if let Some((name, args)) = split_command(first) {
+            let has_space = first.trim_start()[1..].contains(char::is_whitespace);
+            if has_space && self.registry.get(name).is_some() {
+                self.argument = Some((name.to_string(), args.to_string()));
+            } else {
+                self.command_filter = name.to_string();
+            }
         }
         let len = self.filtered_items().len();
         self.state.clamp_selection(len);
@@ -90,43 +60,47 @@ impl CommandPopup {
     pub(crate) fn calculate_required_height(&self) -> u16 {
         self.filtered_items().len().clamp(1, MAX_POPUP_ROWS) as u16
     }
-    fn filtered(&self) -> Vec<(CommandItem, Option<Vec<usize>>, i32)> {
+    /// Whether the popup is completing an argument rather than a command name.
+    pub(crate) fn is_completing_argument(&self) -> bool {
+        self.argument.is_some()
+    }
+    fn filtered(&self) -> Vec<(CommandItem, Option<Vec<usize>>)> {
+        if let Some((name, partial)) = &self.argument {
+            let Some(command) = self.registry.get(name) else {
+                return Vec::new();
+            };
+            return command
+                .complete(partial)
+                .into_iter()
+                .map(|value| {
+                    (
+                        CommandItem::Argument(value),
+                        Some((0..partial.len()).collect()),
+                    )
+                })
+                .collect();
+        }
+
         let filter = self.command_filter.trim().to_ascii_lowercase();
+        let indices = (!filter.is_empty()).then(|| (0..filter.len()).collect::<Vec<_>>());
         let mut out = Vec::new();
-        if filter.is_empty() {
-            for (_, cmd) in &self.builtins {
-                out.push((CommandItem::Builtin(*cmd), None, 0));
-            }
-            for i in 0..self.prompts.len() {
-                out.push((CommandItem::UserPrompt(i), None, 0));
-            }
-            return out;
-        }
-        // 簡易: 前方一致 + 太字位置を 0..filter.len
-        for (_, cmd) in &self.builtins {
-            let name = cmd.command();
-            if name.starts_with(&filter) {
-                out.push((
-                    CommandItem::Builtin(*cmd),
-                    Some((0..filter.len()).collect()),
-                    0,
-                ));
+        for (i, cmd) in self.registry.commands().iter().enumerate() {
+            if cmd.name().starts_with(&filter) {
+                out.push((CommandItem::Command(i), indices.clone()));
             }
         }
         for (i, p) in self.prompts.iter().enumerate() {
-            let n = p.name.to_ascii_lowercase();
-            if n.starts_with(&filter) {
-                out.push((
-                    CommandItem::UserPrompt(i),
-                    Some((0..filter.len()).collect()),
-                    0,
-                ));
+            // Built-in commands win over prompts of the same name
+            if p.name.to_ascii_lowercase().starts_with(&filter)
+                && self.registry.get(&p.name).is_none()
+            {
+                out.push((CommandItem::UserPrompt(i), indices.clone()));
             }
         }
         out
     }
     fn filtered_items(&self) -> Vec<CommandItem> {
-        self.filtered().into_iter().map(|(c, _, _)| c).collect()
+        self.filtered().into_iter().map(|(c, _)| c).collect()
     }
     pub(crate) fn move_up(&mut self) {
         let len = self.filtered_items().len();
@@ -138,36 +112,91 @@ impl CommandPopup {
         self.state.move_down_wrap(len);
         self.state.ensure_visible(len, MAX_POPUP_ROWS.min(len));
     }
+    /// Mouse wheel: move the selection without wrapping.
+    pub(crate) fn scroll(&mut self, down: bool) {
+        let len = self.filtered_items().len();
+        if down {
+            self.state.move_down(len);
+        } else {
+            self.state.move_up(len);
+        }
+        self.state.ensure_visible(len, MAX_POPUP_ROWS.min(len));
+    }
+    /// Select the item shown on `row` of the popup; false if the row is empty.
+    pub(crate) fn select_visible_row(&mut self, row: usize) -> bool {
+        let idx = self.state.scroll_top + row;
+        if idx >= self.filtered_items().len() {
+            return false;
+        }
+        self.state.set_selected(Some(idx));
+        true
+    }
     pub(crate) fn selected_item(&self) -> Option<CommandItem> {
         let m = self.filtered_items();
-        self.state.selected_idx.and_then(|i| m.get(i).copied())
+        self.state.selected_idx.and_then(|i| m.get(i).cloned())
+    }
+    /// Composer text after accepting the selected item.
+    pub(crate) fn completion(&self) -> Option<String> {
+        match self.selected_item()? {
+            CommandItem::Command(i) => {
+                let cmd = self.registry.commands().get(i)?;
+                if cmd.usage().is_empty() {
+                    Some(format!("/{}", cmd.name()))
+                } else {
+                    Some(format!("/{} ", cmd.name()))
+                }
+            }
+            CommandItem::Argument(value) => {
+                let (name, _) = self.argument.as_ref()?;
+                Some(format!("/{name} {value}"))
+            }
+            CommandItem::UserPrompt(i) => {
+                let prompt = self.prompts.get(i)?;
+                if prompt.args.is_empty() && !prompt.content.contains("$ARGUMENTS") {
+                    Some(format!("/{}", prompt.name))
+                } else {
+                    Some(format!("/{} ", prompt.name))
+                }
+            }
+        }
     }
 }
 
 impl WidgetRef for CommandPopup {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let matches = self.filtered();
-        let rows_all: Vec<GenericDisplayRow> = if matches.is_empty() {
-            Vec::new()
-        } else {
-            matches
-                .into_iter()
-                .map(|(item, indices, _)| match item {
-                    CommandItem::Builtin(cmd) => GenericDisplayRow {
-                        name: format!("/{}", cmd.command()),
+        let commands = self.registry.commands();
+        let rows_all: Vec<GenericDisplayRow> = self
+            .filtered()
+            .into_iter()
+            .filter_map(|(item, indices)| match item {
+                CommandItem::Command(i) => {
+                    let cmd = commands.get(i)?;
+                    Some(GenericDisplayRow {
+                        name: format!("/{}", cmd.name()),
                         match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
                         is_current: false,
                         description: Some(cmd.description().to_string()),
-                    },
-                    CommandItem::UserPrompt(i) => GenericDisplayRow {
-                        name: format!("/{}", self.prompts[i].name),
-                        match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
-                        is_current: false,
-                        description: Some("send saved prompt".to_string()),
-                    },
-                })
-                .collect()
-        };
+                    })
+                }
+                CommandItem::Argument(value) => Some(GenericDisplayRow {
+                    name: value,
+                    match_indices: indices,
+                    is_current: false,
+                    description: None,
+                }),
+                CommandItem::UserPrompt(i) => Some(GenericDisplayRow {
+                    name: format!("/{}", self.prompts[i].name),
+                    match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
+                    is_current: false,
+                    description: Some(
+                        self.prompts[i]
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| "send saved prompt".to_string()),
+                    ),
+                }),
+            })
+            .collect();
         render_rows(area, buf, &rows_all, &self.state, MAX_POPUP_ROWS, false);
     }
 }
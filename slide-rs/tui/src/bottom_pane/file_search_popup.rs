@@ -71,6 +71,25 @@ impl FileSearchPopup {
         self.state.move_down_wrap(len);
         self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
     }
+    /// Mouse wheel: move the selection without wrapping.
+    pub(crate) fn scroll(&mut self, down: bool) {
+        let len = self.matches.len();
+        if down {
+            self.state.move_down(len);
+        } else {
+            self.state.move_up(len);
+        }
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+    /// Select the match shown on `row` of the popup; false if the row is empty.
+    pub(crate) fn select_visible_row(&mut self, row: usize) -> bool {
+        let idx = self.state.scroll_top + row;
+        if idx >= self.matches.len() {
+            return false;
+        }
+        self.state.set_selected(Some(idx));
+        true
+    }
     pub(crate) fn selected_match(&self) -> Option<&str> {
         self.state
             .selected_idx
@@ -1,9 +1,9 @@
 //! Bottom pane: shows the composer or an overlay view.
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::widgets::WidgetRef;
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Layout, Position, Rect},
     text::Line,
     widgets::{Block, Borders, Paragraph, Wrap},
 };
@@ -21,10 +21,13 @@ pub mod popup_consts;
 pub mod scroll_state;
 pub mod selection_popup_common;
 pub mod textarea;
+pub mod vim;
 use crate::app_event_sender::AppEventSender;
 use crate::user_approval_widget::ApprovalRequest;
 use approval_modal_view::ApprovalModalView;
 pub use chat_composer::{ChatComposer, InputResult};
+use file_search_popup::FileMatch;
+use slide_core::custom_prompts::CustomPrompt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum CancellationEvent {
@@ -47,6 +50,7 @@ pub(crate) struct BottomPane {
 pub(crate) struct BottomPaneParams {
     pub(crate) has_input_focus: bool,
     pub(crate) placeholder_text: String,
+    pub(crate) app_event_tx: AppEventSender,
 }
 
 impl BottomPane {
@@ -54,7 +58,11 @@ impl BottomPane {
 
     pub fn new(params: BottomPaneParams) -> Self {
         Self {
-            composer: ChatComposer::new_minimal(params.has_input_focus, params.placeholder_text),
+            composer: ChatComposer::new_minimal(
+                params.has_input_focus,
+                params.placeholder_text,
+                params.app_event_tx,
+            ),
             active_view: None,
             has_input_focus: params.has_input_focus,
             is_task_running: false,
@@ -104,12 +112,66 @@ impl BottomPane {
         } else {
             let (res, _redraw) = self.composer.handle_key_event(key_event);
             match res {
-                InputResult::Submitted(_) => Some(res),
-                _ => None,
+                InputResult::None => None,
+                _ => Some(res),
             }
         }
     }
 
+    /// マウスイベント委譲。`area` は `render_ref` に渡した領域。
+    /// 処理した場合は true を返す
+    pub(crate) fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) -> bool {
+        let content = self.layout(area);
+        if let Some(view) = self.active_view.as_mut() {
+            let over_view = content.contains(Position::new(mouse.column, mouse.row));
+            return match mouse.kind {
+                MouseEventKind::ScrollUp if over_view => view.on_mouse_scroll(-3),
+                MouseEventKind::ScrollDown if over_view => view.on_mouse_scroll(3),
+                _ => false,
+            };
+        }
+        self.composer.handle_mouse_event(mouse, content)
+    }
+
+    pub(crate) fn set_editor_config(&mut self, config: &slide_common::EditorConfig) {
+        self.composer.set_clipboard_mode(config.clipboard);
+        self.composer.set_keymap(config.keymap);
+    }
+
+    pub(crate) fn copy_to_clipboard(&mut self, text: &str) {
+        self.composer.copy_to_clipboard(text);
+    }
+
+    /// Composer mode when the Vim keymap is active.
+    pub(crate) fn vim_mode(&self) -> Option<vim::VimMode> {
+        self.composer.vim_mode()
+    }
+
+    /// Replace the composer text, e.g. with messages handed back for editing.
+    pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+        self.composer.set_custom_prompts(prompts);
+    }
+
+    pub(crate) fn set_composer_text(&mut self, text: &str) {
+        self.composer.set_text(text);
+    }
+
+    /// Pasted text goes to the composer unless a view is showing.
+    pub fn handle_paste(&mut self, pasted: String) -> bool {
+        if self.active_view.is_some() {
+            return false;
+        }
+        self.composer.handle_paste(pasted)
+    }
+
+    pub(crate) fn is_in_paste_burst(&self) -> bool {
+        self.composer.is_in_paste_burst()
+    }
+
+    pub(crate) fn flush_paste_burst_if_due(&mut self) -> bool {
+        self.composer.flush_paste_burst_if_due()
+    }
+
     /// Ctrl-C の処理（ビューがあれば優先）
     pub(crate) fn on_ctrl_c(&mut self) -> CancellationEvent {
         if let Some(mut view) = self.active_view.take() {
@@ -123,6 +185,22 @@ impl BottomPane {
         }
     }
 
+    pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
+        self.composer.on_file_search_result(query, matches);
+    }
+
+    pub(crate) fn set_suggestions_enabled(&mut self, enabled: bool) {
+        self.composer.set_suggestions_enabled(enabled);
+    }
+
+    pub(crate) fn on_suggestion(&mut self, text: String, suggestion: String) {
+        self.composer.on_suggestion(text, suggestion);
+    }
+
+    pub(crate) fn take_recent_submission_attachments(&mut self) -> Vec<std::path::PathBuf> {
+        self.composer.take_recent_submission_attachments()
+    }
+
     pub(crate) fn set_task_running(&mut self, running: bool) {
         self.is_task_running = running;
     }
@@ -150,4 +228,14 @@ impl BottomPane {
     pub fn show_approval_modal(&mut self, req: ApprovalRequest, tx: AppEventSender) {
         self.active_view = Some(Box::new(ApprovalModalView::new(req, tx)));
     }
+
+    /// 回答期限切れの承認要求を閉じる
+    pub fn dismiss_approval(&mut self, id: &str) {
+        if let Some(mut view) = self.active_view.take() {
+            view.dismiss_approval(id);
+            if !view.is_complete() {
+                self.active_view = Some(view);
+            }
+        }
+    }
 }
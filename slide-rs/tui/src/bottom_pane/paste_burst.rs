@@ -122,6 +122,11 @@ impl PasteBurst {
     /// Returns true if a newline was appended (we are in a burst context),
     /// false otherwise.
     pub fn append_newline_if_active(&mut self, now: Instant) -> bool {
+        // A held first char belongs before the newline.
+        if let Some((held, _)) = self.pending_first_char.take() {
+            self.buffer.push(held);
+            self.active = true;
+        }
         if self.is_active() {
             self.buffer.push('\n');
             self.burst_window_until = Some(now + PASTE_ENTER_SUPPRESS_WINDOW);
@@ -192,11 +197,12 @@ impl PasteBurst {
 
     /// Before applying modified/non-char input: flush buffered burst immediately.
     pub fn flush_before_modified_input(&mut self) -> Option<String> {
-        if self.is_active() {
+        if self.is_active_internal() {
             self.active = false;
             Some(std::mem::take(&mut self.buffer))
         } else {
-            None
+            // Emit a held first char as normal typed input.
+            self.pending_first_char.take().map(|(ch, _)| ch.to_string())
         }
     }
 
@@ -2,12 +2,13 @@ use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::{StatefulWidgetRef, WidgetRef},
 };
 use std::{
     cell::{Ref, RefCell},
     ops::Range,
+    time::{Duration, Instant},
 };
 use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
@@ -18,6 +19,53 @@ struct TextElement {
     range: Range<usize>,
 }
 
+/// Undo steps kept in the edit history.
+const MAX_UNDO_STATES: usize = 100;
+/// A pause in typing longer than this starts a new undo step.
+const UNDO_GROUP_PAUSE: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone)]
+struct UndoState {
+    text: String,
+    cursor_pos: usize,
+    elements: Vec<TextElement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    /// One character typed at the cursor
+    Insert,
+    /// One grapheme deleted next to the cursor
+    Delete,
+    /// Anything larger; always its own undo step
+    Other,
+}
+
+/// Entries kept in the kill ring.
+const KILL_RING_SIZE: usize = 30;
+
+/// Last kill/yank command, used to append consecutive kills and to let
+/// Alt-Y replace the text just yanked.
+#[derive(Debug, Clone, Default)]
+enum LastCommand {
+    Kill,
+    Yank {
+        range: Range<usize>,
+        index: usize,
+    },
+    #[default]
+    Other,
+}
+
+/// The undo step that consecutive edits are currently merged into.
+#[derive(Debug, Clone, Copy)]
+struct EditGroup {
+    kind: EditKind,
+    /// Cursor after the last edit of the group
+    end: usize,
+    at: Instant,
+}
+
 #[derive(Debug)]
 pub(crate) struct TextArea {
     text: String,
@@ -25,6 +73,18 @@ pub(crate) struct TextArea {
     wrap_cache: RefCell<Option<WrapCache>>,
     preferred_col: Option<usize>,
     elements: Vec<TextElement>,
+    undo_stack: Vec<UndoState>,
+    redo_stack: Vec<UndoState>,
+    edit_group: Option<EditGroup>,
+    in_atomic_edit: bool,
+    /// Killed text, newest last
+    kill_ring: Vec<String>,
+    /// Set when a kill changed the newest entry and it was not taken yet
+    new_kill: bool,
+    last_command: LastCommand,
+    previous_command: LastCommand,
+    /// Highlighted range (Vim visual mode)
+    selection: Option<Range<usize>>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +107,15 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             elements: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            edit_group: None,
+            in_atomic_edit: false,
+            kill_ring: Vec::new(),
+            new_kill: false,
+            last_command: LastCommand::Other,
+            previous_command: LastCommand::Other,
+            selection: None,
         }
     }
 
@@ -56,6 +125,14 @@ impl TextArea {
         self.wrap_cache.replace(None);
         self.preferred_col = None;
         self.elements.clear();
+        self.selection = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.edit_group = None;
+    }
+
+    pub(crate) fn set_selection(&mut self, selection: Option<Range<usize>>) {
+        self.selection = selection;
     }
 
     pub fn text(&self) -> &str {
@@ -68,6 +145,12 @@ impl TextArea {
 
     pub fn insert_str_at(&mut self, pos: usize, text: &str) {
         let pos = self.clamp_pos_for_insertion(pos);
+        let kind = if pos == self.cursor_pos && text.chars().count() == 1 {
+            EditKind::Insert
+        } else {
+            EditKind::Other
+        };
+        self.begin_edit(kind, text);
         self.text.insert_str(pos, text);
         self.wrap_cache.replace(None);
         if pos <= self.cursor_pos {
@@ -75,6 +158,7 @@ impl TextArea {
         }
         self.shift_elements(pos, 0, text.len());
         self.preferred_col = None;
+        self.end_edit();
     }
 
     pub fn replace_range(&mut self, range: Range<usize>, text: &str) {
@@ -93,6 +177,12 @@ impl TextArea {
         }
         let diff = inserted_len as isize - removed_len as isize;
 
+        let kind = if inserted_len == 0 && self.text[start..end].graphemes(true).count() == 1 {
+            EditKind::Delete
+        } else {
+            EditKind::Other
+        };
+        self.begin_edit(kind, text);
         self.text.replace_range(range, text);
         self.wrap_cache.replace(None);
         self.preferred_col = None;
@@ -113,6 +203,103 @@ impl TextArea {
 
         // Ensure cursor is not inside an element
         self.cursor_pos = self.clamp_pos_to_nearest_boundary(self.cursor_pos);
+        self.end_edit();
+    }
+
+    // ===== Edit history =====
+
+    /// Apply the edits made by `f` as a single undo step.
+    pub(crate) fn edit_atomically(&mut self, f: impl FnOnce(&mut Self)) {
+        let before = self.snapshot();
+        self.in_atomic_edit = true;
+        f(self);
+        self.in_atomic_edit = false;
+        self.edit_group = None;
+        if before.text != self.text {
+            self.push_undo_state(before);
+            self.redo_stack.clear();
+        }
+    }
+
+    /// Revert the last undo step. Returns false when there is nothing to undo.
+    pub(crate) fn undo(&mut self) -> bool {
+        let Some(state) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.redo_stack.push(self.snapshot());
+        self.restore(state);
+        true
+    }
+
+    /// Reapply the last undone step. Returns false when there is nothing to redo.
+    pub(crate) fn redo(&mut self) -> bool {
+        let Some(state) = self.redo_stack.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.push_undo_state(current);
+        self.restore(state);
+        true
+    }
+
+    /// Called before every mutation: starts a new undo step unless the edit
+    /// continues the current group (same kind, at the cursor where the last
+    /// edit ended, without a pause and not starting a new word).
+    fn begin_edit(&mut self, kind: EditKind, inserted: &str) {
+        if self.in_atomic_edit {
+            return;
+        }
+        let now = Instant::now();
+        let starts_word = kind == EditKind::Insert
+            && !inserted.starts_with(char::is_whitespace)
+            && self.text[..self.cursor_pos].ends_with(char::is_whitespace);
+        let joins = self.edit_group.is_some_and(|g| {
+            kind != EditKind::Other
+                && g.kind == kind
+                && g.end == self.cursor_pos
+                && now.duration_since(g.at) < UNDO_GROUP_PAUSE
+                && !starts_word
+        });
+        if !joins {
+            let state = self.snapshot();
+            self.push_undo_state(state);
+        }
+        self.redo_stack.clear();
+        self.edit_group = Some(EditGroup {
+            kind,
+            end: self.cursor_pos,
+            at: now,
+        });
+    }
+
+    fn end_edit(&mut self) {
+        if let Some(group) = self.edit_group.as_mut() {
+            group.end = self.cursor_pos;
+        }
+    }
+
+    fn snapshot(&self) -> UndoState {
+        UndoState {
+            text: self.text.clone(),
+            cursor_pos: self.cursor_pos,
+            elements: self.elements.clone(),
+        }
+    }
+
+    fn push_undo_state(&mut self, state: UndoState) {
+        if self.undo_stack.len() == MAX_UNDO_STATES {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(state);
+    }
+
+    fn restore(&mut self, state: UndoState) {
+        self.text = state.text;
+        self.cursor_pos = state.cursor_pos.min(self.text.len());
+        self.elements = state.elements;
+        self.wrap_cache.replace(None);
+        self.preferred_col = None;
+        self.edit_group = None;
     }
 
     pub fn cursor(&self) -> usize {
@@ -206,6 +393,7 @@ impl TextArea {
     }
 
     pub fn input(&mut self, event: KeyEvent) {
+        self.previous_command = std::mem::take(&mut self.last_command);
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -398,6 +586,44 @@ impl TextArea {
             } => {
                 self.move_cursor_to_end_of_line(true);
             }
+            // Undo: Ctrl-Z (on Unix the app takes it to suspend), or
+            // readline's Ctrl-_ (reported as Ctrl-7 by terminals that send
+            // the 0x1f control byte)
+            KeyEvent {
+                code: KeyCode::Char('z' | '_' | '7'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                self.undo();
+            }
+            KeyEvent {
+                code: KeyCode::Char('_'),
+                modifiers,
+                ..
+            } if modifiers == (KeyModifiers::CONTROL | KeyModifiers::SHIFT) => {
+                self.undo();
+            }
+            KeyEvent {
+                code: KeyCode::Char('z' | 'Z'),
+                modifiers,
+                ..
+            } if modifiers == (KeyModifiers::CONTROL | KeyModifiers::SHIFT) => {
+                self.redo();
+            }
+            KeyEvent {
+                code: KeyCode::Char('y'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                self.yank();
+            }
+            KeyEvent {
+                code: KeyCode::Char('y'),
+                modifiers: KeyModifiers::ALT,
+                ..
+            } => {
+                self.yank_pop();
+            }
             _o => {
                 #[cfg(feature = "debug-logs")]
                 tracing::debug!("Unhandled key event in TextArea: {:?}", _o);
@@ -436,17 +662,17 @@ impl TextArea {
 
     pub fn delete_backward_word(&mut self) {
         let start = self.beginning_of_previous_word();
-        self.replace_range(start..self.cursor_pos, "");
+        self.kill_range(start..self.cursor_pos, true);
     }
 
     pub fn kill_to_end_of_line(&mut self) {
         let eol = self.end_of_current_line();
         if self.cursor_pos == eol {
             if eol < self.text.len() {
-                self.replace_range(self.cursor_pos..eol + 1, "");
+                self.kill_range(self.cursor_pos..eol + 1, false);
             }
         } else {
-            self.replace_range(self.cursor_pos..eol, "");
+            self.kill_range(self.cursor_pos..eol, false);
         }
     }
 
@@ -454,11 +680,89 @@ impl TextArea {
         let bol = self.beginning_of_current_line();
         if self.cursor_pos == bol {
             if bol > 0 {
-                self.replace_range(bol - 1..bol, "");
+                self.kill_range(bol - 1..bol, true);
             }
         } else {
-            self.replace_range(bol..self.cursor_pos, "");
+            self.kill_range(bol..self.cursor_pos, true);
+        }
+    }
+
+    // ===== Kill ring =====
+
+    /// Delete `range` and save it in the kill ring. Consecutive kills are
+    /// merged into one entry, prepended when killing backwards.
+    fn kill_range(&mut self, range: Range<usize>, backward: bool) {
+        let range = self.expand_range_to_element_boundaries(range);
+        if range.is_empty() {
+            return;
+        }
+        let killed = self.text[range.clone()].to_string();
+        self.replace_range_raw(range, "");
+        match self.kill_ring.last_mut() {
+            Some(last) if matches!(self.previous_command, LastCommand::Kill) => {
+                if backward {
+                    last.insert_str(0, &killed);
+                } else {
+                    last.push_str(&killed);
+                }
+            }
+            _ => {
+                if self.kill_ring.len() == KILL_RING_SIZE {
+                    self.kill_ring.remove(0);
+                }
+                self.kill_ring.push(killed);
+            }
         }
+        self.last_command = LastCommand::Kill;
+        self.new_kill = true;
+    }
+
+    /// Insert the newest kill at the cursor.
+    pub(crate) fn yank(&mut self) {
+        let Some(text) = self.kill_ring.last().cloned() else {
+            return;
+        };
+        let start = self.clamp_pos_for_insertion(self.cursor_pos);
+        self.insert_str_at(start, &text);
+        self.last_command = LastCommand::Yank {
+            range: start..start + text.len(),
+            index: self.kill_ring.len() - 1,
+        };
+    }
+
+    /// Right after a yank, replace the yanked text with the previous kill.
+    pub(crate) fn yank_pop(&mut self) {
+        let LastCommand::Yank { range, index } = self.previous_command.clone() else {
+            return;
+        };
+        let len = self.kill_ring.len();
+        let index = (index + len - 1) % len;
+        let text = self.kill_ring[index].clone();
+        self.replace_range_raw(range.clone(), &text);
+        self.set_cursor(range.start + text.len());
+        self.last_command = LastCommand::Yank {
+            range: range.start..range.start + text.len(),
+            index,
+        };
+    }
+
+    /// The newest kill ring entry, once per kill, for copying to a clipboard.
+    pub(crate) fn take_new_kill(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.new_kill) {
+            return None;
+        }
+        self.kill_ring.last().cloned()
+    }
+
+    /// Add text copied outside the composer so the next yank inserts it.
+    pub(crate) fn push_kill(&mut self, text: String) {
+        if text.is_empty() || self.kill_ring.last() == Some(&text) {
+            return;
+        }
+        if self.kill_ring.len() == KILL_RING_SIZE {
+            self.kill_ring.remove(0);
+        }
+        self.kill_ring.push(text);
     }
 
     /// Move the cursor left by a single grapheme cluster.
@@ -635,6 +939,13 @@ impl TextArea {
         self.set_cursor(end);
     }
 
+    /// Whether any element overlaps `range`.
+    pub(crate) fn has_element_in(&self, range: Range<usize>) -> bool {
+        self.elements
+            .iter()
+            .any(|e| e.range.start < range.end && e.range.end > range.start)
+    }
+
     fn add_element(&mut self, range: Range<usize>) {
         let elem = TextElement {
             range: range.clone(),
@@ -946,6 +1257,78 @@ impl TextArea {
                 let style = Style::default().fg(Color::Cyan);
                 buf.set_string(area.x + x_off, y, styled, style);
             }
+
+            if let Some(selection) = &self.selection {
+                let start = selection.start.max(line_range.start);
+                let end = selection.end.min(line_range.end);
+                if start < end {
+                    let x_off = self.text[line_range.start..start].width() as u16;
+                    let style = Style::default().add_modifier(Modifier::REVERSED);
+                    buf.set_string(area.x + x_off, y, &self.text[start..end], style);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_str(textarea: &mut TextArea, text: &str) {
+        for c in text.chars() {
+            textarea.input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
         }
     }
+
+    #[test]
+    fn undo_and_redo_by_word() {
+        let mut textarea = TextArea::new();
+        type_str(&mut textarea, "hello big world");
+        assert!(textarea.undo());
+        assert_eq!(textarea.text(), "hello big ");
+        assert!(textarea.undo());
+        assert_eq!(textarea.text(), "hello ");
+        assert!(textarea.redo());
+        assert_eq!(textarea.text(), "hello big ");
+
+        // A new edit drops the redo history.
+        type_str(&mut textarea, "x");
+        assert!(!textarea.redo());
+        assert_eq!(textarea.text(), "hello big x");
+    }
+
+    #[test]
+    fn kills_are_merged_and_yanked_back() {
+        let mut textarea = TextArea::new();
+        type_str(&mut textarea, "one two three");
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        textarea.input(ctrl('w'));
+        textarea.input(ctrl('w'));
+        assert_eq!(textarea.text(), "one ");
+        assert_eq!(textarea.take_new_kill().as_deref(), Some("two three"));
+
+        textarea.input(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        textarea.input(ctrl('k'));
+        textarea.input(ctrl('a'));
+        textarea.input(ctrl('y'));
+        assert_eq!(textarea.text(), " one");
+        // Alt-Y swaps the yank for the previous kill.
+        textarea.input(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::ALT));
+        assert_eq!(textarea.text(), "two threeone");
+    }
+
+    #[test]
+    fn undo_restores_elements() {
+        let mut textarea = TextArea::new();
+        type_str(&mut textarea, "see ");
+        textarea.insert_element("@deck.md");
+        textarea.delete_backward(1);
+        assert_eq!(textarea.text(), "see ");
+        assert!(textarea.undo());
+        assert_eq!(textarea.text(), "see @deck.md");
+        // The element is atomic again: one backspace removes all of it.
+        textarea.delete_backward(1);
+        assert_eq!(textarea.text(), "see ");
+    }
 }
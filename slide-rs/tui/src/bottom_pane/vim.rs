@@ -0,0 +1,628 @@
+//! Optional Vim-style modal editing for the composer (`editor.keymap = "vim"`).
+//!
+//! Normal and visual mode keys are interpreted here and applied to the
+//! [`TextArea`]; insert mode keys go through the default composer keymap.
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::textarea::TextArea;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl VimMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+        }
+    }
+}
+
+/// What the composer should do with a key after Vim handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimOutcome {
+    Handled,
+    /// Enter in normal mode
+    Submit,
+    /// `k`/`j`: move a line, or walk the history at the first/last line
+    Navigate {
+        up: bool,
+    },
+    /// Insert mode: use the default keymap
+    PassThrough,
+}
+
+#[derive(Debug, Default)]
+struct Register {
+    text: String,
+    /// Yanked or deleted whole lines (`yy`, `dd`)
+    linewise: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct Vim {
+    mode: VimMode,
+    /// Count typed before a command
+    count: Option<usize>,
+    /// Operator (`d`, `c`, `y`) or `g` waiting for the next key
+    pending: Option<char>,
+    visual_anchor: usize,
+    register: Register,
+}
+
+impl Vim {
+    pub(crate) fn new() -> Self {
+        Self {
+            mode: VimMode::Insert,
+            count: None,
+            pending: None,
+            visual_anchor: 0,
+            register: Register::default(),
+        }
+    }
+
+    pub(crate) fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Back to insert mode, e.g. after a message was submitted.
+    pub(crate) fn reset(&mut self, textarea: &mut TextArea) {
+        self.mode = VimMode::Insert;
+        self.count = None;
+        self.pending = None;
+        textarea.set_selection(None);
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent, textarea: &mut TextArea) -> VimOutcome {
+        if self.mode == VimMode::Insert {
+            if key.code != KeyCode::Esc {
+                return VimOutcome::PassThrough;
+            }
+            self.mode = VimMode::Normal;
+            let cursor = textarea.cursor();
+            if cursor > line_start(textarea.text(), cursor) {
+                textarea.set_cursor(prev_char(textarea.text(), cursor));
+            }
+            return VimOutcome::Handled;
+        }
+
+        let outcome = self.handle_command_key(key, textarea);
+        if self.mode == VimMode::Visual {
+            textarea.set_selection(Some(self.visual_range(textarea)));
+        } else {
+            textarea.set_selection(None);
+        }
+        if self.mode == VimMode::Normal {
+            clamp_to_char(textarea);
+        }
+        outcome
+    }
+
+    fn handle_command_key(&mut self, key: KeyEvent, textarea: &mut TextArea) -> VimOutcome {
+        let KeyEvent {
+            code, modifiers, ..
+        } = key;
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            self.clear_pending();
+            match code {
+                KeyCode::Char('r') => {
+                    textarea.redo();
+                }
+                KeyCode::Char('c') | KeyCode::Char('[') => self.mode = VimMode::Normal,
+                _ => {}
+            }
+            return VimOutcome::Handled;
+        }
+        let c = match code {
+            KeyCode::Char(c) => c,
+            KeyCode::Esc => {
+                self.clear_pending();
+                self.mode = VimMode::Normal;
+                return VimOutcome::Handled;
+            }
+            KeyCode::Enter if self.mode == VimMode::Normal => {
+                self.clear_pending();
+                return VimOutcome::Submit;
+            }
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            _ => return VimOutcome::Handled,
+        };
+
+        if c.is_ascii_digit() && (c != '0' || self.count.is_some()) && self.pending != Some('g') {
+            let digit = c as usize - '0' as usize;
+            self.count = Some(self.count.unwrap_or(0).saturating_mul(10) + digit);
+            return VimOutcome::Handled;
+        }
+        let count = self.count.take().unwrap_or(1);
+        let pending = self.pending.take();
+
+        if pending == Some('g') {
+            if c == 'g' {
+                self.move_or_apply(None, textarea, Motion::Exclusive(0));
+            }
+            return VimOutcome::Handled;
+        }
+
+        // Operator doubled: whole lines (`dd`, `cc`, `yy`)
+        if let Some(op) = pending {
+            if c == op {
+                self.apply_linewise(op, count, textarea);
+                return VimOutcome::Handled;
+            }
+        }
+
+        // Like Vim, `cw` changes to the end of the word.
+        let c = if pending == Some('c') && c == 'w' {
+            'e'
+        } else {
+            c
+        };
+        if let Some(mut motion) = motion(c, count, textarea) {
+            if pending.is_some() && c == 'w' {
+                // `dw` on the last word of a line stops at the line end.
+                let end = line_end(textarea.text(), textarea.cursor());
+                if let Motion::Exclusive(target) = motion {
+                    motion = Motion::Exclusive(target.min(end));
+                }
+            }
+            self.move_or_apply(pending, textarea, motion);
+            return VimOutcome::Handled;
+        }
+        if pending.is_some() {
+            // Operator followed by something that is not a motion: cancel.
+            return VimOutcome::Handled;
+        }
+
+        if self.mode == VimMode::Visual {
+            return self.handle_visual_key(c, textarea);
+        }
+        self.handle_normal_key(c, count, textarea)
+    }
+
+    fn handle_normal_key(&mut self, c: char, count: usize, textarea: &mut TextArea) -> VimOutcome {
+        let text = textarea.text().to_string();
+        let cursor = textarea.cursor();
+        match c {
+            'j' | 'k' => return VimOutcome::Navigate { up: c == 'k' },
+            'i' => self.mode = VimMode::Insert,
+            'a' => {
+                if cursor < line_end(&text, cursor) {
+                    textarea.set_cursor(next_char(&text, cursor));
+                }
+                self.mode = VimMode::Insert;
+            }
+            'I' => {
+                textarea.set_cursor(first_non_blank(&text, cursor));
+                self.mode = VimMode::Insert;
+            }
+            'A' => {
+                textarea.set_cursor(line_end(&text, cursor));
+                self.mode = VimMode::Insert;
+            }
+            'o' => {
+                textarea.set_cursor(line_end(&text, cursor));
+                textarea.insert_str("\n");
+                self.mode = VimMode::Insert;
+            }
+            'O' => {
+                let start = line_start(&text, cursor);
+                textarea.set_cursor(start);
+                textarea.insert_str("\n");
+                textarea.set_cursor(start);
+                self.mode = VimMode::Insert;
+            }
+            'v' => {
+                self.visual_anchor = cursor;
+                self.mode = VimMode::Visual;
+            }
+            'd' | 'c' | 'y' | 'g' => {
+                self.pending = Some(c);
+                // Keep the count for the motion: `2dw`, `3dd`
+                self.count = (count > 1).then_some(count);
+            }
+            'x' => {
+                let mut end = cursor;
+                for _ in 0..count {
+                    if end < line_end(&text, cursor) {
+                        end = next_char(&text, end);
+                    }
+                }
+                self.apply('d', cursor..end, false, textarea);
+            }
+            'X' => {
+                let mut start = cursor;
+                for _ in 0..count {
+                    if start > line_start(&text, cursor) {
+                        start = prev_char(&text, start);
+                    }
+                }
+                self.apply('d', start..cursor, false, textarea);
+            }
+            'D' => self.apply('d', cursor..line_end(&text, cursor), false, textarea),
+            'C' => self.apply('c', cursor..line_end(&text, cursor), false, textarea),
+            'Y' => self.apply_linewise('y', count, textarea),
+            'p' | 'P' => self.put(c == 'p', count, textarea),
+            'u' => {
+                for _ in 0..count {
+                    textarea.undo();
+                }
+            }
+            _ => {}
+        }
+        VimOutcome::Handled
+    }
+
+    fn handle_visual_key(&mut self, c: char, textarea: &mut TextArea) -> VimOutcome {
+        let range = self.visual_range(textarea);
+        match c {
+            'v' => self.mode = VimMode::Normal,
+            'o' => {
+                let cursor = textarea.cursor();
+                textarea.set_cursor(self.visual_anchor);
+                self.visual_anchor = cursor;
+            }
+            'd' | 'x' => {
+                self.mode = VimMode::Normal;
+                self.apply('d', range, false, textarea);
+            }
+            'c' | 's' => self.apply('c', range, false, textarea),
+            'y' => {
+                self.mode = VimMode::Normal;
+                self.apply('y', range, false, textarea);
+            }
+            'j' | 'k' => {
+                if c == 'j' {
+                    textarea.move_cursor_down();
+                } else {
+                    textarea.move_cursor_up();
+                }
+            }
+            _ => {}
+        }
+        VimOutcome::Handled
+    }
+
+    /// Move the cursor, or apply the pending operator over the motion.
+    fn move_or_apply(&mut self, operator: Option<char>, textarea: &mut TextArea, motion: Motion) {
+        let cursor = textarea.cursor();
+        let text = textarea.text();
+        let (target, inclusive) = match motion {
+            Motion::Exclusive(target) => (target, false),
+            Motion::Inclusive(target) => (target, true),
+        };
+        let Some(op) = operator else {
+            textarea.set_cursor(target);
+            return;
+        };
+        let (start, end) = if target < cursor {
+            (target, cursor)
+        } else if inclusive && target < text.len() {
+            (cursor, next_char(text, target))
+        } else {
+            (cursor, target)
+        };
+        self.apply(op, start..end, false, textarea);
+    }
+
+    fn apply_linewise(&mut self, op: char, count: usize, textarea: &mut TextArea) {
+        let text = textarea.text().to_string();
+        let cursor = textarea.cursor();
+        let start = line_start(&text, cursor);
+        let mut end = line_end(&text, cursor);
+        for _ in 1..count {
+            if end >= text.len() {
+                break;
+            }
+            end = line_end(&text, end + 1);
+        }
+        self.register = Register {
+            text: text[start..end].to_string(),
+            linewise: true,
+        };
+        match op {
+            'd' => {
+                // Take a line break with the lines so no empty line is left.
+                let range = if end < text.len() {
+                    start..end + 1
+                } else if start > 0 {
+                    start - 1..end
+                } else {
+                    start..end
+                };
+                textarea.replace_range(range, "");
+                let text = textarea.text();
+                let pos = first_non_blank(text, start.min(text.len()));
+                textarea.set_cursor(pos);
+            }
+            'c' => {
+                textarea.replace_range(start..end, "");
+                textarea.set_cursor(start);
+                self.mode = VimMode::Insert;
+            }
+            _ => textarea.set_cursor(start),
+        }
+    }
+
+    fn apply(&mut self, op: char, range: Range<usize>, linewise: bool, textarea: &mut TextArea) {
+        if range.is_empty() && op != 'c' {
+            return;
+        }
+        self.register = Register {
+            text: textarea.text()[range.clone()].to_string(),
+            linewise,
+        };
+        match op {
+            'd' => {
+                textarea.replace_range(range.clone(), "");
+                textarea.set_cursor(range.start);
+            }
+            'c' => {
+                textarea.replace_range(range.clone(), "");
+                textarea.set_cursor(range.start);
+                self.mode = VimMode::Insert;
+            }
+            _ => textarea.set_cursor(range.start),
+        }
+    }
+
+    /// `p` puts after the cursor (or below the line), `P` before.
+    fn put(&mut self, after: bool, count: usize, textarea: &mut TextArea) {
+        if self.register.text.is_empty() && !self.register.linewise {
+            return;
+        }
+        let text = textarea.text().to_string();
+        let cursor = textarea.cursor();
+        let body = self.register.text.repeat(count.max(1));
+        if self.register.linewise {
+            let lines = vec![self.register.text.as_str(); count.max(1)].join("\n");
+            if after {
+                let end = line_end(&text, cursor);
+                textarea.set_cursor(end);
+                textarea.insert_str(&format!("\n{lines}"));
+                textarea.set_cursor(end + 1);
+            } else {
+                let start = line_start(&text, cursor);
+                textarea.set_cursor(start);
+                textarea.insert_str(&format!("{lines}\n"));
+                textarea.set_cursor(start);
+            }
+            return;
+        }
+        let at = if after && cursor < line_end(&text, cursor) {
+            next_char(&text, cursor)
+        } else {
+            cursor
+        };
+        textarea.set_cursor(at);
+        textarea.insert_str(&body);
+        let end = at + body.len();
+        textarea.set_cursor(prev_char(textarea.text(), end));
+    }
+
+    /// Selected bytes, including the character under the cursor.
+    fn visual_range(&self, textarea: &TextArea) -> Range<usize> {
+        let text = textarea.text();
+        let cursor = textarea.cursor();
+        let start = self.visual_anchor.min(cursor).min(text.len());
+        let last = self.visual_anchor.max(cursor).min(text.len());
+        let end = if last < text.len() {
+            next_char(text, last)
+        } else {
+            last
+        };
+        start..end
+    }
+
+    fn clear_pending(&mut self) {
+        self.count = None;
+        self.pending = None;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Motion {
+    /// Operators stop before the target
+    Exclusive(usize),
+    /// Operators include the character at the target (`e`, `$`)
+    Inclusive(usize),
+}
+
+fn motion(c: char, count: usize, textarea: &TextArea) -> Option<Motion> {
+    let text = textarea.text();
+    let mut pos = textarea.cursor();
+    let repeat = |mut pos: usize, step: &dyn Fn(usize) -> usize| {
+        for _ in 0..count {
+            pos = step(pos);
+        }
+        pos
+    };
+    let motion = match c {
+        'h' => {
+            let start = line_start(text, pos);
+            pos = repeat(pos, &|p| if p > start { prev_char(text, p) } else { p });
+            Motion::Exclusive(pos)
+        }
+        'l' | ' ' => {
+            let end = line_end(text, pos);
+            pos = repeat(pos, &|p| if p < end { next_char(text, p) } else { p });
+            Motion::Exclusive(pos)
+        }
+        'w' => Motion::Exclusive(repeat(pos, &|p| word_forward(text, p))),
+        'b' => Motion::Exclusive(repeat(pos, &|p| word_backward(text, p))),
+        'e' => Motion::Inclusive(repeat(pos, &|p| word_end(text, p))),
+        '0' => Motion::Exclusive(line_start(text, pos)),
+        '^' => Motion::Exclusive(first_non_blank(text, pos)),
+        '$' => {
+            let end = line_end(text, pos);
+            Motion::Inclusive(if end > line_start(text, pos) {
+                prev_char(text, end)
+            } else {
+                end
+            })
+        }
+        'G' => Motion::Exclusive(line_start(text, text.len())),
+        _ => return None,
+    };
+    Some(motion)
+}
+
+/// In normal mode the cursor sits on a character, not after the last one.
+fn clamp_to_char(textarea: &mut TextArea) {
+    let text = textarea.text();
+    let cursor = textarea.cursor();
+    if cursor > line_start(text, cursor) && cursor == line_end(text, cursor) {
+        let pos = prev_char(text, cursor);
+        textarea.set_cursor(pos);
+    }
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i)
+}
+
+fn first_non_blank(text: &str, pos: usize) -> usize {
+    let start = line_start(text, pos);
+    let end = line_end(text, pos);
+    text[start..end]
+        .find(|c: char| !c.is_whitespace())
+        .map_or(end, |i| start + i)
+}
+
+fn next_char(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .chars()
+        .next()
+        .map_or(text.len(), |c| pos + c.len_utf8())
+}
+
+fn prev_char(text: &str, pos: usize) -> usize {
+    text[..pos]
+        .chars()
+        .next_back()
+        .map_or(0, |c| pos - c.len_utf8())
+}
+
+#[derive(PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    Punct,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+fn class_at(text: &str, pos: usize) -> Option<CharClass> {
+    text[pos..].chars().next().map(class)
+}
+
+/// Start of the next word.
+fn word_forward(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    if let Some(start_class) = class_at(text, pos) {
+        while pos < text.len() && class_at(text, pos).as_ref() == Some(&start_class) {
+            pos = next_char(text, pos);
+        }
+    }
+    while pos < text.len() && class_at(text, pos) == Some(CharClass::Space) {
+        pos = next_char(text, pos);
+    }
+    pos
+}
+
+/// Start of the current or previous word.
+fn word_backward(text: &str, pos: usize) -> usize {
+    let mut pos = pos;
+    while pos > 0 && class_at(text, prev_char(text, pos)) == Some(CharClass::Space) {
+        pos = prev_char(text, pos);
+    }
+    if pos == 0 {
+        return 0;
+    }
+    let word_class = class_at(text, prev_char(text, pos));
+    while pos > 0 && class_at(text, prev_char(text, pos)) == word_class {
+        pos = prev_char(text, pos);
+    }
+    pos
+}
+
+/// Last character of the current or next word.
+fn word_end(text: &str, pos: usize) -> usize {
+    let mut pos = next_char(text, pos);
+    while pos < text.len() && class_at(text, pos) == Some(CharClass::Space) {
+        pos = next_char(text, pos);
+    }
+    let Some(word_class) = class_at(text, pos) else {
+        return prev_char(text, text.len());
+    };
+    while next_char(text, pos) < text.len()
+        && class_at(text, next_char(text, pos)).as_ref() == Some(&word_class)
+    {
+        pos = next_char(text, pos);
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(vim: &mut Vim, textarea: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            let key = match c {
+                '\u{1b}' => KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                c => KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+            };
+            match vim.handle_key(key, textarea) {
+                VimOutcome::PassThrough => textarea.input(key),
+                VimOutcome::Navigate { up: true } => textarea.move_cursor_up(),
+                VimOutcome::Navigate { up: false } => textarea.move_cursor_down(),
+                VimOutcome::Handled | VimOutcome::Submit => {}
+            }
+        }
+    }
+
+    #[test]
+    fn operators_counts_and_registers() {
+        let mut vim = Vim::new();
+        let mut textarea = TextArea::new();
+        keys(&mut vim, &mut textarea, "one two three\nsecond line\u{1b}");
+        assert_eq!(vim.mode(), VimMode::Normal);
+
+        keys(&mut vim, &mut textarea, "gg2dw");
+        assert_eq!(textarea.text(), "three\nsecond line");
+        keys(&mut vim, &mut textarea, "yyjp");
+        assert_eq!(textarea.text(), "three\nsecond line\nthree");
+        keys(&mut vim, &mut textarea, "ggdd");
+        assert_eq!(textarea.text(), "second line\nthree");
+        keys(&mut vim, &mut textarea, "wcwcat\u{1b}");
+        assert_eq!(textarea.text(), "second cat\nthree");
+        keys(&mut vim, &mut textarea, "0vex");
+        assert_eq!(textarea.text(), " cat\nthree");
+        keys(&mut vim, &mut textarea, "u");
+        assert_eq!(textarea.text(), "second cat\nthree");
+    }
+}
@@ -0,0 +1,71 @@
+//! Copying killed composer text and transcript content out of the TUI.
+use std::io::Write;
+
+use base64::Engine;
+use slide_common::ClipboardMode;
+
+pub(crate) struct Clipboard {
+    mode: ClipboardMode,
+    /// Opened lazily; `None` after a failed attempt falls back to OSC 52
+    system: Option<arboard::Clipboard>,
+    system_failed: bool,
+}
+
+impl Clipboard {
+    pub(crate) fn new(mode: ClipboardMode) -> Self {
+        Self {
+            mode,
+            system: None,
+            system_failed: false,
+        }
+    }
+
+    /// Best effort; a terminal or desktop without clipboard support is ignored.
+    pub(crate) fn copy(&mut self, text: &str) {
+        match self.mode {
+            ClipboardMode::Off => {}
+            ClipboardMode::Osc52 => copy_osc52(text),
+            ClipboardMode::System => match self.system() {
+                Some(clipboard) => {
+                    let _ = clipboard.set_text(text.to_string());
+                }
+                None => copy_osc52(text),
+            },
+        }
+    }
+
+    /// Copy the user asked for (`/copy`, `y` in a pager). `off` only keeps
+    /// killed text from the clipboard, so these still go out over OSC 52.
+    pub(crate) fn copy_requested(&mut self, text: &str) {
+        match self.mode {
+            ClipboardMode::Off => copy_osc52(text),
+            _ => self.copy(text),
+        }
+    }
+
+    /// Text on the system clipboard. OSC 52 reads are not supported by most
+    /// terminals, so only the `system` mode can paste.
+    pub(crate) fn paste(&mut self) -> Option<String> {
+        if self.mode != ClipboardMode::System {
+            return None;
+        }
+        self.system()?.get_text().ok()
+    }
+
+    fn system(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.system.is_none() && !self.system_failed {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.system = Some(clipboard),
+                Err(_) => self.system_failed = true,
+            }
+        }
+        self.system.as_mut()
+    }
+}
+
+fn copy_osc52(text: &str) {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b]52;c;{encoded}\x07");
+    let _ = stdout.flush();
+}
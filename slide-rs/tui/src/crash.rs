@@ -0,0 +1,125 @@
+//! Panic hook: a panic in raw mode would otherwise leave the terminal without
+//! echo, with the cursor hidden and mouse reporting on. The hook restores it,
+//! writes a crash report with a backtrace under `~/.slide/crash/` and tells
+//! the user where it is.
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableBracketedPaste, DisableFocusChange, DisableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+
+use crate::history_store::home_dir;
+
+/// Directory crash reports are written to
+pub fn crash_dir() -> PathBuf {
+    home_dir().join(".slide").join("crash")
+}
+
+/// Install the hook once; later calls do nothing.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            restore_terminal();
+            let report = crash_report(info, &Backtrace::force_capture());
+            let saved = save_report(&crash_dir(), &report);
+            let mut stderr = std::io::stderr();
+            let _ = writeln!(stderr, "\nslide crashed: {}", panic_message(info));
+            let _ = match &saved {
+                Ok(path) => writeln!(stderr, "A crash report was written to {}", path.display()),
+                Err(e) => writeln!(
+                    stderr,
+                    "The crash report could not be saved ({e}):\n{report}"
+                ),
+            };
+            let _ = writeln!(
+                stderr,
+                "If the terminal still misbehaves, run `reset`. Your chat sessions are saved; \
+                 `slide` offers to resume them. Please attach the report when filing an issue."
+            );
+        }));
+    });
+}
+
+/// Undo everything the TUI screens switch on. Each step is tried even when
+/// an earlier one fails, e.g. because stdout is already closed.
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let mut stdout = std::io::stdout();
+    let _ = execute!(
+        stdout,
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        Show
+    );
+    // Below the inline viewport so the message does not overwrite it
+    let _ = writeln!(stdout);
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(no message)".to_string());
+    match info.location() {
+        Some(location) => format!("{message} at {location}"),
+        None => message,
+    }
+}
+
+fn crash_report(info: &PanicHookInfo<'_>, backtrace: &Backtrace) -> String {
+    format_report(
+        &panic_message(info),
+        std::thread::current().name().unwrap_or("(unnamed)"),
+        &backtrace.to_string(),
+    )
+}
+
+fn format_report(message: &str, thread: &str, backtrace: &str) -> String {
+    format!(
+        "slide {} crash report\ntime: {}\nos: {} {}\nthread: {thread}\npanic: {message}\n\nbacktrace:\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().to_rfc3339(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    )
+}
+
+fn save_report(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}-{}.txt",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    ));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_saved_with_the_panic_and_backtrace() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-crash-{}", std::process::id()));
+        let report = format_report("index out of bounds at src/app.rs:1:1", "main", "0: main");
+        let path = save_report(&dir, &report)?;
+        let saved = std::fs::read_to_string(&path)?;
+        assert!(saved.contains("thread: main\npanic: index out of bounds at src/app.rs:1:1"));
+        assert!(saved.ends_with("backtrace:\n0: main\n"));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
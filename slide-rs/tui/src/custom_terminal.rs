@@ -29,11 +29,15 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Position;
 use ratatui::layout::Rect;
 use ratatui::layout::Size;
+use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::widgets::StatefulWidget;
 use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
 
+use crate::transcript_store::TranscriptStore;
+
 #[derive(Debug, Hash)]
 pub struct Frame<'a> {
     /// Where should the cursor be after drawing this frame?
@@ -177,6 +181,8 @@ where
     pub last_known_cursor_pos: Position,
     /// Number of frames rendered up until current time.
     frame_count: usize,
+    /// Lines inserted above the viewport, for the transcript pager
+    scrollback: TranscriptStore,
 }
 
 impl<B> Drop for Terminal<B>
@@ -216,6 +222,7 @@ where
             last_known_screen_size: screen_size,
             last_known_cursor_pos: cursor_pos,
             frame_count: 0,
+            scrollback: TranscriptStore::default(),
         })
     }
 
@@ -235,6 +242,32 @@ where
         }
     }
 
+    /// Lines inserted into the terminal scrollback so far.
+    pub(crate) fn scrollback(&self) -> &TranscriptStore {
+        &self.scrollback
+    }
+
+    pub(crate) fn scrollback_mut(&mut self) -> &mut TranscriptStore {
+        &mut self.scrollback
+    }
+
+    /// Replace the store of the scrollback, e.g. with one sized by the config.
+    pub(crate) fn set_scrollback(&mut self, store: TranscriptStore) {
+        self.scrollback = store;
+    }
+
+    pub(crate) fn record_scrollback(&mut self, lines: &[Line]) {
+        self.scrollback.push(lines.iter().map(|line| {
+            Line::from(
+                line.spans
+                    .iter()
+                    .map(|span| Span::styled(span.content.to_string(), span.style))
+                    .collect::<Vec<_>>(),
+            )
+            .style(line.style)
+        }));
+    }
+
     /// Gets the current buffer as a mutable reference.
     pub fn current_buffer_mut(&mut self) -> &mut Buffer {
         &mut self.buffers[self.current]
@@ -278,6 +311,12 @@ where
         self.viewport_area = area;
     }
 
+    /// Forget what was drawn, so the next frame is drawn in full; for when
+    /// something else wrote over the viewport.
+    pub fn invalidate(&mut self) {
+        self.buffers[1 - self.current].reset();
+    }
+
     /// Queries the backend for size and resizes if it doesn't match the previous size.
     pub fn autoresize(&mut self) -> io::Result<()> {
         let screen_size = self.size()?;
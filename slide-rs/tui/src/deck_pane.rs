@@ -0,0 +1,220 @@
+//! Live view of the deck being edited, shown beside the composer in the
+//! split layout (`/split`).
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+use slide_common::{Deck, Slide};
+use slide_core::file_watcher::FsChange;
+
+/// How often the deck file is checked for changes when there is no file
+/// watcher
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+/// Rows the slide list may take before the current slide gets the rest
+const MAX_LIST_ROWS: u16 = 6;
+
+pub(crate) struct DeckPane {
+    path: PathBuf,
+    slides: Vec<Slide>,
+    /// Slide shown in full: the one changed last
+    current: usize,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    error: Option<String>,
+}
+
+impl DeckPane {
+    pub(crate) fn open(path: PathBuf) -> Self {
+        let mut pane = Self {
+            path,
+            slides: Vec::new(),
+            current: 0,
+            modified: None,
+            last_check: Instant::now(),
+            error: None,
+        };
+        pane.reload();
+        pane
+    }
+
+    /// Show slide `index` until the deck changes.
+    pub(crate) fn at_slide(mut self, index: usize) -> Self {
+        self.current = index.min(self.slides.len().saturating_sub(1));
+        self
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn current_slide(&self) -> usize {
+        self.current
+    }
+
+    /// Reload the deck when the watcher reports its file changed; returns
+    /// whether it did.
+    pub(crate) fn on_fs_change(&mut self, change: &FsChange) -> bool {
+        let touched = change.touches(&self.path);
+        if touched {
+            self.reload();
+        }
+        touched
+    }
+
+    /// Without a file watcher: reload the deck when its file changed since
+    /// the last check; returns whether it did.
+    pub(crate) fn refresh(&mut self) -> bool {
+        if self.last_check.elapsed() < REFRESH_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+        let changed = modified != self.modified;
+        if changed {
+            self.reload();
+        }
+        changed
+    }
+
+    fn reload(&mut self) {
+        self.modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+        match std::fs::read_to_string(&self.path) {
+            Ok(markdown) => {
+                self.error = None;
+                self.update(&markdown);
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Replace the slides and move to the first one that changed.
+    fn update(&mut self, markdown: &str) {
+        let slides = Deck::parse(markdown).slides;
+        let changed = slides
+            .iter()
+            .zip(&self.slides)
+            .position(|(new, old)| new != old)
+            .or_else(|| (slides.len() > self.slides.len()).then_some(self.slides.len()));
+        if let Some(index) = changed.filter(|_| !self.slides.is_empty()) {
+            self.current = index;
+        }
+        self.slides = slides;
+        self.current = self.current.min(self.slides.len().saturating_sub(1));
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        let name = self
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = if self.slides.is_empty() {
+            format!(" {name} ")
+        } else {
+            format!(
+                " {name} · slide {}/{} ",
+                self.current + 1,
+                self.slides.len()
+            )
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .title(Span::styled(title, Style::default().fg(Color::Cyan)));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if let Some(error) = &self.error {
+            Paragraph::new(Span::styled(error.clone(), Style::default().fg(Color::Red)))
+                .wrap(Wrap { trim: true })
+                .render(inner, buf);
+            return;
+        }
+
+        let list_rows = (self.slides.len() as u16)
+            .min(MAX_LIST_ROWS)
+            .min(inner.height / 3);
+        let [list_area, slide_area] =
+            Layout::vertical([Constraint::Length(list_rows), Constraint::Min(0)]).areas(inner);
+
+        // Slide list, scrolled so the current slide stays visible
+        let offset = (self.current + 1).saturating_sub(list_rows as usize);
+        let rows: Vec<Line> = self
+            .slides
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(list_rows as usize)
+            .map(|(i, slide)| {
+                let title = slide.title.clone().unwrap_or_else(|| "(untitled)".into());
+                let text = format!("{:>2}. {title}", i + 1);
+                if i == self.current {
+                    Line::styled(text, Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    Line::styled(text, Style::default().fg(Color::Gray))
+                }
+            })
+            .collect();
+        Paragraph::new(rows).render(list_area, buf);
+
+        let Some(slide) = self.slides.get(self.current) else {
+            return;
+        };
+        let lines: Vec<Line> = slide
+            .content
+            .lines()
+            .map(|l| {
+                if l.starts_with('#') {
+                    Line::styled(
+                        l.to_string(),
+                        Style::default()
+                            .fg(Color::Magenta)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Line::from(l.to_string())
+                }
+            })
+            .collect();
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::TOP))
+            .wrap(Wrap { trim: false })
+            .render(slide_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_moves_to_the_changed_slide() {
+        let mut pane = DeckPane::open(PathBuf::from("/nonexistent/deck.md"));
+        assert!(pane.error.is_some());
+
+        pane.update("# Deck\n\n## One\n\na\n\n## Two\n\nb\n\n## Three\n\nc\n");
+        assert_eq!(pane.slides.len(), 4);
+        assert_eq!(pane.current, 0);
+
+        pane.update("# Deck\n\n## One\n\na\n\n## Two\n\nb changed\n\n## Three\n\nc\n");
+        assert_eq!(pane.current, 2);
+
+        // A new slide at the end becomes current
+        pane.update("# Deck\n\n## One\n\na\n\n## Two\n\nb changed\n\n## Three\n\nc\n\n## Four\n");
+        assert_eq!(pane.current, 4);
+
+        // Removing slides keeps the index in range
+        pane.update("# Deck\n");
+        assert_eq!(pane.current, 0);
+    }
+}
@@ -0,0 +1,335 @@
+//! Per-file diff review shown in the apply_patch approval modal.
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use slide_core::codex::ApplyPatchFileChange;
+
+/// Widest the file list gets, in columns
+const MAX_FILE_LIST_WIDTH: u16 = 40;
+
+pub(crate) struct PatchReview {
+    files: Vec<ReviewFile>,
+    selected: usize,
+    /// First diff line shown for the selected file
+    scroll: usize,
+    /// Diff lines visible at the last render, for paging
+    page: Cell<usize>,
+}
+
+struct ReviewFile {
+    path: PathBuf,
+    /// Path relative to the working directory when possible
+    display: String,
+    lines: Vec<Line<'static>>,
+    approved: bool,
+}
+
+impl PatchReview {
+    pub(crate) fn new(mut changes: Vec<(PathBuf, ApplyPatchFileChange)>) -> Self {
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let files = changes
+            .into_iter()
+            .map(|(path, change)| ReviewFile {
+                display: path
+                    .strip_prefix(&cwd)
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string(),
+                lines: diff_lines(&path, &change),
+                path,
+                approved: true,
+            })
+            .collect();
+        Self {
+            files,
+            selected: 0,
+            scroll: 0,
+            page: Cell::new(10),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub(crate) fn approved_count(&self) -> usize {
+        self.files.iter().filter(|f| f.approved).count()
+    }
+
+    /// Files the user kept in the patch.
+    pub(crate) fn approved_files(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|f| f.approved)
+            .map(|f| f.path.clone())
+            .collect()
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key: KeyEvent) {
+        let page = self.page.get().max(1);
+        match (key.code, key.modifiers) {
+            (KeyCode::Tab | KeyCode::Right | KeyCode::Char(']' | 'l'), _) => self.select_file(1),
+            (KeyCode::BackTab | KeyCode::Left | KeyCode::Char('[' | 'h'), _) => {
+                self.select_file(-1)
+            }
+            (KeyCode::Char(' '), _) => {
+                if let Some(file) = self.files.get_mut(self.selected) {
+                    file.approved = !file.approved;
+                }
+            }
+            (KeyCode::Char('a'), _) => self.set_approved(true),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) | (KeyCode::PageDown, _) => {
+                self.scroll_by(page as isize)
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) | (KeyCode::PageUp, _) => {
+                self.scroll_by(-(page as isize))
+            }
+            (KeyCode::Char('d'), _) => self.set_approved(false),
+            (KeyCode::Down | KeyCode::Char('j'), _) => self.scroll_by(1),
+            (KeyCode::Up | KeyCode::Char('k'), _) => self.scroll_by(-1),
+            (KeyCode::Home | KeyCode::Char('g'), _) => self.scroll = 0,
+            (KeyCode::End | KeyCode::Char('G'), _) => self.scroll = self.max_scroll(),
+            _ => {}
+        }
+    }
+
+    fn select_file(&mut self, delta: isize) {
+        if self.files.is_empty() {
+            return;
+        }
+        let len = self.files.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        self.scroll = 0;
+    }
+
+    fn set_approved(&mut self, approved: bool) {
+        if let Some(file) = self.files.get_mut(self.selected) {
+            file.approved = approved;
+        }
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.files
+            .get(self.selected)
+            .map_or(0, |f| f.lines.len().saturating_sub(self.page.get()))
+    }
+
+    pub(crate) fn scroll_by(&mut self, delta: isize) {
+        let scroll = self.scroll as isize + delta;
+        self.scroll = (scroll.max(0) as usize).min(self.max_scroll());
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let longest = self
+            .files
+            .iter()
+            .map(|f| f.display.chars().count() as u16 + 2)
+            .max()
+            .unwrap_or(0);
+        let list_width = longest.min(MAX_FILE_LIST_WIDTH).min(area.width / 3);
+        let [list_area, diff_area] =
+            Layout::horizontal([Constraint::Length(list_width), Constraint::Min(1)]).areas(area);
+
+        // File list; keep the selected file in view
+        let height = list_area.height as usize;
+        let offset = (self.selected + 1).saturating_sub(height);
+        let rows: Vec<Line> = self
+            .files
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, f)| {
+                let (mark, color) = if f.approved {
+                    ("✓ ", Color::Green)
+                } else {
+                    ("✗ ", Color::Red)
+                };
+                let mut style = Style::default();
+                if i == self.selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(vec![
+                    Span::styled(mark, Style::default().fg(color)),
+                    Span::styled(f.display.clone(), style),
+                ])
+            })
+            .collect();
+        Paragraph::new(rows).render(list_area, buf);
+
+        // Diff of the selected file
+        let block = Block::default().borders(Borders::LEFT);
+        let inner = block.inner(diff_area);
+        block.render(diff_area, buf);
+        self.page.set(inner.height as usize);
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+        let scroll = self.scroll.min(self.max_scroll());
+        let visible: Vec<Line> = file
+            .lines
+            .iter()
+            .skip(scroll)
+            .take(inner.height as usize)
+            .cloned()
+            .collect();
+        Paragraph::new(visible).render(inner, buf);
+    }
+}
+
+/// Styled diff lines for one file of the patch.
+fn diff_lines(path: &Path, change: &ApplyPatchFileChange) -> Vec<Line<'static>> {
+    let markdown = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "markdown")
+    );
+    let added = Style::default().fg(Color::Green);
+    let removed = Style::default().fg(Color::Red);
+    match change {
+        ApplyPatchFileChange::Add { content } => {
+            let mut lines = vec![Line::styled("new file", added.add_modifier(Modifier::BOLD))];
+            lines.extend(content.lines().map(|l| diff_line("+", l, markdown, added)));
+            lines
+        }
+        ApplyPatchFileChange::Delete => vec![Line::styled(
+            "file will be deleted",
+            removed.add_modifier(Modifier::BOLD),
+        )],
+        ApplyPatchFileChange::Update {
+            unified_diff,
+            move_path,
+            ..
+        } => {
+            let mut lines = Vec::new();
+            if let Some(dest) = move_path {
+                lines.push(Line::styled(
+                    format!("→ move to {}", dest.display()),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            for l in unified_diff.lines() {
+                let line = if l.starts_with("@@") {
+                    Line::styled(l.to_string(), Style::default().fg(Color::Cyan))
+                } else if let Some(rest) = l.strip_prefix('+') {
+                    diff_line("+", rest, markdown, added)
+                } else if let Some(rest) = l.strip_prefix('-') {
+                    diff_line("-", rest, markdown, removed)
+                } else if let Some(rest) = l.strip_prefix(' ') {
+                    diff_line(" ", rest, markdown, Style::default())
+                } else {
+                    Line::styled(l.to_string(), Style::default().add_modifier(Modifier::DIM))
+                };
+                lines.push(line);
+            }
+            lines
+        }
+    }
+}
+
+fn diff_line(sign: &'static str, text: &str, markdown: bool, base: Style) -> Line<'static> {
+    let mut spans = vec![Span::styled(sign, base)];
+    spans.extend(highlight(text, markdown, base));
+    Line::from(spans)
+}
+
+/// Light syntax highlighting on top of the diff colour: Markdown headings,
+/// inline code and comments. Added and removed lines keep their colour.
+fn highlight(text: &str, markdown: bool, base: Style) -> Vec<Span<'static>> {
+    let trimmed = text.trim_start();
+    let plain = base.fg.is_none();
+    let comment = if markdown {
+        trimmed.starts_with("<!--")
+    } else {
+        trimmed.starts_with("//") || trimmed.starts_with('#')
+    };
+    if comment {
+        return vec![Span::styled(
+            text.to_string(),
+            base.add_modifier(Modifier::DIM),
+        )];
+    }
+    if !markdown {
+        return vec![Span::styled(text.to_string(), base)];
+    }
+    if trimmed.starts_with('#') {
+        let heading = if plain { base.fg(Color::Magenta) } else { base };
+        return vec![Span::styled(
+            text.to_string(),
+            heading.add_modifier(Modifier::BOLD),
+        )];
+    }
+    let code = if plain {
+        base.fg(Color::Yellow)
+    } else {
+        base.add_modifier(Modifier::ITALIC)
+    };
+    text.split('`')
+        .enumerate()
+        .filter(|(_, part)| !part.is_empty())
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                Span::styled(format!("`{part}`"), code)
+            } else {
+                Span::styled(part.to_string(), base)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn files_can_be_rejected_individually() {
+        let mut review = PatchReview::new(vec![
+            (
+                PathBuf::from("/deck/b.md"),
+                ApplyPatchFileChange::Add {
+                    content: "# Title\n".to_string(),
+                },
+            ),
+            (
+                PathBuf::from("/deck/a.md"),
+                ApplyPatchFileChange::Update {
+                    unified_diff: "@@ -1 +1 @@\n-old\n+new\n".to_string(),
+                    move_path: None,
+                    new_content: "new\n".to_string(),
+                },
+            ),
+        ]);
+        assert_eq!(review.len(), 2);
+        assert_eq!(review.approved_count(), 2);
+
+        review.handle_key_event(key(KeyCode::Tab));
+        review.handle_key_event(key(KeyCode::Char(' ')));
+        assert_eq!(review.approved_files(), vec![PathBuf::from("/deck/a.md")]);
+
+        // Wraps around to the first file
+        review.handle_key_event(key(KeyCode::Tab));
+        review.handle_key_event(key(KeyCode::Char('d')));
+        assert_eq!(review.approved_count(), 0);
+        review.handle_key_event(key(KeyCode::Char('a')));
+        assert_eq!(review.approved_files(), vec![PathBuf::from("/deck/a.md")]);
+
+        let lines = &review.files[0].lines;
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Red));
+        assert_eq!(lines[2].spans[0].style.fg, Some(Color::Green));
+    }
+}
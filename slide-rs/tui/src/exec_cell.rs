@@ -0,0 +1,183 @@
+//! Live output of a running shell command, shown above the composer.
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use slide_core::codex::ExecOutputStream;
+
+/// Output lines tailed while the cell is expanded
+const TAIL_LINES: usize = 8;
+/// Output lines kept in the transcript once the command finished
+const SUMMARY_LINES: usize = 5;
+
+pub(crate) struct ExecCell {
+    call_id: String,
+    command: Vec<String>,
+    lines: Vec<(ExecOutputStream, String)>,
+    /// Output after the last newline, per stream
+    partial: [String; 2],
+    exit_code: Option<i32>,
+    expanded: bool,
+}
+
+impl ExecCell {
+    pub(crate) fn new(call_id: String, command: Vec<String>) -> Self {
+        Self {
+            call_id,
+            command,
+            lines: Vec::new(),
+            partial: [String::new(), String::new()],
+            exit_code: None,
+            expanded: true,
+        }
+    }
+
+    pub(crate) fn call_id(&self) -> &str {
+        &self.call_id
+    }
+
+    pub(crate) fn push_output(&mut self, stream: ExecOutputStream, chunk: &str) {
+        let partial = &mut self.partial[stream_index(stream)];
+        partial.push_str(chunk);
+        while let Some(pos) = partial.find('\n') {
+            let line: String = partial.drain(..=pos).collect();
+            let line = line.trim_end_matches(['\n', '\r']).to_string();
+            self.lines.push((stream, line));
+        }
+    }
+
+    pub(crate) fn finish(&mut self, exit_code: i32) {
+        for stream in [ExecOutputStream::Stdout, ExecOutputStream::Stderr] {
+            let rest = std::mem::take(&mut self.partial[stream_index(stream)]);
+            if !rest.is_empty() {
+                self.lines.push((stream, rest));
+            }
+        }
+        self.exit_code = Some(exit_code);
+    }
+
+    pub(crate) fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    pub(crate) fn desired_height(&self) -> u16 {
+        if !self.expanded {
+            return 1;
+        }
+        let shown = self.lines.len().min(TAIL_LINES);
+        let indicator = usize::from(self.lines.len() > TAIL_LINES);
+        (1 + indicator + shown) as u16
+    }
+
+    fn header(&self) -> Line<'static> {
+        let status = match self.exit_code {
+            None => Span::styled("running…", Style::default().fg(Color::Yellow)),
+            Some(0) => Span::styled("exit 0", Style::default().fg(Color::Green)),
+            Some(code) => Span::styled(format!("exit {code}"), Style::default().fg(Color::Red)),
+        };
+        let mut spans = vec![
+            Span::styled("$ ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(self.command.join(" ")),
+            Span::raw("  "),
+            status,
+        ];
+        if !self.expanded {
+            spans.push(Span::styled(
+                format!("  ({} lines, Ctrl+L: expand)", self.lines.len()),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    /// Header, a "N earlier lines" marker when truncated, then the last `n` lines.
+    fn tail(&self, n: usize) -> Vec<Line<'static>> {
+        let mut out = vec![self.header()];
+        let hidden = self.lines.len().saturating_sub(n);
+        if hidden > 0 {
+            out.push(Line::styled(
+                format!("  … {hidden} earlier lines (Ctrl+X: full output)"),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        out.extend(self.lines[hidden..].iter().map(output_line));
+        out
+    }
+
+    /// Summary committed to the transcript when the command ends.
+    pub(crate) fn history_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::from("")];
+        lines.extend(self.tail(SUMMARY_LINES));
+        lines
+    }
+
+    /// Every output line, for the pager.
+    pub(crate) fn full_output(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![self.header()];
+        lines.extend(self.lines.iter().map(output_line));
+        lines
+    }
+
+    /// Output without styling or the command line, for the clipboard.
+    pub(crate) fn output_text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(|(_, line)| line.as_str()).collect();
+        lines.join("\n")
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        let lines = if self.expanded {
+            self.tail(TAIL_LINES)
+        } else {
+            vec![self.header()]
+        };
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+fn stream_index(stream: ExecOutputStream) -> usize {
+    match stream {
+        ExecOutputStream::Stdout => 0,
+        ExecOutputStream::Stderr => 1,
+    }
+}
+
+fn output_line((stream, text): &(ExecOutputStream, String)) -> Line<'static> {
+    let style = match stream {
+        ExecOutputStream::Stdout => Style::default().add_modifier(Modifier::DIM),
+        ExecOutputStream::Stderr => Style::default().fg(Color::Red),
+    };
+    Line::from(vec![Span::raw("  "), Span::styled(text.clone(), style)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_split_into_lines_and_tailed() {
+        let mut cell = ExecCell::new("call".to_string(), vec!["make".to_string()]);
+        for i in 0..10 {
+            cell.push_output(ExecOutputStream::Stdout, &format!("line {i}\n"));
+        }
+        cell.push_output(ExecOutputStream::Stderr, "warn");
+        assert_eq!(cell.lines.len(), 10);
+        assert_eq!(cell.desired_height() as usize, 2 + TAIL_LINES);
+
+        cell.finish(1);
+        assert_eq!(cell.lines.len(), 11);
+        let summary = cell.history_lines();
+        // blank, header, truncation marker, last SUMMARY_LINES lines
+        assert_eq!(summary.len(), 3 + SUMMARY_LINES);
+        assert_eq!(
+            summary[2].spans[0].content,
+            "  … 6 earlier lines (Ctrl+X: full output)"
+        );
+        assert_eq!(cell.full_output().len(), 12);
+
+        cell.toggle_expanded();
+        assert_eq!(cell.desired_height(), 1);
+    }
+}
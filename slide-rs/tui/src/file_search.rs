@@ -0,0 +1,170 @@
+//! Background file search for `@` mentions in the composer and for the
+//! "open file" popup, which ranks decks (`.md`) above other files.
+//!
+//! With the workspace file watcher running, the files are listed once into
+//! a [`FileIndex`] kept current from its change events; otherwise every
+//! query walks the directory.
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use slide_core::file_watcher::FsChange;
+use slide_file_search::FileIndex;
+
+use crate::app_event_sender::{AppEvent, AppEventSender};
+use crate::bottom_pane::file_search_popup::FileMatch;
+
+const MAX_FILE_SEARCH_RESULTS: usize = 8;
+/// Matches fetched for the open popup before decks are moved to the front;
+/// more than it shows so a deck ranked a little lower still makes it
+const MAX_OPEN_SEARCH_RESULTS: usize = 64;
+const NUM_FILE_SEARCH_THREADS: usize = 2;
+
+/// Runs one search at a time; a new query cancels the previous one.
+pub(crate) struct FileSearchManager {
+    search_dir: PathBuf,
+    app_tx: AppEventSender,
+    cancel: Arc<AtomicBool>,
+    /// Files of `search_dir`, listed by the first query and kept current
+    /// from file watcher events; `None` without a watcher
+    index: Option<Arc<Mutex<Option<FileIndex>>>>,
+}
+
+impl FileSearchManager {
+    pub(crate) fn new(search_dir: PathBuf, app_tx: AppEventSender) -> Self {
+        Self {
+            search_dir,
+            app_tx,
+            cancel: Arc::new(AtomicBool::new(false)),
+            index: None,
+        }
+    }
+
+    /// Keep an index of the files, updated with [`Self::on_fs_change`].
+    pub(crate) fn with_index(mut self) -> Self {
+        self.index = Some(Arc::new(Mutex::new(None)));
+        self
+    }
+
+    /// Apply file system changes to the index; a batch of unknown changes
+    /// drops it so the next query lists the files again.
+    pub(crate) fn on_fs_change(&mut self, change: &FsChange) {
+        let Some(index) = &self.index else {
+            return;
+        };
+        let Ok(mut index) = index.lock() else {
+            return;
+        };
+        if change.overflow {
+            *index = None;
+        } else if let Some(index) = index.as_mut() {
+            index.update(&change.paths);
+        }
+    }
+
+    /// Search for an `@` mention; results arrive as
+    /// [`AppEvent::FileSearchResult`].
+    pub(crate) fn on_user_query(&mut self, query: String) {
+        self.search(query, false);
+    }
+
+    /// Search for the open popup; results arrive as
+    /// [`AppEvent::OpenFileSearchResult`], decks first.
+    pub(crate) fn on_open_query(&mut self, query: String) {
+        self.search(query, true);
+    }
+
+    fn search(&mut self, query: String, open: bool) {
+        self.cancel.store(true, Ordering::Relaxed);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = cancel.clone();
+
+        let search_dir = self.search_dir.clone();
+        let app_tx = self.app_tx.clone();
+        let index = self.index.clone();
+        tokio::task::spawn_blocking(move || {
+            let max_results = if open {
+                MAX_OPEN_SEARCH_RESULTS
+            } else {
+                MAX_FILE_SEARCH_RESULTS
+            };
+            let (Some(limit), Some(threads)) = (
+                NonZero::new(max_results),
+                NonZero::new(NUM_FILE_SEARCH_THREADS),
+            ) else {
+                return;
+            };
+            let results = match index.as_ref().and_then(|index| index.lock().ok()) {
+                Some(mut index) => Ok(index
+                    .get_or_insert_with(|| FileIndex::build(&search_dir))
+                    .search(&query, limit, true)),
+                None => slide_file_search::run(
+                    &query,
+                    limit,
+                    &search_dir,
+                    Vec::new(),
+                    threads,
+                    cancel.clone(),
+                    true,
+                ),
+            };
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let mut matches: Vec<FileMatch> = results
+                .map(|r| {
+                    r.matches
+                        .into_iter()
+                        .map(|m| FileMatch {
+                            path: m.path,
+                            indices: m
+                                .indices
+                                .map(|idx| idx.into_iter().map(|i| i as usize).collect()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if open {
+                markdown_first(&mut matches);
+                app_tx.send(AppEvent::OpenFileSearchResult { query, matches });
+            } else {
+                app_tx.send(AppEvent::FileSearchResult { query, matches });
+            }
+        });
+    }
+}
+
+/// Move decks to the front, keeping the match order within decks and
+/// within other files.
+fn markdown_first(matches: &mut [FileMatch]) {
+    matches.sort_by_key(|m| !is_markdown(&m.path));
+}
+
+fn is_markdown(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decks_come_first_in_match_order() {
+        let mut matches: Vec<FileMatch> = ["src/talk.rs", "slides/talk.md", "talk.txt", "TALK.MD"]
+            .into_iter()
+            .map(|path| FileMatch {
+                path: path.to_string(),
+                indices: None,
+            })
+            .collect();
+        markdown_first(&mut matches);
+        let paths: Vec<&str> = matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["slides/talk.md", "TALK.MD", "src/talk.rs", "talk.txt"]
+        );
+    }
+}
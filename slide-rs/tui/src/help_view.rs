@@ -0,0 +1,152 @@
+//! Contents of the help overlay (Ctrl+H, `/help`), built from the keymap,
+//! the slash command registry and the session state each time it opens.
+//! Shown in the transcript pager, which provides `/` search.
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use slide_common::i18n::{t, Msg};
+use slide_core::custom_prompts::{prompt_usage, CustomPrompt};
+
+use crate::keymap::{key_label, COMPOSER_KEYS, CONTEXT_KEYS, GLOBAL_KEYS, VIM_KEYS};
+use crate::slash_command::SlashCommandRegistry;
+
+/// Width of the key/command column
+const KEY_COLUMN: usize = 26;
+
+pub(crate) struct HelpContext<'a> {
+    pub commands: &'a SlashCommandRegistry,
+    pub prompts: &'a [CustomPrompt],
+    /// Model, approval and sandbox policy, working directory
+    pub session: Vec<String>,
+    /// The composer uses the Vim keymap
+    pub vim: bool,
+}
+
+pub(crate) fn help_lines(ctx: &HelpContext) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    if !ctx.session.is_empty() {
+        section(&mut lines, Msg::HelpSession);
+        lines.extend(
+            ctx.session
+                .iter()
+                .map(|fact| Line::from(format!("  {fact}"))),
+        );
+    }
+
+    section(&mut lines, Msg::HelpKeys);
+    for binding in GLOBAL_KEYS {
+        entry(
+            &mut lines,
+            key_label(binding.code, binding.modifiers),
+            t(binding.action.description()),
+        );
+    }
+    for (keys, msg) in CONTEXT_KEYS {
+        entry(&mut lines, keys.to_string(), t(*msg));
+    }
+
+    section(&mut lines, Msg::HelpComposer);
+    for (keys, msg) in COMPOSER_KEYS {
+        entry(&mut lines, keys.to_string(), t(*msg));
+    }
+    if ctx.vim {
+        section(&mut lines, Msg::HelpVim);
+        for (keys, msg) in VIM_KEYS {
+            entry(&mut lines, keys.to_string(), t(*msg));
+        }
+    }
+
+    section(&mut lines, Msg::HelpCommands);
+    for command in ctx.commands.commands() {
+        let head = format!("/{} {}", command.name(), command.usage());
+        entry(
+            &mut lines,
+            head.trim_end().to_string(),
+            command.description(),
+        );
+    }
+    if !ctx.prompts.is_empty() {
+        section(&mut lines, Msg::HelpPrompts);
+        for prompt in ctx.prompts {
+            let head = format!("/{} {}", prompt.name, prompt_usage(prompt));
+            let description = prompt.description.as_deref().unwrap_or_default();
+            entry(&mut lines, head.trim_end().to_string(), description);
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        t(Msg::HelpSearchHint),
+        Style::default().add_modifier(Modifier::DIM),
+    ));
+    lines
+}
+
+fn section(lines: &mut Vec<Line<'static>>, title: Msg) {
+    if !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::styled(
+        t(title),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ));
+}
+
+fn entry(lines: &mut Vec<Line<'static>>, keys: String, description: &str) {
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!("  {keys:<KEY_COLUMN$}"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(" {description}")),
+    ]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lists_keys_commands_prompts_and_session() {
+        let commands = SlashCommandRegistry::default();
+        let prompts = vec![CustomPrompt {
+            name: "review".to_string(),
+            path: PathBuf::from("review.md"),
+            content: "Review {{file}}".to_string(),
+            description: Some("Review a file".to_string()),
+            args: vec!["file".to_string()],
+        }];
+        let ctx = HelpContext {
+            commands: &commands,
+            prompts: &prompts,
+            session: vec!["approval: on-request".to_string()],
+            vim: false,
+        };
+        let lines = text(&help_lines(&ctx));
+        let has = |needle: &str| lines.iter().any(|line| line.contains(needle));
+
+        assert_eq!(lines[0], "Session");
+        assert!(has("approval: on-request"));
+        assert!(has("Ctrl+H") && has("Alt+C"));
+        assert!(has("/model <model>") && has("switch the model"));
+        assert!(has("/review <file>") && has("Review a file"));
+        assert!(!has("Vim"));
+        assert_eq!(
+            commands
+                .commands()
+                .iter()
+                .filter(|c| has(&format!("/{}", c.name())))
+                .count(),
+            commands.commands().len()
+        );
+    }
+}
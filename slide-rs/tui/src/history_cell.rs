@@ -0,0 +1,216 @@
+//! Entries of the session transcript. Each cell keeps what it shows rather
+//! than pre-styled text, so it renders itself again (at another width or in
+//! another style) and gives its plain text for copying and saving.
+use std::path::PathBuf;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::exec_cell::ExecCell;
+use crate::insert_history::word_wrap_lines;
+use crate::markdown::render_markdown;
+use crate::plan_cell::PlanCell;
+use slide_common::i18n::{t, tf, Msg};
+use slide_core::codex::DiffStat;
+
+pub(crate) enum HistoryCell {
+    UserMessage {
+        text: String,
+        attachments: Vec<PathBuf>,
+    },
+    /// Reply of the agent; streamed deltas are appended until another cell
+    /// follows
+    AgentMessage {
+        text: String,
+    },
+    /// A finished command
+    Exec(ExecCell),
+    /// Changes the agent made during a turn
+    Diff {
+        diff: String,
+    },
+    Error {
+        message: String,
+    },
+    /// Lines a patch added and removed, per file as shown and in total
+    PatchStat {
+        files: Vec<(String, DiffStat)>,
+        total: DiffStat,
+    },
+    /// A plan whose steps are all done
+    Plan(PlanCell),
+    /// `/undo-turn` put these files back as they were before the turn
+    TurnReverted {
+        files: Vec<String>,
+    },
+}
+
+impl HistoryCell {
+    pub(crate) fn user(text: String, attachments: Vec<PathBuf>) -> Self {
+        Self::UserMessage { text, attachments }
+    }
+
+    /// Lines inserted into the transcript, starting with a blank separator.
+    pub(crate) fn display_lines(&self) -> Vec<Line<'static>> {
+        match self {
+            HistoryCell::UserMessage { text, attachments } => {
+                let mut lines = vec![Line::from(""), heading("You", Color::Cyan)];
+                lines.extend(text.lines().map(|l| Line::from(l.to_string())));
+                lines.extend(attachments.iter().map(|path| {
+                    Line::from(Span::styled(
+                        format!("📎 {}", path.display()),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ))
+                }));
+                lines
+            }
+            HistoryCell::AgentMessage { text } => std::iter::once(Line::from(""))
+                .chain(render_markdown(text))
+                .collect(),
+            HistoryCell::Exec(cell) => cell.history_lines(),
+            HistoryCell::Diff { diff } => {
+                let mut lines = vec![Line::from(""), heading("Diff", Color::Blue)];
+                lines.extend(diff.lines().map(|l| {
+                    let style = match l.chars().next() {
+                        Some('+') if !l.starts_with("+++") => Style::default().fg(Color::Green),
+                        Some('-') if !l.starts_with("---") => Style::default().fg(Color::Red),
+                        Some('@') => Style::default().fg(Color::Cyan),
+                        _ => Style::default(),
+                    };
+                    Line::styled(l.to_string(), style)
+                }));
+                lines
+            }
+            HistoryCell::Error { message } => {
+                let mut lines = vec![Line::from(""), heading("Error", Color::Red)];
+                lines.extend(message.lines().map(|l| Line::from(l.to_string())));
+                lines
+            }
+            HistoryCell::PatchStat { files, total } => {
+                let summary = Span::styled(
+                    format!("{}, ", patch_summary(files.len())),
+                    Style::default().add_modifier(Modifier::BOLD),
+                );
+                let mut lines = vec![Line::from(""), patch_stat_line(summary, *total)];
+                lines.extend(files.iter().map(|(path, stat)| {
+                    let path = Span::styled(
+                        format!("  {path}  "),
+                        Style::default().add_modifier(Modifier::DIM),
+                    );
+                    patch_stat_line(path, *stat)
+                }));
+                lines
+            }
+            HistoryCell::Plan(cell) => cell.history_lines(),
+            HistoryCell::TurnReverted { files } => {
+                let mut lines = vec![Line::from(""), heading("Reverted turn", Color::Yellow)];
+                lines.extend(files.iter().map(|f| Line::from(format!("  {f}"))));
+                lines
+            }
+        }
+    }
+
+    /// Rows the cell takes at `width`, wrapped like the transcript.
+    pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        word_wrap_lines(&self.display_lines(), width).len() as u16
+    }
+
+    /// The cell as unstyled text, without the blank separator.
+    pub(crate) fn plain_text(&self) -> String {
+        match self {
+            HistoryCell::UserMessage { text, .. } | HistoryCell::AgentMessage { text } => {
+                text.clone()
+            }
+            HistoryCell::Diff { diff } => diff.clone(),
+            HistoryCell::Error { message } => message.clone(),
+            HistoryCell::Exec(_)
+            | HistoryCell::PatchStat { .. }
+            | HistoryCell::Plan(_)
+            | HistoryCell::TurnReverted { .. } => self
+                .display_lines()
+                .iter()
+                .skip(1)
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// `3 files changed`, in the UI language.
+fn patch_summary(files: usize) -> String {
+    match files {
+        1 => t(Msg::PatchFileChanged).to_string(),
+        count => tf(Msg::PatchFilesChanged, &[("count", &count)]),
+    }
+}
+
+/// `label` followed by `+42 −7`, colored like diff lines.
+fn patch_stat_line(label: Span<'static>, stat: DiffStat) -> Line<'static> {
+    Line::from(vec![
+        label,
+        Span::styled(
+            format!("+{}", stat.added),
+            Style::default().fg(Color::Green),
+        ),
+        Span::raw(" "),
+        Span::styled(
+            format!("−{}", stat.removed),
+            Style::default().fg(Color::Red),
+        ),
+    ])
+}
+
+fn heading(title: &str, color: Color) -> Line<'static> {
+    Line::from(Span::styled(
+        title.to_string(),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_render_and_copy_without_styling() {
+        let user = HistoryCell::user(
+            "make a deck\nabout rust".into(),
+            vec![PathBuf::from("notes.md")],
+        );
+        assert_eq!(user.display_lines().len(), 5);
+        assert_eq!(user.plain_text(), "make a deck\nabout rust");
+        // The blank separator and heading take a row each at any width
+        assert_eq!(user.desired_height(80), 5);
+        assert_eq!(
+            HistoryCell::AgentMessage {
+                text: "a reply that wraps".into()
+            }
+            .desired_height(8),
+            4
+        );
+
+        let mut cell = ExecCell::new("call".into(), vec!["ls".into()]);
+        cell.push_output(slide_core::codex::ExecOutputStream::Stdout, "deck.md\n");
+        cell.finish(0);
+        assert_eq!(
+            HistoryCell::Exec(cell).plain_text(),
+            "$ ls  exit 0\n  deck.md"
+        );
+
+        let stat = |added, removed| DiffStat { added, removed };
+        let patch = HistoryCell::PatchStat {
+            files: vec![
+                ("slides/talk.md".into(), stat(40, 5)),
+                ("notes.md".into(), stat(2, 2)),
+            ],
+            total: stat(42, 7),
+        };
+        assert_eq!(
+            patch.plain_text(),
+            "2 files changed, +42 −7\n  slides/talk.md  +40 −5\n  notes.md  +2 −2"
+        );
+    }
+}
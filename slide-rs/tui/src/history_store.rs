@@ -18,9 +18,21 @@ impl HistoryStore {
         let mut path = home_dir();
         path.push(".slide");
         path.push("history.jsonl");
+        Self::new(path)
+    }
+
+    pub fn new(path: PathBuf) -> Self {
         Self { path }
     }
 
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn enabled(&self) -> bool {
+        slide_common::privacy::active().history_enabled()
+    }
+
     /// Ensure parent directory exists.
     fn ensure_parent_dir(&self) -> std::io::Result<()> {
         if let Some(parent) = self.path.parent() {
@@ -31,6 +43,9 @@ impl HistoryStore {
 
     /// Append a text entry as a single JSON line. Best-effort; errors are returned.
     pub fn append(&self, text: &str) -> std::io::Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
         self.ensure_parent_dir()?;
         let ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -53,6 +68,9 @@ impl HistoryStore {
     pub fn metadata(&self) -> (u64, usize) {
         let mut id = 0u64;
         let mut count = 0usize;
+        if !self.enabled() {
+            return (id, count);
+        }
         match std::fs::metadata(&self.path) {
             Ok(meta) => {
                 #[cfg(unix)]
@@ -79,44 +97,36 @@ impl HistoryStore {
         (id, count)
     }
 
-    /// Lookup the `offset`-th entry by counting lines; validate `log_id` on Unix.
-    pub fn lookup(&self, log_id: u64, offset: usize) -> Option<String> {
-        // Validate id on Unix
+    /// All entries, oldest first; empty when `log_id` no longer matches.
+    pub fn entries(&self, log_id: u64) -> Vec<String> {
         #[cfg(unix)]
         {
-            if let Ok(meta) = std::fs::metadata(&self.path) {
-                if meta.ino() != log_id {
-                    return None;
-                }
-            } else {
-                return None;
+            match std::fs::metadata(&self.path) {
+                Ok(meta) if meta.ino() == log_id => {}
+                _ => return Vec::new(),
             }
         }
-        let f = OpenOptions::new().read(true).open(&self.path).ok()?;
-        let reader = std::io::BufReader::new(f);
-        for (idx, line_res) in reader.lines().enumerate() {
-            let line = line_res.ok()?;
-            if idx == offset {
-                // Parse minimal {"text":...}
-                if let Some(txt) = extract_text_field(&line) {
-                    return Some(txt);
-                } else {
-                    return None;
-                }
-            }
-        }
-        None
+        #[cfg(not(unix))]
+        let _ = log_id;
+        let Ok(f) = std::fs::File::open(&self.path) else {
+            return Vec::new();
+        };
+        std::io::BufReader::new(f)
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| extract_text_field(&line).unwrap_or_default())
+            .collect()
     }
 }
 
-fn home_dir() -> PathBuf {
+pub(crate) fn home_dir() -> PathBuf {
     if let Ok(h) = std::env::var("HOME") {
         return PathBuf::from(h);
     }
     std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
 }
 
-fn json_escape(s: &str) -> String {
+pub(crate) fn json_escape(s: &str) -> String {
     let mut out = String::with_capacity(s.len() + 2);
     out.push('"');
     for ch in s.chars() {
@@ -135,9 +145,14 @@ fn json_escape(s: &str) -> String {
 }
 
 fn extract_text_field(line: &str) -> Option<String> {
-    // Extremely small extractor to avoid JSON deps: find "text":"..."
-    let key = "\"text\":";
-    let idx = line.find(key)? + key.len();
+    extract_string_field(line, "text")
+}
+
+/// String value of `"field":"..."` in a JSON line written by [`json_escape`].
+pub(crate) fn extract_string_field(line: &str, field: &str) -> Option<String> {
+    // Extremely small extractor to avoid JSON deps: find "field":"..."
+    let key = format!("\"{field}\":");
+    let idx = line.find(&key)? + key.len();
     let rest = line[idx..].trim_start();
     if !rest.starts_with('"') {
         return None;
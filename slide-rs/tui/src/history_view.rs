@@ -0,0 +1,236 @@
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use slide_common::versions::{diff_stat, unified_diff, DeckVersion, VersionStore};
+use std::io;
+use std::path::PathBuf;
+use tokio::time::{sleep, Duration};
+
+/// Full-screen list of deck snapshots with a diff against the current deck.
+pub struct HistoryView {
+    deck: PathBuf,
+    store: VersionStore,
+    /// Newest first
+    versions: Vec<DeckVersion>,
+    current: String,
+    selected: usize,
+    diff_scroll: u16,
+    /// Restore is armed by the first `r` and performed by the second
+    confirm_restore: bool,
+    status: String,
+    should_quit: bool,
+}
+
+impl HistoryView {
+    pub fn new(deck: PathBuf, store: VersionStore) -> Result<Self> {
+        let mut view = Self {
+            deck,
+            store,
+            versions: Vec::new(),
+            current: String::new(),
+            selected: 0,
+            diff_scroll: 0,
+            confirm_restore: false,
+            status: String::new(),
+            should_quit: false,
+        };
+        view.reload()?;
+        Ok(view)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        self.versions = self.store.list(&self.deck)?;
+        self.versions.reverse();
+        self.current = std::fs::read_to_string(&self.deck).unwrap_or_default();
+        self.selected = self.selected.min(self.versions.len().saturating_sub(1));
+        Ok(())
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        loop {
+            terminal.draw(|f| self.ui(f))?;
+
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key_event(key);
+                    }
+                }
+            }
+
+            if self.should_quit {
+                break;
+            }
+
+            sleep(Duration::from_millis(16)).await;
+        }
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        let restore_armed = std::mem::take(&mut self.confirm_restore);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.versions.len() => {
+                self.selected += 1;
+                self.diff_scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.selected > 0 => {
+                self.selected -= 1;
+                self.diff_scroll = 0;
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.diff_scroll = self.diff_scroll.saturating_add(10);
+            }
+            KeyCode::PageUp => {
+                self.diff_scroll = self.diff_scroll.saturating_sub(10);
+            }
+            KeyCode::Char('r') => {
+                let Some(version) = self.versions.get(self.selected).cloned() else {
+                    return;
+                };
+                if !restore_armed {
+                    self.confirm_restore = true;
+                    self.status = format!("Press r again to restore {}", version.label());
+                    return;
+                }
+                self.status = match self
+                    .store
+                    .restore(&self.deck, &version)
+                    .and_then(|_| self.reload())
+                {
+                    Ok(()) => format!("Restored {}", version.label()),
+                    Err(e) => format!("Restore failed: {e}"),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn ui(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(f.area());
+
+        let header = Paragraph::new(format!(
+            "Deck History: {} ({} versions)",
+            self.deck.display(),
+            self.versions.len()
+        ))
+        .style(Style::default().fg(Color::Cyan))
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(header, chunks[0]);
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(34), Constraint::Min(0)])
+            .split(chunks[1]);
+
+        let items: Vec<ListItem> = self
+            .versions
+            .iter()
+            .map(|v| {
+                let content = v.read().unwrap_or_default();
+                let label = if content == self.current {
+                    format!("{}  current", v.label())
+                } else {
+                    let (added, removed) = diff_stat(&content, &self.current);
+                    format!("{}  +{added} -{removed}", v.label())
+                };
+                ListItem::new(label)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Versions"))
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        let mut state = ListState::default();
+        if !self.versions.is_empty() {
+            state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(list, body[0], &mut state);
+
+        let diff_text = match self.versions.get(self.selected) {
+            None => {
+                Text::from("No snapshots yet. Versions are saved when the agent patches a deck.")
+            }
+            Some(v) => {
+                let old = v.read().unwrap_or_default();
+                if old == self.current {
+                    Text::from("Identical to the current deck")
+                } else {
+                    Text::from(
+                        unified_diff(&old, &self.current)
+                            .lines()
+                            .map(diff_line)
+                            .collect::<Vec<_>>(),
+                    )
+                }
+            }
+        };
+        let diff = Paragraph::new(diff_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Diff (selected → current)"),
+            )
+            .scroll((self.diff_scroll, 0));
+        f.render_widget(diff, body[1]);
+
+        let controls = if self.status.is_empty() {
+            "↑/↓ or j/k: select | PgUp/PgDn: scroll | r: restore | q: quit".to_string()
+        } else {
+            self.status.clone()
+        };
+        let footer = Paragraph::new(controls)
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(footer, chunks[2]);
+    }
+}
+
+fn diff_line(line: &str) -> Line<'static> {
+    let style = if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::Green)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    Line::styled(line.to_string(), style)
+}
@@ -48,6 +48,7 @@ pub fn insert_history_lines_to_writer<B, W>(
     // Pre-wrap lines using word-aware wrapping so terminal scrollback sees the same
     // formatting as the TUI. This avoids character-level hard wrapping by the terminal.
     let wrapped = word_wrap_lines(&lines, area.width.max(1));
+    terminal.record_scrollback(&wrapped);
     let wrapped_lines = wrapped.len() as u16;
     let cursor_top = if area.bottom() < screen_size.height {
         // If the viewport is not at the bottom of the screen, scroll it down to make room.
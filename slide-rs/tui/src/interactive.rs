@@ -1,27 +1,66 @@
 use crate::app::{run_app, AppExit, RunResult};
+use crate::onboarding::LoginScreen;
 use crate::run_preview;
+use crate::session_picker::{SessionChoice, SessionPicker};
+use crate::session_store::SessionStore;
 use anyhow::Result;
+use slide_common::auth::AuthStore;
+use slide_common::WorkspaceState;
+use slide_core::codex::PlanRecord;
+use slide_core::mock_provider::MOCK_PREFIX;
 
-pub struct InteractiveApp {}
+pub struct InteractiveApp {
+    /// Saved plan to continue instead of asking which session to resume
+    plan: Option<PlanRecord>,
+}
 
 impl InteractiveApp {
     pub fn new() -> Self {
-        Self {}
+        Self { plan: None }
+    }
+
+    /// Start by continuing a saved plan.
+    pub fn continuing_plan(record: PlanRecord) -> Self {
+        Self { plan: Some(record) }
     }
 
     pub async fn run(&mut self) -> Result<()> {
-        let mut recent_files: Vec<String> = Vec::new();
+        // First run: offer to log in before anything talks to a model
+        let force_stub = std::env::var("SLIDE_FORCE_STUB")
+            .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        // A scripted mock model needs no key either
+        let mock = std::env::var("SLIDE_MODEL").is_ok_and(|m| m.starts_with(MOCK_PREFIX));
+        if !force_stub && !mock && slide_common::auth::openai_api_key().is_none() {
+            LoginScreen::new(AuthStore::default()).run().await?;
+        }
+
+        let store = SessionStore::default();
+        let mut state = WorkspaceState::load(".");
+
+        // Welcome screen: resume a session or open a deck before chatting
+        let mut resume = None;
+        let mut picker = SessionPicker::new(store, &state.recent_files);
+        if self.plan.is_none() && picker.has_history() {
+            match picker.run().await? {
+                Some(SessionChoice::Resume(id)) => resume = Some(id),
+                Some(SessionChoice::OpenDeck(path)) => {
+                    state.touch(path.display().to_string());
+                    let _ = state.save(".");
+                    run_preview(path).await?;
+                }
+                Some(SessionChoice::New) | None => {}
+            }
+        }
+
         loop {
-            let RunResult {
-                exit,
-                recent_files: recents,
-            } = run_app(recent_files).await?;
-            recent_files = recents;
+            let RunResult { exit, session_id } = run_app(resume.take(), self.plan.take()).await?;
             match exit {
                 AppExit::Quit => break,
                 AppExit::Preview(path) => {
                     // Run preview UI; when it exits, resume interactive loop
                     run_preview(path).await?;
+                    // Pick the conversation up where it was left
+                    resume = Some(session_id);
                 }
             }
         }
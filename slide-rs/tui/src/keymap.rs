@@ -0,0 +1,154 @@
+//! Keys of the chat screen, as shown by the help overlay.
+//!
+//! [`GLOBAL_KEYS`] is also what [`crate::app::App`] dispatches on before a
+//! key reaches the composer, so the overlay lists exactly the shortcuts in
+//! effect. Composer and Vim keys are handled by the text area; their tables
+//! here describe that behaviour.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use slide_common::i18n::Msg;
+
+/// App-wide shortcut, handled before the composer sees the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalAction {
+    ShowHelp,
+    ClearHistory,
+    Transcript,
+    ToggleExecOutput,
+    FullExecOutput,
+    CopyReply,
+    /// Stop the app and return to the shell; `fg` continues it
+    Suspend,
+}
+
+impl GlobalAction {
+    pub fn description(self) -> Msg {
+        match self {
+            GlobalAction::ShowHelp => Msg::KeyShowHelp,
+            GlobalAction::ClearHistory => Msg::KeyClearHistory,
+            GlobalAction::Transcript => Msg::KeyTranscript,
+            GlobalAction::ToggleExecOutput => Msg::KeyToggleExecOutput,
+            GlobalAction::FullExecOutput => Msg::KeyFullExecOutput,
+            GlobalAction::CopyReply => Msg::KeyCopyReply,
+            GlobalAction::Suspend => Msg::KeySuspend,
+        }
+    }
+}
+
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: GlobalAction,
+}
+
+const fn ctrl(c: char, action: GlobalAction) -> KeyBinding {
+    KeyBinding {
+        code: KeyCode::Char(c),
+        modifiers: KeyModifiers::CONTROL,
+        action,
+    }
+}
+
+pub const GLOBAL_KEYS: &[KeyBinding] = &[
+    ctrl('h', GlobalAction::ShowHelp),
+    ctrl('c', GlobalAction::ClearHistory),
+    ctrl('t', GlobalAction::Transcript),
+    ctrl('l', GlobalAction::ToggleExecOutput),
+    ctrl('x', GlobalAction::FullExecOutput),
+    KeyBinding {
+        code: KeyCode::Char('c'),
+        modifiers: KeyModifiers::ALT,
+        action: GlobalAction::CopyReply,
+    },
+    #[cfg(unix)]
+    ctrl('z', GlobalAction::Suspend),
+];
+
+/// The shortcut bound to `key`, if any.
+pub fn global_action(key: &KeyEvent) -> Option<GlobalAction> {
+    GLOBAL_KEYS
+        .iter()
+        .find(|binding| binding.code == key.code && binding.modifiers == key.modifiers)
+        .map(|binding| binding.action)
+}
+
+/// Keys matched with extra conditions in the app (running turn, open
+/// dialog), listed after [`GLOBAL_KEYS`].
+pub const CONTEXT_KEYS: &[(&str, Msg)] = &[
+    ("Esc", Msg::KeyInterrupt),
+    ("Esc / Ctrl+Q", Msg::KeyCloseOrQuit),
+];
+
+/// Keys of the composer with the default (readline) keymap; in Vim
+/// insert mode as well.
+pub const COMPOSER_KEYS: &[(&str, Msg)] = &[
+    ("Enter", Msg::KeySend),
+    ("Shift+Enter / Ctrl+J", Msg::KeyNewline),
+    ("↑/↓ Ctrl+P/N", Msg::KeyHistory),
+    ("Tab", Msg::KeyComplete),
+    ("Ctrl+O", Msg::KeyExpandPaste),
+    ("Ctrl+A / Ctrl+E", Msg::KeyLineStartEnd),
+    ("Ctrl+W / Ctrl+U / Ctrl+K", Msg::KeyKill),
+    ("Ctrl+Y / Alt+Y", Msg::KeyYank),
+    (UNDO_KEYS, Msg::KeyUndoRedo),
+];
+
+/// Ctrl+Z suspends the app where the shell has job control
+#[cfg(unix)]
+const UNDO_KEYS: &str = "Ctrl+_ / Ctrl+Shift+Z";
+#[cfg(not(unix))]
+const UNDO_KEYS: &str = "Ctrl+Z / Ctrl+Shift+Z";
+
+/// Normal and visual mode keys with `editor.keymap = "vim"`.
+pub const VIM_KEYS: &[(&str, Msg)] = &[
+    ("Esc / Ctrl+[", Msg::KeyVimNormal),
+    ("i a I A o O", Msg::KeyVimInsert),
+    ("v", Msg::KeyVimVisual),
+    ("h l w b e 0 ^ $ G", Msg::KeyVimMotion),
+    ("j k", Msg::KeyVimLines),
+    ("d c y x X D C Y", Msg::KeyVimOperators),
+    ("p P u Ctrl+R", Msg::KeyVimPutUndo),
+];
+
+/// `Ctrl+H`, `Alt+C`, ...
+pub fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let mut label = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("Alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        label.push_str("Shift+");
+    }
+    match code {
+        KeyCode::Char(c) => label.push(c.to_ascii_uppercase()),
+        other => label.push_str(&format!("{other:?}")),
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_binding_dispatches_to_its_action() {
+        for binding in GLOBAL_KEYS {
+            let key = KeyEvent::new(binding.code, binding.modifiers);
+            assert_eq!(global_action(&key), Some(binding.action));
+        }
+        let plain = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        assert_eq!(global_action(&plain), None);
+        // Ctrl+Shift+Z stays redo in the composer
+        let redo = KeyEvent::new(
+            KeyCode::Char('z'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert_eq!(global_action(&redo), None);
+        assert_eq!(
+            key_label(KeyCode::Char('c'), KeyModifiers::ALT),
+            "Alt+C".to_string()
+        );
+    }
+}
@@ -3,85 +3,256 @@
 pub mod agent;
 pub mod app;
 pub mod app_event_sender;
+mod attachments;
 pub mod bottom_pane;
+mod clipboard;
+pub mod crash;
 pub mod custom_terminal;
+mod deck_pane;
+mod diff_review;
+mod exec_cell;
+mod file_search;
+mod help_view;
+mod history_cell;
 pub mod history_store;
+pub mod history_view;
 pub mod insert_history;
 pub mod interactive;
+mod keymap;
+mod markdown;
+mod notifier;
+mod onboarding;
+mod plan_cell;
 pub mod preview;
+mod session_picker;
+mod session_store;
+pub mod slash_command;
+mod slide_overview;
 pub mod streaming;
+mod suggestions;
+#[cfg(unix)]
+mod suspend;
+pub mod test_harness;
+mod transcript_store;
+pub mod transcript_view;
 pub mod user_approval_widget;
 pub mod widgets;
 
 use anyhow::Result;
 use clap::Parser;
+use slide_common::privacy::{DataFlow, DataFlowRegistry, DataSink};
+use slide_common::{ApprovalMode, Deck, SlideConfig, WorkspaceState};
 use std::path::{Path, PathBuf};
 
 pub use app::*;
+pub use crash::install_panic_hook;
 pub use interactive::*;
 pub use preview::*;
 
+/// Flags shared by the TUI and every `slide` subcommand. They are global, so
+/// `slide --model gpt-4o generate ...` and `slide generate --model gpt-4o ...`
+/// mean the same.
 #[derive(Debug, Parser, Default)]
 pub struct Cli {
     /// Enable debug output
-    #[clap(long)]
+    #[clap(long, global = true)]
     pub debug: bool,
     /// Override model (e.g., gpt-5)
-    #[clap(long)]
+    #[clap(long, short = 'm', global = true)]
     pub model: Option<String>,
+    /// Use the config profile <config dir>/slide/profiles/<NAME>.json
+    #[clap(long, short = 'p', global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Run as if slide was started in this directory
+    #[clap(long, short = 'C', global = true, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+    /// Sandbox for the agent's commands
+    #[clap(long, global = true, value_parser = SANDBOX_MODES.to_vec())]
+    pub sandbox: Option<String>,
+    /// Explore without changing anything: commands run in the read-only
+    /// sandbox and the agent is not offered tools that write files
+    #[clap(long, global = true, conflicts_with = "sandbox")]
+    pub read_only: bool,
     /// Approval policy: untrusted | on-failure | on-request | never
-    #[clap(long, value_parser = ["untrusted","on-failure","on-request","never"].into_iter().collect::<Vec<&'static str>>())]
-    pub approval_mode: Option<String>,
+    #[clap(long, global = true)]
+    pub approval: Option<ApprovalMode>,
+    /// Deprecated spelling of --approval
+    #[clap(long, global = true, hide = true)]
+    pub approval_mode: Option<ApprovalMode>,
 }
 
-pub async fn run_main(cli: Cli, _sandbox_exe: Option<PathBuf>) -> Result<()> {
-    // Avoid直接の標準出力。デバッグはログや履歴行で扱う方針。
-    if let Some(model) = cli.model.clone() {
-        std::env::set_var("SLIDE_MODEL", model);
-    }
-    if let Some(mode) = cli.approval_mode.clone() {
-        std::env::set_var("SLIDE_APPROVAL_MODE", mode);
+pub const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+
+impl Cli {
+    /// Apply the global flags to the process before anything else runs: move
+    /// to `--cwd` and pass the model, profile and policies on through the
+    /// environment the agent and the config loader read. Returns deprecation
+    /// warnings for the caller to print.
+    pub fn apply_globals(&mut self) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        if let Some(mode) = self.approval_mode.take() {
+            warnings.push("`--approval-mode` is deprecated; use `--approval`".to_string());
+            self.approval.get_or_insert(mode);
+        }
+        if let Some(dir) = &self.cwd {
+            std::env::set_current_dir(dir)
+                .map_err(|e| anyhow::anyhow!("cannot change to {}: {e}", dir.display()))?;
+        }
+        if let Some(profile) = &self.profile {
+            std::env::set_var(slide_common::PROFILE_ENV, profile);
+            let path = SlideConfig::config_path()?;
+            if !path.is_file() {
+                anyhow::bail!(
+                    "profile `{profile}` not found: {} does not exist",
+                    path.display()
+                );
+            }
+        }
+        if let Some(model) = &self.model {
+            std::env::set_var("SLIDE_MODEL", model);
+        }
+        if let Some(mode) = self.approval {
+            std::env::set_var(slide_common::APPROVAL_MODE_ENV, mode.as_str());
+        }
+        if let Some(mode) = &self.sandbox {
+            std::env::set_var("SLIDE_SANDBOX", mode);
+        }
+        if self.read_only {
+            std::env::set_var(slide_core::codex::READ_ONLY_ENV, "1");
+            std::env::set_var("SLIDE_SANDBOX", "read-only");
+        }
+        Ok(warnings)
     }
+}
 
+pub async fn run_main(_cli: Cli, _sandbox_exe: Option<PathBuf>) -> Result<()> {
+    // Avoid直接の標準出力。デバッグはログや履歴行で扱う方針。
+    // フラグは呼び出し側が Cli::apply_globals で環境へ反映済み
     run_interactive().await
 }
 
-/// Run slide preview for a markdown file
+/// Register the files the TUI writes and the text it sends so `slide
+/// privacy report` can list them.
+pub fn register_data_flows(registry: &mut DataFlowRegistry, config: &SlideConfig) {
+    let privacy = &config.privacy;
+    registry.register(DataFlow {
+        subsystem: "tui.history",
+        description: "Every submitted composer message, for ↑/↓ recall",
+        sink: DataSink::File(history_store::HistoryStore::default().path().to_path_buf()),
+        active: privacy.history_enabled(),
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.sessions",
+        description: "Saved chat sessions, for resuming",
+        sink: DataSink::File(session_store::SessionStore::default().dir().to_path_buf()),
+        active: privacy.history_enabled(),
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.transcript",
+        description: "Scrollback past `transcript.max_lines`, for the transcript pager; removed on exit",
+        sink: DataSink::File(
+            session_store::SessionStore::default().transcript_path("<session>"),
+        ),
+        active: privacy.history_enabled(),
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.workspace-state",
+        description: "Recent decks, the last directory, model and open deck, slide positions",
+        sink: DataSink::File(PathBuf::from(slide_common::workspace_state::STATE_FILE)),
+        active: privacy.history_enabled(),
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.events",
+        description: "Every agent event of a session, for `slide replay`",
+        sink: DataSink::File(
+            slide_core::event_log::EventLog::default()
+                .dir()
+                .to_path_buf(),
+        ),
+        active: privacy.history_enabled(),
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.auth",
+        description: "API key saved by `slide login` when no OS keyring is available",
+        sink: DataSink::File(
+            slide_common::auth::AuthStore::default()
+                .path()
+                .to_path_buf(),
+        ),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.crash",
+        description: "Panic message and backtrace when slide crashes",
+        sink: DataSink::File(crash::crash_dir()),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.session-log",
+        description: "Chat messages, approvals and errors of the current session",
+        sink: DataSink::File(PathBuf::from("/tmp/slide.log")),
+        active: true,
+    });
+    registry.register(DataFlow {
+        subsystem: "tui.suggestions",
+        description: "Text being typed in the composer, for ghost-text suggestions",
+        sink: DataSink::Network(slide_core::client::SuggestionService::ENDPOINT.to_string()),
+        active: config.editor.suggestions.enabled,
+    });
+}
+
+/// Run slide preview for a markdown file, starting at the slide it was left
+/// at in this workspace and following edits to the file
 pub async fn run_preview<P: AsRef<Path>>(file_path: P) -> Result<()> {
+    run_preview_with(file_path, PreviewOptions::default()).await?;
+    Ok(())
+}
+
+/// Run the preview as `options` ask: in presenter mode for delivering the
+/// deck, recording a rehearsal, or auto-advancing after the last one.
+/// Returns where the timings of a rehearsal were saved
+pub async fn run_preview_with<P: AsRef<Path>>(
+    file_path: P,
+    options: PreviewOptions,
+) -> Result<Option<PathBuf>> {
+    let file_path = file_path.as_ref();
     let content = tokio::fs::read_to_string(file_path).await?;
-    let slides = parse_slides(&content);
+    let deck = Deck::parse(&content);
+    let config = SlideConfig::load().await.unwrap_or_default();
 
-    let mut preview = SlidePreview::new(slides);
-    preview.run().await
+    let mut state = WorkspaceState::load(".");
+    let mut preview = SlidePreview::new(deck, &config.presentation)
+        .at_slide(state.slide_position(file_path))
+        .watch(file_path.to_path_buf())
+        .with_options(options);
+    preview.run().await?;
+    state.set_slide_position(file_path, preview.current_slide());
+    // Best-effort, like the rest of the workspace state
+    let _ = state.save(".");
+    Ok(preview.saved_timings())
 }
 
-/// Run interactive slide creation mode
-pub async fn run_interactive() -> Result<()> {
-    let mut app = InteractiveApp::new();
-    app.run().await
+/// Browse, diff and restore the snapshots of a deck
+pub async fn run_history<P: AsRef<Path>>(deck_path: P) -> Result<()> {
+    let store = slide_common::VersionStore::new(".");
+    let mut view = history_view::HistoryView::new(deck_path.as_ref().to_path_buf(), store)?;
+    view.run().await
 }
 
-/// Parse markdown content into slides
-fn parse_slides(content: &str) -> Vec<String> {
-    let mut slides = Vec::new();
-    let mut current_slide = String::new();
-
-    for line in content.lines() {
-        if line.starts_with("## ") && !current_slide.is_empty() {
-            slides.push(current_slide.trim().to_string());
-            current_slide = String::new();
-        }
-        current_slide.push_str(line);
-        current_slide.push('\n');
-    }
-
-    if !current_slide.trim().is_empty() {
-        slides.push(current_slide.trim().to_string());
-    }
+/// Login screen of `slide login`; returns where the credentials were saved
+pub async fn run_login() -> Result<Option<String>> {
+    let mut screen = onboarding::LoginScreen::new(slide_common::auth::AuthStore::default());
+    screen.run().await
+}
 
-    if slides.is_empty() {
-        slides.push(content.to_string());
-    }
+/// Continue a saved plan (`slide plan resume`) in the interactive app
+pub async fn run_plan_resume(record: slide_core::codex::PlanRecord) -> Result<()> {
+    let mut app = InteractiveApp::continuing_plan(record);
+    app.run().await
+}
 
-    slides
+/// Run interactive slide creation mode
+pub async fn run_interactive() -> Result<()> {
+    let mut app = InteractiveApp::new();
+    app.run().await
 }
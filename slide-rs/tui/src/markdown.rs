@@ -0,0 +1,446 @@
+//! Markdown for agent answers, rendered one completed line at a time so the
+//! streamed answer and a finished one look the same. Covers headings,
+//! lists, quotes, rules, fenced code with light syntax highlighting, and
+//! bold, italic, strikethrough, code and link spans.
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Renders lines in order; remembers the fenced code block a line is in.
+#[derive(Debug, Default)]
+pub struct MarkdownRenderer {
+    fence: Option<Fence>,
+}
+
+#[derive(Debug)]
+struct Fence {
+    /// "```" or "~~~", possibly longer; the closing fence must match
+    marker: String,
+    lang: String,
+}
+
+/// Render a whole answer with a fresh renderer.
+pub fn render_markdown(text: &str) -> Vec<Line<'static>> {
+    let mut renderer = MarkdownRenderer::default();
+    text.lines()
+        .map(|line| renderer.render_line(line))
+        .collect()
+}
+
+impl MarkdownRenderer {
+    /// Forget an unclosed code block, e.g. when the answer ended inside it.
+    pub fn reset(&mut self) {
+        self.fence = None;
+    }
+
+    pub fn render_line(&mut self, line: &str) -> Line<'static> {
+        let trimmed = line.trim_start();
+        if let Some(fence) = &self.fence {
+            if trimmed.trim_end() == fence.marker {
+                self.fence = None;
+                return Line::styled(line.to_string(), dim());
+            }
+            return Line::from(highlight_code(line, &fence.lang));
+        }
+        if let Some(marker) = fence_marker(trimmed) {
+            self.fence = Some(Fence {
+                marker: marker.to_string(),
+                lang: trimmed[marker.len()..].trim().to_lowercase(),
+            });
+            return Line::styled(line.to_string(), dim());
+        }
+        render_block_line(line)
+    }
+}
+
+fn dim() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+fn fence_marker(trimmed: &str) -> Option<&str> {
+    ["```", "~~~"].into_iter().find_map(|c| {
+        let len = trimmed.len() - trimmed.trim_start_matches(&c[..1]).len();
+        (len >= 3).then(|| &trimmed[..len])
+    })
+}
+
+/// A line outside code blocks: block syntax first, then inline spans.
+fn render_block_line(line: &str) -> Line<'static> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && rest[hashes..].starts_with(' ') {
+        let mut style = Style::default().add_modifier(Modifier::BOLD);
+        style = match hashes {
+            1 => style.fg(Color::Magenta).add_modifier(Modifier::UNDERLINED),
+            2 => style.fg(Color::Magenta),
+            _ => style.fg(Color::Cyan),
+        };
+        return Line::from(inline_spans(rest[hashes..].trim(), style));
+    }
+
+    let compact: String = rest.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|c| compact.chars().all(|x| x.to_string() == *c))
+    {
+        return Line::styled("─".repeat(24), dim());
+    }
+
+    if let Some(quote) = rest.strip_prefix('>') {
+        let mut spans = vec![Span::raw(indent.to_string()), Span::styled("▌ ", dim())];
+        spans.extend(inline_spans(
+            quote.trim_start(),
+            Style::default().add_modifier(Modifier::ITALIC),
+        ));
+        return Line::from(spans);
+    }
+
+    if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| rest.strip_prefix(bullet))
+    {
+        let (mark, item) = if let Some(item) = item.strip_prefix("[ ] ") {
+            ("☐ ", item)
+        } else if let Some(item) = item
+            .strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+        {
+            ("☑ ", item)
+        } else {
+            ("• ", item)
+        };
+        let mut spans = vec![
+            Span::raw(indent.to_string()),
+            Span::styled(mark, Style::default().fg(Color::Cyan)),
+        ];
+        spans.extend(inline_spans(item, Style::default()));
+        return Line::from(spans);
+    }
+
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) {
+        let mut spans = vec![
+            Span::raw(indent.to_string()),
+            Span::styled(
+                rest[..digits + 2].to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ];
+        spans.extend(inline_spans(&rest[digits + 2..], Style::default()));
+        return Line::from(spans);
+    }
+
+    Line::from(inline_spans(line, Style::default()))
+}
+
+/// Emphasis, code and link spans of `text` on top of `base`. A delimiter
+/// without a closing partner on the same line is kept as text.
+fn inline_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = rest.chars().next() {
+        let mut matched: Option<(usize, Vec<Span<'static>>)> = None;
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                let code = &rest[1..1 + end];
+                matched = Some((
+                    end + 2,
+                    vec![Span::styled(code.to_string(), base.fg(Color::Yellow))],
+                ));
+            }
+        } else if let Some(delim) = ["**", "__", "~~"].into_iter().find(|d| rest.starts_with(d)) {
+            if let Some(end) = rest[2..].find(delim).filter(|&end| end > 0) {
+                let modifier = if delim == "~~" {
+                    Modifier::CROSSED_OUT
+                } else {
+                    Modifier::BOLD
+                };
+                matched = Some((
+                    end + 4,
+                    inline_spans(&rest[2..2 + end], base.add_modifier(modifier)),
+                ));
+            }
+        } else if (c == '*' || c == '_')
+            // `snake_case` and `2 * 3` stay as they are
+            && !prev.is_some_and(|p| p.is_alphanumeric())
+            && rest[1..].chars().next().is_some_and(|n| !n.is_whitespace())
+        {
+            if let Some(end) = rest[1..].find(c).filter(|&end| end > 0) {
+                let after = rest[1 + end + 1..].chars().next();
+                if !after.is_some_and(|a| a.is_alphanumeric()) {
+                    matched = Some((
+                        end + 2,
+                        inline_spans(&rest[1..1 + end], base.add_modifier(Modifier::ITALIC)),
+                    ));
+                }
+            }
+        } else if c == '[' {
+            if let Some((label, url, len)) = link(rest) {
+                let mut link_spans = inline_spans(
+                    label,
+                    base.fg(Color::Cyan).add_modifier(Modifier::UNDERLINED),
+                );
+                if url != label {
+                    link_spans.push(Span::styled(format!(" ({url})"), dim()));
+                }
+                matched = Some((len, link_spans));
+            }
+        }
+
+        match matched {
+            Some((len, inner)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.extend(inner);
+                prev = rest[..len].chars().last();
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                prev = Some(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+/// `[label](url)` at the start of `text`: label, url and byte length.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let close = text.find("](")?;
+    let end = text[close + 2..].find(')')?;
+    let label = &text[1..close];
+    let url = &text[close + 2..close + 2 + end];
+    (!label.is_empty() && !url.contains(' ')).then_some((label, url, close + 3 + end))
+}
+
+const KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "class",
+    "const",
+    "continue",
+    "def",
+    "do",
+    "done",
+    "elif",
+    "else",
+    "enum",
+    "esac",
+    "export",
+    "false",
+    "fi",
+    "fn",
+    "for",
+    "from",
+    "func",
+    "function",
+    "if",
+    "impl",
+    "import",
+    "in",
+    "interface",
+    "let",
+    "loop",
+    "match",
+    "mod",
+    "mut",
+    "new",
+    "nil",
+    "None",
+    "null",
+    "pub",
+    "return",
+    "self",
+    "Self",
+    "static",
+    "struct",
+    "then",
+    "trait",
+    "true",
+    "True",
+    "False",
+    "type",
+    "use",
+    "var",
+    "where",
+    "while",
+];
+
+/// Languages whose line comments start with `#`
+const HASH_COMMENTS: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "shell",
+    "console",
+    "python",
+    "py",
+    "ruby",
+    "rb",
+    "toml",
+    "yaml",
+    "yml",
+    "make",
+    "makefile",
+    "dockerfile",
+];
+
+/// Keywords, strings, numbers and line comments; diffs by line prefix.
+fn highlight_code(line: &str, lang: &str) -> Vec<Span<'static>> {
+    if lang == "diff" || lang == "patch" {
+        let style = match line.chars().next() {
+            Some('+') => Style::default().fg(Color::Green),
+            Some('-') => Style::default().fg(Color::Red),
+            Some('@') => Style::default().fg(Color::Cyan),
+            _ => Style::default(),
+        };
+        return vec![Span::styled(line.to_string(), style)];
+    }
+    let comment = if HASH_COMMENTS.contains(&lang) {
+        "#"
+    } else {
+        "//"
+    };
+
+    let mut spans = Vec::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, spans: &mut Vec<Span<'static>>| {
+        if word.is_empty() {
+            return;
+        }
+        let w = std::mem::take(word);
+        let style = if KEYWORDS.contains(&w.as_str()) {
+            Style::default().fg(Color::Magenta)
+        } else if w.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+            Style::default().fg(Color::LightYellow)
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(w, style));
+    };
+
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(comment) {
+            flush(&mut word, &mut spans);
+            spans.push(Span::styled(rest.to_string(), dim()));
+            return spans;
+        }
+        if c == '"' || c == '\'' {
+            // Rust lifetimes and apostrophes in comments-free text stay plain
+            let end = rest[1..].find(c);
+            if let Some(end) = end.filter(|_| c == '"' || word.is_empty()) {
+                flush(&mut word, &mut spans);
+                spans.push(Span::styled(
+                    rest[..end + 2].to_string(),
+                    Style::default().fg(Color::Green),
+                ));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut spans);
+            spans.push(Span::raw(c.to_string()));
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    flush(&mut word, &mut spans);
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn styled<'a>(line: &'a Line, content: &str) -> &'a Span<'a> {
+        line.spans
+            .iter()
+            .find(|s| s.content == content)
+            .unwrap_or_else(|| panic!("no span {content:?} in {line:?}"))
+    }
+
+    #[test]
+    fn block_syntax_is_rendered() {
+        let lines = render_markdown(
+            "## Agenda\n- [x] intro\n* **bold** point\n2. second\n> quoted\n---\nplain",
+        );
+        let rendered: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            rendered,
+            [
+                "Agenda",
+                "☑ intro",
+                "• bold point",
+                "2. second",
+                "▌ quoted",
+                "────────────────────────",
+                "plain"
+            ]
+        );
+        assert!(lines[0].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(styled(&lines[2], "bold")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn inline_spans_keep_unmatched_delimiters() {
+        let line =
+            render_markdown("run `cargo test`, see [docs](https://x.dev) and *this*").remove(0);
+        assert_eq!(
+            text(&line),
+            "run cargo test, see docs (https://x.dev) and this"
+        );
+        assert_eq!(styled(&line, "cargo test").style.fg, Some(Color::Yellow));
+        assert!(styled(&line, "this")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+
+        let line = render_markdown("snake_case_name and 2 * 3 and **open").remove(0);
+        assert_eq!(text(&line), "snake_case_name and 2 * 3 and **open");
+        assert_eq!(line.spans.len(), 1);
+    }
+
+    #[test]
+    fn streamed_lines_render_like_the_whole_answer() {
+        let answer = "```rust\nlet x = \"# not a heading\"; // note\n```\n# Heading";
+        let mut renderer = MarkdownRenderer::default();
+        let streamed: Vec<Line> = answer.lines().map(|l| renderer.render_line(l)).collect();
+        assert_eq!(streamed, render_markdown(answer));
+
+        let code = &streamed[1];
+        assert_eq!(text(code), "let x = \"# not a heading\"; // note");
+        assert_eq!(styled(code, "let").style.fg, Some(Color::Magenta));
+        assert_eq!(
+            styled(code, "\"# not a heading\"").style.fg,
+            Some(Color::Green)
+        );
+        assert_eq!(styled(code, "// note").style.fg, Some(Color::DarkGray));
+        assert_eq!(text(&streamed[3]), "Heading");
+    }
+}
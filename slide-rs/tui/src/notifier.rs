@@ -0,0 +1,88 @@
+//! Bell and desktop notifications for approval requests and finished tasks,
+//! sent only while the terminal is not focused.
+use std::io::Write;
+use std::time::Duration;
+
+use slide_common::{NotificationConfig, NotificationMethod};
+
+pub(crate) struct Notifier {
+    config: NotificationConfig,
+    /// Updated from focus events; assumed focused until the terminal says otherwise
+    focused: bool,
+}
+
+impl Notifier {
+    pub(crate) fn new(config: NotificationConfig) -> Self {
+        Self {
+            config,
+            focused: true,
+        }
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub(crate) fn approval_requested(&self, what: &str) {
+        self.notify(&format!("Approval needed: {what}"));
+    }
+
+    /// Notify about a task that ran for `elapsed`, if it was long enough.
+    pub(crate) fn task_finished(&self, elapsed: Duration) {
+        if elapsed.as_secs() >= self.config.min_task_secs {
+            self.notify(&format!("Task finished in {}s", elapsed.as_secs()));
+        }
+    }
+
+    fn notify(&self, message: &str) {
+        if self.focused {
+            return;
+        }
+        let sequence = notification_sequence(self.config.method, message);
+        if sequence.is_empty() {
+            return;
+        }
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(sequence.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// Escape sequence for `message`: a bell, followed by the desktop
+/// notification of the OSC methods.
+fn notification_sequence(method: NotificationMethod, message: &str) -> String {
+    // Control characters would end the sequence early; `;` separates OSC 777 fields
+    let message: String = message
+        .chars()
+        .map(|c| if c.is_control() || c == ';' { ' ' } else { c })
+        .collect();
+    match method {
+        NotificationMethod::Osc9 => format!("\x07\x1b]9;{message}\x07"),
+        NotificationMethod::Osc777 => format!("\x07\x1b]777;notify;slide;{message}\x07"),
+        NotificationMethod::Bell => "\x07".to_string(),
+        NotificationMethod::Off => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequences_follow_the_configured_method() {
+        let message = "Approval needed: rm -rf out; ls\n";
+        assert_eq!(
+            notification_sequence(NotificationMethod::Osc9, message),
+            "\x07\x1b]9;Approval needed: rm -rf out  ls \x07"
+        );
+        assert_eq!(
+            notification_sequence(NotificationMethod::Osc777, "done"),
+            "\x07\x1b]777;notify;slide;done\x07"
+        );
+        assert_eq!(
+            notification_sequence(NotificationMethod::Bell, "done"),
+            "\x07"
+        );
+        assert_eq!(notification_sequence(NotificationMethod::Off, "done"), "");
+    }
+}
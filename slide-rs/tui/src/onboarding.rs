@@ -0,0 +1,254 @@
+//! Login screen shown on first run and by `slide login`: pick a provider,
+//! paste an API key, check it with a test request and store it in the OS
+//! keyring or `~/.slide/auth.json`.
+use anyhow::Result;
+use crossterm::{
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use slide_common::auth::{AuthDotJson, AuthStore, LOGIN_PROVIDERS};
+use std::io;
+use tokio::time::{sleep, Duration};
+
+/// Characters of the key left readable at the end of the masked input
+const VISIBLE_KEY_SUFFIX: usize = 4;
+
+enum LoginState {
+    Editing,
+    /// Waiting for the test request
+    Checking,
+    Failed(String),
+}
+
+pub(crate) struct LoginScreen {
+    store: AuthStore,
+    /// Index into [`LOGIN_PROVIDERS`]
+    provider: usize,
+    key: String,
+    state: LoginState,
+    /// Where the credentials were saved
+    saved_to: Option<String>,
+    should_quit: bool,
+}
+
+impl LoginScreen {
+    pub(crate) fn new(store: AuthStore) -> Self {
+        Self {
+            store,
+            provider: 0,
+            key: String::new(),
+            state: LoginState::Editing,
+            saved_to: None,
+            should_quit: false,
+        }
+    }
+
+    /// Show the screen until a key is stored or it is closed.
+    /// Returns where credentials were saved, if they were.
+    pub(crate) async fn run(&mut self) -> Result<Option<String>> {
+        let was_raw = is_raw_mode_enabled()?;
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        loop {
+            terminal.draw(|f| self.ui(f))?;
+
+            if matches!(self.state, LoginState::Checking) {
+                self.check_and_save().await;
+                continue;
+            }
+
+            if event::poll(Duration::from_millis(100))? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        self.handle_key_event(key);
+                    }
+                    Event::Paste(pasted) => self.key.push_str(pasted.trim()),
+                    _ => {}
+                }
+            }
+
+            if self.should_quit {
+                break;
+            }
+
+            sleep(Duration::from_millis(16)).await;
+        }
+
+        if !was_raw {
+            disable_raw_mode()?;
+        }
+        execute!(
+            terminal.backend_mut(),
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        )?;
+        terminal.show_cursor()?;
+
+        Ok(self.saved_to.take())
+    }
+
+    fn provider_id(&self) -> &'static str {
+        LOGIN_PROVIDERS[self.provider].0
+    }
+
+    async fn check_and_save(&mut self) {
+        let key = self.key.trim().to_string();
+        let checked = slide_core::client::validate_api_key(self.provider_id(), &key).await;
+        let saved = checked.and_then(|()| {
+            self.store.save(&AuthDotJson {
+                provider: self.provider_id().to_string(),
+                api_key: key,
+            })
+        });
+        match saved {
+            Ok(location) => {
+                self.saved_to = Some(location);
+                self.should_quit = true;
+            }
+            Err(e) => self.state = LoginState::Failed(e.to_string()),
+        }
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            (KeyCode::Tab | KeyCode::Right, _) => {
+                self.provider = (self.provider + 1) % LOGIN_PROVIDERS.len();
+            }
+            (KeyCode::BackTab | KeyCode::Left, _) => {
+                self.provider = (self.provider + LOGIN_PROVIDERS.len() - 1) % LOGIN_PROVIDERS.len();
+            }
+            (KeyCode::Enter, _) if !self.key.trim().is_empty() => {
+                self.state = LoginState::Checking;
+            }
+            (KeyCode::Backspace, _) => {
+                self.key.pop();
+                self.state = LoginState::Editing;
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.key.clear();
+                self.state = LoginState::Editing;
+            }
+            (KeyCode::Char(c), m) if !m.contains(KeyModifiers::CONTROL) => {
+                self.key.push(c);
+                self.state = LoginState::Editing;
+            }
+            _ => {}
+        }
+    }
+
+    fn ui(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .margin(1)
+            .split(f.area());
+
+        let providers: Vec<Span> = LOGIN_PROVIDERS
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, name))| {
+                let style = if i == self.provider {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Gray)
+                };
+                [Span::styled(format!(" {name} "), style), Span::raw(" ")]
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(Line::from(providers))
+                .block(Block::default().borders(Borders::ALL).title("Provider")),
+            chunks[0],
+        );
+
+        let input = Paragraph::new(mask_key(&self.key)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} API key", LOGIN_PROVIDERS[self.provider].1)),
+        );
+        f.render_widget(input, chunks[1]);
+        if matches!(self.state, LoginState::Editing | LoginState::Failed(_)) {
+            let x = chunks[1].x + 1 + mask_key(&self.key).chars().count() as u16;
+            f.set_cursor_position((x.min(chunks[1].right().saturating_sub(2)), chunks[1].y + 1));
+        }
+
+        let mut body = vec![
+            Line::from("Welcome to Slide. Paste an API key to use a real model;"),
+            Line::from(format!(
+                "it is checked with a test request and saved to the OS keyring, or {} (mode 600) without one.",
+                self.store.path().display()
+            )),
+            Line::from(""),
+        ];
+        match &self.state {
+            LoginState::Editing => {}
+            LoginState::Checking => body.push(Line::styled(
+                "Checking the key…",
+                Style::default().fg(Color::Yellow),
+            )),
+            LoginState::Failed(message) => body.push(Line::styled(
+                format!("Login failed: {message}"),
+                Style::default().fg(Color::Red),
+            )),
+        }
+        f.render_widget(Paragraph::new(body).wrap(Wrap { trim: false }), chunks[2]);
+
+        let footer = Paragraph::new(
+            "Enter: check and save | Tab: provider | Ctrl-U: clear | Esc: continue without a key",
+        )
+        .style(Style::default().fg(Color::Yellow));
+        f.render_widget(footer, chunks[3]);
+    }
+}
+
+/// Bullets for the key, keeping its last few characters readable.
+fn mask_key(key: &str) -> String {
+    let len = key.chars().count();
+    let hidden = len.saturating_sub(VISIBLE_KEY_SUFFIX);
+    key.chars()
+        .enumerate()
+        .map(|(i, c)| if i < hidden { '•' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_is_masked_except_its_end() {
+        assert_eq!(mask_key("sk-abcdef1234"), "•••••••••1234");
+        assert_eq!(mask_key("abc"), "abc");
+        assert_eq!(mask_key(""), "");
+    }
+}
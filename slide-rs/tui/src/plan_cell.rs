@@ -0,0 +1,135 @@
+//! The agent's task plan, shown as a checklist above the composer.
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use slide_core::codex::{StepStatus, UpdatePlanArgs};
+
+pub(crate) struct PlanCell {
+    plan: UpdatePlanArgs,
+}
+
+impl PlanCell {
+    pub(crate) fn new(plan: UpdatePlanArgs) -> Self {
+        Self { plan }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.plan.is_finished()
+    }
+
+    pub(crate) fn desired_height(&self) -> u16 {
+        let explanation = usize::from(self.plan.explanation.is_some());
+        (1 + explanation + self.plan.plan.len()) as u16
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::from(vec![
+            Span::styled("Plan", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!(
+                    "  {}/{} done",
+                    self.plan.completed_count(),
+                    self.plan.plan.len()
+                ),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ])];
+        if let Some(explanation) = &self.plan.explanation {
+            lines.push(Line::styled(
+                format!("  {explanation}"),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        for item in &self.plan.plan {
+            let (mark, style) = match item.status {
+                StepStatus::Pending => ("○", Style::default().fg(Color::Gray)),
+                StepStatus::InProgress => (
+                    "▶",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                StepStatus::Completed => (
+                    "✔",
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::DIM),
+                ),
+                StepStatus::Failed => ("✗", Style::default().fg(Color::Red)),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {mark} "), style),
+                Span::styled(item.step.clone(), style),
+            ]));
+        }
+        lines
+    }
+
+    /// Checklist committed to the transcript once every step is done.
+    pub(crate) fn history_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::from("")];
+        lines.extend(self.lines());
+        lines
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        let lines = self.lines();
+        // Keep the header and the steps around the one in progress in view
+        let skip = match self
+            .plan
+            .plan
+            .iter()
+            .position(|i| i.status != StepStatus::Completed)
+        {
+            Some(first_open) => {
+                let offset = lines.len() - self.plan.plan.len() + first_open;
+                (offset + 1)
+                    .saturating_sub(area.height as usize)
+                    .min(lines.len().saturating_sub(area.height as usize))
+            }
+            None => 0,
+        };
+        let visible: Vec<Line> = lines.into_iter().skip(skip).collect();
+        Paragraph::new(visible).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slide_core::codex::PlanItem;
+
+    fn item(step: &str, status: StepStatus) -> PlanItem {
+        PlanItem {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn checklist_marks_each_status() {
+        let cell = PlanCell::new(UpdatePlanArgs {
+            explanation: Some("Build the deck".to_string()),
+            plan: vec![
+                item("Outline", StepStatus::Completed),
+                item("Write slides", StepStatus::InProgress),
+                item("Export", StepStatus::Failed),
+                item("Review", StepStatus::Pending),
+            ],
+        });
+        assert!(!cell.is_finished());
+        assert_eq!(cell.desired_height(), 6);
+
+        let lines = cell.lines();
+        assert_eq!(lines[0].spans[1].content, "  1/4 done");
+        let marks: Vec<&str> = lines[2..]
+            .iter()
+            .map(|l| l.spans[0].content.as_ref())
+            .collect();
+        assert_eq!(marks, vec!["  ✔ ", "  ▶ ", "  ✗ ", "  ○ "]);
+    }
+}
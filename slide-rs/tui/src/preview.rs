@@ -1,3 +1,4 @@
+use crate::slide_overview::{OverviewAction, SlideOverview};
 use anyhow::Result;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
@@ -6,30 +7,165 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Text},
     widgets::{Block, Borders, Clear, Paragraph},
     Frame, Terminal,
 };
+use slide_apply_patch::{FileOutcome, PatchRequest};
+use slide_common::deck::format_duration;
+use slide_common::frontmatter::AspectRatio;
+use slide_common::{
+    Deck, DeckSettings, DeckStats, PresentationConfig, Rehearsal, Slide, TimingReport,
+};
+use slide_core::file_watcher::{workspace_watcher, Subscription};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tokio::time::{sleep, Duration};
 
 pub struct SlidePreview {
-    slides: Vec<String>,
+    slides: Vec<Slide>,
+    /// Frontmatter of the deck: theme, aspect ratio, footer and numbering
+    settings: DeckSettings,
+    stats: DeckStats,
+    presentation: PresentationConfig,
+    /// Deck file; rehearsal timings are written next to it
+    path: Option<PathBuf>,
+    /// Deck file reloaded when the workspace watcher reports it changed
+    source: Option<(PathBuf, Subscription)>,
     current_slide: usize,
     should_quit: bool,
     show_help: bool,
+    show_stats: bool,
+    /// Presenter view: the current and next slide, speaker notes and timers
+    presenter: bool,
+    /// Start of the talk; set when presenter mode is first entered
+    talk_started: Option<Instant>,
+    /// When the current slide was reached
+    slide_started: Instant,
+    /// Digits typed after `g`; Enter jumps to that slide
+    goto: Option<String>,
+    /// Grid of the slides, open with `o`
+    overview: Option<SlideOverview>,
+    /// Rehearsal being recorded
+    rehearsal: Option<Rehearsal>,
+    /// Timings of the last rehearsal of the deck
+    timings: Option<TimingReport>,
+    /// Report file written by a rehearsal of this preview
+    saved_timings: Option<PathBuf>,
+    /// Move on once a slide has been shown as long as it took in the rehearsal
+    auto_advance: bool,
+    /// Outcome of the last key, shown in the footer
+    status: Option<String>,
+}
+
+/// How `slide preview` opens a deck.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreviewOptions {
+    pub presenter: bool,
+    /// Record a rehearsal from the start
+    pub rehearse: bool,
+    pub auto_advance: bool,
 }
 
 impl SlidePreview {
-    pub fn new(slides: Vec<String>) -> Self {
+    pub fn new(deck: Deck, presentation: &PresentationConfig) -> Self {
         Self {
-            slides,
+            slides: deck.slides.clone(),
+            settings: deck.settings.clone(),
+            stats: DeckStats::compute(&deck, presentation),
+            presentation: presentation.clone(),
+            path: None,
+            source: None,
             current_slide: 0,
             should_quit: false,
             show_help: false,
+            show_stats: false,
+            presenter: false,
+            talk_started: None,
+            slide_started: Instant::now(),
+            goto: None,
+            overview: None,
+            rehearsal: None,
+            timings: None,
+            saved_timings: None,
+            auto_advance: false,
+            status: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: PreviewOptions) -> Self {
+        if options.presenter {
+            self = self.presenter();
+        }
+        if options.rehearse {
+            self.toggle_rehearsal();
+        }
+        if options.auto_advance {
+            self.toggle_auto_advance();
+        }
+        self
+    }
+
+    /// Open in presenter mode, with the talk timer running.
+    pub fn presenter(mut self) -> Self {
+        self.toggle_presenter();
+        self
+    }
+
+    /// Start at slide `index` (clamped to the deck).
+    pub fn at_slide(mut self, index: usize) -> Self {
+        self.current_slide = index.min(self.slides.len().saturating_sub(1));
+        self
+    }
+
+    pub fn current_slide(&self) -> usize {
+        self.current_slide
+    }
+
+    /// Reload the deck from `path` whenever it changes on disk, and use
+    /// the timings of its last rehearsal.
+    pub fn watch(mut self, path: PathBuf) -> Self {
+        self.timings = TimingReport::load(&path);
+        self.refresh_stats();
+        self.path = Some(path.clone());
+        self.source = workspace_watcher().map(|watcher| (path, watcher.subscribe()));
+        self
+    }
+
+    /// Estimates of the deck, beside the rehearsed times when there are some.
+    fn refresh_stats(&mut self) {
+        let deck = Deck {
+            slides: self.slides.clone(),
+            settings: self.settings.clone(),
+        };
+        self.stats = DeckStats::compute(&deck, &self.presentation);
+        if let Some(timings) = &self.timings {
+            self.stats = self.stats.clone().with_rehearsal(timings);
+        }
+    }
+
+    /// Re-read the deck when its file changed; keeps the current slide.
+    fn reload_if_changed(&mut self) {
+        let Some((path, changes)) = &mut self.source else {
+            return;
+        };
+        if !changes
+            .try_recv()
+            .is_some_and(|change| change.touches(path))
+        {
+            return;
         }
+        let Ok(content) = std::fs::read_to_string(&*path) else {
+            return;
+        };
+        let deck = Deck::parse(&content);
+        self.slides = deck.slides;
+        self.settings = deck.settings;
+        self.refresh_stats();
+        self.current_slide = self.current_slide.min(self.slides.len().saturating_sub(1));
     }
 
     pub async fn run(&mut self) -> Result<()> {
@@ -41,6 +177,8 @@ impl SlidePreview {
         let mut terminal = Terminal::new(backend)?;
 
         loop {
+            self.reload_if_changed();
+            self.advance_if_due();
             // Draw UI
             terminal.draw(|f| self.ui(f))?;
 
@@ -66,38 +204,266 @@ impl SlidePreview {
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
         terminal.show_cursor()?;
 
+        // Quitting ends a rehearsal like `R` does
+        self.finish_rehearsal()?;
+        Ok(())
+    }
+
+    /// Where the last rehearsal of this preview saved its timings.
+    pub fn saved_timings(&self) -> Option<PathBuf> {
+        self.saved_timings.clone()
+    }
+
+    fn toggle_rehearsal(&mut self) {
+        if self.rehearsal.is_none() {
+            self.rehearsal = Some(Rehearsal::start(
+                self.slides.len(),
+                self.current_slide,
+                Instant::now(),
+            ));
+            self.slide_started = Instant::now();
+            self.status = Some("Recording a rehearsal; R again to stop and save".to_string());
+            return;
+        }
+        self.status = Some(match self.finish_rehearsal() {
+            Ok(Some(path)) => format!("Timings saved to {}", path.display()),
+            Ok(None) => "No deck file to save the timings next to".to_string(),
+            Err(e) => format!("{e:#}"),
+        });
+    }
+
+    /// Stop the rehearsal being recorded and save its timings next to the
+    /// deck. The new timings are used for auto-advance and the stats from
+    /// then on.
+    fn finish_rehearsal(&mut self) -> Result<Option<PathBuf>> {
+        let Some(rehearsal) = self.rehearsal.take() else {
+            return Ok(None);
+        };
+        let deck = Deck {
+            slides: self.slides.clone(),
+            settings: self.settings.clone(),
+        };
+        let report = rehearsal.finish(&deck, Instant::now());
+        let saved = match &self.path {
+            Some(path) => Some(report.save(path)?),
+            None => None,
+        };
+        self.timings = Some(report);
+        self.refresh_stats();
+        if saved.is_some() {
+            self.saved_timings.clone_from(&saved);
+        }
+        Ok(saved)
+    }
+
+    fn toggle_auto_advance(&mut self) {
+        if self.auto_advance {
+            self.auto_advance = false;
+            self.status = Some("Auto-advance off".to_string());
+        } else if self.timings.is_some() {
+            self.auto_advance = true;
+            self.slide_started = Instant::now();
+            self.status = Some("Auto-advancing after the rehearsed times".to_string());
+        } else {
+            self.status = Some("No rehearsal timings yet; record one with R".to_string());
+        }
+    }
+
+    /// Time left on the current slide before auto-advance moves on.
+    fn auto_advance_left(&self) -> Option<Duration> {
+        if !self.auto_advance || self.current_slide + 1 >= self.slides.len() {
+            return None;
+        }
+        let slide = self.stats.slides.get(self.current_slide)?;
+        let due = Duration::from_secs(slide.rehearsed_secs?.into());
+        Some(due.saturating_sub(self.slide_started.elapsed()))
+    }
+
+    fn advance_if_due(&mut self) {
+        if self.auto_advance_left() == Some(Duration::ZERO) {
+            self.go_to(self.current_slide + 1);
+        }
+    }
+
+    /// Show slide `index` (clamped to the deck); the slide timer restarts
+    /// when the slide changes.
+    fn go_to(&mut self, index: usize) {
+        let index = index.min(self.slides.len().saturating_sub(1));
+        if index != self.current_slide {
+            self.current_slide = index;
+            self.slide_started = Instant::now();
+            if let Some(rehearsal) = self.rehearsal.as_mut() {
+                rehearsal.enter(index, self.slide_started);
+            }
+        }
+    }
+
+    /// Exchange slides `a` and `b` in the deck file, through a patch so the
+    /// rest of the file is left as written, and show the reordered deck.
+    fn swap_slides(&mut self, a: usize, b: usize) {
+        self.status = Some(match self.write_swap(a, b) {
+            Ok(()) => format!("Moved slide {} to {}", a + 1, b + 1),
+            Err(err) => format!("Could not reorder the deck: {err}"),
+        });
+    }
+
+    fn write_swap(&mut self, a: usize, b: usize) -> Result<()> {
+        let Some(path) = self.path.clone() else {
+            anyhow::bail!("the preview was not opened from a deck file");
+        };
+        let old = std::fs::read_to_string(&path)?;
+        let mut order: Vec<usize> = (0..Deck::parse(&old).slides.len()).collect();
+        if a.max(b) >= order.len() {
+            anyhow::bail!("the deck changed on disk");
+        }
+        order.swap(a, b);
+        let new = Deck::reorder(&old, &order);
+        if Deck::parse(&new).slides.len() != order.len() {
+            anyhow::bail!("a slide without a ## heading has to stay first");
+        }
+        let cwd = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let file_name = path.file_name().map(Path::new).unwrap_or(&path);
+        let report = PatchRequest::update_file(file_name, &old, &new, cwd)?.apply();
+        if let Some(FileOutcome::Failed(err)) = report.files.into_iter().map(|f| f.outcome).next() {
+            return Err(err.into());
+        }
+        let deck = Deck::parse(&new);
+        self.slides = deck.slides;
+        self.settings = deck.settings;
+        self.refresh_stats();
+        if self.current_slide == a {
+            self.current_slide = b;
+        } else if self.current_slide == b {
+            self.current_slide = a;
+        }
         Ok(())
     }
 
+    fn toggle_presenter(&mut self) {
+        self.presenter = !self.presenter;
+        if self.presenter && self.talk_started.is_none() {
+            self.talk_started = Some(Instant::now());
+            self.slide_started = Instant::now();
+        }
+    }
+
     fn handle_key_event(&mut self, key: KeyEvent) {
+        self.status = None;
+        if let Some(overview) = self.overview.as_mut() {
+            match overview.handle_key_event(key, &self.slides) {
+                OverviewAction::None => {}
+                OverviewAction::Close => self.overview = None,
+                OverviewAction::Open(index) => {
+                    self.overview = None;
+                    self.go_to(index);
+                }
+                OverviewAction::Swap(a, b) => self.swap_slides(a, b),
+            }
+            return;
+        }
+        if let Some(digits) = self.goto.as_mut() {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() => {
+                    digits.push(c);
+                    return;
+                }
+                KeyCode::Backspace => {
+                    digits.pop();
+                    return;
+                }
+                KeyCode::Enter => {
+                    if let Ok(number) = digits.parse::<usize>() {
+                        self.go_to(number.saturating_sub(1));
+                    }
+                    self.goto = None;
+                    return;
+                }
+                KeyCode::Esc => {
+                    self.goto = None;
+                    return;
+                }
+                // Any other key drops the jump and does what it normally does
+                _ => self.goto = None,
+            }
+        }
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.should_quit = true;
             }
-            KeyCode::Right | KeyCode::Char('j') => {
-                if self.current_slide < self.slides.len() - 1 {
-                    self.current_slide += 1;
-                }
+            // Presentation remotes send PageDown/PageUp
+            KeyCode::Right
+            | KeyCode::Down
+            | KeyCode::PageDown
+            | KeyCode::Char(' ')
+            | KeyCode::Char('j') => {
+                self.go_to(self.current_slide + 1);
             }
-            KeyCode::Left | KeyCode::Char('k') => {
-                if self.current_slide > 0 {
-                    self.current_slide -= 1;
-                }
+            KeyCode::Left
+            | KeyCode::Up
+            | KeyCode::PageUp
+            | KeyCode::Backspace
+            | KeyCode::Char('k') => {
+                self.go_to(self.current_slide.saturating_sub(1));
             }
             KeyCode::Home => {
-                self.current_slide = 0;
+                self.go_to(0);
             }
             KeyCode::End => {
-                self.current_slide = self.slides.len().saturating_sub(1);
+                self.go_to(self.slides.len().saturating_sub(1));
+            }
+            KeyCode::Char('g') => {
+                self.goto = Some(String::new());
+            }
+            KeyCode::Char('p') => {
+                self.toggle_presenter();
+            }
+            KeyCode::Char('o') => {
+                self.overview = Some(SlideOverview::new(self.current_slide));
+            }
+            KeyCode::Char('r') if self.presenter => {
+                self.talk_started = Some(Instant::now());
+                self.slide_started = Instant::now();
+            }
+            KeyCode::Char('R') => {
+                self.toggle_rehearsal();
+            }
+            KeyCode::Char('a') => {
+                self.toggle_auto_advance();
             }
             KeyCode::Char('h') => {
                 self.show_help = !self.show_help;
             }
+            KeyCode::Char('s') => {
+                self.show_stats = !self.show_stats;
+            }
             _ => {}
         }
     }
 
-    fn ui(&self, f: &mut Frame) {
+    fn ui(&mut self, f: &mut Frame) {
+        if let Some(overview) = self.overview.as_mut() {
+            // The footer keeps showing the outcome of a reorder
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(f.area());
+            overview.render(f, chunks[0], &self.slides, self.current_slide);
+            let label = Paragraph::new(self.mode_label()).style(Style::default().fg(Color::Yellow));
+            f.render_widget(label, chunks[1]);
+            return;
+        }
+        if self.presenter {
+            self.presenter_ui(f);
+        } else {
+            self.preview_ui(f);
+        }
+        self.overlays_ui(f);
+    }
+
+    fn preview_ui(&self, f: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -107,49 +473,257 @@ impl SlidePreview {
             ])
             .split(f.area());
 
-        // Header with slide counter
-        let title = format!(
+        // Header with slide counter and the deck's settings
+        let mut title = format!(
             "Slide Preview ({}/{})",
             self.current_slide + 1,
             self.slides.len()
         );
+        let settings = [
+            self.settings.theme.clone(),
+            self.settings.aspect_ratio.map(|ratio| ratio.to_string()),
+            self.settings.language.clone(),
+        ];
+        for setting in settings.into_iter().flatten() {
+            title.push_str(&format!(" | {setting}"));
+        }
         let header = Paragraph::new(title)
-            .style(Style::default().fg(Color::Cyan))
+            .style(Style::default().fg(self.accent()))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(header, chunks[0]);
 
         // Current slide content
-        let slide_content = if !self.slides.is_empty() {
-            Text::from(self.slides[self.current_slide].as_str())
+        let slide_content = if let Some(slide) = self.slides.get(self.current_slide) {
+            Text::from(slide.content.as_str())
         } else {
             Text::from("No slides available")
         };
 
         let slide = Paragraph::new(slide_content)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Slide Content"),
-            )
+            .block(self.slide_block("Slide Content"))
             .wrap(ratatui::widgets::Wrap { trim: true });
-        f.render_widget(slide, chunks[1]);
+        f.render_widget(slide, self.slide_area(chunks[1]));
 
         // Footer (status bar style)
+        let timing = self
+            .stats
+            .slides
+            .get(self.current_slide)
+            .map(|s| {
+                format!(
+                    "~{} / {}{}",
+                    format_duration(s.seconds),
+                    format_duration(self.stats.total_seconds),
+                    if s.over_budget { " ⚠" } else { "" }
+                )
+            })
+            .unwrap_or_default();
+        let over_budget = self
+            .stats
+            .slides
+            .get(self.current_slide)
+            .is_some_and(|s| s.over_budget);
         let controls = format!(
-            "NORMAL | Slide {}/{} | ←/→ or j/k | Home/End | h:help | q:quit",
+            "{} | Slide {}/{} | {} | ←/→ or j/k | g<N>⏎ | o:overview | p:presenter | s:stats | h:help | q:quit",
+            self.mode_label(),
             self.current_slide + 1,
-            self.slides.len()
+            self.slides.len(),
+            timing
+        );
+        let footer = Paragraph::new(controls)
+            .style(Style::default().fg(if over_budget {
+                Color::Red
+            } else {
+                Color::Yellow
+            }))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(footer, chunks[2]);
+    }
+
+    /// Color of the deck's theme: Marp's built-in themes get their own,
+    /// other themes the default.
+    fn accent(&self) -> Color {
+        match self.settings.theme.as_deref() {
+            Some("gaia") => Color::Yellow,
+            Some("uncover") => Color::Magenta,
+            _ => Color::Cyan,
+        }
+    }
+
+    /// Frame of the slide on screen, with the deck's footer and, when the
+    /// deck is paginated, the slide number at the bottom.
+    fn slide_block(&self, title: &str) -> Block<'static> {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.accent()))
+            .title(title.to_string());
+        if let Some(footer) = &self.settings.footer {
+            block = block.title_bottom(Line::from(footer.clone()).left_aligned());
+        }
+        if self.settings.paginate {
+            block = block
+                .title_bottom(Line::from((self.current_slide + 1).to_string()).right_aligned());
+        }
+        block
+    }
+
+    /// Part of `area` the slide takes, centered, at the deck's aspect ratio.
+    fn slide_area(&self, area: Rect) -> Rect {
+        match self.settings.aspect_ratio {
+            Some(ratio) => fit_aspect(area, ratio),
+            None => area,
+        }
+    }
+
+    /// `NORMAL`, `REC` while rehearsing, `AUTO m:ss` with the time left
+    /// before auto-advance, or the slide number being typed after `g`;
+    /// followed by the outcome of the last key.
+    fn mode_label(&self) -> String {
+        let mut label = match &self.goto {
+            Some(digits) => format!("GO TO {digits}_"),
+            None => {
+                let mut modes = Vec::new();
+                if self.rehearsal.is_some() {
+                    modes.push("REC".to_string());
+                }
+                if let Some(left) = self.auto_advance_left() {
+                    modes.push(format!("AUTO {}", format_duration(left.as_secs() as u32)));
+                } else if self.auto_advance {
+                    modes.push("AUTO".to_string());
+                }
+                if modes.is_empty() {
+                    "NORMAL".to_string()
+                } else {
+                    modes.join(" ")
+                }
+            }
+        };
+        if let Some(status) = &self.status {
+            label.push_str(&format!(" | {status}"));
+        }
+        label
+    }
+
+    /// Current slide, the next one, speaker notes and the talk and slide
+    /// timers against the estimates of the deck stats.
+    fn presenter_ui(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(f.area());
+
+        let elapsed = self
+            .talk_started
+            .map_or(0, |started| started.elapsed().as_secs() as u32);
+        let on_slide = self.slide_started.elapsed().as_secs() as u32;
+        // The configured budget of a slide, or its rehearsed or estimated time
+        let slide_target = self.stats.slides.get(self.current_slide).map(|s| {
+            self.stats
+                .slide_budget_secs
+                .or(s.rehearsed_secs)
+                .unwrap_or(s.seconds)
+        });
+        let talk_target = match self.stats.rehearsed_seconds() {
+            0 => self.stats.total_seconds,
+            rehearsed => rehearsed,
+        };
+        let over_time =
+            elapsed > talk_target || slide_target.is_some_and(|target| on_slide > target);
+        let header = format!(
+            "Presenter | Slide {}/{} | Elapsed {} / ~{} | This slide {}{}",
+            self.current_slide + 1,
+            self.slides.len(),
+            format_duration(elapsed),
+            format_duration(talk_target),
+            format_duration(on_slide),
+            slide_target
+                .map(|target| format!(" / ~{}", format_duration(target)))
+                .unwrap_or_default(),
+        );
+        let header = Paragraph::new(header)
+            .style(Style::default().fg(if over_time { Color::Red } else { Color::Cyan }))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(header, chunks[0]);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(columns[1]);
+
+        let current = self.slides.get(self.current_slide);
+        let current_text = current.map(Slide::visible_content).unwrap_or_default();
+        let current_panel = Paragraph::new(current_text)
+            .block(self.slide_block("Current"))
+            .wrap(ratatui::widgets::Wrap { trim: true });
+        f.render_widget(current_panel, self.slide_area(columns[0]));
+
+        let next = self.slides.get(self.current_slide + 1);
+        let next_title = match next {
+            Some(slide) => format!("Next: {}", slide.title.as_deref().unwrap_or("(untitled)")),
+            None => "Next".to_string(),
+        };
+        let next_text = next.map_or_else(|| "End of deck".to_string(), Slide::visible_content);
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        render_panel(f, side[0], &next_title, Text::from(next_text), dim);
+
+        let (notes, style) = match current.and_then(Slide::notes) {
+            Some(notes) => (notes, Style::default()),
+            None => ("No notes for this slide".to_string(), dim),
+        };
+        render_panel(f, side[1], "Notes", Text::from(notes), style);
+
+        let controls = format!(
+            "{} | ←/→ or Space/PgUp/PgDn | g<N>⏎: go to slide | r: restart timers | R: rehearse | a: auto | p: preview | h:help | q:quit",
+            self.mode_label()
         );
         let footer = Paragraph::new(controls)
             .style(Style::default().fg(Color::Yellow))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(footer, chunks[2]);
+    }
+
+    fn overlays_ui(&self, f: &mut Frame) {
+        // Deck statistics panel
+        if self.show_stats {
+            let area = centered_rect(70, 70, f.area());
+            let rendered = self.stats.render_lines();
+            // Per-slide rows come last, one per slide.
+            let first_row = rendered.len() - self.stats.slides.len();
+            let lines: Vec<Line> = rendered
+                .into_iter()
+                .enumerate()
+                .map(|(i, l)| {
+                    let style = if i == 0 {
+                        Style::default().fg(Color::Cyan)
+                    } else if i >= first_row && self.stats.slides[i - first_row].over_budget {
+                        Style::default().fg(Color::Red)
+                    } else if i == first_row + self.current_slide {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    };
+                    Line::styled(l, style)
+                })
+                .collect();
+            let panel = Paragraph::new(Text::from(lines))
+                .block(Block::default().borders(Borders::ALL).title("Deck Stats"));
+            f.render_widget(Clear, area);
+            f.render_widget(panel, area);
+        }
 
         // Help modal
         if self.show_help {
             let area = centered_rect(60, 60, f.area());
             let help = Paragraph::new(Text::from(
-                "Preview Help\n\nNavigation:\n  ←/→ or j/k: Prev/Next slide\n  Home/End: First/Last slide\n  h: Toggle help\n  q: Quit preview",
+                "Preview Help\n\nNavigation:\n  ←/→, j/k, Space or PgUp/PgDn: Prev/Next slide\n  Home/End: First/Last slide\n  g<N> Enter: Go to slide N\n  o: Overview of all slides; / to find one, </> to move it\n\nPresenting:\n  p: Toggle presenter mode (next slide, notes, timers)\n  r: Restart the timers\n  R: Start/stop recording a rehearsal; timings are saved next to the deck\n  a: Auto-advance after the rehearsed times\n  Notes are the slide's <!-- comments -->\n\n  s: Toggle deck stats\n  h: Toggle help\n  q: Quit preview",
             ))
             .block(Block::default().borders(Borders::ALL).title("Help"));
             f.render_widget(Clear, area);
@@ -158,6 +732,40 @@ impl SlidePreview {
     }
 }
 
+fn render_panel(f: &mut Frame, area: Rect, title: &str, text: Text, style: Style) {
+    let panel = Paragraph::new(text)
+        .style(style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title.to_string()),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(panel, area);
+}
+
+/// Largest rect of `ratio` centered in `area`. Terminal cells are about
+/// twice as tall as they are wide.
+fn fit_aspect(area: Rect, ratio: AspectRatio) -> Rect {
+    let (w, h) = (u32::from(ratio.width), u32::from(ratio.height));
+    let width = u32::from(area.height) * 2 * w / h;
+    if width <= u32::from(area.width) {
+        let width = width as u16;
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            width,
+            ..area
+        }
+    } else {
+        let height = (u32::from(area.width) * h / (2 * w)) as u16;
+        Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..area
+        }
+    }
+}
+
 fn centered_rect(
     percent_x: u16,
     percent_y: u16,
@@ -184,3 +792,163 @@ fn centered_rect(
 
     horizontal[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn slides_are_reached_by_number_and_by_remote() {
+        let deck = Deck::parse(&(1..=12).map(|i| format!("## S{i}\n")).collect::<String>());
+        let mut preview = SlidePreview::new(deck, &PresentationConfig::default()).presenter();
+        assert!(preview.presenter && preview.talk_started.is_some());
+
+        for code in [
+            KeyCode::Char('g'),
+            KeyCode::Char('1'),
+            KeyCode::Char('0'),
+            KeyCode::Enter,
+        ] {
+            preview.handle_key_event(key(code));
+        }
+        assert_eq!(preview.current_slide(), 9);
+        preview.handle_key_event(key(KeyCode::PageDown));
+        preview.handle_key_event(key(KeyCode::Char(' ')));
+        assert_eq!(preview.current_slide(), 11);
+        preview.handle_key_event(key(KeyCode::PageUp));
+        assert_eq!(preview.current_slide(), 10);
+
+        // Past the end is the last slide; Esc drops the jump without quitting
+        for code in [
+            KeyCode::Char('g'),
+            KeyCode::Char('9'),
+            KeyCode::Char('9'),
+            KeyCode::Enter,
+        ] {
+            preview.handle_key_event(key(code));
+        }
+        assert_eq!(preview.current_slide(), 11);
+        for code in [KeyCode::Char('g'), KeyCode::Char('2'), KeyCode::Esc] {
+            preview.handle_key_event(key(code));
+        }
+        assert_eq!(preview.current_slide(), 11);
+        assert!(!preview.should_quit);
+        // Another key ends the jump and acts as usual
+        for code in [KeyCode::Char('g'), KeyCode::Char('3'), KeyCode::Left] {
+            preview.handle_key_event(key(code));
+        }
+        assert_eq!(preview.current_slide(), 10);
+        assert_eq!(preview.goto, None);
+    }
+
+    #[test]
+    fn rehearsals_are_saved_next_to_the_deck_and_drive_auto_advance() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-rehearsal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("talk.md");
+        let deck = Deck::parse("## One\n- a\n\n## Two\n- b\n");
+        let mut preview =
+            SlidePreview::new(deck, &PresentationConfig::default()).watch(path.clone());
+        preview.handle_key_event(key(KeyCode::Char('a')));
+        assert!(!preview.auto_advance);
+
+        preview.handle_key_event(key(KeyCode::Char('R')));
+        assert!(preview.mode_label().starts_with("REC"));
+        preview.handle_key_event(key(KeyCode::Right));
+        preview.handle_key_event(key(KeyCode::Char('R')));
+        let report = TimingReport::load(&path);
+        assert_eq!(report.map(|r| r.slides.len()), Some(2));
+        assert!(preview.stats.rehearsed_at.is_some());
+
+        // A slide rehearsed in under a second has no time to advance after
+        preview.handle_key_event(key(KeyCode::Home));
+        preview.handle_key_event(key(KeyCode::Char('a')));
+        assert!(preview.auto_advance);
+        assert_eq!(preview.auto_advance_left(), None);
+        if let Some(timings) = preview.timings.as_mut() {
+            timings.slides[0].seconds = 1;
+        }
+        preview.refresh_stats();
+        preview.slide_started = Instant::now() - Duration::from_secs(2);
+        preview.advance_if_due();
+        assert_eq!(preview.current_slide(), 1);
+        // Nothing follows the last slide
+        assert_eq!(preview.auto_advance_left(), None);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn frontmatter_frames_the_slide() -> Result<()> {
+        let deck = Deck::parse(
+            "---\ntheme: gaia\nsize: 4:3\npaginate: true\nfooter: ACME\n---\n# Deck\n\n## One\n",
+        );
+        let mut preview = SlidePreview::new(deck, &PresentationConfig::default()).at_slide(1);
+        assert_eq!(preview.slides.len(), 2);
+        // 30 rows at 4:3 take 80 columns; 40 columns take 15 rows
+        assert_eq!(
+            preview.slide_area(Rect::new(0, 0, 100, 30)),
+            Rect::new(10, 0, 80, 30)
+        );
+        assert_eq!(
+            preview.slide_area(Rect::new(0, 0, 40, 30)),
+            Rect::new(0, 7, 40, 15)
+        );
+
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(100, 30))?;
+        terminal.draw(|f| preview.ui(f))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Slide Preview (2/2) | gaia | 4:3"));
+        assert!(screen.contains("ACME"));
+        Ok(())
+    }
+
+    #[test]
+    fn slides_moved_in_the_overview_are_written_back_to_the_deck() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-overview-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("talk.md");
+        let markdown = "# Talk\n\n## One\n- a\n\n## Two\n- b\n\n## Three\n- c\n";
+        std::fs::write(&path, markdown)?;
+        let mut preview = SlidePreview::new(Deck::parse(markdown), &PresentationConfig::default())
+            .watch(path.clone());
+        preview.go_to(1);
+
+        for code in [KeyCode::Char('o'), KeyCode::Char('>')] {
+            preview.handle_key_event(key(code));
+        }
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "# Talk\n\n## Two\n- b\n\n## One\n- a\n\n## Three\n- c\n"
+        );
+        assert_eq!(preview.slides[2].title.as_deref(), Some("One"));
+        // The slide on screen moved with the swap
+        assert_eq!(preview.current_slide(), 2);
+
+        // Enter opens the selected slide and closes the overview
+        preview.handle_key_event(key(KeyCode::Enter));
+        assert!(preview.overview.is_none());
+        assert_eq!(preview.current_slide(), 2);
+
+        // Without a deck file there is nothing to write
+        let mut detached = SlidePreview::new(Deck::parse(markdown), &PresentationConfig::default());
+        for code in [KeyCode::Char('o'), KeyCode::Char('>')] {
+            detached.handle_key_event(key(code));
+        }
+        assert!(detached
+            .status
+            .is_some_and(|s| s.starts_with("Could not reorder")));
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::time::{sleep, Duration};
+
+use crate::session_store::{SessionStore, SessionSummary};
+
+/// Messages shown in the preview of the selected session
+const PREVIEW_MESSAGES: usize = 6;
+
+/// What the user picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SessionChoice {
+    New,
+    Resume(String),
+    OpenDeck(PathBuf),
+}
+
+enum Entry {
+    New,
+    Session(SessionSummary),
+    Deck(String),
+}
+
+/// Welcome screen listing recent sessions and decks. Enter resumes a
+/// session or opens a deck, `n` starts a new session and `d` deletes the
+/// selected session after a second `d`.
+pub(crate) struct SessionPicker {
+    store: SessionStore,
+    entries: Vec<Entry>,
+    selected: usize,
+    confirm_delete: bool,
+    status: String,
+    choice: Option<SessionChoice>,
+    should_quit: bool,
+}
+
+impl SessionPicker {
+    pub(crate) fn new(store: SessionStore, recent_decks: &[String]) -> Self {
+        let mut entries = vec![Entry::New];
+        entries.extend(store.list().into_iter().map(Entry::Session));
+        entries.extend(recent_decks.iter().cloned().map(Entry::Deck));
+        Self {
+            store,
+            entries,
+            selected: 0,
+            confirm_delete: false,
+            status: String::new(),
+            choice: None,
+            should_quit: false,
+        }
+    }
+
+    /// Whether there is anything to pick besides a new session.
+    pub(crate) fn has_history(&self) -> bool {
+        self.entries.len() > 1
+    }
+
+    /// Show the picker; `None` when it was closed without a choice.
+    pub(crate) async fn run(&mut self) -> Result<Option<SessionChoice>> {
+        // Also opened from the inline app, which already runs in raw mode
+        let was_raw = is_raw_mode_enabled()?;
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        loop {
+            terminal.draw(|f| self.ui(f))?;
+
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key_event(key);
+                    }
+                }
+            }
+
+            if self.should_quit {
+                break;
+            }
+
+            sleep(Duration::from_millis(16)).await;
+        }
+
+        if !was_raw {
+            disable_raw_mode()?;
+        }
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+
+        Ok(self.choice.take())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        let delete_armed = std::mem::take(&mut self.confirm_delete);
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('q') | KeyCode::Esc, _)
+            | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            (KeyCode::Down | KeyCode::Char('j'), _) if self.selected + 1 < self.entries.len() => {
+                self.selected += 1;
+            }
+            (KeyCode::Up | KeyCode::Char('k'), _) if self.selected > 0 => {
+                self.selected -= 1;
+            }
+            (KeyCode::Char('n'), _) => self.choose(SessionChoice::New),
+            (KeyCode::Enter, _) => {
+                let choice = match &self.entries[self.selected] {
+                    Entry::New => SessionChoice::New,
+                    Entry::Session(s) => SessionChoice::Resume(s.id.clone()),
+                    Entry::Deck(path) => SessionChoice::OpenDeck(PathBuf::from(path)),
+                };
+                self.choose(choice);
+            }
+            (KeyCode::Char('d'), _) => {
+                let Some(Entry::Session(session)) = self.entries.get(self.selected) else {
+                    return;
+                };
+                if !delete_armed {
+                    self.confirm_delete = true;
+                    self.status = format!("Press d again to delete \"{}\"", session.title());
+                    return;
+                }
+                self.status = match self.store.delete(&session.id) {
+                    Ok(()) => {
+                        self.entries.remove(self.selected);
+                        self.selected = self.selected.min(self.entries.len() - 1);
+                        "Session deleted".to_string()
+                    }
+                    Err(e) => format!("Delete failed: {e}"),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn choose(&mut self, choice: SessionChoice) {
+        self.choice = Some(choice);
+        self.should_quit = true;
+    }
+
+    fn ui(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(f.area());
+
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let header = Paragraph::new(Line::from(vec![
+            Span::styled(
+                "Welcome to Slide Code",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {}", cwd.display()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]))
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(header, chunks[0]);
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Min(0)])
+            .split(chunks[1]);
+
+        let now = SystemTime::now();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::New => ListItem::new(Span::styled(
+                    "+ New session",
+                    Style::default().fg(Color::Green),
+                )),
+                Entry::Session(s) => ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>8}  ", format_age(now, s.updated)),
+                        Style::default().fg(Color::Gray),
+                    ),
+                    Span::raw(s.title().to_string()),
+                ])),
+                Entry::Deck(path) => ListItem::new(Line::from(vec![
+                    Span::styled("    deck  ", Style::default().fg(Color::Gray)),
+                    Span::styled(path.clone(), Style::default().fg(Color::Magenta)),
+                ])),
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Sessions and decks"),
+            )
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        f.render_stateful_widget(list, body[0], &mut state);
+
+        let preview = match &self.entries[self.selected] {
+            Entry::New => Text::from("Start a new conversation."),
+            Entry::Deck(path) => Text::from(format!("Open {path} in the preview.")),
+            Entry::Session(s) => {
+                let skip = s.messages.len().saturating_sub(PREVIEW_MESSAGES);
+                let mut lines = Vec::new();
+                if skip > 0 {
+                    lines.push(Line::styled(
+                        format!("… {skip} earlier messages"),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                for m in &s.messages[skip..] {
+                    let (label, color) = if m.role == "user" {
+                        ("You", Color::Cyan)
+                    } else {
+                        ("Assistant", Color::Green)
+                    };
+                    lines.push(Line::styled(
+                        label,
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ));
+                    lines.extend(m.text.lines().map(|l| Line::from(l.to_string())));
+                    lines.push(Line::from(""));
+                }
+                Text::from(lines)
+            }
+        };
+        let preview = Paragraph::new(preview)
+            .block(Block::default().borders(Borders::ALL).title("Preview"))
+            .wrap(Wrap { trim: false });
+        f.render_widget(preview, body[1]);
+
+        let controls = if self.status.is_empty() {
+            "↑/↓ or j/k: select | Enter: resume/open | n: new | d: delete | q: close".to_string()
+        } else {
+            self.status.clone()
+        };
+        let footer = Paragraph::new(controls)
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(footer, chunks[2]);
+    }
+}
+
+fn format_age(now: SystemTime, then: SystemTime) -> String {
+    let secs = now.duration_since(then).unwrap_or_default().as_secs();
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
@@ -0,0 +1,185 @@
+//! Persisted chat sessions, listed by the session picker on startup and by
+//! `/sessions`.
+use std::fs::OpenOptions;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use crate::history_store::{extract_string_field, home_dir, json_escape};
+
+#[derive(Clone, Debug)]
+pub(crate) struct SessionStore {
+    dir: PathBuf,
+    /// Nothing is written, e.g. while a recorded session is replayed
+    read_only: bool,
+}
+
+/// One message of a session, as recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SessionMessage {
+    /// `user` or `assistant`
+    pub role: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SessionSummary {
+    pub id: String,
+    pub updated: SystemTime,
+    pub messages: Vec<SessionMessage>,
+}
+
+impl SessionSummary {
+    /// First thing the user asked, used as the session title.
+    pub fn title(&self) -> &str {
+        self.messages
+            .iter()
+            .find(|m| m.role == "user")
+            .map_or("(empty session)", |m| m.text.lines().next().unwrap_or(""))
+    }
+}
+
+impl SessionStore {
+    pub fn default() -> Self {
+        Self::new(home_dir().join(".slide").join("sessions"))
+    }
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            read_only: false,
+        }
+    }
+
+    pub fn read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn enabled(&self) -> bool {
+        slide_common::privacy::active().history_enabled()
+    }
+
+    fn session_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.jsonl"))
+    }
+
+    /// Scrollback of the session that no longer fits in memory; see
+    /// [`crate::transcript_store::TranscriptStore`].
+    pub fn transcript_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.transcript"))
+    }
+
+    /// Identifier for a new session; the file is created by the first append.
+    pub fn new_session_id() -> String {
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        format!("{secs}-{}", std::process::id())
+    }
+
+    /// Append a message to the session. Best-effort; errors are returned.
+    pub fn append(&self, id: &str, role: &str, text: &str) -> std::io::Result<()> {
+        if self.read_only || !self.enabled() || text.trim().is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let line = format!(
+            "{{\"role\":{},\"text\":{}}}\n",
+            json_escape(role),
+            json_escape(text)
+        );
+        let mut opts = OpenOptions::new();
+        opts.append(true).create(true);
+        #[cfg(unix)]
+        {
+            opts.mode(0o600);
+        }
+        let mut f = opts.open(self.session_path(id))?;
+        f.write_all(line.as_bytes())?;
+        f.flush()
+    }
+
+    pub fn load(&self, id: &str) -> Vec<SessionMessage> {
+        let Ok(f) = std::fs::File::open(self.session_path(id)) else {
+            return Vec::new();
+        };
+        std::io::BufReader::new(f)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| {
+                Some(SessionMessage {
+                    role: extract_string_field(&line, "role")?,
+                    text: extract_string_field(&line, "text")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Sessions with at least one message, most recently updated first.
+    pub fn list(&self) -> Vec<SessionSummary> {
+        if !self.enabled() {
+            return Vec::new();
+        }
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<SessionSummary> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                    return None;
+                }
+                let id = path.file_stem()?.to_str()?.to_string();
+                let updated = entry.metadata().ok()?.modified().ok()?;
+                let messages = self.load(&id);
+                (!messages.is_empty()).then_some(SessionSummary {
+                    id,
+                    updated,
+                    messages,
+                })
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated));
+        sessions
+    }
+
+    pub fn delete(&self, id: &str) -> std::io::Result<()> {
+        std::fs::remove_file(self.session_path(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_round_trip_and_can_be_deleted() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("slide-sessions-{}", std::process::id()));
+        let store = SessionStore::new(dir.clone());
+        store.append("1-1", "user", "make a deck\nabout \"Rust\"")?;
+        store.append("1-1", "assistant", "Saved to slides/draft.md")?;
+        store.append("2-1", "user", "   ")?;
+
+        let sessions = store.list();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title(), "make a deck");
+        assert_eq!(sessions[0].messages[0].text, "make a deck\nabout \"Rust\"");
+        assert_eq!(sessions[0].messages[1].role, "assistant");
+
+        store.delete("1-1")?;
+        assert!(store.list().is_empty());
+        std::fs::remove_dir_all(dir)
+    }
+}
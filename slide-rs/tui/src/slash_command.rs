@@ -0,0 +1,613 @@
+//! Slash commands typed into the composer (`/model gpt-4o`, `/undo`, ...).
+//!
+//! Each command parses its arguments into a [`SlashAction`] which the App
+//! dispatches. New commands implement [`SlashCommand`] and are added to the
+//! [`SlashCommandRegistry`].
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use slide_common::i18n::{t, Msg};
+use slide_common::ApprovalMode;
+use slide_core::event_log::ExportFormat;
+
+/// App action requested by a slash command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashAction {
+    /// Create a new deck from the template
+    NewSlide,
+    /// Open a deck in the preview (default: most recent deck)
+    Preview(Option<String>),
+    /// Search the workspace for a file to preview
+    OpenFile,
+    /// Use another model for the following turns
+    SetModel(String),
+    /// Change the approval policy
+    SetApprovals(ApprovalMode),
+    /// Show what changed in a deck since its previous snapshot
+    Diff(Option<String>),
+    /// Restore the snapshot before the current version of a deck
+    Undo(Option<String>),
+    /// Put back every file the agent's last turn changed
+    UndoTurn,
+    /// Toggle the live deck pane beside the composer
+    Split(Option<String>),
+    /// Pick a saved session to resume, or start a new one
+    Sessions,
+    /// Resubmit the last prompt, or the given edited one
+    Retry(Option<String>),
+    /// Draft a commit message for the current diff, or commit with the
+    /// given one
+    Commit(Option<String>),
+    /// Copy part of the transcript to the clipboard
+    Copy(CopyTarget),
+    /// Write the session as a markdown or HTML report, to `path` or
+    /// `session-<id>.<ext>`
+    ExportChat {
+        format: ExportFormat,
+        path: Option<String>,
+    },
+    /// Run the agent's commands in another directory
+    SetCwd(String),
+    /// Hold the agent before its next tool call
+    Pause,
+    /// Let a paused agent go on
+    Resume,
+    /// List the prompt templates in `~/.slide/prompts`
+    Prompts,
+    Help,
+}
+
+/// What `/copy` puts on the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyTarget {
+    /// The agent's last reply
+    Reply,
+    /// Output of the n-th most recent command, 1 being the last
+    Exec(usize),
+    /// Changes of the current turn as a unified diff
+    Diff,
+}
+
+pub trait SlashCommand: Send + Sync {
+    /// Name without the leading `/`
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Argument hint shown in `/help`, e.g. `<model>`
+    fn usage(&self) -> &'static str {
+        ""
+    }
+
+    /// Completions for the argument being typed.
+    fn complete(&self, _arg: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String>;
+}
+
+/// Set of available slash commands, in popup order.
+#[derive(Clone)]
+pub struct SlashCommandRegistry {
+    commands: Vec<Arc<dyn SlashCommand>>,
+}
+
+impl Default for SlashCommandRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(SimpleCommand {
+            name: "new",
+            description: Msg::CmdNew,
+            action: SlashAction::NewSlide,
+        });
+        registry.register(DeckCommand {
+            name: "preview",
+            description: Msg::CmdPreview,
+            action: SlashAction::Preview,
+        });
+        registry.register(SimpleCommand {
+            name: "open",
+            description: Msg::CmdOpen,
+            action: SlashAction::OpenFile,
+        });
+        registry.register(ChoiceCommand {
+            name: "model",
+            description: Msg::CmdModel,
+            usage: "<model>",
+            choices: KNOWN_MODELS,
+            action: SlashAction::SetModel,
+        });
+        registry.register(ApprovalsCommand);
+        registry.register(DeckCommand {
+            name: "diff",
+            description: Msg::CmdDiff,
+            action: SlashAction::Diff,
+        });
+        registry.register(DeckCommand {
+            name: "undo",
+            description: Msg::CmdUndo,
+            action: SlashAction::Undo,
+        });
+        registry.register(SimpleCommand {
+            name: "undo-turn",
+            description: Msg::CmdUndoTurn,
+            action: SlashAction::UndoTurn,
+        });
+        registry.register(DeckCommand {
+            name: "split",
+            description: Msg::CmdSplit,
+            action: SlashAction::Split,
+        });
+        registry.register(SimpleCommand {
+            name: "sessions",
+            description: Msg::CmdSessions,
+            action: SlashAction::Sessions,
+        });
+        registry.register(PromptCommand {
+            name: "retry",
+            description: Msg::CmdRetry,
+            usage: "[prompt]",
+            action: SlashAction::Retry,
+        });
+        registry.register(PromptCommand {
+            name: "commit",
+            description: Msg::CmdCommit,
+            usage: "[message]",
+            action: SlashAction::Commit,
+        });
+        registry.register(CopyCommand);
+        registry.register(ExportChatCommand);
+        registry.register(CdCommand);
+        registry.register(SimpleCommand {
+            name: "pause",
+            description: Msg::CmdPause,
+            action: SlashAction::Pause,
+        });
+        registry.register(SimpleCommand {
+            name: "resume",
+            description: Msg::CmdResume,
+            action: SlashAction::Resume,
+        });
+        registry.register(SimpleCommand {
+            name: "prompts",
+            description: Msg::CmdPrompts,
+            action: SlashAction::Prompts,
+        });
+        registry.register(SimpleCommand {
+            name: "help",
+            description: Msg::CmdHelp,
+            action: SlashAction::Help,
+        });
+        registry
+    }
+}
+
+impl SlashCommandRegistry {
+    pub fn empty() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    /// Add a command; a command with the same name is replaced.
+    pub fn register(&mut self, command: impl SlashCommand + 'static) {
+        self.commands.retain(|c| c.name() != command.name());
+        self.commands.push(Arc::new(command));
+    }
+
+    pub fn commands(&self) -> &[Arc<dyn SlashCommand>] {
+        &self.commands
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn SlashCommand>> {
+        self.commands.iter().find(|c| c.name() == name)
+    }
+
+    /// Commands whose name starts with `prefix`.
+    pub fn matching(&self, prefix: &str) -> Vec<&Arc<dyn SlashCommand>> {
+        let prefix = prefix.to_ascii_lowercase();
+        self.commands
+            .iter()
+            .filter(|c| c.name().starts_with(&prefix))
+            .collect()
+    }
+
+    /// Parse composer text. `None` when the text is not a registered command,
+    /// so it can be sent to the agent as is (e.g. `/slide ...`).
+    pub fn parse(&self, text: &str) -> Option<Result<SlashAction, String>> {
+        let (name, args) = split_command(text)?;
+        let command = self.get(name)?;
+        Some(command.parse(args))
+    }
+}
+
+/// Split `/name args` into its parts.
+pub fn split_command(text: &str) -> Option<(&str, &str)> {
+    let rest = text.trim().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((name, args.trim()))
+}
+
+pub const KNOWN_MODELS: &[&str] = &["gpt-4o", "gpt-4o-mini", "gpt-4.1", "gpt-5", "o3", "o4-mini"];
+
+/// Command without arguments.
+struct SimpleCommand {
+    name: &'static str,
+    description: Msg,
+    action: SlashAction,
+}
+
+impl SlashCommand for SimpleCommand {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        t(self.description)
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        if args.is_empty() {
+            Ok(self.action.clone())
+        } else {
+            Err(format!("/{} takes no arguments", self.name))
+        }
+    }
+}
+
+/// Command with one argument picked from a list of choices.
+struct ChoiceCommand {
+    name: &'static str,
+    description: Msg,
+    usage: &'static str,
+    /// Values offered for completion; others are accepted too
+    choices: &'static [&'static str],
+    action: fn(String) -> SlashAction,
+}
+
+impl SlashCommand for ChoiceCommand {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        t(self.description)
+    }
+
+    fn usage(&self) -> &'static str {
+        self.usage
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        self.choices
+            .iter()
+            .filter(|c| c.starts_with(arg))
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        if args.is_empty() {
+            return Err(format!("usage: /{} {}", self.name, self.usage));
+        }
+        Ok((self.action)(args.to_string()))
+    }
+}
+
+/// `/approvals <policy>`
+struct ApprovalsCommand;
+
+impl SlashCommand for ApprovalsCommand {
+    fn name(&self) -> &'static str {
+        "approvals"
+    }
+
+    fn description(&self) -> &'static str {
+        t(Msg::CmdApprovals)
+    }
+
+    fn usage(&self) -> &'static str {
+        "<policy>"
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        ApprovalMode::NAMES
+            .iter()
+            .filter(|name| name.starts_with(arg))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        if args.is_empty() {
+            return Err("usage: /approvals <policy>".to_string());
+        }
+        args.parse()
+            .map(SlashAction::SetApprovals)
+            .map_err(|e| format!("/approvals: {e}"))
+    }
+}
+
+/// Command taking an optional deck name or path.
+struct DeckCommand {
+    name: &'static str,
+    description: Msg,
+    action: fn(Option<String>) -> SlashAction,
+}
+
+impl SlashCommand for DeckCommand {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        t(self.description)
+    }
+
+    fn usage(&self) -> &'static str {
+        "[deck]"
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        let Ok(project) = slide_common::Project::open(PathBuf::from("slides")) else {
+            return Vec::new();
+        };
+        project
+            .manifest
+            .decks
+            .iter()
+            .map(|d| d.name.clone())
+            .filter(|name| name.starts_with(arg))
+            .collect()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        let deck = (!args.is_empty()).then(|| args.to_string());
+        Ok((self.action)(deck))
+    }
+}
+
+/// Command taking optional free text, e.g. a prompt.
+struct PromptCommand {
+    name: &'static str,
+    description: Msg,
+    usage: &'static str,
+    action: fn(Option<String>) -> SlashAction,
+}
+
+impl SlashCommand for PromptCommand {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        t(self.description)
+    }
+
+    fn usage(&self) -> &'static str {
+        self.usage
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        let prompt = (!args.is_empty()).then(|| args.to_string());
+        Ok((self.action)(prompt))
+    }
+}
+
+/// `/cd <dir>`, completing directory names.
+struct CdCommand;
+
+impl SlashCommand for CdCommand {
+    fn name(&self) -> &'static str {
+        "cd"
+    }
+
+    fn description(&self) -> &'static str {
+        t(Msg::CmdCd)
+    }
+
+    fn usage(&self) -> &'static str {
+        "<dir>"
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        let (parent, prefix) = match arg.rfind('/') {
+            Some(i) => (&arg[..=i], &arg[i + 1..]),
+            None => ("", arg),
+        };
+        let dir = if parent.is_empty() { "." } else { parent };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut dirs: Vec<String> = entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with(prefix) && !name.starts_with('.'))
+            .map(|name| format!("{parent}{name}/"))
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        if args.is_empty() {
+            return Err("usage: /cd <dir>".to_string());
+        }
+        Ok(SlashAction::SetCwd(args.to_string()))
+    }
+}
+
+/// `/copy [reply|exec [n]|diff]`
+struct CopyCommand;
+
+impl SlashCommand for CopyCommand {
+    fn name(&self) -> &'static str {
+        "copy"
+    }
+
+    fn description(&self) -> &'static str {
+        t(Msg::CmdCopy)
+    }
+
+    fn usage(&self) -> &'static str {
+        "[reply|exec [n]|diff]"
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        ["reply", "exec", "diff"]
+            .iter()
+            .filter(|c| c.starts_with(arg))
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        let mut words = args.split_whitespace();
+        let target = match (words.next(), words.next(), words.next()) {
+            (None | Some("reply"), None, None) => CopyTarget::Reply,
+            (Some("diff"), None, None) => CopyTarget::Diff,
+            (Some("exec"), None, None) => CopyTarget::Exec(1),
+            (Some("exec"), Some(n), None) => match n.parse::<usize>() {
+                Ok(n) if n > 0 => CopyTarget::Exec(n),
+                _ => return Err(format!("/copy exec: expected a positive number, got {n}")),
+            },
+            _ => return Err(format!("usage: /copy {}", self.usage())),
+        };
+        Ok(SlashAction::Copy(target))
+    }
+}
+
+/// `/export-chat [markdown|html] [path]`; without a format the path's
+/// extension decides, markdown by default.
+struct ExportChatCommand;
+
+impl SlashCommand for ExportChatCommand {
+    fn name(&self) -> &'static str {
+        "export-chat"
+    }
+
+    fn description(&self) -> &'static str {
+        t(Msg::CmdExportChat)
+    }
+
+    fn usage(&self) -> &'static str {
+        "[markdown|html] [path]"
+    }
+
+    fn complete(&self, arg: &str) -> Vec<String> {
+        ["markdown", "html"]
+            .iter()
+            .filter(|c| c.starts_with(arg))
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    fn parse(&self, args: &str) -> Result<SlashAction, String> {
+        let (first, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let (format, path) = match ExportFormat::from_name(first) {
+            Some(format) => (format, rest.trim()),
+            None => {
+                let format = ExportFormat::from_path(std::path::Path::new(args))
+                    .unwrap_or(ExportFormat::Markdown);
+                (format, args)
+            }
+        };
+        let path = (!path.is_empty()).then(|| path.to_string());
+        Ok(SlashAction::ExportChat { format, path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_builtin_commands() {
+        let registry = SlashCommandRegistry::default();
+        assert_eq!(
+            registry.parse("/model gpt-4o"),
+            Some(Ok(SlashAction::SetModel("gpt-4o".into())))
+        );
+        assert_eq!(registry.parse("/undo"), Some(Ok(SlashAction::Undo(None))));
+        assert_eq!(
+            registry.parse("/undo-turn"),
+            Some(Ok(SlashAction::UndoTurn))
+        );
+        assert_eq!(
+            registry.parse("/retry make it shorter"),
+            Some(Ok(SlashAction::Retry(Some("make it shorter".into()))))
+        );
+        assert_eq!(
+            registry.parse("/commit"),
+            Some(Ok(SlashAction::Commit(None)))
+        );
+        assert_eq!(
+            registry.parse("/commit fix: typo\n\nBody"),
+            Some(Ok(SlashAction::Commit(Some("fix: typo\n\nBody".into()))))
+        );
+        assert_eq!(registry.parse("/open"), Some(Ok(SlashAction::OpenFile)));
+        assert_eq!(
+            registry.parse("/export-chat"),
+            Some(Ok(SlashAction::ExportChat {
+                format: ExportFormat::Markdown,
+                path: None,
+            }))
+        );
+        assert_eq!(
+            registry.parse("/export-chat html"),
+            Some(Ok(SlashAction::ExportChat {
+                format: ExportFormat::Html,
+                path: None,
+            }))
+        );
+        assert_eq!(
+            registry.parse("/export-chat out/run.html"),
+            Some(Ok(SlashAction::ExportChat {
+                format: ExportFormat::Html,
+                path: Some("out/run.html".into()),
+            }))
+        );
+        assert_eq!(registry.parse("/pause"), Some(Ok(SlashAction::Pause)));
+        assert_eq!(registry.parse("/prompts"), Some(Ok(SlashAction::Prompts)));
+        assert_eq!(
+            registry.parse("/cd slides/talk"),
+            Some(Ok(SlashAction::SetCwd("slides/talk".into())))
+        );
+        assert!(matches!(registry.parse("/cd"), Some(Err(_))));
+        assert_eq!(
+            registry.parse("/copy"),
+            Some(Ok(SlashAction::Copy(CopyTarget::Reply)))
+        );
+        assert_eq!(
+            registry.parse("/copy exec 2"),
+            Some(Ok(SlashAction::Copy(CopyTarget::Exec(2))))
+        );
+        assert!(matches!(registry.parse("/copy exec 0"), Some(Err(_))));
+        assert_eq!(
+            registry.parse("/approvals never"),
+            Some(Ok(SlashAction::SetApprovals(ApprovalMode::Never)))
+        );
+        assert!(matches!(
+            registry.parse("/approvals sometimes"),
+            Some(Err(e)) if e.ends_with("expected one of: untrusted, on-failure, on-request, never")
+        ));
+        assert!(matches!(registry.parse("/help me"), Some(Err(_))));
+        // Unknown commands go to the agent untouched.
+        assert_eq!(registry.parse("/slide a deck about Rust"), None);
+        assert_eq!(registry.parse("hello"), None);
+    }
+
+    #[test]
+    fn completes_names_and_arguments() {
+        let registry = SlashCommandRegistry::default();
+        let names: Vec<&str> = registry.matching("ap").iter().map(|c| c.name()).collect();
+        assert_eq!(names, vec!["approvals"]);
+        let approvals = registry.get("approvals").map(|c| c.complete("on-"));
+        assert_eq!(
+            approvals,
+            Some(vec!["on-failure".to_string(), "on-request".to_string()])
+        );
+    }
+}
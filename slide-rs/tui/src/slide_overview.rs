@@ -0,0 +1,317 @@
+//! Overview of the deck in the slide preview (`o`): a grid of slide
+//! thumbnails, a fuzzy search to jump to a slide, and keys that move the
+//! selected slide through the deck.
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use slide_common::Slide;
+
+/// Thumbnails are at least this wide, and at most this many to a row
+const CARD_WIDTH: u16 = 28;
+const MAX_COLUMNS: usize = 4;
+/// Borders, and up to this many lines of the slide
+const CARD_HEIGHT: u16 = 6;
+const THUMBNAIL_LINES: usize = 4;
+
+pub(crate) struct SlideOverview {
+    selected: usize,
+    /// Query typed after `/`
+    query: Option<String>,
+    /// Slides matching the query, best first
+    matches: Vec<usize>,
+    /// Thumbnails per row at the last draw; Up/Down move by a row
+    columns: usize,
+    /// First row shown
+    scroll: usize,
+}
+
+/// What the preview does after a key in the overview.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum OverviewAction {
+    None,
+    Close,
+    /// Show this slide and close the overview
+    Open(usize),
+    /// Exchange these two slides in the deck; the selection follows the first
+    Swap(usize, usize),
+}
+
+impl SlideOverview {
+    pub fn new(selected: usize) -> Self {
+        Self {
+            selected,
+            query: None,
+            matches: Vec::new(),
+            columns: 1,
+            scroll: 0,
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent, slides: &[Slide]) -> OverviewAction {
+        let last = slides.len().saturating_sub(1);
+        if let Some(query) = self.query.as_mut() {
+            match key.code {
+                KeyCode::Char(c) => query.push(c),
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Enter => {
+                    self.query = None;
+                    return match self.matches.first() {
+                        Some(&index) => OverviewAction::Open(index),
+                        None => OverviewAction::None,
+                    };
+                }
+                KeyCode::Esc => {
+                    self.query = None;
+                    self.matches.clear();
+                    return OverviewAction::None;
+                }
+                _ => return OverviewAction::None,
+            }
+            self.update_matches(slides);
+            return OverviewAction::None;
+        }
+        match key.code {
+            KeyCode::Char('o') | KeyCode::Char('q') | KeyCode::Esc => return OverviewAction::Close,
+            KeyCode::Enter => return OverviewAction::Open(self.selected),
+            KeyCode::Left | KeyCode::Char('h') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(self.columns)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + self.columns).min(last)
+            }
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = last,
+            KeyCode::Char('/') => {
+                self.query = Some(String::new());
+                self.matches.clear();
+            }
+            // Next match of the last search
+            KeyCode::Char('n') => {
+                if let Some(next) = self
+                    .matches
+                    .iter()
+                    .position(|&i| i == self.selected)
+                    .and_then(|at| self.matches.get(at + 1).or(self.matches.first()))
+                {
+                    self.selected = *next;
+                }
+            }
+            KeyCode::Char('<') | KeyCode::Char('H') if self.selected > 0 => {
+                self.selected -= 1;
+                return OverviewAction::Swap(self.selected + 1, self.selected);
+            }
+            KeyCode::Char('>') | KeyCode::Char('L') if self.selected < last => {
+                self.selected += 1;
+                return OverviewAction::Swap(self.selected - 1, self.selected);
+            }
+            _ => {}
+        }
+        OverviewAction::None
+    }
+
+    /// Rank the slides against the query by their title and thumbnail
+    /// text; the best match is selected.
+    fn update_matches(&mut self, slides: &[Slide]) {
+        let query = self.query.as_deref().unwrap_or_default();
+        if query.is_empty() {
+            self.matches.clear();
+            return;
+        }
+        let mut scored: Vec<(u32, usize)> = slides
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slide)| {
+                let text = format!("{} {}", title(slide, i), thumbnail(slide).join(" "));
+                slide_file_search::fuzzy_score(query, &text).map(|score| (score, i))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        if let Some(&best) = self.matches.first() {
+            self.selected = best;
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, slides: &[Slide], current: usize) {
+        f.render_widget(Clear, area);
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Overview ({} slides) | ←↑↓→ move | ⏎ open | / find | </> reorder | o close",
+            slides.len()
+        ));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        if inner.height < 2 || inner.width == 0 {
+            return;
+        }
+
+        // The last line shows the search
+        let grid = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        let prompt = match &self.query {
+            Some(query) => format!("/{query}_  ({} matching)", self.matches.len()),
+            None if !self.matches.is_empty() => {
+                format!("{} matching | n: next match", self.matches.len())
+            }
+            None => "/: find a slide".to_string(),
+        };
+        f.render_widget(
+            Paragraph::new(prompt).style(Style::default().fg(Color::Yellow)),
+            Rect {
+                y: inner.y + grid.height,
+                height: 1,
+                ..inner
+            },
+        );
+
+        self.columns = usize::from(grid.width / CARD_WIDTH).clamp(1, MAX_COLUMNS);
+        let card_width = grid.width / self.columns as u16;
+        let rows = usize::from((grid.height / CARD_HEIGHT).max(1));
+        let selected_row = self.selected / self.columns;
+        if selected_row < self.scroll {
+            self.scroll = selected_row;
+        } else if selected_row >= self.scroll + rows {
+            self.scroll = selected_row + 1 - rows;
+        }
+
+        let first = self.scroll * self.columns;
+        for (i, slide) in slides
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows * self.columns)
+        {
+            let at = i - first;
+            let card = Rect {
+                x: grid.x + (at % self.columns) as u16 * card_width,
+                y: grid.y + (at / self.columns) as u16 * CARD_HEIGHT,
+                width: card_width,
+                height: CARD_HEIGHT.min(grid.height),
+            };
+            let dimmed =
+                self.query.as_ref().is_some_and(|q| !q.is_empty()) && !self.matches.contains(&i);
+            let border = if i == self.selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if dimmed {
+                Style::default().add_modifier(Modifier::DIM)
+            } else {
+                Style::default()
+            };
+            let heading = Style::default().fg(if i == current {
+                Color::Cyan
+            } else {
+                Color::White
+            });
+            let lines: Vec<Line> = thumbnail(slide).into_iter().map(Line::from).collect();
+            let thumb = Paragraph::new(Text::from(lines))
+                .style(if dimmed {
+                    Style::default().add_modifier(Modifier::DIM)
+                } else {
+                    Style::default()
+                })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(border)
+                        .title(Line::styled(
+                            format!("{}. {}", i + 1, title(slide, i)),
+                            heading,
+                        )),
+                );
+            f.render_widget(thumb, card);
+        }
+    }
+}
+
+fn title(slide: &Slide, index: usize) -> String {
+    slide
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("Slide {}", index + 1))
+}
+
+/// First lines of the slide as the audience sees it, heading left out;
+/// bullets come first when the slide has any.
+fn thumbnail(slide: &Slide) -> Vec<String> {
+    let content = slide.visible_content();
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let bullets: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| line.starts_with("- ") || line.starts_with("* "))
+        .collect();
+    let shown = if bullets.is_empty() { lines } else { bullets };
+    shown
+        .into_iter()
+        .take(THUMBNAIL_LINES)
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slide_common::Deck;
+
+    fn press(overview: &mut SlideOverview, slides: &[Slide], codes: &[KeyCode]) -> OverviewAction {
+        let mut action = OverviewAction::None;
+        for code in codes {
+            action = overview.handle_key_event(KeyEvent::from(*code), slides);
+        }
+        action
+    }
+
+    #[test]
+    fn search_jumps_to_the_best_match_and_moves_follow_the_slide() {
+        let deck = Deck::parse(
+            "## Intro\n- who we are\n\n## Pricing\n- plans and tiers\n\n## Roadmap\n- next quarter\n",
+        );
+        let mut overview = SlideOverview::new(0);
+        overview.columns = 2;
+        assert_eq!(
+            press(&mut overview, &deck.slides, &[KeyCode::Down]),
+            OverviewAction::None
+        );
+        assert_eq!(overview.selected, 2);
+
+        let query: Vec<KeyCode> = "/tiers".chars().map(KeyCode::Char).collect();
+        press(&mut overview, &deck.slides, &query);
+        assert_eq!(overview.selected, 1);
+        assert_eq!(
+            press(&mut overview, &deck.slides, &[KeyCode::Enter]),
+            OverviewAction::Open(1)
+        );
+
+        assert_eq!(
+            press(&mut overview, &deck.slides, &[KeyCode::Char('>')]),
+            OverviewAction::Swap(1, 2)
+        );
+        assert_eq!(overview.selected, 2);
+        // Nothing follows the last slide
+        assert_eq!(
+            press(&mut overview, &deck.slides, &[KeyCode::Char('>')]),
+            OverviewAction::None
+        );
+        assert_eq!(
+            press(&mut overview, &deck.slides, &[KeyCode::Esc]),
+            OverviewAction::Close
+        );
+        assert_eq!(thumbnail(&deck.slides[1]), ["- plans and tiers"]);
+    }
+}
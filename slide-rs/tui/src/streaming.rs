@@ -1,15 +1,19 @@
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 
+use crate::markdown::MarkdownRenderer;
+
 /// シンプルな行単位ストリーミング制御。
 /// - デルタをバッファに貯め、改行が来たら“完成した行”だけを返す。
 /// - 先頭で見出し行（空行 + ラベル）を一度だけ挿入し、その後は本文のみを流す。
+/// - 完成した行は Markdown として整形する（確定後の AgentMessage と同じ見た目）。
 /// - finalize() で残りの未完テキストを1行として返す。
 #[derive(Default, Debug)]
 pub struct AnswerStreamState {
     buffer: String,
     header_emitted: bool,
     active: bool,
+    markdown: MarkdownRenderer,
 }
 
 impl AnswerStreamState {
@@ -51,8 +55,9 @@ impl AnswerStreamState {
     /// 未出力の残りを1行として返し、状態をクリア。
     pub fn finalize(&mut self) -> Vec<Line<'static>> {
         let mut out: Vec<Line<'static>> = Vec::new();
-        if !self.buffer.is_empty() {
-            let tail = self.buffer.trim_end_matches('\r');
+        let buffer = std::mem::take(&mut self.buffer);
+        if !buffer.is_empty() {
+            let tail = buffer.trim_end_matches('\r');
             if !self.header_emitted {
                 out.push(Line::from(""));
                 out.push(self.header_line());
@@ -61,102 +66,37 @@ impl AnswerStreamState {
             // ツール実行結果のスタイリングを適用
             out.push(self.format_line(tail));
         }
-        self.buffer.clear();
         self.header_emitted = false;
         self.active = false;
+        self.markdown.reset();
         out
     }
 
-    /// 行のスタイリングを適用（ツール実行結果の色分け）
-    fn format_line(&self, line: &str) -> Line<'static> {
+    /// 行のスタイリングを適用（ツール実行の見出しは色分け、それ以外は Markdown）
+    fn format_line(&mut self, line: &str) -> Line<'static> {
         let trimmed = line.trim();
+        let label = [
+            ("Updated Plan", Color::Blue),
+            ("Proposed Change", Color::Yellow),
+            ("Change Approved", Color::Green),
+            ("Explored", Color::Cyan),
+            ("[Tool Execution Result]", Color::Magenta),
+            ("[Tool Execution]", Color::Yellow),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| trimmed.starts_with(prefix));
 
-        // セクションヘッダーの色分け
-        if trimmed.starts_with("Updated Plan") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("Proposed Change") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("Change Approved") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("Explored") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("[Tool Execution Result]") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Magenta)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("[Tool Execution]") {
-            Line::from(Span::styled(
+        match label {
+            Some((_, color)) => Line::from(Span::styled(
                 line.to_string(),
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        } else if trimmed.starts_with("▶") {
-            Line::from(Span::styled(
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )),
+            None if trimmed.starts_with('▶') => Line::from(Span::styled(
                 line.to_string(),
                 Style::default().fg(Color::Yellow),
-            ))
-        // 差分表示の色分け
-        } else if trimmed.starts_with("+") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default().fg(Color::Green),
-            ))
-        } else if trimmed.starts_with("-") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default().fg(Color::Red),
-            ))
-        } else if trimmed.starts_with("@@") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            ))
-        // チェックボックス付きタスクリスト
-        } else if trimmed.starts_with("□") || trimmed.starts_with("☑") {
-            let checkbox_color = if trimmed.starts_with("☑") {
-                Color::Green
-            } else {
-                Color::Gray
-            };
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default().fg(checkbox_color),
-            ))
-        // ファイルパスのハイライト
-        } else if trimmed.contains(".rs") || trimmed.contains(".toml") || trimmed.contains(".md") {
-            Line::from(Span::styled(
-                line.to_string(),
-                Style::default().fg(Color::LightBlue),
-            ))
-        // その他の行
-        } else {
-            Line::from(String::from(line))
+            )),
+            // コードブロックの内外は MarkdownRenderer が行をまたいで保持する
+            None => self.markdown.render_line(line),
         }
     }
 
@@ -0,0 +1,73 @@
+//! Ghost-text suggestions for the composer: after a pause in typing the
+//! text is sent to [`SuggestionService`] and the proposed continuation is
+//! handed back to the composer, which shows it dimmed after the cursor.
+use std::sync::Arc;
+use std::time::Duration;
+
+use slide_common::SuggestionConfig;
+use slide_core::client::SuggestionService;
+use tokio::task::JoinHandle;
+
+use crate::app_event_sender::{AppEvent, AppEventSender};
+
+/// Runs one request at a time; new text cancels the request in flight.
+pub(crate) struct SuggestionManager {
+    /// `None` when suggestions are off or no model is available
+    service: Option<Arc<SuggestionService>>,
+    debounce: Duration,
+    app_tx: AppEventSender,
+    pending: Option<JoinHandle<()>>,
+}
+
+impl SuggestionManager {
+    pub(crate) fn disabled(app_tx: AppEventSender) -> Self {
+        Self {
+            service: None,
+            debounce: Duration::ZERO,
+            app_tx,
+            pending: None,
+        }
+    }
+
+    /// Suggestions as configured; off without an API key.
+    pub(crate) fn from_config(config: &SuggestionConfig, app_tx: AppEventSender) -> Self {
+        let service = config
+            .enabled
+            .then(|| SuggestionService::from_env(&config.model))
+            .flatten();
+        Self {
+            service: service.map(Arc::new),
+            debounce: Duration::from_millis(config.debounce_ms),
+            app_tx,
+            pending: None,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.service.is_some()
+    }
+
+    /// Ask for a continuation of `text` once typing has paused.
+    pub(crate) fn on_text_changed(&mut self, text: String) {
+        self.cancel();
+        let Some(service) = self.service.clone() else {
+            return;
+        };
+        let app_tx = self.app_tx.clone();
+        let debounce = self.debounce;
+        self.pending = Some(tokio::spawn(async move {
+            tokio::time::sleep(debounce).await;
+            // Failures only mean no ghost text this time
+            if let Ok(Some(suggestion)) = service.suggest(&text).await {
+                app_tx.send(AppEvent::SuggestionResult { text, suggestion });
+            }
+        }));
+    }
+
+    /// Drop the request in flight, if any.
+    pub(crate) fn cancel(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.abort();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Ctrl+Z: stop slide like any foreground job, and continue it with `fg`.
+//!
+//! In raw mode the terminal hands Ctrl+Z to the app as a key instead of
+//! stopping it, so the app stops itself: it clears the input area, gives
+//! the terminal back in the state the shell expects and raises `SIGTSTP`.
+//! `fg` sends `SIGCONT`, the raise returns, and the terminal is set up
+//! again with the input area below whatever the shell printed meanwhile.
+//! The agent is stopped along with the app; a running turn carries on when
+//! it continues.
+use std::io;
+
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use ratatui::backend::{Backend, ClearType};
+use ratatui::layout::Position;
+
+use crate::custom_terminal::Terminal;
+
+/// Stop the process until the shell continues it. `mouse` is whether the
+/// app captures the mouse. The caller draws the input area again.
+pub(crate) fn suspend<B: Backend>(terminal: &mut Terminal<B>, mouse: bool) -> io::Result<()> {
+    let viewport = terminal.viewport_area;
+    terminal.set_cursor_position(Position::new(0, viewport.y))?;
+    terminal
+        .backend_mut()
+        .clear_region(ClearType::AfterCursor)?;
+    terminal.show_cursor()?;
+    terminal.backend_mut().flush()?;
+    let mut stdout = io::stdout();
+    execute!(
+        stdout,
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture
+    )?;
+    disable_raw_mode()?;
+
+    // SAFETY: raise has no preconditions. Without job control (an orphaned
+    // process group) the signal is discarded and slide simply carries on
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+
+    enable_raw_mode()?;
+    execute!(stdout, EnableBracketedPaste, EnableFocusChange)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    // Scroll the shell's output up out of the rows the input area takes
+    let size = terminal.size()?;
+    let bottom = size.height.saturating_sub(1);
+    let top = size.height.saturating_sub(viewport.height);
+    let cursor = terminal
+        .get_cursor_position()
+        .unwrap_or(Position::new(0, bottom));
+    let overlap = (cursor.y + 1).saturating_sub(top);
+    if overlap > 0 {
+        terminal.set_cursor_position(Position::new(0, bottom))?;
+        terminal.backend_mut().append_lines(overlap)?;
+    }
+    terminal.set_cursor_position(Position::new(0, top))?;
+    terminal
+        .backend_mut()
+        .clear_region(ClearType::AfterCursor)?;
+    terminal.backend_mut().flush()?;
+    terminal.invalidate();
+    Ok(())
+}
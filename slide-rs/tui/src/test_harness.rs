@@ -0,0 +1,153 @@
+//! Headless driver for snapshot tests of the bottom pane and other widgets.
+//!
+//! [`TuiHarness`] owns a `BottomPane` drawn on a ratatui [`TestBackend`]:
+//! tests send keys and pastes, open the approval modal, read the rendered
+//! screen as plain text with [`TuiHarness::snapshot`] and collect the
+//! [`AppEvent`]s the pane sent. Snapshots are stable strings, so they can be
+//! compared inline or handed to a snapshot library such as `insta`.
+//!
+//! [`render_widget`] snapshots any other widget the same way.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::WidgetRef;
+use ratatui::Terminal;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app_event_sender::{AppEvent, AppEventSender};
+use crate::bottom_pane::paste_burst::PasteBurst;
+use crate::bottom_pane::{BottomPane, BottomPaneParams, InputResult};
+use crate::user_approval_widget::ApprovalRequest;
+
+/// Placeholder of the composer, as in the app.
+const PLACEHOLDER: &str = "Ask Slide Code to do anything";
+
+pub struct TuiHarness {
+    terminal: Terminal<TestBackend>,
+    pane: BottomPane,
+    app_event_tx: AppEventSender,
+    app_events: UnboundedReceiver<AppEvent>,
+}
+
+impl TuiHarness {
+    /// A focused, empty composer on a `width` x `height` screen.
+    pub fn new(width: u16, height: u16) -> std::io::Result<Self> {
+        let (tx, app_events) = unbounded_channel();
+        let app_event_tx = AppEventSender::new(tx);
+        let pane = BottomPane::new(BottomPaneParams {
+            has_input_focus: true,
+            placeholder_text: PLACEHOLDER.to_string(),
+            app_event_tx: app_event_tx.clone(),
+        });
+        Ok(Self {
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            pane,
+            app_event_tx,
+            app_events,
+        })
+    }
+
+    /// Send one key to the pane, as the app does.
+    pub fn press(&mut self, key: KeyEvent) -> Option<InputResult> {
+        self.pane.handle_key_event(key)
+    }
+
+    /// Send a key without modifiers.
+    pub fn press_code(&mut self, code: KeyCode) -> Option<InputResult> {
+        self.press(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    /// Type `text` key by key, slow enough not to be taken for a paste.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press_code(KeyCode::Char(c));
+            std::thread::sleep(PasteBurst::recommended_flush_delay());
+            self.pane.flush_paste_burst_if_due();
+        }
+    }
+
+    /// Paste `text` at once, like a bracketed paste.
+    pub fn paste(&mut self, text: &str) {
+        self.pane.handle_paste(text.to_string());
+    }
+
+    /// Ask for approval as the app does when the agent requests it.
+    pub fn show_approval(&mut self, request: ApprovalRequest) {
+        self.pane
+            .show_approval_modal(request, self.app_event_tx.clone());
+    }
+
+    /// Close request `id` as the app does when the agent stops waiting.
+    pub fn dismiss_approval(&mut self, id: &str) {
+        self.pane.dismiss_approval(id);
+    }
+
+    /// Whether a modal currently takes the keys instead of the composer.
+    pub fn is_intercepting_input(&self) -> bool {
+        self.pane.is_intercepting_input()
+    }
+
+    /// Events the pane sent since the last call, oldest first.
+    pub fn drain_app_events(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.app_events.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    /// Draw the pane at the top of the screen, as tall as it asks to be,
+    /// and return the buffer.
+    pub fn render(&mut self) -> std::io::Result<&Buffer> {
+        let pane = &self.pane;
+        self.terminal.draw(|f| {
+            let mut area = f.area();
+            area.height = pane.desired_height(area.width).min(area.height);
+            pane.render_ref(area, f.buffer_mut());
+        })?;
+        Ok(self.terminal.backend().buffer())
+    }
+
+    /// The screen as text: one line per row without trailing spaces, blank
+    /// rows at the bottom left out.
+    pub fn snapshot(&mut self) -> std::io::Result<String> {
+        Ok(buffer_to_string(self.render()?))
+    }
+}
+
+/// Render `widget` on a `width` x `height` buffer and return it as text, as
+/// [`TuiHarness::snapshot`] does.
+pub fn render_widget(widget: &impl WidgetRef, width: u16, height: u16) -> String {
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    widget.render_ref(area, &mut buf);
+    buffer_to_string(&buf)
+}
+
+/// Rows of `buf` as text. Styles are dropped; a wide character stands for
+/// the cells it covers.
+pub fn buffer_to_string(buf: &Buffer) -> String {
+    let area = buf.area;
+    let mut lines: Vec<String> = (area.top()..area.bottom())
+        .map(|y| {
+            let mut line = String::new();
+            let mut covered = 0;
+            for x in area.left()..area.right() {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                let symbol = buf[(x, y)].symbol();
+                covered = symbol.width().saturating_sub(1);
+                line.push_str(symbol);
+            }
+            line.trim_end().to_string()
+        })
+        .collect();
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,236 @@
+//! Scrollback of the session, kept for the transcript pager.
+//!
+//! The newest lines stay in memory, up to `transcript.max_lines` of the
+//! config. Older ones are moved to a spill file next to the session, one
+//! JSON line each, and [`SpilledLines`] reads them back a page at a time
+//! when the pager is scrolled up past what it holds. The file is removed
+//! when the TUI exits. Without a spill file (history disabled by the privacy
+//! settings) older lines are dropped.
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+
+/// Lines kept in memory until the config is read
+const DEFAULT_MAX_LINES: usize = 5_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TranscriptStore {
+    recent: VecDeque<Line<'static>>,
+    max_lines: usize,
+    spill: Option<PathBuf>,
+    /// Lines written to the spill file so far
+    spilled: usize,
+}
+
+impl TranscriptStore {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            recent: VecDeque::new(),
+            max_lines: max_lines.max(1),
+            spill: None,
+            spilled: 0,
+        }
+    }
+
+    /// Move lines past the cap to `path` instead of dropping them. The file
+    /// is started afresh. Ignored when history is disabled by the privacy
+    /// settings.
+    pub fn with_spill_file(mut self, path: PathBuf) -> Self {
+        let _ = std::fs::remove_file(&path);
+        if slide_common::privacy::active().history_enabled() {
+            self.spill = Some(path);
+        }
+        self
+    }
+
+    /// Delete the spill file; older lines are dropped from then on.
+    pub fn remove_spill_file(&mut self) {
+        if let Some(path) = self.spill.take() {
+            let _ = std::fs::remove_file(path);
+        }
+        self.spilled = 0;
+    }
+
+    /// Lines kept in memory, oldest first
+    pub fn recent(&self) -> impl Iterator<Item = &Line<'static>> {
+        self.recent.iter()
+    }
+
+    /// Handle on the lines moved out of memory, for the pager
+    pub fn earlier(&self) -> SpilledLines {
+        SpilledLines {
+            path: self.spill.clone(),
+            remaining: self.spilled,
+        }
+    }
+
+    pub fn push(&mut self, lines: impl IntoIterator<Item = Line<'static>>) {
+        self.recent.extend(lines);
+        let excess = self.recent.len().saturating_sub(self.max_lines);
+        if excess == 0 {
+            return;
+        }
+        let evicted: Vec<Line<'static>> = self.recent.drain(..excess).collect();
+        let Some(path) = &self.spill else {
+            return;
+        };
+        match append_lines(path, &evicted) {
+            Ok(()) => self.spilled += evicted.len(),
+            // The pager can only show what was written before the failure
+            Err(_) => self.spill = None,
+        }
+    }
+}
+
+impl Default for TranscriptStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LINES)
+    }
+}
+
+/// Lines of a [`TranscriptStore`] that were moved to its spill file.
+pub(crate) struct SpilledLines {
+    path: Option<PathBuf>,
+    /// Lines not handed to the pager yet; they are the oldest of the file
+    remaining: usize,
+}
+
+impl SpilledLines {
+    pub fn none() -> Self {
+        Self {
+            path: None,
+            remaining: 0,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Up to `count` lines from just before those already loaded, oldest
+    /// first. Empty once the start of the file is reached or it cannot be
+    /// read.
+    pub fn load_before(&mut self, count: usize) -> Vec<Line<'static>> {
+        let start = self.remaining.saturating_sub(count);
+        let lines = self
+            .path
+            .as_ref()
+            .and_then(|path| File::open(path).ok())
+            .map(|file| {
+                BufReader::new(file)
+                    .lines()
+                    .skip(start)
+                    .take(self.remaining - start)
+                    .map_while(Result::ok)
+                    .filter_map(|json| serde_json::from_str::<StoredLine>(&json).ok())
+                    .map(StoredLine::into_line)
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.remaining = start;
+        lines
+    }
+}
+
+/// A line as written to the spill file.
+#[derive(Serialize, Deserialize)]
+struct StoredLine {
+    style: Style,
+    spans: Vec<(String, Style)>,
+}
+
+impl StoredLine {
+    fn from_line(line: &Line) -> Self {
+        Self {
+            style: line.style,
+            spans: line
+                .spans
+                .iter()
+                .map(|span| (span.content.to_string(), span.style))
+                .collect(),
+        }
+    }
+
+    fn into_line(self) -> Line<'static> {
+        Line::from(
+            self.spans
+                .into_iter()
+                .map(|(content, style)| Span::styled(content, style))
+                .collect::<Vec<_>>(),
+        )
+        .style(self.style)
+    }
+}
+
+fn append_lines(path: &Path, lines: &[Line]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut opts = OpenOptions::new();
+    opts.append(true).create(true);
+    #[cfg(unix)]
+    {
+        opts.mode(0o600);
+    }
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&serde_json::to_string(&StoredLine::from_line(line))?);
+        out.push('\n');
+    }
+    let mut f = opts.open(path)?;
+    f.write_all(out.as_bytes())?;
+    f.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn texts<'a>(lines: impl IntoIterator<Item = &'a Line<'static>>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lines_past_the_cap_spill_and_load_back_in_pages() -> std::io::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("slide-transcript-{}.lines", std::process::id()));
+        let mut store = TranscriptStore::new(3).with_spill_file(path.clone());
+        store.push((0..5).map(|i| Line::from(format!("line {i}"))));
+        store.push([Line::from(vec![
+            Span::raw("line "),
+            Span::styled("5", Style::default().fg(Color::Red)),
+        ])]);
+        assert_eq!(texts(store.recent()), ["line 3", "line 4", "line 5"]);
+
+        let mut earlier = store.earlier();
+        assert_eq!(earlier.remaining(), 3);
+        assert_eq!(texts(&earlier.load_before(2)), ["line 1", "line 2"]);
+        assert_eq!(texts(&earlier.load_before(2)), ["line 0"]);
+        assert!(earlier.load_before(2).is_empty());
+
+        // Styles survive the round trip
+        store.push((6..9).map(|i| Line::from(format!("line {i}"))));
+        let reloaded = store.earlier().load_before(1);
+        assert_eq!(texts(&reloaded), ["line 5"]);
+        assert_eq!(reloaded[0].spans[1].style.fg, Some(Color::Red));
+
+        // Without a spill file the oldest lines are dropped
+        let mut store = TranscriptStore::new(2);
+        store.push((0..4).map(|i| Line::from(format!("line {i}"))));
+        assert_eq!(texts(store.recent()), ["line 2", "line 3"]);
+        assert_eq!(store.earlier().remaining(), 0);
+        std::fs::remove_file(path)
+    }
+}
@@ -0,0 +1,583 @@
+use anyhow::Result;
+use crossterm::{
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+use std::io;
+use std::path::PathBuf;
+use tokio::time::{sleep, Duration};
+use unicode_width::UnicodeWidthChar;
+
+use crate::transcript_store::SpilledLines;
+
+/// Lines read back from the spill file at a time
+const EARLIER_PAGE: usize = 500;
+
+/// Full-screen pager over the session transcript with Vim-style keys
+/// (j/k, Ctrl-D/Ctrl-U, gg/G) and `/` search with n/N. Runs on top of the
+/// inline viewport, which keeps raw mode enabled. With mouse capture on,
+/// the wheel scrolls and clicking a file path closes the pager and returns it.
+pub struct TranscriptView {
+    title: String,
+    lines: Vec<Line<'static>>,
+    /// Lines before `lines` still on disk, read as the top is reached
+    earlier: SpilledLines,
+    scroll: usize,
+    /// Height of the text area at the last draw
+    page: usize,
+    /// `g` typed, waiting for the second `g`
+    pending_g: bool,
+    /// Query being typed after `/`
+    search_input: Option<String>,
+    /// Last submitted query, matched case-insensitively
+    query: String,
+    /// (line, byte range) of every match, in transcript order
+    matches: Vec<(usize, std::ops::Range<usize>)>,
+    /// Index into `matches` of the match jumped to last
+    current_match: Option<usize>,
+    /// Text area at the last draw, for mapping clicks to lines
+    body: Rect,
+    /// File path the user clicked
+    clicked_path: Option<PathBuf>,
+    /// What `y` copies instead of the shown lines, e.g. output without styling
+    copy_text: Option<String>,
+    /// Text the user asked to copy with `y`
+    copied: Option<String>,
+    should_quit: bool,
+}
+
+impl TranscriptView {
+    pub fn new(lines: Vec<Line<'static>>) -> Self {
+        Self {
+            title: "Transcript".to_string(),
+            lines,
+            earlier: SpilledLines::none(),
+            // Open at the end, like the terminal scrollback
+            scroll: usize::MAX,
+            page: 0,
+            pending_g: false,
+            search_input: None,
+            query: String::new(),
+            matches: Vec::new(),
+            current_match: None,
+            body: Rect::default(),
+            clicked_path: None,
+            copy_text: None,
+            copied: None,
+            should_quit: false,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Open at the first line instead of the end.
+    pub fn at_top(mut self) -> Self {
+        self.scroll = 0;
+        self
+    }
+
+    /// Lines of the transcript moved out of memory, shown once the pager is
+    /// scrolled up to them.
+    pub(crate) fn with_earlier(mut self, earlier: SpilledLines) -> Self {
+        self.earlier = earlier;
+        self
+    }
+
+    pub fn with_copy_text(mut self, text: String) -> Self {
+        self.copy_text = Some(text);
+        self
+    }
+
+    /// Text to put on the clipboard after the pager closed with `y`.
+    pub fn take_copied(&mut self) -> Option<String> {
+        self.copied.take()
+    }
+
+    /// Show the pager until it is closed. Returns the file path clicked, if any.
+    pub async fn run(&mut self) -> Result<Option<PathBuf>> {
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        // Nothing changes between input events, so frames follow them
+        let mut dirty = true;
+        loop {
+            if dirty {
+                terminal.draw(|f| self.ui(f))?;
+                dirty = false;
+            }
+
+            if event::poll(Duration::from_millis(100))? {
+                dirty = true;
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        self.handle_key_event(key);
+                    }
+                    Event::Mouse(mouse) => self.handle_mouse_event(mouse),
+                    _ => {}
+                }
+            }
+
+            if self.should_quit {
+                break;
+            }
+
+            sleep(Duration::from_millis(16)).await;
+        }
+
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        Ok(self.clicked_path.take())
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.page)
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        if self.search_input.is_some() {
+            self.handle_search_key(key);
+            return;
+        }
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let half_page = (self.page / 2).max(1);
+        self.scroll = self.scroll.min(self.max_scroll());
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('q') | KeyCode::Esc, _)
+            | (KeyCode::Char('t' | 'c'), KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            // The pager owns the alternate screen, so the caller copies once it is closed
+            (KeyCode::Char('y'), _) => {
+                self.copied = Some(self.copy_text.clone().unwrap_or_else(|| {
+                    let lines: Vec<String> = self.lines.iter().map(line_text).collect();
+                    lines.join("\n")
+                }));
+                self.should_quit = true;
+            }
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                self.scroll = self.scroll.saturating_add(half_page);
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.scroll = self.scroll.saturating_sub(half_page);
+            }
+            (KeyCode::Char('j') | KeyCode::Down, _) => {
+                self.scroll = self.scroll.saturating_add(1);
+            }
+            (KeyCode::Char('k') | KeyCode::Up, _) => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            (KeyCode::PageDown | KeyCode::Char(' '), _) => {
+                self.scroll = self.scroll.saturating_add(self.page.max(1));
+            }
+            (KeyCode::PageUp, _) => {
+                self.scroll = self.scroll.saturating_sub(self.page.max(1));
+            }
+            (KeyCode::Char('g'), _) if pending_g => {
+                self.scroll = 0;
+            }
+            (KeyCode::Char('g'), _) => {
+                self.pending_g = true;
+            }
+            (KeyCode::Home, _) => {
+                self.scroll = 0;
+            }
+            (KeyCode::Char('G') | KeyCode::End, _) => {
+                self.scroll = usize::MAX;
+            }
+            (KeyCode::Char('/'), _) => {
+                self.search_input = Some(String::new());
+            }
+            (KeyCode::Char('n'), _) => self.jump_to_match(true),
+            (KeyCode::Char('N'), _) => self.jump_to_match(false),
+            _ => {}
+        }
+        self.scroll = self.scroll.min(self.max_scroll());
+        self.load_earlier();
+    }
+
+    /// At the top of the loaded lines, read the page before them from disk.
+    /// The view stays on the same line and search matches are kept.
+    fn load_earlier(&mut self) {
+        if self.scroll > 0 || self.earlier.remaining() == 0 {
+            return;
+        }
+        let mut lines = self.earlier.load_before(EARLIER_PAGE);
+        let count = lines.len();
+        if count == 0 {
+            return;
+        }
+        lines.append(&mut self.lines);
+        self.lines = lines;
+        self.scroll += count;
+        let current = self
+            .current_match
+            .and_then(|i| self.matches.get(i))
+            .map(|(line, range)| (line + count, range.clone()));
+        self.find_matches();
+        self.current_match = current.and_then(|c| self.matches.iter().position(|m| *m == c));
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        self.scroll = self.scroll.min(self.max_scroll());
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(3),
+            MouseEventKind::ScrollDown => {
+                self.scroll = self.scroll.saturating_add(3).min(self.max_scroll());
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if self.body.contains(Position::new(mouse.column, mouse.row)) =>
+            {
+                let index = self.scroll + (mouse.row - self.body.y) as usize;
+                let column = (mouse.column - self.body.x) as usize;
+                if let Some(path) = self
+                    .lines
+                    .get(index)
+                    .and_then(|line| path_at(&line_text(line), column))
+                {
+                    self.clicked_path = Some(path);
+                    self.should_quit = true;
+                }
+            }
+            _ => {}
+        }
+        self.load_earlier();
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.search_input.as_mut() else {
+            return;
+        };
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.search_input = None;
+            }
+            (KeyCode::Enter, _) => {
+                let query = std::mem::take(input);
+                self.search_input = None;
+                self.search(query);
+            }
+            // Backspace on an empty query leaves search, like Vim
+            (KeyCode::Backspace, _) if input.is_empty() => self.search_input = None,
+            (KeyCode::Backspace, _) => {
+                input.pop();
+            }
+            (KeyCode::Char(c), m) if !m.contains(KeyModifiers::CONTROL) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Find every occurrence of `query` and jump to the first one at or
+    /// below the top of the screen. An empty query repeats the last search.
+    fn search(&mut self, query: String) {
+        if !query.is_empty() {
+            self.query = query;
+        }
+        self.current_match = None;
+        self.find_matches();
+        if self.matches.is_empty() {
+            return;
+        }
+        let top = self.scroll.min(self.max_scroll());
+        if let Some(first) = self.matches.iter().position(|(line, _)| *line >= top) {
+            self.current_match = Some(first);
+            self.scroll_to_current();
+        } else {
+            // Wrap to the top, like Vim
+            self.jump_to_match(true);
+        }
+    }
+
+    /// Every occurrence of the query in the loaded lines.
+    fn find_matches(&mut self) {
+        self.matches.clear();
+        let needle = self.query.to_ascii_lowercase();
+        if needle.is_empty() {
+            return;
+        }
+        for (i, line) in self.lines.iter().enumerate() {
+            let text = line_text(line).to_ascii_lowercase();
+            let mut from = 0;
+            while let Some(pos) = text[from..].find(&needle) {
+                let start = from + pos;
+                self.matches.push((i, start..start + needle.len()));
+                from = start + needle.len();
+            }
+        }
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let len = self.matches.len();
+        self.current_match = Some(match (self.current_match, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        });
+        self.scroll_to_current();
+    }
+
+    /// Scroll so the current match sits a third of the way down the page.
+    fn scroll_to_current(&mut self) {
+        if let Some((line, _)) = self.current_match.and_then(|i| self.matches.get(i)) {
+            self.scroll = line.saturating_sub(self.page / 3).min(self.max_scroll());
+        }
+    }
+
+    /// `line` with its matches highlighted; the current match stands out.
+    fn highlighted(&self, index: usize, line: &Line<'static>) -> Line<'static> {
+        let ranges: Vec<(std::ops::Range<usize>, bool)> = self
+            .matches
+            .iter()
+            .enumerate()
+            .filter(|(_, (l, _))| *l == index)
+            .map(|(i, (_, range))| (range.clone(), Some(i) == self.current_match))
+            .collect();
+        if ranges.is_empty() {
+            return line.clone();
+        }
+        let mut spans = Vec::new();
+        let mut offset = 0;
+        for span in &line.spans {
+            let content = span.content.as_ref();
+            let end = offset + content.len();
+            let mut cut = offset;
+            for (range, current) in &ranges {
+                let start = range.start.clamp(cut, end);
+                let stop = range.end.clamp(cut, end);
+                if start == stop {
+                    continue;
+                }
+                if start > cut {
+                    spans.push(Span::styled(
+                        content[cut - offset..start - offset].to_string(),
+                        span.style,
+                    ));
+                }
+                let style = if *current {
+                    span.style.fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    span.style.add_modifier(Modifier::REVERSED)
+                };
+                spans.push(Span::styled(
+                    content[start - offset..stop - offset].to_string(),
+                    style,
+                ));
+                cut = stop;
+            }
+            if cut < end {
+                spans.push(Span::styled(
+                    content[cut - offset..].to_string(),
+                    span.style,
+                ));
+            }
+            offset = end;
+        }
+        Line::from(spans).style(line.style)
+    }
+
+    fn ui(&mut self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(f.area());
+
+        self.page = chunks[0].height.saturating_sub(2) as usize;
+        self.scroll = self.scroll.min(self.max_scroll());
+        // Fill a page that the lines in memory do not
+        self.load_earlier();
+        self.scroll = self.scroll.min(self.max_scroll());
+
+        let visible: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.page)
+            .map(|(i, line)| self.highlighted(i, line))
+            .collect();
+        let mut title = format!(
+            "{} ({}-{} of {})",
+            self.title,
+            (self.scroll + 1).min(self.lines.len()),
+            (self.scroll + visible.len()).min(self.lines.len()),
+            self.lines.len()
+        );
+        if self.earlier.remaining() > 0 {
+            title.push_str(&format!(" +{} earlier", self.earlier.remaining()));
+        }
+        if !self.query.is_empty() {
+            match self.current_match {
+                Some(i) => title.push_str(&format!(
+                    " [match {}/{} for \"{}\"]",
+                    i + 1,
+                    self.matches.len(),
+                    self.query
+                )),
+                None => title.push_str(&format!(" [no matches for \"{}\"]", self.query)),
+            }
+        }
+        // Lines were wrapped to the viewport width when they were inserted.
+        let block = Block::default().borders(Borders::ALL).title(title);
+        self.body = block.inner(chunks[0]);
+        let body = Paragraph::new(Text::from(visible)).block(block);
+        f.render_widget(body, chunks[0]);
+
+        let footer = match &self.search_input {
+            Some(input) => Paragraph::new(format!("/{input}")),
+            None => Paragraph::new(
+                "j/k: line | Ctrl-D/Ctrl-U: half page | gg/G: top/bottom | /: search | n/N: next/prev | y: copy | q: close",
+            )
+            .style(Style::default().fg(Color::Yellow)),
+        };
+        f.render_widget(footer, chunks[1]);
+        if let Some(input) = &self.search_input {
+            let x = chunks[1].x + 1 + input.chars().count() as u16;
+            f.set_cursor_position((x.min(chunks[1].right().saturating_sub(1)), chunks[1].y));
+        }
+    }
+}
+
+/// Existing file named by the word at display `column` of `text`, ignoring
+/// surrounding quotes and punctuation and a trailing `:line` suffix.
+fn path_at(text: &str, column: usize) -> Option<PathBuf> {
+    // Byte offset of the character drawn at `column`
+    let mut width = 0;
+    let (at, ch) = text.char_indices().find(|(_, ch)| {
+        width += UnicodeWidthChar::width(*ch).unwrap_or(0);
+        width > column
+    })?;
+    if ch.is_whitespace() {
+        return None;
+    }
+    let start = text[..at]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let end = text[at..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| at + i);
+    let is_quote = |c: char| "\"'`()[]<>{},;".contains(c);
+    let word = text[start..end].trim_start_matches(is_quote);
+    let word = word
+        .split(':')
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(|c: char| is_quote(c) || c == '.');
+    if word.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(word);
+    path.is_file().then_some(path)
+}
+
+fn line_text(line: &Line) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript_store::TranscriptStore;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn search_jumps_between_matches() {
+        let lines = (0..50)
+            .map(|i| {
+                let text = if i % 20 == 5 {
+                    "Error: build failed"
+                } else {
+                    "ok"
+                };
+                Line::from(vec![Span::raw(format!("{i}: ")), Span::raw(text)])
+            })
+            .collect();
+        let mut view = TranscriptView::new(lines);
+        view.page = 10;
+        view.handle_key_event(key('g'));
+        view.handle_key_event(key('g'));
+        view.handle_key_event(key('/'));
+        for c in "error".chars() {
+            view.handle_key_event(key(c));
+        }
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(view.matches.len(), 3);
+        assert_eq!(view.current_match, Some(0));
+        assert_eq!(view.scroll, 2);
+
+        view.handle_key_event(key('n'));
+        assert_eq!(view.current_match, Some(1));
+        assert_eq!(view.scroll, 22);
+        view.handle_key_event(key('N'));
+        view.handle_key_event(key('N'));
+        assert_eq!(view.current_match, Some(2));
+
+        // The match is split out of the span that contains it
+        let line = view.highlighted(5, &view.lines[5]);
+        let texts: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, vec!["5: ", "Error", ": build failed"]);
+    }
+
+    #[test]
+    fn scrolling_to_the_top_reads_spilled_lines() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(format!("slide-pager-{}.lines", std::process::id()));
+        let mut store = TranscriptStore::new(10).with_spill_file(path.clone());
+        store.push((0..30).map(|i| Line::from(format!("line {i}"))));
+        let mut view =
+            TranscriptView::new(store.recent().cloned().collect()).with_earlier(store.earlier());
+        view.page = 5;
+        view.query = "line 2".to_string();
+        view.find_matches();
+        view.current_match = Some(1);
+        assert_eq!(view.matches[1].0, 1);
+
+        view.handle_key_event(key('g'));
+        view.handle_key_event(key('g'));
+        // The same line stays on top, now after the 20 lines read back
+        assert_eq!(view.lines.len(), 30);
+        assert_eq!(view.scroll, 20);
+        assert_eq!(line_text(&view.lines[0]), "line 0");
+        // Matches now include the earlier lines and follow the current one
+        assert_eq!(view.matches.len(), 11);
+        assert_eq!(view.current_match.map(|i| view.matches[i].0), Some(21));
+        store.remove_spill_file();
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn clicked_word_is_an_existing_file() -> std::io::Result<()> {
+        let file = std::env::temp_dir().join(format!("slide-click-{}.md", std::process::id()));
+        std::fs::write(&file, "# Deck")?;
+        let text = format!("保存: `{}`: done", file.display());
+        // "保存: `" takes 7 columns; the quotes and `:` are not part of the path
+        assert_eq!(path_at(&text, 7), Some(file.clone()));
+        assert_eq!(path_at(&text, 10), Some(file.clone()));
+        assert_eq!(path_at(&text, 5), None);
+        assert_eq!(path_at(&text, 0), None);
+        std::fs::remove_file(file)
+    }
+}
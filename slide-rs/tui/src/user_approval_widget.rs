@@ -3,15 +3,19 @@ use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
     prelude::Widget,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Paragraph, WidgetRef},
+    widgets::{Block, BorderType, Borders, Paragraph, WidgetRef, Wrap},
 };
 
 use crate::app_event_sender::{AppEvent, AppEventSender};
 use crate::bottom_pane::scroll_state::ScrollState;
 use crate::bottom_pane::selection_popup_common::{render_rows, GenericDisplayRow};
-use slide_core::codex::ReviewDecision;
+use crate::diff_review::PatchReview;
+use slide_common::i18n::{t, tf, Msg};
+use slide_core::codex::{ApplyPatchFileChange, ParsedCommand, ReviewDecision};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 #[derive(Clone, Debug)]
 pub enum ApprovalRequest {
@@ -19,40 +23,113 @@ pub enum ApprovalRequest {
         id: String,
         command: Vec<String>,
         reason: Option<String>,
+        cwd: PathBuf,
+        /// Gist of each command, from `parse_command`
+        parsed: Vec<ParsedCommand>,
+        /// Sandbox policy the command would run under
+        sandbox: Option<String>,
+        /// Needs permissions outside the sandbox policy
+        escalated: bool,
+        /// Why the safety checks asked
+        risk: Option<String>,
+        /// Prefix `p` allows for the rest of the session
+        prefix: Option<Vec<String>>,
+        /// When the agent stops waiting for an answer
+        deadline: Option<Instant>,
     },
     Patch {
         id: String,
-        changes: Vec<String>,
+        changes: Vec<(PathBuf, ApplyPatchFileChange)>,
         reason: Option<String>,
+        deadline: Option<Instant>,
+        /// Patched files that already have uncommitted changes
+        dirty_files: Vec<PathBuf>,
+    },
+    /// Several commands from one response, each of which can be deselected
+    ExecPlan {
+        id: String,
+        commands: Vec<Vec<String>>,
+        deadline: Option<Instant>,
     },
 }
 
+impl ApprovalRequest {
+    pub fn id(&self) -> &str {
+        match self {
+            ApprovalRequest::Exec { id, .. }
+            | ApprovalRequest::Patch { id, .. }
+            | ApprovalRequest::ExecPlan { id, .. } => id,
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        match self {
+            ApprovalRequest::Exec { deadline, .. }
+            | ApprovalRequest::Patch { deadline, .. }
+            | ApprovalRequest::ExecPlan { deadline, .. } => *deadline,
+        }
+    }
+}
+
+/// Deadline of a request the agent waits `timeout_secs` for, counted from now.
+pub fn approval_deadline(timeout_secs: Option<u64>) -> Option<Instant> {
+    timeout_secs.map(|secs| Instant::now() + Duration::from_secs(secs))
+}
+
 pub struct UserApprovalWidget {
     request: ApprovalRequest,
     complete: bool,
     tx: AppEventSender,
     scroll: ScrollState,
-    page_size_hint: usize,
+    /// Diff review for patch requests
+    review: Option<PatchReview>,
+    /// Which plan commands will run
+    plan_selected: Vec<bool>,
 }
 
 impl UserApprovalWidget {
     pub fn new(request: ApprovalRequest, tx: AppEventSender) -> Self {
+        let review = match &request {
+            ApprovalRequest::Patch { changes, .. } => Some(PatchReview::new(changes.clone())),
+            ApprovalRequest::Exec { .. } | ApprovalRequest::ExecPlan { .. } => None,
+        };
         let mut scroll = ScrollState::new();
-        if matches!(request, ApprovalRequest::Patch { .. }) {
-            scroll.selected_idx = Some(0);
-        }
+        let plan_selected = match &request {
+            ApprovalRequest::ExecPlan { commands, .. } => {
+                scroll.selected_idx = Some(0);
+                vec![true; commands.len()]
+            }
+            _ => Vec::new(),
+        };
         Self {
             request,
             complete: false,
             tx,
             scroll,
-            page_size_hint: 10,
+            review,
+            plan_selected,
         }
     }
     pub fn handle_key_event(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
-                self.emit_decision(ReviewDecision::Approved);
+                self.emit_decision(self.approved_decision());
+                self.complete = true;
+            }
+            KeyCode::Char('a') if matches!(self.request, ApprovalRequest::Exec { .. }) => {
+                self.emit_decision(ReviewDecision::ApprovedForSession);
+                self.complete = true;
+            }
+            KeyCode::Char('p')
+                if matches!(
+                    self.request,
+                    ApprovalRequest::Exec {
+                        prefix: Some(_),
+                        ..
+                    }
+                ) =>
+            {
+                self.emit_decision(ReviewDecision::ApprovedPrefix);
                 self.complete = true;
             }
             KeyCode::Char('n') | KeyCode::Char('N') => {
@@ -63,46 +140,132 @@ impl UserApprovalWidget {
                 self.emit_decision(ReviewDecision::Abort);
                 self.complete = true;
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.move_selection_up(1);
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.move_selection_down(1);
-            }
-            KeyCode::PageUp => {
-                let step = self.page_size_hint.max(1);
-                self.move_selection_up(step);
-            }
-            KeyCode::PageDown => {
-                let step = self.page_size_hint.max(1);
-                self.move_selection_down(step);
-            }
-            KeyCode::Home | KeyCode::Char('g') => {
-                self.select_home();
+            _ => {
+                if let Some(review) = self.review.as_mut() {
+                    review.handle_key_event(key);
+                } else if !self.plan_selected.is_empty() {
+                    self.handle_plan_key(key);
+                }
             }
-            KeyCode::End | KeyCode::Char('G') => {
-                self.select_end();
+        }
+    }
+    /// Mouse wheel over the widget scrolls the diff of a patch request.
+    pub fn on_mouse_scroll(&mut self, delta: isize) -> bool {
+        match self.review.as_mut() {
+            Some(review) => {
+                review.scroll_by(delta);
+                true
             }
-            _ => {}
+            None => false,
         }
     }
+
     pub fn on_ctrl_c(&mut self) {
         self.complete = true;
     }
+
+    /// The agent stopped waiting for request `id`: close it without
+    /// answering. Returns whether this widget showed it.
+    pub fn on_timed_out(&mut self, id: &str) -> bool {
+        let shown = self.request.id() == id;
+        if shown {
+            self.complete = true;
+        }
+        shown
+    }
+
+    /// Countdown to the deadline, e.g. "12s left to answer".
+    fn time_left(&self) -> Option<String> {
+        let left = self
+            .request
+            .deadline()?
+            .saturating_duration_since(Instant::now());
+        // Round up so the countdown reaches 0 when the agent gives up
+        let secs = left.as_millis().div_ceil(1000);
+        Some(tf(Msg::ApprovalTimeLeft, &[("secs", &secs)]))
+    }
     pub fn is_complete(&self) -> bool {
         self.complete
     }
-    pub fn desired_height(&self, _width: u16) -> u16 {
-        10
+    pub fn desired_height(&self, width: u16) -> u16 {
+        // Patch diffs get more room; the viewport is capped at the terminal height
+        if self.review.is_some() {
+            return 24;
+        }
+        match self.exec_details() {
+            Some(lines) => {
+                let inner = usize::from(width.saturating_sub(2)).max(1);
+                let rows: usize = lines.iter().map(|l| l.width().div_ceil(inner).max(1)).sum();
+                // Borders, header and footer around the details
+                (rows.min(MAX_DETAIL_ROWS) + 4) as u16
+            }
+            None => 10,
+        }
+    }
+
+    /// Command, its gist, where and how it would run, and why approval is
+    /// needed, for single-command requests.
+    fn exec_details(&self) -> Option<Vec<Line<'static>>> {
+        let ApprovalRequest::Exec {
+            command,
+            cwd,
+            parsed,
+            sandbox,
+            escalated,
+            risk,
+            ..
+        } = &self.request
+        else {
+            return None;
+        };
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let mut lines = vec![Line::from(vec![
+            Span::styled("$ ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(command.join(" ")),
+        ])];
+        lines.extend(
+            parsed
+                .iter()
+                .filter_map(ParsedCommand::summary)
+                .map(|summary| Line::styled(format!("  ↳ {summary}"), dim)),
+        );
+        lines.push(Line::styled(
+            tf(Msg::StatusCwd, &[("cwd", &cwd.display())]),
+            dim,
+        ));
+        if let Some(sandbox) = sandbox {
+            let mut text = tf(Msg::StatusSandbox, &[("policy", sandbox)]);
+            if *escalated {
+                text.push_str(&format!(" — {}", t(Msg::ApprovalEscalated)));
+            }
+            lines.push(Line::styled(
+                text,
+                if *escalated {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    dim
+                },
+            ));
+        }
+        if let Some(risk) = risk {
+            lines.push(Line::styled(
+                tf(Msg::ApprovalRisk, &[("reason", risk)]),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        Some(lines)
     }
 }
 
+/// Rows of command details shown before the modal stops growing
+const MAX_DETAIL_ROWS: usize = 12;
+
 impl WidgetRef for &UserApprovalWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Double)
-            .title("Approval Required");
+            .title(t(Msg::ApprovalTitle));
         let inner_area = block.inner(area);
         block.render(area, buf);
 
@@ -113,14 +276,12 @@ impl WidgetRef for &UserApprovalWidget {
         ])
         .areas::<3>(inner_area);
         // Header
-        let header_line: Line = match &self.request {
-            ApprovalRequest::Exec {
-                command, reason, ..
-            } => {
-                let mut spans = vec![
-                    Span::styled("$ ", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(command.join(" ")),
-                ];
+        let mut header_line: Line = match &self.request {
+            ApprovalRequest::Exec { reason, .. } => {
+                let mut spans = vec![Span::styled(
+                    t(Msg::ApprovalRunCommand),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )];
                 if let Some(r) = reason {
                     spans.push(Span::raw("  — "));
                     spans.push(Span::styled(
@@ -130,11 +291,56 @@ impl WidgetRef for &UserApprovalWidget {
                 }
                 Line::from(spans)
             }
-            ApprovalRequest::Patch { reason, .. } => {
+            ApprovalRequest::ExecPlan { commands, .. } => {
+                let selected = self.plan_selected.iter().filter(|s| **s).count();
+                Line::from(vec![
+                    Span::styled(
+                        t(Msg::ApprovalRunCommands),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!(
+                        "  {}",
+                        tf(
+                            Msg::ApprovalCommandsSelected,
+                            &[("selected", &selected), ("total", &commands.len())],
+                        )
+                    )),
+                ])
+            }
+            ApprovalRequest::Patch {
+                reason,
+                dirty_files,
+                ..
+            } => {
                 let mut spans = vec![Span::styled(
-                    "apply_patch changes",
+                    t(Msg::ApprovalPatchChanges),
                     Style::default().add_modifier(Modifier::BOLD),
                 )];
+                if !dirty_files.is_empty() {
+                    let files: Vec<String> = dirty_files
+                        .iter()
+                        .map(|f| f.display().to_string())
+                        .collect();
+                    spans.push(Span::styled(
+                        format!(
+                            "  ⚠ {}",
+                            tf(Msg::ApprovalDirtyFiles, &[("files", &files.join(", "))])
+                        ),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                if let Some(review) = &self.review {
+                    spans.push(Span::raw(format!(
+                        "  {}",
+                        tf(
+                            Msg::ApprovalFilesSelected,
+                            &[
+                                ("selected", &review.approved_count()),
+                                ("total", &review.len()),
+                            ],
+                        )
+                    )));
+                }
                 if let Some(r) = reason {
                     spans.push(Span::raw("  — "));
                     spans.push(Span::styled(
@@ -145,29 +351,37 @@ impl WidgetRef for &UserApprovalWidget {
                 Line::from(spans)
             }
         };
+        if let Some(left) = self.time_left() {
+            header_line.push_span(Span::raw("  "));
+            header_line.push_span(Span::styled(left, Style::default().fg(Color::Yellow)));
+        }
         Paragraph::new(header_line).render(areas[0], buf);
 
-        // Body rows (scrollable)
+        // Body: diff review for patches, the command otherwise
         let rows_area = areas[1];
-        if rows_area.height > 0 {
+        if let Some(review) = &self.review {
+            review.render(rows_area, buf);
+        } else if let Some(details) = self.exec_details() {
+            Paragraph::new(details)
+                .wrap(Wrap { trim: false })
+                .render(rows_area, buf);
+        } else if rows_area.height > 0 {
             let rows_all: Vec<GenericDisplayRow> = match &self.request {
-                ApprovalRequest::Exec { command, .. } => {
-                    vec![GenericDisplayRow {
-                        name: format!("$ {}", command.join(" ")),
-                        match_indices: None,
-                        is_current: false,
-                        description: None,
-                    }]
-                }
-                ApprovalRequest::Patch { changes, .. } => changes
+                ApprovalRequest::ExecPlan { commands, .. } => commands
                     .iter()
-                    .map(|ch| GenericDisplayRow {
-                        name: ch.clone(),
+                    .zip(&self.plan_selected)
+                    .map(|(command, selected)| GenericDisplayRow {
+                        name: format!(
+                            "{} $ {}",
+                            if *selected { "✓" } else { "✗" },
+                            command.join(" ")
+                        ),
                         match_indices: None,
                         is_current: false,
                         description: None,
                     })
                     .collect(),
+                ApprovalRequest::Exec { .. } | ApprovalRequest::Patch { .. } => Vec::new(),
             };
             render_rows(rows_area, buf, &rows_all, &self.scroll, usize::MAX, true);
         }
@@ -179,18 +393,51 @@ impl WidgetRef for &UserApprovalWidget {
             width: inner_area.width,
             height: 1,
         };
+        let approve_label = match self.request {
+            ApprovalRequest::Patch { .. } => Msg::ApprovalApplySelected,
+            ApprovalRequest::ExecPlan { .. } => Msg::ApprovalRunSelected,
+            ApprovalRequest::Exec { .. } => Msg::ApprovalApprove,
+        };
         let mut footer_spans = vec![
             Span::styled(" y ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(": approve   "),
+            Span::raw(format!(": {}   ", t(approve_label))),
+        ];
+        if let ApprovalRequest::Exec { prefix, .. } = &self.request {
+            footer_spans.push(Span::styled(
+                " a ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            footer_spans.push(Span::raw(format!(
+                ": {}   ",
+                t(Msg::ApprovalApproveSession)
+            )));
+            if let Some(prefix) = prefix {
+                footer_spans.push(Span::styled(
+                    " p ",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+                footer_spans.push(Span::raw(format!(
+                    ": {}   ",
+                    tf(Msg::ApprovalAllowPrefix, &[("prefix", &prefix.join(" "))])
+                )));
+            }
+        }
+        footer_spans.extend([
             Span::styled(" n ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(": deny   "),
+            Span::raw(format!(": {}   ", t(Msg::ApprovalDeny))),
             Span::styled(" Esc ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(": close"),
-        ];
+            Span::raw(format!(": {}", t(Msg::ApprovalClose))),
+        ]);
         if matches!(self.request, ApprovalRequest::Patch { .. }) {
             footer_spans.push(Span::raw("    "));
             footer_spans.push(Span::styled(
-                "↑/↓ PgUp/PgDn Home/End j/k g/G",
+                t(Msg::ApprovalPatchKeys),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        } else if matches!(self.request, ApprovalRequest::ExecPlan { .. }) {
+            footer_spans.push(Span::raw("    "));
+            footer_spans.push(Span::styled(
+                t(Msg::ApprovalPlanKeys),
                 Style::default().add_modifier(Modifier::DIM),
             ));
         }
@@ -199,83 +446,130 @@ impl WidgetRef for &UserApprovalWidget {
 }
 
 impl UserApprovalWidget {
+    /// Decision for `y`: everything, the selected subset, or nothing when
+    /// the user deselected every file or command.
+    fn approved_decision(&self) -> ReviewDecision {
+        if let Some(review) = &self.review {
+            return match review.approved_count() {
+                0 => ReviewDecision::Denied,
+                n if n == review.len() => ReviewDecision::Approved,
+                _ => ReviewDecision::ApprovedFiles(review.approved_files()),
+            };
+        }
+        if self.plan_selected.is_empty() || self.plan_selected.iter().all(|s| *s) {
+            return ReviewDecision::Approved;
+        }
+        let indices: Vec<usize> = (0..self.plan_selected.len())
+            .filter(|&i| self.plan_selected[i])
+            .collect();
+        if indices.is_empty() {
+            ReviewDecision::Denied
+        } else {
+            ReviewDecision::ApprovedCommands(indices)
+        }
+    }
+
+    fn handle_plan_key(&mut self, key: KeyEvent) {
+        let len = self.plan_selected.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.move_up_wrap(len),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll.move_down_wrap(len),
+            KeyCode::Char(' ') => {
+                if let Some(selected) = self
+                    .scroll
+                    .selected_idx
+                    .and_then(|i| self.plan_selected.get_mut(i))
+                {
+                    *selected = !*selected;
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn emit_decision(&self, decision: ReviewDecision) {
         match &self.request {
-            ApprovalRequest::Exec { id, .. } => self.tx.send(AppEvent::ExecApproval {
-                id: id.clone(),
-                decision,
-            }),
+            ApprovalRequest::Exec { id, .. } | ApprovalRequest::ExecPlan { id, .. } => {
+                self.tx.send(AppEvent::ExecApproval {
+                    id: id.clone(),
+                    decision,
+                })
+            }
             ApprovalRequest::Patch { id, .. } => self.tx.send(AppEvent::PatchApproval {
                 id: id.clone(),
                 decision,
             }),
         }
     }
+}
 
-    fn total_lines(&self) -> usize {
-        match &self.request {
-            ApprovalRequest::Exec { .. } => 1,
-            ApprovalRequest::Patch { changes, .. } => changes.len(),
-        }
-    }
-
-    fn ensure_clamped(&mut self) {
-        let len = self.total_lines();
-        self.scroll.clamp_selection(len);
-        let window = self.page_size_hint.max(1);
-        self.scroll.ensure_visible(len, window);
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
 
-    fn move_selection_up(&mut self, step: usize) {
-        if !matches!(self.request, ApprovalRequest::Patch { .. }) {
-            return;
-        }
-        let len = self.total_lines();
-        if len == 0 {
-            return;
+    fn exec_request(prefix: Option<Vec<String>>) -> ApprovalRequest {
+        let command: Vec<String> = ["bash", "-lc", "rm -r build"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        ApprovalRequest::Exec {
+            id: "1".to_string(),
+            parsed: slide_core::codex::parse_command(&command),
+            command,
+            reason: None,
+            cwd: PathBuf::from("/work/talk"),
+            sandbox: Some("workspace-write".to_string()),
+            escalated: true,
+            risk: Some("Command can delete files or directories".to_string()),
+            prefix,
+            deadline: None,
         }
-        let cur = self.scroll.selected_idx.unwrap_or(0);
-        let new_idx = cur.saturating_sub(step);
-        self.scroll.selected_idx = Some(new_idx);
-        self.ensure_clamped();
     }
 
-    fn move_selection_down(&mut self, step: usize) {
-        if !matches!(self.request, ApprovalRequest::Patch { .. }) {
-            return;
-        }
-        let len = self.total_lines();
-        if len == 0 {
-            return;
-        }
-        let cur = self.scroll.selected_idx.unwrap_or(0);
-        let new_idx = (cur + step).min(len.saturating_sub(1));
-        self.scroll.selected_idx = Some(new_idx);
-        self.ensure_clamped();
-    }
+    #[test]
+    fn exec_details_and_session_shortcuts() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut widget =
+            UserApprovalWidget::new(exec_request(None), AppEventSender::new(tx.clone()));
+        let details: Vec<String> = widget
+            .exec_details()
+            .unwrap()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            details,
+            vec![
+                "$ bash -lc rm -r build",
+                "  ↳ Run `rm -r build`",
+                "cwd: /work/talk",
+                "sandbox: workspace-write — needs permissions outside the sandbox",
+                "risk: Command can delete files or directories",
+            ]
+        );
 
-    fn select_home(&mut self) {
-        if !matches!(self.request, ApprovalRequest::Patch { .. }) {
-            return;
-        }
-        self.scroll.selected_idx = Some(0);
-        self.scroll.scroll_top = 0;
-    }
+        // Without a prefix `p` is not offered
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(!widget.is_complete());
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::ExecApproval {
+                decision: ReviewDecision::ApprovedForSession,
+                ..
+            })
+        ));
 
-    fn select_end(&mut self) {
-        if !matches!(self.request, ApprovalRequest::Patch { .. }) {
-            return;
-        }
-        let len = self.total_lines();
-        if len == 0 {
-            self.scroll.selected_idx = None;
-            self.scroll.scroll_top = 0;
-            return;
-        }
-        self.scroll.selected_idx = Some(len - 1);
-        self.scroll.scroll_top = self
-            .scroll
-            .scroll_top
-            .max(len.saturating_sub(self.page_size_hint.max(1)));
+        let prefix = Some(vec!["rm".to_string(), "-r".to_string()]);
+        let mut widget = UserApprovalWidget::new(exec_request(prefix), AppEventSender::new(tx));
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::ExecApproval {
+                decision: ReviewDecision::ApprovedPrefix,
+                ..
+            })
+        ));
     }
 }
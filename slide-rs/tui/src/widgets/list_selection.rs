@@ -3,7 +3,7 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, WidgetRef, Wrap},
 };
 
 pub struct ListSelection<'a> {
@@ -32,6 +32,45 @@ impl<'a> ListSelection<'a> {
     }
 
     pub fn render(self, f: &mut Frame, area: Rect) {
+        let list_area = self.render_frame(f, area);
+
+        // Items
+        let mut lines: Vec<Line> = Vec::new();
+        for (idx, item) in self.items.iter().enumerate() {
+            let selected = idx == self.selected;
+            let style = if selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(item.clone(), style)));
+        }
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No results",
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+        }
+        let list = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
+        f.render_widget(list, list_area);
+    }
+
+    /// Like [`Self::render`], with `list` drawn in place of the items.
+    pub fn render_with<W: WidgetRef>(self, f: &mut Frame, area: Rect, list: &W) {
+        let list_area = self.render_frame(f, area);
+        let block = Block::default().borders(Borders::ALL);
+        let inner = block.inner(list_area);
+        f.render_widget(block, list_area);
+        f.render_widget(list, inner);
+    }
+
+    /// Border, filter line and hint; returns the area left for the list.
+    fn render_frame(&self, f: &mut Frame, area: Rect) -> Rect {
         f.render_widget(Clear, area);
         let outer = Block::default().borders(Borders::ALL).title(self.title);
         let inner = outer.inner(area);
@@ -62,35 +101,12 @@ impl<'a> ListSelection<'a> {
             .wrap(Wrap { trim: true });
         f.render_widget(filter, chunks[0]);
 
-        // Items
-        let mut lines: Vec<Line> = Vec::new();
-        for (idx, item) in self.items.iter().enumerate() {
-            let selected = idx == self.selected;
-            let style = if selected {
-                Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::LightYellow)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-            lines.push(Line::from(Span::styled(item.clone(), style)));
-        }
-        if lines.is_empty() {
-            lines.push(Line::from(Span::styled(
-                "No results",
-                Style::default()
-                    .fg(Color::DarkGray)
-                    .add_modifier(Modifier::ITALIC),
-            )));
-        }
-        let list = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
-        f.render_widget(list, chunks[1]);
-
         // Hint
         let hint = Paragraph::new(self.hint)
             .style(Style::default().fg(Color::Gray))
             .block(Block::default().borders(Borders::NONE));
         f.render_widget(hint, chunks[2]);
+
+        chunks[1]
     }
 }
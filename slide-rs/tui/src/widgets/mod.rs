@@ -1,5 +1,4 @@
 pub mod banner;
-pub mod chat;
 pub mod composer;
 pub mod list_selection;
 pub mod modal;
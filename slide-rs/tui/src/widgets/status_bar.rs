@@ -1,45 +1,403 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
 use ratatui::{
     layout::Alignment,
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
 };
+use slide_common::i18n::{t, tf, Msg};
+use slide_core::codex::{AskForApproval, SandboxPolicy, TokenUsage};
+use slide_core::git_status::GitStatus;
+
+/// Session facts shown in the status bar, kept up to date from core events.
+#[derive(Debug, Default)]
+pub struct StatusInfo {
+    model: String,
+    provider: String,
+    approval_policy: Option<AskForApproval>,
+    sandbox_policy: Option<SandboxPolicy>,
+    task_started: Option<Instant>,
+    /// Messages waiting for the running task to finish
+    queued: usize,
+    /// The agent is held before its next tool call
+    plan_paused: bool,
+    input_tokens: u64,
+    output_tokens: u64,
+    /// Spend so far; `None` once a turn ran on a model without known prices
+    cost_usd: Option<f64>,
+    /// Directory the session started in, and where commands run now
+    workspace: Option<PathBuf>,
+    cwd: Option<PathBuf>,
+    /// Started with `slide --read-only`
+    read_only: bool,
+    /// Branch and uncommitted files, inside a git repository
+    git: Option<GitStatus>,
+}
+
+impl StatusInfo {
+    pub fn new() -> Self {
+        Self {
+            cost_usd: Some(0.0),
+            ..Self::default()
+        }
+    }
+
+    pub fn configure(
+        &mut self,
+        model: String,
+        provider: String,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+    ) {
+        self.model = model;
+        self.provider = provider;
+        self.approval_policy = Some(approval_policy);
+        self.sandbox_policy = Some(sandbox_policy);
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn set_git_status(&mut self, status: GitStatus) {
+        self.git = Some(status);
+    }
+
+    /// Branch, commits ahead/behind and changed files, e.g. "main ↑2 · 3 changed".
+    fn git_label(&self) -> Option<String> {
+        let git = self.git.as_ref()?;
+        let mut label = git
+            .branch
+            .clone()
+            .unwrap_or_else(|| t(Msg::StatusGitDetached).to_string());
+        if git.ahead > 0 {
+            label.push_str(&format!(" ↑{}", git.ahead));
+        }
+        if git.behind > 0 {
+            label.push_str(&format!(" ↓{}", git.behind));
+        }
+        if !git.dirty.is_empty() {
+            label.push_str(" · ");
+            label.push_str(&tf(Msg::StatusGitDirty, &[("count", &git.dirty.len())]));
+        }
+        Some(label)
+    }
+
+    /// The first directory reported is the workspace.
+    pub fn set_cwd(&mut self, cwd: PathBuf) {
+        if self.workspace.is_none() {
+            self.workspace = Some(cwd.clone());
+        }
+        self.cwd = Some(cwd);
+    }
+
+    /// Directory the agent runs its commands in, once reported.
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// The working directory relative to the workspace, or in full outside it.
+    fn cwd_label(&self) -> Option<String> {
+        let cwd = self.cwd.as_deref()?;
+        let relative = self
+            .workspace
+            .as_deref()
+            .and_then(|workspace| cwd.strip_prefix(workspace).ok());
+        Some(match relative {
+            Some(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Some(rel) => format!("./{}", rel.display()),
+            None => home_relative(cwd),
+        })
+    }
+
+    pub fn task_started(&mut self) {
+        self.task_started = Some(Instant::now());
+    }
+
+    pub fn task_running(&self) -> bool {
+        self.task_started.is_some()
+    }
+
+    /// Stop the clock; returns how long the task ran.
+    pub fn task_finished(&mut self) -> Option<Duration> {
+        self.task_started.take().map(|started| started.elapsed())
+    }
+
+    pub fn set_queued(&mut self, queued: usize) {
+        self.queued = queued;
+    }
+
+    pub fn set_plan_paused(&mut self, paused: bool) {
+        self.plan_paused = paused;
+    }
+
+    /// Add a turn's usage, priced with the model that ran it.
+    pub fn add_usage(&mut self, usage: TokenUsage) {
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost_usd = self
+            .cost_usd
+            .zip(usage.estimated_cost_usd(&self.provider, &self.model))
+            .map(|(total, turn)| total + turn);
+    }
+
+    /// Model, policies and working directory as listed in the help overlay.
+    pub fn facts(&self) -> Vec<String> {
+        let mut facts = Vec::new();
+        if self.read_only {
+            facts.push(t(Msg::StatusReadOnly).to_string());
+        }
+        if !self.model.is_empty() {
+            facts.push(format!("{} ({})", self.model, self.provider));
+        }
+        if let Some(policy) = &self.approval_policy {
+            facts.push(tf(Msg::StatusApproval, &[("policy", &policy.as_str())]));
+        }
+        if let Some(policy) = &self.sandbox_policy {
+            facts.push(tf(
+                Msg::StatusSandbox,
+                &[("policy", &sandbox_label(policy))],
+            ));
+        }
+        if let Some(cwd) = self.cwd_label() {
+            facts.push(tf(Msg::StatusCwd, &[("cwd", &cwd)]));
+        }
+        facts.extend(self.git_label());
+        facts
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    fn spans(&self) -> Vec<Span<'static>> {
+        let dim = Style::default().fg(Color::Gray);
+        let sep = || Span::styled("  |  ", dim);
+        let mut spans = Vec::new();
+        if let Some(started) = self.task_started {
+            spans.push(Span::styled(
+                format!(" {}", format_elapsed(started.elapsed())),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if self.queued > 0 {
+            spans.push(Span::styled(
+                format!(" {}", tf(Msg::StatusQueued, &[("count", &self.queued)])),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if self.plan_paused {
+            spans.push(Span::styled(
+                format!(" {}", t(Msg::StatusPaused)),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        if !self.model.is_empty() {
+            spans.push(sep());
+            spans.push(Span::styled(
+                self.model.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(format!(" ({})", self.provider), dim));
+        }
+        if let Some(policy) = &self.approval_policy {
+            spans.push(sep());
+            spans.push(Span::styled(
+                tf(Msg::StatusApproval, &[("policy", &policy.as_str())]),
+                dim,
+            ));
+        }
+        if let Some(policy) = &self.sandbox_policy {
+            spans.push(sep());
+            spans.push(Span::styled(
+                tf(Msg::StatusSandbox, &[("policy", &sandbox_label(policy))]),
+                dim,
+            ));
+        }
+        if let Some(cwd) = self.cwd_label() {
+            spans.push(sep());
+            spans.push(Span::styled(tf(Msg::StatusCwd, &[("cwd", &cwd)]), dim));
+        }
+        if let Some(git) = self.git_label() {
+            let dirty = self.git.as_ref().is_some_and(|git| !git.dirty.is_empty());
+            spans.push(sep());
+            spans.push(Span::styled(
+                git,
+                if dirty {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    dim
+                },
+            ));
+        }
+        spans.push(sep());
+        let mut usage = tf(
+            Msg::StatusTokens,
+            &[("count", &format_tokens(self.total_tokens()))],
+        );
+        if let Some(cost) = self.cost_usd {
+            usage.push_str(&format!(" · ${cost:.2}"));
+        }
+        spans.push(Span::styled(usage, dim));
+        spans
+    }
+}
 
 pub struct StatusBar<'a> {
     mode: &'a str,
     status: &'a str,
-    hints: &'a str,
+    info: &'a StatusInfo,
 }
 
 impl<'a> StatusBar<'a> {
-    pub fn new(mode: &'a str, status: &'a str, hints: &'a str) -> Self {
-        Self {
-            mode,
-            status,
-            hints,
-        }
+    pub fn new(mode: &'a str, status: &'a str, info: &'a StatusInfo) -> Self {
+        Self { mode, status, info }
     }
 }
 
 impl<'a> ratatui::widgets::Widget for StatusBar<'a> {
     fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
-        let line = Line::from(vec![
-            Span::styled(
-                format!(" {} ", self.mode),
+        let mut spans = vec![Span::styled(
+            format!(" {} ", self.mode),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )];
+        if self.info.read_only {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!(" {} ", t(Msg::StatusReadOnly)),
                 Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Cyan)
+                    .fg(Color::White)
+                    .bg(Color::Red)
                     .add_modifier(Modifier::BOLD),
-            ),
+            ));
+        }
+        spans.extend([
             Span::raw("  "),
-            Span::styled(
-                format!("{}", self.status),
-                Style::default().fg(Color::Yellow),
-            ),
-            Span::raw("  |  "),
-            Span::styled(self.hints, Style::default().fg(Color::Gray)),
+            Span::styled(self.status.to_string(), Style::default().fg(Color::Yellow)),
         ]);
-        let widget = Paragraph::new(line).alignment(Alignment::Left);
+        spans.extend(self.info.spans());
+        let widget = Paragraph::new(Line::from(spans)).alignment(Alignment::Left);
         widget.render(area, buf);
     }
 }
+
+fn sandbox_label(policy: &SandboxPolicy) -> &'static str {
+    match policy {
+        SandboxPolicy::DangerFullAccess => "full-access",
+        SandboxPolicy::ReadOnly => "read-only",
+        SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+    }
+}
+
+fn home_relative(path: &Path) -> String {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match home.and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rel) => format!("~/{}", rel.display()),
+        None => path.display().to_string(),
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 3600 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..=999 => tokens.to_string(),
+        1_000..=999_999 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_accumulates_and_is_priced_per_model() {
+        let mut info = StatusInfo::new();
+        info.configure(
+            "gpt-5-mini".to_string(),
+            "openai".to_string(),
+            AskForApproval::OnRequest,
+            SandboxPolicy::ReadOnly,
+        );
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 500_000,
+        };
+        info.add_usage(usage);
+        assert_eq!(info.total_tokens(), 1_500_000);
+        assert_eq!(info.cost_usd, Some(1.25));
+
+        // A model without known prices makes the total unknown
+        info.configure(
+            "custom-model".to_string(),
+            "openai".to_string(),
+            AskForApproval::OnRequest,
+            SandboxPolicy::ReadOnly,
+        );
+        info.add_usage(usage);
+        assert_eq!(info.cost_usd, None);
+        assert_eq!(format_tokens(info.total_tokens()), "3.0M");
+        assert_eq!(format_elapsed(Duration::from_secs(3725)), "1:02:05");
+
+        info.set_queued(2);
+        assert!(info.spans().iter().any(|s| s.content == " queued (2)"));
+    }
+
+    #[test]
+    fn read_only_sessions_show_a_badge() {
+        let mut info = StatusInfo::new();
+        let render = |info: &StatusInfo| {
+            let area = ratatui::layout::Rect::new(0, 0, 60, 1);
+            let mut buf = ratatui::buffer::Buffer::empty(area);
+            ratatui::widgets::Widget::render(StatusBar::new("CHAT", "Idle", info), area, &mut buf);
+            crate::test_harness::buffer_to_string(&buf)
+        };
+        assert!(render(&info).starts_with(" CHAT   Idle"));
+        info.set_read_only(true);
+        assert!(render(&info).starts_with(" CHAT   READ-ONLY   Idle"));
+        assert_eq!(info.facts(), ["READ-ONLY"]);
+    }
+
+    #[test]
+    fn git_status_shows_branch_and_changes() {
+        let mut info = StatusInfo::new();
+        assert_eq!(info.git_label(), None);
+        info.set_git_status(GitStatus {
+            branch: Some("main".to_string()),
+            ahead: 2,
+            dirty: vec![PathBuf::from("slides/talk.md")],
+            ..Default::default()
+        });
+        assert_eq!(info.git_label().as_deref(), Some("main ↑2 · 1 changed"));
+        info.set_git_status(GitStatus {
+            behind: 1,
+            ..Default::default()
+        });
+        assert_eq!(info.git_label().as_deref(), Some("detached HEAD ↓1"));
+    }
+
+    #[test]
+    fn cwd_is_shown_relative_to_the_workspace() {
+        let mut info = StatusInfo::new();
+        assert_eq!(info.cwd_label(), None);
+        info.set_cwd(PathBuf::from("/work/talk"));
+        assert_eq!(info.cwd_label().as_deref(), Some("."));
+        info.set_cwd(PathBuf::from("/work/talk/slides"));
+        assert_eq!(info.cwd_label().as_deref(), Some("./slides"));
+        info.set_cwd(PathBuf::from("/srv/shared"));
+        assert_eq!(info.cwd_label().as_deref(), Some("/srv/shared"));
+    }
+}
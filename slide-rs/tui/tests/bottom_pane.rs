@@ -0,0 +1,137 @@
+//! Snapshot tests of the bottom pane, driven through the headless harness.
+use crossterm::event::KeyCode;
+use slide_core::codex::ReviewDecision;
+use slide_tui::app_event_sender::AppEvent;
+use slide_tui::bottom_pane::InputResult;
+use slide_tui::test_harness::TuiHarness;
+use slide_tui::user_approval_widget::ApprovalRequest;
+use std::io::Result;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn exec_request(command: &[&str]) -> ApprovalRequest {
+    ApprovalRequest::Exec {
+        id: "call-1".to_string(),
+        command: command.iter().map(|s| s.to_string()).collect(),
+        reason: Some("clean up".to_string()),
+        cwd: PathBuf::from("/work"),
+        parsed: Vec::new(),
+        sandbox: None,
+        escalated: false,
+        risk: None,
+        prefix: None,
+        deadline: None,
+    }
+}
+
+#[test]
+fn composer_shows_placeholder_then_typed_text_and_clears_on_submit() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 8)?;
+    assert_eq!(harness.snapshot()?, "││Ask Slide Code to do anything");
+
+    harness.type_text("make a deck");
+    assert_eq!(harness.snapshot()?, "││make a deck");
+
+    let result = harness.press_code(KeyCode::Enter);
+    assert_eq!(result, Some(InputResult::Submitted("make a deck".into())));
+    assert_eq!(harness.snapshot()?, "││Ask Slide Code to do anything");
+    Ok(())
+}
+
+#[test]
+fn slash_popup_lists_matching_commands() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 8)?;
+    harness.type_text("/un");
+    assert_eq!(
+        harness.snapshot()?,
+        concat!(
+            "││/un\n",
+            "▌/undo  restore the previous version of a deck\n",
+            "▌/undo-turn  put back every file the last turn changed",
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn large_paste_shows_a_placeholder() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 8)?;
+    harness.type_text("summarize ");
+    harness.paste(&"line\n".repeat(40));
+    assert_eq!(harness.snapshot()?, "││summarize [pasted 40 lines]");
+    Ok(())
+}
+
+#[test]
+fn file_mention_starts_a_search() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 8)?;
+    harness.type_text("@tal");
+    let queries: Vec<String> = harness
+        .drain_app_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            AppEvent::StartFileSearch(query) => Some(query),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(queries.last().map(String::as_str), Some("tal"));
+    Ok(())
+}
+
+#[test]
+fn approval_modal_takes_keys_until_answered() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 12)?;
+    harness.show_approval(exec_request(&["rm", "old.md"]));
+    assert!(harness.is_intercepting_input());
+    assert_eq!(
+        harness.snapshot()?,
+        concat!(
+            "╔Approval Required═════════════════════════════════════════╗\n",
+            "║Run command  — clean up                                   ║\n",
+            "║$ rm old.md                                               ║\n",
+            "║cwd: /work                                                ║\n",
+            "║ y : approve    a : approve for the session    n : deny   ║\n",
+            "╚══════════════════════════════════════════════════════════╝",
+        )
+    );
+
+    assert_eq!(harness.press_code(KeyCode::Char('y')), None);
+    assert!(!harness.is_intercepting_input());
+    let decisions: Vec<(String, ReviewDecision)> = harness
+        .drain_app_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            AppEvent::ExecApproval { id, decision } => Some((id, decision)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        decisions,
+        [("call-1".to_string(), ReviewDecision::Approved)]
+    );
+    assert_eq!(harness.snapshot()?, "││Ask Slide Code to do anything");
+    Ok(())
+}
+
+#[test]
+fn approval_modal_counts_down_and_closes_on_timeout() -> Result<()> {
+    let mut harness = TuiHarness::new(60, 12)?;
+    let mut request = exec_request(&["rm", "old.md"]);
+    if let ApprovalRequest::Exec { deadline, .. } = &mut request {
+        *deadline = Some(Instant::now() + Duration::from_secs(30));
+    }
+    harness.show_approval(request);
+    let snapshot = harness.snapshot()?;
+    assert!(
+        snapshot.contains("║Run command  — clean up  30s left to answer"),
+        "{snapshot}"
+    );
+
+    // Another request's timeout leaves the modal open
+    harness.dismiss_approval("call-2");
+    assert!(harness.is_intercepting_input());
+    harness.dismiss_approval("call-1");
+    assert!(!harness.is_intercepting_input());
+    assert!(harness.drain_app_events().is_empty());
+    Ok(())
+}